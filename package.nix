@@ -19,6 +19,8 @@ pkgs.rustPlatform.buildRustPackage rec {
 
     mkdir -p $out/share/${pname}
     cp -r challenges $out/share/${pname}/
+    mkdir -p $out/share/${pname}/nvim
+    cp -r plugin $out/share/${pname}/nvim/
   '';
 
   src = lib.sourceByRegex ./. [
@@ -27,6 +29,7 @@ pkgs.rustPlatform.buildRustPackage rec {
     "^src.*$"
     "^tests.*$"
     "^challenges.*$"
+    "^plugin.*$"
   ];
 
   doCheck = false;
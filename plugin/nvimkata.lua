@@ -0,0 +1,216 @@
+-- nvimkata plugin: `:Nvimkata <ID>` plays a challenge in the running neovim, in a
+-- new tab with the target (the hint for blind challenges) on top, instead of
+-- spawning a nested nvim. Challenges are served and recorded by
+-- `nvimkata rpc-serve`, started on first use.
+--
+-- Options:
+--   vim.g.nvimkata_cmd  command starting nvimkata, as a list (default: { "nvimkata" })
+
+if vim.g.loaded_nvimkata then
+  return
+end
+vim.g.loaded_nvimkata = true
+
+if vim.fn.has("nvim-0.8") == 0 then
+  vim.notify("nvimkata: neovim 0.8 or newer is required", vim.log.levels.ERROR)
+  return
+end
+
+local job = nil
+local next_id = 0
+-- Reply callbacks by request id
+local pending = {}
+-- Incomplete last line of the server output
+local partial = ""
+-- The challenge being played: { tab, buf, target }
+local session = nil
+-- Challenge ids for completion, fetched once
+local ids = {}
+
+local function notify(msg, level)
+  vim.notify("nvimkata: " .. msg, level or vim.log.levels.INFO)
+end
+
+local function on_stdout(_, data)
+  data[1] = partial .. data[1]
+  partial = table.remove(data)
+  for _, line in ipairs(data) do
+    local ok, reply = pcall(vim.json.decode, line)
+    if ok and type(reply) == "table" and pending[reply.id] then
+      local callback = pending[reply.id]
+      pending[reply.id] = nil
+      if reply.error ~= nil and reply.error ~= vim.NIL then
+        notify(reply.error, vim.log.levels.ERROR)
+      else
+        callback(reply.result)
+      end
+    end
+  end
+end
+
+local function request(method, params, callback)
+  if not job then
+    local cmd = vim.list_extend(vim.deepcopy(vim.g.nvimkata_cmd or { "nvimkata" }), { "rpc-serve" })
+    local ok, id = pcall(vim.fn.jobstart, cmd, {
+      on_stdout = on_stdout,
+      on_stderr = function(_, data)
+        local msg = vim.trim(table.concat(data, "\n"))
+        if msg ~= "" then
+          notify(msg, vim.log.levels.WARN)
+        end
+      end,
+      on_exit = function()
+        job = nil
+        pending = {}
+        partial = ""
+      end,
+    })
+    if not ok or id <= 0 then
+      notify("failed to start " .. table.concat(cmd, " "), vim.log.levels.ERROR)
+      return
+    end
+    job = id
+  end
+  next_id = next_id + 1
+  pending[next_id] = callback
+  vim.fn.chansend(job, vim.json.encode({ id = next_id, method = method, params = params }) .. "\n")
+end
+
+-- Scratch buffer holding the lines of `path`
+local function scratch(path, filetype)
+  local buf = vim.api.nvim_create_buf(false, true)
+  vim.api.nvim_buf_set_lines(buf, 0, -1, false, vim.fn.readfile(path))
+  vim.api.nvim_set_option_value("swapfile", false, { buf = buf })
+  if filetype ~= nil and filetype ~= vim.NIL then
+    vim.api.nvim_set_option_value("filetype", filetype, { buf = buf })
+  end
+  return buf
+end
+
+-- Detach the runtime, close the tab and send the final buffer to the server
+local function finish()
+  if not session then
+    return
+  end
+  local s = session
+  session = nil
+  pcall(vim.api.nvim_del_augroup_by_name, "nvimkata_session")
+  if _G._ks_detach then
+    _G._ks_detach()
+  end
+  local lines = vim.api.nvim_buf_is_valid(s.buf) and vim.api.nvim_buf_get_lines(s.buf, 0, -1, false) or {}
+  if vim.api.nvim_tabpage_is_valid(s.tab) and #vim.api.nvim_list_tabpages() > 1 then
+    vim.cmd.tabclose(vim.api.nvim_tabpage_get_number(s.tab))
+  end
+  for _, buf in pairs({ s.buf, s.target, s.instructions }) do
+    if vim.api.nvim_buf_is_valid(buf) then
+      vim.api.nvim_buf_delete(buf, { force = true })
+    end
+  end
+  request("finish", { lines = lines }, function(result)
+    notify(result.message, result.solved and vim.log.levels.INFO or vim.log.levels.WARN)
+  end)
+end
+
+-- Give up: record the keys typed so far, like :w in a nested session
+local function stop()
+  if session and _G._ks_stop then
+    _G._ks_stop()
+  end
+  finish()
+end
+
+local function open(challenge)
+  vim.cmd.tabnew()
+  local tab = vim.api.nvim_get_current_tabpage()
+  local empty = vim.api.nvim_get_current_buf()
+
+  -- The instructions (description, and the hint in blind mode) go on top, then
+  -- the target unless the challenge is blind
+  local instructions, instructions_win
+  if challenge.instructions ~= nil and challenge.instructions ~= vim.NIL then
+    instructions = scratch(challenge.instructions)
+    vim.api.nvim_win_set_buf(0, instructions)
+    vim.api.nvim_set_option_value("modifiable", false, { buf = instructions })
+    vim.wo.winbar = "  [INSTRUCTIONS]"
+    vim.wo.wrap = true
+    instructions_win = vim.api.nvim_get_current_win()
+  end
+  local target
+  if not challenge.blind then
+    if instructions then
+      vim.cmd("belowright split")
+    end
+    target = scratch(challenge.target, challenge.filetype)
+    vim.api.nvim_win_set_buf(0, target)
+    vim.api.nvim_set_option_value("modifiable", false, { buf = target })
+    vim.wo.winbar = "  [TARGET]"
+    vim.wo.wrap = true
+    vim.cmd("diffthis")
+  end
+  if vim.api.nvim_buf_is_valid(empty) then
+    vim.api.nvim_buf_delete(empty, { force = true })
+  end
+
+  vim.cmd("belowright split")
+  local buf = scratch(challenge.start, challenge.filetype)
+  vim.api.nvim_win_set_buf(0, buf)
+  -- :w gives up like in a nested session, through BufWriteCmd
+  vim.api.nvim_set_option_value("buftype", "acwrite", { buf = buf })
+  vim.api.nvim_buf_set_name(buf, "nvimkata://" .. challenge.id)
+  vim.wo.wrap = true
+  if not challenge.blind then
+    vim.cmd("diffthis")
+    vim.opt.diffopt:append("context:99999")
+  end
+  if instructions_win then
+    vim.api.nvim_win_set_height(instructions_win, vim.api.nvim_buf_line_count(instructions) + 1)
+  end
+
+  session = { tab = tab, buf = buf, target = target, instructions = instructions }
+  local group = vim.api.nvim_create_augroup("nvimkata_session", { clear = true })
+  vim.api.nvim_create_autocmd("BufWriteCmd", {
+    group = group,
+    buffer = buf,
+    callback = function()
+      vim.schedule(stop)
+    end,
+  })
+  vim.api.nvim_create_autocmd("TabClosed", {
+    group = group,
+    callback = function()
+      if session and not vim.api.nvim_tabpage_is_valid(session.tab) then
+        vim.schedule(stop)
+      end
+    end,
+  })
+
+  -- Called by the runtime once a solved challenge isn't retried
+  _G._VK_ON_EXIT = function()
+    vim.schedule(finish)
+  end
+  vim.cmd.luafile(vim.fn.fnameescape(challenge.script))
+end
+
+vim.api.nvim_create_user_command("Nvimkata", function(opts)
+  if session then
+    notify("a challenge is already open", vim.log.levels.WARN)
+    return
+  end
+  request("open", { challenge = opts.args }, open)
+end, {
+  nargs = 1,
+  desc = "Play a nvimkata challenge",
+  complete = function(lead)
+    if #ids == 0 then
+      request("list", nil, function(result)
+        ids = vim.tbl_map(function(c)
+          return c.id
+        end, result)
+      end)
+    end
+    return vim.tbl_filter(function(id)
+      return vim.startswith(id, lead)
+    end, ids)
+  end,
+})
@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+
+use crate::challenge::Grade;
+
+/// Time between animation frames.
+pub const TICK: Duration = Duration::from_millis(40);
+
+/// Frames of the keystroke bar filling up and the grade climbing to its letter.
+pub const REVEAL_FRAMES: u16 = 20;
+
+/// Frames of confetti after the reveal, for new bests.
+pub const CONFETTI_FRAMES: u16 = 30;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Turn animations on or off for the rest of the session. Only the first call
+/// has an effect.
+pub fn set_enabled(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(true)
+}
+
+/// Frame counter of a running animation. Without animations it starts at the end.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    started: Instant,
+    frames: u16,
+    skipped: bool,
+}
+
+impl Clock {
+    /// Start an animation of `frames` frames.
+    pub fn start(frames: u16) -> Self {
+        Self {
+            started: Instant::now(),
+            frames,
+            skipped: !enabled(),
+        }
+    }
+
+    /// Current frame, `frames` once the animation is over.
+    pub fn frame(&self) -> u16 {
+        if self.skipped {
+            return self.frames;
+        }
+        let elapsed = self.started.elapsed().as_millis() / TICK.as_millis();
+        u16::try_from(elapsed).map_or(self.frames, |f| f.min(self.frames))
+    }
+
+    pub fn running(&self) -> bool {
+        self.frame() < self.frames
+    }
+
+    /// Jump to the last frame.
+    pub fn skip(&mut self) {
+        self.skipped = true;
+    }
+}
+
+/// Part of `total` shown at `frame`: from 0 up to `total` at [`REVEAL_FRAMES`].
+pub fn portion(frame: u16, total: u32) -> u32 {
+    total * u32::from(frame.min(REVEAL_FRAMES)) / u32::from(REVEAL_FRAMES)
+}
+
+/// Grade shown at `frame`: climbs from F and lands on `grade` at [`REVEAL_FRAMES`].
+pub fn climbing_grade(frame: u16, grade: Grade) -> Grade {
+    const GRADES: [Grade; 6] = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::E, Grade::F];
+    let steps = u32::from(5 - grade.rank());
+    let rank = 5 - portion(frame, steps);
+    GRADES[rank as usize]
+}
+
+/// Sprinkle confetti over the blank cells of `area`, falling one row every
+/// other frame. Frames before [`REVEAL_FRAMES`] draw nothing.
+pub fn draw_confetti(buf: &mut Buffer, area: Rect, frame: u16) {
+    const PIECES: u32 = 40;
+    const GLYPHS: [&str; 4] = ["*", "+", "·", "•"];
+    const COLORS: [Color; 5] = [
+        Color::Yellow,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Green,
+        Color::Rgb(255, 165, 0),
+    ];
+    let Some(fall) = frame.checked_sub(REVEAL_FRAMES) else {
+        return;
+    };
+    if area.is_empty() {
+        return;
+    }
+    for piece in 0..PIECES {
+        // A small LCG keeps the pieces scattered but the same on every frame
+        let seed = piece.wrapping_mul(1_103_515_245).wrapping_add(12_345) >> 8;
+        let x = area.x + u16::try_from(seed % u32::from(area.width)).unwrap_or(0);
+        let row = (seed >> 8) + u32::from(fall / 2);
+        let y = area.y + u16::try_from(row % u32::from(area.height)).unwrap_or(0);
+        let cell = &mut buf[(x, y)];
+        if cell.symbol() == " " {
+            cell.set_symbol(GLYPHS[(seed % 4) as usize])
+                .set_fg(COLORS[(seed % 5) as usize]);
+        }
+    }
+}
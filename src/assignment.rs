@@ -0,0 +1,156 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::challenge::{Challenge, Grade, Topic};
+use crate::collection;
+use crate::goals;
+use crate::state::GameState;
+
+/// An assignment file as distributed by an instructor.
+#[derive(Debug, Deserialize)]
+struct AssignmentFile {
+    title: String,
+    #[serde(default)]
+    description: String,
+    /// Due date as `YYYY-MM-DD`.
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    min_grade: Option<Grade>,
+    challenges: Vec<String>,
+}
+
+/// A set of challenges to complete by a due date, each with at least a minimum grade.
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub title: String,
+    pub description: String,
+    /// Due day as days since the Unix epoch.
+    pub due: Option<u32>,
+    /// Grade every challenge needs; completing it is enough when unset.
+    /// Freestyle challenges only need to be completed.
+    pub min_grade: Option<Grade>,
+    pub challenges: Vec<Challenge>,
+}
+
+impl Assignment {
+    /// Whether a challenge of this assignment meets its requirements.
+    pub fn is_done(&self, challenge: &Challenge, state: &GameState) -> bool {
+        if !state.is_completed(&challenge.id) {
+            return false;
+        }
+        match self.min_grade {
+            Some(min) if !challenge.is_freestyle() => state
+                .best_grade(&challenge.id)
+                .is_some_and(|g| g.rank() <= min.rank()),
+            _ => true,
+        }
+    }
+
+    /// Number of challenges that meet the requirements.
+    pub fn done_count(&self, state: &GameState) -> usize {
+        self.challenges
+            .iter()
+            .filter(|c| self.is_done(c, state))
+            .count()
+    }
+
+    pub fn is_complete(&self, state: &GameState) -> bool {
+        self.done_count(state) == self.challenges.len()
+    }
+
+    /// Past the due date without being complete.
+    pub fn is_overdue(&self, state: &GameState, today: u32) -> bool {
+        self.due.is_some_and(|due| today > due) && !self.is_complete(state)
+    }
+}
+
+/// Load every `.toml` assignment in `dir`, in file name order. Malformed files
+/// and invalid due dates are reported and skipped.
+pub fn load_assignments(dir: &Path, topics: &[Topic]) -> Vec<Assignment> {
+    collection::toml_files(dir)
+        .iter()
+        .filter_map(|path| load_assignment(path, topics))
+        .collect()
+}
+
+fn load_assignment(path: &Path, topics: &[Topic]) -> Option<Assignment> {
+    let content = fs::read_to_string(path).ok()?;
+    let file: AssignmentFile = match toml::from_str(&content) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Warning: failed to parse {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let due = match file.due.as_deref().map(|d| (d, goals::parse_day(d))) {
+        Some((date, None)) => {
+            eprintln!(
+                "Warning: invalid due date '{date}' in {} (use YYYY-MM-DD)",
+                path.display()
+            );
+            return None;
+        }
+        Some((_, day)) => day,
+        None => None,
+    };
+    Some(Assignment {
+        title: file.title,
+        description: file.description,
+        due,
+        min_grade: file.min_grade,
+        challenges: collection::resolve(topics, &file.challenges, path),
+    })
+}
+
+/// Plain-text completion report of all assignments, to send back to the instructor.
+pub fn report(assignments: &[Assignment], state: &GameState, today: u32) -> String {
+    let mut out = format!(
+        "# nvimkata assignment report\n\nGenerated: {}\n",
+        goals::format_day(today)
+    );
+    for assignment in assignments {
+        let _ = write!(out, "\n## {}\n\n", assignment.title);
+        if let Some(due) = assignment.due {
+            let _ = write!(out, "Due: {} | ", goals::format_day(due));
+        }
+        if let Some(grade) = assignment.min_grade {
+            let _ = write!(out, "Minimum grade: {} | ", grade.display_char());
+        }
+        let _ = writeln!(
+            out,
+            "Progress: {}/{}{}\n",
+            assignment.done_count(state),
+            assignment.challenges.len(),
+            if assignment.is_overdue(state, today) {
+                " (overdue)"
+            } else {
+                ""
+            }
+        );
+        for challenge in &assignment.challenges {
+            let mark = if assignment.is_done(challenge, state) {
+                'x'
+            } else {
+                ' '
+            };
+            let result = match (
+                state.best_grade(&challenge.id),
+                state.best_keystrokes(&challenge.id),
+            ) {
+                (_, Some(keys)) if challenge.is_freestyle() => format!("{keys} keys"),
+                (Some(grade), Some(keys)) => format!("{}, {keys} keys", grade.display_char()),
+                _ => "not attempted".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "- [{mark}] {} {}: {result}",
+                challenge.id, challenge.title
+            );
+        }
+    }
+    out
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write as _;
+
+use crate::challenge::{self, Challenge, Grade};
+use crate::state::GameState;
+
+/// Default for the fewest recorded attempts a challenge needs to be calibrated.
+pub const MIN_ATTEMPTS: usize = 5;
+
+/// What the recorded attempts say about the par of a challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The typical attempt grades B or C.
+    Fits,
+    /// The typical attempt grades D or worse.
+    TooStrict,
+    /// The typical attempt grades A, or the best one beats par.
+    TooLax,
+}
+
+impl Verdict {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fits => "fits",
+            Self::TooStrict => "too strict",
+            Self::TooLax => "too lax",
+        }
+    }
+}
+
+/// The attempts at a challenge measured against its par and curve, with a
+/// curve fitted to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    pub attempts: usize,
+    /// Keystrokes of the fewest attempt.
+    pub best: u32,
+    /// Keystrokes of the median attempt.
+    pub median: u32,
+    /// Grade of the median attempt on the current curve.
+    pub median_grade: Grade,
+    pub verdict: Verdict,
+    /// The best attempt when it beats par, as the new par.
+    pub par: Option<u32>,
+    /// A curve on which the median attempt grades C: B at the fastest quarter
+    /// of the attempts, C at half, D at three quarters and E at nine tenths.
+    pub thresholds: [u32; 5],
+}
+
+/// Keystrokes of every recorded attempt at `challenge` in `saves`.
+pub fn attempts(challenge: &Challenge, saves: &[GameState]) -> Vec<u32> {
+    saves
+        .iter()
+        .filter_map(|save| save.history.get(&challenge.id))
+        .flatten()
+        .map(|attempt| attempt.keystrokes)
+        .collect()
+}
+
+/// Calibrate `challenge` from the keystrokes of its attempts, against the
+/// par for the player's keyboard layout. `None` for a
+/// challenge without a par, or with fewer than `min_attempts` attempts.
+pub fn calibrate(
+    challenge: &Challenge,
+    attempts: &[u32],
+    min_attempts: usize,
+) -> Option<Calibration> {
+    if challenge.is_freestyle()
+        || challenge.par_keystrokes == 0
+        || attempts.len() < min_attempts.max(1)
+    {
+        return None;
+    }
+    let mut sorted = attempts.to_vec();
+    sorted.sort_unstable();
+    let quantile = |num: usize, den: usize| sorted[(sorted.len() - 1) * num / den];
+    let best = sorted[0];
+    let median = quantile(1, 2);
+    let median_grade = challenge.score(median);
+    let par = (best < challenge.par()).then_some(best);
+    let verdict = if par.is_some() || median_grade == Grade::A {
+        Verdict::TooLax
+    } else if matches!(median_grade, Grade::B | Grade::C) {
+        Verdict::Fits
+    } else {
+        Verdict::TooStrict
+    };
+    // Tenths of par reaching each quantile, rising by at least a tenth
+    let base = par.unwrap_or(challenge.par());
+    let mut thresholds = [10; 5];
+    for (i, (num, den)) in [(1, 4), (1, 2), (3, 4), (9, 10)].into_iter().enumerate() {
+        let tenths = (quantile(num, den) * 10).div_ceil(base);
+        thresholds[i + 1] = tenths.max(thresholds[i] + 1);
+    }
+    Some(Calibration {
+        attempts: attempts.len(),
+        best,
+        median,
+        median_grade,
+        verdict,
+        par,
+        thresholds,
+    })
+}
+
+/// `tenths` of par as a multiple, `2.5x`.
+fn multiple(tenths: u32) -> String {
+    format!("{}.{}x", tenths / 10, tenths % 10)
+}
+
+/// Report of the calibrated challenges that don't fit their par, with the
+/// par and thresholds to paste into their files, and a count of the rest.
+pub fn report(calibrations: &[(&Challenge, Calibration)], saves: usize, skipped: usize) -> String {
+    let mut out = String::new();
+    let flagged: Vec<&(&Challenge, Calibration)> = calibrations
+        .iter()
+        .filter(|(_, c)| c.verdict != Verdict::Fits)
+        .collect();
+    for (challenge, calibration) in &flagged {
+        let par = challenge.par();
+        let _ = writeln!(
+            out,
+            "{} - {}: par {par} looks {}",
+            challenge.id,
+            challenge.title,
+            calibration.verdict.label()
+        );
+        let _ = writeln!(
+            out,
+            "  {} attempts, best {} keys, median {} keys ({} par, grade {} on {:?})",
+            calibration.attempts,
+            calibration.best,
+            calibration.median,
+            multiple(calibration.median * 10 / par),
+            calibration.median_grade.display_char(),
+            challenge.curve()
+        );
+        if let Some(par) = calibration.par {
+            let layout = challenge::layout();
+            let field = if challenge.layout_par.contains_key(&layout) {
+                format!("layout_par.{}", layout.name().to_lowercase())
+            } else {
+                "par_keystrokes".to_string()
+            };
+            let _ = writeln!(out, "  {field} = {par}");
+        }
+        let _ = writeln!(out, "  thresholds = {:?}", calibration.thresholds);
+    }
+    let noun = if saves == 1 { "save" } else { "saves" };
+    let _ = writeln!(
+        out,
+        "{} of {} challenges flagged from {saves} {noun}; {skipped} with too few attempts",
+        flagged.len(),
+        calibrations.len()
+    );
+    out
+}
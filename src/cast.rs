@@ -0,0 +1,276 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::challenge::Challenge;
+use crate::game;
+use crate::nvim::Snapshot;
+use crate::report;
+use crate::state::AttemptRecord;
+
+/// Clears the screen and homes the cursor before each frame.
+const CLEAR: &str = "\x1b[H\x1b[2J";
+const REVERSE: &str = "\x1b[7m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Time between keystrokes of a replay: the run's own pace, kept between
+/// these bounds so that quick runs can be followed and slow ones don't drag.
+const MIN_PACE_MS: u32 = 80;
+const MAX_PACE_MS: u32 = 1000;
+
+/// How long the last frame stays before the result line.
+const HOLD_MS: u32 = 1500;
+
+/// Time each frame of an HTML replay is shown.
+const HTML_FRAME_MS: u32 = 1200;
+
+/// Where `nvimkata replay` writes a replay instead of playing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayExport {
+    /// An asciicast with a frame per keystroke, see [`Cast::to_asciicast`].
+    Asciinema(PathBuf),
+    /// A page animating a frame per command, see [`html`].
+    Html(PathBuf),
+    /// A plain-text dump of a frame per command, see [`text`].
+    Text(PathBuf),
+}
+
+impl ReplayExport {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Asciinema(path) | Self::Html(path) | Self::Text(path) => path,
+        }
+    }
+
+    /// Whether the frames are one per command rather than per keystroke.
+    pub fn by_command(&self) -> bool {
+        !matches!(self, Self::Asciinema(_))
+    }
+}
+
+/// A replay as terminal output: what to print, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cast {
+    pub title: String,
+    pub width: usize,
+    pub height: usize,
+    /// Output with its time in milliseconds from the start, in order.
+    pub events: Vec<(u32, String)>,
+}
+
+#[derive(Serialize)]
+struct Header<'a> {
+    version: u8,
+    width: usize,
+    height: usize,
+    title: &'a str,
+}
+
+impl Cast {
+    /// The replay of a run of `challenge`: a frame per snapshot, the first
+    /// one before any keys and one after each of `keys`, at the pace of its
+    /// `time_ms`, then the result line.
+    pub fn replay(
+        challenge: &Challenge,
+        keys: &[String],
+        snapshots: &[Snapshot],
+        time_ms: u32,
+        result: &str,
+    ) -> Self {
+        let title = format!("{} - {}", challenge.id, challenge.title);
+        let pace = u32::try_from(keys.len())
+            .ok()
+            .and_then(|n| time_ms.checked_div(n))
+            .unwrap_or(0)
+            .clamp(MIN_PACE_MS, MAX_PACE_MS);
+        let mut time = 0;
+        let mut events = Vec::new();
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            let typed = &keys[..i.min(keys.len())];
+            events.push((time, frame(&title, snapshot, typed, keys.len())));
+            time += pace;
+        }
+        events.push((time + HOLD_MS, format!("\r\n{result}\r\n")));
+        let width = snapshots
+            .iter()
+            .flat_map(|s| &s.lines)
+            .map(|line| line.chars().count() + 1)
+            .chain([title.chars().count() + 20, result.chars().count(), 40])
+            .max()
+            .unwrap_or(40);
+        let height = snapshots.iter().map(|s| s.lines.len()).max().unwrap_or(0) + 4;
+        Self {
+            title,
+            width,
+            height,
+            events,
+        }
+    }
+
+    /// The cast in the asciicast v2 format asciinema plays: a JSON header
+    /// line, then an output event per line.
+    pub fn to_asciicast(&self) -> String {
+        let header = Header {
+            version: 2,
+            width: self.width,
+            height: self.height,
+            title: &self.title,
+        };
+        let mut cast = serde_json::to_string(&header).unwrap_or_default() + "\n";
+        for (ms, output) in &self.events {
+            let output = serde_json::to_string(output).unwrap_or_default();
+            let _ = writeln!(cast, "[{}.{:03}, \"o\", {output}]", ms / 1000, ms % 1000);
+        }
+        cast
+    }
+}
+
+/// A frame of a replay: a bar with the title and keystroke count, the buffer
+/// with the cursor in reverse video, and the keys typed so far.
+fn frame(title: &str, snapshot: &Snapshot, typed: &[String], total: usize) -> String {
+    let mut out = format!(
+        "{CLEAR}{REVERSE} {title} | {}/{total} keys {RESET}\r\n",
+        typed.len()
+    );
+    let [cursor_line, cursor_col] = snapshot.cursor;
+    for (n, line) in (1..).zip(&snapshot.lines) {
+        if n == cursor_line {
+            out.push_str(&with_cursor(line, cursor_col));
+        } else {
+            out.push_str(line);
+        }
+        out.push_str("\r\n");
+    }
+    let _ = write!(out, "\r\n{DIM}{}{RESET}", typed.concat());
+    out
+}
+
+/// `line` with the character at the 1-based byte column `col` in reverse
+/// video, or a space after its end.
+fn with_cursor(line: &str, col: u32) -> String {
+    let (before, at, after) = split_at_cursor(line, col);
+    format!("{before}{REVERSE}{}{RESET}{after}", at.unwrap_or(' '))
+}
+
+/// `line` split around the character at the 1-based byte column `col`: the
+/// text before it, the character, `None` past the end, and the text after it.
+pub fn split_at_cursor(line: &str, col: u32) -> (&str, Option<char>, &str) {
+    let at = usize::try_from(col.saturating_sub(1)).unwrap_or(usize::MAX);
+    match line.char_indices().find(|&(i, _)| i >= at) {
+        Some((i, c)) => (&line[..i], Some(c), &line[i + c.len_utf8()..]),
+        None => (line, None, ""),
+    }
+}
+
+/// Caption of frame `i` of a replay by command: the start, then each command
+/// with its number.
+pub fn caption(i: usize, steps: &[String]) -> String {
+    match i.checked_sub(1).and_then(|step| steps.get(step)) {
+        Some(step) => format!("{i}/{}: {step}", steps.len()),
+        None => "start".to_string(),
+    }
+}
+
+/// A replay as plain text for a code block: a frame per snapshot, the start
+/// and then the buffer after each of `steps`, the cursor line marked with `>`
+/// and the cursor column with a `^` below it.
+pub fn text(
+    challenge: &Challenge,
+    steps: &[String],
+    snapshots: &[Snapshot],
+    result: &str,
+) -> String {
+    let mut out = format!("{} - {}\n", challenge.id, challenge.title);
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let _ = writeln!(out, "\n-- {} --", caption(i, steps));
+        let [cursor_line, cursor_col] = snapshot.cursor;
+        for (n, line) in (1..).zip(&snapshot.lines) {
+            if n == cursor_line {
+                let (before, _, _) = split_at_cursor(line, cursor_col);
+                let _ = writeln!(out, "> {line}");
+                let _ = writeln!(out, "  {}^", " ".repeat(before.chars().count()));
+            } else {
+                let _ = writeln!(out, "  {line}");
+            }
+        }
+    }
+    let _ = writeln!(out, "\n{result}");
+    out
+}
+
+/// A replay as a self-contained HTML page to embed: the frames of [`text`]
+/// shown in turn, the cursor highlighted. Without JavaScript, all frames are
+/// listed.
+pub fn html(
+    challenge: &Challenge,
+    steps: &[String],
+    snapshots: &[Snapshot],
+    result: &str,
+) -> String {
+    let title = report::escape(&format!("{} - {}", challenge.id, challenge.title));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n\
+         .frame pre {{ background: #1e1e1e; color: #d4d4d4; padding: 1em; }}\n\
+         .cursor {{ background: #d4d4d4; color: #1e1e1e; }}\n\
+         .playing .frame {{ display: none; }}\n\
+         .playing .frame.shown {{ display: block; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<div class=\"replay\">\n"
+    );
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let _ = write!(
+            out,
+            "<figure class=\"frame\"><figcaption>{}</figcaption><pre>",
+            report::escape(&caption(i, steps))
+        );
+        let [cursor_line, cursor_col] = snapshot.cursor;
+        for (n, line) in (1..).zip(&snapshot.lines) {
+            if n == cursor_line {
+                let (before, at, after) = split_at_cursor(line, cursor_col);
+                let _ = write!(
+                    out,
+                    "{}<span class=\"cursor\">{}</span>{}",
+                    report::escape(before),
+                    report::escape(&at.unwrap_or(' ').to_string()),
+                    report::escape(after)
+                );
+            } else {
+                out.push_str(&report::escape(line));
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre></figure>\n");
+    }
+    let _ = write!(
+        out,
+        "</div>\n<p>{}</p>\n<script>\n\
+         const replay = document.querySelector('.replay');\n\
+         const frames = replay.querySelectorAll('.frame');\n\
+         let shown = 0;\n\
+         replay.classList.add('playing');\n\
+         frames[0].classList.add('shown');\n\
+         setInterval(() => {{\n\
+         \x20 frames[shown].classList.remove('shown');\n\
+         \x20 shown = (shown + 1) % frames.length;\n\
+         \x20 frames[shown].classList.add('shown');\n\
+         }}, {HTML_FRAME_MS});\n\
+         </script>\n</body>\n</html>\n",
+        report::escape(result)
+    );
+    out
+}
+
+/// The closing line of a replay: the grade, unless freestyle, keystrokes and
+/// time of the attempt.
+pub fn result_line(attempt: &AttemptRecord, freestyle: bool) -> String {
+    let mut line = format!("Solved in {} keystrokes", attempt.keystrokes);
+    if let Some(time_ms) = attempt.time_ms {
+        let _ = write!(line, ", {}", game::format_time(time_ms));
+    }
+    if !freestyle {
+        let _ = write!(line, ", grade {}", attempt.grade.display_char());
+    }
+    line
+}
@@ -0,0 +1,320 @@
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::challenge::{Challenge, Grade, Topic};
+use crate::game;
+use crate::state::GameState;
+
+/// Output format of `nvimkata list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    /// Aligned columns for reading in a terminal.
+    #[default]
+    Table,
+    /// Topics with their challenges as a JSON array.
+    Json,
+    /// One challenge per line with a header row, tab separated.
+    Tsv,
+}
+
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err(format!(
+                "invalid list format '{s}' (use table, json or tsv)"
+            )),
+        }
+    }
+}
+
+/// Order of `nvimkata list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListSort {
+    /// By topic, in curriculum order.
+    #[default]
+    Topic,
+    /// Speedrun ranking: every challenge by best time, fastest first, see
+    /// [`speedrun`].
+    Time,
+}
+
+impl FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "topic" => Ok(Self::Topic),
+            "time" => Ok(Self::Time),
+            _ => Err(format!("invalid list order '{s}' (use topic or time)")),
+        }
+    }
+}
+
+/// A topic as listed by `nvimkata list --format json`.
+#[derive(Debug, Serialize)]
+pub struct TopicEntry<'a> {
+    pub id: u8,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub challenges: Vec<ChallengeEntry<'a>>,
+}
+
+/// A challenge with the player's best result.
+#[derive(Debug, Serialize)]
+pub struct ChallengeEntry<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    pub difficulty: u8,
+    /// Par keystrokes, `None` for freestyle challenges.
+    pub par: Option<u32>,
+    /// The challenge's `focused_actions`.
+    pub tags: &'a [String],
+    /// Best grade, `None` when unplayed or freestyle.
+    pub grade: Option<Grade>,
+    pub best_keystrokes: Option<u32>,
+    pub completed: bool,
+    pub freestyle: bool,
+}
+
+impl<'a> ChallengeEntry<'a> {
+    pub fn new(challenge: &'a Challenge, state: &GameState) -> Self {
+        let freestyle = challenge.is_freestyle();
+        Self {
+            id: &challenge.id,
+            title: &challenge.title,
+            difficulty: challenge.difficulty,
+            par: (!freestyle).then_some(challenge.par()),
+            tags: challenge.focused_actions.as_deref().unwrap_or_default(),
+            grade: state.best_grade(&challenge.id).filter(|_| !freestyle),
+            best_keystrokes: state.best_keystrokes(&challenge.id),
+            completed: state.is_completed(&challenge.id),
+            freestyle,
+        }
+    }
+}
+
+/// Every topic with challenges, with the player's best results.
+pub fn entries<'a>(topics: &'a [Topic], state: &GameState) -> Vec<TopicEntry<'a>> {
+    topics
+        .iter()
+        .filter(|t| !t.challenges.is_empty())
+        .map(|topic| TopicEntry {
+            id: topic.id,
+            name: &topic.name,
+            description: &topic.description,
+            challenges: topic
+                .challenges
+                .iter()
+                .map(|c| ChallengeEntry::new(c, state))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Render the curriculum in the given format.
+pub fn render(
+    topics: &[Topic],
+    state: &GameState,
+    format: ListFormat,
+) -> Result<String, serde_json::Error> {
+    let entries = entries(topics, state);
+    Ok(match format {
+        ListFormat::Table => table(&entries),
+        ListFormat::Json => serde_json::to_string_pretty(&entries)? + "\n",
+        ListFormat::Tsv => tsv(&entries),
+    })
+}
+
+const HEADER: [&str; 8] = [
+    "topic",
+    "id",
+    "title",
+    "difficulty",
+    "par",
+    "tags",
+    "grade",
+    "best",
+];
+
+/// Columns of a challenge in `HEADER` order.
+fn row(topic: &TopicEntry, entry: &ChallengeEntry) -> [String; 8] {
+    let optional = |n: Option<u32>| n.map_or_else(String::new, |n| n.to_string());
+    [
+        topic.name.to_string(),
+        entry.id.to_string(),
+        entry.title.to_string(),
+        entry.difficulty.to_string(),
+        optional(entry.par),
+        entry.tags.join(","),
+        entry
+            .grade
+            .map_or_else(String::new, |g| g.display_char().to_string()),
+        optional(entry.best_keystrokes),
+    ]
+}
+
+/// A challenge in the speedrun ranking of `nvimkata list --sort time`.
+#[derive(Debug, Serialize)]
+pub struct SpeedrunEntry<'a> {
+    pub topic: &'a str,
+    pub id: &'a str,
+    pub title: &'a str,
+    /// Fastest timed solve, `None` when there is none.
+    pub best_time_ms: Option<u32>,
+    pub best_keystrokes: Option<u32>,
+}
+
+/// Every challenge by the player's best time, fastest first, then those
+/// without one in curriculum order.
+pub fn ranking<'a>(topics: &'a [Topic], state: &GameState) -> Vec<SpeedrunEntry<'a>> {
+    let mut ranking: Vec<SpeedrunEntry> = topics
+        .iter()
+        .flat_map(|topic| topic.challenges.iter().map(move |c| (topic, c)))
+        .map(|(topic, c)| SpeedrunEntry {
+            topic: &topic.name,
+            id: &c.id,
+            title: &c.title,
+            best_time_ms: state.best_time(&c.id),
+            best_keystrokes: state.best_keystrokes(&c.id),
+        })
+        .collect();
+    ranking.sort_by_key(|entry| (entry.best_time_ms.is_none(), entry.best_time_ms));
+    ranking
+}
+
+/// Render the speedrun ranking in the given format.
+pub fn speedrun(
+    topics: &[Topic],
+    state: &GameState,
+    format: ListFormat,
+) -> Result<String, serde_json::Error> {
+    let ranking = ranking(topics, state);
+    let optional = |n: Option<u32>| n.map_or_else(String::new, |n| n.to_string());
+    Ok(match format {
+        ListFormat::Json => serde_json::to_string_pretty(&ranking)? + "\n",
+        ListFormat::Tsv => {
+            let clean = |s: &str| s.replace(['\t', '\n'], " ");
+            let mut out = String::from("topic\tid\ttitle\ttime_ms\tbest\n");
+            for entry in &ranking {
+                let _ = writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}",
+                    clean(entry.topic),
+                    clean(entry.id),
+                    clean(entry.title),
+                    optional(entry.best_time_ms),
+                    optional(entry.best_keystrokes)
+                );
+            }
+            out
+        }
+        ListFormat::Table => {
+            let timed = ranking.iter().filter(|e| e.best_time_ms.is_some()).count();
+            let rows: Vec<[String; 4]> = ranking
+                .iter()
+                .map(|entry| {
+                    [
+                        entry
+                            .best_time_ms
+                            .map_or_else(|| "-".to_string(), game::format_time),
+                        entry.id.to_string(),
+                        entry.title.to_string(),
+                        entry.topic.to_string(),
+                    ]
+                })
+                .collect();
+            let header = ["time", "id", "title", "topic"].map(String::from);
+            let width = |i: usize| {
+                rows.iter()
+                    .chain([&header])
+                    .map(|r| r[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            };
+            let widths = [width(0), width(1), width(2)];
+            let mut out = format!("Fastest solves ({timed} of {} timed)\n", ranking.len());
+            for row in [&header].into_iter().chain(&rows) {
+                let line = format!(
+                    "  {:<w0$} {:<w1$} {:<w2$} {}",
+                    row[0],
+                    row[1],
+                    row[2],
+                    row[3],
+                    w0 = widths[0],
+                    w1 = widths[1],
+                    w2 = widths[2]
+                );
+                let _ = writeln!(out, "{}", line.trim_end());
+            }
+            out
+        }
+    })
+}
+
+fn tsv(entries: &[TopicEntry]) -> String {
+    // Tabs and newlines would break the columns
+    let clean = |s: &str| s.replace(['\t', '\n'], " ");
+    let mut out = HEADER.join("\t");
+    out.push('\n');
+    for topic in entries {
+        for entry in &topic.challenges {
+            let fields: Vec<String> = row(topic, entry).iter().map(|f| clean(f)).collect();
+            out.push_str(&fields.join("\t"));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn table(entries: &[TopicEntry]) -> String {
+    let rows: Vec<[String; 8]> = entries
+        .iter()
+        .flat_map(|t| t.challenges.iter().map(move |c| row(t, c)))
+        .collect();
+    // Topic is the section heading and tags come last, unpadded
+    let columns = [1, 3, 4, 6, 7, 2];
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|&i| {
+            rows.iter()
+                .map(|r| r[i].chars().count())
+                .chain([HEADER[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |fields: [&str; 8]| {
+        let mut out = String::from(" ");
+        for (&i, width) in columns.iter().zip(&widths) {
+            let _ = write!(out, " {:<width$}", fields[i]);
+        }
+        if !fields[5].is_empty() {
+            let _ = write!(out, "  {}", fields[5]);
+        }
+        out.trim_end().to_string()
+    };
+
+    let mut out = String::new();
+    let mut rows = rows.iter();
+    for topic in entries {
+        let completed = topic.challenges.iter().filter(|c| c.completed).count();
+        let _ = writeln!(
+            out,
+            "{} ({completed}/{})",
+            topic.name,
+            topic.challenges.len()
+        );
+        let _ = writeln!(out, "{}", line(HEADER));
+        for row in rows.by_ref().take(topic.challenges.len()) {
+            let _ = writeln!(out, "{}", line(row.each_ref().map(String::as_str)));
+        }
+    }
+    out
+}
@@ -1,6 +1,18 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
 use ratatui::style::{Color, Modifier, Style};
 use serde::{Deserialize, Serialize};
 
+use crate::commands;
+use crate::error::CurriculumError;
+use crate::heatmap::KeyboardLayout;
+use crate::notation::{self, Key};
+use crate::one_hand;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Challenge {
     pub id: String,
@@ -8,22 +20,342 @@ pub struct Challenge {
     pub title: String,
     pub topic: String,
     pub difficulty: u8,
+    #[serde(default)]
     pub hint: String,
     #[serde(default)]
     pub detailed_hint: Option<String>,
+    /// Hint tiers, from a nudge to the exact keys; each F1 reveals one more.
+    /// Replaces `hint` and `detailed_hint` when given.
+    #[serde(default)]
+    pub hints: Vec<String>,
+    /// Parts of the target highlighted once the hint is shown, to point at
+    /// where to look.
+    #[serde(default)]
+    pub hint_region: Vec<HintRegion>,
+    /// Context for the task, e.g. "normalize these dates to ISO-8601", shown in
+    /// the picker and above the target while playing.
+    #[serde(default)]
+    pub description: Option<String>,
     #[serde(default)]
     pub par_keystrokes: u32,
     #[serde(default)]
     pub perfect_moves: Option<Vec<String>>,
     #[serde(default)]
     pub focused_actions: Option<Vec<String>>,
-    pub start: BufferContent,
-    pub target: BufferContent,
+    /// Grading curve for grades A through E, in tenths of par. Falls back to the
+    /// topic curve, then to `DEFAULT_CURVE`.
+    #[serde(default)]
+    pub thresholds: Option<[u32; 5]>,
+    /// Par on keyboard layouts the challenge is much harder to type on, such
+    /// as `dvorak = 14` for heavy `;` and `,` use. Replaces `par_keystrokes`
+    /// for players with that `keyboard_layout`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layout_par: BTreeMap<KeyboardLayout, u32>,
+    /// Par of the one-hand variant, played with only the keys of `one_hand` in
+    /// the config. Without one, the challenge is played as usual in one-hand
+    /// mode.
+    #[serde(default)]
+    pub one_hand_par: Option<u32>,
+    /// Freestyle only: a known good keystroke count to measure personal bests against.
+    #[serde(default)]
+    pub reference_keystrokes: Option<u32>,
+    /// Neovim filetype applied to the challenge buffers, e.g. `python`.
+    #[serde(default)]
+    pub filetype: Option<String>,
+    /// Editor state prepared before the challenge starts.
+    #[serde(default)]
+    pub setup: Setup,
+    /// Key sequences (in key notation, e.g. `x`, `<Left>`, `:s`) that void the attempt.
+    #[serde(default)]
+    pub forbidden_keys: Vec<String>,
+    /// Technique the solution must use; matching the target without it is flagged.
+    #[serde(default)]
+    pub required_technique: Option<Technique>,
+    /// Guided steps. A tutorial challenge completes once every step's keys were typed
+    /// in order, instead of when the buffer matches the target.
+    #[serde(default)]
+    pub tutorial_steps: Vec<TutorialStep>,
+    /// IDs of challenges that must be completed before this one can be played.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Blind mode: the target is hidden while editing, leaving only the hint to
+    /// work from. It is revealed on the result screen.
+    #[serde(default)]
+    pub blind: bool,
+    /// Dictation mode: the target is shown for this many seconds, then hidden, and
+    /// the change must be reproduced from memory.
+    #[serde(default)]
+    pub reveal_secs: Option<u32>,
+    /// Substitution trainer: solved by a single `:s`, `:g` or `:v` command run on
+    /// the start buffer, and graded on the length of that command (counting its
+    /// `:` and Enter, like `perfect_moves`) instead of the keys typed.
+    #[serde(default)]
+    pub single_command: bool,
+    /// Macro challenge: the job must be done by a recorded macro.
+    #[serde(default, rename = "macro")]
+    pub recorded_macro: Option<MacroSpec>,
+    /// The buffers, `None` while unloaded. Read them with [`Challenge::start`]
+    /// and [`Challenge::target`] on a [`Challenge::loaded`] challenge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<BufferContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<BufferContent>,
+    /// Where to read `start` and `target` back from. Set when the curriculum
+    /// loader dropped them to keep only the metadata in memory; see
+    /// [`Challenge::loaded`].
+    #[serde(skip)]
+    pub source: Option<Source>,
+    /// Intro of the challenge's topic, set by the curriculum loader and shown
+    /// by F4 while playing.
+    #[serde(skip)]
+    pub intro: Option<String>,
+    /// Category of the challenge's topic, set by the curriculum loader.
+    #[serde(skip)]
+    pub category: Option<Category>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The file an unloaded challenge reads its buffers back from, and what they
+/// hashed to when it was loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub path: PathBuf,
+    /// [`challenge_hash`](crate::state::challenge_hash) of the challenge with
+    /// its buffers, to check saved results against without reading them.
+    pub content_hash: String,
+}
+
+/// The buffers of a challenge file, read on their own when a challenge is
+/// previewed or played.
+#[derive(Deserialize)]
+struct Buffers {
+    start: BufferContent,
+    target: BufferContent,
+}
+
+/// A technique verified by scanning the key log of an attempt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Technique {
+    /// Short description shown to the player, e.g. "a macro" or ":g".
+    pub name: String,
+    /// Key sequences in key notation; typing any one of them counts as using the technique.
+    pub keys: Vec<String>,
+}
+
+/// How a macro challenge is checked: the macro recorded into `register` must turn
+/// the start buffer into the target when replayed `replays` times from the start
+/// cursor. Graded on the length of the macro plus the number of times it was
+/// invoked, instead of every key typed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroSpec {
+    /// Register the macro is recorded into, `a` to `z`.
+    #[serde(default = "default_macro_register")]
+    pub register: char,
+    /// Replays that finish the job, counting the run made while recording.
+    pub replays: u32,
+}
+
+fn default_macro_register() -> char {
+    'q'
+}
+
+impl MacroSpec {
+    /// Invocations of the macro in a key log: each `@` of the register or `@@`,
+    /// whatever its count.
+    pub fn invocations(&self, keys: &str) -> usize {
+        let keys = split_keys(keys);
+        let register = self.register.to_string();
+        let mut count = 0;
+        let mut i = 0;
+        while i + 1 < keys.len() {
+            if keys[i] == "@" && (keys[i + 1] == register || keys[i + 1] == "@") {
+                count += 1;
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+        count
+    }
+
+    /// Score of a run: keys in the recorded macro plus its invocations.
+    pub fn keystrokes(&self, macro_keys: &str, keys: &str) -> usize {
+        count_keystrokes(macro_keys) + self.invocations(keys)
+    }
+}
+
+/// One instruction of a tutorial challenge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TutorialStep {
+    /// Shown in the winbar while the step is active, e.g. "Press w three times".
+    pub instruction: String,
+    /// Key sequence in key notation that completes the step, e.g. `www` or `<Esc>`.
+    pub keys: String,
+}
+
+/// Registers, options, marks and a quickfix list applied to the challenge buffer
+/// before play starts (and again on retry).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Setup {
+    /// Register name to content, e.g. `a = "hello"`. Content ending in a newline is linewise.
+    #[serde(default)]
+    pub registers: BTreeMap<String, String>,
+    /// Local options in `:setlocal` syntax, e.g. `"shiftwidth=2"` or `"expandtab"`.
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Mark name to `[line, column]`, both 1-based.
+    #[serde(default)]
+    pub marks: BTreeMap<String, [u32; 2]>,
+    /// Quickfix list entries pointing into the challenge buffer, in list order.
+    #[serde(default)]
+    pub quickfix: Vec<QuickfixEntry>,
+}
+
+/// A quickfix list entry, e.g. `{ line = 3, col = 5, text = "unused variable" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickfixEntry {
+    /// 1-based line in the challenge buffer.
+    pub line: u32,
+    /// 1-based column, the start of the line by default.
+    #[serde(default = "default_quickfix_col")]
+    pub col: u32,
+    /// Message shown in the quickfix window.
+    #[serde(default)]
+    pub text: String,
+}
+
+fn default_quickfix_col() -> u32 {
+    1
+}
+
+impl Setup {
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty()
+            && self.options.is_empty()
+            && self.marks.is_empty()
+            && self.quickfix.is_empty()
+    }
+
+    /// Check register, option and mark names and quickfix positions. Returns a
+    /// description of the first problem.
+    pub fn validate(&self) -> Result<(), String> {
+        for name in self.registers.keys() {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() || "\"-+*/".contains(c) => {}
+                _ => return Err(format!("invalid register name '{name}'")),
+            }
+        }
+        for option in &self.options {
+            let valid = !option.is_empty()
+                && option.chars().all(|c| {
+                    c.is_ascii_alphanumeric()
+                        || matches!(c, '=' | ',' | ':' | '-' | '_' | '.' | '+' | '^')
+                });
+            if !valid {
+                return Err(format!("invalid option '{option}'"));
+            }
+        }
+        for name in self.marks.keys() {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => {}
+                _ => return Err(format!("invalid mark name '{name}'")),
+            }
+        }
+        if let Some(entry) = self.quickfix.iter().find(|e| e.line == 0 || e.col == 0) {
+            return Err(format!(
+                "invalid quickfix position {}:{}",
+                entry.line, entry.col
+            ));
+        }
+        Ok(())
+    }
+}
+
+static LAYOUT: OnceLock<KeyboardLayout> = OnceLock::new();
+
+/// Grade against the par for `layout` for the rest of the session. Only the
+/// first call has an effect; returns false when it selected another layout.
+pub fn set_layout(layout: KeyboardLayout) -> bool {
+    *LAYOUT.get_or_init(|| layout) == layout
+}
+
+/// The player's keyboard layout, QWERTY until set.
+pub fn layout() -> KeyboardLayout {
+    LAYOUT.get().copied().unwrap_or_default()
+}
+
+/// Default grading curve: keystroke limits for grades A through E in tenths of par.
+/// A is par itself, B is 1.4x par, and so on.
+pub const DEFAULT_CURVE: [u32; 5] = [10, 14, 18, 24, 28];
+
+/// A curve is usable when its limits never decrease and Grade A allows at least par.
+pub fn is_valid_curve(curve: &[u32; 5]) -> bool {
+    curve[0] >= 10 && curve.windows(2).all(|w| w[0] <= w[1])
+}
+
+/// A stretch of the target, e.g. `{ line = 2, col = 5, end_col = 9 }`. Lines
+/// and columns count from 1 like `cursor` and are inclusive. The region runs to
+/// the end of its last line without `end_col`, and covers whole lines without
+/// `col`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintRegion {
+    pub line: u32,
+    #[serde(default)]
+    pub col: Option<u32>,
+    /// Last line, `line` by default.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    #[serde(default)]
+    pub end_col: Option<u32>,
+}
+
+impl HintRegion {
+    /// Whether the region starts at a line and column and doesn't end before it.
+    pub fn is_valid(&self) -> bool {
+        let end_line = self.end_line.unwrap_or(self.line);
+        let ordered = match (self.col, self.end_col) {
+            (Some(col), Some(end_col)) => end_line > self.line || end_col >= col,
+            _ => true,
+        };
+        self.line > 0
+            && self.col != Some(0)
+            && self.end_col != Some(0)
+            && end_line >= self.line
+            && ordered
+    }
+
+    /// The region in `content` as `[line, column, length]` spans, one per
+    /// line, for highlighting. Parts past the end of the content are dropped.
+    pub fn spans(&self, content: &str) -> Vec<[u32; 3]> {
+        let lines: Vec<&str> = content.lines().collect();
+        let end_line = self.end_line.unwrap_or(self.line);
+        (self.line..=end_line)
+            .filter_map(|n| {
+                let text = lines.get(usize::try_from(n).ok()?.checked_sub(1)?)?;
+                let len = u32::try_from(text.len()).ok()?;
+                let start = if n == self.line {
+                    self.col.unwrap_or(1)
+                } else {
+                    1
+                };
+                let end = match self.end_col {
+                    Some(col) if n == end_line => col.min(len),
+                    _ => len,
+                };
+                (start <= end).then(|| [n, start, end - start + 1])
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BufferContent {
     pub content: String,
+    /// Cursor position as `[line, column]`, both 1-based. On `start` this places the
+    /// cursor; on `target` the final cursor position must match for the challenge to complete.
+    #[serde(default)]
+    pub cursor: Option<[u32; 2]>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -57,6 +389,27 @@ impl Grade {
         }
     }
 
+    /// Position in the A-F ordering, 0 for A up to 5 for F. Lower is better.
+    pub fn rank(self) -> u8 {
+        match self {
+            Self::A => 0,
+            Self::B => 1,
+            Self::C => 2,
+            Self::D => 3,
+            Self::E => 4,
+            Self::F => 5,
+        }
+    }
+
+    /// The grade `by` grades worse, down to F.
+    #[must_use]
+    pub fn lowered(self, by: u32) -> Self {
+        let grades = [Self::A, Self::B, Self::C, Self::D, Self::E, Self::F];
+        let rank =
+            usize::from(self.rank()).saturating_add(usize::try_from(by).unwrap_or(usize::MAX));
+        grades[rank.min(grades.len() - 1)]
+    }
+
     pub fn display_char(self) -> &'static str {
         match self {
             Self::A => "A",
@@ -79,49 +432,65 @@ pub fn grade_display(grade: Option<Grade>) -> (&'static str, Style) {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Category {
+    Tutorial,
     Beginner,
     Intermediate,
     Advanced,
     Legendary,
     Freestyle,
+    /// Installed challenge packs, one topic each.
+    Pack,
+    /// Challenges gathered from other topics, such as favorites. Not listed in `ALL`.
+    Collection,
 }
 
 impl Category {
-    pub const ALL: [Category; 5] = [
+    pub const ALL: [Category; 7] = [
+        Self::Tutorial,
         Self::Beginner,
         Self::Intermediate,
         Self::Advanced,
         Self::Legendary,
         Self::Freestyle,
+        Self::Pack,
     ];
 
     pub fn for_topic(id: u8) -> Self {
         match id {
+            0 => Self::Tutorial,
             1 | 2 => Self::Beginner,
             3 | 4 => Self::Intermediate,
             5..=7 => Self::Advanced,
             100..=107 => Self::Freestyle,
+            150..=199 => Self::Pack,
+            200.. => Self::Collection,
             _ => Self::Legendary,
         }
     }
 
     pub fn name(self) -> &'static str {
         match self {
+            Self::Tutorial => "TUTORIAL",
             Self::Beginner => "BEGINNER",
             Self::Intermediate => "INTERMEDIATE",
             Self::Advanced => "ADVANCED",
             Self::Legendary => "LEGENDARY",
             Self::Freestyle => "FREESTYLE",
+            Self::Pack => "PACKS",
+            Self::Collection => "COLLECTION",
         }
     }
 
     pub fn color(self) -> Color {
         match self {
+            Self::Tutorial => Color::Green,
             Self::Beginner => Color::Cyan,
             Self::Intermediate => Color::Blue,
             Self::Advanced => Color::Magenta,
             Self::Legendary => Color::Rgb(255, 165, 0),
             Self::Freestyle => Color::Red,
+            Self::Pack => Color::LightGreen,
+            Self::Collection => Color::Yellow,
         }
     }
 }
@@ -131,48 +500,238 @@ pub struct Topic {
     pub id: u8,
     pub name: String,
     pub description: String,
+    /// Cheat sheet of the motions the topic teaches, from its `intro.md`.
+    #[serde(default)]
+    pub intro: Option<String>,
     pub challenges: Vec<Challenge>,
 }
 
 impl Challenge {
+    /// Text shown in an instructions window while playing: the description, and
+    /// the hint in place of the hidden target of a blind challenge.
+    pub fn instructions(&self) -> Option<String> {
+        let hint = self.hint_tiers().first().copied().filter(|_| self.blind);
+        match (self.description.as_deref(), hint) {
+            (Some(description), Some(hint)) => {
+                Some(format!("{}\n\n{hint}", description.trim_end()))
+            }
+            (Some(text), None) | (None, Some(text)) => Some(text.trim_end().to_string()),
+            (None, None) => None,
+        }
+    }
+
+    /// Drop the start and target buffers, to be read back from `path` when the
+    /// challenge is previewed or played. `content_hash` is the
+    /// [`challenge_hash`](crate::state::challenge_hash) they had.
+    pub fn unload(&mut self, path: &Path, content_hash: String) {
+        self.start = None;
+        self.target = None;
+        self.source = Some(Source {
+            path: path.to_path_buf(),
+            content_hash,
+        });
+    }
+
+    /// This challenge with its start and target buffers, read back from its
+    /// file if they were unloaded.
+    pub fn loaded(&self) -> Result<Cow<'_, Self>, CurriculumError> {
+        let Some(Source { path, .. }) = &self.source else {
+            return Ok(Cow::Borrowed(self));
+        };
+        let content = fs::read_to_string(path).map_err(|source| CurriculumError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        let buffers: Buffers =
+            toml::from_str(&content).map_err(|source| CurriculumError::Parse {
+                path: path.clone(),
+                source,
+            })?;
+        Ok(Cow::Owned(Self {
+            start: Some(buffers.start),
+            target: Some(buffers.target),
+            source: None,
+            ..self.clone()
+        }))
+    }
+
+    /// The start buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the buffers were unloaded: read them back with [`Self::loaded`].
+    pub fn start(&self) -> &BufferContent {
+        self.start
+            .as_ref()
+            .expect("start buffer read before the challenge was loaded")
+    }
+
+    /// The target buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the buffers were unloaded: read them back with [`Self::loaded`].
+    pub fn target(&self) -> &BufferContent {
+        self.target
+            .as_ref()
+            .expect("target buffer read before the challenge was loaded")
+    }
+
     /// Returns true if this is a freestyle challenge (no par, no `perfect_moves`).
     pub fn is_freestyle(&self) -> bool {
         self.par_keystrokes == 0 && self.perfect_moves.is_none()
     }
 
+    /// Hint tiers in the order F1 reveals them: `hints`, or else `hint` and
+    /// `detailed_hint`. Blank ones are left out.
+    pub fn hint_tiers(&self) -> Vec<&str> {
+        let tiers: Vec<&str> = if self.hints.is_empty() {
+            std::iter::once(self.hint.as_str())
+                .chain(self.detailed_hint.as_deref())
+                .collect()
+        } else {
+            self.hints.iter().map(String::as_str).collect()
+        };
+        tiers.into_iter().filter(|h| !h.trim().is_empty()).collect()
+    }
+
+    /// Spans of the target to highlight with the hint, see [`HintRegion::spans`].
+    pub fn hint_spans(&self) -> Vec<[u32; 3]> {
+        self.hint_region
+            .iter()
+            .flat_map(|region| region.spans(&self.target().content))
+            .collect()
+    }
+
+    /// Returns true if this challenge is verified by `tutorial_steps`.
+    pub fn is_tutorial(&self) -> bool {
+        !self.tutorial_steps.is_empty()
+    }
+
+    /// Whether the challenge is graded on the keys typed, so that hardcore
+    /// mode can end a run once they pass the grade F threshold.
+    pub fn has_key_budget(&self) -> bool {
+        !self.is_freestyle()
+            && !self.is_tutorial()
+            && !self.single_command
+            && self.recorded_macro.is_none()
+    }
+
+    /// Number of tutorial steps a key log completes. Each step's keys must appear
+    /// after the keys that completed the previous step.
+    pub fn tutorial_steps_done(&self, keys: &str) -> usize {
+        let mut rest = keys;
+        for (i, step) in self.tutorial_steps.iter().enumerate() {
+            match rest.find(step.keys.as_str()) {
+                Some(pos) => rest = &rest[pos + step.keys.len()..],
+                None => return i,
+            }
+        }
+        self.tutorial_steps.len()
+    }
+
+    /// First `forbidden_keys` sequence typed in a key log, if any. Sequences
+    /// match whole keys, so `c` isn't found in `<Esc>`, and text typed in insert
+    /// mode doesn't count.
+    pub fn forbidden_key_in(&self, keys: &str) -> Option<&str> {
+        let keys = notation::tokenize(keys);
+        let text = commands::typed_text(&keys);
+        self.forbidden_keys
+            .iter()
+            .map(String::as_str)
+            .find(|seq| commands::typed_as_keys(&keys, &text, &notation::tokenize(seq)))
+    }
+
+    /// Whether a key log uses the `required_technique` (always true without one):
+    /// one of its sequences typed within a command, not as insert-mode text.
+    pub fn technique_used(&self, keys: &str) -> bool {
+        let Some(technique) = &self.required_technique else {
+            return true;
+        };
+        let sequences: Vec<Vec<Key>> = technique
+            .keys
+            .iter()
+            .map(|seq| notation::tokenize(seq))
+            .collect();
+        let keys = notation::tokenize(keys);
+        let text = commands::typed_text(&keys);
+        let mut start = 0;
+        commands::classify(&keys).iter().any(|command| {
+            let range = start..start + command.keys.len();
+            start = range.end;
+            sequences
+                .iter()
+                .any(|seq| commands::typed_as_keys(&command.keys, &text[range.clone()], seq))
+        })
+    }
+
+    /// The grading curve in effect for this challenge.
+    pub fn curve(&self) -> [u32; 5] {
+        self.thresholds.unwrap_or(DEFAULT_CURVE)
+    }
+
     /// Score a completed challenge based on keystroke count vs par.
     /// Always returns a grade (F for anything above E threshold).
     pub fn score(&self, keystrokes: u32) -> Grade {
-        let par = self.par_keystrokes;
-        if keystrokes <= par {
-            Grade::A
-        } else if keystrokes <= par * 14 / 10 {
-            Grade::B
-        } else if keystrokes <= par * 18 / 10 {
-            Grade::C
-        } else if keystrokes <= par * 24 / 10 {
-            Grade::D
-        } else if keystrokes <= par * 28 / 10 {
-            Grade::E
-        } else {
-            Grade::F
-        }
+        [Grade::A, Grade::B, Grade::C, Grade::D, Grade::E]
+            .into_iter()
+            .find(|&g| keystrokes <= self.threshold(g))
+            .unwrap_or(Grade::F)
+    }
+
+    /// [`Self::score`] of a run that revealed `hints_used` hint tiers: each tier
+    /// past the first lowers the grade by one.
+    pub fn score_with_hints(&self, keystrokes: u32, hints_used: u32) -> Grade {
+        self.score(keystrokes).lowered(hints_used.saturating_sub(1))
+    }
+
+    /// The par graded against: the one of the one-hand variant when it is
+    /// played, else the one for the player's keyboard layout, if the challenge
+    /// has them, else `par_keystrokes`.
+    pub fn par(&self) -> u32 {
+        self.one_hand_par
+            .filter(|_| one_hand::active().is_some())
+            .or_else(|| self.layout_par.get(&layout()).copied())
+            .unwrap_or(self.par_keystrokes)
+    }
+
+    /// The only keys the challenge may be played with: those of the one-hand
+    /// variant, when it is played.
+    pub fn one_hand_keys(&self) -> Option<&'static [Key]> {
+        self.one_hand_par.and(one_hand::active())
     }
 
-    /// Get the keystroke threshold for a given grade.
+    /// What voids a key log: its first `forbidden_keys` sequence, or else its
+    /// first key outside [`Self::one_hand_keys`].
+    pub fn violation_in(&self, keys: &str) -> Option<String> {
+        self.forbidden_key_in(keys)
+            .map(str::to_string)
+            .or_else(|| one_hand::stray_key(self.one_hand_keys()?, keys))
+    }
+
+    /// Get the keystroke threshold for a given grade, on the [`Self::par`] of
+    /// the player's layout.
+    /// The F threshold is display-only and extends the curve by its last step.
     pub fn threshold(&self, grade: Grade) -> u32 {
-        let par = self.par_keystrokes;
-        match grade {
-            Grade::A => par,
-            Grade::B => par * 14 / 10,
-            Grade::C => par * 18 / 10,
-            Grade::D => par * 24 / 10,
-            Grade::E => par * 28 / 10,
-            Grade::F => par * 32 / 10,
-        }
+        let par = self.par();
+        let curve = self.curve();
+        let tenths = match grade {
+            Grade::F => curve[4] + (curve[4] - curve[3]),
+            g => curve[usize::from(g.rank())],
+        };
+        par * tenths / 10
     }
 }
 
+/// A filetype name is safe to pass to `:setlocal` when it only uses
+/// alphanumerics, `_`, `-` and `.` (for compound filetypes).
+pub fn is_valid_filetype(ft: &str) -> bool {
+    !ft.is_empty()
+        && ft
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
 /// Count keystrokes in a vim key notation string.
 /// Regular characters count as 1. `<...>` sequences (e.g., `<Esc>`, `<C-r>`) count as 1.
 ///
@@ -180,17 +739,24 @@ impl Challenge {
 /// must be written as `<lt>` in `perfect_moves` to avoid being parsed as a vim key name.
 /// For example, `ciw<lt>Esc>` types the literal text `<Esc>` rather than pressing Escape.
 pub fn count_keystrokes(s: &str) -> usize {
-    let mut count = 0;
-    let mut chars = s.chars();
-    while let Some(c) = chars.next() {
-        if c == '<' {
-            for c2 in chars.by_ref() {
-                if c2 == '>' {
-                    break;
-                }
+    split_keys(s).len()
+}
+
+/// Split key notation into one string per keystroke, e.g. `ciw<Esc>` into
+/// `c`, `i`, `w` and `<Esc>`. A `<` that does not start a `<name>` is a key of
+/// its own. See [`crate::notation`] for structured keys.
+pub fn split_keys(s: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        let len = match rest.find('>') {
+            Some(end) if c == '<' && end > 1 && !rest[1..end].contains(char::is_whitespace) => {
+                end + 1
             }
-        }
-        count += 1;
+            _ => c.len_utf8(),
+        };
+        keys.push(&rest[..len]);
+        rest = &rest[len..];
     }
-    count
+    keys
 }
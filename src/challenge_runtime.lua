@@ -1,20 +1,102 @@
 -- nvimkata challenge runtime
 -- Variables injected by Rust preamble:
---   _VK_NUMBER, _VK_TITLE, _VK_PAR, _VK_HINT, _VK_DETAILED_HINT,
---   _VK_FREESTYLE, _VK_RESULTS_PATH, _VK_TARGET_PATH, _VK_START_PATH,
+--   _VK_NUMBER, _VK_TITLE, _VK_PAR, _VK_HINTS (hint tiers, revealed one per F1),
+--   _VK_HINT_SPANS (list of { line, col, length } of the target, 1-based, highlighted
+--     once the hint is shown),
+--   _VK_FREESTYLE, _VK_REFERENCE, _VK_RESULTS_PATH, _VK_TARGET_PATH, _VK_START_PATH,
+--   _VK_START_CURSOR, _VK_TARGET_CURSOR ({ line, col } 1-based, or nil),
+--   _VK_APPLY_SETUP (function(buf) applying registers, options and marks),
+--   _VK_FORBIDDEN (list of { sequence, keys }: each forbidden key sequence as written
+--     and its keys in key notation),
+--   _VK_ONE_HAND (one-hand variant: the only keys allowed, in key notation; nil = any),
+--   _VK_TECHNIQUE_NAME, _VK_TECHNIQUE_KEYS (required technique, its key sequences as
+--     in _VK_FORBIDDEN; empty list = none),
+--   _VK_TUTORIAL_STEPS (list of { instruction, keys }; non-empty = tutorial mode,
+--     completed by typing each step's keys instead of matching the target),
 --   _VK_THRESHOLD_A, _VK_THRESHOLD_B, _VK_THRESHOLD_C, _VK_THRESHOLD_D,
---   _VK_THRESHOLD_E, _VK_THRESHOLD_F
+--   _VK_THRESHOLD_E, _VK_THRESHOLD_F,
+--   _VK_BLIND (the target is hidden; only the hint is shown),
+--   _VK_REVEAL_MS (dictation: the target is hidden after this long; 0 = always shown),
+--   _VK_SINGLE_COMMAND (solved by one :s, :g or :v command run on the start buffer,
+--     graded on the command's length),
+--   _VK_MACRO_REGISTER, _VK_MACRO_REPLAYS (macro challenge: the macro recorded into
+--     the register must reach the target when replayed that many times from the
+--     start; graded on its length plus its invocations. Empty register = none),
+--   _VK_SHELL_ALLOWED (programs shell commands may run; nil = any, {} = none),
+--   _VK_BRIEFING (the challenge is shown with the clock stopped until Enter),
+--   _VK_HARDCORE (the run fails once its keys pass _VK_THRESHOLD_F),
+--   _VK_BANNER_PATH (file whose first line is shown in the bar, such as a duel
+--     opponent's result, read on every tick; nil = none),
+--   _VK_PERFECT_MOVES (list of moves in key notation, played back on giving up;
+--     empty = none),
+--   _VK_EMBEDDED (played in a running neovim through the plugin; instead of quitting,
+--     the runtime calls _VK_ON_EXIT, which the plugin defines)
+
+-- Fallbacks for releases older than the APIs used (0.8 is the minimum): vim.uv is
+-- vim.loop before 0.10, and float windows take a title from 0.9
+local uv = vim.uv or vim.loop
+local has_float_title = vim.fn.has("nvim-0.9") == 1
 
 local ks = 0
 local done = false
 local cmd_start_ks = nil
 local win = vim.api.nvim_get_current_win()
 local buf = vim.api.nvim_get_current_buf()
-local t0 = vim.uv.now()
+-- Monotonic clock in milliseconds
+local function now_ms()
+  return math.floor(uv.hrtime() / 1e6)
+end
+local t0 = now_ms()
+-- When the latest key was pressed; a solve is timed to the key that completed it
+local last_key = t0
 local showing_hint = false
+-- Deepest hint tier revealed. Hints seen stay seen across retries.
+local hints_used = 0
+local paused = false
+-- Keys are not counted until the briefing ends
+local briefing = _VK_BRIEFING
 local f1_code = vim.api.nvim_replace_termcodes("<F1>", true, false, true)
+local f2_code = vim.api.nvim_replace_termcodes("<F2>", true, false, true)
+local f3_code = vim.api.nvim_replace_termcodes("<F3>", true, false, true)
+local f4_code = vim.api.nvim_replace_termcodes("<F4>", true, false, true)
+local f5_code = vim.api.nvim_replace_termcodes("<F5>", true, false, true)
 local key_log = {}
+-- Whether each key_log entry is a character typed in insert mode
+local typed_text = {}
+local violation = nil
+local function keytrans(k)
+  return vim.fn.keytrans(vim.api.nvim_replace_termcodes(k, true, true, true))
+end
+-- Keys of the one-hand variant in keytrans notation, as a set; nil = any
+local one_hand = nil
+if _VK_ONE_HAND then
+  one_hand = {}
+  for _, k in ipairs(_VK_ONE_HAND) do
+    one_hand[keytrans(k)] = true
+  end
+end
+-- Key sequences as { sequence, keys in keytrans notation }
+local function key_sequences(entries)
+  local sequences = {}
+  for _, entry in ipairs(entries) do
+    local keys = {}
+    for _, k in ipairs(entry[2]) do
+      keys[#keys + 1] = keytrans(k)
+    end
+    sequences[#sequences + 1] = { entry[1], keys }
+  end
+  return sequences
+end
+local forbidden = key_sequences(_VK_FORBIDDEN)
+local technique = key_sequences(_VK_TECHNIQUE_KEYS)
 local timer_tick
+local tutorial = #_VK_TUTORIAL_STEPS > 0
+local step = 1
+-- Number of key_log entries typed before the current tutorial step began
+local step_start = 0
+-- Autocmds and the key logger, removed again by _G._ks_detach in embedded mode
+local group = vim.api.nvim_create_augroup("nvimkata", { clear = true })
+local ns = vim.api.nvim_create_namespace("nvimkata")
 
 local function norm(lines)
   local r = {}
@@ -28,17 +110,123 @@ local function norm(lines)
 end
 
 local target_norm = norm(vim.fn.readfile(_VK_TARGET_PATH))
+-- The buffer still holds the start content while the runtime loads
+local start_norm = norm(vim.api.nvim_buf_get_lines(buf, 0, -1, false))
+-- The command that solved a single-command challenge
+local solved_command = nil
+-- The macro that solved a macro challenge, in key notation
+local macro_keys = nil
+-- Shell commands run, as typed on the command line
+local shell_commands = {}
+
+-- The target window sits above the challenge window, unless the challenge is blind.
+-- Dictation: it is swapped for a placeholder once _VK_REVEAL_MS have passed, and
+-- shown again on finish or retry
+local target_win = nil
+local target_buf = nil
+local hidden_buf = nil
+local target_hidden = false
+local hint_region_shown = false
+if not _VK_BLIND then
+  target_win = vim.fn.win_getid(vim.fn.winnr("k"))
+  if target_win == 0 or target_win == win then
+    target_win = nil
+  else
+    target_buf = vim.api.nvim_win_get_buf(target_win)
+  end
+end
+
+local function set_target_hidden(hide)
+  if not target_win or not vim.api.nvim_win_is_valid(target_win) or hide == target_hidden then
+    return
+  end
+  target_hidden = hide
+  if hide then
+    if not hidden_buf or not vim.api.nvim_buf_is_valid(hidden_buf) then
+      hidden_buf = vim.api.nvim_create_buf(false, true)
+      vim.api.nvim_buf_set_lines(hidden_buf, 0, -1, false, { "", "  Reproduce the target from memory" })
+      vim.api.nvim_set_option_value("modifiable", false, { buf = hidden_buf })
+      vim.api.nvim_set_option_value("bufhidden", "wipe", { buf = hidden_buf })
+    end
+    vim.api.nvim_win_call(target_win, function()
+      vim.cmd("diffoff")
+    end)
+    vim.api.nvim_win_set_buf(target_win, hidden_buf)
+    vim.api.nvim_win_call(win, function()
+      vim.cmd("diffoff")
+    end)
+  else
+    vim.api.nvim_win_set_buf(target_win, target_buf)
+    for _, w in ipairs({ target_win, win }) do
+      vim.api.nvim_win_call(w, function()
+        vim.cmd("diffthis")
+      end)
+    end
+  end
+  vim.api.nvim_set_option_value("winbar", hide and "  [TARGET HIDDEN]" or "  [TARGET]", { win = target_win })
+  vim.api.nvim_set_option_value("wrap", true, { win = target_win })
+end
+
+-- Elapsed milliseconds as mm:ss.mmm
+-- Highlight the parts of the target the hint points at
+local function show_hint_region()
+  if hint_region_shown or not target_buf or not vim.api.nvim_buf_is_valid(target_buf) then
+    return
+  end
+  hint_region_shown = true
+  for _, span in ipairs(_VK_HINT_SPANS) do
+    pcall(vim.api.nvim_buf_set_extmark, target_buf, ns, span[1] - 1, span[2] - 1, {
+      end_col = span[2] - 1 + span[3],
+      hl_group = "IncSearch",
+    })
+  end
+end
+
+local function format_time(ms)
+  local secs = math.floor(ms / 1000)
+  return string.format("%02d:%02d.%03d", math.floor(secs / 60), secs % 60, ms % 1000)
+end
 
 local function set_bar(n, elapsed)
   if not vim.api.nvim_win_is_valid(win) then
     return
   end
-  local m = math.floor(elapsed / 60)
-  local s = elapsed % 60
+  local secs = math.floor(elapsed / 1000)
+  local m = math.floor(secs / 60)
+  local s = secs % 60
   local bar = string.format("  #%03d - %s | %d keys | %02d:%02d", _VK_NUMBER, _VK_TITLE, n, m, s)
   if _VK_FREESTYLE then
     bar = bar .. " | FREESTYLE"
   end
+  if _VK_BLIND then
+    bar = bar .. " | BLIND"
+  end
+  if _VK_HARDCORE then
+    bar = bar .. " | HARDCORE: " .. _VK_THRESHOLD_F .. " MAX"
+  end
+  if _VK_SINGLE_COMMAND then
+    bar = bar .. " | ONE :s/:g COMMAND"
+  end
+  if _VK_MACRO_REGISTER ~= "" then
+    bar = bar .. ' | MACRO IN "' .. _VK_MACRO_REGISTER
+  end
+  if violation then
+    bar = bar .. " | FORBIDDEN: " .. violation
+  end
+  if _VK_BANNER_PATH and vim.fn.filereadable(_VK_BANNER_PATH) == 1 then
+    local banner = vim.fn.readfile(_VK_BANNER_PATH, "", 1)[1]
+    if banner and banner ~= "" then
+      bar = bar .. " | " .. banner
+    end
+  end
+  if tutorial then
+    local current = _VK_TUTORIAL_STEPS[step]
+    if current then
+      bar = string.format("%s | STEP %d/%d: %s", bar, step, #_VK_TUTORIAL_STEPS, current[1])
+    else
+      bar = bar .. " | ALL STEPS DONE"
+    end
+  end
   vim.api.nvim_set_option_value("winbar", bar:gsub("%%", "%%%%"), { win = win })
 end
 
@@ -92,8 +280,8 @@ local function show_hint_float(title, text, footer)
     height = #lines,
     style = "minimal",
     border = "rounded",
-    title = " " .. title .. " ",
-    title_pos = "center",
+    title = has_float_title and " " .. title .. " " or nil,
+    title_pos = has_float_title and "center" or nil,
   })
 
   vim.cmd("redraw")
@@ -104,17 +292,150 @@ local function show_hint_float(title, text, footer)
   end
   showing_hint = false
 
-  -- Return whether F1 was pressed to dismiss
-  if ok and key == f1_code then
+  -- Return the key that dismissed it
+  return ok and key or ""
+end
+
+-- Topic intro in a float, as written so tables keep their layout. j/k scroll,
+-- any other key closes it.
+local function show_intro()
+  showing_hint = true
+  local ui = vim.api.nvim_list_uis()[1] or { width = 80, height = 24 }
+  local lines = vim.split(vim.trim(_VK_INTRO), "\n")
+  local width = 0
+  for _, l in ipairs(lines) do
+    width = math.max(width, vim.fn.strdisplaywidth(l))
+  end
+  width = math.min(width + 2, ui.width - 4)
+  local height = math.min(#lines, ui.height - 6)
+
+  local float_buf = vim.api.nvim_create_buf(false, true)
+  vim.api.nvim_buf_set_lines(float_buf, 0, -1, false, lines)
+  vim.api.nvim_set_option_value("filetype", "markdown", { buf = float_buf })
+  vim.api.nvim_set_option_value("modifiable", false, { buf = float_buf })
+  vim.api.nvim_set_option_value("bufhidden", "wipe", { buf = float_buf })
+
+  local float_win = vim.api.nvim_open_win(float_buf, true, {
+    relative = "editor",
+    row = math.floor((ui.height - height) / 2),
+    col = math.floor((ui.width - width) / 2),
+    width = width,
+    height = height,
+    style = "minimal",
+    border = "rounded",
+    title = has_float_title and " Cheat sheet (j/k: scroll) " or nil,
+    title_pos = has_float_title and "center" or nil,
+  })
+
+  local down = vim.api.nvim_replace_termcodes("<Down>", true, false, true)
+  local up = vim.api.nvim_replace_termcodes("<Up>", true, false, true)
+  while true do
+    vim.cmd("redraw")
+    local ok, key = pcall(vim.fn.getcharstr)
+    local scroll = nil
+    if ok and (key == "j" or key == down) then
+      scroll = "\5"
+    elseif ok and (key == "k" or key == up) then
+      scroll = "\25"
+    end
+    if not scroll then
+      break
+    end
+    vim.api.nvim_win_call(float_win, function()
+      vim.cmd("normal! " .. scroll)
+    end)
+  end
+
+  if vim.api.nvim_win_is_valid(float_win) then
+    vim.api.nvim_win_close(float_win, true)
+  end
+  showing_hint = false
+end
+
+-- Cursor position as { line, col }, both 1-based
+local function cursor_pos()
+  if not vim.api.nvim_win_is_valid(win) then
+    return { 0, 0 }
+  end
+  local pos = vim.api.nvim_win_get_cursor(win)
+  return { pos[1], pos[2] + 1 }
+end
+
+local function place_start_cursor()
+  if _VK_START_CURSOR then
+    pcall(vim.api.nvim_win_set_cursor, win, { _VK_START_CURSOR[1], _VK_START_CURSOR[2] - 1 })
+  end
+end
+
+local function cursor_matches()
+  if not _VK_TARGET_CURSOR then
     return true
   end
+  local pos = cursor_pos()
+  return pos[1] == _VK_TARGET_CURSOR[1] and pos[2] == _VK_TARGET_CURSOR[2]
+end
+
+-- Whether the key_log entries ending at `last` are the keys of a sequence, none
+-- of them text typed in insert mode
+local function typed_as_keys(keys, last)
+  local first = last - #keys
+  if #keys == 0 or first < 0 then
+    return false
+  end
+  for i, k in ipairs(keys) do
+    if key_log[first + i] ~= k or typed_text[first + i] then
+      return false
+    end
+  end
+  return true
+end
+
+-- Whether the key log contains any of the required technique's key sequences,
+-- typed as keys rather than as insert-mode text
+local function technique_used()
+  if #technique == 0 then
+    return true
+  end
+  for last = 1, #key_log do
+    for _, entry in ipairs(technique) do
+      if typed_as_keys(entry[2], last) then
+        return true
+      end
+    end
+  end
   return false
 end
 
-local function write_results(n, elapsed, keys)
+-- outcome: "completed" when the target was reached, "submitted" when ended early with :w.
+-- Quitting without either leaves no results, which nvimkata reads as aborted.
+local function write_results(n, elapsed, keys, outcome)
   local f = io.open(_VK_RESULTS_PATH, "w")
   if f then
-    f:write(tostring(n) .. "\n" .. tostring(elapsed) .. "\n" .. keys)
+    local pos = cursor_pos()
+    f:write(string.format("%d\n%d\n", n, elapsed) .. keys)
+    f:write(string.format("\ncursor=%d,%d", pos[1], pos[2]))
+    if violation then
+      f:write("\nviolation=" .. violation)
+    end
+    if not technique_used() then
+      f:write("\ntechnique=missing")
+    end
+    if tutorial then
+      f:write("\nsteps=" .. tostring(step - 1))
+    end
+    if solved_command then
+      f:write("\ncommand=" .. solved_command)
+    end
+    if macro_keys then
+      f:write("\nmacro=" .. macro_keys)
+    end
+    for _, cmd in ipairs(shell_commands) do
+      f:write("\nshell=" .. cmd)
+    end
+    if hints_used > 0 then
+      f:write("\nhints=" .. hints_used)
+    end
+    f:write("\noutcome=" .. outcome)
     f:close()
   end
 end
@@ -139,14 +460,17 @@ local function get_grade(n)
 end
 
 local function show_result_float(n, elapsed, matched)
-  local grade = matched and get_grade(n) or nil
-  local m = math.floor(elapsed / 60)
-  local s = elapsed % 60
+  local grade = matched and not violation and get_grade(n) or nil
+  local time = format_time(elapsed)
 
   local lines = {}
   table.insert(lines, "")
-  if _VK_FREESTYLE then
-    if matched then
+  if matched and violation then
+    table.insert(lines, "  CONSTRAINT VIOLATED (" .. violation .. ")")
+  elseif _VK_FREESTYLE then
+    if matched and _VK_REFERENCE > 0 and n <= _VK_REFERENCE then
+      table.insert(lines, "  COMPLETED (BEAT THE REFERENCE!)")
+    elseif matched then
       table.insert(lines, "  COMPLETED")
     else
       table.insert(lines, "  FAILED")
@@ -157,10 +481,21 @@ local function show_result_float(n, elapsed, matched)
     table.insert(lines, "  FAILED")
   end
   table.insert(lines, "")
-  if _VK_FREESTYLE then
-    table.insert(lines, string.format("  %d keys | %02d:%02d", n, m, s))
+  if _VK_FREESTYLE and _VK_REFERENCE > 0 then
+    table.insert(lines, string.format("  %d keys (reference: %d) | %s", n, _VK_REFERENCE, time))
+  elseif _VK_FREESTYLE then
+    table.insert(lines, string.format("  %d keys | %s", n, time))
   else
-    table.insert(lines, string.format("  %d keys (par: %d) | %02d:%02d", n, _VK_PAR, m, s))
+    table.insert(lines, string.format("  %d keys (par: %d) | %s", n, _VK_PAR, time))
+  end
+  if matched and not technique_used() then
+    table.insert(lines, "  Technique not used: " .. _VK_TECHNIQUE_NAME)
+  end
+  if _VK_MACRO_REGISTER ~= "" and not macro_keys then
+    table.insert(
+      lines,
+      string.format('  Macro in "%s does not do it in %d replays', _VK_MACRO_REGISTER, _VK_MACRO_REPLAYS)
+    )
   end
   table.insert(lines, "")
   table.insert(lines, "  r: retry | any other key: exit")
@@ -189,8 +524,8 @@ local function show_result_float(n, elapsed, matched)
     height = height,
     style = "minimal",
     border = "rounded",
-    title = " Result ",
-    title_pos = "center",
+    title = has_float_title and " Result " or nil,
+    title_pos = has_float_title and "center" or nil,
   })
 
   vim.api.nvim_set_option_value("modifiable", false, { buf = float_buf })
@@ -220,93 +555,543 @@ local function show_result_float(n, elapsed, matched)
   return false
 end
 
+local function start_timer()
+  _G._ks_timer:start(
+    100,
+    100,
+    vim.schedule_wrap(function()
+      timer_tick()
+    end)
+  )
+end
+
+-- Cover the editor until a key is pressed. The clock stops and keys are not counted.
+local function pause()
+  paused = true
+  _G._ks_timer:stop()
+  local paused_at = now_ms()
+  local ui = vim.api.nvim_list_uis()[1] or { width = 80, height = 24 }
+  local height = math.max(1, ui.height - 1)
+
+  local lines = {}
+  for _ = 1, height do
+    lines[#lines + 1] = ""
+  end
+  local middle = math.max(1, math.floor(height / 2))
+  local function center(text)
+    return string.rep(" ", math.max(0, math.floor((ui.width - #text) / 2))) .. text
+  end
+  lines[middle] = center("PAUSED")
+  if middle + 2 <= height then
+    lines[middle + 2] = center("any key: resume")
+  end
+
+  local float_buf = vim.api.nvim_create_buf(false, true)
+  vim.api.nvim_buf_set_lines(float_buf, 0, -1, false, lines)
+  vim.api.nvim_set_option_value("modifiable", false, { buf = float_buf })
+  vim.api.nvim_set_option_value("bufhidden", "wipe", { buf = float_buf })
+  local float_win = vim.api.nvim_open_win(float_buf, true, {
+    relative = "editor",
+    row = 0,
+    col = 0,
+    width = ui.width,
+    height = height,
+    style = "minimal",
+    zindex = 250,
+  })
+  vim.api.nvim_buf_add_highlight(float_buf, -1, "Title", middle - 1, 0, -1)
+
+  vim.cmd("redraw")
+  pcall(vim.fn.getcharstr)
+
+  if vim.api.nvim_win_is_valid(float_win) then
+    vim.api.nvim_win_close(float_win, true)
+  end
+  -- Move the clock forward by the pause so it is not timed
+  local pause_ms = now_ms() - paused_at
+  t0 = t0 + pause_ms
+  last_key = last_key + pause_ms
+  paused = false
+  set_bar(ks, now_ms() - t0)
+  start_timer()
+end
+
+-- Show the challenge with the clock stopped until Enter starts it, or q quits
+local function brief()
+  local title = _VK_TITLE:gsub("%%", "%%%%")
+  vim.api.nvim_set_option_value(
+    "winbar",
+    string.format("  #%03d - %s | BRIEFING | Enter: start | q: quit", _VK_NUMBER, title),
+    { win = win }
+  )
+  vim.cmd("redraw")
+  local ok, char
+  repeat
+    ok, char = pcall(vim.fn.getcharstr)
+  until not ok or char == "\r" or char == "q"
+  if char == "q" then
+    done = true
+    if _VK_EMBEDDED then
+      _VK_ON_EXIT()
+    else
+      vim.cmd("qall!")
+    end
+    return
+  end
+  briefing = false
+  t0 = now_ms()
+  last_key = t0
+  set_bar(0, 0)
+  start_timer()
+end
+
 local function do_retry()
+  -- The retry replaces the solved run, so quitting from here on is an abort
+  os.remove(_VK_RESULTS_PATH)
+  set_target_hidden(false)
   -- Reset buffer from start file
   local start_lines = vim.fn.readfile(_VK_START_PATH)
   vim.api.nvim_buf_set_lines(buf, 0, -1, false, start_lines)
+  _VK_APPLY_SETUP(buf)
+  place_start_cursor()
   -- Reset state
   ks = 0
   done = false
   cmd_start_ks = nil
-  t0 = vim.uv.now()
+  t0 = now_ms()
+  last_key = t0
   key_log = {}
+  typed_text = {}
+  violation = nil
+  solved_command = nil
+  macro_keys = nil
+  shell_commands = {}
+  step = 1
+  step_start = 0
   set_bar(0, 0)
-  -- Restart timer
-  _G._ks_timer:start(
-    100,
-    100,
-    vim.schedule_wrap(function()
-      timer_tick()
-    end)
-  )
+  start_timer()
+end
+
+-- Return the first forbidden sequence the key log now ends with, or its last
+-- key when the one-hand variant doesn't allow it, if any
+local function check_forbidden()
+  if one_hand and not one_hand[key_log[#key_log]] then
+    return key_log[#key_log]
+  end
+  for _, entry in ipairs(forbidden) do
+    if typed_as_keys(entry[2], #key_log) then
+      return entry[1]
+    end
+  end
+  return nil
+end
+
+-- Advance the tutorial when the keys typed since the step began end with its keys
+local function check_step()
+  local current = _VK_TUTORIAL_STEPS[step]
+  if not current then
+    return
+  end
+  local typed = table.concat(key_log, "", step_start + 1)
+  if typed:sub(-#current[2]) == current[2] then
+    step = step + 1
+    step_start = #key_log
+  end
+end
+
+-- Keys in a key notation string, with <...> names counting as one
+local function count_keys(notation)
+  return vim.fn.strchars((notation:gsub("<[^<>]+>", "x")))
+end
+
+-- Invocations of the macro in a key log: each @ of its register or @@
+local function macro_invocations(keys)
+  local n = 0
+  local i = 1
+  while i < #keys do
+    local next_key = keys:sub(i + 1, i + 1)
+    if keys:sub(i, i) == "@" and (next_key == _VK_MACRO_REGISTER or next_key == "@") then
+      n = n + 1
+      i = i + 2
+    else
+      i = i + 1
+    end
+  end
+  return n
+end
+
+-- Replay the recorded macro on a scratch copy of the start buffer
+local function macro_replays(macro)
+  local scratch = vim.api.nvim_create_buf(false, true)
+  vim.api.nvim_buf_set_lines(scratch, 0, -1, false, vim.fn.readfile(_VK_START_PATH))
+  -- The setup may set registers too; keep the recorded macro
+  _VK_APPLY_SETUP(scratch)
+  vim.fn.setreg(_VK_MACRO_REGISTER, macro)
+  vim.api.nvim_buf_call(scratch, function()
+    if _VK_START_CURSOR then
+      pcall(vim.api.nvim_win_set_cursor, 0, { _VK_START_CURSOR[1], _VK_START_CURSOR[2] - 1 })
+    end
+    vim.cmd("silent! normal! " .. _VK_MACRO_REPLAYS .. "@" .. _VK_MACRO_REGISTER)
+  end)
+  local replayed = norm(vim.api.nvim_buf_get_lines(scratch, 0, -1, false)) == target_norm
+  vim.api.nvim_buf_delete(scratch, { force = true })
+  return replayed
 end
 
 local function finish(n, elapsed, keys, matched)
   done = true
   _G._ks_timer:stop()
-  write_results(n, elapsed, keys)
+  write_results(n, elapsed, keys, "completed")
+  set_target_hidden(false)
 
   local retry = show_result_float(n, elapsed, matched)
   if retry then
     do_retry()
+  elseif _VK_EMBEDDED then
+    _VK_ON_EXIT()
   else
     vim.cmd("silent! write | qall!")
   end
 end
 
+-- Hardcore: past the grade F threshold the run ends as failed, with the
+-- buffer back at the start
+local function over_budget()
+  _G._ks_timer:stop()
+  vim.cmd("stopinsert")
+  local elapsed = last_key - t0
+  vim.api.nvim_buf_set_lines(buf, 0, -1, false, vim.fn.readfile(_VK_START_PATH))
+  write_results(ks, elapsed, table.concat(key_log), "over_budget")
+  set_target_hidden(false)
+  set_bar(ks, elapsed)
+  local text = string.format(
+    "%d keys is past the grade F threshold of %d, so this run failed. The buffer is back at the start.",
+    ks,
+    _VK_THRESHOLD_F
+  )
+  if show_hint_float("Over budget", text, "r: retry | any other key: exit") == "r" then
+    do_retry()
+  elseif _VK_EMBEDDED then
+    _VK_ON_EXIT()
+  else
+    vim.cmd("silent! write | qall!")
+  end
+end
+
+-- Giving up ends the run as failed, then plays the perfect moves back from the
+-- start buffer, one move at a time, and lists them
+local function give_up()
+  _G._ks_timer:stop()
+  vim.cmd("stopinsert")
+  local elapsed = last_key - t0
+  write_results(ks, elapsed, table.concat(key_log), "gave_up")
+  set_target_hidden(false)
+  vim.api.nvim_buf_set_lines(buf, 0, -1, false, vim.fn.readfile(_VK_START_PATH))
+  _VK_APPLY_SETUP(buf)
+  place_start_cursor()
+
+  local function ask()
+    local text = "This challenge has no perfect moves to show."
+    if #_VK_PERFECT_MOVES > 0 then
+      text = string.format(
+        "Perfect moves (%d keys):\n%s",
+        count_keys(table.concat(_VK_PERFECT_MOVES)),
+        table.concat(_VK_PERFECT_MOVES, "  ")
+      )
+    end
+    if show_hint_float("Gave up", text, "r: retry | any other key: exit") == "r" then
+      do_retry()
+    elseif _VK_EMBEDDED then
+      _VK_ON_EXIT()
+    else
+      vim.cmd("silent! write | qall!")
+    end
+  end
+
+  local function play(i)
+    if i > #_VK_PERFECT_MOVES then
+      vim.defer_fn(ask, 1500)
+      return
+    end
+    local move = _VK_PERFECT_MOVES[i]
+    local bar = string.format("  #%03d - %s | GAVE UP | move %d/%d: %s", _VK_NUMBER, _VK_TITLE, i, #_VK_PERFECT_MOVES, move)
+    vim.api.nvim_set_option_value("winbar", bar:gsub("%%", "%%%%"), { win = win })
+    vim.api.nvim_feedkeys(vim.api.nvim_replace_termcodes(move, true, false, true), "n", false)
+    vim.defer_fn(function()
+      play(i + 1)
+    end, 700)
+  end
+  -- Start from normal mode, after the stopinsert above
+  vim.api.nvim_feedkeys(vim.api.nvim_replace_termcodes("<C-\\><C-n>", true, false, true), "n", false)
+  vim.defer_fn(function()
+    play(1)
+  end, 700)
+end
+
+_VK_APPLY_SETUP(buf)
+place_start_cursor()
 set_bar(0, 0)
 
--- F1 hint popup (filtered from keystroke count)
+-- F1 hint popup, F1 again for the next tier (filtered from keystroke count)
 for _, mode in ipairs({ "n", "i", "v" }) do
   vim.keymap.set(mode, "<F1>", function()
-    local hint_footer = _VK_DETAILED_HINT ~= "" and "F1: detailed hint | any key: close" or "any key: close"
-    local dismissed_with_f1 = show_hint_float("Hint", _VK_HINT, hint_footer)
-    if dismissed_with_f1 and _VK_DETAILED_HINT ~= "" then
-      show_hint_float("Detailed Hint", _VK_DETAILED_HINT, "any key: close")
+    if #_VK_HINTS == 0 then
+      show_hint_float("Hint", "This challenge has no hint.", "any key: close")
+      return
+    end
+    show_hint_region()
+    -- Tiers past the first lower the grade, so they are only shown on request
+    local tier = 1
+    while true do
+      if not done then
+        hints_used = math.max(hints_used, tier)
+      end
+      local title = #_VK_HINTS > 1 and string.format("Hint %d/%d", tier, #_VK_HINTS) or "Hint"
+      local footer = tier < #_VK_HINTS and "F1: next hint (lowers the grade) | any key: close"
+        or "any key: close"
+      if show_hint_float(title, _VK_HINTS[tier], footer) ~= f1_code or tier == #_VK_HINTS then
+        break
+      end
+      tier = tier + 1
+    end
+  end, { noremap = true, silent = true, buffer = _VK_EMBEDDED and buf or nil })
+end
+
+-- F4 shows the cheat sheet of the topic (filtered from keystroke count)
+for _, mode in ipairs({ "n", "i", "v" }) do
+  vim.keymap.set(mode, "<F4>", function()
+    if _VK_INTRO == "" then
+      show_hint_float("Cheat sheet", "This topic has no cheat sheet.", "any key: close")
+    else
+      show_intro()
     end
-  end, { noremap = true, silent = true })
+  end, { noremap = true, silent = true, buffer = _VK_EMBEDDED and buf or nil })
+end
+
+-- F2 pauses (filtered from keystroke count)
+for _, mode in ipairs({ "n", "i", "v" }) do
+  vim.keymap.set(mode, "<F2>", function()
+    if not done then
+      pause()
+    end
+  end, { noremap = true, silent = true, buffer = _VK_EMBEDDED and buf or nil })
+end
+
+-- F5 restarts the run in place: the buffer, keys and clock start over without
+-- leaving neovim (filtered from keystroke count)
+for _, mode in ipairs({ "n", "i", "v" }) do
+  vim.keymap.set(mode, "<F5>", function()
+    if done or briefing then
+      return
+    end
+    -- Keys leaving the mode are counted, and dropped again by the retry
+    vim.cmd("stopinsert")
+    if mode == "v" then
+      vim.cmd("normal! \27")
+    end
+    do_retry()
+  end, { noremap = true, silent = true, buffer = _VK_EMBEDDED and buf or nil })
+end
+
+-- F3 gives up to watch the perfect moves (filtered from keystroke count)
+for _, mode in ipairs({ "n", "i", "v" }) do
+  vim.keymap.set(mode, "<F3>", function()
+    if done or briefing then
+      return
+    end
+    -- The moves play back once the mode has been left
+    done = true
+    if mode == "v" then
+      vim.cmd("normal! \27")
+    end
+    vim.schedule(give_up)
+  end, { noremap = true, silent = true, buffer = _VK_EMBEDDED and buf or nil })
 end
 
 -- Track command-line entry for :w subtraction
 vim.api.nvim_create_autocmd("CmdlineEnter", {
+  group = group,
   callback = function()
     cmd_start_ks = ks
   end,
 })
+-- Whether an Ex command line is a :substitute, :global or :vglobal, after its range
+local function is_single_command(cmd)
+  local rest, prev = cmd, nil
+  while rest ~= prev do
+    prev = rest
+    rest = rest:gsub("^[%s%d%%,;.$+%-]+", ""):gsub("^'.", "")
+  end
+  local name = rest:match("^%a+") or ""
+  for _, full in ipairs({ "substitute", "global", "vglobal" }) do
+    if #name > 0 and full:sub(1, #name) == name then
+      return true
+    end
+  end
+  return false
+end
+
+-- A single-command challenge is solved by a command run on the untouched start
+-- buffer that leaves the target. The buffer is checked once the command has run.
+local function check_command(cmd)
+  local lines = norm(vim.api.nvim_buf_get_lines(buf, 0, -1, false))
+  if lines ~= start_norm or not is_single_command(cmd) then
+    return
+  end
+  vim.schedule(function()
+    if done then
+      return
+    end
+    if norm(vim.api.nvim_buf_get_lines(buf, 0, -1, false)) == target_norm and cursor_matches() then
+      solved_command = cmd
+      -- The command, its : and Enter, like perfect_moves count it
+      finish(vim.fn.strchars(cmd) + 2, last_key - t0, table.concat(key_log), true)
+    end
+  end)
+end
+
+-- The shell command an Ex command runs (`:!cmd`, `:r !cmd`, `:w !cmd` or a
+-- `:{range}!cmd` filter), nil for other commands
+local function shell_command(cmd)
+  local rest, prev = cmd, nil
+  while rest ~= prev do
+    prev = rest
+    rest = rest:gsub("^[%s%d%%,;.$+%-]+", ""):gsub("^'.", "")
+  end
+  local word, after = rest:match("^(%a+)!?%s*(.*)$")
+  if word and #word >= 3 and ("silent"):sub(1, #word) == word then
+    rest = after
+  end
+  local name, space, command = rest:match("^(%a*)(%s*)!(.*)$")
+  if not name then
+    return nil
+  end
+  local is_read = ("read"):sub(1, #name) == name
+  local is_write = ("write"):sub(1, #name) == name and space ~= ""
+  if name ~= "" and not is_read and not is_write then
+    return nil
+  end
+  return command
+end
+
+-- Whether the policy lets a shell command run: every program in its pipeline
+-- is allowed, and there is nothing else for the shell to run or redirect to.
+-- Only typed commands are checked, so the policy is best effort: `:exe`,
+-- system(), :terminal and Lua still reach the shell unseen
+local function shell_allowed(command)
+  if not _VK_SHELL_ALLOWED then
+    return true
+  end
+  if command:find("[;&`$<>()]") then
+    return false
+  end
+  for segment in (command .. "|"):gmatch("([^|]*)|") do
+    local program = (segment:match("^%s*(%S*)") or ""):match("[^/]*$")
+    if not vim.tbl_contains(_VK_SHELL_ALLOWED, program) then
+      return false
+    end
+  end
+  return true
+end
+
 vim.api.nvim_create_autocmd("CmdlineLeave", {
-  callback = function()
+  group = group,
+  callback = function(args)
     cmd_start_ks = nil
+    if done or args.match ~= ":" or vim.v.event.abort then
+      return
+    end
+    local cmd = vim.fn.getcmdline()
+    local shell = shell_command(cmd)
+    if shell and not shell_allowed(shell) then
+      -- Refuse it: the command line is dropped as if abandoned
+      vim.cmd("let v:event.abort = v:true")
+      vim.schedule(function()
+        vim.notify("nvimkata: shell command not allowed", vim.log.levels.WARN)
+      end)
+      return
+    elseif shell then
+      table.insert(shell_commands, cmd)
+    end
+    if _VK_SINGLE_COMMAND then
+      check_command(cmd)
+    end
   end,
 })
 
--- Count keystrokes (filter F1)
-vim.on_key(function(_, typed)
-  if done or showing_hint or not typed or typed == "" then
+-- Count keystrokes (filter F1, F2, F3 and F5). Before 0.10 only the key after mappings is passed.
+vim.on_key(function(key, typed)
+  typed = typed or key
+  if done or showing_hint or paused or briefing or not typed or typed == "" then
     return
   end
-  if typed == f1_code then
+  if typed == f1_code or typed == f2_code or typed == f3_code or typed == f4_code
+    or typed == f5_code
+  then
     return
   end
   ks = ks + 1
+  last_key = now_ms()
   key_log[#key_log + 1] = vim.fn.keytrans(typed)
-  local elapsed = math.floor((vim.uv.now() - t0) / 1000)
-  set_bar(ks, elapsed)
-end)
+  -- A printable character in insert or replace mode is text, not a command
+  local mode = vim.api.nvim_get_mode().mode
+  local byte = typed:byte(1)
+  typed_text[#key_log] = (mode:sub(1, 1) == "i" or mode:sub(1, 1) == "R")
+    and vim.fn.strchars(typed) == 1 and byte >= 32 and byte ~= 127 and byte ~= 128
+  if not violation then
+    violation = check_forbidden()
+  end
+  if tutorial then
+    check_step()
+  end
+  if _VK_HARDCORE and ks > _VK_THRESHOLD_F then
+    -- The buffer can't be changed from on_key
+    done = true
+    vim.schedule(over_budget)
+    return
+  end
+  set_bar(ks, last_key - t0)
+end, ns)
 
 -- Timer tick function
 timer_tick = function()
-  if done then
+  if done or paused then
     return
   end
-  local elapsed = math.floor((vim.uv.now() - t0) / 1000)
-  set_bar(ks, elapsed)
-  local matched = norm(vim.api.nvim_buf_get_lines(buf, 0, -1, false)) == target_norm
-  if matched then
-    finish(ks, elapsed, table.concat(key_log), matched)
+  set_bar(ks, now_ms() - t0)
+  if _VK_REVEAL_MS > 0 and not target_hidden and now_ms() - t0 >= _VK_REVEAL_MS then
+    set_target_hidden(true)
+  end
+  -- Single-command challenges are checked when a command runs
+  if _VK_SINGLE_COMMAND then
+    return
+  end
+  local matched
+  if tutorial then
+    matched = step > #_VK_TUTORIAL_STEPS
+  else
+    matched = norm(vim.api.nvim_buf_get_lines(buf, 0, -1, false)) == target_norm and cursor_matches()
+  end
+  if matched and _VK_MACRO_REGISTER ~= "" then
+    -- Wait for the recording to end
+    if vim.fn.reg_recording() ~= "" then
+      return
+    end
+    -- Stop counting keys while the macro replays
+    done = true
+    local macro = vim.fn.getreg(_VK_MACRO_REGISTER)
+    if macro ~= "" and macro_replays(macro) then
+      macro_keys = vim.fn.keytrans(macro)
+    end
+    local keys = table.concat(key_log)
+    local n = macro_keys and count_keys(macro_keys) + macro_invocations(keys) or ks
+    finish(n, last_key - t0, keys, macro_keys ~= nil)
+  elseif matched then
+    finish(ks, last_key - t0, table.concat(key_log), matched)
   end
 end
 
-local t = vim.uv.new_timer()
+local t = uv.new_timer()
 _G._ks_timer = t
 _G._ks_stop = function()
   if done then
@@ -314,19 +1099,24 @@ _G._ks_stop = function()
   end
   done = true
   t:stop()
-  local elapsed = math.floor((vim.uv.now() - t0) / 1000)
+  local elapsed = now_ms() - t0
   local save_ks = 2
   if cmd_start_ks then
     save_ks = ks - cmd_start_ks + 1
   end
   local final_ks = math.max(0, ks - save_ks)
-  write_results(final_ks, elapsed, table.concat(key_log, "", 1, final_ks))
+  write_results(final_ks, elapsed, table.concat(key_log, "", 1, final_ks), "submitted")
 end
 
-t:start(
-  100,
-  100,
-  vim.schedule_wrap(function()
-    timer_tick()
-  end)
-)
+_G._ks_detach = function()
+  done = true
+  t:stop()
+  vim.on_key(nil, ns)
+  pcall(vim.api.nvim_del_augroup_by_id, group)
+end
+
+if briefing then
+  vim.schedule(brief)
+else
+  start_timer()
+end
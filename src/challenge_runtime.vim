@@ -0,0 +1,333 @@
+" nvimkata challenge runtime for classic Vim (vimscript, no Lua required).
+" Variables set in preamble (prepended by Rust):
+"   g:vk_number, g:vk_title, g:vk_par, g:vk_hints (hint tiers, one more per F1),
+"   g:vk_hint_spans ([line, col, length] of the target highlighted with the hint),
+"   g:vk_intro (lines of the topic cheat sheet, toggled by F4),
+"   g:vk_freestyle, g:vk_reference,
+"   g:vk_tutorial, g:vk_blind, g:vk_reveal_ms, g:vk_single_command,
+"   g:vk_macro_register, g:vk_macro_replays (macro challenge, '' for none),
+"   g:vk_shell_allowed (programs shell commands may run, v:null for any),
+"   g:vk_briefing (the clock starts on Enter; nvimkata drops the keys up to it),
+"   g:vk_banner_path (file whose first line is shown in the bar, '' for none),
+"   g:vk_results_path, g:vk_target_path, g:vk_macro_path,
+"   g:vk_start_cursor, g:vk_target_cursor ([line, col], 1-based, or [] for none),
+"   VkApplySetup() applying the challenge [setup] to the current buffer.
+"
+" Keys are logged by Vim itself (-W), then counted and checked by nvimkata once
+" Vim exits. This script detects completion and writes the results file:
+" elapsed milliseconds, then `cursor=line,col`, then `outcome=completed`,
+" `outcome=submitted` if the session ended on :w or `outcome=gave_up` on F3,
+" and `command=cmd` for the command that solved a single-command challenge,
+" `shell=cmd` for each shell command run and `hints=n` for the deepest hint tier
+" shown. A macro that solves a macro challenge is written to g:vk_macro_path.
+" Quitting otherwise writes no results, which nvimkata reads as aborted.
+
+let s:buf = bufnr('%')
+let s:win = win_getid()
+let s:done = 0
+let s:shell = []
+let s:hint = 0
+let s:hints_used = 0
+let s:start = reltime()
+
+set laststatus=2
+
+" Trim trailing whitespace per line and trailing empty lines
+function! s:Norm(lines) abort
+  let lines = map(copy(a:lines), {_, l -> substitute(l, '\s\+$', '', '')})
+  while !empty(lines) && lines[-1] ==# ''
+    call remove(lines, -1)
+  endwhile
+  return lines
+endfunction
+
+let s:target = s:Norm(readfile(g:vk_target_path))
+" The buffer still holds the start content while the runtime loads
+let s:start_raw = getbufline(s:buf, 1, '$')
+let s:start_lines = s:Norm(s:start_raw)
+
+" The target window is above, unless the challenge is blind. Dictation: it is
+" swapped for a placeholder once g:vk_reveal_ms have passed
+let s:target_win = !g:vk_blind ? win_getid(winnr('k')) : 0
+let s:target_hidden = 0
+let s:hint_region_shown = 0
+
+function! s:HideTarget() abort
+  let s:target_hidden = 1
+  if s:target_win == 0 || s:target_win == s:win
+    return
+  endif
+  call clearmatches(s:target_win)
+  call win_execute(s:target_win, [
+        \ 'diffoff',
+        \ 'silent enew',
+        \ 'setlocal buftype=nofile bufhidden=wipe noswapfile',
+        \ "call setline(1, ['', '  Reproduce the target from memory'])",
+        \ 'setlocal nomodifiable',
+        \ "let &l:statusline = '  [TARGET HIDDEN]'",
+        \ ])
+  call win_execute(s:win, 'diffoff')
+endfunction
+
+function! s:Elapsed() abort
+  return float2nr(reltimefloat(reltime(s:start)) * 1000)
+endfunction
+
+function! s:Cursor() abort
+  let pos = getcurpos(s:win)
+  return [pos[1], pos[2]]
+endfunction
+
+" Statusline of the challenge window, redrawn by the timer
+function! VkBar() abort
+  let secs = s:Elapsed() / 1000
+  let bar = printf('  #%03d - %s', g:vk_number, g:vk_title)
+  if g:vk_tutorial
+    let bar .= ' | :w when done'
+  elseif g:vk_freestyle
+    if g:vk_reference > 0
+      let bar .= ' | reference ' . g:vk_reference
+    endif
+  else
+    let bar .= ' | par ' . g:vk_par
+  endif
+  if g:vk_blind
+    let bar .= ' | blind'
+  endif
+  if g:vk_single_command
+    let bar .= ' | one :s/:g command'
+  endif
+  if g:vk_macro_register !=# ''
+    let bar .= ' | macro in "' . g:vk_macro_register
+  endif
+  if g:vk_banner_path !=# '' && filereadable(g:vk_banner_path)
+    let banner = get(readfile(g:vk_banner_path, '', 1), 0, '')
+    if banner !=# ''
+      let bar .= ' | ' . banner
+    endif
+  endif
+  return bar . printf(' | %02d:%02d | F1: hint | F3: give up', secs / 60, secs % 60)
+endfunction
+
+" Extra result lines, e.g. `command=...`, are passed after the outcome
+function! s:Finish(outcome, ...) abort
+  if s:done
+    return
+  endif
+  let s:done = 1
+  call timer_stop(s:timer)
+  let pos = s:Cursor()
+  let shell = map(copy(s:shell), {_, cmd -> 'shell=' . cmd})
+  let hints = s:hints_used > 0 ? ['hints=' . s:hints_used] : []
+  call writefile([s:Elapsed(), 'cursor=' . pos[0] . ',' . pos[1], 'outcome=' . a:outcome] + a:000 + shell + hints, g:vk_results_path)
+  call win_execute(s:win, 'silent! write!')
+  qall!
+endfunction
+
+function! s:Tick(timer) abort
+  if s:done
+    return
+  endif
+  redrawstatus!
+  if g:vk_reveal_ms > 0 && !s:target_hidden && s:Elapsed() >= g:vk_reveal_ms
+    call s:HideTarget()
+  endif
+  " Tutorials are checked from the key log, so they end on :w, and
+  " single-command challenges when a command runs
+  if g:vk_tutorial || g:vk_single_command
+    return
+  endif
+  if s:Norm(getbufline(s:buf, 1, '$')) ==# s:target
+        \ && (empty(g:vk_target_cursor) || s:Cursor() ==# g:vk_target_cursor)
+    if g:vk_macro_register !=# ''
+      " Wait for the recording to end
+      if reg_recording() !=# ''
+        return
+      endif
+      let macro = getreg(g:vk_macro_register)
+      if macro !=# '' && s:MacroReplays(macro)
+        call writefile(split(macro, "\n", 1), g:vk_macro_path, 'b')
+      endif
+    endif
+    call s:Finish('completed')
+  endif
+endfunction
+
+" Replay the recorded macro on a scratch copy of the start buffer
+function! s:MacroReplays(macro) abort
+  noautocmd keepalt botright new
+  setlocal buftype=nofile bufhidden=wipe noswapfile
+  call setline(1, s:start_raw)
+  " The setup may set registers too; keep the recorded macro
+  call VkApplySetup()
+  call setreg(g:vk_macro_register, a:macro)
+  if !empty(g:vk_start_cursor)
+    call cursor(g:vk_start_cursor[0], g:vk_start_cursor[1])
+  endif
+  execute 'silent! normal! ' . g:vk_macro_replays . '@' . g:vk_macro_register
+  let replayed = s:Norm(getline(1, '$')) ==# s:target
+  noautocmd close
+  call win_gotoid(s:win)
+  return replayed
+endfunction
+
+" Whether an Ex command line is a :substitute, :global or :vglobal, after its range
+function! s:IsSingleCommand(cmd) abort
+  let name = matchstr(a:cmd, '^\%([[:space:][:digit:]%,;.$+-]\|''.\)*\zs\a*')
+  return name !=# '' && ('substitute' =~# '^' . name || 'global' =~# '^' . name
+        \ || 'vglobal' =~# '^' . name)
+endfunction
+
+" A single-command challenge is solved by a command run on the untouched start
+" buffer that leaves the target. The buffer is checked once the command has run.
+function! s:CheckCommand(cmd) abort
+  if s:done || s:Norm(getbufline(s:buf, 1, '$')) !=# s:start_lines || !s:IsSingleCommand(a:cmd)
+    return
+  endif
+  let s:command = a:cmd
+  call timer_start(0, function('s:CommandRan'))
+endfunction
+
+function! s:CommandRan(timer) abort
+  if s:Norm(getbufline(s:buf, 1, '$')) ==# s:target
+        \ && (empty(g:vk_target_cursor) || s:Cursor() ==# g:vk_target_cursor)
+    call s:Finish('completed', 'command=' . s:command)
+  endif
+endfunction
+
+" The shell command an Ex command runs (:!cmd, :r !cmd, :w !cmd or a
+" :{range}!cmd filter), as a one-item list, or [] for other commands
+function! s:ShellCommand(cmd) abort
+  let rest = matchstr(a:cmd, '^\%([[:space:][:digit:]%,;.$+-]\|''.\)*\zs.*')
+  let rest = substitute(rest, '^sil\%[ent]!\=\s*', '', '')
+  let m = matchlist(rest, '^\%(r\%[ead]\s*\|w\%[rite]\s\+\)\=!\(.*\)$')
+  return empty(m) ? [] : [m[1]]
+endfunction
+
+" Whether g:vk_shell_allowed lets a shell command run: every program in its
+" pipeline is allowed, and there is nothing else for the shell to run or redirect to.
+" Only typed commands are checked, so the policy is best effort: :execute,
+" system() and :terminal still reach the shell unseen
+function! s:ShellAllowed(command) abort
+  if g:vk_shell_allowed is v:null
+    return 1
+  endif
+  if a:command =~# '[;&`$<>()]'
+    return 0
+  endif
+  for segment in split(a:command, '|', 1)
+    let program = fnamemodify(matchstr(segment, '^\s*\zs\S*'), ':t')
+    if index(g:vk_shell_allowed, program) < 0
+      return 0
+    endif
+  endfor
+  return 1
+endfunction
+
+" Enter on the command line: record shell commands, or drop refused ones
+function! s:CmdlineEnter() abort
+  if getcmdtype() !=# ':' || s:done
+    return "\<CR>"
+  endif
+  let shell = s:ShellCommand(getcmdline())
+  if !empty(shell) && !s:ShellAllowed(shell[0])
+    call timer_start(0, {-> execute('echohl WarningMsg | echo "nvimkata: shell command not allowed" | echohl None', '')})
+    return "\<C-u>\<C-c>"
+  endif
+  if !empty(shell)
+    call add(s:shell, getcmdline())
+  endif
+  return "\<CR>"
+endfunction
+
+call VkApplySetup()
+if !empty(g:vk_start_cursor)
+  call cursor(g:vk_start_cursor[0], g:vk_start_cursor[1])
+endif
+
+" Highlight the parts of the target the hint points at, eight spans per call
+" for older Vims
+function! s:ShowHintRegion() abort
+  if s:hint_region_shown || s:target_hidden || s:target_win == 0 || s:target_win == s:win
+    return
+  endif
+  let s:hint_region_shown = 1
+  for i in range(0, len(g:vk_hint_spans) - 1, 8)
+    call matchaddpos('IncSearch', g:vk_hint_spans[i : i + 7], 10, -1, {'window': s:target_win})
+  endfor
+endfunction
+
+" F1 shows the next hint tier, starting over after the last (filtered from the
+" keystroke count). Tiers past the first lower the grade.
+function! s:Hint() abort
+  if empty(g:vk_hints)
+    echo 'This challenge has no hint.'
+    return
+  endif
+  call s:ShowHintRegion()
+  let s:hint = s:hint % len(g:vk_hints) + 1
+  let s:hints_used = max([s:hints_used, s:hint])
+  echo printf('Hint %d/%d: %s', s:hint, len(g:vk_hints), g:vk_hints[s:hint - 1])
+endfunction
+
+" F4 shows or hides the topic cheat sheet beside the buffers (filtered from the
+" keystroke count). It takes no keys, so editing goes on while it is open.
+let s:intro_popup = 0
+function! s:Intro() abort
+  if empty(g:vk_intro)
+    echo 'This topic has no cheat sheet.'
+  elseif !has('popupwin')
+    echo 'The cheat sheet needs Vim with +popupwin.'
+  elseif s:intro_popup
+    call popup_close(s:intro_popup)
+    let s:intro_popup = 0
+  else
+    let s:intro_popup = popup_create(g:vk_intro, {
+          \ 'line': 2, 'col': &columns, 'pos': 'topright',
+          \ 'maxheight': &lines - 6, 'border': [],
+          \ 'title': ' Cheat sheet (F4: close) '})
+  endif
+endfunction
+
+nnoremap <buffer> <F1> <Cmd>call <SID>Hint()<CR>
+inoremap <buffer> <F1> <Cmd>call <SID>Hint()<CR>
+nnoremap <buffer> <F4> <Cmd>call <SID>Intro()<CR>
+inoremap <buffer> <F4> <Cmd>call <SID>Intro()<CR>
+" F3 gives up; the result screen shows the perfect moves
+nnoremap <buffer> <F3> <Cmd>call <SID>Finish('gave_up')<CR>
+inoremap <buffer> <F3> <Cmd>call <SID>Finish('gave_up')<CR>
+cnoremap <buffer> <expr> <CR> <SID>CmdlineEnter()
+
+" Stop and quit on :w
+augroup nvimkata
+  autocmd!
+  execute 'autocmd BufWritePost <buffer=' . s:buf . '> call s:Finish("submitted")'
+  if g:vk_single_command
+    autocmd CmdlineLeave : if !get(v:event, 'abort') | call s:CheckCommand(getcmdline()) | endif
+  endif
+augroup END
+
+" Briefing: the challenge is shown with the clock stopped until Enter starts
+" it, or q quits
+function! s:Brief(timer) abort
+  call setwinvar(s:win, '&statusline',
+        \ printf('  #%03d - %s | briefing | Enter: start | q: quit', g:vk_number, substitute(g:vk_title, '%', '%%', 'g')))
+  redraw!
+  let char = 0
+  while char != 13 && char != char2nr('q')
+    let char = getchar()
+  endwhile
+  if char != 13
+    qall!
+  endif
+  let s:start = reltime()
+  call setwinvar(s:win, '&statusline', '%{VkBar()}')
+  let s:timer = timer_start(100, function('s:Tick'), {'repeat': -1})
+endfunction
+
+if g:vk_briefing
+  call timer_start(0, function('s:Brief'))
+else
+  call setwinvar(s:win, '&statusline', '%{VkBar()}')
+  let s:timer = timer_start(100, function('s:Tick'), {'repeat': -1})
+endif
@@ -0,0 +1,1144 @@
+use std::fmt::{self, Write as _};
+use std::path::PathBuf;
+
+use crate::calibrate;
+use crate::cast::ReplayExport;
+use crate::catalog::{ListFormat, ListSort};
+use crate::duel;
+use crate::generate;
+use crate::mutate::Mutation;
+use crate::report::{self, ReportFormat};
+use crate::state;
+use crate::unlock::UnlockPolicy;
+
+/// Subcommands in the order they are listed in the help text.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("play", "Launch the TUI (the default without a subcommand)"),
+    ("list", "List topics and challenges with your best grades"),
+    ("stats", "Print lifetime stats"),
+    ("export", "Write the save file as JSON"),
+    ("report", "Print an assignment or progress report"),
+    (
+        "reset",
+        "Clear the progress of a challenge, a topic or everything",
+    ),
+    ("state", "Maintain the save file"),
+    ("pack", "Install, list and remove challenge packs"),
+    ("author", "Tools for challenge authors"),
+    ("gen", "Generate a challenge from a template"),
+    ("from-file", "Restore a file of yours from a mutation"),
+    ("import-diff", "Make challenges of the hunks of a diff"),
+    ("replay", "Replay an attempt, or export it to share"),
+    ("versus", "Take turns at a challenge against friends"),
+    ("duel", "Race a friend online through a relay"),
+    ("config", "Show the config file"),
+    (
+        "rpc-serve",
+        "Serve challenges to the :Nvimkata neovim plugin",
+    ),
+    ("help", "Show help for a command"),
+];
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Play(PlayArgs),
+    List {
+        profile: Option<String>,
+        format: ListFormat,
+        sort: ListSort,
+    },
+    Stats {
+        profile: Option<String>,
+    },
+    Export {
+        profile: Option<String>,
+        /// Output file, stdout if unset.
+        output: Option<PathBuf>,
+    },
+    Report {
+        profile: Option<String>,
+        /// Period of the progress report in days.
+        since: Option<u32>,
+        format: Option<ReportFormat>,
+    },
+    Reset {
+        profile: Option<String>,
+        scope: ResetScope,
+    },
+    State(StateCommand),
+    Pack(PackCommand),
+    Author(AuthorCommand),
+    /// Write a challenge generated from a template, or list the templates
+    /// for `template: None`.
+    Gen {
+        template: Option<String>,
+        /// Seed of the generator, from the clock if unset.
+        seed: Option<u64>,
+        /// Output file, stdout if unset.
+        output: Option<PathBuf>,
+    },
+    /// Play a freestyle challenge restoring `file` from `mutation`, or write
+    /// it to `output`.
+    FromFile {
+        file: PathBuf,
+        mutation: Mutation,
+        /// Seed of the mutation, from the clock if unset.
+        seed: Option<u64>,
+        output: Option<PathBuf>,
+        /// Editor binary, instead of `nvim_bin` from the config.
+        nvim_bin: Option<String>,
+    },
+    /// Write freestyle challenges made of the hunks of `patch` to `dir`, or
+    /// one made of the whole patch.
+    ImportDiff {
+        patch: PathBuf,
+        whole: bool,
+        /// Directory to write to, the current one if unset.
+        dir: Option<PathBuf>,
+    },
+    /// Replay attempt `attempt` of a challenge from its key log, in the
+    /// terminal or into an asciinema cast.
+    Replay {
+        id: String,
+        /// 1-based, in the order of the history screen: fewest keystrokes first.
+        attempt: usize,
+        /// Write the replay to a file instead of playing it.
+        export: Option<ReplayExport>,
+        profile: Option<String>,
+        /// Neovim binary for the headless snapshots.
+        nvim_bin: Option<String>,
+    },
+    /// Play a challenge in turns, one run per player, and count the winner in
+    /// the versus ledger.
+    Versus {
+        id: String,
+        players: Vec<String>,
+        /// Editor binary, instead of `nvim_bin` from the config.
+        nvim_bin: Option<String>,
+    },
+    Duel(DuelCommand),
+    Config(ConfigCommand),
+    /// Answer requests of the neovim plugin on stdin and stdout.
+    RpcServe {
+        profile: Option<String>,
+    },
+    /// Help for a subcommand, or the overview for `None`.
+    Help(Option<&'static str>),
+    Version,
+}
+
+/// Options of the TUI. Unset values fall back to the config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct PlayArgs {
+    pub unlock_all: bool,
+    pub unlock_policy: Option<UnlockPolicy>,
+    pub profile: Option<String>,
+    /// Editor binary, instead of `nvim_bin` from the config.
+    pub nvim_bin: Option<String>,
+    /// Draw without colors, like the `mono` theme.
+    pub no_color: bool,
+    /// Print how loading the challenges went.
+    pub verbose: bool,
+    /// Fail runs past the grade F threshold, like `hardcore` in the config.
+    pub hardcore: bool,
+    /// Print how the challenge with this ID would be launched, instead of
+    /// starting the TUI.
+    pub dry_run: Option<String>,
+}
+
+/// What `nvimkata reset` clears.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResetScope {
+    /// All results, attempts, stats and activity.
+    All,
+    /// Every challenge of a topic, by name or ID.
+    Topic(String),
+    /// One challenge by ID.
+    Challenge(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateCommand {
+    /// Fold another save file into the profile's save.
+    Merge {
+        file: PathBuf,
+        profile: Option<String>,
+    },
+    /// Resolve git merge conflicts in the profile's save.
+    Doctor { profile: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackCommand {
+    /// Install a pack archive from a file or URL, or a pack of the registry by name.
+    Install { source: String },
+    /// Print the installed packs.
+    List,
+    /// Uninstall a pack by name.
+    Remove { name: String },
+    /// Print the registry packs matching a query, or all of them for `None`.
+    Search { query: Option<String> },
+    /// Install newer registry versions of the installed packs, or of one pack.
+    Update { name: Option<String>, check: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorCommand {
+    /// Load the curriculum and count the challenges per topic, and unless
+    /// `verify` is off, run every `perfect_moves` headlessly.
+    Check {
+        dir: Option<PathBuf>,
+        verify: bool,
+        /// Neovim binary for verifying.
+        nvim_bin: Option<String>,
+    },
+    /// Feed a key sequence to a challenge headlessly.
+    Solve {
+        id: String,
+        keys: String,
+        record: bool,
+        profile: Option<String>,
+        /// Neovim binary for the headless run.
+        nvim_bin: Option<String>,
+    },
+    /// Flag the challenges whose par the recorded attempts find too strict or
+    /// too lax, from the save files `saves`, or the profile's save without
+    /// any.
+    Calibrate {
+        dir: Option<PathBuf>,
+        saves: Vec<PathBuf>,
+        profile: Option<String>,
+        /// Fewest attempts a challenge needs to be judged.
+        min_attempts: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuelCommand {
+    /// Pair up the players joining each room, on `listen`.
+    Relay { listen: String },
+    /// Join `room` on the relay at `relay` and play its challenge.
+    Join {
+        relay: String,
+        room: String,
+        player: String,
+        /// The challenge, for the first player in the room.
+        challenge: Option<String>,
+        /// Editor binary, instead of `nvim_bin` from the config.
+        nvim_bin: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print the config file.
+    #[default]
+    Show,
+    /// Print the config file path.
+    Path,
+}
+
+/// Why a command failed, with the exit code to report it with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// Invalid command line, for the named subcommand if any. Exit code 2.
+    Usage {
+        command: Option<&'static str>,
+        message: String,
+    },
+    /// The command ran and failed. Exit code 1.
+    Failed(String),
+}
+
+impl CliError {
+    pub fn usage(command: Option<&'static str>, message: impl Into<String>) -> Self {
+        Self::Usage {
+            command,
+            message: message.into(),
+        }
+    }
+
+    pub fn failed(message: impl fmt::Display) -> Self {
+        Self::Failed(message.to_string())
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Usage { .. } => 2,
+            Self::Failed(_) => 1,
+        }
+    }
+
+    /// Lines to print on stderr: the error, and for usage errors where to find help.
+    pub fn report(&self) -> String {
+        match self {
+            Self::Usage { command, message } => {
+                let help = command.map_or_else(|| "help".to_string(), |c| format!("help {c}"));
+                format!("error: {message}\nrun 'nvimkata {help}' for usage.")
+            }
+            Self::Failed(message) => format!("error: {message}"),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usage { message, .. } | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl<E: std::error::Error> From<E> for CliError {
+    fn from(e: E) -> Self {
+        Self::failed(e)
+    }
+}
+
+/// Arguments of one subcommand, consumed front to back.
+struct Args<'a> {
+    command: &'static str,
+    iter: std::slice::Iter<'a, String>,
+}
+
+impl<'a> Args<'a> {
+    fn new(command: &'static str, args: &'a [String]) -> Self {
+        Self {
+            command,
+            iter: args.iter(),
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.iter.next().map(String::as_str)
+    }
+
+    fn value(&mut self, flag: &str) -> Result<&'a str, CliError> {
+        self.next()
+            .ok_or_else(|| self.error(format!("{flag} requires a value")))
+    }
+
+    fn profile(&mut self) -> Result<String, CliError> {
+        let name = self.value("--profile")?;
+        if state::is_valid_profile(name) {
+            Ok(name.to_string())
+        } else {
+            Err(self.error(format!(
+                "invalid profile name '{name}' (use letters, digits, '-' and '_')"
+            )))
+        }
+    }
+
+    fn seed(&mut self) -> Result<u64, CliError> {
+        let value = self.value("--seed")?;
+        value
+            .parse()
+            .map_err(|_| self.error(format!("invalid seed: {value}")))
+    }
+
+    fn error(&self, message: impl Into<String>) -> CliError {
+        CliError::usage(Some(self.command), message)
+    }
+
+    fn unexpected(&self, arg: &str) -> CliError {
+        if arg.starts_with('-') {
+            self.error(format!("unknown option: {arg}"))
+        } else {
+            self.error(format!("unexpected argument: {arg}"))
+        }
+    }
+}
+
+/// Parse the command line, without the program name. Bare options such as
+/// `nvimkata --unlock-all` launch the TUI like `nvimkata play`.
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    let Some(first) = args.first() else {
+        return Ok(Command::Play(PlayArgs::default()));
+    };
+    let rest = &args[1..];
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Help(None)),
+        "-V" | "--version" => Ok(Command::Version),
+        "help" => parse_help(rest),
+        "play" => parse_play(Args::new("play", rest)),
+        "list" => parse_list(Args::new("list", rest)),
+        "stats" => parse_profile(Args::new("stats", rest), |profile| Command::Stats {
+            profile,
+        }),
+        "export" => parse_export(Args::new("export", rest)),
+        "report" => parse_report(Args::new("report", rest)),
+        "reset" => parse_reset(Args::new("reset", rest)),
+        "state" => parse_state(rest),
+        "pack" => parse_pack(rest),
+        "author" => parse_author(rest),
+        // Kept from before `author` grouped the authoring tools
+        "solve" => parse_solve(Args::new("author", rest)),
+        "gen" => parse_gen(Args::new("gen", rest)),
+        "from-file" => parse_from_file(Args::new("from-file", rest)),
+        "import-diff" => parse_import_diff(Args::new("import-diff", rest)),
+        "replay" => parse_replay(Args::new("replay", rest)),
+        "versus" => parse_versus(Args::new("versus", rest)),
+        "duel" => parse_duel(rest),
+        "config" => parse_config(Args::new("config", rest)),
+        "rpc-serve" => parse_profile(Args::new("rpc-serve", rest), |profile| Command::RpcServe {
+            profile,
+        }),
+        arg if arg.starts_with('-') => parse_play(Args::new("play", args)),
+        other => Err(CliError::usage(None, format!("unknown command: {other}"))),
+    }
+}
+
+fn command_name(name: &str) -> Option<&'static str> {
+    COMMANDS.iter().map(|(c, _)| *c).find(|c| *c == name)
+}
+
+fn parse_help(args: &[String]) -> Result<Command, CliError> {
+    match args {
+        [] => Ok(Command::Help(None)),
+        [name] => command_name(name)
+            .map(|c| Command::Help(Some(c)))
+            .ok_or_else(|| CliError::usage(None, format!("unknown command: {name}"))),
+        [_, extra, ..] => Err(CliError::usage(
+            Some("help"),
+            format!("unexpected argument: {extra}"),
+        )),
+    }
+}
+
+fn parse_play(mut args: Args) -> Result<Command, CliError> {
+    let mut play = PlayArgs::default();
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--unlock-all" => play.unlock_all = true,
+            "--unlock-policy" => {
+                let policy = args.value(arg)?;
+                play.unlock_policy = Some(policy.parse().map_err(|e: String| args.error(e))?);
+            }
+            "--profile" => play.profile = Some(args.profile()?),
+            "--nvim-bin" => play.nvim_bin = Some(args.value(arg)?.to_string()),
+            "--no-color" => play.no_color = true,
+            "-v" | "--verbose" => play.verbose = true,
+            "--hardcore" => play.hardcore = true,
+            "--dry-run" => play.dry_run = Some(args.value(arg)?.to_string()),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::Play(play))
+}
+
+/// Parse a subcommand whose only option is `--profile`.
+fn parse_profile(
+    mut args: Args,
+    build: fn(Option<String>) -> Command,
+) -> Result<Command, CliError> {
+    let mut profile = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--profile" => profile = Some(args.profile()?),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(build(profile))
+}
+
+fn parse_list(mut args: Args) -> Result<Command, CliError> {
+    let mut profile = None;
+    let mut format = ListFormat::default();
+    let mut sort = ListSort::default();
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--profile" => profile = Some(args.profile()?),
+            "--format" => {
+                let name = args.value(arg)?;
+                format = name.parse().map_err(|e: String| args.error(e))?;
+            }
+            "--sort" => {
+                let name = args.value(arg)?;
+                sort = name.parse().map_err(|e: String| args.error(e))?;
+            }
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::List {
+        profile,
+        format,
+        sort,
+    })
+}
+
+fn parse_export(mut args: Args) -> Result<Command, CliError> {
+    let mut profile = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--profile" => profile = Some(args.profile()?),
+            "-o" | "--output" => output = Some(PathBuf::from(args.value(arg)?)),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::Export { profile, output })
+}
+
+fn parse_report(mut args: Args) -> Result<Command, CliError> {
+    let mut profile = None;
+    let mut since = None;
+    let mut format = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--profile" => profile = Some(args.profile()?),
+            "--since" => {
+                let period = args.value(arg)?;
+                since = Some(report::parse_period(period).map_err(|e| args.error(e))?);
+            }
+            "--format" => {
+                let name = args.value(arg)?;
+                format = Some(name.parse().map_err(|e: String| args.error(e))?);
+            }
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::Report {
+        profile,
+        since,
+        format,
+    })
+}
+
+fn parse_state(args: &[String]) -> Result<Command, CliError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(CliError::usage(Some("state"), "missing state command"));
+    };
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Help(Some("state"))),
+        "merge" => {
+            let mut args = Args::new("state", rest);
+            let mut file = None;
+            let mut profile = None;
+            while let Some(arg) = args.next() {
+                match arg {
+                    "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+                    "--profile" => profile = Some(args.profile()?),
+                    other if file.is_none() && !other.starts_with('-') => {
+                        file = Some(PathBuf::from(other));
+                    }
+                    other => return Err(args.unexpected(other)),
+                }
+            }
+            let file = file.ok_or_else(|| args.error("missing save file to merge"))?;
+            Ok(Command::State(StateCommand::Merge { file, profile }))
+        }
+        "doctor" => parse_profile(Args::new("state", rest), |profile| {
+            Command::State(StateCommand::Doctor { profile })
+        }),
+        other => Err(CliError::usage(
+            Some("state"),
+            format!("unknown state command: {other}"),
+        )),
+    }
+}
+
+fn parse_pack(args: &[String]) -> Result<Command, CliError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(CliError::usage(Some("pack"), "missing pack command"));
+    };
+    if matches!(first.as_str(), "-h" | "--help") {
+        return Ok(Command::Help(Some("pack")));
+    }
+    let mut args = Args::new("pack", rest);
+    let mut operand = None;
+    let mut check = false;
+    let takes_operand = !matches!(first.as_str(), "list" | "browse");
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--check" if first == "update" => check = true,
+            other if takes_operand && operand.is_none() && !other.starts_with('-') => {
+                operand = Some(other.to_string());
+            }
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let command = match first.as_str() {
+        "install" => PackCommand::Install {
+            source: operand.ok_or_else(|| args.error("missing pack file or URL"))?,
+        },
+        "list" => PackCommand::List,
+        "remove" => PackCommand::Remove {
+            name: operand.ok_or_else(|| args.error("missing pack name"))?,
+        },
+        "search" => PackCommand::Search {
+            query: Some(operand.ok_or_else(|| args.error("missing search query"))?),
+        },
+        "browse" => PackCommand::Search { query: None },
+        "update" => PackCommand::Update {
+            name: operand,
+            check,
+        },
+        other => return Err(args.error(format!("unknown pack command: {other}"))),
+    };
+    Ok(Command::Pack(command))
+}
+
+fn parse_author(args: &[String]) -> Result<Command, CliError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(CliError::usage(Some("author"), "missing author command"));
+    };
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Help(Some("author"))),
+        "solve" => parse_solve(Args::new("author", rest)),
+        "calibrate" => parse_calibrate(Args::new("author", rest)),
+        "check" => {
+            let mut args = Args::new("author", rest);
+            let mut dir = None;
+            let mut verify = true;
+            let mut nvim_bin = None;
+            while let Some(arg) = args.next() {
+                match arg {
+                    "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+                    "--no-verify" => verify = false,
+                    "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+                    other if dir.is_none() && !other.starts_with('-') => {
+                        dir = Some(PathBuf::from(other));
+                    }
+                    other => return Err(args.unexpected(other)),
+                }
+            }
+            Ok(Command::Author(AuthorCommand::Check {
+                dir,
+                verify,
+                nvim_bin,
+            }))
+        }
+        other => Err(CliError::usage(
+            Some("author"),
+            format!("unknown author command: {other}"),
+        )),
+    }
+}
+
+fn parse_solve(mut args: Args) -> Result<Command, CliError> {
+    let mut id = None;
+    let mut keys = None;
+    let mut record = false;
+    let mut profile = None;
+    let mut nvim_bin = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--keys" => keys = Some(args.value(arg)?.to_string()),
+            "--record" => record = true,
+            "--profile" => profile = Some(args.profile()?),
+            "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+            other if id.is_none() && !other.starts_with('-') => id = Some(other.to_string()),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let id = id.ok_or_else(|| args.error("missing challenge id"))?;
+    let keys = keys.ok_or_else(|| args.error("missing --keys"))?;
+    Ok(Command::Author(AuthorCommand::Solve {
+        id,
+        keys,
+        record,
+        profile,
+        nvim_bin,
+    }))
+}
+
+fn parse_calibrate(mut args: Args) -> Result<Command, CliError> {
+    let mut dir = None;
+    let mut saves = Vec::new();
+    let mut profile = None;
+    let mut min_attempts = calibrate::MIN_ATTEMPTS;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--save" => saves.push(PathBuf::from(args.value(arg)?)),
+            "--profile" => profile = Some(args.profile()?),
+            "--min-attempts" => {
+                let value = args.value(arg)?;
+                min_attempts = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| args.error(format!("invalid attempt count: {value}")))?;
+            }
+            other if dir.is_none() && !other.starts_with('-') => dir = Some(PathBuf::from(other)),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    if profile.is_some() && !saves.is_empty() {
+        return Err(args.error("--profile and --save can't be combined"));
+    }
+    Ok(Command::Author(AuthorCommand::Calibrate {
+        dir,
+        saves,
+        profile,
+        min_attempts,
+    }))
+}
+
+fn parse_gen(mut args: Args) -> Result<Command, CliError> {
+    let mut template = None;
+    let mut seed = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--template" => template = Some(args.value(arg)?.to_string()),
+            "--seed" => seed = Some(args.seed()?),
+            "-o" | "--output" => output = Some(PathBuf::from(args.value(arg)?)),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::Gen {
+        template,
+        seed,
+        output,
+    })
+}
+
+fn parse_from_file(mut args: Args) -> Result<Command, CliError> {
+    let mut file = None;
+    let mut mutation = None;
+    let mut seed = None;
+    let mut output = None;
+    let mut nvim_bin = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--mutate" => {
+                let name = args.value(arg)?;
+                mutation = Some(name.parse().map_err(|e: String| args.error(e))?);
+            }
+            "--seed" => seed = Some(args.seed()?),
+            "-o" | "--output" => output = Some(PathBuf::from(args.value(arg)?)),
+            "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+            other if file.is_none() && !other.starts_with('-') => file = Some(PathBuf::from(other)),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let file = file.ok_or_else(|| args.error("missing file to mutate"))?;
+    let mutation = mutation.ok_or_else(|| args.error("missing --mutate"))?;
+    Ok(Command::FromFile {
+        file,
+        mutation,
+        seed,
+        output,
+        nvim_bin,
+    })
+}
+
+fn parse_import_diff(mut args: Args) -> Result<Command, CliError> {
+    let mut patch = None;
+    let mut whole = false;
+    let mut dir = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--whole" => whole = true,
+            "-o" | "--output" => dir = Some(PathBuf::from(args.value(arg)?)),
+            other if patch.is_none() && !other.starts_with('-') => {
+                patch = Some(PathBuf::from(other));
+            }
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let patch = patch.ok_or_else(|| args.error("missing patch file"))?;
+    Ok(Command::ImportDiff { patch, whole, dir })
+}
+
+fn parse_replay(mut args: Args) -> Result<Command, CliError> {
+    let mut id = None;
+    let mut attempt = 1;
+    let mut export = None;
+    let mut profile = None;
+    let mut nvim_bin = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--attempt" => {
+                let value = args.value(arg)?;
+                attempt = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| args.error(format!("invalid attempt number: {value}")))?;
+            }
+            "--asciinema" | "--html" | "--text" => {
+                if export.is_some() {
+                    return Err(args.error("use only one of --asciinema, --html and --text"));
+                }
+                let path = PathBuf::from(args.value(arg)?);
+                export = Some(match arg {
+                    "--asciinema" => ReplayExport::Asciinema(path),
+                    "--html" => ReplayExport::Html(path),
+                    _ => ReplayExport::Text(path),
+                });
+            }
+            "--profile" => profile = Some(args.profile()?),
+            "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+            other if id.is_none() && !other.starts_with('-') => id = Some(other.to_string()),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let id = id.ok_or_else(|| args.error("missing challenge ID"))?;
+    Ok(Command::Replay {
+        id,
+        attempt,
+        export,
+        profile,
+        nvim_bin,
+    })
+}
+
+fn parse_versus(mut args: Args) -> Result<Command, CliError> {
+    let mut id = None;
+    let mut players: Vec<String> = Vec::new();
+    let mut nvim_bin = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--players" => {
+                players = args
+                    .value(arg)?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(ToString::to_string)
+                    .collect();
+            }
+            "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+            other if id.is_none() && !other.starts_with('-') => id = Some(other.to_string()),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    let id = id.ok_or_else(|| args.error("missing challenge ID"))?;
+    let distinct: std::collections::BTreeSet<&String> = players.iter().collect();
+    if players.len() < 2 || distinct.len() < players.len() {
+        return Err(args.error("--players needs two or more different names, e.g. ada,bob"));
+    }
+    Ok(Command::Versus {
+        id,
+        players,
+        nvim_bin,
+    })
+}
+
+fn parse_duel(args: &[String]) -> Result<Command, CliError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(CliError::usage(Some("duel"), "missing duel command"));
+    };
+    let mut args = Args::new("duel", rest);
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Help(Some("duel"))),
+        "relay" => {
+            let mut listen = format!("0.0.0.0:{}", duel::DEFAULT_PORT);
+            while let Some(arg) = args.next() {
+                match arg {
+                    "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+                    "--listen" => listen = duel::with_port(args.value(arg)?),
+                    other => return Err(args.unexpected(other)),
+                }
+            }
+            Ok(Command::Duel(DuelCommand::Relay { listen }))
+        }
+        "join" => {
+            let (mut relay, mut room, mut player) = (None, None, None);
+            let (mut challenge, mut nvim_bin) = (None, None);
+            while let Some(arg) = args.next() {
+                match arg {
+                    "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+                    "--room" => room = Some(args.value(arg)?.to_string()),
+                    "--player" => player = Some(args.value(arg)?.to_string()),
+                    "--challenge" => challenge = Some(args.value(arg)?.to_string()),
+                    "--nvim-bin" => nvim_bin = Some(args.value(arg)?.to_string()),
+                    other if relay.is_none() && !other.starts_with('-') => {
+                        relay = Some(duel::with_port(other));
+                    }
+                    other => return Err(args.unexpected(other)),
+                }
+            }
+            Ok(Command::Duel(DuelCommand::Join {
+                relay: relay.ok_or_else(|| args.error("missing relay address"))?,
+                room: room.ok_or_else(|| args.error("missing --room"))?,
+                player: player.ok_or_else(|| args.error("missing --player"))?,
+                challenge,
+                nvim_bin,
+            }))
+        }
+        other => Err(CliError::usage(
+            Some("duel"),
+            format!("unknown duel command: {other}"),
+        )),
+    }
+}
+
+fn parse_reset(mut args: Args) -> Result<Command, CliError> {
+    let mut profile = None;
+    let mut scope = None;
+    while let Some(arg) = args.next() {
+        let next = match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "--profile" => {
+                profile = Some(args.profile()?);
+                continue;
+            }
+            "--all" => ResetScope::All,
+            "--topic" => ResetScope::Topic(args.value(arg)?.to_string()),
+            "--challenge" => ResetScope::Challenge(args.value(arg)?.to_string()),
+            other => return Err(args.unexpected(other)),
+        };
+        if scope.replace(next).is_some() {
+            return Err(args.error("give only one of --all, --topic and --challenge"));
+        }
+    }
+    let scope =
+        scope.ok_or_else(|| args.error("one of --all, --topic or --challenge is required"))?;
+    Ok(Command::Reset { profile, scope })
+}
+
+fn parse_config(mut args: Args) -> Result<Command, CliError> {
+    let mut command = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help(Some(args.command))),
+            "show" if command.is_none() => command = Some(ConfigCommand::Show),
+            "path" if command.is_none() => command = Some(ConfigCommand::Path),
+            other => return Err(args.unexpected(other)),
+        }
+    }
+    Ok(Command::Config(command.unwrap_or_default()))
+}
+
+/// Help text for a subcommand, or the overview for `None`.
+pub fn help(command: Option<&str>) -> String {
+    match command {
+        None => overview(),
+        Some("play") => "Usage: nvimkata [play] [OPTIONS]\n\n\
+             Launch the TUI.\n\n\
+             Options:\n\
+             \x20 --unlock-all       Unlock all categories (skip progression)\n\
+             \x20 --unlock-policy <POLICY>\n\
+             \x20                    When categories unlock: all, 80%, C (minimum grade) or off\n\
+             \x20 --profile <NAME>   Use a separate save profile\n\
+             \x20 --nvim-bin <PATH>  Editor binary, instead of nvim_bin from the config\n\
+             \x20 --no-color         Draw without colors (also when NO_COLOR is set)\n\
+             \x20 -v, --verbose      Print how many challenges were loaded, from the cache\n\
+             \x20                    or parsed, and how long it took\n\
+             \x20 --hardcore         End runs as failed once their keys pass the grade F\n\
+             \x20                    threshold (also hardcore = true in the config)\n\
+             \x20 --dry-run <ID>     Print the editor command, session files, runtime script\n\
+             \x20                    and environment a challenge would be launched with,\n\
+             \x20                    without launching it\n"
+            .to_string(),
+        Some("list") => {
+            "Usage: nvimkata list [--format <FORMAT>] [--sort <ORDER>] [--profile <NAME>]\n\n\
+             List every topic and challenge with its difficulty, par, tags and your\n\
+             best grade.\n\n\
+             Options:\n\
+             \x20 --format <FORMAT>  table, json or tsv (default: table)\n\
+             \x20 --sort <ORDER>     topic, or time for a speedrun ranking of every\n\
+             \x20                    challenge by best time (default: topic)\n"
+                .to_string()
+        }
+        Some("stats") => "Usage: nvimkata stats [--profile <NAME>]\n\n\
+             Print completion, grades, keystrokes, practice time, streaks and most\n\
+             used commands.\n"
+            .to_string(),
+        Some("export") => "Usage: nvimkata export [--output <FILE>] [--profile <NAME>]\n\n\
+             Write the save file as JSON, to stdout unless --output is given.\n"
+            .to_string(),
+        Some("report") => {
+            "Usage: nvimkata report [--since <PERIOD>] [--format <FORMAT>] [--profile <NAME>]\n\n\
+             Print the progress of the assignments in ~/.config/nvimkata/assignments/\n\
+             as a completion report to send back. With --since or --format it prints\n\
+             a progress report of your own practice instead.\n\n\
+             Options:\n\
+             \x20 --since <PERIOD>   Period to cover, e.g. 7d or 2w (default: 7d)\n\
+             \x20 --format <FORMAT>  markdown or html (default: markdown)\n"
+                .to_string()
+        }
+        Some(name @ ("author" | "gen" | "from-file" | "import-diff")) => authoring_help(name),
+        Some(name @ ("versus" | "duel")) => match_help(name),
+        Some("replay") => replay_help(),
+        Some("reset") => "Usage: nvimkata reset (--all | --topic <TOPIC> | --challenge <ID>) \
+             [--profile <NAME>]\n\n\
+             Delete best results and stored attempts. Notes, ratings and bookmarks\n\
+             are kept.\n\n\
+             Options:\n\
+             \x20 --all               Everything, including lifetime stats and activity\n\
+             \x20 --topic <TOPIC>     Every challenge of a topic, by name or ID\n\
+             \x20 --challenge <ID>    One challenge\n"
+            .to_string(),
+        Some("state") => "Usage: nvimkata state merge <FILE> [--profile <NAME>]\n\
+             \x20      nvimkata state doctor [--profile <NAME>]\n\n\
+             merge   Fold another save file, e.g. from a second machine, into yours:\n\
+             \x20       the better best result of each challenge wins, attempt histories\n\
+             \x20       are joined, and stats and activity are added up\n\
+             doctor  Resolve the git merge-conflict markers in your save by merging\n\
+             \x20       both sides, for saves kept in git with state_dir in the config\n"
+            .to_string(),
+        Some("pack") => "Usage: nvimkata pack install <FILE|URL|NAME>\n\
+             \x20      nvimkata pack list\n\
+             \x20      nvimkata pack remove <NAME>\n\
+             \x20      nvimkata pack search <QUERY>\n\
+             \x20      nvimkata pack browse\n\
+             \x20      nvimkata pack update [NAME] [--check]\n\n\
+             install  Install a pack archive, replacing an installed pack of the same\n\
+             \x20        name. An archive is a tar or zip file of challenge TOML files\n\
+             \x20        and a pack.toml with the name, author and version of the pack;\n\
+             \x20        a NAME is downloaded from the registry and its checksum verified\n\
+             list     Print the installed packs\n\
+             remove   Uninstall a pack\n\
+             search   Print the registry packs whose name, author or description\n\
+             \x20        contains QUERY\n\
+             browse   Print every pack in the registry\n\
+             update   Install newer registry versions of the installed packs;\n\
+             \x20        --check only lists them\n\n\
+             Installed packs are shown as extra topics in the hub. The registry is\n\
+             the JSON index at registry_url in the config file.\n"
+            .to_string(),
+        Some("config") => "Usage: nvimkata config [show|path]\n\n\
+             show  Print the config file (default)\n\
+             path  Print the path of the config file\n"
+            .to_string(),
+        Some("rpc-serve") => "Usage: nvimkata rpc-serve [--profile <NAME>]\n\n\
+             Started by the neovim plugin in plugin/nvimkata.lua, which plays challenges\n\
+             with :Nvimkata <ID> in the running neovim. Requests and replies are JSON\n\
+             objects, one per line on stdin and stdout.\n"
+            .to_string(),
+        Some(_) => "Usage: nvimkata help [COMMAND]\n".to_string(),
+    }
+}
+
+/// Help text of `replay`, with its export formats.
+fn replay_help() -> String {
+    "Usage: nvimkata replay <ID> [--attempt <N>] [--profile <NAME>]\n\
+         \x20                      [--asciinema <FILE> | --html <FILE> | --text <FILE>]\n\
+         \x20                      [--nvim-bin <PATH>]\n\n\
+         Replay a recorded attempt from its key log, keystroke by keystroke, in\n\
+         headless Neovim. Attempts are numbered as in the history screen, the\n\
+         fewest keystrokes first; 1 by default. Instead of playing it in the\n\
+         terminal, write it to FILE to share:\n\n\
+         --asciinema  an asciicast of every keystroke, to play with asciinema\n\
+         --html       a self-contained page showing the buffer after each\n\
+         \x20            command in turn, to embed in a blog post\n\
+         --text       the buffer after each command as plain text, for a code\n\
+         \x20            block\n"
+        .to_string()
+}
+
+/// Help texts of the commands for playing against friends: `versus` and
+/// `duel`.
+fn match_help(command: &str) -> String {
+    match command {
+        "duel" => "Usage: nvimkata duel join <RELAY> --room <NAME> --player <NAME>\n\
+             \x20                        [--challenge <ID>] [--nvim-bin <PATH>]\n\
+             \x20      nvimkata duel relay [--listen <ADDR>]\n\n\
+             join   Join a room on the relay at RELAY (host[:port], port 7373 by\n\
+             \x20      default) and wait for an opponent. The first player in the room\n\
+             \x20      picks the challenge with --challenge. Both start together; the\n\
+             \x20      opponent's result shows in the bar as soon as it is in\n\
+             relay  Pair up the players joining each room (0.0.0.0:7373 by default)\n\n\
+             Duels need a build with the net feature.\n"
+            .to_string(),
+        _ => "Usage: nvimkata versus <ID> --players <NAMES> [--nvim-bin <PATH>]\n\n\
+             Take turns at a challenge, e.g. --players ada,bob. Each player gets one\n\
+             run; the fewest keystrokes win, then the faster time. Runs are not\n\
+             recorded in your save; the wins of each group of players add up in\n\
+             versus.json in the data directory.\n"
+            .to_string(),
+    }
+}
+
+/// Help texts of the commands for making challenges: `author`, `gen`,
+/// `from-file` and `import-diff`.
+fn authoring_help(command: &str) -> String {
+    match command {
+        "author" => "Usage: nvimkata author check [DIR] [--no-verify] [--nvim-bin <PATH>]\n\
+             \x20      nvimkata author solve <ID> --keys <KEYS> [--record] [--profile <NAME>]\n\
+             \x20                            [--nvim-bin <PATH>]\n\
+             \x20      nvimkata author calibrate [DIR] [--save <FILE>]... [--profile <NAME>]\n\
+             \x20                                [--min-attempts <N>]\n\n\
+             check  Load the challenges directory and count the challenges per topic;\n\
+             \x20      problems are printed as warnings. Every perfect_moves is run in\n\
+             \x20      headless Neovim and must match its target; --no-verify skips\n\
+             \x20      this, so Neovim isn't needed\n\
+             solve  Run a key sequence headlessly, e.g. \"ciwfoo<Esc>\", and exit with 1\n\
+             \x20      unless it matches the target; --record saves a matching run,\n\
+             \x20      without a time as headless runs aren't timed\n\
+             calibrate\n\
+             \x20      Flag the challenges whose par the recorded attempts find too strict\n\
+             \x20      (the median attempt grades D or worse) or too lax (it grades A, or\n\
+             \x20      an attempt beats par), and propose a par and thresholds on which\n\
+             \x20      the median attempt grades C. --save reads a save file, such as one\n\
+             \x20      written by export, and can be repeated to pool several players;\n\
+             \x20      without it, your own save is read. Challenges with fewer than\n\
+             \x20      --min-attempts attempts (default: 5) are skipped\n"
+            .to_string(),
+        "gen" => gen_help(),
+        "from-file" => from_file_help(),
+        _ => "Usage: nvimkata import-diff <PATCH> [--whole] [--output <DIR>]\n\n\
+             Turn each hunk of a unified diff, like git diff writes, into a freestyle\n\
+             challenge from its old lines to its new ones, written as DIR/<ID>.toml\n\
+             (default: the current directory). --whole makes one challenge of every\n\
+             hunk instead. Put the files in a topic directory of your challenges to\n\
+             play them.\n"
+            .to_string(),
+    }
+}
+
+/// Help text of `gen`, with the templates to pick from.
+fn gen_help() -> String {
+    let mut out = "Usage: nvimkata gen [--template <NAME>] [--seed <N>] [--output <FILE>]\n\n\
+         Write a challenge generated from a template, to stdout unless --output is\n\
+         given. The same template and seed always give the same challenge; without\n\
+         --seed one is picked from the clock. Put the file in a topic directory of\n\
+         your challenges to play it. Without --template the templates are listed.\n\n\
+         Templates:\n"
+        .to_string();
+    for template in generate::TEMPLATES {
+        let _ = writeln!(out, "  {:<14} {}", template.name, template.description);
+    }
+    out
+}
+
+/// Help text of `from-file`, with the mutations to pick from.
+fn from_file_help() -> String {
+    let mut out = "Usage: nvimkata from-file <FILE> --mutate <MUTATION> [--seed <N>]\n\
+         \x20                         [--output <FILE>] [--nvim-bin <PATH>]\n\n\
+         Mutate a copy of FILE and play restoring the original as a freestyle\n\
+         challenge, which is not recorded. With --output the challenge file is\n\
+         written instead. The file itself is never changed.\n\n\
+         Mutations:\n"
+        .to_string();
+    for mutation in Mutation::ALL {
+        let _ = writeln!(out, "  {:<18} {}", mutation.name(), mutation.description());
+    }
+    out
+}
+
+/// The help overview: every subcommand with a line about it.
+fn overview() -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let mut out = format!(
+        "nvimkata {version} — practice efficient editing in Neovim\n\n\
+         Usage: nvimkata [COMMAND] [OPTIONS]\n\nCommands:\n"
+    );
+    for (name, description) in COMMANDS {
+        let _ = writeln!(out, "  {name:<10} {description}");
+    }
+    out.push_str(
+        "\nWithout a command, options are passed to play.\n\
+         Settings are read from ~/.config/nvimkata/config.toml.\n\
+         Run 'nvimkata help <COMMAND>' for the options of a command.\n",
+    );
+    out
+}
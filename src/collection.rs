@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::challenge::{Challenge, Topic};
+use crate::state::GameState;
+
+/// Topic ID of the favorites collection. IDs from 200 up are reserved for
+/// collections, which gather challenges from other topics.
+pub const FAVORITES_ID: u8 = 200;
+
+/// Topic ID of the first playlist; the others follow in file name order.
+pub const FIRST_PLAYLIST_ID: u8 = FAVORITES_ID + 1;
+
+/// Bookmarked challenges as a pseudo-topic, in the order they were bookmarked.
+/// Bookmarks of challenges that no longer exist are skipped.
+pub fn favorites(topics: &[Topic], state: &GameState) -> Topic {
+    Topic {
+        id: FAVORITES_ID,
+        name: "Favorites".to_string(),
+        description: "Bookmarked challenges from all topics".to_string(),
+        intro: None,
+        challenges: find_all(topics, &state.favorites),
+    }
+}
+
+/// A user-defined playlist file.
+#[derive(Debug, Deserialize)]
+struct PlaylistFile {
+    /// Display name, the file name without extension by default.
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: String,
+    /// Challenge IDs, played in this order.
+    challenges: Vec<String>,
+}
+
+/// Load every `.toml` playlist in `dir` as a pseudo-topic, in file name order.
+/// Unknown challenge IDs, malformed files and files past the last free topic ID
+/// are reported and skipped; a missing directory yields no playlists.
+pub fn load_playlists(dir: &Path, topics: &[Topic]) -> Vec<Topic> {
+    let mut playlists = Vec::new();
+    let mut ids = FIRST_PLAYLIST_ID..=u8::MAX;
+    for path in toml_files(dir) {
+        let Some(playlist) = load_playlist(&path, topics) else {
+            continue;
+        };
+        let Some(id) = ids.next() else {
+            eprintln!("Warning: too many playlists, ignoring {}", path.display());
+            continue;
+        };
+        playlists.push(Topic { id, ..playlist });
+    }
+    playlists
+}
+
+fn load_playlist(path: &Path, topics: &[Topic]) -> Option<Topic> {
+    let content = fs::read_to_string(path).ok()?;
+    let file: PlaylistFile = match toml::from_str(&content) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Warning: failed to parse {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let challenges = resolve(topics, &file.challenges, path);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+    Some(Topic {
+        id: FIRST_PLAYLIST_ID,
+        name: file.name.or(stem).unwrap_or_default(),
+        description: file.description,
+        intro: None,
+        challenges,
+    })
+}
+
+/// `.toml` files in `dir`, sorted by name. A missing directory yields none.
+pub fn toml_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Challenges listed in a collection file, in order. Unknown IDs are reported
+/// and skipped.
+pub fn resolve(topics: &[Topic], ids: &[String], source: &Path) -> Vec<Challenge> {
+    let challenges = find_all(topics, ids);
+    for id in ids {
+        if !challenges.iter().any(|c| &c.id == id) {
+            eprintln!(
+                "Warning: ignoring unknown challenge '{id}' in {}",
+                source.display()
+            );
+        }
+    }
+    challenges
+}
+
+/// Challenges with the given IDs, in that order.
+fn find_all(topics: &[Topic], ids: &[String]) -> Vec<Challenge> {
+    find(topics, ids).into_iter().cloned().collect()
+}
+
+/// The challenges with the given IDs, in that order. Unknown IDs are skipped.
+pub fn find<'a>(topics: &'a [Topic], ids: &[String]) -> Vec<&'a Challenge> {
+    ids.iter()
+        .filter_map(|id| {
+            topics
+                .iter()
+                .flat_map(|t| t.challenges.iter())
+                .find(|c| &c.id == id)
+        })
+        .collect()
+}
@@ -0,0 +1,456 @@
+use std::ops::Range;
+
+use ratatui::style::Color;
+
+use crate::notation::{self, Key};
+
+/// What a command does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandKind {
+    Motion,
+    /// An operator with its motion or text object, e.g. `ciw` or `d3w`.
+    Operator,
+    /// Insert mode, including the typed text up to `<Esc>`.
+    Insert,
+    /// Single-key edits such as `x`, `p`, `u` or `r{char}`.
+    Edit,
+    Visual,
+    Search,
+    Ex,
+    Macro,
+    Other,
+}
+
+impl CommandKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Motion => "motion",
+            Self::Operator => "operator",
+            Self::Insert => "insert",
+            Self::Edit => "edit",
+            Self::Visual => "visual",
+            Self::Search => "search",
+            Self::Ex => "ex",
+            Self::Macro => "macro",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Motion => Color::Cyan,
+            Self::Operator => Color::Yellow,
+            Self::Insert => Color::Green,
+            Self::Edit => Color::Magenta,
+            Self::Visual => Color::Blue,
+            Self::Search | Self::Ex => Color::LightRed,
+            Self::Macro => Color::LightMagenta,
+            Self::Other => Color::Gray,
+        }
+    }
+}
+
+/// One normal-mode command of a key log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// All keys of the command, including count, register and typed text.
+    pub keys: Vec<Key>,
+    /// The command without count, register or arguments, for aggregation:
+    /// `f` for `3fw`, `ciw` for `"aciwfoo<Esc>`, `:s` for `:%s/a/b/<CR>`.
+    pub name: String,
+    pub kind: CommandKind,
+}
+
+impl Command {
+    /// The command's keys in canonical notation.
+    pub fn notation(&self) -> String {
+        notation::format(&self.keys)
+    }
+
+    /// The register the command names, e.g. `"a` for `2"ayy`.
+    pub fn register(&self) -> Option<String> {
+        let mut keys = self
+            .keys
+            .iter()
+            .skip_while(|key| matches!(key, Key::Char('0'..='9')));
+        match (keys.next(), keys.next()) {
+            (Some(Key::Char('"')), Some(Key::Char(c))) => Some(format!("\"{c}")),
+            _ => None,
+        }
+    }
+}
+
+/// Segment a key log into commands. Parsing follows normal mode, tracking
+/// visual mode and macro recording; keys that make no sense there become
+/// single-key `Other` commands.
+pub fn classify(keys: &[Key]) -> Vec<Command> {
+    Parser::new(keys).commands()
+}
+
+/// Stands in for each character typed in insert mode in a scrubbed key log.
+pub const TYPED_PLACEHOLDER: char = '*';
+
+/// The key log with every character typed in insert mode replaced by
+/// [`TYPED_PLACEHOLDER`], so it keeps its keystrokes but not the text. Keys
+/// such as `<BS>` or `<Enter>` in the text are kept.
+pub fn scrub_typed_text(keys: &[Key]) -> Vec<Key> {
+    keys.iter()
+        .zip(typed_text(keys))
+        .map(|(key, text)| {
+            if text {
+                Key::Char(TYPED_PLACEHOLDER)
+            } else {
+                key.clone()
+            }
+        })
+        .collect()
+}
+
+/// For each key of the log, whether it is a character typed in insert mode.
+pub fn typed_text(keys: &[Key]) -> Vec<bool> {
+    let mut parser = Parser::new(keys);
+    parser.commands();
+    let mut text = vec![false; keys.len()];
+    for range in parser.typed {
+        for i in range {
+            text[i] = matches!(keys[i], Key::Char(_));
+        }
+    }
+    text
+}
+
+/// Whether `seq` was typed in the key log as keys, not as insert-mode text:
+/// its keys must match whole keys of the log, none of them typed text.
+pub fn typed_as_keys(keys: &[Key], text: &[bool], seq: &[Key]) -> bool {
+    !seq.is_empty()
+        && keys
+            .windows(seq.len())
+            .zip(text.windows(seq.len()))
+            .any(|(window, text)| window == seq && !text.contains(&true))
+}
+
+/// Motion keys that take no argument.
+const MOTIONS: &str = "hjklwWbBeE0^$G{}()%HMLnN*#;,|+-_ ";
+
+struct Parser<'a> {
+    keys: &'a [Key],
+    pos: usize,
+    visual: bool,
+    recording: bool,
+    /// Keys typed in insert mode, without the closing `<Esc>`.
+    typed: Vec<Range<usize>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(keys: &'a [Key]) -> Self {
+        Self {
+            keys,
+            pos: 0,
+            visual: false,
+            recording: false,
+            typed: Vec::new(),
+        }
+    }
+
+    fn commands(&mut self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        while self.pos < self.keys.len() {
+            commands.push(self.command());
+        }
+        commands
+    }
+
+    fn peek(&self) -> Option<&Key> {
+        self.keys.get(self.pos)
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        match self.peek() {
+            Some(Key::Char(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn advance(&mut self) {
+        self.pos = (self.pos + 1).min(self.keys.len());
+    }
+
+    /// Take the next key as a character argument, such as the target of `f`.
+    fn arg(&mut self) -> String {
+        let arg = self.peek().map(ToString::to_string).unwrap_or_default();
+        self.advance();
+        arg
+    }
+
+    fn command(&mut self) -> Command {
+        let start = self.pos;
+        self.skip_count();
+        if self.peek_char() == Some('"') {
+            self.advance();
+            self.advance();
+            self.skip_count();
+        }
+        let (name, kind) = self.body();
+        Command {
+            keys: self.keys[start..self.pos].to_vec(),
+            name,
+            kind,
+        }
+    }
+
+    fn skip_count(&mut self) {
+        if matches!(self.peek_char(), Some('1'..='9')) {
+            while matches!(self.peek_char(), Some('0'..='9')) {
+                self.advance();
+            }
+        }
+    }
+
+    fn body(&mut self) -> (String, CommandKind) {
+        if let Some(name) = self.motion() {
+            let kind = if name == "/" || name == "?" {
+                CommandKind::Search
+            } else {
+                CommandKind::Motion
+            };
+            return (name, kind);
+        }
+        let Some(key) = self.peek().cloned() else {
+            // A trailing count or register
+            return (String::new(), CommandKind::Other);
+        };
+        self.advance();
+        match key {
+            Key::Char(c) if self.visual => self.visual_char(c),
+            Key::Char(c) => self.normal_char(c),
+            Key::Ctrl(c) => self.ctrl(c),
+            Key::Special(name) if name == "Esc" && self.visual => {
+                self.visual = false;
+                ("<Esc>".to_string(), CommandKind::Visual)
+            }
+            Key::Special(name) if name == "Del" => ("<Del>".to_string(), CommandKind::Edit),
+            other @ Key::Special(_) => (other.to_string(), CommandKind::Other),
+        }
+    }
+
+    /// Parse a motion at the current position, or leave it untouched.
+    fn motion(&mut self) -> Option<String> {
+        let key = self.peek()?.clone();
+        let name = match key {
+            Key::Char(c) if MOTIONS.contains(c) => {
+                self.advance();
+                c.to_string()
+            }
+            Key::Char(c @ ('f' | 'F' | 't' | 'T' | '\'' | '`' | '[' | ']')) => {
+                self.advance();
+                self.arg();
+                c.to_string()
+            }
+            Key::Char(c @ ('/' | '?')) => {
+                self.advance();
+                self.until_enter();
+                c.to_string()
+            }
+            Key::Char('g')
+                if matches!(
+                    self.keys.get(self.pos + 1),
+                    Some(Key::Char(
+                        'g' | 'e' | 'E' | '_' | 'j' | 'k' | '0' | '$' | 'm' | 'o'
+                    ))
+                ) =>
+            {
+                self.advance();
+                format!("g{}", self.arg())
+            }
+            Key::Ctrl('d' | 'u' | 'f' | 'b' | 'e' | 'y' | 'o' | 'i') => {
+                self.advance();
+                key.to_string()
+            }
+            Key::Special(ref name)
+                if matches!(
+                    name.as_str(),
+                    "Up" | "Down" | "Left" | "Right" | "Home" | "End" | "Enter" | "BS"
+                ) =>
+            {
+                self.advance();
+                key.to_string()
+            }
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    fn normal_char(&mut self, c: char) -> (String, CommandKind) {
+        match c {
+            'd' | 'c' | 'y' | '<' | '>' | '=' | '!' => self.operator(&c.to_string()),
+            'i' | 'a' | 'I' | 'A' | 'o' | 'O' | 's' | 'S' | 'C' | 'R' => self.insert(c.to_string()),
+            'x' | 'X' | 'p' | 'P' | 'u' | 'U' | 'J' | '~' | '.' | 'D' | 'Y' | '&' => {
+                (c.to_string(), CommandKind::Edit)
+            }
+            'r' => {
+                self.arg();
+                ("r".to_string(), CommandKind::Edit)
+            }
+            'v' | 'V' => {
+                self.visual = true;
+                (c.to_string(), CommandKind::Visual)
+            }
+            ':' => self.ex(),
+            'q' if self.recording => {
+                self.recording = false;
+                ("q".to_string(), CommandKind::Macro)
+            }
+            'q' => {
+                self.recording = true;
+                self.arg();
+                ("q".to_string(), CommandKind::Macro)
+            }
+            '@' => {
+                self.arg();
+                ("@".to_string(), CommandKind::Macro)
+            }
+            'm' | 'z' | 'Z' => (format!("{c}{}", self.arg()), CommandKind::Other),
+            'g' => self.g_command(),
+            _ => (c.to_string(), CommandKind::Other),
+        }
+    }
+
+    /// Keys in visual mode. Operators act on the selection and leave visual mode.
+    fn visual_char(&mut self, c: char) -> (String, CommandKind) {
+        match c {
+            'i' | 'a' => (format!("{c}{}", self.arg()), CommandKind::Motion),
+            'o' | 'O' => (c.to_string(), CommandKind::Motion),
+            'v' | 'V' => {
+                self.visual = false;
+                (c.to_string(), CommandKind::Visual)
+            }
+            'c' | 's' | 'C' | 'S' | 'R' | 'I' | 'A' => {
+                self.visual = false;
+                self.insert(c.to_string())
+            }
+            'd' | 'y' | 'x' | 'X' | 'D' | 'Y' | '<' | '>' | '=' | '~' | 'u' | 'U' | 'J' | 'p'
+            | 'P' | '!' => {
+                self.visual = false;
+                (c.to_string(), CommandKind::Operator)
+            }
+            'r' => {
+                self.visual = false;
+                self.arg();
+                ("r".to_string(), CommandKind::Operator)
+            }
+            ':' => {
+                self.visual = false;
+                self.ex()
+            }
+            _ => self.normal_char(c),
+        }
+    }
+
+    /// An operator followed by a doubled operator, a text object or a motion.
+    /// `c` continues into insert mode.
+    fn operator(&mut self, op: &str) -> (String, CommandKind) {
+        self.skip_count();
+        let last = op.chars().last();
+        let name = match self.peek_char() {
+            Some(c) if Some(c) == last => {
+                self.advance();
+                format!("{op}{c}")
+            }
+            Some(c @ ('i' | 'a')) => {
+                self.advance();
+                format!("{op}{c}{}", self.arg())
+            }
+            _ => match self.motion() {
+                Some(motion) => format!("{op}{motion}"),
+                None => op.to_string(),
+            },
+        };
+        if op == "c" {
+            self.insert_text();
+        }
+        (name, CommandKind::Operator)
+    }
+
+    fn insert(&mut self, name: String) -> (String, CommandKind) {
+        self.insert_text();
+        (name, CommandKind::Insert)
+    }
+
+    /// Skip typed text up to and including `<Esc>`.
+    fn insert_text(&mut self) {
+        let start = self.pos;
+        while let Some(key) = self.peek() {
+            if *key == Key::Special("Esc".to_string()) {
+                self.typed.push(start..self.pos);
+                self.advance();
+                return;
+            }
+            self.advance();
+        }
+        self.typed.push(start..self.pos);
+    }
+
+    /// Skip a command line up to and including `<Enter>` or `<Esc>`.
+    fn until_enter(&mut self) -> Vec<Key> {
+        let start = self.pos;
+        while let Some(key) = self.peek() {
+            let done = matches!(key, Key::Special(name) if name == "Enter" || name == "Esc");
+            self.advance();
+            if done {
+                return self.keys[start..self.pos - 1].to_vec();
+            }
+        }
+        self.keys[start..].to_vec()
+    }
+
+    /// An Ex command, named by its command word without the range, e.g. `:s`.
+    fn ex(&mut self) -> (String, CommandKind) {
+        let line: String = self
+            .until_enter()
+            .iter()
+            .filter_map(|key| match key {
+                Key::Char(c) => Some(*c),
+                _ => None,
+            })
+            .collect();
+        let word: String = line
+            .trim_start_matches(|c: char| "%$.,;'<>0123456789 ".contains(c))
+            .chars()
+            .take_while(char::is_ascii_alphabetic)
+            .collect();
+        (format!(":{word}"), CommandKind::Ex)
+    }
+
+    fn g_command(&mut self) -> (String, CommandKind) {
+        let Some(c) = self.peek_char() else {
+            let arg = self.arg();
+            return (format!("g{arg}"), CommandKind::Other);
+        };
+        self.advance();
+        let name = format!("g{c}");
+        match c {
+            'u' | 'U' | '~' | 'q' | 'w' | '?' => self.operator(&name),
+            'v' => {
+                self.visual = true;
+                (name, CommandKind::Visual)
+            }
+            'i' | 'I' => self.insert(name),
+            'J' | 'p' | 'P' | '&' => (name, CommandKind::Edit),
+            '*' | '#' => (name, CommandKind::Motion),
+            _ => (name, CommandKind::Other),
+        }
+    }
+
+    fn ctrl(&mut self, c: char) -> (String, CommandKind) {
+        let name = Key::Ctrl(c).to_string();
+        match c {
+            'r' | 'a' | 'x' => (name, CommandKind::Edit),
+            'v' => {
+                self.visual = !self.visual;
+                (name, CommandKind::Visual)
+            }
+            'w' => (format!("{name}{}", self.arg()), CommandKind::Other),
+            _ => (name, CommandKind::Other),
+        }
+    }
+}
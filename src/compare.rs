@@ -0,0 +1,76 @@
+use crate::commands::{self, Command};
+use crate::diff::{self, DiffOp};
+use crate::notation;
+
+/// A stretch of a run lined up with the perfect moves: commands both used, or
+/// where they went different ways.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Segment {
+    pub mine: Vec<Command>,
+    pub perfect: Vec<Command>,
+}
+
+impl Segment {
+    /// Whether the run did what the perfect moves do here.
+    pub fn matches(&self) -> bool {
+        self.mine == self.perfect
+    }
+}
+
+/// A key log lined up with the perfect moves, command by command. Commands
+/// both used are a segment each; the commands between them are one segment
+/// per stretch, such as `dw dw dw dw` where the perfect moves have `daw`.
+pub fn compare(keys: &str, perfect_moves: &[String]) -> Vec<Segment> {
+    let mine = commands::classify(&notation::tokenize(keys));
+    let perfect = commands::classify(&notation::tokenize(&perfect_moves.concat()));
+    let mut segments = Vec::new();
+    let mut open: Option<Segment> = None;
+    for op in diff::diff(&mine, &perfect) {
+        match op {
+            DiffOp::Equal(i, _) => {
+                segments.extend(open.take());
+                segments.push(Segment {
+                    mine: vec![mine[i].clone()],
+                    perfect: vec![mine[i].clone()],
+                });
+            }
+            DiffOp::Delete(i) => open
+                .get_or_insert_with(Segment::default)
+                .mine
+                .push(mine[i].clone()),
+            DiffOp::Insert(j) => open
+                .get_or_insert_with(Segment::default)
+                .perfect
+                .push(perfect[j].clone()),
+        }
+    }
+    segments.extend(open);
+    segments
+}
+
+/// Commands in notation, with runs of the same command counted: `4x dw` for
+/// `dw` four times in a row. `-` when there are none.
+pub fn summary(commands: &[Command]) -> String {
+    if commands.is_empty() {
+        return "-".to_string();
+    }
+    let mut parts: Vec<(String, usize)> = Vec::new();
+    for command in commands {
+        let notation = command.notation();
+        match parts.last_mut() {
+            Some((last, n)) if *last == notation => *n += 1,
+            _ => parts.push((notation, 1)),
+        }
+    }
+    parts
+        .into_iter()
+        .map(|(notation, n)| {
+            if n == 1 {
+                notation
+            } else {
+                format!("{n}x {notation}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
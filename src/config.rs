@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::challenge;
+use crate::curriculum;
+use crate::dirs;
+use crate::editor::{EditorKind, SessionOptions, ShellPolicy};
+use crate::error::ConfigError;
+use crate::feedback::Feedback;
+use crate::goals::Goal;
+use crate::heatmap::KeyboardLayout;
+use crate::keymap::{Action, Keymap};
+use crate::nvim::Launcher;
+use crate::one_hand;
+use crate::opponents::Opponent;
+use crate::state::{self, HistoryRetention, KeyLog};
+use crate::theme::Theme;
+use crate::unlock::UnlockPolicy;
+
+/// User settings read from `config.toml` in the nvimkata config directory.
+/// Command-line flags take precedence over these.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Config {
+    /// Challenges directory, instead of the bundled one.
+    #[serde(default)]
+    pub challenges_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Keyboard layout: drawn by the key heatmap on the skills screen, and
+    /// grading challenges with a `layout_par` for it.
+    #[serde(default)]
+    pub keyboard_layout: KeyboardLayout,
+    /// Unlock all categories (skip progression).
+    #[serde(default)]
+    pub unlock_all: bool,
+    /// When a category unlocks: `all`, a percentage like `80%`, a minimum grade like `C`, or `off`.
+    #[serde(default)]
+    pub unlock_policy: UnlockPolicy,
+    /// Editor challenges are played in: `neovim` (default) or `vim`.
+    #[serde(default)]
+    pub editor: EditorKind,
+    /// Editor binary, `nvim` (or `vim`) from PATH by default.
+    #[serde(default)]
+    pub nvim_bin: Option<String>,
+    /// Extra arguments passed to the editor for challenge sessions.
+    #[serde(default)]
+    pub nvim_args: Vec<String>,
+    /// Attempts kept per challenge: `best` (the 10 with the fewest
+    /// keystrokes), `last N`, `all` or `daily` (the best of each day).
+    #[serde(default)]
+    pub history: HistoryRetention,
+    /// How much of the key log of each attempt is saved: `full`, `scrub` or `off`.
+    #[serde(default)]
+    pub key_log: KeyLog,
+    /// Directory of the save files instead of the data directory, e.g. in a
+    /// dotfiles repository.
+    #[serde(default)]
+    pub state_dir: Option<PathBuf>,
+    /// Save profile used when `--profile` is not given.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Daily practice goals tracked with streaks in the hub header.
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    /// Key bindings per action in key notation, replacing that action's defaults.
+    #[serde(default)]
+    pub keys: BTreeMap<Action, Vec<String>>,
+    /// Shell commands during challenges: `mode = "log"` (default),
+    /// `"discourage"` or `"whitelist"` with the `allowed` programs.
+    #[serde(default)]
+    pub shell: ShellPolicy,
+    /// Show each challenge until Enter is pressed before the clock starts.
+    #[serde(default)]
+    pub briefing: bool,
+    /// Fail a run as soon as its keys pass the grade F threshold, resetting
+    /// the buffer. `nvimkata play --hardcore` turns it on for one session.
+    #[serde(default)]
+    pub hardcore: bool,
+    /// Draw the result screen without animations.
+    #[serde(default)]
+    pub reduce_motion: bool,
+    /// Show grades not refreshed by a solve within this many days as decayed.
+    #[serde(default)]
+    pub decay_days: Option<u32>,
+    /// Terminal bell and shell commands when a challenge is completed, beaten
+    /// or failed.
+    #[serde(default)]
+    pub feedback: Feedback,
+    /// Keys of the one-hand variants: `left` or `right` for the keys of that
+    /// hand on `keyboard_layout`, or the keys themselves in key notation.
+    /// Challenges with a one-hand par are then played with only these keys.
+    #[serde(default)]
+    pub one_hand: Option<String>,
+    /// Virtual opponents shown in the challenge details and results.
+    #[serde(default)]
+    pub opponents: Vec<Opponent>,
+    /// URL of the pack registry index searched by `nvimkata pack search`.
+    #[serde(default)]
+    pub registry_url: Option<String>,
+}
+
+impl Config {
+    pub fn launcher(&self) -> Launcher {
+        Launcher {
+            editor: self.editor,
+            bin: self
+                .nvim_bin
+                .clone()
+                .unwrap_or_else(|| self.editor.default_bin().to_string()),
+            args: self.nvim_args.clone(),
+            version: None,
+            session: self.session_options(),
+        }
+    }
+
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions {
+            shell: self.shell.clone(),
+            briefing: self.briefing,
+            hardcore: self.hardcore,
+            banner: None,
+        }
+    }
+
+    pub fn keymap(&self) -> Keymap {
+        Keymap::new(&self.keys)
+    }
+
+    /// The configured challenges directory, or the bundled one.
+    pub fn challenges_path(&self) -> PathBuf {
+        self.challenges_dir
+            .clone()
+            .unwrap_or_else(curriculum::bundled_dir)
+    }
+
+    /// Apply the settings that hold for the whole process: the save directory,
+    /// key log, history retention, grade decay, keyboard layout and one-hand
+    /// keys. Only the first call has an effect, so a later one fails with the
+    /// first setting that differs from what the process already runs with.
+    pub fn apply(&self) -> Result<(), ConfigError> {
+        let one_hand = self
+            .one_hand
+            .as_ref()
+            .map(|keys| one_hand::keys(keys, self.keyboard_layout));
+        let applied = [
+            ("state_dir", state::set_dir(self.state_dir.clone())),
+            ("key_log", state::set_key_log(self.key_log)),
+            ("history", state::set_history_retention(self.history)),
+            ("decay_days", state::set_decay_days(self.decay_days)),
+            (
+                "keyboard_layout",
+                challenge::set_layout(self.keyboard_layout),
+            ),
+            ("one_hand", one_hand::set(one_hand)),
+        ];
+        match applied.into_iter().find(|(_, applied)| !applied) {
+            Some((setting, _)) => Err(ConfigError { setting }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Load the config file. A missing file yields the defaults; a malformed one
+/// is reported and ignored.
+pub fn load_config() -> Config {
+    let path = config_path();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Config::default();
+    };
+    match toml::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Warning: failed to parse {}: {}", path.display(), e);
+            Config::default()
+        }
+    }
+}
+
+/// Directory of user playlists, `playlists/` in the nvimkata config directory.
+pub fn playlists_dir() -> PathBuf {
+    dirs::config_dir().join("playlists")
+}
+
+/// Directory of assignments handed out by an instructor.
+pub fn assignments_dir() -> PathBuf {
+    dirs::config_dir().join("assignments")
+}
+
+/// Path of `config.toml` in the nvimkata config directory.
+pub fn config_path() -> PathBuf {
+    dirs::config_dir().join("config.toml")
+}
@@ -1,10 +1,42 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
-use crate::challenge::{Challenge, Topic, count_keystrokes};
+use serde::{Deserialize, Serialize};
+
+use crate::challenge::{
+    Category, Challenge, HintRegion, Setup, Topic, count_keystrokes, is_valid_curve,
+    is_valid_filetype,
+};
+use crate::dirs;
+use crate::lint::{self, Lint};
+use crate::pack::{self, FIRST_PACK_ID, LAST_PACK_ID, Pack};
+use crate::state::challenge_hash;
+
+/// Optional per-topic settings file inside a topic directory.
+pub const TOPIC_FILE: &str = "topic.toml";
+/// Optional intro of a topic directory, in Markdown.
+pub const INTRO_FILE: &str = "intro.md";
+
+/// Settings shared by all challenges of a topic.
+#[derive(Debug, Default, Deserialize)]
+struct TopicConfig {
+    /// Grading curve for challenges that don't define their own `thresholds`.
+    #[serde(default)]
+    thresholds: Option<[u32; 5]>,
+}
 
 /// Topic metadata. Challenge TOML files live in subdirectories.
 const TOPICS: &[(u8, &str, &str, &str)] = &[
+    (
+        0,
+        "00_tutorial",
+        "Tutorial",
+        "Guided first steps: hjkl, words, insert mode, undo",
+    ),
     (
         1,
         "01_motions",
@@ -107,51 +139,573 @@ const FREESTYLE_TOPICS: &[(u8, &str, &str, &str)] = &[
     ),
 ];
 
-/// Load all topics from a challenges directory.
+/// A problem found while loading the curriculum: a file that couldn't be
+/// loaded, or a setting that was dropped from one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub message: String,
+    /// The challenge in the file couldn't be loaded at all.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,
+}
+
+impl Diagnostic {
+    fn warning(path: &Path, message: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message,
+            failed: false,
+        }
+    }
+
+    fn failure(path: &Path, message: String) -> Self {
+        Self {
+            failed: true,
+            ..Self::warning(path, message)
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// How a curriculum load went, for the summary of `--verbose` and the hub.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadStats {
+    pub challenges: usize,
+    /// Challenge files found, including those that failed to parse.
+    pub files: usize,
+    /// Files taken from the cache instead of being parsed again.
+    pub cached: usize,
+    pub elapsed: Duration,
+    /// Load problems, by topic and file, for printing or listing in the hub.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Content problems of the loaded challenges, by topic and file, followed
+    /// by duplicate IDs.
+    pub lints: Vec<Lint>,
+}
+
+impl fmt::Display for LoadStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "loaded {} challenges from {} files ({} cached) in {} ms",
+            self.challenges,
+            self.files,
+            self.cached,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Load all topics from a challenges directory, with the challenge cache in
+/// the cache directory, printing the load problems. Installed packs are left out.
 pub fn load_curriculum(challenges_dir: &Path) -> Vec<Topic> {
-    TOPICS
+    let (topics, stats) = load_curriculum_with(challenges_dir, &[], Some(&cache_path()));
+    for diagnostic in &stats.diagnostics {
+        eprintln!("Warning: {diagnostic}");
+    }
+    topics
+}
+
+/// Load all topics from a challenges directory, followed by a topic for each
+/// of `packs`. Challenge files are parsed on all cores; with a `cache` file,
+/// files whose modification time and size are unchanged since the last load
+/// are taken from it instead. Only the metadata of each challenge is kept: its
+/// buffers are read by [`Challenge::loaded`]. Problems are collected in the
+/// stats rather than printed.
+pub fn load_curriculum_with(
+    challenges_dir: &Path,
+    packs: &[Pack],
+    cache: Option<&Path>,
+) -> (Vec<Topic>, LoadStats) {
+    let started = Instant::now();
+    // Cache entries are keyed by absolute path, whatever the working directory
+    let root = fs::canonicalize(challenges_dir).unwrap_or_else(|_| challenges_dir.to_path_buf());
+    let mut dirs: Vec<(u8, String, String, PathBuf)> = TOPICS
         .iter()
         .chain(FREESTYLE_TOPICS.iter())
         .map(|(id, dir_name, name, description)| {
-            let dir = challenges_dir.join(dir_name);
-            let challenges = load_challenges_from_dir(&dir);
+            let dir = root.join(dir_name);
+            (*id, (*name).to_string(), (*description).to_string(), dir)
+        })
+        .collect();
+    let mut diagnostics = Vec::new();
+    let mut ids = FIRST_PACK_ID..=LAST_PACK_ID;
+    for pack in packs {
+        let Some(id) = ids.next() else {
+            diagnostics.push(Diagnostic::warning(
+                &pack.dir,
+                format!("too many packs, ignoring {}", pack.manifest.name),
+            ));
+            continue;
+        };
+        let dir = fs::canonicalize(&pack.dir).unwrap_or_else(|_| pack.dir.clone());
+        dirs.push((id, pack.manifest.name.clone(), pack.description(), dir));
+    }
+    let files: Vec<Vec<PathBuf>> = dirs.iter().map(|(.., dir)| challenge_files(dir)).collect();
+
+    let mut cached = cache.map(ChallengeCache::read).unwrap_or_default();
+    let all: Vec<&PathBuf> = files.iter().flatten().collect();
+    let loaded = parallel_map(&all, |path| cached.load(path));
+    let hits = loaded
+        .iter()
+        .filter(|(_, source)| matches!(source, Source::Cache))
+        .count();
+    if let Some(cache) = cache {
+        cached.update(
+            &root,
+            all.iter()
+                .zip(&loaded)
+                .map(|(path, (_, source))| (*path, source)),
+        );
+        if hits < loaded.len() || cached.pruned {
+            cached.write(cache);
+        }
+    }
+
+    let mut lints = Vec::new();
+    let mut loaded = loaded.into_iter();
+    let mut topics: Vec<Topic> = dirs
+        .iter()
+        .zip(&files)
+        .map(|((id, name, description, dir), files)| {
+            let config = load_topic_config(dir, &mut diagnostics);
+            let mut challenges = Vec::new();
+            for (file, _) in loaded.by_ref().take(files.len()) {
+                diagnostics.extend(file.diagnostics);
+                challenges.extend(file.challenge);
+                lints.extend(file.lints);
+            }
+            let intro = load_intro(dir);
+            for challenge in &mut challenges {
+                challenge.thresholds = challenge.thresholds.or(config.thresholds);
+                challenge.intro.clone_from(&intro);
+                challenge.category = Some(Category::for_topic(*id));
+            }
             Topic {
                 id: *id,
-                name: (*name).to_string(),
-                description: (*description).to_string(),
+                name: name.clone(),
+                description: description.clone(),
+                intro,
                 challenges,
             }
         })
-        .collect()
+        .collect();
+
+    prune_requirements(&mut topics, &mut diagnostics);
+    lints.extend(lint::duplicate_ids(&topics));
+    let stats = LoadStats {
+        challenges: topics.iter().map(|t| t.challenges.len()).sum(),
+        files: all.len(),
+        cached: hits,
+        elapsed: started.elapsed(),
+        diagnostics,
+        lints,
+    };
+    (topics, stats)
+}
+
+/// Load `topic.toml` from a topic directory. Missing or invalid files yield
+/// defaults; problems are added to `diagnostics`.
+fn load_topic_config(dir: &Path, diagnostics: &mut Vec<Diagnostic>) -> TopicConfig {
+    let path = dir.join(TOPIC_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return TopicConfig::default();
+    };
+    match toml::from_str::<TopicConfig>(&content) {
+        Ok(mut config) => {
+            if config.thresholds.is_some_and(|c| !is_valid_curve(&c)) {
+                diagnostics.push(Diagnostic::warning(
+                    &path,
+                    "ignoring invalid thresholds".to_string(),
+                ));
+                config.thresholds = None;
+            }
+            config
+        }
+        Err(e) => {
+            diagnostics.push(Diagnostic::warning(&path, format!("failed to parse: {e}")));
+            TopicConfig::default()
+        }
+    }
 }
 
-/// Load all .toml challenge files from a directory.
-fn load_challenges_from_dir(dir: &Path) -> Vec<Challenge> {
-    let mut challenges = Vec::new();
+/// Read `intro.md` from a topic directory; `None` when missing or blank.
+fn load_intro(dir: &Path) -> Option<String> {
+    fs::read_to_string(dir.join(INTRO_FILE))
+        .ok()
+        .filter(|intro| !intro.trim().is_empty())
+}
+
+/// The .toml challenge files of a topic or pack directory, without `topic.toml`
+/// and `pack.toml`, sorted.
+pub fn challenge_files(dir: &Path) -> Vec<PathBuf> {
     let Ok(entries) = fs::read_dir(dir) else {
-        return challenges;
+        return Vec::new();
     };
     let mut paths: Vec<PathBuf> = entries
         .filter_map(std::result::Result::ok)
         .map(|e| e.path())
         .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .filter(|p| {
+            p.file_name()
+                .is_none_or(|name| name != TOPIC_FILE && name != pack::MANIFEST)
+        })
         .collect();
     paths.sort();
-    for path in paths {
-        match fs::read_to_string(&path) {
-            Ok(content) => match toml::from_str::<Challenge>(&content) {
-                Ok(mut challenge) => {
-                    if let Some(moves) = &challenge.perfect_moves {
-                        challenge.par_keystrokes =
-                            u32::try_from(moves.iter().map(|m| count_keystrokes(m)).sum::<usize>())
-                                .expect("keystroke count exceeds u32");
-                    }
-                    challenges.push(challenge);
-                }
-                Err(e) => eprintln!("Warning: failed to parse {}: {}", path.display(), e),
-            },
-            Err(e) => eprintln!("Warning: failed to read {}: {}", path.display(), e),
+    paths
+}
+
+/// Drop requirements that don't name another loaded challenge or that close a
+/// cycle, with a warning.
+fn prune_requirements(topics: &mut [Topic], diagnostics: &mut Vec<Diagnostic>) {
+    let ids: HashSet<String> = topics
+        .iter()
+        .flat_map(|t| t.challenges.iter().map(|c| c.id.clone()))
+        .collect();
+    for challenge in topics.iter_mut().flat_map(|t| t.challenges.iter_mut()) {
+        let id = &challenge.id;
+        let path = challenge
+            .source
+            .as_ref()
+            .map(|source| source.path.clone())
+            .unwrap_or_default();
+        challenge.requires.retain(|req| {
+            let known = req != id && ids.contains(req);
+            if !known {
+                diagnostics.push(Diagnostic::warning(
+                    &path,
+                    format!("ignoring unknown requirement '{req}' of {id}"),
+                ));
+            }
+            known
+        });
+    }
+    break_requirement_cycles(topics, diagnostics);
+}
+
+/// Drop the requirements that lead back to a challenge requiring them, such as
+/// `b` of `a` when `b` requires `a`, with a warning. Requirements are followed
+/// in curriculum order, and of each cycle the one closing it is dropped.
+fn break_requirement_cycles(topics: &mut [Topic], diagnostics: &mut Vec<Diagnostic>) {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Visit {
+        New,
+        Open,
+        Done,
+    }
+
+    fn follow(
+        node: usize,
+        edges: &[Vec<usize>],
+        visits: &mut [Visit],
+        cyclic: &mut Vec<(usize, usize)>,
+    ) {
+        visits[node] = Visit::Open;
+        for &next in &edges[node] {
+            match visits[next] {
+                Visit::New => follow(next, edges, visits, cyclic),
+                Visit::Open => cyclic.push((node, next)),
+                Visit::Done => {}
+            }
+        }
+        visits[node] = Visit::Done;
+    }
+
+    let challenges: Vec<&Challenge> = topics.iter().flat_map(|t| &t.challenges).collect();
+    let mut index = HashMap::new();
+    for (i, challenge) in challenges.iter().enumerate() {
+        index.entry(challenge.id.as_str()).or_insert(i);
+    }
+    let edges: Vec<Vec<usize>> = challenges
+        .iter()
+        .map(|c| {
+            c.requires
+                .iter()
+                .filter_map(|req| index.get(req.as_str()).copied())
+                .collect()
+        })
+        .collect();
+    let mut visits = vec![Visit::New; challenges.len()];
+    let mut cyclic = Vec::new();
+    for node in 0..challenges.len() {
+        if visits[node] == Visit::New {
+            follow(node, &edges, &mut visits, &mut cyclic);
+        }
+    }
+    let ids: Vec<String> = challenges.iter().map(|c| c.id.clone()).collect();
+
+    for (node, challenge) in topics
+        .iter_mut()
+        .flat_map(|t| t.challenges.iter_mut())
+        .enumerate()
+    {
+        for &(_, next) in cyclic.iter().filter(|(from, _)| *from == node) {
+            let req = &ids[next];
+            challenge.requires.retain(|r| r != req);
+            let path = challenge
+                .source
+                .as_ref()
+                .map(|source| source.path.clone())
+                .unwrap_or_default();
+            diagnostics.push(Diagnostic::warning(
+                &path,
+                format!("ignoring cyclic requirement '{req}' of {}", challenge.id),
+            ));
+        }
+    }
+}
+
+/// Run `f` on every item on all cores, keeping the order of `items`.
+fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk = items.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(&f).collect::<Vec<R>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("challenge loader panicked"))
+            .collect()
+    })
+}
+
+/// A challenge file as loaded: the challenge if it could be used with the
+/// [`challenge_hash`] of its buffers, the problems found loading it and the
+/// lints of its content.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LoadedFile {
+    challenge: Option<Challenge>,
+    #[serde(default)]
+    content_hash: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lints: Vec<Lint>,
+}
+
+/// Read and check a challenge file. Invalid settings are dropped with a warning.
+fn load_challenge_file(path: &Path) -> LoadedFile {
+    let failed = |message: String| LoadedFile {
+        challenge: None,
+        content_hash: String::new(),
+        diagnostics: vec![Diagnostic::failure(path, message)],
+        lints: Vec::new(),
+    };
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => return failed(format!("failed to read: {e}")),
+    };
+    let mut challenge = match toml::from_str::<Challenge>(&content) {
+        Ok(challenge) => challenge,
+        Err(e) => return failed(format!("failed to parse: {e}")),
+    };
+    if challenge.start.is_none() || challenge.target.is_none() {
+        return failed("missing start or target buffer".to_string());
+    }
+    let mut diagnostics = Vec::new();
+    if let Err(e) = challenge.setup.validate() {
+        diagnostics.push(Diagnostic::warning(path, format!("ignoring setup: {e}")));
+        challenge.setup = Setup::default();
+    }
+    let mut ignore =
+        |what: &str| diagnostics.push(Diagnostic::warning(path, format!("ignoring {what}")));
+    if challenge.thresholds.is_some_and(|c| !is_valid_curve(&c)) {
+        ignore("invalid thresholds");
+        challenge.thresholds = None;
+    }
+    if challenge.one_hand_par == Some(0) {
+        ignore("one_hand_par of 0");
+        challenge.one_hand_par = None;
+    }
+    if challenge.layout_par.values().any(|&par| par == 0) {
+        ignore("layout_par of 0");
+        challenge.layout_par.retain(|_, par| *par > 0);
+    }
+    if challenge
+        .filetype
+        .as_deref()
+        .is_some_and(|ft| !is_valid_filetype(ft))
+    {
+        ignore("invalid filetype");
+        challenge.filetype = None;
+    }
+    if challenge.forbidden_keys.iter().any(String::is_empty) {
+        ignore("empty forbidden key");
+        challenge.forbidden_keys.retain(|k| !k.is_empty());
+    }
+    if challenge.tutorial_steps.iter().any(|s| s.keys.is_empty()) {
+        ignore("tutorial step without keys");
+        challenge.tutorial_steps.retain(|s| !s.keys.is_empty());
+    }
+    if !challenge.hint_region.iter().all(HintRegion::is_valid) {
+        ignore("invalid hint_region");
+        challenge.hint_region.retain(HintRegion::is_valid);
+    }
+    if challenge.reveal_secs.is_some() && challenge.blind {
+        ignore("reveal_secs of a blind challenge");
+        challenge.reveal_secs = None;
+    }
+    if challenge
+        .recorded_macro
+        .as_ref()
+        .is_some_and(|m| !m.register.is_ascii_lowercase() || m.replays == 0)
+    {
+        ignore("invalid macro");
+        challenge.recorded_macro = None;
+    }
+    // Macro challenges are graded on the macro, so their par is set by hand
+    if let Some(moves) = &challenge.perfect_moves
+        && challenge.recorded_macro.is_none()
+    {
+        challenge.par_keystrokes =
+            u32::try_from(moves.iter().map(|m| count_keystrokes(m)).sum::<usize>())
+                .expect("keystroke count exceeds u32");
+    }
+    let lints = lint::check(&challenge);
+    // The buffers are read again when the challenge is previewed or played,
+    // but saved results are checked against them on every start
+    let content_hash = challenge_hash(&challenge);
+    challenge.unload(path, content_hash.clone());
+    LoadedFile {
+        challenge: Some(challenge),
+        content_hash,
+        diagnostics,
+        lints,
+    }
+}
+
+/// Version of the cache format, bumped when the cached entries change
+/// within a release.
+const CACHE_FORMAT: u32 = 6;
+
+/// Version a cache was written by: the nvimkata version and the cache format.
+fn cache_version() -> String {
+    format!("{}+{CACHE_FORMAT}", env!("CARGO_PKG_VERSION"))
+}
+
+/// The bundled challenges: `share/nvimkata/challenges` next to the binary's
+/// directory if it exists, `challenges` in the working directory otherwise.
+pub fn bundled_dir() -> PathBuf {
+    if let Ok(exe) = std::env::current_exe() {
+        let dir = exe
+            .parent()
+            .unwrap_or(&exe)
+            .join("../share/nvimkata/challenges");
+        if dir.exists() {
+            return dir;
+        }
+    }
+    PathBuf::from("challenges")
+}
+
+/// Challenge cache file, `challenges.json` in the cache directory.
+pub fn cache_path() -> PathBuf {
+    dirs::cache_dir().join("challenges.json")
+}
+
+/// Checked challenge files by absolute path, with the modification time and
+/// size they were loaded at. The whole cache is dropped when nvimkata is
+/// updated, as the challenge format or the checks may have changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChallengeCache {
+    version: String,
+    files: BTreeMap<PathBuf, CacheEntry>,
+    /// Entries of deleted files were dropped, so the cache needs writing.
+    #[serde(skip)]
+    pruned: bool,
+}
+
+/// Where a loaded challenge file came from.
+enum Source {
+    Cache,
+    /// Read from disk, with the new cache entry unless the file couldn't be
+    /// stamped.
+    Read(Option<Box<CacheEntry>>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    modified_ns: u64,
+    size: u64,
+    #[serde(flatten)]
+    file: LoadedFile,
+}
+
+impl ChallengeCache {
+    /// Read the cache at `path`. A missing, broken or outdated cache is empty.
+    fn read(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|cache| cache.version == cache_version())
+            .unwrap_or_default()
+    }
+
+    /// Load a challenge file, from the cache if it is unchanged.
+    fn load(&self, path: &Path) -> (LoadedFile, Source) {
+        let stamp = fs::metadata(path).ok().and_then(|meta| {
+            let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+            Some((u64::try_from(modified.as_nanos()).ok()?, meta.len()))
+        });
+        if let (Some((modified_ns, size)), Some(entry)) = (stamp, self.files.get(path))
+            && (entry.modified_ns, entry.size) == (modified_ns, size)
+        {
+            // The cache doesn't store where the buffers are read back from
+            let mut file = entry.file.clone();
+            if let Some(challenge) = &mut file.challenge {
+                challenge.unload(path, file.content_hash.clone());
+            }
+            return (file, Source::Cache);
+        }
+        let file = load_challenge_file(path);
+        let entry = stamp.map(|(modified_ns, size)| {
+            Box::new(CacheEntry {
+                modified_ns,
+                size,
+                file: file.clone(),
+            })
+        });
+        (file, Source::Read(entry))
+    }
+
+    /// Store the entries of newly read files and drop the entries of files
+    /// under `root` that are gone.
+    fn update<'a>(&mut self, root: &Path, loaded: impl Iterator<Item = (&'a PathBuf, &'a Source)>) {
+        let mut seen = HashSet::new();
+        for (path, source) in loaded {
+            seen.insert(path.clone());
+            if let Source::Read(Some(entry)) = source {
+                self.files.insert(path.clone(), CacheEntry::clone(entry));
+            }
+        }
+        let before = self.files.len();
+        self.files
+            .retain(|path, _| seen.contains(path) || (!path.starts_with(root) && path.exists()));
+        self.pruned = self.files.len() < before;
+    }
+
+    /// Write the cache to `path`. Failing to is not worth a warning; the next
+    /// start just parses the files again.
+    fn write(&mut self, path: &Path) {
+        self.version = cache_version();
+        let Ok(json) = serde_json::to_string(self) else {
+            return;
+        };
+        // Replace the file in one step, so a concurrent start never reads half a cache
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if fs::write(&tmp, json).is_ok() && fs::rename(&tmp, path).is_err() {
+            let _ = fs::remove_file(&tmp);
         }
     }
-    challenges
 }
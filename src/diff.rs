@@ -0,0 +1,88 @@
+/// One step of an edit script turning `a` into `b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    /// `a[i]` and `b[j]` are equal.
+    Equal(usize, usize),
+    /// `a[i]` is not in `b`.
+    Delete(usize),
+    /// `b[j]` is not in `a`.
+    Insert(usize),
+}
+
+/// Shortest edit script between two sequences via longest common subsequence.
+pub fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            ops.push(DiffOp::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(DiffOp::Delete(i));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..a.len()).map(DiffOp::Delete));
+    ops.extend((j..b.len()).map(DiffOp::Insert));
+    ops
+}
+
+/// A row of a side-by-side view: indexes into `a` and `b`, either of which may be
+/// empty on its side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub changed: bool,
+}
+
+/// Align two sequences into side-by-side rows. Within each changed block,
+/// deleted and inserted items are paired up row by row.
+pub fn side_by_side<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let flush = |rows: &mut Vec<Row>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>| {
+        for k in 0..deleted.len().max(inserted.len()) {
+            rows.push(Row {
+                left: deleted.get(k).copied(),
+                right: inserted.get(k).copied(),
+                changed: true,
+            });
+        }
+        deleted.clear();
+        inserted.clear();
+    };
+    for op in diff(a, b) {
+        match op {
+            DiffOp::Equal(i, j) => {
+                flush(&mut rows, &mut deleted, &mut inserted);
+                rows.push(Row {
+                    left: Some(i),
+                    right: Some(j),
+                    changed: false,
+                });
+            }
+            DiffOp::Delete(i) => deleted.push(i),
+            DiffOp::Insert(j) => inserted.push(j),
+        }
+    }
+    flush(&mut rows, &mut deleted, &mut inserted);
+    rows
+}
@@ -0,0 +1,193 @@
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Platform conventions a directory is resolved with. Resolving takes the
+/// platform and environment as arguments, so every platform can be tested on any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Unix,
+    Windows,
+}
+
+impl Platform {
+    /// The platform nvimkata was built for.
+    pub const CURRENT: Self = if cfg!(windows) {
+        Self::Windows
+    } else {
+        Self::Unix
+    };
+}
+
+/// Config directory: `$XDG_CONFIG_HOME/nvimkata` or `~/.config/nvimkata`, and
+/// `%APPDATA%\nvimkata` on Windows.
+pub fn config_dir() -> PathBuf {
+    config_dir_for(Platform::CURRENT, env_var)
+}
+
+/// Save data directory: `$XDG_DATA_HOME/nvimkata` or `~/.local/share/nvimkata`,
+/// and `%LOCALAPPDATA%\nvimkata` on Windows.
+pub fn data_dir() -> PathBuf {
+    data_dir_for(Platform::CURRENT, env_var)
+}
+
+/// Cache directory: `$XDG_CACHE_HOME/nvimkata` or `~/.cache/nvimkata`, and
+/// `%LOCALAPPDATA%\nvimkata\cache` on Windows.
+pub fn cache_dir() -> PathBuf {
+    cache_dir_for(Platform::CURRENT, env_var)
+}
+
+/// Parent of the session directories, `nvimkata` in the temp directory.
+pub fn sessions_root() -> PathBuf {
+    std::env::temp_dir().join("nvimkata")
+}
+
+/// A unique directory for the files of one session, removed again when dropped.
+/// It is named `{kind}-{pid}-{suffix}`, so concurrent nvimkata processes never
+/// share files and directories left by a crash can be told apart.
+#[derive(Debug)]
+pub struct SessionDir {
+    path: PathBuf,
+}
+
+impl SessionDir {
+    /// Create a session directory in [`sessions_root`].
+    pub fn create(kind: &str) -> io::Result<Self> {
+        Self::create_in(&sessions_root(), kind)
+    }
+
+    pub fn create_in(parent: &Path, kind: &str) -> io::Result<Self> {
+        fs::create_dir_all(parent)?;
+        loop {
+            let path = parent.join(format!(
+                "{kind}-{}-{:x}",
+                std::process::id(),
+                random_suffix()
+            ));
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SessionDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn random_suffix() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish()
+}
+
+/// Session directories older than this are stale where running processes
+/// cannot be listed.
+const STALE_AGE: Duration = Duration::from_hours(24);
+
+/// Remove the session directories in `parent` left behind by nvimkata processes
+/// that are gone, e.g. after a crash. Returns how many were removed.
+pub fn clean_stale_sessions(parent: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let pid = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.rsplit('-').nth(1))
+            .and_then(|pid| pid.parse::<u32>().ok());
+        if let Some(pid) = pid
+            && path.is_dir()
+            && is_stale(pid, &path)
+            && fs::remove_dir_all(&path).is_ok()
+        {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Whether the session of process `pid` in `path` is over. Processes are looked
+/// up in `/proc` where there is one; elsewhere old sessions are stale.
+fn is_stale(pid: u32, path: &Path) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        return !proc.join(pid.to_string()).exists();
+    }
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > STALE_AGE)
+}
+
+pub fn config_dir_for(platform: Platform, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let base = match platform {
+        Platform::Unix => var("XDG_CONFIG_HOME")
+            .map_or_else(|| home(platform, &var).join(".config"), PathBuf::from),
+        Platform::Windows => var("APPDATA").map_or_else(
+            || home(platform, &var).join("AppData").join("Roaming"),
+            PathBuf::from,
+        ),
+    };
+    base.join("nvimkata")
+}
+
+pub fn data_dir_for(platform: Platform, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let base = match platform {
+        Platform::Unix => var("XDG_DATA_HOME").map_or_else(
+            || home(platform, &var).join(".local").join("share"),
+            PathBuf::from,
+        ),
+        Platform::Windows => var("LOCALAPPDATA").map_or_else(
+            || home(platform, &var).join("AppData").join("Local"),
+            PathBuf::from,
+        ),
+    };
+    base.join("nvimkata")
+}
+
+pub fn cache_dir_for(platform: Platform, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    match platform {
+        Platform::Unix => var("XDG_CACHE_HOME")
+            .map_or_else(|| home(platform, &var).join(".cache"), PathBuf::from)
+            .join("nvimkata"),
+        Platform::Windows => data_dir_for(platform, var).join("cache"),
+    }
+}
+
+/// Home directory, or the current directory if it is not set.
+fn home(platform: Platform, var: &impl Fn(&str) -> Option<String>) -> PathBuf {
+    let name = match platform {
+        Platform::Unix => "HOME",
+        Platform::Windows => "USERPROFILE",
+    };
+    PathBuf::from(var(name).unwrap_or_else(|| ".".to_string()))
+}
+
+/// A set, non-empty environment variable.
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+
+use crate::versus::Turn;
+
+/// Port of the relay when an address leaves it out.
+pub const DEFAULT_PORT: u16 = 7373;
+
+/// A line of the duel protocol, one JSON object per line over TCP. Both
+/// players join a room on the relay, get the same challenge and start signal,
+/// and hear of each other's result as soon as it is in. Connecting needs the
+/// `net` feature; the protocol itself is always built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Client to relay: join `room` as `player`. The first player in a room
+    /// picks the challenge; the second may leave it out, or must name the same
+    /// one.
+    Join {
+        room: String,
+        player: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        challenge: Option<String>,
+    },
+    /// Relay to both players once the room is full: play `challenge` now.
+    Start { challenge: String, opponent: String },
+    /// A finished run, sent by a client and passed on to its opponent.
+    Result { turn: Turn },
+    /// Relay to a client whose join was refused.
+    Error { message: String },
+}
+
+impl Message {
+    /// The message as a protocol line, newline included.
+    pub fn to_line(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self).map(|json| json + "\n")
+    }
+
+    pub fn from_line(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line.trim_end())
+    }
+}
+
+/// `addr` with the default port added when it has none: `example.org` is
+/// `example.org:7373`.
+pub fn with_port(addr: &str) -> String {
+    if addr
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        addr.to_string()
+    } else {
+        format!("{addr}:{DEFAULT_PORT}")
+    }
+}
+
+/// The bar banner for an opponent's finished run.
+pub fn banner(turn: &Turn) -> String {
+    if turn.solved {
+        format!("{} finished in {} keys", turn.player, turn.keystrokes)
+    } else {
+        format!("{} gave up after {} keys", turn.player, turn.keystrokes)
+    }
+}
+
+#[cfg(feature = "net")]
+pub use net::{Connection, Duel, relay};
+
+#[cfg(feature = "net")]
+mod net {
+    use std::collections::HashMap;
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::net::{Shutdown, TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::Message;
+    use crate::versus::Turn;
+
+    /// A player waiting in a room for an opponent.
+    struct Waiting {
+        player: String,
+        challenge: String,
+        stream: TcpStream,
+    }
+
+    type Rooms = Arc<Mutex<HashMap<String, Waiting>>>;
+
+    /// Longest protocol line read, so a peer can't have it buffered without end.
+    const MAX_LINE: u64 = 256 * 1024;
+
+    /// Run the relay on `listener`, pairing players by room, until accepting
+    /// fails.
+    pub fn relay(listener: &TcpListener) -> io::Result<()> {
+        let rooms = Rooms::default();
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let rooms = Arc::clone(&rooms);
+            thread::spawn(move || {
+                // A player that drops out only ends its own duel
+                let _ = serve(stream, &rooms);
+            });
+        }
+        Ok(())
+    }
+
+    fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Message>> {
+        let mut line = String::new();
+        let read = Read::take(&mut *reader, MAX_LINE).read_line(&mut line)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        if read as u64 == MAX_LINE && !line.ends_with('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("message longer than {MAX_LINE} bytes"),
+            ));
+        }
+        Message::from_line(&line)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn send(mut stream: &TcpStream, message: &Message) -> io::Result<()> {
+        stream.write_all(message.to_line()?.as_bytes())
+    }
+
+    /// Handle a connection: its join, then, for the second player of a room,
+    /// the whole duel.
+    fn serve(stream: TcpStream, rooms: &Rooms) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let Some(Message::Join {
+            room,
+            player,
+            challenge,
+        }) = read_message(&mut reader)?
+        else {
+            return send(&stream, &error("expected a join"));
+        };
+        let host = {
+            let mut rooms = rooms
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            // A host that left while waiting frees the room
+            let waiting = rooms.remove(&room).filter(|host| !is_closed(&host.stream));
+            match (waiting, challenge) {
+                (Some(host), _) if host.player == player => {
+                    rooms.insert(room, host);
+                    return send(&stream, &error(&format!("{player} is already in the room")));
+                }
+                (Some(host), Some(challenge)) if challenge != host.challenge => {
+                    let message = format!("room {room} is playing {}", host.challenge);
+                    rooms.insert(room, host);
+                    return send(&stream, &error(&message));
+                }
+                (Some(host), _) => host,
+                (None, Some(challenge)) => {
+                    rooms.insert(
+                        room,
+                        Waiting {
+                            player,
+                            challenge,
+                            stream,
+                        },
+                    );
+                    return Ok(());
+                }
+                (None, None) => {
+                    return send(
+                        &stream,
+                        &error(&format!(
+                            "nobody is waiting in room {room} to pick a challenge"
+                        )),
+                    );
+                }
+            }
+        };
+        for (to, opponent) in [(&host.stream, &player), (&stream, &host.player)] {
+            send(
+                to,
+                &Message::Start {
+                    challenge: host.challenge.clone(),
+                    opponent: opponent.clone(),
+                },
+            )?;
+        }
+        let host_reader = BufReader::new(host.stream.try_clone()?);
+        let guest = stream.try_clone()?;
+        let forward = thread::spawn(move || pass_results(host_reader, &guest));
+        pass_results(reader, &host.stream);
+        let _ = forward.join();
+        Ok(())
+    }
+
+    /// Whether the peer of `stream` has closed it, checked without blocking or
+    /// consuming anything it sent.
+    fn is_closed(stream: &TcpStream) -> bool {
+        if stream.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let closed = match stream.peek(&mut [0]) {
+            Ok(read) => read == 0,
+            Err(e) => e.kind() != io::ErrorKind::WouldBlock,
+        };
+        stream.set_nonblocking(false).is_err() || closed
+    }
+
+    /// Pass each result read from `from` on to `to` until `from` closes, then
+    /// close `to` so its player stops waiting.
+    fn pass_results(mut from: impl BufRead, to: &TcpStream) {
+        while let Ok(Some(message)) = read_message(&mut from) {
+            if let Message::Result { .. } = message
+                && send(to, &message).is_err()
+            {
+                break;
+            }
+        }
+        let _ = to.shutdown(Shutdown::Both);
+    }
+
+    fn error(message: &str) -> Message {
+        Message::Error {
+            message: message.to_string(),
+        }
+    }
+
+    /// A player's connection to the relay.
+    pub struct Connection {
+        reader: BufReader<TcpStream>,
+        stream: TcpStream,
+    }
+
+    impl Connection {
+        /// Join `room` on the relay at `addr` and wait for the start signal.
+        /// Returns the connection, the challenge ID and the opponent's name.
+        pub fn join(
+            addr: &str,
+            room: &str,
+            player: &str,
+            challenge: Option<&str>,
+        ) -> io::Result<(Self, String, String)> {
+            let stream = TcpStream::connect(addr)?;
+            let mut connection = Self {
+                reader: BufReader::new(stream.try_clone()?),
+                stream,
+            };
+            send(
+                &connection.stream,
+                &Message::Join {
+                    room: room.to_string(),
+                    player: player.to_string(),
+                    challenge: challenge.map(ToString::to_string),
+                },
+            )?;
+            match read_message(&mut connection.reader)? {
+                Some(Message::Start {
+                    challenge,
+                    opponent,
+                }) => Ok((connection, challenge, opponent)),
+                Some(Message::Error { message }) => Err(io::Error::other(message)),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the relay closed the duel before it started",
+                )),
+            }
+        }
+
+        /// Wait for the opponent's run in the background, calling `on_result`
+        /// with it as soon as it is in.
+        pub fn watch(self, on_result: impl FnOnce(&Turn) + Send + 'static) -> Duel {
+            let mut reader = self.reader;
+            let opponent = thread::spawn(move || {
+                while let Ok(Some(message)) = read_message(&mut reader) {
+                    if let Message::Result { turn } = message {
+                        on_result(&turn);
+                        return Some(turn);
+                    }
+                }
+                None
+            });
+            Duel {
+                stream: self.stream,
+                opponent,
+            }
+        }
+    }
+
+    /// A started duel, with the opponent's run awaited in the background.
+    pub struct Duel {
+        stream: TcpStream,
+        opponent: thread::JoinHandle<Option<Turn>>,
+    }
+
+    impl Duel {
+        /// Send the player's finished run to the opponent.
+        pub fn send_result(&self, turn: &Turn) -> io::Result<()> {
+            send(&self.stream, &Message::Result { turn: turn.clone() })
+        }
+
+        /// Whether the opponent's run is in, or the opponent left.
+        pub fn opponent_done(&self) -> bool {
+            self.opponent.is_finished()
+        }
+
+        /// Wait for the opponent's run. `None` when the opponent left without
+        /// finishing.
+        pub fn opponent(self) -> Option<Turn> {
+            self.opponent.join().ok().flatten()
+        }
+    }
+}
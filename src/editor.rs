@@ -0,0 +1,299 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::challenge::Challenge;
+use crate::error::{Error, NvimError};
+use crate::nvim::{self, ChallengeResult, Launcher, Neovim};
+use crate::vim::Vim;
+
+/// Editor that challenges are played in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorKind {
+    #[default]
+    #[serde(alias = "nvim")]
+    Neovim,
+    Vim,
+}
+
+impl EditorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Neovim => "neovim",
+            Self::Vim => "vim",
+        }
+    }
+
+    /// Start of the first `--version` line of this editor.
+    fn version_prefix(self) -> &'static str {
+        match self {
+            Self::Neovim => "NVIM ",
+            Self::Vim => "VIM - Vi IMproved",
+        }
+    }
+
+    /// Oldest supported release. Neovim 0.8 brings `keytrans()` and the winbar;
+    /// newer APIs are used with fallbacks. Vim 8.2 brings `<Cmd>` mappings and
+    /// `getcurpos()` for other windows.
+    pub fn min_version(self) -> Version {
+        match self {
+            Self::Neovim => Version::new(0, 8, 0),
+            Self::Vim => Version::new(8, 2, 0),
+        }
+    }
+
+    /// Binary run when no other is configured.
+    pub fn default_bin(self) -> &'static str {
+        match self {
+            Self::Neovim => "nvim",
+            Self::Vim => "vim",
+        }
+    }
+}
+
+/// User settings the challenge runtime applies in every session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    pub shell: ShellPolicy,
+    /// Show the challenge, with the clock stopped, until Enter is pressed.
+    pub briefing: bool,
+    /// End a run as failed once its keys pass the grade F threshold, see
+    /// [`Challenge::has_key_budget`].
+    pub hardcore: bool,
+    /// File whose first line is shown in the bar while it plays, such as the
+    /// news that a duel opponent finished. Nothing is shown while it is missing.
+    pub banner: Option<PathBuf>,
+}
+
+/// What the challenge runtime does with shell commands typed as `:!cmd`,
+/// `:r !cmd`, `:w !cmd` or a `:{range}!cmd` filter. Commands that run are
+/// listed in the results either way.
+///
+/// This is best effort: only the command line as typed is checked, so shell
+/// commands run through `:execute`, `system()`, `:terminal`, Lua or a `|` after
+/// another command are neither refused nor listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ShellPolicy {
+    #[serde(default)]
+    pub mode: ShellMode,
+    /// Programs that may run in `whitelist` mode, e.g. `["sort", "column"]`.
+    #[serde(default)]
+    pub allowed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellMode {
+    /// Run every shell command.
+    #[default]
+    Log,
+    /// Refuse every shell command typed on the command line.
+    Discourage,
+    /// Refuse shell commands typed on the command line that run other programs
+    /// than those in `allowed`.
+    Whitelist,
+}
+
+impl ShellPolicy {
+    /// The programs that may run, or `None` when any may.
+    pub fn allowed_programs(&self) -> Option<&[String]> {
+        match self.mode {
+            ShellMode::Log => None,
+            ShellMode::Discourage => Some(&[]),
+            ShellMode::Whitelist => Some(&self.allowed),
+        }
+    }
+}
+
+/// An editor release, e.g. `0.10.2` for neovim or `9.1.0` for Vim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Read the version from the first `--version` line, e.g. `NVIM v0.10.2`,
+    /// `NVIM v0.11.0-dev-1234+gabcdef` or `VIM - Vi IMproved 9.1 (2024 Jan 02)`.
+    /// A missing patch number is 0.
+    pub fn parse(line: &str) -> Option<Self> {
+        let word = line.split_whitespace().find_map(|word| {
+            let word = word.strip_prefix('v').unwrap_or(word);
+            word.starts_with(|c: char| c.is_ascii_digit())
+                .then_some(word)
+        })?;
+        let mut numbers = word.split(|c: char| !c.is_ascii_digit());
+        let major = numbers.next()?.parse().ok()?;
+        let minor = numbers.next()?.parse().ok()?;
+        let patch = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// An editor hosting challenge sessions. A session shows the target above the
+/// challenge buffer with the differences highlighted, and is responsible for:
+///
+/// - logging every key typed, in `keytrans()` notation, and counting them,
+/// - detecting completion: the buffer matches the target (and its cursor, if
+///   set), or every tutorial step was typed,
+/// - ending early on `:w`, leaving the keys of the write command out of the count.
+pub trait Editor {
+    /// Play a challenge. Returns the result after the editor exits.
+    fn run_challenge(&self, challenge: &Challenge, number: usize)
+    -> Result<ChallengeResult, Error>;
+
+    /// The files and command [`Editor::run_challenge`] uses for a session in
+    /// `dir`, without writing or running anything.
+    fn plan_challenge(&self, challenge: &Challenge, number: usize, dir: &Path) -> LaunchPlan;
+
+    /// Open the start content of `challenge`, or a blank buffer, without a
+    /// target, grading or results.
+    fn run_sandbox(&self, challenge: Option<&Challenge>) -> Result<(), Error>;
+}
+
+/// What launching a challenge session takes: the files written to its session
+/// directory and the editor command run on them.
+#[derive(Debug)]
+pub struct LaunchPlan {
+    /// Files in the order they are written, with their contents.
+    pub files: Vec<(PathBuf, String)>,
+    pub command: Command,
+    /// Where the editor's stderr goes, for the error when it fails.
+    pub stderr: PathBuf,
+}
+
+impl LaunchPlan {
+    /// Write the files and run the editor, waiting for it to exit.
+    pub fn run(mut self, launcher: &Launcher) -> Result<(), Error> {
+        for (path, content) in &self.files {
+            nvim::write_session_file(path, content)?;
+        }
+        launcher.run_logged(&mut self.command, &self.stderr)
+    }
+
+    /// The command as it would be typed in a POSIX shell.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.command.get_program())
+            .chain(self.command.get_args())
+            .map(|arg| shell_quote(&arg.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Quote `arg` for a POSIX shell, leaving plain words as they are.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// The editor `launcher` starts.
+pub fn editor(launcher: &Launcher) -> Box<dyn Editor + '_> {
+    match launcher.editor {
+        EditorKind::Neovim => Box::new(Neovim(launcher)),
+        EditorKind::Vim => Box::new(Vim(launcher)),
+    }
+}
+
+/// What to try after a session of `launcher` failed to start with `error`, with
+/// the ID of the challenge it was playing, if any, for `play --dry-run`.
+pub fn launch_hints(launcher: &Launcher, challenge: Option<&str>, error: &Error) -> Vec<String> {
+    let name = launcher.editor.name();
+    let bin = &launcher.bin;
+    match error {
+        Error::Nvim(NvimError::NotFound { .. }) => {
+            return vec![format!(
+                "{name} ('{bin}') was not found; install it, or point nvim_bin in \
+                 config.toml or --nvim-bin at it"
+            )];
+        }
+        // Nothing the editor setup can fix
+        Error::Config(_)
+        | Error::Curriculum(_)
+        | Error::Session(_)
+        | Error::State(_)
+        | Error::Terminal(_) => {
+            return Vec::new();
+        }
+        Error::Nvim(
+            NvimError::Unusable { .. } | NvimError::Spawn { .. } | NvimError::Exited { .. },
+        ) => {}
+    }
+    let mut hints = Vec::new();
+    if !launcher.args.iter().any(|a| a == "--clean" || a == "-u") {
+        hints.push(format!(
+            "Errors in your {name} config or plugins can end a session early; add \
+             \"--clean\" to nvim_args in config.toml to start without them"
+        ));
+    }
+    if let Some(id) = challenge {
+        hints.push(format!(
+            "'nvimkata play --dry-run {id}' prints the command line and runtime script \
+             to run by hand"
+        ));
+    }
+    hints.push(format!(
+        "'{bin} --version' checks that {name} itself starts"
+    ));
+    hints
+}
+
+/// Run the launcher's binary with `--version` and return the detected version.
+/// Fails with a message saying how to fix it when the binary is missing, is not
+/// the configured editor or is older than [`EditorKind::min_version`].
+pub fn check_version(launcher: &Launcher) -> Result<Version, String> {
+    let name = launcher.editor.name();
+    let bin = &launcher.bin;
+    let output = Command::new(bin).arg("--version").output().map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            format!(
+                "{name} ('{bin}') was not found; install it, or point nvim_bin in \
+                 config.toml or --nvim-bin at it"
+            )
+        } else {
+            format!("failed to run '{bin} --version': {e}")
+        }
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next().unwrap_or("").trim();
+    if !output.status.success() || !line.starts_with(launcher.editor.version_prefix()) {
+        return Err(format!(
+            "'{bin}' does not look like {name}: '{bin} --version' printed '{line}'"
+        ));
+    }
+    let version = Version::parse(line)
+        .ok_or_else(|| format!("could not read the {name} version from '{line}'"))?;
+    let min = launcher.editor.min_version();
+    if version < min {
+        return Err(format!(
+            "{name} {version} ('{bin}') is too old; nvimkata needs {min} or newer"
+        ));
+    }
+    Ok(version)
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use crate::challenge::{Challenge, Topic};
+use crate::config::Config;
+use crate::curriculum::{self, LoadStats};
+use crate::editor;
+use crate::error::{CurriculumError, Error, NvimError, StateError};
+use crate::nvim::{ChallengeResult, EmbeddedSession, Launcher};
+use crate::pack;
+use crate::state::{self, GameState};
+
+/// What [`Nvimkata::load`] loads. The defaults are those of bare `nvimkata`
+/// without a config file.
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub config: Config,
+    /// Challenges directory instead of the configured or bundled one.
+    pub challenges_dir: Option<PathBuf>,
+    /// Save profile instead of the configured one.
+    pub profile: Option<String>,
+    /// Load the installed packs after the curriculum.
+    pub packs: bool,
+    /// Read and update the challenge cache.
+    pub cache: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            config: Config::default(),
+            challenges_dir: None,
+            profile: None,
+            packs: true,
+            cache: true,
+        }
+    }
+}
+
+/// The engine behind the TUI, for embedding it in other frontends: the loaded
+/// challenges, a save and the editor to play them in.
+///
+/// Part of the config holds for the whole process, so all engines of a process
+/// must share those settings; in practice, only one engine can exist per
+/// process.
+pub struct Nvimkata {
+    topics: Vec<Topic>,
+    load: LoadStats,
+    state: GameState,
+    launcher: Launcher,
+}
+
+impl Nvimkata {
+    /// Load the challenges and the save. Applies the process-wide settings of
+    /// the config, see [`Config::apply`], and fails with [`Error::Config`] when
+    /// an earlier engine of this process applied different ones.
+    pub fn load(options: LoadOptions) -> Result<Self, Error> {
+        let config = options.config;
+        config.apply()?;
+        let dir = options
+            .challenges_dir
+            .unwrap_or_else(|| config.challenges_path());
+        let packs = if options.packs {
+            pack::installed(&pack::packs_dir())
+        } else {
+            Vec::new()
+        };
+        let cache = options.cache.then(curriculum::cache_path);
+        let (topics, load) = curriculum::load_curriculum_with(&dir, &packs, cache.as_deref());
+        if topics.iter().all(|t| t.challenges.is_empty()) {
+            return Err(CurriculumError::Empty { dir }.into());
+        }
+
+        let profile = options.profile.or_else(|| config.profile.clone());
+        if let Some(name) = profile.as_deref().filter(|p| !state::is_valid_profile(p)) {
+            return Err(StateError::InvalidProfile {
+                name: name.to_string(),
+            }
+            .into());
+        }
+        let mut state = GameState::load_profile(profile.as_deref())?;
+        let challenges: Vec<Challenge> = topics
+            .iter()
+            .flat_map(|t| t.challenges.iter().cloned())
+            .collect();
+        state.mark_stale(&challenges);
+        state.mark_ambiguous(&topics);
+
+        Ok(Self {
+            topics,
+            load,
+            state,
+            launcher: config.launcher(),
+        })
+    }
+
+    /// The curriculum topics followed by the installed packs.
+    pub fn topics(&self) -> &[Topic] {
+        &self.topics
+    }
+
+    /// Files that failed to load or were partly ignored, and content warnings.
+    pub fn load_stats(&self) -> &LoadStats {
+        &self.load
+    }
+
+    /// The challenge with `id` and its display number, counting from 1
+    /// across all topics.
+    pub fn challenge(&self, id: &str) -> Option<(usize, &Challenge)> {
+        find(&self.topics, id)
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// The save, for recording changes made outside [`Self::play`] and
+    /// [`Self::finish`]. They are kept with [`Self::save`].
+    pub fn state_mut(&mut self) -> &mut GameState {
+        &mut self.state
+    }
+
+    pub fn launcher(&self) -> &Launcher {
+        &self.launcher
+    }
+
+    /// Play challenge `id` in the configured editor, which takes over the
+    /// terminal until it exits. The run is recorded and the save written.
+    pub fn play(&mut self, id: &str) -> Result<ChallengeResult, Error> {
+        if self.launcher.version.is_none() {
+            let version = editor::check_version(&self.launcher)
+                .map_err(|message| NvimError::Unusable { message })?;
+            self.launcher.version = Some(version);
+        }
+        let (number, challenge) = find(&self.topics, id).ok_or_else(|| unknown(id))?;
+        let challenge = challenge.loaded()?;
+        let result = editor::editor(&self.launcher).run_challenge(&challenge, number)?;
+        self.state.record_session(&challenge, &result);
+        self.state.save()?;
+        Ok(result)
+    }
+
+    /// Write the session files of challenge `id` to a new session directory in
+    /// `dir`, for a Neovim that is already running to load, like the plugin
+    /// does. Hand the session to [`Self::finish`] when the challenge ends.
+    pub fn embed(&self, id: &str, dir: &Path) -> Result<EmbeddedSession, Error> {
+        let (number, challenge) = find(&self.topics, id).ok_or_else(|| unknown(id))?;
+        let challenge = challenge.loaded()?;
+        Ok(EmbeddedSession::create(
+            dir,
+            &challenge,
+            number,
+            &self.launcher.session,
+        )?)
+    }
+
+    /// Evaluate an embedded session of challenge `id`, given the final lines
+    /// of its buffer. The run is recorded and the save written.
+    pub fn finish(
+        &mut self,
+        id: &str,
+        session: &EmbeddedSession,
+        lines: &[String],
+    ) -> Result<ChallengeResult, Error> {
+        let (_, challenge) = find(&self.topics, id).ok_or_else(|| unknown(id))?;
+        let challenge = challenge.loaded()?;
+        let result = session.finish(&challenge, lines);
+        self.state.record_session(&challenge, &result);
+        self.state.save()?;
+        Ok(result)
+    }
+
+    pub fn save(&self) -> Result<(), StateError> {
+        self.state.save()
+    }
+}
+
+fn find<'a>(topics: &'a [Topic], id: &str) -> Option<(usize, &'a Challenge)> {
+    topics
+        .iter()
+        .flat_map(|t| t.challenges.iter())
+        .enumerate()
+        .find(|(_, c)| c.id == id)
+        .map(|(i, c)| (i + 1, c))
+}
+
+fn unknown(id: &str) -> Error {
+    CurriculumError::UnknownChallenge { id: id.to_string() }.into()
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use crate::editor::Version;
+use crate::state::SaveError;
+
+/// Why an operation of the library failed, by the part that failed.
+#[derive(Debug)]
+pub enum Error {
+    Config(ConfigError),
+    Curriculum(CurriculumError),
+    Session(SessionError),
+    State(StateError),
+    Nvim(NvimError),
+    /// Drawing the TUI or reading keys failed.
+    Terminal(io::Error),
+}
+
+/// The challenges could not be loaded, or a challenge could not be read back
+/// for playing.
+#[derive(Debug)]
+pub enum CurriculumError {
+    /// No challenges were found in `dir` and the installed packs.
+    Empty {
+        dir: PathBuf,
+    },
+    UnknownChallenge {
+        id: String,
+    },
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+/// A config was applied to a process that already runs with another value of
+/// one of its process-wide settings, see [`crate::config::Config::apply`].
+#[derive(Debug)]
+pub struct ConfigError {
+    /// The config key of the setting, e.g. `state_dir`.
+    pub setting: &'static str,
+}
+
+/// A session directory or one of its files could not be written or read.
+#[derive(Debug)]
+pub struct SessionError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+/// The save file could not be loaded or written.
+#[derive(Debug)]
+pub enum StateError {
+    /// Profile names may only use ASCII letters, digits, `-` and `_`.
+    InvalidProfile {
+        name: String,
+    },
+    Load(SaveError),
+    Write {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+/// The editor could not be started, or exited unsuccessfully.
+#[derive(Debug)]
+pub enum NvimError {
+    /// `bin` is not installed, or not in `PATH`.
+    NotFound {
+        bin: String,
+    },
+    /// `bin` is not a supported release of the editor, see
+    /// [`crate::editor::check_version`].
+    Unusable {
+        message: String,
+    },
+    Spawn {
+        bin: String,
+        source: io::Error,
+    },
+    Exited {
+        /// Default binary name of the editor, `nvim` or `vim`.
+        editor: &'static str,
+        version: Option<Version>,
+        status: ExitStatus,
+        /// The last lines the editor wrote to stderr, without blank lines.
+        stderr: Vec<String>,
+    },
+}
+
+impl SessionError {
+    /// Wrap an I/O error on `path`, for `map_err`.
+    pub fn at(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl NvimError {
+    /// Error for `bin` failing to start.
+    pub fn spawn(bin: &str, source: io::Error) -> Self {
+        if source.kind() == io::ErrorKind::NotFound {
+            Self::NotFound {
+                bin: bin.to_string(),
+            }
+        } else {
+            Self::Spawn {
+                bin: bin.to_string(),
+                source,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(e) => e.fmt(f),
+            Self::Curriculum(e) => e.fmt(f),
+            Self::Session(e) => e.fmt(f),
+            Self::State(e) => e.fmt(f),
+            Self::Nvim(e) => e.fmt(f),
+            Self::Terminal(e) => write!(f, "terminal: {e}"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is already set to another value in this process; only one config \
+             can be applied per process",
+            self.setting
+        )
+    }
+}
+
+impl fmt::Display for CurriculumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty { dir } => write!(
+                f,
+                "no challenges found in {}, make sure the 'challenges/' directory exists",
+                dir.display()
+            ),
+            Self::UnknownChallenge { id } => write!(f, "no challenge with id '{id}'"),
+            Self::Read { path, source } => {
+                write!(f, "failed to read {}: {source}", path.display())
+            }
+            Self::Parse { path, source } => {
+                write!(f, "failed to parse {}: {source}", path.display())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "session file {}: {}", self.path.display(), self.source)
+    }
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProfile { name } => write!(
+                f,
+                "invalid profile name '{name}' (use letters, digits, '-' and '_')"
+            ),
+            Self::Load(e) => e.fmt(f),
+            Self::Write { path, source } => {
+                write!(
+                    f,
+                    "failed to write save file '{}': {source}",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for NvimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { bin } => write!(f, "'{bin}' was not found"),
+            Self::Unusable { message } => f.write_str(message),
+            Self::Spawn { bin, source } => write!(f, "failed to start '{bin}': {source}"),
+            Self::Exited {
+                editor,
+                version,
+                status,
+                stderr,
+            } => {
+                write!(f, "{editor}")?;
+                if let Some(version) = version {
+                    write!(f, " {version}")?;
+                }
+                write!(f, " exited with status: {status}")?;
+                for line in stderr {
+                    write!(f, "\n{line}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(e) => Some(e),
+            Self::Curriculum(e) => Some(e),
+            Self::Session(e) => Some(e),
+            Self::State(e) => Some(e),
+            Self::Nvim(e) => Some(e),
+            Self::Terminal(e) => Some(e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl std::error::Error for CurriculumError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source),
+            Self::Empty { .. } | Self::UnknownChallenge { .. } => None,
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl std::error::Error for StateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Load(e) => Some(e),
+            Self::Write { source, .. } => Some(source),
+            Self::InvalidProfile { .. } => None,
+        }
+    }
+}
+
+impl std::error::Error for NvimError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Spawn { source, .. } => Some(source),
+            Self::NotFound { .. } | Self::Unusable { .. } | Self::Exited { .. } => None,
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
+
+impl From<CurriculumError> for Error {
+    fn from(e: CurriculumError) -> Self {
+        Self::Curriculum(e)
+    }
+}
+
+impl From<SessionError> for Error {
+    fn from(e: SessionError) -> Self {
+        Self::Session(e)
+    }
+}
+
+impl From<StateError> for Error {
+    fn from(e: StateError) -> Self {
+        Self::State(e)
+    }
+}
+
+impl From<SaveError> for StateError {
+    fn from(e: SaveError) -> Self {
+        Self::Load(e)
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(e: SaveError) -> Self {
+        Self::State(e.into())
+    }
+}
+
+impl From<NvimError> for Error {
+    fn from(e: NvimError) -> Self {
+        Self::Nvim(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Terminal(e)
+    }
+}
+
+/// For the TUI screens, which fail with [`io::Error`]: terminal errors come
+/// back out as they went in.
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Terminal(e) => e,
+            e => io::Error::other(e),
+        }
+    }
+}
+
+impl From<CurriculumError> for io::Error {
+    fn from(e: CurriculumError) -> Self {
+        Error::from(e).into()
+    }
+}
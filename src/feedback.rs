@@ -0,0 +1,88 @@
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// What a finished challenge run is announced as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackEvent {
+    Completed,
+    /// Completed with fewer keystrokes than any earlier run.
+    NewBest,
+    Failed,
+}
+
+impl FeedbackEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::NewBest => "new_best",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Bell and shell commands run when a challenge ends, from the `[feedback]`
+/// table of the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Feedback {
+    /// Ring the terminal bell on every event.
+    #[serde(default)]
+    pub bell: bool,
+    #[serde(default)]
+    pub completed: Option<String>,
+    /// Falls back to `completed` when unset.
+    #[serde(default)]
+    pub new_best: Option<String>,
+    #[serde(default)]
+    pub failed: Option<String>,
+}
+
+impl Feedback {
+    /// Shell command configured for `event`, if any.
+    pub fn command(&self, event: FeedbackEvent) -> Option<&str> {
+        match event {
+            FeedbackEvent::Completed => self.completed.as_deref(),
+            FeedbackEvent::NewBest => self.new_best.as_deref().or(self.completed.as_deref()),
+            FeedbackEvent::Failed => self.failed.as_deref(),
+        }
+    }
+}
+
+static ACTIVE: OnceLock<Feedback> = OnceLock::new();
+
+/// Select the feedback for the rest of the session. Only the first call has an effect.
+pub fn set(feedback: Feedback) {
+    let _ = ACTIVE.set(feedback);
+}
+
+/// Ring the bell and start the command for `event` in the background. The
+/// command runs with `sh -c` and gets `NVIMKATA_EVENT` and `NVIMKATA_CHALLENGE`
+/// in its environment; its output is discarded and failures are ignored, so a
+/// broken hook never gets in the way of playing.
+pub fn notify(event: FeedbackEvent, challenge_id: &str) {
+    let Some(feedback) = ACTIVE.get() else {
+        return;
+    };
+    if feedback.bell {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+    }
+    let Some(command) = feedback.command(event) else {
+        return;
+    };
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("NVIMKATA_EVENT", event.name())
+        .env("NVIMKATA_CHALLENGE", challenge_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        // Reap the process without holding up the result screen
+        std::thread::spawn(move || child.wait());
+    }
+}
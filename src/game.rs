@@ -1,23 +1,44 @@
 use ratatui::Frame;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use ratatui::layout::{Constraint, Layout};
+use ratatui::crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Margin};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use std::borrow::Cow;
 use std::time::Duration;
 
-use crate::challenge::{Category, Grade, Topic, grade_display};
+use crate::animation;
+use crate::challenge::{BufferContent, Category, Challenge, Grade, Topic, grade_display};
+use crate::commands::{self, Command, CommandKind};
+use crate::compare::{self, Segment};
+use crate::diff::{self, DiffOp};
+use crate::editor;
+use crate::feedback::{self, FeedbackEvent};
+use crate::goals;
+use crate::history;
+use crate::intro;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::notation::{self, Key};
 use crate::nvim;
-use crate::state::GameState;
+use crate::opponents;
+use crate::preview;
+use crate::screen::{self, NarrowLayout};
+use crate::state::{AttemptRecord, ChallengeNote, GameState, VersionContent};
+use crate::stepper;
+use crate::theme;
+use crate::unlock;
 
 /// Run the challenge picker for a topic. Lets user select and play individual challenges.
 /// `challenge_offset` is the number of challenges in all preceding topics, used for
 /// globally unique display numbers.
 pub fn run_challenge_picker(
     terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
     state: &mut GameState,
     topic: &Topic,
     challenge_offset: usize,
+    unlock_all: bool,
 ) -> std::io::Result<()> {
     if topic.challenges.is_empty() {
         return Ok(());
@@ -25,158 +46,252 @@ pub fn run_challenge_picker(
 
     let mut list_state = ListState::default();
     list_state.select(Some(0));
-    let mut pending_g = false;
+    let mut pending: Vec<KeyPress> = Vec::new();
     let mut count: Option<u32> = None;
     let mut list_height: u16 = 0;
+    let mut detail_scroll: u16 = 0;
+    let mut show_times = false;
+    // Play every challenge blind, not only those marked `blind`
+    let mut blind = false;
+    let mut narrow = NarrowLayout::default();
+    // The selected challenge with its buffers, read once per selection
+    let mut detail: Option<(usize, Result<Challenge, String>)> = None;
 
     loop {
+        let selected = list_state.selected();
+        detail = detail
+            .filter(|(shown, _)| Some(*shown) == selected)
+            .or_else(|| selected.map(|i| (i, with_buffers(&topic.challenges[i]))));
         terminal.draw(|frame| {
-            render_picker(frame, topic, state, &mut list_state, &mut list_height);
+            render_picker(
+                frame,
+                topic,
+                detail.as_ref(),
+                state,
+                keymap,
+                &mut list_state,
+                &mut list_height,
+                &mut detail_scroll,
+                unlock_all,
+                show_times,
+                blind,
+                narrow,
+            );
+            theme::apply(frame.buffer_mut());
         })?;
 
-        if event::poll(Duration::from_millis(100))?
-            && let Event::Key(key) = event::read()?
-        {
+        if let Some(key) = screen::poll_key(terminal)? {
             if key.kind != KeyEventKind::Press {
                 continue;
             }
 
             let len = topic.challenges.len();
 
-            // Handle pending gg
-            if pending_g {
-                pending_g = false;
-                count = None;
-                if key.code == KeyCode::Char('g') {
-                    list_state.select(Some(0));
-                    continue;
-                }
+            // Count prefix (applied to up/down)
+            if pending.is_empty() && count_prefix(&mut count, key.code) {
+                continue;
             }
 
-            // Count prefix (applied to j/k)
-            match key.code {
-                KeyCode::Char(c @ '1'..='9') => {
-                    count = Some(count.unwrap_or(0) * 10 + (c as u32 - '0' as u32));
-                    continue;
+            let Some(action) = keymap.resolve(&mut pending, key.into()) else {
+                if pending.is_empty() {
+                    count = None;
                 }
-                KeyCode::Char('0') if count.is_some() => {
-                    count = count.map(|c| c * 10);
-                    continue;
-                }
-                _ => {}
-            }
-
+                continue;
+            };
             let n = count.unwrap_or(1) as usize;
             count = None;
+            let half = (list_height / 2).max(1) as usize;
 
-            match key.code {
-                KeyCode::Char('q' | 'h') | KeyCode::Esc => {
-                    return Ok(());
-                }
-                KeyCode::Char('j') => {
-                    if let Some(mut i) = list_state.selected() {
-                        for _ in 0..n {
-                            i = (i + 1) % len;
-                        }
-                        list_state.select(Some(i));
-                    }
-                }
-                KeyCode::Char('k') => {
-                    if let Some(mut i) = list_state.selected() {
-                        for _ in 0..n {
-                            i = if i == 0 { len - 1 } else { i - 1 };
-                        }
-                        list_state.select(Some(i));
-                    }
-                }
-                KeyCode::Char('g') => pending_g = true,
-                KeyCode::Char('G') => list_state.select(Some(len - 1)),
-                KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    if let Some(mut i) = list_state.selected() {
-                        let half = (list_height / 2).max(1) as usize;
-                        for _ in 0..half {
-                            i = (i + 1) % len;
-                        }
-                        list_state.select(Some(i));
+            match action {
+                Action::Quit | Action::Back => return Ok(()),
+                Action::Down => move_selection(&mut list_state, len, n, true),
+                Action::Up => move_selection(&mut list_state, len, n, false),
+                Action::Top => list_state.select(Some(0)),
+                Action::Bottom => list_state.select(Some(len - 1)),
+                Action::HalfPageDown => move_selection(&mut list_state, len, half, true),
+                Action::HalfPageUp => move_selection(&mut list_state, len, half, false),
+                Action::Select => {
+                    if let Some(i) = list_state.selected()
+                        && unlock::is_challenge_unlocked(&topic.challenges[i], state, unlock_all)
+                    {
+                        let challenge = as_played(&topic.challenges[i], blind);
+                        let number = challenge_offset + i + 1;
+                        play_challenge_loop(terminal, launcher, keymap, state, &challenge, number)?;
                     }
                 }
-                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    if let Some(mut i) = list_state.selected() {
-                        let half = (list_height / 2).max(1) as usize;
-                        for _ in 0..half {
-                            i = if i == 0 { len - 1 } else { i - 1 };
+                Action::Help => show_help(terminal)?,
+                Action::ScrollDown => detail_scroll = detail_scroll.saturating_add(1),
+                Action::ScrollUp => detail_scroll = detail_scroll.saturating_sub(1),
+                // Blind challenges keep the target hidden until played
+                Action::Preview | Action::Compare => {
+                    if let Some(i) = list_state.selected()
+                        && !(blind || topic.challenges[i].blind)
+                    {
+                        let challenge = &*topic.challenges[i].loaded()?;
+                        if action == Action::Preview {
+                            preview::show_preview(terminal, keymap, challenge)?;
+                        } else {
+                            preview::show_comparison(terminal, keymap, challenge)?;
                         }
-                        list_state.select(Some(i));
                     }
                 }
-                KeyCode::Char('l') | KeyCode::Enter => {
-                    if let Some(i) = list_state.selected() {
-                        let challenge = &topic.challenges[i];
-                        let number = challenge_offset + i + 1;
-                        play_challenge_loop(terminal, state, challenge, number)?;
+                Action::History | Action::Bookmark | Action::Reset => {
+                    if let Some(challenge) = list_state.selected().map(|i| &topic.challenges[i]) {
+                        challenge_action(terminal, launcher, keymap, state, challenge, action)?;
                     }
                 }
-                KeyCode::Char('?') => {
-                    show_help(terminal)?;
-                }
+                Action::Times => show_times = !show_times,
+                Action::Blind => blind = !blind,
+                Action::Layout => narrow = narrow.next(),
+                Action::Intro => intro::show_intro(terminal, keymap, topic)?,
                 _ => {}
             }
+            if list_state.selected() != selected {
+                detail_scroll = 0;
+            }
+        }
+    }
+}
+
+/// A challenge with its buffers, for the detail panel, or why they can't be
+/// read when its file broke since startup.
+pub fn with_buffers(challenge: &Challenge) -> Result<Challenge, String> {
+    challenge
+        .loaded()
+        .map(Cow::into_owned)
+        .map_err(|e| e.to_string())
+}
+
+/// A challenge as played from the picker: blind when the picker's blind toggle is on.
+fn as_played(challenge: &Challenge, blind: bool) -> Cow<'_, Challenge> {
+    if blind && !challenge.blind {
+        Cow::Owned(Challenge {
+            blind: true,
+            ..challenge.clone()
+        })
+    } else {
+        Cow::Borrowed(challenge)
+    }
+}
+
+/// Feed a key to a count prefix like the `12` of `12j`. Returns whether the
+/// key was a digit of the count.
+pub fn count_prefix(count: &mut Option<u32>, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char(c @ '1'..='9') => {
+            *count = Some(count.unwrap_or(0) * 10 + (c as u32 - '0' as u32));
+            true
+        }
+        KeyCode::Char('0') if count.is_some() => {
+            *count = count.map(|c| c * 10);
+            true
         }
+        _ => false,
+    }
+}
+
+/// Move the selection `n` steps, wrapping around a list of `len` entries.
+fn move_selection(list_state: &mut ListState, len: usize, n: usize, forward: bool) {
+    if let Some(i) = list_state.selected() {
+        let n = n % len;
+        let i = if forward {
+            (i + n) % len
+        } else {
+            (i + len - n) % len
+        };
+        list_state.select(Some(i));
     }
 }
 
-/// Play a single challenge with retry support.
-fn play_challenge_loop(
+/// Play a single challenge with retry support. Returns the fewest keystrokes
+/// among the solved runs, if any.
+pub fn play_challenge_loop(
     terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
     state: &mut GameState,
     challenge: &crate::challenge::Challenge,
     number: usize,
-) -> std::io::Result<()> {
+) -> std::io::Result<Option<u32>> {
+    let challenge = &*challenge.loaded()?;
     let freestyle = challenge.is_freestyle();
+    let mut best: Option<u32> = None;
     loop {
         ratatui::restore();
-        let result = nvim::run_challenge(challenge, number)?;
+        let result = editor::editor(launcher).run_challenge(challenge, number);
         *terminal = ratatui::init();
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let hints = editor::launch_hints(launcher, Some(&challenge.id), &e);
+                screen::show_error(terminal, "Editor failed", &e.to_string(), &hints)?;
+                return Ok(best);
+            }
+        };
+        // Quitting without finishing is not an attempt; back to the picker
+        if !result.outcome.is_attempt() {
+            return Ok(best);
+        }
+        let solved = result.buffer_matches && result.violation.is_none();
+        state.log_activity(goals::today(), solved, result.elapsed_ms / 1000);
+        if solved {
+            best = Some(best.map_or(result.keystrokes, |b| b.min(result.keystrokes)));
+        }
+        // Key log of the best attempt before this run, to diff against
+        let ghost = state
+            .best_attempt(&challenge.id)
+            .map(|a| a.keys.clone())
+            .filter(|k| !k.is_empty());
 
         if freestyle {
             let personal_best = state.best_keystrokes(&challenge.id);
-            if result.buffer_matches {
+            if solved {
                 state.record_freestyle_result(
                     &challenge.id,
                     result.keystrokes,
-                    result.elapsed_secs,
+                    Some(result.elapsed_ms),
                     &result.keys,
                     &challenge.version,
+                    challenge.reference_keystrokes,
                 );
+                state.record_content(challenge);
             }
 
             let retry = show_result_screen(
                 terminal,
+                launcher,
+                keymap,
+                state,
                 challenge,
                 number,
                 None,
-                result.keystrokes,
-                result.elapsed_secs,
-                result.buffer_matches,
+                &result,
                 personal_best,
+                ghost.as_deref(),
             )?;
 
             state.save().ok();
             if !retry {
-                return Ok(());
+                return Ok(best);
             }
         } else {
+            let personal_best = state.best_keystrokes(&challenge.id);
             // Score
-            let grade = if result.buffer_matches {
-                let grade = challenge.score(result.keystrokes);
-                state.record_result(
+            let grade = if solved {
+                let grade = challenge.score_with_hints(result.keystrokes, result.hints_used);
+                state.record_attempt(
                     &challenge.id,
-                    grade,
-                    result.keystrokes,
-                    result.elapsed_secs,
-                    &result.keys,
+                    AttemptRecord {
+                        grade,
+                        keystrokes: result.keystrokes,
+                        time_ms: Some(result.elapsed_ms),
+                        keys: result.keys.clone(),
+                        technique_missing: result.technique_missing,
+                        timestamp: None,
+                    },
                     &challenge.version,
                 );
+                state.record_content(challenge);
                 Some(grade)
             } else {
                 None
@@ -185,30 +300,40 @@ fn play_challenge_loop(
             // Show result
             let retry = show_result_screen(
                 terminal,
+                launcher,
+                keymap,
+                state,
                 challenge,
                 number,
                 grade,
-                result.keystrokes,
-                result.elapsed_secs,
-                result.buffer_matches,
-                None,
+                &result,
+                personal_best,
+                ghost.as_deref(),
             )?;
 
             state.save().ok();
 
             if !retry {
-                return Ok(());
+                return Ok(best);
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_picker(
     frame: &mut Frame,
     topic: &Topic,
+    detail: Option<&(usize, Result<Challenge, String>)>,
     state: &GameState,
+    keymap: &Keymap,
     list_state: &mut ListState,
     list_height: &mut u16,
+    detail_scroll: &mut u16,
+    unlock_all: bool,
+    show_times: bool,
+    blind: bool,
+    narrow: NarrowLayout,
 ) {
     let cat = Category::for_topic(topic.id);
     let cat_color = cat.color();
@@ -222,7 +347,7 @@ fn render_picker(
     .areas(frame.area());
 
     // Header
-    let title = Paragraph::new(Line::from(vec![
+    let mut title = vec![
         Span::raw(" "),
         Span::styled(
             format!(" {} ", cat.name()),
@@ -233,52 +358,29 @@ fn render_picker(
         ),
         Span::raw(" "),
         Span::styled(&topic.name, Style::new().add_modifier(Modifier::BOLD)),
-    ]))
-    .block(Block::bordered());
+    ];
+    if blind {
+        title.push(Span::styled(" [BLIND]", Style::new().fg(Color::Magenta)));
+    }
+    let title = Paragraph::new(Line::from(title)).block(Block::bordered());
     frame.render_widget(title, header);
 
     frame.render_widget(Paragraph::new(topic_stats_line(topic, state)), stats_area);
 
     // Challenge list
-    let [list_area, detail_area] =
-        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+    let [list_area, detail_area] = screen::split_panels(body, narrow);
 
     *list_height = list_area.height.saturating_sub(2);
 
     let selected = list_state.selected().unwrap_or(0);
     let num_style = Style::new().fg(Color::DarkGray);
-    let is_freestyle = cat == Category::Freestyle;
     let items: Vec<ListItem> = topic
         .challenges
         .iter()
         .enumerate()
         .map(|(n, c)| {
             let num_span = Span::styled(format!("{:>2} ", n.abs_diff(selected)), num_style);
-            let (badge, badge_style) = if is_freestyle {
-                if let Some(best) = state.best_keystrokes(&c.id) {
-                    (format!("[{best}]"), Style::new().fg(Color::Cyan))
-                } else {
-                    ("[-]".to_string(), Style::new().fg(Color::Gray))
-                }
-            } else {
-                let (s, st) = grade_display(state.best_grade(&c.id));
-                (format!("[{s}]"), st)
-            };
-            let title_style = if state.best_grade(&c.id).is_some() {
-                Style::new()
-            } else {
-                Style::new().fg(Color::Gray)
-            };
-            let mut spans = vec![
-                num_span,
-                Span::styled(format!("{badge} "), badge_style),
-                Span::styled(c.title.as_str(), title_style),
-            ];
-            if state.is_stale(&c.id) {
-                spans.push(Span::styled(" *", Style::new().fg(Color::Yellow)));
-            }
-            let text = Line::from(spans);
-            ListItem::new(text)
+            picker_item(c, state, unlock_all, show_times, num_span)
         })
         .collect();
 
@@ -294,19 +396,187 @@ fn render_picker(
     frame.render_stateful_widget(list, list_area, list_state);
 
     // Detail panel for selected challenge
-    if let Some(i) = list_state.selected() {
-        let challenge = &topic.challenges[i];
-        render_challenge_detail(frame, detail_area, challenge, state);
+    if let Some((i, loaded)) = detail {
+        let (challenge, unreadable) = match loaded {
+            Ok(challenge) => (challenge, None),
+            Err(e) => (&topic.challenges[*i], Some(e.as_str())),
+        };
+        render_challenge_detail(
+            frame,
+            detail_area,
+            challenge,
+            unreadable,
+            state,
+            detail_scroll,
+            unlock_all,
+            blind || challenge.blind,
+        );
     }
 
     // Footer
     frame.render_widget(
-        Paragraph::new(" j/k: navigate | l/Enter: play | ?: help | h/q: back")
-            .style(Style::new().fg(Color::DarkGray)),
+        Paragraph::new(picker_footer(
+            keymap,
+            body.width < screen::NARROW_WIDTH,
+            topic.intro.is_some(),
+        ))
+        .style(Style::new().fg(Color::DarkGray)),
         footer,
     );
 }
 
+/// One row of the challenge list: grade or keystroke badge, optionally the
+/// best time, title and markers.
+fn picker_item<'a>(
+    c: &'a Challenge,
+    state: &GameState,
+    unlock_all: bool,
+    show_time: bool,
+    num_span: Span<'a>,
+) -> ListItem<'a> {
+    if !unlock::is_challenge_unlocked(c, state, unlock_all) {
+        return ListItem::new(Line::from(vec![
+            num_span,
+            Span::styled(format!("[x] {}", c.title), Style::new().fg(Color::DarkGray)),
+        ]));
+    }
+    let (badge, badge_style) = if c.is_freestyle() {
+        if let Some(best) = state.best_keystrokes(&c.id) {
+            if state.beat_reference(&c.id) {
+                (format!("[{best}★]"), Grade::A.style())
+            } else {
+                (format!("[{best}]"), Style::new().fg(Color::Cyan))
+            }
+        } else {
+            ("[-]".to_string(), Style::new().fg(Color::Gray))
+        }
+    } else {
+        let (s, st) = best_grade_display(state, &c.id);
+        (format!("[{s}]"), st)
+    };
+    let title_style = if state.best_grade(&c.id).is_some() {
+        Style::new()
+    } else {
+        Style::new().fg(Color::Gray)
+    };
+    let mut spans = vec![num_span, Span::styled(format!("{badge} "), badge_style)];
+    if show_time {
+        let time = state
+            .best_time(&c.id)
+            .map_or_else(|| "--:--.---".to_string(), format_time);
+        spans.push(Span::styled(
+            format!("{time} "),
+            Style::new().fg(Color::Cyan),
+        ));
+    }
+    spans.push(Span::styled(c.title.as_str(), title_style));
+    if state.technique_missing(&c.id) {
+        spans.push(Span::styled(" !", Style::new().fg(Color::Yellow)));
+    }
+    if state.is_favorite(&c.id) {
+        spans.push(Span::styled(" ♥", Style::new().fg(Color::Magenta)));
+    }
+    if state.is_stale(&c.id) {
+        spans.push(Span::styled(" *", Style::new().fg(Color::Yellow)));
+    }
+    if state.is_decayed(&c.id) {
+        spans.push(Span::styled(" ~", DECAYED));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Style of decayed grades and their ` ~` marker: dimmed, apart from the
+/// yellow ` *` of outdated scores.
+pub const DECAYED: Style = Style::new().fg(Color::DarkGray);
+
+/// The best grade of a challenge as listed, dimmed once it has decayed.
+pub fn best_grade_display(state: &GameState, challenge_id: &str) -> (&'static str, Style) {
+    let (grade, style) = grade_display(state.best_grade(challenge_id));
+    if state.is_decayed(challenge_id) {
+        (grade, DECAYED)
+    } else {
+        (grade, style)
+    }
+}
+
+/// Picker actions on the selected `challenge` that come back to the picker:
+/// its history, bookmark, or a reset of its progress once confirmed.
+fn challenge_action(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    state: &mut GameState,
+    challenge: &Challenge,
+    action: Action,
+) -> std::io::Result<()> {
+    match action {
+        Action::History => history::show_history(terminal, launcher, keymap, state, challenge)?,
+        Action::Bookmark => {
+            state.toggle_favorite(&challenge.id);
+            state.save().ok();
+        }
+        Action::Reset => {
+            let question = format!(
+                "Reset \"{}\"? Its best result and attempts are deleted; the note and \
+                 bookmark stay.",
+                challenge.title
+            );
+            if screen::confirm(terminal, &question)? {
+                state.reset_challenge(&challenge.id);
+                state.save().ok();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Milliseconds as `mm:ss.mmm`.
+pub fn format_time(ms: u32) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}.{:03}", secs / 60, secs % 60, ms % 1000)
+}
+
+/// Time of a recorded run as [`format_time`], or `untimed`.
+pub fn format_run_time(ms: Option<u32>) -> String {
+    ms.map_or_else(|| "untimed".to_string(), format_time)
+}
+
+/// Key hints for the picker footer, with the layout key on `narrow` terminals
+/// and the intro key for topics with an intro.
+fn picker_footer(keymap: &Keymap, narrow: bool, intro: bool) -> String {
+    let layout = if narrow {
+        format!(" | {}: layout", keymap.label(Action::Layout))
+    } else {
+        String::new()
+    };
+    let intro = if intro {
+        format!(" | {}: intro", keymap.label(Action::Intro))
+    } else {
+        String::new()
+    };
+    format!(
+        " {}/{}: navigate | {}: play | {}/{}: scroll | {}: preview | {}: compare \
+         | {}: history | {}: bookmark | {}: times | {}: blind | {}: reset{intro}{layout} | {}: help \
+         | {}/{}: back",
+        keymap.label(Action::Down),
+        keymap.label(Action::Up),
+        keymap.label(Action::Select),
+        keymap.label(Action::ScrollDown),
+        keymap.label(Action::ScrollUp),
+        keymap.label(Action::Preview),
+        keymap.label(Action::Compare),
+        keymap.label(Action::History),
+        keymap.label(Action::Bookmark),
+        keymap.label(Action::Times),
+        keymap.label(Action::Blind),
+        keymap.label(Action::Reset),
+        keymap.label(Action::Help),
+        keymap.label(Action::Back),
+        keymap.label(Action::Quit),
+    )
+}
+
 fn topic_stats_line<'a>(topic: &Topic, state: &GameState) -> Line<'a> {
     let attempted = topic
         .challenges
@@ -324,6 +594,11 @@ fn topic_stats_line<'a>(topic: &Topic, state: &GameState) -> Line<'a> {
         .iter()
         .filter(|c| state.is_stale(&c.id))
         .count();
+    let decayed = topic
+        .challenges
+        .iter()
+        .filter(|c| state.is_decayed(&c.id))
+        .count();
     let attempts: usize = topic
         .challenges
         .iter()
@@ -341,17 +616,69 @@ fn topic_stats_line<'a>(topic: &Topic, state: &GameState) -> Line<'a> {
             Style::new().fg(Color::Yellow),
         ));
     }
+    if decayed > 0 {
+        spans.push(Span::styled(
+            format!(" | {decayed} grade(s) decayed ~"),
+            Style::new().fg(Color::Gray),
+        ));
+    }
     Line::from(spans)
 }
 
-fn render_challenge_detail(
+/// Top 3 attempts with key presses, preceded by a blank line.
+fn attempt_lines(challenge: &crate::challenge::Challenge, state: &GameState) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    if let Some(history) = state.history.get(&challenge.id)
+        && !history.is_empty()
+    {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Top attempts:",
+            Style::new().fg(Color::Yellow),
+        )));
+        for (i, attempt) in history.iter().take(3).enumerate() {
+            let (label, style) = grade_display(Some(attempt.grade));
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {}. ", i + 1)),
+                Span::styled(format!("[{label}]"), style),
+                Span::raw(format!(
+                    " {} | {} keys | {}",
+                    attempt.keys,
+                    attempt.keystrokes,
+                    format_run_time(attempt.time_ms)
+                )),
+            ]));
+        }
+    }
+    lines
+}
+
+/// The detail panel of the picker. `unreadable` is why the buffers of a
+/// challenge without them couldn't be read; the panel then leaves out what
+/// needs them.
+#[allow(clippy::too_many_arguments)]
+pub fn render_challenge_detail(
     frame: &mut Frame,
     area: ratatui::layout::Rect,
     challenge: &crate::challenge::Challenge,
+    unreadable: Option<&str>,
     state: &GameState,
+    scroll: &mut u16,
+    unlock_all: bool,
+    blind: bool,
 ) {
     let mut lines = vec![];
 
+    if let Some(description) = &challenge.description {
+        lines.extend(
+            description
+                .trim_end()
+                .lines()
+                .map(|l| Line::from(l.to_string())),
+        );
+        lines.push(Line::from(""));
+    }
+
     // Show focused actions if available
     if let Some(actions) = &challenge.focused_actions {
         let mut spans = vec![Span::styled("Skills: ", Style::new().fg(Color::Gray))];
@@ -368,83 +695,307 @@ fn render_challenge_detail(
         lines.push(Line::from(""));
     }
 
-    if challenge.is_freestyle() {
-        let best_str = state
-            .best_keystrokes(&challenge.id)
-            .map_or("N/A".to_string(), |b| format!("{b} keystrokes"));
-        lines.push(Line::from(format!("Personal best: {best_str}")));
-    } else {
-        lines.push(Line::from(format!(
-            "Par: {} keystrokes",
-            challenge.par_keystrokes
-        )));
-        lines.push(threshold_line(challenge));
+    let missing = unlock::missing_requirements(challenge, state);
+    if !unlock_all && !missing.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Locked: ", Style::new().fg(Color::Gray)),
+            Span::styled(
+                format!("complete {} first", missing.join(", ")),
+                Style::new().fg(Color::Red),
+            ),
+        ]));
+        lines.push(Line::from(""));
     }
 
-    // Top 3 attempts with key presses
-    if let Some(history) = state.history.get(&challenge.id)
-        && !history.is_empty()
-    {
+    let notes = note_lines(state.note(&challenge.id), None, "");
+    if !notes.is_empty() {
+        lines.extend(notes);
         lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled(
-            "Top attempts:",
-            Style::new().fg(Color::Yellow),
-        )));
-        for (i, attempt) in history.iter().take(3).enumerate() {
-            let (label, style) = grade_display(Some(attempt.grade));
-            lines.push(Line::from(vec![
-                Span::raw(format!("  {}. ", i + 1)),
-                Span::styled(format!("[{label}]"), style),
-                Span::raw(format!(
-                    " {} | {} keys | {:02}:{:02}",
-                    attempt.keys,
-                    attempt.keystrokes,
-                    attempt.time_secs / 60,
-                    attempt.time_secs % 60
-                )),
-            ]));
-        }
     }
 
-    // Show target content (truncated to fit remaining space)
-    // Reserve lines for: border(2) + header/blank(2) + "Press ENTER" footer(2)
-    let used = lines.len();
-    let available = area.height.saturating_sub(2) as usize;
-    let remaining = available.saturating_sub(used + 4);
-    let target_lines: Vec<&str> = challenge.target.content.lines().collect();
-    if remaining > 0 {
+    if let Some(error) = unreadable {
+        lines.push(Line::from(vec![
+            Span::styled("File unreadable: ", Style::new().fg(Color::Gray)),
+            Span::styled(error.to_string(), Style::new().fg(Color::Red)),
+        ]));
+        lines.push(Line::from(""));
+    } else {
+        lines.extend(version_lines(challenge, state));
+    }
+    lines.extend(setup_lines(challenge));
+    lines.extend(score_lines(challenge, state));
+
+    lines.extend(attempt_lines(challenge, state));
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press ENTER to start challenge",
+        Style::new().fg(Color::Green),
+    )));
+
+    // Full target content; the panel scrolls independently of the list
+    if unreadable.is_none() {
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
             "Preview:",
             Style::new().add_modifier(Modifier::BOLD),
         )));
-        let show = remaining.min(target_lines.len()).min(20);
-        for (i, line) in target_lines[..show].iter().enumerate() {
-            lines.push(Line::from(vec![
-                Span::styled(format!("{:>3} ", i + 1), Style::new().fg(Color::DarkGray)),
-                Span::styled(*line, Style::new().fg(Color::Gray)),
-            ]));
-        }
-        if target_lines.len() > show {
+        if blind {
             lines.push(Line::from(Span::styled(
-                format!("  ... ({} more lines)", target_lines.len() - show),
-                Style::new().fg(Color::DarkGray),
+                "Blind: the target is hidden until you submit. Work from the hint:",
+                Style::new().fg(Color::Magenta),
             )));
+            lines.extend(
+                challenge
+                    .hint_tiers()
+                    .first()
+                    .map(|hint| Line::from(hint.to_string())),
+            );
+        } else {
+            lines.extend(preview::numbered_lines(&challenge.target().content));
         }
     }
 
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        "Press ENTER to start challenge",
-        Style::new().fg(Color::Green),
-    )));
+    // Estimate rows after wrapping to find how far the panel can scroll
+    let inner_width = usize::from(area.width.saturating_sub(2)).max(1);
+    let rows: usize = lines
+        .iter()
+        .map(|l| l.width().div_ceil(inner_width).max(1))
+        .sum();
+    let visible = usize::from(area.height.saturating_sub(2));
+    let max = u16::try_from(rows.saturating_sub(visible)).unwrap_or(u16::MAX);
+    *scroll = (*scroll).min(max);
+    let title = if max > 0 {
+        format!(" Details [{}/{}] ", *scroll + 1, max + 1)
+    } else {
+        " Details ".to_string()
+    };
 
     let detail = Paragraph::new(lines)
-        .block(Block::bordered().title(" Details "))
-        .wrap(Wrap { trim: false });
+        .block(Block::bordered().title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((*scroll, 0));
     frame.render_widget(detail, area);
 }
 
+/// What changed since the version of an outdated best result: whether the
+/// start buffer did, and a diff of the old target against the current one.
+fn version_lines(challenge: &Challenge, state: &GameState) -> Vec<Line<'static>> {
+    let Some(best) = state.challenges.get(&challenge.id).filter(|b| b.stale) else {
+        return vec![];
+    };
+    let yellow = Style::new().fg(Color::Yellow);
+    let dim = Style::new().fg(Color::Gray);
+    let mut lines = vec![Line::styled(
+        if best.version == challenge.version {
+            format!(
+                "Changed since your best, still version {}",
+                challenge.version
+            )
+        } else {
+            format!(
+                "Changed since your best: version {} -> {}",
+                if best.version.is_empty() {
+                    "?"
+                } else {
+                    &best.version
+                },
+                challenge.version
+            )
+        },
+        yellow,
+    )];
+    let Some(old) = state.best_version_content(&challenge.id) else {
+        lines.push(Line::styled("The old version was not kept.", dim));
+        lines.push(Line::from(""));
+        return lines;
+    };
+    let current = VersionContent::of(challenge);
+    lines.push(Line::styled(
+        match (
+            old.start_hash == current.start_hash,
+            old.target_hash == current.target_hash,
+        ) {
+            (true, true) => "Start and target are the same; only the scoring changed.",
+            (true, false) => "Same start, new target:",
+            (false, true) => "New start buffer, same target.",
+            (false, false) => "New start buffer and target:",
+        },
+        dim,
+    ));
+    if old.target_hash != current.target_hash {
+        let before: Vec<&str> = old.target.lines().collect();
+        let after: Vec<&str> = current.target.lines().collect();
+        for op in diff::diff(&before, &after) {
+            lines.push(match op {
+                DiffOp::Equal(i, _) => Line::styled(format!("  {}", before[i]), dim),
+                DiffOp::Delete(i) => {
+                    Line::styled(format!("- {}", before[i]), Style::new().fg(Color::Red))
+                }
+                DiffOp::Insert(j) => {
+                    Line::styled(format!("+ {}", after[j]), Style::new().fg(Color::Green))
+                }
+            });
+        }
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+/// Cursor and filetype details, when the challenge sets them. The cursor is
+/// left out of a challenge without its buffers.
+fn setup_lines(challenge: &crate::challenge::Challenge) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let fmt_cursor = |buffer: Option<&BufferContent>| {
+        buffer
+            .and_then(|b| b.cursor)
+            .map(|[l, col]| format!("{l}:{col}"))
+    };
+    match (
+        fmt_cursor(challenge.start.as_ref()),
+        fmt_cursor(challenge.target.as_ref()),
+    ) {
+        (None, None) => {}
+        (start, target) => lines.push(Line::from(vec![
+            Span::styled("Cursor: ", Style::new().fg(Color::Gray)),
+            Span::raw(format!(
+                "starts at {} | must end at {}",
+                start.as_deref().unwrap_or("1:1"),
+                target.as_deref().unwrap_or("anywhere")
+            )),
+        ])),
+    }
+
+    if !challenge.setup.is_empty() {
+        let mut parts = vec![];
+        if !challenge.setup.registers.is_empty() {
+            let names: Vec<String> = challenge
+                .setup
+                .registers
+                .keys()
+                .map(|r| format!("\"{r}"))
+                .collect();
+            parts.push(format!("registers {}", names.join(" ")));
+        }
+        if !challenge.setup.marks.is_empty() {
+            let names: Vec<&str> = challenge.setup.marks.keys().map(String::as_str).collect();
+            parts.push(format!("marks {}", names.join(" ")));
+        }
+        if !challenge.setup.options.is_empty() {
+            parts.push(challenge.setup.options.join(" "));
+        }
+        if !challenge.setup.quickfix.is_empty() {
+            parts.push(format!(
+                "quickfix list of {}",
+                challenge.setup.quickfix.len()
+            ));
+        }
+        lines.push(Line::from(vec![
+            Span::styled("Setup: ", Style::new().fg(Color::Gray)),
+            Span::raw(parts.join(" | ")),
+        ]));
+    }
+
+    if let Some(technique) = &challenge.required_technique {
+        lines.push(Line::from(vec![
+            Span::styled("Required: ", Style::new().fg(Color::Gray)),
+            Span::styled(technique.name.clone(), Style::new().fg(Color::Yellow)),
+        ]));
+    }
+
+    if !challenge.forbidden_keys.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Forbidden: ", Style::new().fg(Color::Gray)),
+            Span::styled(
+                challenge.forbidden_keys.join(" "),
+                Style::new().fg(Color::Red),
+            ),
+        ]));
+    }
+
+    if challenge.single_command {
+        lines.push(Line::from(vec![
+            Span::styled("One command: ", Style::new().fg(Color::Gray)),
+            Span::raw("a single :s, :g or :v on the start buffer, graded on its length"),
+        ]));
+    }
+
+    if let Some(spec) = &challenge.recorded_macro {
+        lines.push(Line::from(vec![
+            Span::styled("Macro: ", Style::new().fg(Color::Gray)),
+            Span::raw(format!(
+                "record into \"{} so {} replays finish the job, graded on its length",
+                spec.register, spec.replays
+            )),
+        ]));
+    }
+
+    if let Some(secs) = challenge.reveal_secs {
+        lines.push(Line::from(vec![
+            Span::styled("Dictation: ", Style::new().fg(Color::Gray)),
+            Span::raw(format!("target shown for {secs}s, then from memory")),
+        ]));
+    }
+
+    if let Some(ft) = &challenge.filetype {
+        lines.push(Line::from(vec![
+            Span::styled("Filetype: ", Style::new().fg(Color::Gray)),
+            Span::raw(ft.clone()),
+        ]));
+    }
+    lines
+}
+
+/// Par and grade thresholds, or personal best for freestyle challenges.
+fn score_lines(challenge: &crate::challenge::Challenge, state: &GameState) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    if challenge.is_freestyle() {
+        let best_str = state
+            .best_keystrokes(&challenge.id)
+            .map_or("N/A".to_string(), |b| format!("{b} keystrokes"));
+        if let Some(reference) = challenge.reference_keystrokes {
+            let mut spans = vec![Span::raw(format!(
+                "Reference: {reference} | Your best: {best_str}"
+            ))];
+            if state.beat_reference(&challenge.id) {
+                spans.push(Span::styled(" ★ beat the reference", Grade::A.style()));
+            }
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(format!("Personal best: {best_str}")));
+        }
+    } else {
+        lines.push(Line::from(format!("Par: {} keystrokes", challenge.par())));
+        lines.push(threshold_line(challenge));
+        if challenge.one_hand_keys().is_some() {
+            lines.push(Line::styled(
+                "One-hand variant: a key outside one_hand voids the run",
+                Style::new().fg(Color::Yellow),
+            ));
+        }
+        lines.extend(opponents::lines(
+            challenge,
+            state.best_keystrokes(&challenge.id),
+        ));
+    }
+    if let Some(archived) = state.archived.get(&challenge.id) {
+        let version = if archived.version.is_empty() {
+            String::new()
+        } else {
+            format!(" on version {}", archived.version)
+        };
+        lines.push(Line::styled(
+            format!(
+                "Archived best: {} in {} keystrokes{version}",
+                archived.grade.display_char(),
+                archived.keystrokes
+            ),
+            Style::new().fg(Color::Gray),
+        ));
+    }
+    lines
+}
+
 /// Show the how-to-play help screen. Blocks until any key is pressed.
 pub fn show_help(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
     loop {
@@ -494,7 +1045,33 @@ pub fn show_help(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()>
                     "   F1     Show hint (again for detailed hint)",
                     dim,
                 )),
+                Line::from(Span::styled(
+                    "   F2     Pause (the clock stops until you resume)",
+                    dim,
+                )),
+                Line::from(Span::styled(
+                    "   F3     Give up and watch the perfect moves",
+                    dim,
+                )),
+                Line::from(Span::styled("   F4     Cheat sheet of the topic", dim)),
+                Line::from(Span::styled(
+                    "   F5     Restart from the start buffer, in place",
+                    dim,
+                )),
                 Line::from(Span::styled("   :w     Finish early and submit", dim)),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "   On the result screen, c compares your keys with the perfect moves.",
+                    dim,
+                )),
+                Line::from(Span::styled(
+                    "   In the hub and the challenge list, i shows the intro of a topic.",
+                    dim,
+                )),
+                Line::from(Span::styled(
+                    "   R in the hub looks up commands and the challenges using them.",
+                    dim,
+                )),
             ];
 
             let help = Paragraph::new(lines)
@@ -506,10 +1083,10 @@ pub fn show_help(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()>
                 Paragraph::new(" any key: back").style(Style::new().fg(Color::DarkGray)),
                 footer,
             );
+            theme::apply(frame.buffer_mut());
         })?;
 
-        if event::poll(Duration::from_millis(100))?
-            && let Event::Key(key) = event::read()?
+        if let Some(key) = screen::poll_key(terminal)?
             && key.kind == KeyEventKind::Press
         {
             return Ok(());
@@ -518,100 +1095,663 @@ pub fn show_help(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()>
 }
 
 /// Show the result screen. Returns true if the user wants to retry.
-/// `personal_best` is the previous best keystroke count for freestyle challenges.
+/// `personal_best` is the previous best keystroke count, and `ghost` the key log
+/// of the previous best attempt, diffed against this run. Unless animations are
+/// off, the keystroke bar fills up and the grade climbs to its letter first, with
+/// confetti for a new best; a key press skips to the end. The feedback hooks for
+/// the run are started as the screen opens. `n` edits the
+/// challenge note in place and `1`-`5` rate its difficulty (`0` clears the rating).
+/// `c` compares the run with the perfect moves, when the challenge has them, and
+/// `s` steps through the run a command at a time.
 #[allow(clippy::too_many_arguments)]
 fn show_result_screen(
     terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    state: &mut GameState,
     challenge: &crate::challenge::Challenge,
     number: usize,
     grade: Option<Grade>,
-    keystrokes: u32,
-    elapsed_secs: u32,
-    buffer_matched: bool,
+    result: &nvim::ChallengeResult,
     personal_best: Option<u32>,
+    ghost: Option<&str>,
 ) -> std::io::Result<bool> {
-    let freestyle = challenge.is_freestyle();
+    // Note text being typed, while editing
+    let mut editing: Option<String> = None;
+    let steppable = !result.keys.is_empty();
+    let comparable = steppable
+        && challenge
+            .perfect_moves
+            .as_ref()
+            .is_some_and(|moves| !moves.is_empty());
+    let solved = result.buffer_matches && result.violation.is_none();
+    let new_best = solved && personal_best.is_none_or(|best| result.keystrokes < best);
+    let frames = animation::REVEAL_FRAMES
+        + if new_best {
+            animation::CONFETTI_FRAMES
+        } else {
+            0
+        };
+    let mut clock = animation::Clock::start(frames);
+    let event = match (solved, new_best) {
+        (false, _) => FeedbackEvent::Failed,
+        (true, true) => FeedbackEvent::NewBest,
+        (true, false) => FeedbackEvent::Completed,
+    };
+    feedback::notify(event, &challenge.id);
     loop {
+        let tick = clock.frame();
         terminal.draw(|frame| {
-            let area = frame.area();
-
-            let (status, status_color) = if freestyle {
-                if buffer_matched {
-                    let is_new_best = personal_best.is_none_or(|prev| keystrokes < prev);
-                    if is_new_best {
-                        ("COMPLETED (NEW BEST!)".to_string(), Color::Cyan)
-                    } else {
-                        ("COMPLETED".to_string(), Color::Green)
-                    }
-                } else {
-                    ("FAILED".to_string(), Color::Red)
-                }
-            } else if let Some(g) = grade {
-                let grade_name = match g {
-                    Grade::A => "GRADE A",
-                    Grade::B => "GRADE B",
-                    Grade::C => "GRADE C",
-                    Grade::D => "GRADE D",
-                    Grade::E => "GRADE E",
-                    Grade::F => "GRADE F",
-                };
-                (grade_name.to_string(), g.color())
-            } else {
-                ("FAILED".to_string(), Color::Red)
-            };
-
-            let time_str = format!("{:02}:{:02}", elapsed_secs / 60, elapsed_secs % 60);
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
 
-            let mut lines = vec![
-                Line::from(""),
-                Line::from(Span::styled(
-                    format!(" #{number:03} - {}", challenge.title),
-                    Style::new().add_modifier(Modifier::BOLD),
-                )),
-                Line::from(""),
-            ];
+            let best_time = state.best_time(&challenge.id);
+            let mut lines = result_lines(
+                challenge,
+                number,
+                grade,
+                result,
+                personal_best,
+                best_time,
+                tick,
+            );
+            if challenge.blind {
+                lines.extend(reveal_lines(challenge));
+            }
+            lines.extend(command_lines(&result.keys));
+            lines.extend(ghost_lines(
+                &result.keys,
+                ghost,
+                challenge.perfect_moves.as_deref(),
+            ));
+            let notes = note_lines(state.note(&challenge.id), editing.as_deref(), " ");
+            if !notes.is_empty() {
+                lines.push(Line::from(""));
+                lines.extend(notes);
+            }
+            let result = Paragraph::new(lines)
+                .block(Block::bordered().title(" Result "))
+                .wrap(Wrap { trim: false });
+            frame.render_widget(result, main);
+            if new_best && tick < frames {
+                animation::draw_confetti(frame.buffer_mut(), main.inner(Margin::new(1, 1)), tick);
+            }
 
-            let dim = Style::new().fg(Color::Gray);
-            lines.push(Line::from(Span::styled(
-                format!(" {status}"),
-                Style::new().fg(status_color).add_modifier(Modifier::BOLD),
-            )));
-            lines.push(Line::from(""));
-            if freestyle {
-                lines.push(Line::from(vec![
-                    Span::styled(" Keystrokes: ", dim),
-                    Span::raw(format!("{keystrokes}")),
-                ]));
+            let hint = if editing.is_some() {
+                " enter: save note | esc: cancel"
+            } else if comparable {
+                " r: retry | s: step | c: compare | n: note | 1-5: rate difficulty | any key: back"
+            } else if steppable {
+                " r: retry | s: step | n: note | 1-5: rate difficulty | any key: back"
             } else {
-                lines.push(Line::from(vec![
-                    Span::styled(" Keystrokes: ", dim),
-                    Span::raw(format!("{keystrokes} (par: {})", challenge.par_keystrokes)),
-                ]));
+                " r: retry | n: note | 1-5: rate difficulty | any key: back"
+            };
+            frame.render_widget(
+                Paragraph::new(hint).style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        let timeout = if clock.running() {
+            animation::TICK
+        } else {
+            Duration::from_millis(100)
+        };
+        if let Some(key) = screen::poll_key_within(terminal, timeout)?
+            && key.kind == KeyEventKind::Press
+        {
+            if clock.running() {
+                clock.skip();
+                continue;
             }
-            lines.push(Line::from(vec![
-                Span::styled(" Time: ", dim),
-                Span::raw(time_str),
-            ]));
+            if comparable && editing.is_none() && key.code == KeyCode::Char('c') {
+                show_comparison(terminal, challenge, number, result)?;
+                continue;
+            }
+            if steppable && editing.is_none() && key.code == KeyCode::Char('s') {
+                stepper::show_stepper(terminal, launcher, keymap, challenge, &result.keys)?;
+                continue;
+            }
+            if let Some(retry) = result_key(state, challenge, &mut editing, key.code) {
+                return Ok(retry);
+            }
+        }
+    }
+}
 
+/// Show a run next to the perfect moves of its challenge, segment by segment.
+/// Blocks until any key is pressed.
+fn show_comparison(
+    terminal: &mut ratatui::DefaultTerminal,
+    challenge: &Challenge,
+    number: usize,
+    result: &nvim::ChallengeResult,
+) -> std::io::Result<()> {
+    let moves = challenge.perfect_moves.as_deref().unwrap_or_default();
+    let segments = compare::compare(&result.keys, moves);
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(" #{number:03} - {}", challenge.title),
+            Style::new().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(comparison_lines(
+        &segments,
+        result.keystrokes,
+        challenge.par(),
+    ));
+    loop {
+        terminal.draw(|frame| {
             let [main, footer] =
-                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
-
-            let result = Paragraph::new(lines).block(Block::bordered().title(" Result "));
-            frame.render_widget(result, main);
-
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            let comparison = Paragraph::new(lines.clone())
+                .block(Block::bordered().title(" Solution Comparison "))
+                .wrap(Wrap { trim: false });
+            frame.render_widget(comparison, main);
             frame.render_widget(
-                Paragraph::new(" r: retry | any key: back").style(Style::new().fg(Color::DarkGray)),
+                Paragraph::new(" any key: back").style(Style::new().fg(Color::DarkGray)),
                 footer,
             );
+            theme::apply(frame.buffer_mut());
         })?;
 
-        if event::poll(Duration::from_millis(100))?
-            && let Event::Key(key) = event::read()?
+        if let Some(key) = screen::poll_key(terminal)?
             && key.kind == KeyEventKind::Press
         {
-            return Ok(key.code == KeyCode::Char('r'));
+            return Ok(());
+        }
+    }
+}
+
+/// A summary line and one line per segment: `=` where the run did what the
+/// perfect moves do, `≠` with the kinds of commands either side used where it
+/// didn't.
+fn comparison_lines(segments: &[Segment], keystrokes: u32, par: u32) -> Vec<Line<'static>> {
+    let dim = Style::new().fg(Color::Gray);
+    let matching = segments.iter().filter(|s| s.matches()).count();
+    let width = segments
+        .iter()
+        .map(|s| compare::summary(&s.mine).chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+    let color = |commands: &[Command]| commands.first().map_or(Color::Gray, |c| c.kind.color());
+    let kinds = |commands: &[Command]| {
+        let mut names: Vec<&str> = vec![];
+        for command in commands {
+            if !names.contains(&command.kind.name()) {
+                names.push(command.kind.name());
+            }
+        }
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                " You: {keystrokes} keys | par: {par} keys | {matching} of {} segments match",
+                segments.len()
+            ),
+            dim,
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("     {:width$}   par", "you"),
+            Style::new().add_modifier(Modifier::BOLD),
+        )),
+    ];
+    for segment in segments {
+        let mine = compare::summary(&segment.mine);
+        let perfect = compare::summary(&segment.perfect);
+        let mut spans = if segment.matches() {
+            vec![Span::styled("   = ", Style::new().fg(Color::Green))]
+        } else {
+            vec![Span::styled(
+                "   ≠ ",
+                theme::mark(Style::new().fg(Color::Red), Modifier::BOLD),
+            )]
+        };
+        spans.push(Span::styled(
+            format!("{mine:width$}"),
+            Style::new().fg(color(&segment.mine)),
+        ));
+        spans.push(Span::styled(" — ", dim));
+        spans.push(Span::styled(
+            perfect,
+            Style::new().fg(color(&segment.perfect)),
+        ));
+        if !segment.matches() {
+            spans.push(Span::styled(
+                format!(
+                    "  ({} vs {})",
+                    kinds(&segment.mine),
+                    kinds(&segment.perfect)
+                ),
+                dim,
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// Handle a key on the result screen: edit the note, rate the difficulty, or
+/// leave with `Some(retry)`.
+fn result_key(
+    state: &mut GameState,
+    challenge: &Challenge,
+    editing: &mut Option<String>,
+    code: KeyCode,
+) -> Option<bool> {
+    if let Some(text) = editing {
+        match code {
+            KeyCode::Enter => {
+                state.set_note_text(&challenge.id, text);
+                *editing = None;
+            }
+            KeyCode::Esc => *editing = None,
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => {}
+        }
+        return None;
+    }
+    match code {
+        KeyCode::Char('n') => {
+            *editing = Some(
+                state
+                    .note(&challenge.id)
+                    .map(|n| n.text.clone())
+                    .unwrap_or_default(),
+            );
+        }
+        KeyCode::Char(c @ '1'..='5') => {
+            state.set_rating(
+                &challenge.id,
+                c.to_digit(10).and_then(|d| u8::try_from(d).ok()),
+            );
+        }
+        KeyCode::Char('0') => state.set_rating(&challenge.id, None),
+        code => return Some(code == KeyCode::Char('r')),
+    }
+    None
+}
+
+/// Title, status, keystrokes and time of a run for the result screen, at
+/// `frame` of the grade reveal.
+fn result_lines(
+    challenge: &crate::challenge::Challenge,
+    number: usize,
+    grade: Option<Grade>,
+    result: &nvim::ChallengeResult,
+    personal_best: Option<u32>,
+    best_time: Option<u32>,
+    frame: u16,
+) -> Vec<Line<'static>> {
+    let keystrokes = result.keystrokes;
+    let elapsed_ms = result.elapsed_ms;
+    let shown_grade = grade.map(|g| animation::climbing_grade(frame, g));
+    let (status, status_color) = result_status(challenge, shown_grade, result, personal_best);
+    let solved = result.buffer_matches && result.violation.is_none();
+    let time_str = match best_time {
+        Some(best) if solved && best == elapsed_ms => {
+            format!("{} (best time)", format_time(elapsed_ms))
+        }
+        Some(best) => format!("{} (best: {})", format_time(elapsed_ms), format_time(best)),
+        None => format_time(elapsed_ms),
+    };
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(" #{number:03} - {}", challenge.title),
+            Style::new().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    let dim = Style::new().fg(Color::Gray);
+    lines.push(Line::from(Span::styled(
+        format!(" {status}"),
+        Style::new().fg(status_color).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(""));
+    if challenge.is_freestyle() {
+        let detail = match (challenge.reference_keystrokes, personal_best) {
+            (Some(r), Some(b)) => format!("{keystrokes} (reference: {r} | your best: {b})"),
+            (Some(r), None) => format!("{keystrokes} (reference: {r})"),
+            (None, _) => format!("{keystrokes}"),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(" Keystrokes: ", dim),
+            Span::raw(detail),
+        ]));
+    } else {
+        lines.push(Line::from(vec![
+            Span::styled(" Keystrokes: ", dim),
+            Span::raw(format!("{keystrokes} (par: {})", challenge.par())),
+        ]));
+        if grade.is_some() {
+            lines.push(par_bar_line(
+                animation::portion(frame, keystrokes),
+                challenge.par(),
+                keystrokes,
+            ));
+            lines.extend(opponents::lines(challenge, Some(keystrokes)));
         }
     }
+    lines.push(Line::from(vec![
+        Span::styled(" Time: ", dim),
+        Span::raw(time_str),
+    ]));
+    if result.hints_used > 0 {
+        let tiers = challenge.hint_tiers().len();
+        let hints = if result.hints_used > 1 && grade.is_some() {
+            format!(
+                "{} of {tiers} (grade lowered by {})",
+                result.hints_used,
+                result.hints_used - 1
+            )
+        } else {
+            format!("{} of {tiers}", result.hints_used)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(" Hints: ", dim),
+            Span::raw(hints),
+        ]));
+    }
+    if !result.shell_commands.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(" Shell: ", dim),
+            Span::raw(
+                result
+                    .shell_commands
+                    .iter()
+                    .map(|cmd| format!(":{cmd}"))
+                    .collect::<Vec<_>>()
+                    .join("  "),
+            ),
+        ]));
+    }
+    if let Some(technique) = challenge
+        .required_technique
+        .as_ref()
+        .filter(|_| result.buffer_matches && result.technique_missing)
+    {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(
+                " Technique not used: {} (topic goal not met)",
+                technique.name
+            ),
+            Style::new().fg(Color::Yellow),
+        )));
+    }
+    lines
+}
+
+/// The change a blind challenge asked for, hidden while editing: start vs
+/// target, with removed lines in red and added lines in green. Only shown on
+/// the result screen, whose challenge was loaded to be played.
+fn reveal_lines(challenge: &Challenge) -> Vec<Line<'static>> {
+    let start: Vec<&str> = challenge.start().content.lines().collect();
+    let target: Vec<&str> = challenge.target().content.lines().collect();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            " Target (hidden while editing):",
+            Style::new().fg(Color::Gray),
+        )),
+    ];
+    for row in diff::side_by_side(&start, &target) {
+        if !row.changed {
+            if let Some(i) = row.left {
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", start[i]),
+                    Style::new().fg(Color::Gray),
+                )));
+            }
+            continue;
+        }
+        if let Some(i) = row.left {
+            lines.push(Line::from(Span::styled(
+                format!("  - {}", start[i]),
+                Style::new().fg(Color::Red),
+            )));
+        }
+        if let Some(j) = row.right {
+            lines.push(Line::from(Span::styled(
+                format!("  + {}", target[j]),
+                Style::new().fg(Color::Green),
+            )));
+        }
+    }
+    lines
+}
+
+/// This run's key log split into commands, colored by kind, followed by how
+/// many commands of each kind it used.
+fn command_lines(keys: &str) -> Vec<Line<'static>> {
+    let commands = commands::classify(&notation::tokenize(keys));
+    if commands.is_empty() {
+        return vec![];
+    }
+    let dim = Style::new().fg(Color::Gray);
+    let mut spans = vec![Span::raw("  ")];
+    let mut kinds: Vec<(CommandKind, u32)> = vec![];
+    for (i, command) in commands.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" ", dim));
+        }
+        spans.push(Span::styled(
+            command.notation(),
+            Style::new().fg(command.kind.color()),
+        ));
+        match kinds.iter_mut().find(|(kind, _)| *kind == command.kind) {
+            Some((_, n)) => *n += 1,
+            None => kinds.push((command.kind, 1)),
+        }
+    }
+    let summary = kinds
+        .iter()
+        .map(|(kind, n)| format!("{n} {}", kind.name()))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!(" Commands ({}): ", commands.len()), dim),
+            Span::raw(summary),
+        ]),
+        Line::from(spans),
+    ]
+}
+
+/// This run's key log diffed against the previous best attempt and against the
+/// perfect moves, when there are any.
+fn ghost_lines(
+    keys: &str,
+    ghost: Option<&str>,
+    perfect_moves: Option<&[String]>,
+) -> Vec<Line<'static>> {
+    let perfect = perfect_moves.map(<[String]>::concat);
+    let others = [("your best", ghost), ("perfect moves", perfect.as_deref())];
+    let dim = Style::new().fg(Color::Gray);
+    let mut lines = vec![];
+    for (label, other) in others {
+        let Some(other) = other.filter(|o| *o != keys && !keys.is_empty()) else {
+            continue;
+        };
+        if lines.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled(" Ghost diff: ", dim),
+                Span::styled(
+                    "only this run",
+                    theme::mark(Style::new().fg(Color::Red), Modifier::CROSSED_OUT),
+                ),
+                Span::styled(" | ", dim),
+                Span::styled(
+                    "only the ghost",
+                    theme::mark(Style::new().fg(Color::Green), Modifier::UNDERLINED),
+                ),
+            ]));
+        }
+        let other_keys = notation::tokenize(other);
+        lines.push(Line::from(Span::styled(
+            format!(" vs {label} ({} keys):", other_keys.len()),
+            dim,
+        )));
+        lines.push(key_diff_line(&notation::tokenize(keys), &other_keys));
+    }
+    lines
+}
+
+/// Keys of two runs aligned on one line: shared keys dimmed, keys only in `mine`
+/// in red (struck through with markers) and keys only in `other` in green
+/// (underlined).
+fn key_diff_line(mine: &[Key], other: &[Key]) -> Line<'static> {
+    let dim = Style::new().fg(Color::Gray);
+    let only = |color, modifier| theme::mark(Style::new().fg(Color::Black).bg(color), modifier);
+    let mut spans = vec![Span::raw("  ")];
+    for op in diff::diff(mine, other) {
+        let (key, style) = match op {
+            DiffOp::Equal(i, _) => (&mine[i], dim),
+            DiffOp::Delete(i) => (&mine[i], only(Color::Red, Modifier::CROSSED_OUT)),
+            DiffOp::Insert(j) => (&other[j], only(Color::Green, Modifier::UNDERLINED)),
+        };
+        spans.push(Span::styled(key.to_string(), style));
+    }
+    Line::from(spans)
+}
+
+/// Rating and note lines, each starting with `indent`. While `editing`, the note
+/// line shows the text being typed instead of the saved one.
+fn note_lines(
+    note: Option<&ChallengeNote>,
+    editing: Option<&str>,
+    indent: &'static str,
+) -> Vec<Line<'static>> {
+    let label = Style::new().fg(Color::Gray);
+    let mut lines = vec![];
+    if let Some(rating) = note.and_then(|n| n.rating) {
+        let filled = usize::from(rating);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{indent}Your rating: "), label),
+            Span::styled(
+                format!("{}{}", "★".repeat(filled), "☆".repeat(5 - filled)),
+                Style::new().fg(Color::Yellow),
+            ),
+        ]));
+    }
+    if let Some(text) = editing {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{indent}Note: "), label),
+            Span::raw(text.to_string()),
+            Span::styled("_", Style::new().add_modifier(Modifier::SLOW_BLINK)),
+        ]));
+    } else if let Some(text) = note.map(|n| n.text.as_str()).filter(|t| !t.is_empty()) {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{indent}Note: "), label),
+            Span::styled(text.to_string(), Style::new().fg(Color::Cyan)),
+        ]));
+    }
+    lines
+}
+
+/// Headline and color for the result screen. A `violation` (forbidden key used)
+/// voids an otherwise matching attempt.
+fn result_status(
+    challenge: &crate::challenge::Challenge,
+    grade: Option<Grade>,
+    result: &nvim::ChallengeResult,
+    personal_best: Option<u32>,
+) -> (String, Color) {
+    if result.outcome == nvim::Outcome::OverBudget {
+        (
+            format!(
+                "OVER BUDGET ({} keys past {})",
+                result.keystrokes,
+                challenge.threshold(Grade::F)
+            ),
+            Color::Red,
+        )
+    } else if result.outcome == nvim::Outcome::GaveUp {
+        ("GAVE UP".to_string(), Color::Red)
+    } else if let Some(keys) = result
+        .violation
+        .as_deref()
+        .filter(|_| result.buffer_matches)
+    {
+        (format!("CONSTRAINT VIOLATED (used {keys})"), Color::Red)
+    } else if challenge.is_freestyle() {
+        if result.buffer_matches {
+            let is_new_best = personal_best.is_none_or(|prev| result.keystrokes < prev);
+            let beat_reference = challenge
+                .reference_keystrokes
+                .is_some_and(|r| result.keystrokes <= r);
+            if beat_reference {
+                (
+                    "COMPLETED (BEAT THE REFERENCE!)".to_string(),
+                    Grade::A.color(),
+                )
+            } else if is_new_best {
+                ("COMPLETED (NEW BEST!)".to_string(), Color::Cyan)
+            } else {
+                ("COMPLETED".to_string(), Color::Green)
+            }
+        } else {
+            ("FAILED".to_string(), Color::Red)
+        }
+    } else if let Some(g) = grade {
+        let grade_name = match g {
+            Grade::A => "GRADE A",
+            Grade::B => "GRADE B",
+            Grade::C => "GRADE C",
+            Grade::D => "GRADE D",
+            Grade::E => "GRADE E",
+            Grade::F => "GRADE F",
+        };
+        (grade_name.to_string(), g.color())
+    } else {
+        ("FAILED".to_string(), Color::Red)
+    }
+}
+
+/// Bar of `shown` keystrokes on a scale up to the larger of `par` and the run's
+/// `keystrokes`, with a mark at par. Keys over par are yellow.
+fn par_bar_line(shown: u32, par: u32, keystrokes: u32) -> Line<'static> {
+    const WIDTH: u32 = 30;
+    let scale = par.max(keystrokes).max(1);
+    let par_col = par * WIDTH / scale;
+    let filled = shown * WIDTH / scale;
+    let mut spans = vec![Span::raw(" ")];
+    for col in 0..WIDTH {
+        let style = match col {
+            _ if col >= filled => Style::new().fg(Color::DarkGray),
+            _ if col >= par_col => Style::new().fg(Color::Yellow),
+            _ => Style::new().fg(Color::Cyan),
+        };
+        let glyph = match col {
+            _ if col == par_col && col > 0 => "│",
+            _ if col < filled => "█",
+            _ => "░",
+        };
+        spans.push(Span::styled(glyph, style));
+    }
+    spans.push(Span::styled(
+        format!(" {shown}/{par}"),
+        Style::new().fg(Color::Gray),
+    ));
+    Line::from(spans)
 }
 
 fn threshold_line(challenge: &crate::challenge::Challenge) -> Line<'static> {
@@ -0,0 +1,285 @@
+use std::ops::RangeInclusive;
+
+use serde::Serialize;
+
+use crate::challenge::{Challenge, count_keystrokes};
+
+/// Words the generated buffers are made of: letters only, so `w` steps over
+/// exactly one of them.
+const WORDS: &[&str] = &[
+    "apple", "river", "stone", "cloud", "green", "quick", "lamp", "paper", "tiger", "maple",
+    "ocean", "silver", "north", "piano", "candle", "garden", "window", "rocket", "bridge",
+    "yellow", "forest", "pencil", "orange", "winter", "shadow", "copper", "valley", "mirror",
+    "cactus", "harbor", "velvet", "meadow", "thunder", "lantern", "pepper", "saddle", "tunnel",
+    "violet", "walnut", "zebra",
+];
+
+/// Deterministic pseudo-random numbers (`SplitMix64`), so that a seed gives the
+/// same challenge on every machine.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let span = u64::try_from(range.end() - range.start() + 1).unwrap_or(u64::MAX);
+        range.start() + usize::try_from(self.next_u64() % span).unwrap_or(0)
+    }
+
+    /// One of `items`, which must not be empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..=items.len() - 1)]
+    }
+}
+
+/// A kind of challenge generated with random buffers and positions.
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    task: fn(&mut Rng) -> Task,
+}
+
+/// Templates in the order `nvimkata gen` lists them.
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "delete_word",
+        description: "Delete the Nth word on line M",
+        task: delete_word,
+    },
+    Template {
+        name: "change_word",
+        description: "Replace the Nth word on line M with another word",
+        task: change_word,
+    },
+    Template {
+        name: "delete_lines",
+        description: "Delete a run of lines",
+        task: delete_lines,
+    },
+    Template {
+        name: "swap_lines",
+        description: "Swap a line with the one below it",
+        task: swap_lines,
+    },
+];
+
+/// What a template made of a seed, before it is named and scored.
+struct Task {
+    title: String,
+    description: String,
+    hint: &'static str,
+    difficulty: u8,
+    moves: Vec<String>,
+    start: Vec<String>,
+    target: Vec<String>,
+}
+
+/// A generated challenge, with the fields its challenge file needs. Without
+/// `perfect_moves` it is a freestyle challenge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Generated {
+    pub id: String,
+    pub version: String,
+    pub title: String,
+    pub topic: String,
+    pub difficulty: u8,
+    pub description: String,
+    pub hint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filetype: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub perfect_moves: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub par_keystrokes: Option<u32>,
+    pub start: Buffer,
+    pub target: Buffer,
+    /// The command line that generates the challenge again.
+    #[serde(skip)]
+    pub command: String,
+}
+
+/// Buffer content of a generated challenge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Buffer {
+    pub content: String,
+}
+
+impl Generated {
+    /// The challenge file, headed by the command that generates it again.
+    pub fn to_toml(&self) -> String {
+        let body = toml::to_string(self).unwrap_or_default();
+        format!("# Generated by `{}`\n{body}", self.command)
+    }
+
+    /// The challenge as the curriculum loads it from [`Self::to_toml`].
+    pub fn challenge(&self) -> Result<Challenge, toml::de::Error> {
+        toml::from_str(&self.to_toml())
+    }
+}
+
+pub fn template(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// Generate a challenge from `template` and `seed`. The same pair always
+/// gives the same challenge; its ID is `gen_{template}_{seed}`.
+pub fn generate(template: &Template, seed: u64) -> Generated {
+    let task = (template.task)(&mut Rng::new(seed));
+    let keys = task.moves.concat();
+    Generated {
+        id: format!("gen_{}_{seed}", template.name),
+        version: "1.0.0".to_string(),
+        title: task.title,
+        topic: "generated".to_string(),
+        difficulty: task.difficulty,
+        description: task.description,
+        hint: task.hint.to_string(),
+        filetype: None,
+        perfect_moves: task.moves,
+        par_keystrokes: Some(u32::try_from(count_keystrokes(&keys)).unwrap_or(u32::MAX)),
+        start: Buffer {
+            content: task.start.join("\n"),
+        },
+        target: Buffer {
+            content: task.target.join("\n"),
+        },
+        command: format!("nvimkata gen --template {} --seed {seed}", template.name),
+    }
+}
+
+/// A number of lines in `count` of random words, each with a number of words
+/// in `words`.
+fn word_lines(
+    rng: &mut Rng,
+    count: RangeInclusive<usize>,
+    words: RangeInclusive<usize>,
+) -> Vec<Vec<&'static str>> {
+    (0..rng.range(count))
+        .map(|_| {
+            let len = rng.range(words.clone());
+            (0..len).map(|_| *rng.pick(WORDS)).collect()
+        })
+        .collect()
+}
+
+fn join(lines: &[Vec<&str>]) -> Vec<String> {
+    lines.iter().map(|words| words.join(" ")).collect()
+}
+
+/// Keys moving `by` times with `motion`, with a count when that is shorter:
+/// nothing, the motion, or the count and the motion.
+fn repeat(motion: &str, by: usize) -> Option<String> {
+    match by {
+        0 => None,
+        1 => Some(motion.to_string()),
+        n => Some(format!("{n}{motion}")),
+    }
+}
+
+/// Keys from the start of the buffer to word `word` of line `line`, both
+/// counting from 1.
+fn moves_to(line: usize, word: usize) -> Vec<String> {
+    repeat("j", line - 1)
+        .into_iter()
+        .chain(repeat("w", word - 1))
+        .collect()
+}
+
+fn delete_word(rng: &mut Rng) -> Task {
+    let mut lines = word_lines(rng, 2..=5, 4..=7);
+    let line = rng.range(1..=lines.len());
+    // Not the last word, which dw would leave a trailing space before
+    let word = rng.range(1..=lines[line - 1].len() - 1);
+    let start = join(&lines);
+    let removed = lines[line - 1].remove(word - 1);
+    let mut moves = moves_to(line, word);
+    moves.push("dw".to_string());
+    Task {
+        title: format!("Delete word {word} on line {line}"),
+        description: format!("Delete '{removed}', word {word} of line {line}."),
+        hint: "Counts before j and w get there, then dw",
+        difficulty: 1,
+        moves,
+        start,
+        target: join(&lines),
+    }
+}
+
+fn change_word(rng: &mut Rng) -> Task {
+    let mut lines = word_lines(rng, 2..=5, 4..=7);
+    let line = rng.range(1..=lines.len());
+    let word = rng.range(1..=lines[line - 1].len());
+    let start = join(&lines);
+    let old = lines[line - 1][word - 1];
+    let new = loop {
+        let new = *rng.pick(WORDS);
+        if new != old {
+            break new;
+        }
+    };
+    lines[line - 1][word - 1] = new;
+    let mut moves = moves_to(line, word);
+    moves.extend(["cw".to_string(), new.to_string(), "<Esc>".to_string()]);
+    Task {
+        title: format!("Change word {word} on line {line}"),
+        description: format!("Replace '{old}', word {word} of line {line}, with '{new}'."),
+        hint: "Counts before j and w get there, then cw",
+        difficulty: 1,
+        moves,
+        start,
+        target: join(&lines),
+    }
+}
+
+fn delete_lines(rng: &mut Rng) -> Task {
+    let mut lines = word_lines(rng, 5..=9, 3..=6);
+    let count = rng.range(2..=3);
+    let line = rng.range(1..=lines.len() - count + 1);
+    let start = join(&lines);
+    lines.drain(line - 1..line - 1 + count);
+    let mut moves = moves_to(line, 1);
+    moves.push(format!("{count}dd"));
+    Task {
+        title: format!("Delete {count} lines from line {line}"),
+        description: format!("Delete lines {line} to {}.", line + count - 1),
+        hint: "dd takes a count",
+        difficulty: 1,
+        moves,
+        start,
+        target: join(&lines),
+    }
+}
+
+fn swap_lines(rng: &mut Rng) -> Task {
+    let mut lines = word_lines(rng, 3..=6, 3..=6);
+    let line = rng.range(1..=lines.len() - 1);
+    // Identical lines would leave nothing to do
+    while lines[line] == lines[line - 1] {
+        lines[line] = word_lines(rng, 1..=1, 3..=6).remove(0);
+    }
+    let start = join(&lines);
+    lines.swap(line - 1, line);
+    let mut moves = moves_to(line, 1);
+    moves.push("ddp".to_string());
+    Task {
+        title: format!("Swap lines {line} and {}", line + 1),
+        description: format!("Move line {line} below line {}.", line + 1),
+        hint: "Delete the line and put it back below the next one",
+        difficulty: 2,
+        moves,
+        start,
+        target: join(&lines),
+    }
+}
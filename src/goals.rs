@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::state::DayActivity;
+
+/// Consecutive met days needed to earn a streak freeze.
+pub const FREEZE_INTERVAL: u32 = 7;
+
+/// Most streak freezes that can be banked at once.
+pub const MAX_FREEZES: u32 = 2;
+
+/// Current time as seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Current day as days since the Unix epoch (UTC).
+pub fn today() -> u32 {
+    day_of(now())
+}
+
+/// Day of a Unix timestamp, as days since the Unix epoch (UTC).
+pub fn day_of(timestamp: u64) -> u32 {
+    u32::try_from(timestamp / 86_400).unwrap_or(u32::MAX)
+}
+
+/// Parse a `YYYY-MM-DD` date into days since the Unix epoch.
+pub fn parse_day(date: &str) -> Option<u32> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days from civil date, with years starting in March
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u32::try_from(era * 146_097 + doe - 719_468).ok()?;
+    // Reject days past the end of the month, which roll over
+    (format_day(days) == date.trim()).then_some(days)
+}
+
+/// Format days since the Unix epoch as a `YYYY-MM-DD` date.
+pub fn format_day(days: u32) -> String {
+    let z = i64::from(days) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalKind {
+    /// Challenges completed (target matched) per day.
+    Challenges,
+    /// Minutes spent in challenges per day.
+    Minutes,
+}
+
+/// A daily practice goal from the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Goal {
+    pub kind: GoalKind,
+    pub target: u32,
+    /// Missed days in a row that pause the streak instead of breaking it.
+    #[serde(default = "default_grace_days")]
+    pub grace_days: u32,
+}
+
+fn default_grace_days() -> u32 {
+    1
+}
+
+/// Streak state for a goal as of a given day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streak {
+    /// Met days in the running streak.
+    pub current: u32,
+    /// Longest streak ever reached.
+    pub best: u32,
+    /// Banked freezes, each covering one missed day beyond the grace days.
+    pub freezes: u32,
+}
+
+impl Goal {
+    /// Progress towards the target on a single day.
+    pub fn progress(&self, day: &DayActivity) -> u32 {
+        match self.kind {
+            GoalKind::Challenges => day.challenges,
+            GoalKind::Minutes => day.secs / 60,
+        }
+    }
+
+    pub fn is_met(&self, day: &DayActivity) -> bool {
+        self.progress(day) >= self.target
+    }
+
+    /// Short unit label, e.g. "challenges" or "min".
+    pub fn unit(&self) -> &'static str {
+        match self.kind {
+            GoalKind::Challenges => "challenges",
+            GoalKind::Minutes => "min",
+        }
+    }
+
+    /// Replay daily activity up to `today` to compute the streak.
+    ///
+    /// A met day extends the streak and every `FREEZE_INTERVAL` met days bank a
+    /// freeze. A missed day first uses up the grace days, then a freeze; only when
+    /// neither is left does the streak reset. Today never counts as missed since
+    /// it is not over yet.
+    pub fn streak(&self, activity: &BTreeMap<u32, DayActivity>, today: u32) -> Streak {
+        let mut streak = Streak::default();
+        let Some(&first) = activity.keys().next() else {
+            return streak;
+        };
+        let mut missed_run = 0;
+        for day in first..=today {
+            let met = activity.get(&day).is_some_and(|a| self.is_met(a));
+            if met {
+                streak.current += 1;
+                streak.best = streak.best.max(streak.current);
+                missed_run = 0;
+                if streak.current % FREEZE_INTERVAL == 0 {
+                    streak.freezes = (streak.freezes + 1).min(MAX_FREEZES);
+                }
+            } else if day < today {
+                missed_run += 1;
+                if missed_run <= self.grace_days {
+                    continue;
+                }
+                if streak.freezes > 0 {
+                    streak.freezes -= 1;
+                } else {
+                    streak.current = 0;
+                    missed_run = 0;
+                }
+            }
+        }
+        streak
+    }
+}
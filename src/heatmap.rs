@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::goals;
+use crate::notation::Key;
+use crate::state::DayActivity;
+use crate::theme;
+
+/// Keyboard layout of the player: drawn by the key heatmap, and picking the
+/// par of challenges with a `layout_par` for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Colemak,
+    Dvorak,
+}
+
+impl KeyboardLayout {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Qwerty => "QWERTY",
+            Self::Colemak => "Colemak",
+            Self::Dvorak => "Dvorak",
+        }
+    }
+
+    /// Keys of the rows under one hand in touch typing: the left or right part
+    /// of each row, with their shifted characters and Ctrl chords.
+    pub fn hand_keys(self, left: bool) -> Vec<Key> {
+        let mut keys = Vec::new();
+        for ((plain, shifted), split) in self.rows().into_iter().zip(LEFT_COLUMNS) {
+            let half = plain.chars().zip(shifted.chars());
+            let half: Vec<(char, char)> = if left {
+                half.take(split).collect()
+            } else {
+                half.skip(split).collect()
+            };
+            for (c, s) in half {
+                keys.extend([Key::Char(c), Key::Char(s)]);
+                if c.is_ascii_alphabetic() {
+                    keys.push(Key::Ctrl(c));
+                }
+            }
+        }
+        keys
+    }
+
+    /// Key rows as (unshifted, shifted) characters of each key, top to bottom.
+    fn rows(self) -> [(&'static str, &'static str); 4] {
+        match self {
+            Self::Qwerty => [
+                ("`1234567890-=", "~!@#$%^&*()_+"),
+                ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+                ("asdfghjkl;'", "ASDFGHJKL:\""),
+                ("zxcvbnm,./", "ZXCVBNM<>?"),
+            ],
+            Self::Colemak => [
+                ("`1234567890-=", "~!@#$%^&*()_+"),
+                ("qwfpgjluy;[]\\", "QWFPGJLUY:{}|"),
+                ("arstdhneio'", "ARSTDHNEIO\""),
+                ("zxcvbkm,./", "ZXCVBKM<>?"),
+            ],
+            Self::Dvorak => [
+                ("`1234567890[]", "~!@#$%^&*(){}"),
+                ("',.pyfgcrl/=\\", "\"<>PYFGCRL?+|"),
+                ("aoeuidhtns-", "AOEUIDHTNS_"),
+                (";qjkxbmwvz", ":QJKXBMWVZ"),
+            ],
+        }
+    }
+}
+
+/// Keys of each row typed with the left hand in touch typing.
+const LEFT_COLUMNS: [usize; 4] = [6, 5, 5, 5];
+
+/// Row offsets in key widths, for the stagger of a physical keyboard.
+const ROW_INDENT: [usize; 4] = [0, 3, 4, 6];
+
+/// Heat colors from least to most used.
+const HEAT: [Color; 5] = [
+    Color::Blue,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Red,
+];
+
+/// Keys outside the drawn rows that are listed below the keyboard.
+const EXTRA_KEYS: [&str; 5] = ["<Space>", "<Esc>", "<Enter>", "<Tab>", "<BS>"];
+
+/// Draw `layout` with each key colored by how often it was pressed. `usage`
+/// counts presses by key notation as in [`Key`]'s `Display`; a key's count
+/// includes its shifted character and Ctrl chord. With [`theme::markers`], keys
+/// also show their heat level from 1 to 5.
+pub fn heatmap_lines(layout: KeyboardLayout, usage: &BTreeMap<String, u32>) -> Vec<Line<'static>> {
+    let count = |key: Key| usage.get(&key.to_string()).copied().unwrap_or(0);
+    let rows: Vec<Vec<(char, u32)>> = layout
+        .rows()
+        .iter()
+        .map(|(plain, shifted)| {
+            plain
+                .chars()
+                .zip(shifted.chars())
+                .map(|(c, s)| {
+                    (
+                        c,
+                        count(Key::Char(c)) + count(Key::Char(s)) + count(Key::Ctrl(c)),
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let extras: Vec<(&str, u32)> = EXTRA_KEYS
+        .iter()
+        .map(|name| (*name, usage.get(*name).copied().unwrap_or(0)))
+        .collect();
+    let max = rows
+        .iter()
+        .flatten()
+        .map(|&(_, n)| n)
+        .chain(extras.iter().map(|&(_, n)| n))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<Line> = rows
+        .iter()
+        .zip(ROW_INDENT)
+        .map(|(row, indent)| {
+            let mut spans = vec![Span::raw(" ".repeat(2 + indent))];
+            for &(c, n) in row {
+                let mark = heat_level(n, max)
+                    .filter(|_| theme::markers())
+                    .map_or(" ".to_string(), |level| level.to_string());
+                spans.push(Span::styled(format!(" {c}{mark}"), heat_style(n, max)));
+                spans.push(Span::raw(" "));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let mut spans = vec![Span::raw("  ")];
+    for (name, n) in extras {
+        spans.push(Span::styled(format!(" {name} {n} "), heat_style(n, max)));
+        spans.push(Span::raw(" "));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(spans));
+
+    let mut legend = vec![Span::styled("  less ", Style::new().fg(Color::Gray))];
+    legend.extend((1..).zip(HEAT).map(|(level, c)| {
+        let label = if theme::markers() {
+            format!("{level} ")
+        } else {
+            "  ".to_string()
+        };
+        Span::styled(label, Style::new().fg(Color::Black).bg(c))
+    }));
+    legend.push(Span::styled(" more", Style::new().fg(Color::Gray)));
+    lines.push(Line::from(legend));
+    lines
+}
+
+/// Heat level of `count` from 1 to `HEAT.len()`, `None` for unused keys.
+fn heat_level(count: u32, max: u32) -> Option<usize> {
+    if count == 0 || max == 0 {
+        return None;
+    }
+    Some(
+        (count as usize * HEAT.len())
+            .div_ceil(max as usize)
+            .clamp(1, HEAT.len()),
+    )
+}
+
+fn heat_style(count: u32, max: u32) -> Style {
+    match heat_level(count, max) {
+        Some(level) => Style::new().fg(Color::Black).bg(HEAT[level - 1]),
+        None => Style::new().fg(Color::DarkGray),
+    }
+}
+
+/// Weeks shown by the practice calendar.
+pub const CALENDAR_WEEKS: u32 = 26;
+
+/// Calendar shades from least to most practice.
+const GREENS: [Color; 4] = [
+    Color::Rgb(14, 68, 41),
+    Color::Rgb(0, 109, 50),
+    Color::Rgb(38, 166, 65),
+    Color::Rgb(57, 211, 83),
+];
+
+/// Calendar cells by shade with [`theme::markers`], denser for more practice.
+const SHADES: [&str; 4] = ["░ ", "▒ ", "▓ ", "█ "];
+
+/// Contribution calendar of the last [`CALENDAR_WEEKS`] weeks: a column per
+/// week ending with the one of `today`, a row per weekday from Monday. Days
+/// are shaded by the challenges completed on them.
+pub fn calendar_lines(activity: &BTreeMap<u32, DayActivity>, today: u32) -> Vec<Line<'static>> {
+    // The Unix epoch was a Thursday
+    let weekday = |day: u32| (day + 3) % 7;
+    let start = (today - weekday(today)).saturating_sub(7 * (CALENDAR_WEEKS - 1));
+    let window = activity.range(start..=today);
+    let max = window.clone().map(|(_, a)| a.challenges).max().unwrap_or(0);
+    let days_practiced = window.clone().count();
+    let completed: u32 = window.map(|(_, a)| a.challenges).sum();
+
+    let dim = Style::new().fg(Color::Gray);
+    let mut months = String::from("      ");
+    let mut last_month = "";
+    for week in 0..CALENDAR_WEEKS {
+        let date = goals::format_day(start + 7 * week);
+        let month = month_name(&date);
+        if month != last_month && months.len() <= 6 + 2 * week as usize {
+            months.truncate(6 + 2 * week as usize);
+            months.push_str(month);
+            last_month = month;
+        }
+    }
+    let mut lines = vec![Line::styled(months, dim)];
+
+    for (row, label) in (0..).zip(["Mon", "", "Wed", "", "Fri", "", "Sun"]) {
+        let mut spans = vec![Span::styled(format!("  {label:<4}"), dim)];
+        for week in 0..CALENDAR_WEEKS {
+            let day = start + 7 * week + row;
+            if day > today {
+                break;
+            }
+            let cell = match activity.get(&day) {
+                Some(a) => {
+                    let level = (a.challenges as usize * GREENS.len())
+                        .div_ceil(max.max(1) as usize)
+                        .clamp(1, GREENS.len());
+                    let cell = if theme::markers() {
+                        SHADES[level - 1]
+                    } else {
+                        "■ "
+                    };
+                    Span::styled(cell, Style::new().fg(GREENS[level - 1]))
+                }
+                None => Span::styled("· ", Style::new().fg(Color::DarkGray)),
+            };
+            spans.push(cell);
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::styled(
+        format!("  {completed} challenges completed on {days_practiced} days"),
+        dim,
+    ));
+    lines
+}
+
+/// Abbreviated month of a `YYYY-MM-DD` date.
+fn month_name(date: &str) -> &'static str {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month: usize = date.get(5..7).and_then(|m| m.parse().ok()).unwrap_or(1);
+    MONTHS[month.clamp(1, 12) - 1]
+}
@@ -0,0 +1,328 @@
+use ratatui::Frame;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::challenge::{Challenge, grade_display};
+use crate::game;
+use crate::goals;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::nvim;
+use crate::preview;
+use crate::screen;
+use crate::state::{AttemptRecord, GameState, Solution};
+use crate::stepper;
+use crate::theme;
+
+/// Browse the stored attempts of a challenge with their full key logs, by
+/// keystrokes or newest first, or on the solutions tab each distinct solution,
+/// by keystrokes or most used first. Selecting one steps through its key log.
+/// Attempts can be deleted; the state is saved after each deletion.
+pub fn show_history(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    state: &mut GameState,
+    challenge: &Challenge,
+) -> std::io::Result<()> {
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+    let mut pending: Vec<KeyPress> = Vec::new();
+    let mut keys_scroll: u16 = 0;
+    let mut page: u16 = 0;
+    let mut status: Option<String> = None;
+    let mut newest_first = false;
+    let mut tab = Tab::Attempts;
+
+    loop {
+        let stored = state
+            .history
+            .get(&challenge.id)
+            .map_or(&[][..], Vec::as_slice);
+        // Stored attempts are sorted by keystrokes; undated ones go last
+        let mut attempts: Vec<(usize, &AttemptRecord)> = stored.iter().enumerate().collect();
+        if newest_first {
+            attempts.sort_by_key(|(_, a)| std::cmp::Reverse(a.timestamp));
+        }
+        // Solutions are sorted by keystrokes too
+        let mut solutions: Vec<&Solution> = state
+            .solutions
+            .get(&challenge.id)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .collect();
+        if newest_first {
+            solutions.sort_by_key(|s| std::cmp::Reverse(s.uses));
+        }
+        let rows = match tab {
+            Tab::Attempts => attempt_rows(challenge, &attempts, newest_first),
+            Tab::Solutions => solution_rows(&solutions, newest_first),
+        };
+        terminal.draw(|frame| {
+            page = render_history(
+                frame,
+                keymap,
+                challenge,
+                &rows,
+                &mut list_state,
+                &mut keys_scroll,
+                status.as_deref(),
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            let Some(action) = keymap.resolve(&mut pending, key.into()) else {
+                continue;
+            };
+            let selected = list_state.selected().unwrap_or(0);
+            let len = rows.items.len();
+            status = None;
+            match action {
+                Action::History | Action::Back | Action::Quit => return Ok(()),
+                Action::Down if len > 0 => list_state.select(Some((selected + 1) % len)),
+                Action::Up if len > 0 => list_state.select(Some((selected + len - 1) % len)),
+                Action::Top => list_state.select(Some(0)),
+                Action::Bottom => list_state.select(Some(len.saturating_sub(1))),
+                Action::ScrollDown
+                | Action::ScrollUp
+                | Action::HalfPageDown
+                | Action::HalfPageUp => {
+                    keys_scroll = preview::scroll(keys_scroll, action, page, u16::MAX);
+                }
+                Action::Sort => {
+                    newest_first = !newest_first;
+                    list_state.select(Some(0));
+                }
+                Action::Tab => {
+                    tab = match tab {
+                        Tab::Attempts => Tab::Solutions,
+                        Tab::Solutions => Tab::Attempts,
+                    };
+                    list_state.select(Some(0));
+                }
+                Action::Select => {
+                    if let Some(keys) = rows.keys.get(selected).filter(|k| !k.is_empty()) {
+                        stepper::show_stepper(terminal, launcher, keymap, challenge, keys)?;
+                    }
+                }
+                Action::Delete if tab == Tab::Attempts => {
+                    let Some(&(index, _)) = attempts.get(selected) else {
+                        continue;
+                    };
+                    if let Some(attempt) = state.delete_attempt(&challenge.id, index) {
+                        state.save().ok();
+                        status = Some(format!(
+                            "Deleted attempt {} ({} keys)",
+                            selected + 1,
+                            attempt.keystrokes
+                        ));
+                        list_state.select(Some(selected.min(len.saturating_sub(2))));
+                    }
+                }
+                _ => {}
+            }
+            if list_state.selected() != Some(selected) {
+                keys_scroll = 0;
+            }
+        }
+    }
+}
+
+/// The tabs of the history screen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Attempts,
+    /// Distinct solutions, however often each was used.
+    Solutions,
+}
+
+/// The list of the current tab: its title, the rows and the key log of each.
+struct Rows<'a> {
+    title: String,
+    items: Vec<ListItem<'a>>,
+    keys: Vec<&'a str>,
+}
+
+fn attempt_rows<'a>(
+    challenge: &Challenge,
+    attempts: &[(usize, &'a AttemptRecord)],
+    newest_first: bool,
+) -> Rows<'a> {
+    let freestyle = challenge.is_freestyle();
+    let items = attempts
+        .iter()
+        .enumerate()
+        .map(|(i, (_, attempt))| {
+            // Freestyle attempts carry a placeholder grade
+            let (label, style) = grade_display((!freestyle).then_some(attempt.grade));
+            let mut spans = vec![
+                Span::raw(format!("{:>2}. ", i + 1)),
+                Span::styled(format!("[{label}]"), style),
+                Span::raw(format!(
+                    " {:>4} keys | {}",
+                    attempt.keystrokes,
+                    game::format_run_time(attempt.time_ms)
+                )),
+            ];
+            if let Some(timestamp) = attempt.timestamp {
+                spans.push(Span::styled(
+                    format!(" | {}", goals::format_day(goals::day_of(timestamp))),
+                    Style::new().fg(Color::Gray),
+                ));
+            }
+            if attempt.technique_missing {
+                spans.push(Span::styled(
+                    " | technique not used",
+                    Style::new().fg(Color::Yellow),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let order = if newest_first {
+        "newest first"
+    } else {
+        "fewest keystrokes first"
+    };
+    Rows {
+        title: format!(" Attempts ({order}) "),
+        items,
+        keys: attempts.iter().map(|(_, a)| a.keys.as_str()).collect(),
+    }
+}
+
+fn solution_rows<'a>(solutions: &[&'a Solution], most_used_first: bool) -> Rows<'a> {
+    let items = solutions
+        .iter()
+        .enumerate()
+        .map(|(i, solution)| {
+            let mut spans = vec![Span::raw(format!(
+                "{:>2}. {:>4} keys | used {}x",
+                i + 1,
+                solution.keystrokes,
+                solution.uses
+            ))];
+            if let Some(timestamp) = solution.last_used {
+                spans.push(Span::styled(
+                    format!(" | last {}", goals::format_day(goals::day_of(timestamp))),
+                    Style::new().fg(Color::Gray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let order = if most_used_first {
+        "most used first"
+    } else {
+        "fewest keystrokes first"
+    };
+    Rows {
+        title: format!(" Solutions ({order}) "),
+        items,
+        keys: solutions.iter().map(|s| s.keys.as_str()).collect(),
+    }
+}
+
+/// Draw the history screen. Returns the height of the key log pane.
+fn render_history(
+    frame: &mut Frame,
+    keymap: &Keymap,
+    challenge: &Challenge,
+    rows: &Rows,
+    list_state: &mut ListState,
+    keys_scroll: &mut u16,
+    status: Option<&str>,
+) -> u16 {
+    let [header, list_area, keys_area, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Percentage(40),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let title = Paragraph::new(Line::from(vec![
+        Span::raw(" History: "),
+        Span::styled(&challenge.title, Style::new().add_modifier(Modifier::BOLD)),
+        Span::styled(
+            format!(" ({} stored)", rows.items.len()),
+            Style::new().fg(Color::Gray),
+        ),
+    ]))
+    .block(Block::bordered());
+    frame.render_widget(title, header);
+
+    let list = List::new(rows.items.clone())
+        .block(Block::bordered().title(rows.title.as_str()))
+        .highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(list, list_area, list_state);
+
+    let keys = list_state
+        .selected()
+        .and_then(|i| rows.keys.get(i))
+        .copied()
+        .unwrap_or("");
+    render_keys(frame, keys_area, keys, keys_scroll);
+
+    let footer_line = status.map_or_else(
+        || {
+            Line::styled(
+                format!(
+                    " {}/{}: select | {}: step through | {}/{}: scroll keys | {}: sort \
+                     | {}: attempts/solutions | {}: delete | {}/{}: back",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::Select),
+                    keymap.label(Action::ScrollDown),
+                    keymap.label(Action::ScrollUp),
+                    keymap.label(Action::Sort),
+                    keymap.label(Action::Tab),
+                    keymap.label(Action::Delete),
+                    keymap.label(Action::Back),
+                    keymap.label(Action::Quit),
+                ),
+                Style::new().fg(Color::DarkGray),
+            )
+        },
+        |s| Line::styled(format!(" {s}"), Style::new().fg(Color::Yellow)),
+    );
+    frame.render_widget(Paragraph::new(footer_line), footer);
+
+    keys_area.height.saturating_sub(2)
+}
+
+/// Wrapped key log of the selected attempt, clamping `scroll` to its length.
+fn render_keys(frame: &mut Frame, area: Rect, keys: &str, scroll: &mut u16) {
+    let inner_width = usize::from(area.width.saturating_sub(2)).max(1);
+    let rows = keys.chars().count().div_ceil(inner_width).max(1);
+    let visible = usize::from(area.height.saturating_sub(2));
+    let max = u16::try_from(rows.saturating_sub(visible)).unwrap_or(u16::MAX);
+    *scroll = (*scroll).min(max);
+    let title = if max > 0 {
+        format!(" Keys [{}/{}] ", *scroll + 1, max + 1)
+    } else {
+        " Keys ".to_string()
+    };
+    let text = if keys.is_empty() {
+        Line::styled("(no key log recorded)", Style::new().fg(Color::Gray))
+    } else {
+        Line::raw(keys)
+    };
+    frame.render_widget(
+        Paragraph::new(text)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((*scroll, 0)),
+        area,
+    );
+}
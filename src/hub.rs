@@ -1,17 +1,47 @@
 use ratatui::Frame;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::event::KeyEventKind;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
-use std::time::Duration;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
 
-use crate::challenge::{Category, Grade, Topic, grade_display};
+use crate::assignment::Assignment;
+use crate::challenge::{Category, Challenge, Grade, Topic, grade_display};
+use crate::collection;
+use crate::curriculum::Diagnostic;
 use crate::game;
+use crate::goals::{self, Goal};
+use crate::heatmap::KeyboardLayout;
+use crate::intro;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::lint::{self, Lint};
+use crate::playlist;
+use crate::recommend;
+use crate::screen::{self, NarrowLayout};
+use crate::skills::{self, SkillRegistry};
 use crate::state::GameState;
+use crate::theme;
+use crate::unlock::{self, UnlockPolicy};
+
+/// Number of challenges suggested in the hub's recommendation panel.
+const RECOMMENDATION_COUNT: u16 = 5;
 
 pub enum HubAction {
     SelectTopic(u8),
+    Sandbox,
+    Favorites,
+    /// Play the given challenges back to back under this title, as a drill.
+    Drill(&'static str, Vec<String>),
+    /// Archive the outdated best results, accepting them as historical.
+    AcceptStale,
+    /// Play the playlist with this topic ID.
+    Playlist(u8),
+    /// Play the assignment at this index.
+    Assignment(usize),
+    /// Clear the progress of every challenge in the topic with this ID.
+    ResetTopic(u8),
+    /// Browse the command reference.
+    Reference,
     Quit,
 }
 
@@ -24,20 +54,56 @@ enum HubListItem {
         topic_name: String,
         total: usize,
     },
+    /// Ungraded practice, always available.
+    Sandbox,
+    /// Bookmarked challenges, selectable once there are any.
+    Favorites,
+    /// The weakest graded challenges, selectable once any were attempted.
+    Drill,
+    /// The challenges with outdated scores, selectable while there are any.
+    Revalidate,
+    /// A user playlist, by index into `Hub::playlists`.
+    Playlist(usize),
+    /// An instructor's assignment, by index into `Hub::assignments`.
+    Assignment(usize),
 }
 
 pub struct Hub {
     topics: Vec<Topic>,
+    playlists: Vec<Topic>,
+    assignments: Vec<Assignment>,
+    skills: SkillRegistry,
+    goals: Vec<Goal>,
+    keymap: Keymap,
+    keyboard_layout: KeyboardLayout,
     list_items: Vec<HubListItem>,
     list_state: ListState,
-    pending_g: bool,
+    pending: Vec<KeyPress>,
     count: Option<u32>,
     list_height: u16,
+    narrow: NarrowLayout,
     unlock_all: bool,
+    /// Category unlock rule, `Off` when `unlock_all` is set.
+    unlock_policy: UnlockPolicy,
+    /// Load problems and lint warnings of the challenges, listed by the
+    /// warnings key.
+    diagnostics: Vec<Diagnostic>,
+    lints: Vec<Lint>,
 }
 
 impl Hub {
-    pub fn new(topics: Vec<Topic>, unlock_all: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        topics: Vec<Topic>,
+        playlists: Vec<Topic>,
+        assignments: Vec<Assignment>,
+        skills: SkillRegistry,
+        goals: Vec<Goal>,
+        keymap: Keymap,
+        keyboard_layout: KeyboardLayout,
+        unlock_all: bool,
+        unlock_policy: UnlockPolicy,
+    ) -> Self {
         let mut list_items = Vec::new();
 
         for cat in Category::ALL {
@@ -60,6 +126,15 @@ impl Hub {
                 });
             }
         }
+        list_items.push(HubListItem::Spacer);
+        list_items.push(HubListItem::Header(Category::Collection));
+        list_items.push(HubListItem::Favorites);
+        list_items.push(HubListItem::Drill);
+        list_items.push(HubListItem::Revalidate);
+        list_items.extend((0..playlists.len()).map(HubListItem::Playlist));
+        list_items.extend((0..assignments.len()).map(HubListItem::Assignment));
+        list_items.push(HubListItem::Spacer);
+        list_items.push(HubListItem::Sandbox);
 
         let mut list_state = ListState::default();
         // Select first selectable entry
@@ -72,96 +147,138 @@ impl Hub {
 
         Self {
             topics,
+            playlists,
+            assignments,
+            skills,
+            goals,
+            keymap,
+            keyboard_layout,
             list_items,
             list_state,
-            pending_g: false,
+            pending: Vec::new(),
             count: None,
             list_height: 0,
+            narrow: NarrowLayout::default(),
             unlock_all,
+            unlock_policy: if unlock_all {
+                UnlockPolicy::Off
+            } else {
+                unlock_policy
+            },
+            diagnostics: Vec::new(),
+            lints: Vec::new(),
         }
     }
 
+    /// List load problems and lints on the warnings screen, count them in the
+    /// footer and show challenges that failed to load in the header.
+    #[must_use]
+    pub fn with_warnings(mut self, diagnostics: Vec<Diagnostic>, lints: Vec<Lint>) -> Self {
+        self.diagnostics = diagnostics;
+        self.lints = lints;
+        self
+    }
+
     pub fn run(
         &mut self,
         terminal: &mut ratatui::DefaultTerminal,
         state: &GameState,
     ) -> std::io::Result<HubAction> {
         loop {
-            terminal.draw(|frame| self.render(frame, state))?;
+            terminal.draw(|frame| {
+                self.render(frame, state);
+                theme::apply(frame.buffer_mut());
+            })?;
 
-            if event::poll(Duration::from_millis(100))?
-                && let Event::Key(key) = event::read()?
-            {
+            if let Some(key) = screen::poll_key(terminal)? {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
-                // Handle pending gg
-                if self.pending_g {
-                    self.pending_g = false;
-                    self.count = None;
-                    if key.code == KeyCode::Char('g') {
-                        self.jump_first(state);
-                        continue;
-                    }
-                }
 
-                // Count prefix (applied to j/k)
-                match key.code {
-                    KeyCode::Char(c @ '1'..='9') => {
-                        self.count = Some(self.count.unwrap_or(0) * 10 + (c as u32 - '0' as u32));
-                        continue;
-                    }
-                    KeyCode::Char('0') if self.count.is_some() => {
-                        self.count = self.count.map(|c| c * 10);
-                        continue;
-                    }
-                    _ => {}
+                // Count prefix (applied to up/down)
+                if self.pending.is_empty() && game::count_prefix(&mut self.count, key.code) {
+                    continue;
                 }
 
+                let Some(action) = self.keymap.resolve(&mut self.pending, key.into()) else {
+                    if self.pending.is_empty() {
+                        self.count = None;
+                    }
+                    continue;
+                };
                 let n = self.count.unwrap_or(1) as usize;
                 self.count = None;
 
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(HubAction::Quit),
-                    KeyCode::Char('j') => {
+                match action {
+                    Action::Quit => return Ok(HubAction::Quit),
+                    Action::Down => {
                         for _ in 0..n {
                             self.next(state);
                         }
                     }
-                    KeyCode::Char('k') => {
+                    Action::Up => {
                         for _ in 0..n {
                             self.previous(state);
                         }
                     }
-                    KeyCode::Char('g') => self.pending_g = true,
-                    KeyCode::Char('G') => self.jump_last(state),
-                    KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Action::Top => self.jump_first(state),
+                    Action::Bottom => self.jump_last(state),
+                    Action::HalfPageDown => {
                         let half = (self.list_height / 2).max(1) as usize;
                         for _ in 0..half {
                             self.next(state);
                         }
                     }
-                    KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Action::HalfPageUp => {
                         let half = (self.list_height / 2).max(1) as usize;
                         for _ in 0..half {
                             self.previous(state);
                         }
                     }
-                    KeyCode::Char('l') | KeyCode::Enter => {
+                    Action::Select => {
                         if let Some(i) = self.list_state.selected()
-                            && let HubListItem::Entry { topic_id, .. } = &self.list_items[i]
-                            && is_category_unlocked(
-                                Category::for_topic(*topic_id),
-                                &self.topics,
-                                state,
-                                self.unlock_all,
-                            )
+                            && self.is_item_selectable(i, state)
                         {
-                            return Ok(HubAction::SelectTopic(*topic_id));
+                            return Ok(match &self.list_items[i] {
+                                HubListItem::Entry { topic_id, .. } => {
+                                    HubAction::SelectTopic(*topic_id)
+                                }
+                                HubListItem::Favorites => HubAction::Favorites,
+                                HubListItem::Drill => HubAction::Drill(
+                                    "Drill: weakest challenges",
+                                    self.drill(state).iter().map(|c| c.id.clone()).collect(),
+                                ),
+                                HubListItem::Revalidate => HubAction::Drill(
+                                    "Re-validate: outdated scores",
+                                    self.outdated(state).iter().map(|c| c.id.clone()).collect(),
+                                ),
+                                HubListItem::Playlist(p) => {
+                                    HubAction::Playlist(self.playlists[*p].id)
+                                }
+                                HubListItem::Assignment(a) => HubAction::Assignment(*a),
+                                _ => HubAction::Sandbox,
+                            });
                         }
                     }
-                    KeyCode::Char('?') => {
-                        game::show_help(terminal)?;
+                    Action::Skills => {
+                        skills::show_skills(
+                            terminal,
+                            &self.keymap,
+                            &self.skills,
+                            &self.topics,
+                            state,
+                            self.keyboard_layout,
+                        )?;
+                    }
+                    Action::Help => game::show_help(terminal)?,
+                    Action::Layout => self.narrow = self.narrow.next(),
+                    Action::Warnings => self.show_warnings(terminal)?,
+                    Action::Intro => self.show_intro(terminal)?,
+                    Action::Reference => return Ok(HubAction::Reference),
+                    Action::Reset => {
+                        if let Some(action) = self.confirm_reset(terminal, state)? {
+                            return Ok(action);
+                        }
                     }
                     _ => {}
                 }
@@ -169,6 +286,68 @@ impl Hub {
         }
     }
 
+    /// The topic of the selected entry, if a topic is selected.
+    fn selected_topic(&self) -> Option<&Topic> {
+        match self.list_state.selected().map(|i| &self.list_items[i]) {
+            Some(HubListItem::Entry { topic_id, .. }) => {
+                self.topics.iter().find(|t| t.id == *topic_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Show the intro of the selected topic, if it has one.
+    fn show_intro(&self, terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+        match self.selected_topic() {
+            Some(topic) => intro::show_intro(terminal, &self.keymap, topic),
+            None => Ok(()),
+        }
+    }
+
+    /// List the load problems and lint warnings of the challenges.
+    fn show_warnings(&self, terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+        lint::show_warnings(
+            terminal,
+            &self.keymap,
+            "Challenge warnings",
+            &self.diagnostics,
+            &self.lints,
+        )
+    }
+
+    /// Ask before resetting the selected topic, or before accepting the
+    /// outdated scores as historical on the re-validate entry.
+    fn confirm_reset(
+        &self,
+        terminal: &mut ratatui::DefaultTerminal,
+        state: &GameState,
+    ) -> std::io::Result<Option<HubAction>> {
+        let (question, action) = match self.list_state.selected().map(|i| &self.list_items[i]) {
+            Some(HubListItem::Entry {
+                topic_id,
+                topic_name,
+                ..
+            }) => (
+                format!(
+                    "Reset all of {topic_name}? Best results and attempts of its challenges \
+                     are deleted; notes and bookmarks stay."
+                ),
+                HubAction::ResetTopic(*topic_id),
+            ),
+            Some(HubListItem::Revalidate) if state.stale_count() > 0 => (
+                format!(
+                    "Accept {} outdated score(s) as historical? They are archived and count \
+                     as not completed until solved again; attempts stay.",
+                    state.stale_count()
+                ),
+                HubAction::AcceptStale,
+            ),
+            _ => return Ok(None),
+        };
+        let confirmed = screen::confirm(terminal, &question)?;
+        Ok(confirmed.then_some(action))
+    }
+
     fn render(&mut self, frame: &mut Frame, state: &GameState) {
         let [header, body, footer] = Layout::vertical([
             Constraint::Length(5),
@@ -177,20 +356,55 @@ impl Hub {
         ])
         .areas(frame.area());
 
-        Self::render_header(frame, header, state, &self.topics);
+        let failed = self.diagnostics.iter().filter(|d| d.failed).count();
+        Self::render_header(frame, header, state, &self.topics, &self.goals, failed);
         self.render_topics(frame, body, state);
+        let keys = &self.keymap;
+        let layout = if body.width < screen::NARROW_WIDTH {
+            format!(" | {}: layout", keys.label(Action::Layout))
+        } else {
+            String::new()
+        };
+        let intro = if self.selected_topic().is_some_and(|t| t.intro.is_some()) {
+            format!(" | {}: intro", keys.label(Action::Intro))
+        } else {
+            String::new()
+        };
+        let warnings = match self.diagnostics.len() + self.lints.len() {
+            0 => String::new(),
+            1 => format!(" | {}: 1 warning", keys.label(Action::Warnings)),
+            n => format!(" | {}: {n} warnings", keys.label(Action::Warnings)),
+        };
         frame.render_widget(
-            Paragraph::new(" j/k: navigate | l/Enter: select | ?: help | q: quit")
-                .style(Style::new().fg(Color::DarkGray)),
+            Paragraph::new(format!(
+                " {}/{}: navigate | {}: select | {}: skills | {}: reference | {}: reset{intro}{layout}{warnings} \
+                 | {}: help | {}: quit",
+                keys.label(Action::Down),
+                keys.label(Action::Up),
+                keys.label(Action::Select),
+                keys.label(Action::Skills),
+                keys.label(Action::Reference),
+                keys.label(Action::Reset),
+                keys.label(Action::Help),
+                keys.label(Action::Quit),
+            ))
+            .style(Style::new().fg(Color::DarkGray)),
             footer,
         );
     }
 
-    fn render_header(frame: &mut Frame, area: Rect, state: &GameState, topics: &[Topic]) {
+    fn render_header(
+        frame: &mut Frame,
+        area: Rect,
+        state: &GameState,
+        topics: &[Topic],
+        goals: &[Goal],
+        failed: usize,
+    ) {
         let [title_area, stats_area] =
             Layout::vertical([Constraint::Length(3), Constraint::Length(2)]).areas(area);
 
-        let title = Paragraph::new(Line::from(vec![
+        let mut title_spans = vec![
             Span::raw(" "),
             Span::styled(
                 " NVIMKATA ",
@@ -199,14 +413,23 @@ impl Hub {
                     .bg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
-        ]))
-        .block(Block::bordered());
+        ];
+        let today = goals::today();
+        for goal in goals {
+            title_spans.extend(goal_spans(goal, state, today));
+        }
+        let title = Paragraph::new(Line::from(title_spans)).block(Block::bordered());
         frame.render_widget(title, title_area);
 
-        // Exclude freestyle topics from completion/perfect stats
+        // Exclude tutorial, freestyle and pack topics from completion/perfect stats
         let curriculum_topics: Vec<&Topic> = topics
             .iter()
-            .filter(|t| Category::for_topic(t.id) != Category::Freestyle)
+            .filter(|t| {
+                !matches!(
+                    Category::for_topic(t.id),
+                    Category::Tutorial | Category::Freestyle | Category::Pack
+                )
+            })
             .collect();
         let curriculum_ids: std::collections::HashSet<&str> = curriculum_topics
             .iter()
@@ -224,6 +447,7 @@ impl Hub {
             .filter(|(id, r)| curriculum_ids.contains(id.as_str()) && r.grade == Grade::A)
             .count();
         let outdated = state.stale_count();
+        let decayed = state.decayed_count();
         let mut stats_spans = vec![Span::styled(
             format!(
                 " Completed: {completed}/{total} | Grade A: {perfects} | Attempts: {}",
@@ -238,13 +462,27 @@ impl Hub {
                 Style::new().fg(Color::Yellow),
             ));
         }
+        if decayed > 0 {
+            stats_spans.push(Span::styled(
+                format!(" | {decayed} grade(s) decayed ~"),
+                Style::new().fg(Color::Gray),
+            ));
+        }
+        if failed > 0 {
+            stats_spans.push(Span::styled(" | ", Style::new().fg(Color::Gray)));
+            stats_spans.push(Span::styled(
+                match failed {
+                    1 => "1 challenge failed to load".to_string(),
+                    n => format!("{n} challenges failed to load"),
+                },
+                Style::new().fg(Color::Red),
+            ));
+        }
         frame.render_widget(Paragraph::new(Line::from(stats_spans)), stats_area);
     }
 
     fn render_topics(&mut self, frame: &mut Frame, area: Rect, state: &GameState) {
-        let [list_area, detail_area] =
-            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .areas(area);
+        let [list_area, detail_area] = screen::split_panels(area, self.narrow);
 
         self.list_height = list_area.height.saturating_sub(2);
 
@@ -293,13 +531,108 @@ impl Hub {
 
         frame.render_stateful_widget(list, list_area, &mut self.list_state);
 
+        let [detail_area, recommend_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(RECOMMENDATION_COUNT + 2),
+        ])
+        .areas(detail_area);
+
         // Detail panel
-        if let Some(i) = self.list_state.selected()
-            && let HubListItem::Entry { topic_id, .. } = &self.list_items[i]
-            && let Some(topic) = self.topics.iter().find(|t| t.id == *topic_id)
-        {
-            Self::render_topic_detail(frame, detail_area, topic, state);
+        match self.list_state.selected().map(|i| &self.list_items[i]) {
+            Some(HubListItem::Entry { topic_id, .. }) => {
+                if let Some(topic) = self.topics.iter().find(|t| t.id == *topic_id) {
+                    Self::render_topic_detail(frame, detail_area, topic, state);
+                }
+            }
+            Some(HubListItem::Favorites) => {
+                let topic = collection::favorites(&self.topics, state);
+                Self::render_topic_detail(frame, detail_area, &topic, state);
+            }
+            Some(HubListItem::Drill) => self.render_drill_detail(frame, detail_area, state),
+            Some(HubListItem::Revalidate) => {
+                self.render_revalidate_detail(frame, detail_area, state);
+            }
+            Some(HubListItem::Playlist(p)) => {
+                Self::render_topic_detail(frame, detail_area, &self.playlists[*p], state);
+            }
+            Some(HubListItem::Assignment(a)) => {
+                Self::render_assignment_detail(frame, detail_area, &self.assignments[*a], state);
+            }
+            Some(HubListItem::Sandbox) => Self::render_sandbox_detail(frame, detail_area),
+            _ => {}
         }
+
+        self.render_recommendations(frame, recommend_area, state);
+    }
+
+    /// Challenges of all unlocked topics that are unlocked themselves.
+    fn unlocked_challenges(&self, state: &GameState) -> Vec<&Challenge> {
+        self.topics
+            .iter()
+            .filter(|t| {
+                unlock::is_category_unlocked(
+                    Category::for_topic(t.id),
+                    &self.topics,
+                    state,
+                    self.unlock_policy,
+                )
+            })
+            .flat_map(|t| t.challenges.iter())
+            .filter(|c| unlock::is_challenge_unlocked(c, state, self.unlock_all))
+            .collect()
+    }
+
+    /// Challenges of the weak-spot drill.
+    fn drill(&self, state: &GameState) -> Vec<&Challenge> {
+        recommend::weakest(
+            &self.unlocked_challenges(state),
+            state,
+            playlist::DRILL_SIZE,
+        )
+    }
+
+    /// Challenges with outdated scores, to re-validate.
+    fn outdated(&self, state: &GameState) -> Vec<&Challenge> {
+        self.topics
+            .iter()
+            .flat_map(|t| &t.challenges)
+            .filter(|c| state.is_stale(&c.id))
+            .collect()
+    }
+
+    /// Suggest the next challenges to practice from all unlocked topics.
+    fn render_recommendations(&self, frame: &mut Frame, area: Rect, state: &GameState) {
+        let candidates = self.unlocked_challenges(state);
+
+        let dim = Style::new().fg(Color::Gray);
+        let lines: Vec<Line> =
+            recommend::recommend(&candidates, state, usize::from(RECOMMENDATION_COUNT))
+                .into_iter()
+                .map(|c| {
+                    let (grade_str, grade_style) = game::best_grade_display(state, &c.id);
+                    let mut spans = vec![
+                        Span::styled(format!("[{grade_str}] "), grade_style),
+                        Span::raw(c.title.as_str()),
+                    ];
+                    if let Some(topic) = self
+                        .topics
+                        .iter()
+                        .find(|t| t.challenges.iter().any(|tc| tc.id == c.id))
+                    {
+                        spans.push(Span::styled(format!(" ({})", topic.name), dim));
+                    }
+                    if state.is_stale(&c.id) {
+                        spans.push(Span::styled(" *", Style::new().fg(Color::Yellow)));
+                    }
+                    if state.is_decayed(&c.id) {
+                        spans.push(Span::styled(" ~", game::DECAYED));
+                    }
+                    Line::from(spans)
+                })
+                .collect();
+
+        let panel = Paragraph::new(lines).block(Block::bordered().title(" Recommended "));
+        frame.render_widget(panel, area);
     }
 
     fn render_list_item<'a>(
@@ -310,8 +643,21 @@ impl Hub {
     ) -> ListItem<'a> {
         match item {
             HubListItem::Spacer => ListItem::new(Line::from("")),
+            HubListItem::Sandbox => ListItem::new(Line::from(vec![
+                num_span,
+                Span::styled("~ Sandbox", Style::new().fg(Color::Cyan)),
+            ])),
+            HubListItem::Favorites
+            | HubListItem::Drill
+            | HubListItem::Revalidate
+            | HubListItem::Playlist(_)
+            | HubListItem::Assignment(_) => ListItem::new(Line::from(vec![
+                num_span,
+                self.collection_span(item, state),
+            ])),
             HubListItem::Header(cat) => {
-                let locked = !is_category_unlocked(*cat, &self.topics, state, self.unlock_all);
+                let locked =
+                    !unlock::is_category_unlocked(*cat, &self.topics, state, self.unlock_policy);
                 let suffix = if locked { " [LOCKED]" } else { "" };
                 let style = if locked {
                     Style::new().fg(Color::DarkGray)
@@ -327,89 +673,147 @@ impl Hub {
                 topic_id,
                 topic_name,
                 total,
-            } => {
-                let cat = Category::for_topic(*topic_id);
-                let locked = !is_category_unlocked(cat, &self.topics, state, self.unlock_all);
-
-                if locked {
-                    return ListItem::new(Line::from(vec![
-                        num_span,
-                        Span::styled(
-                            format!("x {topic_name} ({total})"),
-                            Style::new().fg(Color::DarkGray),
-                        ),
-                    ]));
-                }
+            } => self.render_topic_item(*topic_id, topic_name, *total, num_span, state),
+        }
+    }
 
-                let attempted = self
-                    .topics
-                    .iter()
-                    .find(|t| t.id == *topic_id)
-                    .map_or(0, |t| {
-                        t.challenges
-                            .iter()
-                            .filter(|c| state.best_grade(&c.id).is_some())
-                            .count()
-                    });
-
-                let has_stale = self
-                    .topics
-                    .iter()
-                    .find(|t| t.id == *topic_id)
-                    .is_some_and(|t| t.challenges.iter().any(|c| state.is_stale(&c.id)));
-                let stale_suffix: Vec<Span> = if has_stale {
-                    vec![Span::styled(" *", Style::new().fg(Color::Yellow))]
+    /// Label of a collection entry, dimmed while there is nothing to play.
+    fn collection_span(&self, item: &HubListItem, state: &GameState) -> Span<'static> {
+        let (label, color, empty) = match item {
+            HubListItem::Favorites => (
+                format!("♥ Favorites ({})", state.favorites.len()),
+                Color::Magenta,
+                state.favorites.is_empty(),
+            ),
+            HubListItem::Drill => (
+                "! Drill weakest".to_string(),
+                Color::Red,
+                self.drill(state).is_empty(),
+            ),
+            HubListItem::Revalidate => {
+                let outdated = self.outdated(state).len();
+                (
+                    format!("* Re-validate ({outdated})"),
+                    Color::Yellow,
+                    outdated == 0,
+                )
+            }
+            HubListItem::Playlist(p) => {
+                let playlist = &self.playlists[*p];
+                (
+                    format!("≡ {} ({})", playlist.name, playlist.challenges.len()),
+                    Color::Yellow,
+                    playlist.challenges.is_empty(),
+                )
+            }
+            HubListItem::Assignment(a) => {
+                let assignment = &self.assignments[*a];
+                let (color, mark) = if assignment.is_complete(state) {
+                    (Color::Green, " ✓")
+                } else if assignment.is_overdue(state, goals::today()) {
+                    (Color::Red, " overdue")
                 } else {
-                    vec![]
+                    (Color::Cyan, "")
                 };
+                (
+                    format!(
+                        "✎ {} ({}/{}){}",
+                        assignment.title,
+                        assignment.done_count(state),
+                        assignment.challenges.len(),
+                        if theme::markers() { mark } else { "" }
+                    ),
+                    color,
+                    assignment.challenges.is_empty(),
+                )
+            }
+            _ => (String::new(), Color::Reset, true),
+        };
+        Span::styled(
+            label,
+            Style::new().fg(if empty { Color::DarkGray } else { color }),
+        )
+    }
 
-                if cat == Category::Freestyle {
-                    let mut spans = vec![
-                        num_span,
-                        Span::styled(
-                            format!("> {topic_name} ({attempted}/{total})"),
-                            Style::new().fg(Color::White),
-                        ),
-                    ];
-                    spans.extend(stale_suffix);
-                    return ListItem::new(Line::from(spans));
-                }
+    fn render_topic_item<'a>(
+        &self,
+        topic_id: u8,
+        topic_name: &str,
+        total: usize,
+        num_span: Span<'a>,
+        state: &GameState,
+    ) -> ListItem<'a> {
+        let cat = Category::for_topic(topic_id);
+        let locked = !unlock::is_category_unlocked(cat, &self.topics, state, self.unlock_policy);
 
-                let all_done = attempted == *total && *total > 0;
-                let all_perfect = all_done
-                    && self
-                        .topics
-                        .iter()
-                        .find(|t| t.id == *topic_id)
-                        .is_some_and(|t| {
-                            t.challenges
-                                .iter()
-                                .all(|c| state.best_grade(&c.id) == Some(Grade::A))
-                        });
-
-                let prefix = if all_perfect {
-                    "* "
-                } else if all_done {
-                    "+ "
-                } else {
-                    "> "
-                };
-                let style = if all_perfect {
-                    Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD)
-                } else if all_done {
-                    Style::new().fg(Color::Green)
-                } else {
-                    Style::new().fg(Color::White)
-                };
+        if locked {
+            return ListItem::new(Line::from(vec![
+                num_span,
+                Span::styled(
+                    format!("x {topic_name} ({total})"),
+                    Style::new().fg(Color::DarkGray),
+                ),
+            ]));
+        }
 
-                let mut spans = vec![
-                    num_span,
-                    Span::styled(format!("{prefix}{topic_name} ({attempted}/{total})"), style),
-                ];
-                spans.extend(stale_suffix);
-                ListItem::new(Line::from(spans))
-            }
+        let topic = self.topics.iter().find(|t| t.id == topic_id);
+        let attempted = topic.map_or(0, |t| {
+            t.challenges
+                .iter()
+                .filter(|c| state.best_grade(&c.id).is_some())
+                .count()
+        });
+
+        let has_stale = topic.is_some_and(|t| t.challenges.iter().any(|c| state.is_stale(&c.id)));
+        let has_decayed =
+            topic.is_some_and(|t| t.challenges.iter().any(|c| state.is_decayed(&c.id)));
+        let markers = [
+            has_stale.then(|| Span::styled(" *", Style::new().fg(Color::Yellow))),
+            has_decayed.then(|| Span::styled(" ~", game::DECAYED)),
+        ];
+
+        if cat == Category::Freestyle {
+            let mut spans = vec![
+                num_span,
+                Span::styled(
+                    format!("> {topic_name} ({attempted}/{total})"),
+                    Style::new().fg(Color::White),
+                ),
+            ];
+            spans.extend(markers.into_iter().flatten());
+            return ListItem::new(Line::from(spans));
         }
+
+        let all_done = total > 0
+            && topic.is_some_and(|t| t.challenges.iter().all(|c| state.is_completed(&c.id)));
+        let all_perfect = all_done
+            && topic.is_some_and(|t| {
+                t.challenges
+                    .iter()
+                    .all(|c| state.best_grade(&c.id) == Some(Grade::A))
+            });
+
+        let prefix = if all_perfect {
+            "* "
+        } else if all_done {
+            "+ "
+        } else {
+            "> "
+        };
+        let style = if all_perfect {
+            Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD)
+        } else if all_done {
+            Style::new().fg(Color::Green)
+        } else {
+            Style::new().fg(Color::White)
+        };
+
+        let mut spans = vec![
+            num_span,
+            Span::styled(format!("{prefix}{topic_name} ({attempted}/{total})"), style),
+        ];
+        spans.extend(markers.into_iter().flatten());
+        ListItem::new(Line::from(spans))
     }
 
     fn render_topic_detail(frame: &mut Frame, area: Rect, topic: &Topic, state: &GameState) {
@@ -419,7 +823,10 @@ impl Hub {
 
         let mut spans = vec![Span::styled("Description: ", Style::new().fg(Color::Gray))];
         let tag_style = Style::new().fg(Color::White).bg(Color::DarkGray);
-        if cat == Category::Freestyle {
+        if matches!(
+            cat,
+            Category::Freestyle | Category::Pack | Category::Collection
+        ) {
             spans.push(Span::styled(format!(" {} ", topic.description), tag_style));
         } else {
             for (i, skill) in topic.description.split(", ").enumerate() {
@@ -430,16 +837,32 @@ impl Hub {
             }
         }
         lines.push(Line::from(spans));
+        let last = match state
+            .last_practiced(&topic.challenges)
+            .map(|t| goals::today().saturating_sub(goals::day_of(t)))
+        {
+            None => "never".to_string(),
+            Some(0) => "today".to_string(),
+            Some(1) => "yesterday".to_string(),
+            Some(days) => format!("{days} days ago"),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Last practiced: ", Style::new().fg(Color::Gray)),
+            Span::raw(last),
+        ]));
         lines.push(Line::from(""));
 
-        let is_freestyle = cat == Category::Freestyle;
         let stale_span = Span::styled(" *", Style::new().fg(Color::Yellow));
         for challenge in &topic.challenges {
             let is_stale = state.is_stale(&challenge.id);
-            if is_freestyle {
+            if challenge.is_freestyle() {
                 let (badge, badge_style) = if let Some(best) = state.best_keystrokes(&challenge.id)
                 {
-                    (format!("[{best}]"), Style::new().fg(Color::Cyan))
+                    if state.beat_reference(&challenge.id) {
+                        (format!("[{best}★]"), Grade::A.style())
+                    } else {
+                        (format!("[{best}]"), Style::new().fg(Color::Cyan))
+                    }
                 } else {
                     ("[-]".to_string(), Style::new().fg(Color::Gray))
                 };
@@ -457,7 +880,7 @@ impl Hub {
                 }
                 lines.push(Line::from(spans));
             } else {
-                let (grade_str, grade_style) = grade_display(state.best_grade(&challenge.id));
+                let (grade_str, grade_style) = game::best_grade_display(state, &challenge.id);
                 let title_style = if state.best_grade(&challenge.id).is_some() {
                     Style::new()
                 } else {
@@ -470,6 +893,9 @@ impl Hub {
                 if is_stale {
                     spans.push(stale_span.clone());
                 }
+                if state.is_decayed(&challenge.id) {
+                    spans.push(Span::styled(" ~", game::DECAYED));
+                }
                 lines.push(Line::from(spans));
             }
         }
@@ -484,14 +910,213 @@ impl Hub {
         frame.render_widget(detail, area);
     }
 
+    fn render_assignment_detail(
+        frame: &mut Frame,
+        area: Rect,
+        assignment: &Assignment,
+        state: &GameState,
+    ) {
+        let dim = Style::new().fg(Color::Gray);
+        let mut lines = Vec::new();
+        if !assignment.description.is_empty() {
+            lines.push(Line::from(Span::styled(
+                assignment.description.as_str(),
+                dim,
+            )));
+            lines.push(Line::from(""));
+        }
+        if let Some(due) = assignment.due {
+            let today = goals::today();
+            let (when, style) = match due.cmp(&today) {
+                _ if assignment.is_complete(state) => (String::new(), dim),
+                std::cmp::Ordering::Greater => (format!(" (in {} days)", due - today), dim),
+                std::cmp::Ordering::Equal => {
+                    (" (today)".to_string(), Style::new().fg(Color::Yellow))
+                }
+                std::cmp::Ordering::Less => (
+                    format!(" (overdue by {} days)", today - due),
+                    Style::new().fg(Color::Red),
+                ),
+            };
+            lines.push(Line::from(vec![
+                Span::styled("Due: ", dim),
+                Span::raw(goals::format_day(due)),
+                Span::styled(when, style),
+            ]));
+        }
+        if let Some(grade) = assignment.min_grade {
+            lines.push(Line::from(vec![
+                Span::styled("Minimum grade: ", dim),
+                Span::styled(grade.display_char(), grade.style()),
+            ]));
+        }
+        lines.push(Line::from(vec![
+            Span::styled("Progress: ", dim),
+            Span::raw(format!(
+                "{}/{}",
+                assignment.done_count(state),
+                assignment.challenges.len()
+            )),
+        ]));
+        lines.push(Line::from(""));
+
+        for challenge in &assignment.challenges {
+            let (mark, mark_style) = if assignment.is_done(challenge, state) {
+                ("[x] ", Style::new().fg(Color::Green))
+            } else {
+                ("[ ] ", dim)
+            };
+            let (grade_str, grade_style) = grade_display(state.best_grade(&challenge.id));
+            lines.push(Line::from(vec![
+                Span::styled(mark, mark_style),
+                Span::styled(format!("[{grade_str}] "), grade_style),
+                Span::raw(challenge.title.as_str()),
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Press ENTER to start the assignment",
+            Style::new().fg(Color::Green),
+        )));
+        lines.push(Line::from(Span::styled(
+            "Export a completion report with `nvimkata report`",
+            dim,
+        )));
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+
+    fn render_drill_detail(&self, frame: &mut Frame, area: Rect, state: &GameState) {
+        let dim = Style::new().fg(Color::Gray);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "Replay the challenges with the worst grades for their difficulty,",
+                dim,
+            )),
+            Line::from(Span::styled(
+                "one after another, and compare the grades before and after.",
+                dim,
+            )),
+            Line::from(""),
+        ];
+        let drill = self.drill(state);
+        if drill.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Nothing to drill: complete a few challenges below Grade A first.",
+                dim,
+            )));
+        } else {
+            for challenge in drill {
+                let (grade_str, grade_style) = grade_display(state.best_grade(&challenge.id));
+                lines.push(Line::from(vec![
+                    Span::styled(format!("[{grade_str}] "), grade_style),
+                    Span::raw(challenge.title.as_str()),
+                ]));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Press ENTER to start the drill",
+                Style::new().fg(Color::Green),
+            )));
+        }
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+
+    fn render_revalidate_detail(&self, frame: &mut Frame, area: Rect, state: &GameState) {
+        let dim = Style::new().fg(Color::Gray);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "Replay the challenges changed since their best results,",
+                dim,
+            )),
+            Line::from(Span::styled(
+                "one after another, to bring the outdated scores up to date.",
+                dim,
+            )),
+            Line::from(""),
+        ];
+        let outdated = self.outdated(state);
+        if outdated.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Nothing to re-validate: every score is up to date.",
+                dim,
+            )));
+        } else {
+            for challenge in outdated {
+                let (grade_str, grade_style) = grade_display(state.best_grade(&challenge.id));
+                lines.push(Line::from(vec![
+                    Span::styled(format!("[{grade_str}] "), grade_style),
+                    Span::raw(challenge.title.as_str()),
+                    Span::styled(" *", Style::new().fg(Color::Yellow)),
+                ]));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Press ENTER to re-validate them",
+                Style::new().fg(Color::Green),
+            )));
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Press {} to accept them as historical instead",
+                    self.keymap.label(Action::Reset)
+                ),
+                dim,
+            )));
+        }
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+
+    fn render_sandbox_detail(frame: &mut Frame, area: Rect) {
+        let dim = Style::new().fg(Color::Gray);
+        let lines = vec![
+            Line::from(Span::styled(
+                "Practice freely in neovim with the keystroke counter running.",
+                dim,
+            )),
+            Line::from(Span::styled(
+                "Nothing is graded or saved: warm up or try out motions.",
+                dim,
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Start from a blank buffer or any challenge's start content.",
+                dim,
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Press ENTER to choose a buffer",
+                Style::new().fg(Color::Green),
+            )),
+        ];
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+
     fn is_item_selectable(&self, idx: usize, state: &GameState) -> bool {
         match &self.list_items[idx] {
             HubListItem::Spacer | HubListItem::Header(_) => false,
-            HubListItem::Entry { topic_id, .. } => is_category_unlocked(
+            HubListItem::Sandbox => true,
+            HubListItem::Favorites => !state.favorites.is_empty(),
+            HubListItem::Drill => !self.drill(state).is_empty(),
+            HubListItem::Revalidate => !self.outdated(state).is_empty(),
+            HubListItem::Playlist(p) => !self.playlists[*p].challenges.is_empty(),
+            HubListItem::Assignment(a) => !self.assignments[*a].challenges.is_empty(),
+            HubListItem::Entry { topic_id, .. } => unlock::is_category_unlocked(
                 Category::for_topic(*topic_id),
                 &self.topics,
                 state,
-                self.unlock_all,
+                self.unlock_policy,
             ),
         }
     }
@@ -553,28 +1178,43 @@ impl Hub {
     }
 }
 
-/// A category is unlocked if all challenges in the previous category have been completed.
-fn is_category_unlocked(
-    cat: Category,
-    topics: &[Topic],
-    state: &GameState,
-    unlock_all: bool,
-) -> bool {
-    if unlock_all {
-        return true;
+/// Progress bar and streak for a daily goal, shown in the hub header.
+fn goal_spans(goal: &Goal, state: &GameState, today: u32) -> Vec<Span<'static>> {
+    const WIDTH: u32 = 10;
+    let progress = goal.progress(&state.activity_on(today));
+    let filled =
+        usize::try_from(progress.min(goal.target) * WIDTH / goal.target.max(1)).unwrap_or_default();
+    let reached = progress >= goal.target;
+    let color = if reached { Color::Green } else { Color::Yellow };
+    let streak = state.streak(goal, today);
+    let mut spans = vec![
+        Span::styled(
+            format!("  {progress}/{} {} ", goal.target, goal.unit()),
+            Style::new().fg(Color::Gray),
+        ),
+        Span::styled("█".repeat(filled), Style::new().fg(color)),
+        Span::styled(
+            "░".repeat(WIDTH as usize - filled),
+            Style::new().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            if reached && theme::markers() {
+                " ✓"
+            } else {
+                ""
+            },
+            Style::new().fg(color),
+        ),
+        Span::styled(
+            format!(" streak {}", streak.current),
+            Style::new().fg(Color::Cyan),
+        ),
+    ];
+    if streak.freezes > 0 {
+        spans.push(Span::styled(
+            format!(" ({}❄)", streak.freezes),
+            Style::new().fg(Color::Blue),
+        ));
     }
-    let prev = match cat {
-        Category::Beginner | Category::Freestyle => return true,
-        Category::Intermediate => Category::Beginner,
-        Category::Advanced => Category::Intermediate,
-        Category::Legendary => Category::Advanced,
-    };
-    topics
-        .iter()
-        .filter(|t| Category::for_topic(t.id) == prev && !t.challenges.is_empty())
-        .all(|t| {
-            t.challenges
-                .iter()
-                .all(|c| state.best_grade(&c.id).is_some())
-        })
+    spans
 }
@@ -0,0 +1,118 @@
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::challenge::Topic;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::preview;
+use crate::screen;
+use crate::theme;
+
+/// Style of `code` spans and fenced blocks, where the keys are.
+const CODE: Style = Style::new().fg(Color::Yellow);
+
+/// Lines of a topic intro, with a light touch of Markdown: `#` headings in
+/// bold, `code` spans and fenced blocks highlighted. Anything else is shown
+/// as written, so tables and lists keep their layout.
+pub fn render(intro: &str) -> Vec<Line<'static>> {
+    let mut fenced = false;
+    let mut lines = Vec::new();
+    for line in intro.trim_end().lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            lines.push(Line::styled(format!("  {line}"), CODE));
+        } else if line.starts_with('#') {
+            let heading = line.trim_start_matches('#').trim();
+            lines.push(Line::styled(
+                heading.to_string(),
+                Style::new().fg(Color::White).add_modifier(Modifier::BOLD),
+            ));
+        } else {
+            lines.push(Line::from(code_spans(line)));
+        }
+    }
+    lines
+}
+
+/// Split a line at its backticks, highlighting the `code` between them. An
+/// unpaired backtick is kept as text.
+fn code_spans(line: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('`')
+        && let Some(len) = rest[start + 1..].find('`')
+    {
+        spans.push(Span::raw(rest[..start].to_string()));
+        spans.push(Span::styled(
+            rest[start + 1..start + 1 + len].to_string(),
+            CODE,
+        ));
+        rest = &rest[start + len + 2..];
+    }
+    spans.push(Span::raw(rest.to_string()));
+    spans
+}
+
+/// Show the intro of a topic, scrollable. Returns on the intro key, back or
+/// quit; does nothing for a topic without an intro.
+pub fn show_intro(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &Keymap,
+    topic: &Topic,
+) -> std::io::Result<()> {
+    let Some(intro) = &topic.intro else {
+        return Ok(());
+    };
+    let lines = render(intro);
+    let max = u16::try_from(lines.len().saturating_sub(1)).unwrap_or(u16::MAX);
+    let mut offset: u16 = 0;
+    let mut page: u16 = 0;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            page = main.height.saturating_sub(2);
+            let title = Line::from(Span::styled(
+                format!(" {} ", topic.name),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            frame.render_widget(
+                Paragraph::new(lines.clone())
+                    .block(Block::bordered().title(title))
+                    .wrap(Wrap { trim: false })
+                    .scroll((offset, 0)),
+                main,
+            );
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: scroll | {}/{}: half page | {}/{}: close",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::HalfPageDown),
+                    keymap.label(Action::HalfPageUp),
+                    keymap.label(Action::Intro),
+                    keymap.label(Action::Quit),
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Intro | Action::Back | Action::Quit) => return Ok(()),
+                Some(action) => offset = preview::scroll(offset, action, page, max),
+                None => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Navigation and actions shared by the hub, the challenge picker and list screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Down,
+    Up,
+    Top,
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    Select,
+    /// Leave the current screen (the hub has nothing to go back to).
+    Back,
+    Quit,
+    Help,
+    Skills,
+    /// Scroll the detail panel.
+    ScrollDown,
+    ScrollUp,
+    /// Full-screen target preview.
+    Preview,
+    /// Side-by-side start vs target preview.
+    Compare,
+    /// Stored attempts of a challenge.
+    History,
+    /// Delete the selected entry.
+    Delete,
+    /// Bookmark the selected challenge.
+    Bookmark,
+    /// Show or hide the best-time column.
+    Times,
+    /// Play every challenge blind, with the target hidden.
+    Blind,
+    /// On a narrow terminal, switch between stacked panels, only the list and
+    /// only the details.
+    Layout,
+    /// Clear the progress of the selected challenge or topic.
+    Reset,
+    /// Switch the order of a list, e.g. attempts by keystrokes or by date.
+    Sort,
+    /// Lint warnings of the loaded challenges.
+    Warnings,
+    /// Intro and cheat sheet of the selected topic.
+    Intro,
+    /// Command reference, with the challenges using each command.
+    Reference,
+    /// Start typing a search query.
+    Search,
+    /// Switch between the tabs of a screen, e.g. attempts and solutions.
+    Tab,
+}
+
+impl Action {
+    const ALL: [Action; 28] = [
+        Self::Down,
+        Self::Up,
+        Self::Top,
+        Self::Bottom,
+        Self::HalfPageDown,
+        Self::HalfPageUp,
+        Self::Select,
+        Self::Back,
+        Self::Quit,
+        Self::Help,
+        Self::Skills,
+        Self::ScrollDown,
+        Self::ScrollUp,
+        Self::Preview,
+        Self::Compare,
+        Self::History,
+        Self::Delete,
+        Self::Bookmark,
+        Self::Times,
+        Self::Blind,
+        Self::Layout,
+        Self::Reset,
+        Self::Sort,
+        Self::Warnings,
+        Self::Intro,
+        Self::Reference,
+        Self::Search,
+        Self::Tab,
+    ];
+
+    /// Default bindings in key notation.
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Down => &["j", "<Down>"],
+            Self::Up => &["k", "<Up>"],
+            Self::Top => &["gg", "<Home>"],
+            Self::Bottom => &["G", "<End>"],
+            Self::HalfPageDown => &["<C-d>"],
+            Self::HalfPageUp => &["<C-u>"],
+            Self::Select => &["l", "<Enter>"],
+            Self::Back => &["h"],
+            Self::Quit => &["q", "<Esc>"],
+            Self::Help => &["?"],
+            Self::Skills => &["s"],
+            Self::ScrollDown => &["J", "<C-e>"],
+            Self::ScrollUp => &["K", "<C-y>"],
+            Self::Preview => &["f"],
+            Self::Compare => &["p"],
+            Self::History => &["H"],
+            Self::Delete => &["dd"],
+            Self::Bookmark => &["b"],
+            Self::Times => &["t"],
+            Self::Blind => &["B"],
+            Self::Layout => &["L"],
+            Self::Reset => &["x"],
+            Self::Sort => &["o"],
+            Self::Warnings => &["W"],
+            Self::Intro => &["i"],
+            Self::Reference => &["R"],
+            Self::Search => &["/"],
+            Self::Tab => &["<Tab>"],
+        }
+    }
+}
+
+/// A single key press: the key code and whether Ctrl was held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl From<KeyEvent> for KeyPress {
+    fn from(key: KeyEvent) -> Self {
+        Self {
+            code: key.code,
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+}
+
+/// Parse key notation such as `gg`, `<C-d>` or `<Down>` into key presses.
+pub fn parse_keys(notation: &str) -> Result<Vec<KeyPress>, String> {
+    let mut keys = Vec::new();
+    let mut rest = notation;
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && end > 1
+        {
+            keys.push(parse_special(&rest[1..end])?);
+            rest = &rest[end + 1..];
+        } else {
+            keys.push(KeyPress {
+                code: KeyCode::Char(c),
+                ctrl: false,
+            });
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if keys.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    Ok(keys)
+}
+
+fn parse_special(name: &str) -> Result<KeyPress, String> {
+    if let Some(key) = name.strip_prefix("C-").or_else(|| name.strip_prefix("c-")) {
+        let mut chars = key.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(KeyPress {
+                code: KeyCode::Char(c.to_ascii_lowercase()),
+                ctrl: true,
+            }),
+            _ => Err(format!("unknown key <{name}>")),
+        };
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "down" => KeyCode::Down,
+        "up" => KeyCode::Up,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "cr" | "return" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "bs" => KeyCode::Backspace,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "lt" => KeyCode::Char('<'),
+        _ => return Err(format!("unknown key <{name}>")),
+    };
+    Ok(KeyPress { code, ctrl: false })
+}
+
+/// Key bindings for TUI screens. Bindings may be multi-key sequences like `gg`.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyPress>, Action)>,
+    labels: BTreeMap<Action, String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl Keymap {
+    /// Build a keymap from the defaults, replacing the bindings of every action
+    /// listed in `overrides`. Invalid notation is reported and skipped.
+    pub fn new(overrides: &BTreeMap<Action, Vec<String>>) -> Self {
+        let mut bindings = Vec::new();
+        let mut labels = BTreeMap::new();
+        for action in Action::ALL {
+            let keys: Vec<&str> = match overrides.get(&action) {
+                Some(keys) => keys.iter().map(String::as_str).collect(),
+                None => action.default_keys().to_vec(),
+            };
+            for notation in keys {
+                match parse_keys(notation) {
+                    Ok(seq) => {
+                        labels.entry(action).or_insert_with(|| notation.to_string());
+                        bindings.push((seq, action));
+                    }
+                    Err(e) => eprintln!("Warning: ignoring key binding '{notation}': {e}"),
+                }
+            }
+        }
+        Self { bindings, labels }
+    }
+
+    /// Feed a key press. `pending` holds the keys of an unfinished sequence
+    /// between calls. Returns the action once a binding is complete.
+    pub fn resolve(&self, pending: &mut Vec<KeyPress>, key: KeyPress) -> Option<Action> {
+        pending.push(key);
+        loop {
+            if let Some((_, action)) = self.bindings.iter().find(|(seq, _)| seq == pending) {
+                pending.clear();
+                return Some(*action);
+            }
+            if self
+                .bindings
+                .iter()
+                .any(|(seq, _)| seq.len() > pending.len() && seq.starts_with(pending))
+            {
+                return None;
+            }
+            if pending.len() <= 1 {
+                pending.clear();
+                return None;
+            }
+            // The sequence went nowhere; retry with only the latest key.
+            pending.drain(..pending.len() - 1);
+        }
+    }
+
+    /// First binding of an action in key notation, for footers.
+    pub fn label(&self, action: Action) -> &str {
+        self.labels.get(&action).map_or("-", String::as_str)
+    }
+}
@@ -1,8 +1,56 @@
 #![allow(clippy::must_use_candidate, clippy::missing_errors_doc)]
 
+pub mod animation;
+pub mod assignment;
+pub mod calibrate;
+pub mod cast;
+pub mod catalog;
 pub mod challenge;
+pub mod cli;
+pub mod collection;
+pub mod commands;
+pub mod compare;
+pub mod config;
 pub mod curriculum;
+pub mod diff;
+pub mod dirs;
+pub mod duel;
+pub mod editor;
+pub mod engine;
+pub mod error;
+pub mod feedback;
 pub mod game;
+pub mod generate;
+pub mod goals;
+pub mod heatmap;
+pub mod history;
 pub mod hub;
+pub mod intro;
+pub mod keymap;
+pub mod lint;
+pub mod mutate;
+pub mod notation;
 pub mod nvim;
+pub mod one_hand;
+pub mod opponents;
+pub mod pack;
+pub mod patch;
+pub mod playlist;
+pub mod preview;
+pub mod recommend;
+pub mod reference;
+pub mod registry;
+pub mod report;
+pub mod rpc;
+pub mod sandbox;
+pub mod screen;
+pub mod skills;
 pub mod state;
+pub mod stepper;
+pub mod theme;
+pub mod unlock;
+pub mod versus;
+pub mod vim;
+
+pub use engine::{LoadOptions, Nvimkata};
+pub use error::Error;
@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use serde::{Deserialize, Serialize};
+
+use crate::challenge::{Challenge, Topic};
+use crate::curriculum::Diagnostic;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::notation::{self, Key};
+use crate::preview;
+use crate::screen;
+use crate::theme;
+
+/// Lines wider than this wrap when the buffer and the target share a terminal.
+pub const MAX_LINE_WIDTH: usize = 80;
+
+/// A problem with the content of a challenge that doesn't keep it from loading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lint {
+    /// ID of the challenge.
+    pub challenge: String,
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.challenge, self.message)
+    }
+}
+
+/// Check the buffers and hint of a challenge: trailing whitespace in the
+/// target, which matching ignores; indentation mixing tabs and spaces; lines
+/// wider than [`MAX_LINE_WIDTH`]; and ex commands or special keys named in the
+/// hint that `perfect_moves` never uses.
+pub fn check(challenge: &Challenge) -> Vec<Lint> {
+    let mut messages = Vec::new();
+    let trailing: Vec<String> = numbered(&challenge.target().content)
+        .filter(|(_, line)| line.ends_with([' ', '\t']))
+        .map(|(n, _)| n.to_string())
+        .collect();
+    if !trailing.is_empty() {
+        messages.push(format!(
+            "trailing whitespace on target line {}, which matching ignores",
+            trailing.join(", ")
+        ));
+    }
+
+    let start = indentation(&challenge.start().content);
+    let target = indentation(&challenge.target().content);
+    for (name, indent) in [("start", start), ("target", target)] {
+        if indent == (true, true) {
+            messages.push(format!("{name} indents with both tabs and spaces"));
+        }
+    }
+    if start != target && start.0 != start.1 && target.0 != target.1 {
+        let kind = |(tabs, _): (bool, bool)| if tabs { "tabs" } else { "spaces" };
+        messages.push(format!(
+            "start indents with {} but target with {}",
+            kind(start),
+            kind(target)
+        ));
+    }
+
+    for (name, content) in [
+        ("start", &challenge.start().content),
+        ("target", &challenge.target().content),
+    ] {
+        if let Some((n, width)) = numbered(content)
+            .map(|(n, line)| (n, line.chars().count()))
+            .filter(|(_, width)| *width > MAX_LINE_WIDTH)
+            .max_by_key(|(_, width)| *width)
+        {
+            messages.push(format!(
+                "{name} line {n} is {width} columns wide, more than {MAX_LINE_WIDTH}"
+            ));
+        }
+    }
+
+    if let Some(moves) = &challenge.perfect_moves {
+        for key in unused_hint_keys(&challenge.hint_tiers().join(" "), moves) {
+            messages.push(format!(
+                "hint mentions {key}, which perfect_moves never uses"
+            ));
+        }
+    }
+    messages
+        .into_iter()
+        .map(|message| Lint {
+            challenge: challenge.id.clone(),
+            message,
+        })
+        .collect()
+}
+
+/// One lint for each challenge ID shared by several challenges, naming the
+/// topics they are in.
+pub fn duplicate_ids(topics: &[Topic]) -> Vec<Lint> {
+    let mut seen: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for topic in topics {
+        for challenge in &topic.challenges {
+            seen.entry(&challenge.id).or_default().push(&topic.name);
+        }
+    }
+    seen.into_iter()
+        .filter(|(_, topics)| topics.len() > 1)
+        .map(|(id, topics)| Lint {
+            challenge: id.to_string(),
+            message: format!(
+                "ID is used by {} challenges, in {}",
+                topics.len(),
+                topics.join(", ")
+            ),
+        })
+        .collect()
+}
+
+/// Lines with their 1-based numbers.
+fn numbered(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.lines().enumerate().map(|(i, line)| (i + 1, line))
+}
+
+/// Whether any line of `content` is indented with tabs, and with spaces.
+fn indentation(content: &str) -> (bool, bool) {
+    content
+        .lines()
+        .fold((false, false), |(tabs, spaces), line| {
+            let indent: String = line
+                .chars()
+                .take_while(|c| matches!(c, ' ' | '\t'))
+                .collect();
+            (
+                tabs || indent.contains('\t'),
+                spaces || indent.contains(' '),
+            )
+        })
+}
+
+/// Ex commands with a short name that isn't an abbreviation of the long one.
+const EX_ALIASES: [(&str, &str); 2] = [("t", "copy"), ("m", "move")];
+
+/// Ex commands like `:sort` and special keys like `<C-r>` named in `hint`
+/// that `moves` never use, in hint order.
+fn unused_hint_keys(hint: &str, moves: &[String]) -> Vec<String> {
+    let moves = moves.concat();
+    let used_commands = ex_commands(&moves);
+    let used_keys = notation::tokenize(&moves);
+    let mut unused = Vec::new();
+    for word in hint.split_whitespace() {
+        let word = word.trim_matches(|c: char| matches!(c, ',' | '.' | '(' | ')' | '\'' | '"'));
+        if word.starts_with(':') {
+            for name in ex_commands(word) {
+                let used = used_commands.iter().any(|used| same_command(used, &name));
+                if !used && !unused.contains(&format!(":{name}")) {
+                    unused.push(format!(":{name}"));
+                }
+            }
+        }
+        for key in special_keys(word) {
+            let name = key.to_string();
+            if !used_keys.contains(&key) && !unused.contains(&name) {
+                unused.push(name);
+            }
+        }
+    }
+    unused
+}
+
+/// Names of the ex commands in a key sequence: the lowercase letters after
+/// each `:` and its range, e.g. `s` for `:%s/a/b` and `sort` for `:'<,'>sort`.
+fn ex_commands(keys: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = keys;
+    while let Some(pos) = rest.find(':') {
+        rest = &rest[pos + 1..];
+        let mut chars = rest.chars().peekable();
+        let mut range_len = 0;
+        while let Some(&c) = chars.peek() {
+            let mark = c == '\'';
+            if !(mark || c.is_ascii_digit() || matches!(c, '%' | ',' | '.' | '$')) {
+                break;
+            }
+            chars.next();
+            range_len += c.len_utf8();
+            // A mark is followed by its name, like 'a or '<
+            if mark && let Some(name) = chars.next() {
+                range_len += name.len_utf8();
+            }
+        }
+        let name: String = rest[range_len..]
+            .chars()
+            .take_while(char::is_ascii_lowercase)
+            .collect();
+        if !name.is_empty() {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Whether two ex command names are the same command. Either may be
+/// abbreviated, like `norm` for `normal`, or an alias, like `t` for `copy`.
+fn same_command(a: &str, b: &str) -> bool {
+    let long = |name| {
+        EX_ALIASES
+            .iter()
+            .find(|(short, _)| *short == name)
+            .map_or(name, |(_, long)| *long)
+    };
+    let (a, b) = (long(a), long(b));
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Ctrl and named special keys in key notation within a word, like `<C-r>`
+/// in `<C-r>0`.
+fn special_keys(word: &str) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut rest = word;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        match rest[1..].find('>') {
+            Some(end)
+                if end > 0
+                    && rest[1..=end]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-') =>
+            {
+                if let Some(key) = Key::parse(&rest[..end + 2])
+                    && key.is_known()
+                    && !matches!(key, Key::Char(_))
+                {
+                    keys.push(key);
+                }
+                rest = &rest[end + 2..];
+            }
+            _ => rest = &rest[1..],
+        }
+    }
+    keys
+}
+
+/// List load problems and lint warnings under `title`, each group under a
+/// heading when there are both. Returns on the warnings key, back or quit.
+pub fn show_warnings(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &Keymap,
+    title: &str,
+    diagnostics: &[Diagnostic],
+    lints: &[Lint],
+) -> std::io::Result<()> {
+    let headings = !diagnostics.is_empty() && !lints.is_empty();
+    let heading = |text| Line::styled(text, Style::new().add_modifier(Modifier::BOLD));
+    let mut rows: Vec<Line> = Vec::new();
+    if headings {
+        rows.push(heading("Load problems"));
+    }
+    for diagnostic in diagnostics {
+        let color = if diagnostic.failed {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        // Parse errors span several lines, with the offending source
+        let mut message = diagnostic.message.lines();
+        rows.push(Line::from(vec![
+            Span::styled(
+                format!("{} ", diagnostic.path.display()),
+                Style::new().fg(color),
+            ),
+            Span::raw(message.next().unwrap_or_default()),
+        ]));
+        rows.extend(message.map(|line| Line::raw(format!("  {line}"))));
+    }
+    if headings {
+        rows.push(Line::raw(""));
+        rows.push(heading("Content warnings"));
+    }
+    rows.extend(lints.iter().map(|lint| {
+        Line::from(vec![
+            Span::styled(
+                format!("{} ", lint.challenge),
+                Style::new().fg(Color::Yellow),
+            ),
+            Span::raw(lint.message.as_str()),
+        ])
+    }));
+    let count = diagnostics.len() + lints.len();
+    let max = u16::try_from(rows.len().saturating_sub(1)).unwrap_or(u16::MAX);
+    let mut offset: u16 = 0;
+    let mut page: u16 = 0;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            page = main.height.saturating_sub(2);
+            let title = Line::from(Span::styled(
+                format!(" {title} ({count}) "),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            frame.render_widget(
+                Paragraph::new(rows.clone())
+                    .block(Block::bordered().title(title))
+                    .wrap(Wrap { trim: false })
+                    .scroll((offset, 0)),
+                main,
+            );
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: scroll | {}/{}: half page | {}/{}: close",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::HalfPageDown),
+                    keymap.label(Action::HalfPageUp),
+                    keymap.label(Action::Warnings),
+                    keymap.label(Action::Quit),
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Warnings | Action::Back | Action::Quit) => return Ok(()),
+                Some(action) => offset = preview::scroll(offset, action, page, max),
+                None => {}
+            }
+        }
+    }
+}
@@ -1,106 +1,1408 @@
-use std::path::PathBuf;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use nvimkata::{challenge, curriculum, game, hub, state};
+use nvimkata::catalog::{ListFormat, ListSort};
+use nvimkata::challenge::Grade;
+use nvimkata::cli::{
+    self, AuthorCommand, CliError, Command, ConfigCommand, DuelCommand, PackCommand, PlayArgs,
+    ResetScope, StateCommand,
+};
+use nvimkata::editor::{self, EditorKind};
+use nvimkata::error::CurriculumError;
+use nvimkata::{
+    animation, assignment, calibrate, cast, catalog, challenge, collection, config, curriculum,
+    dirs, feedback, game, generate, goals, hub, keymap, lint, mutate, notation, nvim, opponents,
+    pack, patch, playlist, reference, registry, report, rpc, sandbox, skills, state, stepper,
+    theme, unlock, versus,
+};
 
-fn challenges_dir() -> PathBuf {
-    // Check for bundled challenges next to the binary first,
-    // then fall back to the current directory.
-    if let Ok(exe) = std::env::current_exe() {
-        let dir = exe
-            .parent()
-            .unwrap_or(&exe)
-            .join("../share/nvimkata/challenges");
-        if dir.exists() {
-            return dir;
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse(&args).and_then(run_command) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e.report());
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run_command(command: Command) -> Result<ExitCode, CliError> {
+    match command {
+        Command::Help(name) => {
+            print!("{}", cli::help(name));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Version => {
+            println!("nvimkata {}", env!("CARGO_PKG_VERSION"));
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
+
+    // Session directories left behind by crashed runs
+    dirs::clean_stale_sessions(&dirs::sessions_root());
+    let config = config::load_config();
+    config.apply().map_err(CliError::failed)?;
+    let challenges_path = config.challenges_path();
+    match command {
+        Command::Help(_) | Command::Version => {}
+        Command::Play(args) => play(&args, &challenges_path, &config)?,
+        Command::List {
+            profile,
+            format,
+            sort,
+        } => list(profile, format, sort, &challenges_path, &config)?,
+        Command::Stats { profile } => stats(profile, &challenges_path, &config)?,
+        Command::Export { profile, output } => export(profile, output.as_deref(), &config)?,
+        Command::Report {
+            profile,
+            since,
+            format,
+        } => report(profile, since, format, &challenges_path, &config)?,
+        Command::Reset { profile, scope } => reset(profile, &scope, &challenges_path, &config)?,
+        Command::State(StateCommand::Merge { file, profile }) => {
+            merge_state(profile, &file, &config)?;
+        }
+        Command::State(StateCommand::Doctor { profile }) => state_doctor(profile, &config)?,
+        Command::Pack(command) => run_pack(command, &config)?,
+        Command::Author(command) => {
+            if !run_author(command, &challenges_path, &config)? {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Gen {
+            template,
+            seed,
+            output,
+        } => generate(template.as_deref(), seed, output.as_deref())?,
+        Command::FromFile {
+            file,
+            mutation,
+            seed,
+            output,
+            nvim_bin,
+        } => from_file(&file, mutation, seed, output.as_deref(), nvim_bin, &config)?,
+        Command::ImportDiff { patch, whole, dir } => import_diff(&patch, whole, dir.as_deref())?,
+        Command::Replay {
+            id,
+            attempt,
+            export,
+            profile,
+            nvim_bin,
+        } => {
+            let launcher = headless_launcher(&config, nvim_bin)?;
+            let state = load_state(profile, &config)?;
+            let topics = load_topics(&challenges_path)?;
+            replay(&id, attempt, export.as_ref(), &topics, &state, &launcher)?;
+        }
+        Command::Versus {
+            id,
+            players,
+            nvim_bin,
+        } => play_versus(&id, &players, nvim_bin, &challenges_path, &config)?,
+        Command::Duel(command) => run_duel(command, &challenges_path, &config)?,
+        Command::RpcServe { profile } => {
+            let topics = load_topics(&challenges_path)?;
+            let mut state = load_state(profile, &config)?;
+            state.mark_ambiguous(&topics);
+            let mut server = rpc::Server::new(&topics, &mut state, dirs::sessions_root(), true)
+                .with_session_options(config.session_options())
+                .with_unlock(config.unlock_all, config.unlock_policy);
+            rpc::serve(
+                std::io::stdin().lock(),
+                std::io::stdout().lock(),
+                &mut server,
+            )?;
+        }
+        Command::Config(ConfigCommand::Path) => println!("{}", config::config_path().display()),
+        Command::Config(ConfigCommand::Show) => {
+            let path = config::config_path();
+            match std::fs::read_to_string(&path) {
+                Ok(content) => {
+                    println!("# {}", path.display());
+                    print!("{content}");
+                }
+                Err(_) => println!("# {} does not exist, using the defaults", path.display()),
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `profile`, or the profile from the config file.
+fn resolve_profile(
+    profile: Option<String>,
+    config: &config::Config,
+) -> Result<Option<String>, CliError> {
+    let profile = profile.or_else(|| config.profile.clone());
+    if let Some(name) = profile.as_deref().filter(|p| !state::is_valid_profile(p)) {
+        return Err(CliError::failed(format!(
+            "invalid profile name '{name}' in the config file (use letters, digits, '-' and '_')"
+        )));
+    }
+    Ok(profile)
+}
+
+/// Load the save of `profile`, or of the profile from the config file.
+fn load_state(
+    profile: Option<String>,
+    config: &config::Config,
+) -> Result<state::GameState, CliError> {
+    let profile = resolve_profile(profile, config)?;
+    state::GameState::load_profile(profile.as_deref()).map_err(|e| {
+        if e.conflict {
+            CliError::failed(format!(
+                "unresolved merge conflict in '{}', run 'nvimkata state doctor' to merge both sides",
+                e.path.display()
+            ))
+        } else {
+            CliError::failed(format!(
+                "incompatible save file at '{}', delete the file to start fresh",
+                e.path.display()
+            ))
+        }
+    })
+}
+
+/// Print `output`. A closed pipe, as with `nvimkata list | head`, is not an error.
+fn write_stdout(output: &str) -> Result<(), CliError> {
+    match std::io::stdout().lock().write_all(output.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Load the curriculum and the installed packs, failing if there are no challenges.
+fn load_topics(challenges_path: &Path) -> Result<Vec<challenge::Topic>, CliError> {
+    require_challenges(load_printing_problems(challenges_path), challenges_path)
+}
+
+/// [`pack::load_with_packs`] for commands without the TUI: load problems are printed.
+fn load_printing_problems(challenges_path: &Path) -> Vec<challenge::Topic> {
+    let (topics, load) = pack::load_with_packs(challenges_path);
+    for diagnostic in &load.diagnostics {
+        eprintln!("Warning: {diagnostic}");
+    }
+    topics
+}
+
+fn require_challenges(
+    topics: Vec<challenge::Topic>,
+    challenges_path: &Path,
+) -> Result<Vec<challenge::Topic>, CliError> {
+    if topics.iter().all(|t| t.challenges.is_empty()) {
+        return Err(CliError::failed(CurriculumError::Empty {
+            dir: challenges_path.to_path_buf(),
+        }));
+    }
+    Ok(topics)
+}
+
+/// Run `nvimkata list`: every challenge by topic with its best result.
+fn list(
+    profile: Option<String>,
+    format: ListFormat,
+    sort: ListSort,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let topics = load_topics(challenges_path)?;
+    let state = load_state(profile, config)?;
+    let out = match sort {
+        ListSort::Topic => catalog::render(&topics, &state, format)?,
+        ListSort::Time => catalog::speedrun(&topics, &state, format)?,
+    };
+    write_stdout(&out)
+}
+
+/// Run `nvimkata stats`: completion, grades, keystrokes, lifetime practice and
+/// most used commands.
+fn stats(
+    profile: Option<String>,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let topics = load_topics(challenges_path)?;
+    let state = load_state(profile, config)?;
+    let challenges: Vec<challenge::Challenge> =
+        topics.iter().flat_map(|t| t.challenges.clone()).collect();
+    let completed = challenges
+        .iter()
+        .filter(|c| state.is_completed(&c.id))
+        .count();
+    println!("Completed: {completed}/{} challenges", challenges.len());
+    println!("Runs recorded: {}", state.stats.challenges_attempted);
+    println!("Total keystrokes: {}", state.stats.total_keystrokes);
+    let grades: Vec<String> = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::E, Grade::F]
+        .into_iter()
+        .map(|grade| {
+            let count = challenges
+                .iter()
+                .filter(|c| !c.is_freestyle() && state.best_grade(&c.id) == Some(grade))
+                .count();
+            format!("{} {count}", grade.display_char())
+        })
+        .collect();
+    println!("Best grades: {}", grades.join(" | "));
+    let runs: Vec<String> = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::E, Grade::F]
+        .into_iter()
+        .map(|grade| {
+            format!(
+                "{} {}",
+                grade.display_char(),
+                state.stats.grade_count(grade)
+            )
+        })
+        .collect();
+    println!("Runs by grade: {}", runs.join(" | "));
+    println!(
+        "Practice time: {}",
+        report::format_duration(state.stats.practice_secs)
+    );
+    if state.stats.longest_streak > 0 {
+        println!("Longest streak: {} day(s)", state.stats.longest_streak);
+    }
+    if let Some(first) = state.stats.first_played {
+        println!("First played: {}", goals::format_day(first));
+    }
+    for category in challenge::Category::ALL {
+        if let Some(&at) = state.stats.category_completed.get(category.name()) {
+            println!(
+                "Completed {}: {}",
+                category.name(),
+                goals::format_day(goals::day_of(at))
+            );
+        }
+    }
+    let top: Vec<String> = state
+        .stats
+        .top_commands(5)
+        .iter()
+        .map(|(name, n)| format!("{name} ({n})"))
+        .collect();
+    if !top.is_empty() {
+        println!("Top commands: {}", top.join(", "));
+    }
+    let reference = reference::load_reference(challenges_path);
+    let never_used: Vec<&str> = reference
+        .never_used(&state, &reference.challenges(&topics))
+        .into_iter()
+        .map(|i| reference.commands[i].keys.as_str())
+        .collect();
+    if !never_used.is_empty() {
+        println!("Never used: {}", never_used.join(", "));
+    }
+    let last = state
+        .stats
+        .last_played
+        .or_else(|| state.last_practiced(&challenges).map(goals::day_of));
+    if let Some(last) = last {
+        println!("Last practiced: {}", goals::format_day(last));
+    }
+    Ok(())
+}
+
+/// Run `nvimkata export`: the save as JSON, to a file or stdout.
+fn export(
+    profile: Option<String>,
+    output: Option<&Path>,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let state = load_state(profile, config)?;
+    let json = serde_json::to_string_pretty(&state)?;
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")
+            .map_err(|e| CliError::failed(format!("failed to write {}: {e}", path.display())))?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+/// Run `nvimkata gen`: a generated challenge file, to a file or stdout, or the
+/// templates without one.
+fn generate(
+    template: Option<&str>,
+    seed: Option<u64>,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let Some(name) = template else {
+        for template in generate::TEMPLATES {
+            println!("{:<14} {}", template.name, template.description);
+        }
+        return Ok(());
+    };
+    let template = generate::template(name).ok_or_else(|| {
+        let names: Vec<&str> = generate::TEMPLATES.iter().map(|t| t.name).collect();
+        CliError::failed(format!(
+            "no template '{name}' (available: {})",
+            names.join(", ")
+        ))
+    })?;
+    let generated = generate::generate(template, seed.unwrap_or_else(clock_seed));
+    write_generated(&generated, output)
+}
+
+/// Run `nvimkata from-file`: play restoring `file` from `mutation` as an
+/// ad-hoc freestyle challenge, or write the challenge to `output`.
+fn from_file(
+    file: &Path,
+    mutation: mutate::Mutation,
+    seed: Option<u64>,
+    output: Option<&Path>,
+    nvim_bin: Option<String>,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let source = std::fs::read_to_string(file)
+        .map_err(|e| CliError::failed(format!("failed to read {}: {e}", file.display())))?;
+    let generated = mutate::from_file(file, &source, mutation, seed.unwrap_or_else(clock_seed))
+        .map_err(CliError::failed)?;
+    if output.is_some() {
+        return write_generated(&generated, output);
+    }
+
+    let mut launcher = config.launcher();
+    if let Some(bin) = nvim_bin {
+        launcher.bin = bin;
+    }
+    require_editor(&mut launcher)?;
+    let challenge = generated.challenge()?;
+    println!("{}", generated.description);
+    let result = editor::editor(&launcher).run_challenge(&challenge, 1)?;
+    let time = game::format_time(result.elapsed_ms);
+    if result.outcome == nvim::Outcome::Aborted {
+        println!("Quit without restoring it");
+    } else if result.buffer_matches {
+        println!("Restored in {} keystrokes, {time}", result.keystrokes);
+    } else {
+        println!("Not restored ({} keystrokes, {time})", result.keystrokes);
+    }
+    println!("Play it again with: {}", generated.command);
+    Ok(())
+}
+
+/// Run `nvimkata import-diff`: write a challenge file per hunk of `patch`, or
+/// one for the whole patch, to `dir`.
+fn import_diff(patch: &Path, whole: bool, dir: Option<&Path>) -> Result<(), CliError> {
+    let text = std::fs::read_to_string(patch)
+        .map_err(|e| CliError::failed(format!("failed to read {}: {e}", patch.display())))?;
+    let hunks =
+        patch::parse(&text).map_err(|e| CliError::failed(format!("{}: {e}", patch.display())))?;
+    if hunks.is_empty() {
+        return Err(CliError::failed(format!(
+            "no hunks found in {}",
+            patch.display()
+        )));
+    }
+    let dir = dir.unwrap_or(Path::new("."));
+    for generated in patch::challenges(patch, &hunks, whole) {
+        let file = dir.join(format!("{}.toml", generated.id));
+        write_generated(&generated, Some(&file))?;
+        println!("{}  {}", file.display(), generated.title);
+    }
+    Ok(())
+}
+
+/// Run `nvimkata replay`: snapshot attempt `attempt` of `id` keystroke by
+/// keystroke, or command by command for the HTML and text exports, then play
+/// it in the terminal or write it to the export's file.
+fn replay(
+    id: &str,
+    attempt: usize,
+    export: Option<&cast::ReplayExport>,
+    topics: &[challenge::Topic],
+    state: &state::GameState,
+    launcher: &nvim::Launcher,
+) -> Result<(), CliError> {
+    let challenge = topics
+        .iter()
+        .flat_map(|t| t.challenges.iter())
+        .find(|c| c.id == id)
+        .ok_or_else(|| CliError::failed(format!("no challenge with id '{id}'")))?
+        .loaded()?;
+    let attempts = state.history.get(id).map_or(&[][..], Vec::as_slice);
+    let record = attempts.get(attempt - 1).ok_or_else(|| {
+        CliError::failed(match attempts.len() {
+            0 => format!("no attempts of '{id}' recorded"),
+            1 => format!("'{id}' has 1 recorded attempt"),
+            n => format!("'{id}' has {n} recorded attempts"),
+        })
+    })?;
+    if record.keys.is_empty() {
+        return Err(CliError::failed(format!(
+            "attempt {attempt} of '{id}' has no key log (key_log is off in the config)"
+        )));
+    }
+    let steps: Vec<String> = if export.is_some_and(cast::ReplayExport::by_command) {
+        stepper::steps(&record.keys)
+    } else {
+        notation::tokenize(&record.keys)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    };
+    let snapshots = nvim::snapshots(launcher, &challenge, &steps, Duration::from_secs(30))?;
+    if snapshots.len() <= steps.len() {
+        eprintln!(
+            "Warning: the replay timed out after {} of {} steps",
+            snapshots.len().saturating_sub(1),
+            steps.len()
+        );
+    }
+    let result = cast::result_line(record, challenge.is_freestyle());
+    let Some(export) = export else {
+        let cast = cast::Cast::replay(
+            &challenge,
+            &steps,
+            &snapshots,
+            record.time_ms.unwrap_or_default(),
+            &result,
+        );
+        let mut out = std::io::stdout().lock();
+        let mut clock = 0;
+        for (ms, output) in &cast.events {
+            std::thread::sleep(Duration::from_millis(u64::from(ms - clock)));
+            clock = *ms;
+            out.write_all(output.as_bytes())?;
+            out.flush()?;
         }
+        return Ok(());
+    };
+    let content = match export {
+        cast::ReplayExport::Asciinema(_) => cast::Cast::replay(
+            &challenge,
+            &steps,
+            &snapshots,
+            record.time_ms.unwrap_or_default(),
+            &result,
+        )
+        .to_asciicast(),
+        cast::ReplayExport::Html(_) => cast::html(&challenge, &steps, &snapshots, &result),
+        cast::ReplayExport::Text(_) => cast::text(&challenge, &steps, &snapshots, &result),
+    };
+    let path = export.path();
+    std::fs::write(path, content)
+        .map_err(|e| CliError::failed(format!("failed to write {}: {e}", path.display())))?;
+    match export {
+        cast::ReplayExport::Asciinema(_) => println!(
+            "Wrote {} ({} keystrokes); play it with: asciinema play {}",
+            path.display(),
+            steps.len(),
+            path.display()
+        ),
+        _ => println!("Wrote {} ({} commands)", path.display(), steps.len()),
     }
-    PathBuf::from("challenges")
+    Ok(())
 }
 
-fn print_help() {
-    let version = env!("CARGO_PKG_VERSION");
-    println!("nvimkata {version} — practice efficient editing in Neovim");
+/// Run `nvimkata versus`: each player in turn plays `id` once, then the
+/// summary and running score are shown. The runs stay out of the save file;
+/// only the versus ledger counts them.
+fn play_versus(
+    id: &str,
+    players: &[String],
+    nvim_bin: Option<String>,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let topics = load_topics(challenges_path)?;
+    let challenge = topics
+        .iter()
+        .flat_map(|t| t.challenges.iter())
+        .find(|c| c.id == id)
+        .ok_or_else(|| CliError::failed(format!("no challenge with id '{id}'")))?
+        .loaded()?;
+    let mut launcher = config.launcher();
+    if let Some(bin) = nvim_bin {
+        launcher.bin = bin;
+    }
+    require_editor(&mut launcher)?;
+
+    println!("{}: {}", challenge.id, challenge.title);
+    let mut turns = Vec::new();
+    for player in players {
+        print!("{player}, your turn. Press Enter to start...");
+        std::io::stdout().flush()?;
+        std::io::stdin().read_line(&mut String::new())?;
+        let result = editor::editor(&launcher).run_challenge(&challenge, 1)?;
+        turns.push(versus::Turn::of(player, &result));
+    }
+
+    let path = versus::ledger_path();
+    let ledger_error =
+        |e: std::io::Error| CliError::failed(format!("versus ledger {}: {e}", path.display()));
+    let mut ledger = versus::Ledger::load(&path).map_err(ledger_error)?;
+    let score = ledger.record(&turns).clone();
+    ledger.save(&path).map_err(ledger_error)?;
     println!();
-    println!("Usage: nvimkata [OPTIONS]");
+    for line in versus::summary(&turns, &score) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Run `nvimkata duel`: the relay, or a player's side of a duel.
+#[cfg(feature = "net")]
+fn run_duel(
+    command: DuelCommand,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    match command {
+        DuelCommand::Relay { listen } => {
+            let listener = std::net::TcpListener::bind(&listen)
+                .map_err(|e| CliError::failed(format!("failed to listen on {listen}: {e}")))?;
+            println!("Relaying duels on {listen}");
+            nvimkata::duel::relay(&listener)?;
+            Ok(())
+        }
+        DuelCommand::Join {
+            relay,
+            room,
+            player,
+            challenge,
+            nvim_bin,
+        } => {
+            let mut launcher = config.launcher();
+            if let Some(bin) = nvim_bin {
+                launcher.bin = bin;
+            }
+            require_editor(&mut launcher)?;
+            let topics = load_topics(challenges_path)?;
+            let find = |id: &str| {
+                topics
+                    .iter()
+                    .flat_map(|t| t.challenges.iter())
+                    .find(|c| c.id == id)
+                    .ok_or_else(|| CliError::failed(format!("no challenge with id '{id}'")))
+            };
+            if let Some(id) = &challenge {
+                find(id)?;
+            }
+            println!("Waiting for an opponent in room {room} on {relay}...");
+            let (connection, id, opponent) =
+                nvimkata::duel::Connection::join(&relay, &room, &player, challenge.as_deref())
+                    .map_err(|e| CliError::failed(format!("duel on {relay}: {e}")))?;
+            let challenge = find(&id)?.loaded()?;
+            println!("{player} vs {opponent}: {}", challenge.title);
+            play_duel(&launcher, &challenge, connection, &player, &opponent)
+        }
+    }
+}
+
+/// Play the challenge of a started duel, showing the opponent's result in
+/// the bar as soon as it is in, then compare both runs. Neither is recorded.
+#[cfg(feature = "net")]
+fn play_duel(
+    launcher: &nvim::Launcher,
+    challenge: &challenge::Challenge,
+    connection: nvimkata::duel::Connection,
+    player: &str,
+    opponent: &str,
+) -> Result<(), CliError> {
+    let session = dirs::SessionDir::create("duel")?;
+    let banner = session.path().join("banner");
+    let mut launcher = launcher.clone();
+    launcher.session.banner = Some(banner.clone());
+    let duel = connection.watch(move |turn| {
+        let _ = std::fs::write(&banner, nvimkata::duel::banner(turn));
+    });
+    let result = editor::editor(&launcher).run_challenge(challenge, 1)?;
+    let turn = versus::Turn::of(player, &result);
+    duel.send_result(&turn)
+        .map_err(|e| CliError::failed(format!("failed to send your result: {e}")))?;
+    if !duel.opponent_done() {
+        println!("Waiting for {opponent} to finish...");
+    }
+    let mut turns = vec![turn];
+    match duel.opponent() {
+        Some(theirs) => turns.push(theirs),
+        None => println!("{opponent} left without finishing"),
+    }
     println!();
-    println!("Options:");
-    println!("  --unlock-all  Unlock all categories (skip progression)");
-    println!("  -h, --help    Show this help message");
+    for line in versus::results(&turns) {
+        println!("{line}");
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let mut unlock_all = false;
+/// Builds without the `net` feature have no duels.
+#[cfg(not(feature = "net"))]
+fn run_duel(_: DuelCommand, _: &Path, _: &config::Config) -> Result<(), CliError> {
+    Err(CliError::failed(
+        "duels need nvimkata built with the net feature (cargo build --features net)",
+    ))
+}
+
+/// A seed for when none is given, from the clock.
+fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
 
-    for arg in &args {
-        match arg.as_str() {
-            "-h" | "--help" => {
-                print_help();
-                return Ok(());
+/// Write a generated challenge file to `output`, or stdout.
+fn write_generated(generated: &generate::Generated, output: Option<&Path>) -> Result<(), CliError> {
+    let toml = generated.to_toml();
+    match output {
+        Some(path) => std::fs::write(path, toml)
+            .map_err(|e| CliError::failed(format!("failed to write {}: {e}", path.display())))?,
+        None => print!("{toml}"),
+    }
+    Ok(())
+}
+
+/// Run `nvimkata reset`: clear the progress in `scope` and save.
+fn reset(
+    profile: Option<String>,
+    scope: &ResetScope,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let mut state = load_state(profile, config)?;
+    match scope {
+        ResetScope::All => {
+            state.reset_all();
+            println!("Reset all progress");
+        }
+        ResetScope::Topic(name) => {
+            let topics = load_topics(challenges_path)?;
+            let topic = topics
+                .iter()
+                .find(|t| t.id.to_string() == *name || t.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CliError::failed(format!("no topic '{name}'")))?;
+            let count = state.reset_challenges(&topic.challenges);
+            println!("Reset {count} challenge(s) of {}", topic.name);
+        }
+        ResetScope::Challenge(id) => {
+            if !state.reset_challenge(id) {
+                return Err(CliError::failed(format!("no progress recorded for '{id}'")));
             }
-            "--unlock-all" => unlock_all = true,
-            other => {
-                eprintln!("unknown option: {other}");
-                eprintln!("run with --help for usage.");
-                std::process::exit(1);
+            println!("Reset {id}");
+        }
+    }
+    state.save().map_err(CliError::failed)
+}
+
+/// Run `nvimkata state merge`: fold the save at `file` into the profile's save.
+fn merge_state(
+    profile: Option<String>,
+    file: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let mut state = load_state(profile, config)?;
+    let other = state::GameState::load_file(file).map_err(CliError::failed)?;
+    let summary = state.merge(other);
+    state.save().map_err(CliError::failed)?;
+    println!(
+        "Merged {}: {} best result(s) and {} attempt(s) taken over",
+        file.display(),
+        summary.improved,
+        summary.attempts
+    );
+    Ok(())
+}
+
+/// Run `nvimkata state doctor`: merge the sides of a conflicted save and save
+/// the result.
+fn state_doctor(profile: Option<String>, config: &config::Config) -> Result<(), CliError> {
+    let profile = resolve_profile(profile, config)?;
+    let path = state::save_path(profile.as_deref());
+    let (state, summary) =
+        state::GameState::load_resolving_conflicts(profile.as_deref()).map_err(CliError::failed)?;
+    let Some(summary) = summary else {
+        println!("{} has no merge conflicts", path.display());
+        return Ok(());
+    };
+    state.save().map_err(CliError::failed)?;
+    println!(
+        "Resolved the conflicts in {}: {} best result(s) and {} attempt(s) taken from the other side",
+        path.display(),
+        summary.improved,
+        summary.attempts
+    );
+    Ok(())
+}
+
+/// Run `nvimkata report`: print the completion report of all assignments, or
+/// with `--since`/`--format` a progress report of the player's own practice.
+fn report(
+    profile: Option<String>,
+    since: Option<u32>,
+    format: Option<report::ReportFormat>,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let topics = load_printing_problems(challenges_path);
+    let state = load_state(profile, config)?;
+    if since.is_some() || format.is_some() {
+        let registry = skills::load_skills(challenges_path);
+        let progress = report::ProgressReport::new(
+            &topics,
+            &registry,
+            &state,
+            goals::today(),
+            since.unwrap_or(7),
+        );
+        print!("{}", progress.render(format.unwrap_or_default()));
+        return Ok(());
+    }
+    let dir = config::assignments_dir();
+    let assignments = assignment::load_assignments(&dir, &topics);
+    if assignments.is_empty() {
+        return Err(CliError::failed(format!(
+            "no assignments found in {}",
+            dir.display()
+        )));
+    }
+    print!(
+        "{}",
+        assignment::report(&assignments, &state, goals::today())
+    );
+    Ok(())
+}
+
+/// Run `nvimkata author`, returning whether the check or solve passed.
+fn run_author(
+    command: AuthorCommand,
+    challenges_path: &Path,
+    config: &config::Config,
+) -> Result<bool, CliError> {
+    match command {
+        AuthorCommand::Check {
+            dir,
+            verify,
+            nvim_bin,
+        } => {
+            // Only verifying runs the editor, so --no-verify works without it
+            let launcher = verify
+                .then(|| {
+                    headless_launcher(config, nvim_bin).map_err(|e| {
+                        CliError::failed(format!("{e}; pass --no-verify to check without it"))
+                    })
+                })
+                .transpose()?;
+            author_check(dir.as_deref().unwrap_or(challenges_path), launcher.as_ref())
+        }
+        AuthorCommand::Solve {
+            id,
+            keys,
+            record,
+            profile,
+            nvim_bin,
+        } => {
+            let launcher = headless_launcher(config, nvim_bin)?;
+            let profile = profile.or_else(|| config.profile.clone());
+            solve(
+                &id,
+                keys,
+                record,
+                profile.as_deref(),
+                challenges_path,
+                &launcher,
+            )
+        }
+        AuthorCommand::Calibrate {
+            dir,
+            saves,
+            profile,
+            min_attempts,
+        } => {
+            let dir = dir.as_deref().unwrap_or(challenges_path);
+            author_calibrate(dir, &saves, profile, min_attempts, config)?;
+            Ok(true)
+        }
+    }
+}
+
+/// Run `nvimkata pack`: manage the packs directory and search the registry.
+fn run_pack(command: PackCommand, config: &config::Config) -> Result<(), CliError> {
+    let dir = pack::packs_dir();
+    match command {
+        PackCommand::Install { source } => {
+            // Anything that is neither a file nor a URL names a registry pack
+            let pack = if pack::is_url(&source) || Path::new(&source).exists() {
+                pack::install(&source, &dir)?
+            } else {
+                let index = registry_index(config)?;
+                let entry = index.find(&source).ok_or_else(|| {
+                    CliError::failed(format!("no file or registry pack named '{source}'"))
+                })?;
+                registry::install(entry, &dir)?
+            };
+            println!(
+                "installed {} {} by {} ({} challenges)",
+                pack.manifest.name,
+                pack.manifest.version,
+                pack.manifest.author,
+                curriculum::challenge_files(&pack.dir).len()
+            );
+        }
+        PackCommand::List => {
+            let packs = pack::installed(&dir);
+            if packs.is_empty() {
+                println!("no packs installed in {}", dir.display());
+            }
+            for pack in packs {
+                println!(
+                    "{:<20} {:<10} by {:<20} {:>3} challenges",
+                    pack.manifest.name,
+                    pack.manifest.version,
+                    pack.manifest.author,
+                    curriculum::challenge_files(&pack.dir).len()
+                );
+            }
+        }
+        PackCommand::Remove { name } => {
+            let pack = pack::remove(&name, &dir)?;
+            println!("removed {} {}", pack.manifest.name, pack.manifest.version);
+        }
+        PackCommand::Search { query } => {
+            let index = registry_index(config)?;
+            let installed = pack::installed(&dir);
+            let found = index.search(query.as_deref().unwrap_or_default());
+            if found.is_empty() {
+                println!("no packs found");
+            }
+            for entry in found {
+                let status = installed
+                    .iter()
+                    .find(|p| p.manifest.name == entry.name)
+                    .map_or_else(String::new, |p| {
+                        format!(" [installed {}]", p.manifest.version)
+                    });
+                println!(
+                    "{:<20} {:<10} by {:<20} {}{status}",
+                    entry.name,
+                    entry.version,
+                    entry.author,
+                    entry.description.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        PackCommand::Update { name, check } => {
+            let index = registry_index(config)?;
+            let installed = pack::installed(&dir);
+            let updates: Vec<_> = index
+                .updates(&installed)
+                .into_iter()
+                .filter(|(pack, _)| name.as_ref().is_none_or(|n| *n == pack.manifest.name))
+                .collect();
+            if updates.is_empty() {
+                println!("all packs are up to date");
+            }
+            for (pack, entry) in updates {
+                if !check {
+                    registry::install(entry, &dir)?;
+                }
+                println!(
+                    "{} {} {} -> {}",
+                    if check { "outdated" } else { "updated" },
+                    pack.manifest.name,
+                    pack.manifest.version,
+                    entry.version
+                );
             }
         }
     }
+    Ok(())
+}
 
-    // Check neovim is available
-    if std::process::Command::new("nvim")
-        .arg("--version")
-        .output()
-        .is_err()
-    {
-        eprintln!("error: neovim (nvim) is required but not found in PATH.");
-        std::process::exit(1);
+/// The pack registry index at `registry_url` from the config.
+fn registry_index(config: &config::Config) -> Result<registry::Index, CliError> {
+    let url = config.registry_url.as_deref().ok_or_else(|| {
+        CliError::failed("no pack registry configured, set registry_url in the config file")
+    })?;
+    Ok(registry::fetch_index(url)?)
+}
+
+/// Run `nvimkata author check`: load a challenges directory so that problems
+/// with its files are printed as warnings, and count the challenges per topic.
+/// With a `launcher`, the perfect moves of every challenge are run headlessly
+/// too. Returns whether they all matched their targets.
+fn author_check(dir: &Path, launcher: Option<&nvim::Launcher>) -> Result<bool, CliError> {
+    if !dir.is_dir() {
+        return Err(CliError::failed(format!(
+            "{} is not a directory",
+            dir.display()
+        )));
+    }
+    let (topics, load) =
+        curriculum::load_curriculum_with(dir, &[], Some(&curriculum::cache_path()));
+    for diagnostic in &load.diagnostics {
+        eprintln!("Warning: {diagnostic}");
+    }
+    for topic in topics.iter().filter(|t| !t.challenges.is_empty()) {
+        println!(
+            "{:<20} {:>3} challenges",
+            topic.name,
+            topic.challenges.len()
+        );
+    }
+    let total: usize = topics.iter().map(|t| t.challenges.len()).sum();
+    if total == 0 {
+        return Err(CliError::failed(format!(
+            "no challenges found in {}",
+            dir.display()
+        )));
+    }
+    for lint in &load.lints {
+        eprintln!("Warning: {lint}");
     }
+    match load.diagnostics.len() + load.lints.len() {
+        0 => println!("{total} challenges loaded"),
+        1 => println!("{total} challenges loaded, 1 warning"),
+        n => println!("{total} challenges loaded, {n} warnings"),
+    }
+    let Some(launcher) = launcher else {
+        return Ok(true);
+    };
+    let mut checked = 0;
+    let mut failed = 0;
+    for challenge in topics.iter().flat_map(|t| &t.challenges) {
+        let Some(moves) = &challenge.perfect_moves else {
+            continue;
+        };
+        checked += 1;
+        let problem = match nvim::run_headless(
+            launcher,
+            &*challenge.loaded()?,
+            moves,
+            Duration::from_secs(5),
+        ) {
+            Ok(result) if result.buffer_matches => continue,
+            Ok(result) if result.outcome == nvim::Outcome::TimedOut => "timed out".to_string(),
+            Ok(_) => "perfect_moves don't produce the target".to_string(),
+            Err(e) => e.to_string(),
+        };
+        eprintln!("Error: {}: {problem}", challenge.id);
+        failed += 1;
+    }
+    if failed == 0 {
+        println!("{checked} perfect_moves verified");
+    } else {
+        println!("{failed} of {checked} perfect_moves failed verification");
+    }
+    Ok(failed == 0)
+}
+
+/// Run `nvimkata author calibrate`: judge the par of each challenge in `dir`
+/// by the attempts recorded in `saves`, or the profile's save without any,
+/// and print the challenges it doesn't fit.
+fn author_calibrate(
+    dir: &Path,
+    saves: &[PathBuf],
+    profile: Option<String>,
+    min_attempts: usize,
+    config: &config::Config,
+) -> Result<(), CliError> {
+    let topics = load_topics(dir)?;
+    let states = if saves.is_empty() {
+        vec![load_state(profile, config)?]
+    } else {
+        saves
+            .iter()
+            .map(|path| state::GameState::load_file(path).map_err(CliError::failed))
+            .collect::<Result<_, _>>()?
+    };
+    let mut calibrations = Vec::new();
+    let mut skipped = 0;
+    for challenge in topics.iter().flat_map(|t| &t.challenges) {
+        if challenge.is_freestyle() {
+            continue;
+        }
+        let attempts = calibrate::attempts(challenge, &states);
+        match calibrate::calibrate(challenge, &attempts, min_attempts) {
+            Some(calibration) => calibrations.push((challenge, calibration)),
+            None => skipped += 1,
+        }
+    }
+    print!(
+        "{}",
+        calibrate::report(&calibrations, states.len(), skipped)
+    );
+    Ok(())
+}
 
-    let challenges_path = challenges_dir();
-    let topics = curriculum::load_curriculum(&challenges_path);
+/// Run `nvimkata author solve`: feed a key sequence to a challenge headlessly
+/// and report the outcome. Returns whether the target was matched.
+fn solve(
+    id: &str,
+    keys: String,
+    record: bool,
+    profile: Option<&str>,
+    challenges_path: &Path,
+    launcher: &nvim::Launcher,
+) -> Result<bool, CliError> {
+    let topics = load_printing_problems(challenges_path);
+    let challenge = topics
+        .iter()
+        .flat_map(|t| t.challenges.iter())
+        .find(|c| c.id == id)
+        .ok_or_else(|| CliError::failed(format!("no challenge with id '{id}'")))?
+        .loaded()?;
 
-    if topics.iter().all(|t| t.challenges.is_empty()) {
-        eprintln!("no challenges found. make sure the 'challenges/' directory exists.");
-        eprintln!("looked in: {}", challenges_path.display());
-        std::process::exit(1);
+    let result = nvim::run_headless(launcher, &challenge, &[keys], Duration::from_secs(5))?;
+    let matched = result.buffer_matches && result.violation.is_none();
+
+    if result.outcome == nvim::Outcome::TimedOut {
+        println!("{id}: timed out");
+    } else if !result.buffer_matches {
+        println!(
+            "{id}: target not matched ({} keystrokes)",
+            result.keystrokes
+        );
+    } else if let Some(seq) = &result.violation {
+        println!("{id}: constraint violated (used {seq})");
+    } else if challenge.is_freestyle() {
+        let reference = challenge
+            .reference_keystrokes
+            .map_or_else(String::new, |r| format!(" (reference: {r})"));
+        println!(
+            "{id}: matched in {} keystrokes{reference}",
+            result.keystrokes
+        );
+    } else {
+        let grade = challenge.score(result.keystrokes);
+        println!(
+            "{id}: matched in {} keystrokes (par: {}, grade {})",
+            result.keystrokes,
+            challenge.par(),
+            grade.display_char()
+        );
+    }
+    if matched
+        && let Some(technique) = challenge
+            .required_technique
+            .as_ref()
+            .filter(|_| result.technique_missing)
+    {
+        println!("{id}: technique not used: {}", technique.name);
     }
 
-    let mut state = match state::GameState::load() {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!(
-                "error: incompatible save file at '{}', delete the file to start fresh.",
-                e.path.display()
-            );
-            std::process::exit(1);
+    // Headless runs aren't timed, so they are recorded without a time
+    if record && matched {
+        let mut state = state::GameState::load_profile(profile)?;
+        state.mark_ambiguous(&topics);
+        let recorded = if challenge.is_freestyle() {
+            state.record_freestyle_result(
+                &challenge.id,
+                result.keystrokes,
+                None,
+                &result.keys,
+                &challenge.version,
+                challenge.reference_keystrokes,
+            )
+        } else {
+            state.record_attempt(
+                &challenge.id,
+                state::AttemptRecord {
+                    grade: challenge.score(result.keystrokes),
+                    keystrokes: result.keystrokes,
+                    time_ms: None,
+                    keys: result.keys.clone(),
+                    technique_missing: result.technique_missing,
+                    timestamp: None,
+                },
+                &challenge.version,
+            )
+        };
+        if !recorded {
+            return Err(CliError::failed(format!(
+                "{id} is used by more than one challenge, so its attempts are not recorded"
+            )));
         }
+        state.record_content(&challenge);
+        state.save().map_err(CliError::failed)?;
+        println!("{id}: attempt recorded");
+    }
+    Ok(matched)
+}
+
+/// Launcher for headless runs, which need Neovim whichever editor is
+/// configured. Fails unless the binary is a supported Neovim.
+fn headless_launcher(
+    config: &config::Config,
+    nvim_bin: Option<String>,
+) -> Result<nvim::Launcher, CliError> {
+    let mut launcher = match config.editor {
+        EditorKind::Neovim => config.launcher(),
+        EditorKind::Vim => nvim::Launcher::default(),
+    };
+    if let Some(bin) = nvim_bin {
+        launcher.bin = bin;
+    }
+    require_editor(&mut launcher)?;
+    Ok(launcher)
+}
+
+/// Fail unless the launcher's binary is a supported release of the configured
+/// editor, and store its version.
+fn require_editor(launcher: &mut nvim::Launcher) -> Result<(), CliError> {
+    launcher.version = Some(editor::check_version(launcher).map_err(CliError::failed)?);
+    Ok(())
+}
+
+/// Run `nvimkata play --dry-run <ID>`: print how challenge `id` would be
+/// launched, for debugging sessions. Nothing is written and the editor is only
+/// asked for its version.
+fn dry_run(id: &str, launcher: &nvim::Launcher, challenges_path: &Path) -> Result<(), CliError> {
+    let topics = load_printing_problems(challenges_path);
+    let (challenge, number) = topics
+        .iter()
+        .find_map(|t| {
+            let i = t.challenges.iter().position(|c| c.id == id)?;
+            Some((&t.challenges[i], i + 1))
+        })
+        .ok_or_else(|| CliError::failed(format!("no challenge with id '{id}'")))?;
+    let challenge = challenge.loaded()?;
+
+    let name = launcher.editor.name();
+    println!("Editor: {name} ('{}')", launcher.bin);
+    match find_in_path(&launcher.bin) {
+        Some(path) => println!("  found at {}", path.display()),
+        None => println!("  not found in PATH"),
+    }
+    match editor::check_version(launcher) {
+        Ok(version) => println!("  version {version}"),
+        Err(e) => println!("  not usable: {e}"),
+    }
+    let session = &launcher.session;
+    println!(
+        "  shell commands: {}, briefing: {}, hardcore: {}",
+        format!("{:?}", session.shell.mode).to_lowercase(),
+        if session.briefing { "on" } else { "off" },
+        if session.hardcore { "on" } else { "off" }
+    );
+
+    println!("\nEnvironment:");
+    let vars: &[&str] = match launcher.editor {
+        EditorKind::Neovim => &["NVIM_APPNAME", "XDG_CONFIG_HOME", "VIMINIT"],
+        EditorKind::Vim => &["VIMINIT", "MYVIMRC"],
     };
+    for var in vars {
+        match std::env::var(var) {
+            Ok(value) => println!("  {var}={value}"),
+            Err(_) => println!("  {var} is not set"),
+        }
+    }
+    if launcher.args.iter().any(|a| a == "--clean" || a == "-u") {
+        println!("  nvim_args replace your config and plugins");
+    } else {
+        println!(
+            "  Your config and plugins are loaded: mappings and plugins that handle keys\n  \
+             can change what the keystroke log sees. Add \"--clean\" to nvim_args to rule\n  \
+             them out."
+        );
+    }
+
+    let dir = dirs::sessions_root().join(format!("challenge-{}-dry-run", std::process::id()));
+    let plan = editor::editor(launcher).plan_challenge(&challenge, number, &dir);
+    println!(
+        "\nFiles written to a new session directory like {}:",
+        dir.display()
+    );
+    for (path, content) in &plan.files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("  {name:<20} {} lines", content.lines().count());
+    }
+    println!(
+        "\nCommand, with stderr written to {}:\n  {}",
+        plan.stderr
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy(),
+        plan.command_line()
+    );
+    for (path, content) in &plan.files {
+        if path
+            .extension()
+            .is_some_and(|ext| ext == "lua" || ext == "vim")
+        {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            println!("\nRuntime script {name}:\n{content}");
+        }
+    }
+    Ok(())
+}
+
+/// Where the shell would find `bin`: the path itself if it names a file, or
+/// the first match in `PATH`.
+fn find_in_path(bin: &str) -> Option<PathBuf> {
+    let path = Path::new(bin);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(bin))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run `nvimkata play`: the TUI. Flags take precedence over the config file.
+fn play(args: &PlayArgs, challenges_path: &Path, config: &config::Config) -> Result<(), CliError> {
+    let mut launcher = config.launcher();
+    if let Some(bin) = &args.nvim_bin {
+        launcher.bin.clone_from(bin);
+    }
+    launcher.session.hardcore |= args.hardcore;
+    if let Some(id) = &args.dry_run {
+        return dry_run(id, &launcher, challenges_path);
+    }
+    require_editor(&mut launcher)?;
+    theme::set(theme::resolve(config.theme, args.no_color));
+    animation::set_enabled(!config.reduce_motion);
+    feedback::set(config.feedback.clone());
+    opponents::set(config.opponents.clone());
+
+    let (topics, load) = pack::load_with_packs(challenges_path);
+    if args.verbose {
+        eprintln!("nvimkata: {load}");
+    }
+    let topics = require_challenges(topics, challenges_path)?;
+    let skills = skills::load_skills(challenges_path);
+    let reference = reference::load_reference(challenges_path);
+    let playlists = collection::load_playlists(&config::playlists_dir(), &topics);
+    let assignments = assignment::load_assignments(&config::assignments_dir(), &topics);
+
+    let mut state = load_state(args.profile.clone(), config)?;
     let all_challenges: Vec<challenge::Challenge> =
         topics.iter().flat_map(|t| t.challenges.clone()).collect();
     state.mark_stale(&all_challenges);
+    state.mark_ambiguous(&topics);
+    state.record_category_completions(&topics, goals::now());
     let mut terminal = ratatui::init();
 
-    let result = run(&mut terminal, &mut state, &topics, unlock_all);
+    let result = run(
+        &mut terminal,
+        &mut state,
+        &topics,
+        &playlists,
+        &assignments,
+        skills,
+        &reference,
+        config,
+        args.unlock_all || config.unlock_all,
+        args.unlock_policy.unwrap_or(config.unlock_policy),
+        load,
+    );
 
     ratatui::restore();
-    state.save()?;
+    state.save().map_err(CliError::failed)?;
+    result.map_err(CliError::failed)
+}
 
-    result?;
-    Ok(())
+/// Before the hub, list challenge IDs shared by several challenges: their
+/// results are not recorded until the IDs are made unique.
+fn warn_duplicates(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &keymap::Keymap,
+    topics: &[challenge::Topic],
+) -> std::io::Result<()> {
+    let duplicates = lint::duplicate_ids(topics);
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    lint::show_warnings(
+        terminal,
+        keymap,
+        "Duplicate challenge IDs, not recorded",
+        &[],
+        &duplicates,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run(
     terminal: &mut ratatui::DefaultTerminal,
     state: &mut state::GameState,
     topics: &[challenge::Topic],
+    playlists: &[challenge::Topic],
+    assignments: &[assignment::Assignment],
+    skills: skills::SkillRegistry,
+    reference: &reference::Reference,
+    config: &config::Config,
     unlock_all: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut hub = hub::Hub::new(topics.to_vec(), unlock_all);
+    unlock_policy: unlock::UnlockPolicy,
+    load: curriculum::LoadStats,
+) -> Result<(), nvimkata::Error> {
+    let launcher = config.launcher();
+    let keymap = config.keymap();
+    let mut hub = hub::Hub::new(
+        topics.to_vec(),
+        playlists.to_vec(),
+        assignments.to_vec(),
+        skills,
+        config.goals.clone(),
+        keymap.clone(),
+        config.keyboard_layout,
+        unlock_all,
+        unlock_policy,
+    )
+    .with_warnings(load.diagnostics, load.lints);
+    warn_duplicates(terminal, &keymap, topics)?;
 
     loop {
         match hub.run(terminal, state)? {
             hub::HubAction::Quit => return Ok(()),
+            hub::HubAction::Sandbox => sandbox::run_sandbox(terminal, &launcher, &keymap, topics)?,
+            hub::HubAction::Favorites => {
+                let topic = collection::favorites(topics, state);
+                game::run_challenge_picker(
+                    terminal, &launcher, &keymap, state, &topic, 0, unlock_all,
+                )?;
+                state.save()?;
+            }
+            hub::HubAction::Drill(title, ids) => {
+                let drill = collection::find(topics, &ids);
+                playlist::run_playlist(terminal, &launcher, &keymap, state, title, &drill)?;
+                state.save()?;
+            }
+            hub::HubAction::AcceptStale => {
+                state.archive_stale();
+                state.save()?;
+            }
+            hub::HubAction::Playlist(topic_id) => {
+                if let Some(playlist) = playlists.iter().find(|t| t.id == topic_id) {
+                    let challenges: Vec<&challenge::Challenge> =
+                        playlist.challenges.iter().collect();
+                    playlist::run_playlist(
+                        terminal,
+                        &launcher,
+                        &keymap,
+                        state,
+                        &format!("Playlist: {}", playlist.name),
+                        &challenges,
+                    )?;
+                    state.save()?;
+                }
+            }
+            hub::HubAction::Assignment(index) => {
+                let assignment = &assignments[index];
+                let challenges: Vec<&challenge::Challenge> = assignment.challenges.iter().collect();
+                playlist::run_playlist(
+                    terminal,
+                    &launcher,
+                    &keymap,
+                    state,
+                    &format!("Assignment: {}", assignment.title),
+                    &challenges,
+                )?;
+                state.save()?;
+            }
+            hub::HubAction::Reference => {
+                reference::run_reference(
+                    terminal, &launcher, &keymap, state, reference, topics, unlock_all,
+                )?;
+                state.save()?;
+            }
+            hub::HubAction::ResetTopic(topic_id) => {
+                if let Some(topic) = topics.iter().find(|t| t.id == topic_id) {
+                    state.reset_challenges(&topic.challenges);
+                    state.save()?;
+                }
+            }
             hub::HubAction::SelectTopic(topic_id) => {
                 if let Some(topic) = topics.iter().find(|t| t.id == topic_id) {
                     let offset: usize = topics
@@ -108,10 +1410,15 @@ fn run(
                         .filter(|t| t.id < topic_id)
                         .map(|t| t.challenges.len())
                         .sum();
-                    game::run_challenge_picker(terminal, state, topic, offset)?;
+                    game::run_challenge_picker(
+                        terminal, &launcher, &keymap, state, topic, offset, unlock_all,
+                    )?;
                     state.save()?;
                 }
             }
         }
+        if state.record_category_completions(topics, goals::now()) {
+            state.save()?;
+        }
     }
 }
@@ -0,0 +1,339 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::generate::{Buffer, Generated, Rng};
+
+/// A change made to a source file, which the player undoes to restore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Rename a variable everywhere it is used.
+    RenameVar,
+    /// Shuffle a block of imports.
+    ReorderImports,
+    /// Wrap a few lines in a new function.
+    WrapInFunction,
+}
+
+impl Mutation {
+    pub const ALL: [Self; 3] = [Self::RenameVar, Self::ReorderImports, Self::WrapInFunction];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RenameVar => "rename-var",
+            Self::ReorderImports => "reorder-imports",
+            Self::WrapInFunction => "wrap-in-function",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::RenameVar => "Rename a variable everywhere it is used",
+            Self::ReorderImports => "Shuffle a block of imports",
+            Self::WrapInFunction => "Wrap a few lines in a new function",
+        }
+    }
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Mutation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|m| m.name() == s.trim().to_ascii_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "invalid mutation '{s}' (use rename-var, reorder-imports or wrap-in-function)"
+                )
+            })
+    }
+}
+
+/// Keywords that are never renamed, of the languages whose declarations
+/// [`rename_var`] recognizes.
+const KEYWORDS: &[&str] = &[
+    "mut", "ref", "self", "Self", "const", "static", "let", "var", "local", "function", "return",
+    "if", "else", "for", "while", "in", "true", "false", "nil", "null", "None", "True", "False",
+];
+
+/// Names a renamed variable gets, the first one the file doesn't use.
+const NAMES: &[&str] = &[
+    "value", "item", "data", "result", "temp", "entry", "count", "total", "buffer", "state",
+];
+
+/// Line prefixes of imports.
+const IMPORTS: &[&str] = &[
+    "use ",
+    "pub use ",
+    "import ",
+    "from ",
+    "#include ",
+    "extern crate ",
+];
+
+/// Apply `mutation` to `source`, choosing where with `rng`. Returns the
+/// mutated source and a description of the change to undo, or `None` when the
+/// file has nothing to apply it to.
+pub fn mutate(
+    source: &str,
+    mutation: Mutation,
+    filetype: Option<&str>,
+    rng: &mut Rng,
+) -> Option<(String, String)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let (lines, description) = match mutation {
+        Mutation::RenameVar => return rename_var(source, rng),
+        Mutation::ReorderImports => reorder_imports(&lines, rng)?,
+        Mutation::WrapInFunction => wrap_in_function(&lines, filetype, rng)?,
+    };
+    Some((lines.join("\n"), description))
+}
+
+/// A freestyle challenge restoring `source`, the content of `path`, from
+/// `mutation` applied to it. The same file, mutation and seed always give the
+/// same challenge.
+pub fn from_file(
+    path: &Path,
+    source: &str,
+    mutation: Mutation,
+    seed: u64,
+) -> Result<Generated, String> {
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    );
+    let filetype = path
+        .extension()
+        .and_then(|ext| filetype(&ext.to_string_lossy()));
+    let source = source.trim_end_matches('\n');
+    let (start, change) = mutate(source, mutation, filetype, &mut Rng::new(seed))
+        .ok_or_else(|| format!("{name} has nothing to {}", nothing_to(mutation)))?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(Generated {
+        id: format!("file_{stem}_{}_{seed}", mutation.name().replace('-', "_")),
+        version: "1.0.0".to_string(),
+        title: format!("Restore {name}"),
+        topic: "from-file".to_string(),
+        difficulty: 2,
+        description: format!("{change}. Restore the original {name}."),
+        hint: hint(mutation).to_string(),
+        filetype: filetype.map(str::to_string),
+        perfect_moves: Vec::new(),
+        par_keystrokes: None,
+        start: Buffer { content: start },
+        target: Buffer {
+            content: source.to_string(),
+        },
+        command: format!(
+            "nvimkata from-file {} --mutate {mutation} --seed {seed}",
+            path.display()
+        ),
+    })
+}
+
+/// Neovim filetype of a file extension, for the common languages.
+pub fn filetype(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "lua" => "lua",
+        "go" => "go",
+        "js" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "java" => "java",
+        "rb" => "ruby",
+        "sh" | "bash" => "sh",
+        "nix" => "nix",
+        "toml" => "toml",
+        _ => return None,
+    })
+}
+
+fn nothing_to(mutation: Mutation) -> &'static str {
+    match mutation {
+        Mutation::RenameVar => "rename: no variable is declared and used",
+        Mutation::ReorderImports => "reorder: no two imports are next to each other",
+        Mutation::WrapInFunction => "wrap: no two lines are next to each other",
+    }
+}
+
+fn hint(mutation: Mutation) -> &'static str {
+    match mutation {
+        Mutation::RenameVar => "A :%s with \\< and \\> renames it back in one go",
+        Mutation::ReorderImports => "ddp and dd with P move lines; :m does too",
+        Mutation::WrapInFunction => "Delete the wrapper lines, then dedent with <",
+    }
+}
+
+/// Identifiers of `source` with their byte ranges.
+fn identifiers(source: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut start = None;
+    for (i, c) in source.char_indices().chain([(source.len(), ' ')]) {
+        let word = c == '_' || c.is_alphanumeric();
+        match start {
+            None if word => start = Some(i),
+            Some(s) if !word => {
+                let ident = &source[s..i];
+                if !ident.starts_with(|c: char| c.is_ascii_digit()) {
+                    found.push((s, ident));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Rename a variable declared with `let`, `var`, `const` or `local`, or
+/// assigned at the start of a line, that is used again after it.
+fn rename_var(source: &str, rng: &mut Rng) -> Option<(String, String)> {
+    let idents = identifiers(source);
+    let mut candidates: Vec<&str> = Vec::new();
+    for (i, &(at, ident)) in idents.iter().enumerate() {
+        let declared = i > 0
+            && matches!(idents[i - 1].1, "let" | "mut" | "var" | "const" | "local")
+            && source[idents[i - 1].0 + idents[i - 1].1.len()..at]
+                .trim()
+                .is_empty();
+        let line_start = source[..at].rfind('\n').map_or(0, |n| n + 1);
+        let assigned = source[line_start..at].trim().is_empty()
+            && source[at + ident.len()..].trim_start().starts_with('=')
+            && !source[at + ident.len()..].trim_start().starts_with("==");
+        if (declared || assigned)
+            && ident.len() > 1
+            && !KEYWORDS.contains(&ident)
+            && !candidates.contains(&ident)
+            && idents.iter().filter(|(_, i)| *i == ident).count() > 1
+        {
+            candidates.push(ident);
+        }
+    }
+    if candidates.is_empty() {
+        return None;
+    }
+    let old = *rng.pick(&candidates);
+    let new = NAMES.iter().find(|n| idents.iter().all(|(_, i)| i != *n))?;
+    let mut out = String::with_capacity(source.len());
+    let mut end = 0;
+    for &(at, ident) in &idents {
+        if ident == old {
+            out.push_str(&source[end..at]);
+            out.push_str(new);
+            end = at + ident.len();
+        }
+    }
+    out.push_str(&source[end..]);
+    Some((out, format!("'{old}' was renamed to '{new}'")))
+}
+
+fn is_import(line: &str) -> bool {
+    let line = line.trim_start();
+    IMPORTS.iter().any(|p| line.starts_with(p)) && !line.trim_end().ends_with(['{', '(', ','])
+}
+
+/// Shuffle a run of at least two import lines that aren't all the same.
+fn reorder_imports(lines: &[&str], rng: &mut Rng) -> Option<(Vec<String>, String)> {
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let len = lines[i..].iter().take_while(|l| is_import(l)).count();
+        if len > 1 && lines[i..i + len].iter().any(|l| *l != lines[i]) {
+            runs.push(i..i + len);
+        }
+        i += len.max(1);
+    }
+    if runs.is_empty() {
+        return None;
+    }
+    let run = rng.pick(&runs).clone();
+    let mut block: Vec<&str> = lines[run.clone()].to_vec();
+    while block == lines[run.clone()] {
+        // Fisher-Yates
+        for i in (1..block.len()).rev() {
+            block.swap(i, rng.range(0..=i));
+        }
+    }
+    let mut out: Vec<String> = lines.iter().map(ToString::to_string).collect();
+    out.splice(run.clone(), block.iter().map(ToString::to_string));
+    let description = format!(
+        "The imports on lines {} to {} were shuffled",
+        run.start + 1,
+        run.end
+    );
+    Some((out, description))
+}
+
+/// Wrap two to five lines of a paragraph in a function named `wrapped`, in
+/// the syntax of `filetype`.
+fn wrap_in_function(
+    lines: &[&str],
+    filetype: Option<&str>,
+    rng: &mut Rng,
+) -> Option<(Vec<String>, String)> {
+    let mut paragraphs = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let len = lines[i..]
+            .iter()
+            .take_while(|l| !l.trim().is_empty())
+            .count();
+        if len > 1 {
+            paragraphs.push((i, len));
+        }
+        i += len.max(1);
+    }
+    if paragraphs.is_empty() {
+        return None;
+    }
+    let &(first, len) = rng.pick(&paragraphs);
+    let count = rng.range(2..=len.min(5));
+    let at = first + rng.range(0..=len - count);
+    let block = &lines[at..at + count];
+
+    let indent: String = block
+        .iter()
+        .map(|l| &l[..l.len() - l.trim_start().len()])
+        .min_by_key(|i| i.len())
+        .unwrap_or_default()
+        .to_string();
+    let unit = if lines.iter().any(|l| l.starts_with('\t')) {
+        "\t"
+    } else {
+        "    "
+    };
+    let (open, close) = match filetype {
+        Some("rust") => ("fn wrapped() {", Some("}")),
+        Some("python") => ("def wrapped():", None),
+        Some("lua") => ("local function wrapped()", Some("end")),
+        Some("go") => ("func wrapped() {", Some("}")),
+        Some("sh") => ("wrapped() {", Some("}")),
+        Some("ruby") => ("def wrapped", Some("end")),
+        _ => ("function wrapped() {", Some("}")),
+    };
+    let mut wrapped = vec![format!("{indent}{open}")];
+    wrapped.extend(block.iter().map(|l| format!("{unit}{l}")));
+    wrapped.extend(close.map(|c| format!("{indent}{c}")));
+
+    let mut out: Vec<String> = lines.iter().map(ToString::to_string).collect();
+    out.splice(at..at + count, wrapped);
+    let description = format!(
+        "Lines {} to {} were wrapped in a function",
+        at + 1,
+        at + count
+    );
+    Some((out, description))
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::challenge::split_keys;
+
+/// One keystroke in vim key notation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A typed character. `<lt>`, `<Space>`, `<Bar>` and `<Bslash>` are characters too.
+    Char(char),
+    /// Ctrl with a character, e.g. `<C-r>`. Letters are stored lowercase.
+    Ctrl(char),
+    /// Any other named key by its canonical name without brackets, e.g. `Esc`.
+    Special(String),
+}
+
+/// Canonical names of special keys and their aliases, matched case-insensitively.
+const SPECIAL_NAMES: [(&str, &[&str]); 15] = [
+    ("Esc", &["esc", "escape"]),
+    ("Enter", &["enter", "cr", "return"]),
+    ("Tab", &["tab"]),
+    ("BS", &["bs", "backspace"]),
+    ("Del", &["del", "delete"]),
+    ("Up", &["up"]),
+    ("Down", &["down"]),
+    ("Left", &["left"]),
+    ("Right", &["right"]),
+    ("Home", &["home"]),
+    ("End", &["end"]),
+    ("PageUp", &["pageup"]),
+    ("PageDown", &["pagedown"]),
+    ("Insert", &["insert"]),
+    ("Nul", &["nul"]),
+];
+
+impl Key {
+    /// Parse a single keystroke such as `w`, `<Esc>` or `<C-R>`. Returns `None`
+    /// for anything that is not exactly one key.
+    pub fn parse(token: &str) -> Option<Self> {
+        let mut chars = token.chars();
+        let first = chars.next()?;
+        if chars.next().is_none() {
+            return Some(Self::Char(first));
+        }
+        let name = token.strip_prefix('<')?.strip_suffix('>')?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self::from_name(name))
+    }
+
+    fn from_name(name: &str) -> Self {
+        let lower = name.to_ascii_lowercase();
+        match lower.as_str() {
+            "lt" => return Self::Char('<'),
+            "space" => return Self::Char(' '),
+            "bar" => return Self::Char('|'),
+            "bslash" => return Self::Char('\\'),
+            _ => {}
+        }
+        if let Some(rest) = lower.strip_prefix("c-") {
+            let mut chars = rest.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                return Self::Ctrl(c);
+            }
+        }
+        let canonical = SPECIAL_NAMES
+            .iter()
+            .find(|(_, aliases)| aliases.contains(&lower.as_str()))
+            .map_or(name, |(canonical, _)| canonical);
+        Self::Special(canonical.to_string())
+    }
+
+    /// Whether this is a character, a Ctrl key or a named special key, rather
+    /// than a name vim doesn't know, like `<li>` in an HTML snippet.
+    pub fn is_known(&self) -> bool {
+        match self {
+            Self::Special(name) => SPECIAL_NAMES.iter().any(|(canonical, _)| canonical == name),
+            Self::Char(_) | Self::Ctrl(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    /// Canonical notation, which parses back to the same key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Char('<') => f.write_str("<lt>"),
+            Self::Char(' ') => f.write_str("<Space>"),
+            Self::Char(c) => write!(f, "{c}"),
+            Self::Ctrl(c) => write!(f, "<C-{c}>"),
+            Self::Special(name) => write!(f, "<{name}>"),
+        }
+    }
+}
+
+/// Tokenize key notation, such as a key log or `perfect_moves`, into keys.
+/// Yields one key per keystroke counted by
+/// [`count_keystrokes`](crate::challenge::count_keystrokes).
+pub fn tokenize(s: &str) -> Vec<Key> {
+    split_keys(s).into_iter().filter_map(Key::parse).collect()
+}
+
+/// Format keys back into canonical notation.
+pub fn format(keys: &[Key]) -> String {
+    keys.iter().map(ToString::to_string).collect()
+}
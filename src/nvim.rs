@@ -1,16 +1,183 @@
+use std::fmt::Write as _;
 use std::fs;
-use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
 
-use crate::challenge::{Challenge, Grade};
+use serde::Deserialize;
+
+use crate::challenge::{Challenge, Grade, Setup, TutorialStep, count_keystrokes};
+use crate::dirs::{self, Platform, SessionDir};
+use crate::editor::{Editor, EditorKind, LaunchPlan, SessionOptions, Version};
+use crate::error::{Error, NvimError, SessionError};
+use crate::notation;
 
 /// Result of running a challenge in neovim.
 pub struct ChallengeResult {
     pub buffer_matches: bool,
     pub keystrokes: u32,
-    pub elapsed_secs: u32,
+    pub elapsed_ms: u32,
     pub keys: String,
+    /// First `forbidden_keys` sequence pressed during the attempt, if any.
+    pub violation: Option<String>,
+    /// The target was reached without the challenge's `required_technique`.
+    pub technique_missing: bool,
+    /// Shell commands run during the attempt, e.g. `%!sort`.
+    pub shell_commands: Vec<String>,
+    /// Deepest hint tier revealed, 0 for none. See [`Challenge::score_with_hints`].
+    pub hints_used: u32,
+    pub outcome: Outcome,
+}
+
+/// How a session ended, as written by the runtime to the results file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Outcome {
+    /// The target was reached (or every tutorial step typed).
+    Completed,
+    /// Ended early with `:w`; the buffer is graded as it is.
+    SubmittedEarly,
+    /// Quit without finishing, e.g. with `:qall!`, so no results were written.
+    /// Aborted runs are not recorded.
+    #[default]
+    Aborted,
+    /// A headless run did not finish in time.
+    TimedOut,
+    /// Hardcore mode ended the run once its keys passed the grade F
+    /// threshold. It counts as a failed attempt.
+    OverBudget,
+    /// The player gave up (F3) to see the perfect moves. It counts as a
+    /// failed attempt.
+    GaveUp,
+}
+
+impl Outcome {
+    /// Parse the `outcome=` value of the results file.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "completed" => Some(Self::Completed),
+            "submitted" => Some(Self::SubmittedEarly),
+            "aborted" => Some(Self::Aborted),
+            "timed_out" => Some(Self::TimedOut),
+            "over_budget" => Some(Self::OverBudget),
+            "gave_up" => Some(Self::GaveUp),
+            _ => None,
+        }
+    }
+
+    /// Whether the run counts as an attempt, solved or not.
+    pub fn is_attempt(self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::SubmittedEarly | Self::OverBudget | Self::GaveUp
+        )
+    }
+
+    /// Whether the buffer is compared with the target: an attempt the runtime
+    /// didn't end as failed.
+    pub fn can_match(self) -> bool {
+        matches!(self, Self::Completed | Self::SubmittedEarly)
+    }
+}
+
+/// How to launch the editor: which one, its binary and extra arguments for
+/// interactive sessions.
+#[derive(Debug, Clone)]
+pub struct Launcher {
+    pub editor: EditorKind,
+    pub bin: String,
+    pub args: Vec<String>,
+    /// Detected by [`crate::editor::check_version`], `None` until checked.
+    pub version: Option<Version>,
+    pub session: SessionOptions,
+}
+
+impl Default for Launcher {
+    fn default() -> Self {
+        Self {
+            editor: EditorKind::Neovim,
+            bin: "nvim".to_string(),
+            args: Vec::new(),
+            version: None,
+            session: SessionOptions::default(),
+        }
+    }
+}
+
+impl Launcher {
+    /// Error for a session that exited unsuccessfully, naming the editor version
+    /// if known and followed by the last [`STDERR_LINES`] lines of what it wrote
+    /// to stderr.
+    pub fn exit_error(&self, status: ExitStatus, stderr: &str) -> NvimError {
+        let mut lines: Vec<String> = stderr
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        lines.drain(..lines.len().saturating_sub(STDERR_LINES));
+        NvimError::Exited {
+            editor: self.editor.default_bin(),
+            version: self.version,
+            status,
+            stderr: lines,
+        }
+    }
+
+    /// Run `command` and wait for it to exit, with its stderr written to `log`
+    /// so that an editor failing before its UI starts can say why.
+    pub fn run_logged(&self, command: &mut Command, log: &Path) -> Result<(), Error> {
+        let file = fs::File::create(log).map_err(SessionError::at(log))?;
+        let status = command
+            .stderr(file)
+            .status()
+            .map_err(|e| NvimError::spawn(&self.bin, e))?;
+        if !status.success() {
+            let stderr = fs::read(log).unwrap_or_default();
+            return Err(self
+                .exit_error(status, &String::from_utf8_lossy(&stderr))
+                .into());
+        }
+        Ok(())
+    }
+}
+
+/// Create a session directory of `kind` in [`dirs::sessions_root`].
+pub fn session_dir(kind: &str) -> Result<SessionDir, SessionError> {
+    SessionDir::create(kind).map_err(SessionError::at(&dirs::sessions_root()))
+}
+
+/// Read a file the editor left in a session directory.
+pub fn read_session_file(path: &Path) -> Result<String, SessionError> {
+    fs::read_to_string(path).map_err(SessionError::at(path))
+}
+
+/// Write a file of a session directory.
+pub fn write_session_file(path: &Path, content: &str) -> Result<(), SessionError> {
+    fs::write(path, content).map_err(SessionError::at(path))
+}
+
+/// Lines of an editor's stderr kept in the error of a failed session.
+pub const STDERR_LINES: usize = 10;
+
+/// Neovim sessions, driven by the Lua runtime.
+pub struct Neovim<'a>(pub &'a Launcher);
+
+impl Editor for Neovim<'_> {
+    fn run_challenge(
+        &self,
+        challenge: &Challenge,
+        number: usize,
+    ) -> Result<ChallengeResult, Error> {
+        run_challenge(self.0, challenge, number)
+    }
+
+    fn plan_challenge(&self, challenge: &Challenge, number: usize, dir: &Path) -> LaunchPlan {
+        plan_challenge(self.0, challenge, number, dir)
+    }
+
+    fn run_sandbox(&self, challenge: Option<&Challenge>) -> Result<(), Error> {
+        run_sandbox(self.0, challenge)
+    }
 }
 
 /// Temporary file paths for a challenge session.
@@ -20,93 +187,534 @@ struct SessionFiles {
     results: PathBuf,
     start: PathBuf,
     lua: PathBuf,
+    /// The description, and the hint shown instead of the target in blind mode.
+    instructions: PathBuf,
 }
 
 impl SessionFiles {
-    fn new() -> Self {
-        let dir = std::env::temp_dir().join("nvimkata");
+    fn in_dir(dir: &Path) -> Self {
         Self {
             buffer: dir.join("challenge_buffer"),
             target: dir.join("challenge_target"),
             results: dir.join("results"),
             start: dir.join("challenge_start"),
             lua: dir.join("runtime.lua"),
+            instructions: dir.join("instructions"),
         }
     }
-
-    fn ensure_dir(&self) -> io::Result<()> {
-        if let Some(parent) = self.buffer.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        Ok(())
-    }
 }
 
 /// Launch neovim with a challenge. Returns the result after nvim exits.
-pub fn run_challenge(challenge: &Challenge, number: usize) -> io::Result<ChallengeResult> {
-    let files = SessionFiles::new();
-    files.ensure_dir()?;
+pub fn run_challenge(
+    launcher: &Launcher,
+    challenge: &Challenge,
+    number: usize,
+) -> Result<ChallengeResult, Error> {
+    let dir = session_dir("challenge")?;
+    plan_challenge(launcher, challenge, number, dir.path()).run(launcher)?;
+
+    let files = SessionFiles::in_dir(dir.path());
+    let content = read_session_file(&files.buffer)?;
+    Ok(evaluate(challenge, &content, read_results(&files.results)))
+}
+
+/// The files and neovim command of a challenge session in `dir`.
+pub fn plan_challenge(
+    launcher: &Launcher,
+    challenge: &Challenge,
+    number: usize,
+    dir: &Path,
+) -> LaunchPlan {
+    let files = SessionFiles::in_dir(dir);
 
-    // Write start content, target content, and start backup to temp files
-    fs::write(&files.buffer, &challenge.start.content)?;
-    fs::write(&files.target, &challenge.target.content)?;
-    fs::write(&files.start, &challenge.start.content)?;
+    // Start content, target content, and start backup
+    let mut written = vec![
+        (files.buffer.clone(), challenge.start().content.clone()),
+        (files.target.clone(), challenge.target().content.clone()),
+        (files.start.clone(), challenge.start().content.clone()),
+    ];
 
-    // Remove old results file if exists
-    let _ = fs::remove_file(&files.results);
+    // The Lua runtime script
+    let lua_script = build_lua_script(
+        challenge,
+        number,
+        challenge.is_freestyle(),
+        &files,
+        &launcher.session,
+        false,
+    );
+    written.push((files.lua.clone(), lua_script));
 
-    let freestyle = challenge.is_freestyle();
+    // Apply the challenge filetype to both the target and the start buffer
+    let filetype = challenge
+        .filetype
+        .as_deref()
+        .map_or_else(String::new, |ft| format!(" | setlocal filetype={ft}"));
 
-    // Build and write the Lua runtime script
-    let lua_script = build_lua_script(challenge, number, freestyle, &files);
-    fs::write(&files.lua, &lua_script)?;
+    // Open the instructions (the description, and the hint in blind mode) and the
+    // target in horizontal splits above the challenge buffer, read-only and labeled.
+    // Blind challenges have no target split.
+    let instructions = match challenge.instructions() {
+        Some(text) => {
+            let split = format!(
+                "split {} | setlocal readonly nomodifiable noswapfile buftype=nofile wrap | \
+                 let &l:winbar = '  [INSTRUCTIONS]' | resize {} | wincmd j | ",
+                fnameescape(&files.instructions),
+                text.lines().count() + 1
+            );
+            written.push((files.instructions.clone(), text));
+            split
+        }
+        None => String::new(),
+    };
+    let split = if challenge.blind {
+        format!("{}{filetype}", instructions.trim_end_matches(" | "))
+    } else {
+        format!(
+            "{instructions}split {} | \
+             setlocal readonly nomodifiable noswapfile buftype=nofile{filetype} | \
+             let &l:winbar = '  [TARGET]' | \
+             diffthis | set diffopt+=context:99999 | setlocal wrap nocursorbind | \
+             wincmd j{filetype} | diffthis | set diffopt+=context:99999 | setlocal wrap nocursorbind",
+            fnameescape(&files.target)
+        )
+    };
 
-    // Build nvim command
-    let status = Command::new("nvim")
+    let mut command = Command::new(&launcher.bin);
+    command
+        .args(&launcher.args)
         // Disable swap files and viminfo to avoid noise
         .arg("--cmd")
         .arg("set noswapfile noundofile nobackup nowritebackup")
-        // Open target in a horizontal split (top, read-only, labeled)
         .arg("-c")
-        .arg(format!(
-            "split {} | setlocal readonly nomodifiable noswapfile buftype=nofile | \
-             let &l:winbar = '  [TARGET]' | \
-             diffthis | set diffopt+=context:99999 | setlocal wrap nocursorbind | \
-             wincmd j | diffthis | set diffopt+=context:99999 | setlocal wrap nocursorbind",
-            files.target.display()
-        ))
+        .arg(split)
         // Load the Lua runtime
         .arg("-c")
-        .arg(format!("luafile {}", files.lua.display()))
-        // Stop counting keystrokes and quit on :w
+        .arg(format!("luafile {}", fnameescape(&files.lua)))
+        // Stop counting keystrokes and quit on :w. The challenge buffer is current
+        // again after the split, and <buffer> needs no path pattern to escape.
         .arg("-c")
-        .arg(format!(
-            "autocmd BufWritePost {} lua _G._ks_stop(); vim.cmd('qall!')",
-            files.buffer.display()
-        ))
+        .arg("autocmd BufWritePost <buffer> lua _G._ks_stop(); vim.cmd('qall!')")
         // Open the challenge buffer
-        .arg(&files.buffer)
-        .status()?;
+        .arg(&files.buffer);
+    LaunchPlan {
+        files: written,
+        command,
+        stderr: dir.join("stderr"),
+    }
+}
+
+/// Compare the final buffer `content` and the runtime's results with the target.
+fn evaluate(challenge: &Challenge, content: &str, results: SessionResults) -> ChallengeResult {
+    let cursor_matches = challenge
+        .target()
+        .cursor
+        .is_none_or(|target| results.cursor == Some(target));
+    // Single-command and macro challenges are only solved by the command or
+    // macro the runtime saw turn the start buffer into the target
+    let buffer_matches = results.outcome.can_match()
+        && (!challenge.single_command || results.command.is_some())
+        && (challenge.recorded_macro.is_none() || results.macro_keys.is_some())
+        && if challenge.is_tutorial() {
+            results.steps_done == challenge.tutorial_steps.len()
+        } else {
+            cursor_matches && normalize(content) == normalize(&challenge.target().content)
+        };
+
+    ChallengeResult {
+        buffer_matches,
+        keystrokes: results.keystrokes,
+        elapsed_ms: results.elapsed_ms,
+        keys: results.keys,
+        violation: results.violation,
+        technique_missing: results.technique_missing,
+        shell_commands: results.shell_commands,
+        hints_used: results.hints_used,
+        outcome: results.outcome,
+    }
+}
+
+/// A challenge played inside an already running neovim, through the plugin and
+/// `nvimkata rpc-serve`. The plugin opens the start and target files in scratch
+/// buffers and loads the script, which runs the challenge runtime in embedded mode.
+pub struct EmbeddedSession {
+    files: SessionFiles,
+    /// Removed with the session.
+    _dir: SessionDir,
+}
+
+impl EmbeddedSession {
+    /// Write the session files of `challenge` to a new session directory in `parent`.
+    pub fn create(
+        parent: &Path,
+        challenge: &Challenge,
+        number: usize,
+        options: &SessionOptions,
+    ) -> Result<Self, SessionError> {
+        let dir = SessionDir::create_in(parent, "embedded").map_err(SessionError::at(parent))?;
+        let files = SessionFiles::in_dir(dir.path());
+        write_session_file(&files.target, &challenge.target().content)?;
+        write_session_file(&files.start, &challenge.start().content)?;
+        if let Some(text) = challenge.instructions() {
+            write_session_file(&files.instructions, &text)?;
+        }
+        let lua_script = build_lua_script(
+            challenge,
+            number,
+            challenge.is_freestyle(),
+            &files,
+            options,
+            true,
+        );
+        write_session_file(&files.lua, &lua_script)?;
+        Ok(Self { files, _dir: dir })
+    }
+
+    pub fn start_path(&self) -> &Path {
+        &self.files.start
+    }
+
+    pub fn target_path(&self) -> &Path {
+        &self.files.target
+    }
+
+    /// The description, and the hint in place of the target of a blind
+    /// challenge, for challenges with [`Challenge::instructions`].
+    pub fn instructions_path(&self) -> &Path {
+        &self.files.instructions
+    }
+
+    /// Lua script to load in the challenge buffer.
+    pub fn script_path(&self) -> &Path {
+        &self.files.lua
+    }
+
+    /// Evaluate the session once the runtime has written its results, given the
+    /// final lines of the challenge buffer.
+    pub fn finish(&self, challenge: &Challenge, lines: &[String]) -> ChallengeResult {
+        evaluate(
+            challenge,
+            &lines.join("\n"),
+            read_results(&self.files.results),
+        )
+    }
+}
+
+/// Open neovim in the sandbox: the start content of `challenge`, or a blank
+/// buffer, with the keystroke HUD but no target, grading or results.
+pub fn run_sandbox(launcher: &Launcher, challenge: Option<&Challenge>) -> Result<(), Error> {
+    let dir = session_dir("sandbox")?;
+    let buffer = dir.path().join("sandbox_buffer");
+    let start = dir.path().join("sandbox_start");
+    let lua = dir.path().join("sandbox.lua");
+
+    let content = challenge.map_or("", |c| c.start().content.as_str());
+    write_session_file(&buffer, content)?;
+    write_session_file(&start, content)?;
+
+    let setup = challenge.map_or_else(String::new, |c| setup_lua(&c.setup));
+    let preamble = format!(
+        "_VK_TITLE = '{title}'\n\
+         _VK_START_PATH = '{start_path}'\n\
+         _VK_START_CURSOR = {start_cursor}\n\
+         _VK_APPLY_SETUP = function(buf) {setup} end\n",
+        title = escape_for_lua_sq(challenge.map_or("blank buffer", |c| c.title.as_str())),
+        start_path = escape_for_lua_sq(&start.display().to_string()),
+        start_cursor = lua_cursor(challenge.and_then(|c| c.start().cursor)),
+    );
+    write_session_file(
+        &lua,
+        &format!("{preamble}\n{}", include_str!("sandbox_runtime.lua")),
+    )?;
+
+    let filetype = challenge
+        .and_then(|c| c.filetype.as_deref())
+        .map_or_else(String::new, |ft| format!("setlocal filetype={ft} | "));
+
+    launcher.run_logged(
+        Command::new(&launcher.bin)
+            .args(&launcher.args)
+            .arg("--cmd")
+            .arg("set noswapfile noundofile nobackup nowritebackup")
+            .arg("-c")
+            .arg(format!("{filetype}luafile {}", fnameescape(&lua)))
+            // Leave on :w, like a challenge
+            .arg("-c")
+            .arg("autocmd BufWritePost <buffer> qall!")
+            .arg(&buffer),
+        &dir.path().join("stderr"),
+    )
+}
+
+/// Feed `moves` to a challenge in a headless nvim (no config, no runtime UI) and
+/// report the outcome. The moves are concatenated and fed at once, followed by
+/// `<Esc>`, so insert-mode sequences spanning adjacent moves work. Keystrokes are
+/// counted with `count_keystrokes`; elapsed time is always 0. A run that does not
+/// finish within `timeout` is killed and reported as [`Outcome::TimedOut`]. The
+/// launcher's extra arguments are not used since headless runs start without any
+/// config.
+pub fn run_headless(
+    launcher: &Launcher,
+    challenge: &Challenge,
+    moves: &[String],
+    timeout: Duration,
+) -> Result<ChallengeResult, Error> {
+    let dir = session_dir("headless")?;
+    let buffer = dir.path().join("buffer");
+    let results_path = dir.path().join("results");
+    let stderr = dir.path().join("stderr");
+    write_session_file(&buffer, &challenge.start().content)?;
+    let log = fs::File::create(&stderr).map_err(SessionError::at(&stderr))?;
+
+    let keys = moves.concat();
+    let keystrokes = u32::try_from(count_keystrokes(&keys)).unwrap_or(u32::MAX);
+    let lua = headless_lua(challenge, &keys, keystrokes, &results_path);
+
+    let mut child = Command::new(&launcher.bin)
+        .arg("--headless")
+        .arg("-u")
+        .arg("NONE")
+        .arg("-i")
+        .arg("NONE")
+        .arg("--cmd")
+        .arg("set noswapfile noundofile nobackup nowritebackup")
+        .arg("-c")
+        .arg(&lua)
+        .arg("-c")
+        .arg("silent! write | qall!")
+        .arg(&buffer)
+        .stderr(log)
+        .spawn()
+        .map_err(|e| NvimError::spawn(&launcher.bin, e))?;
 
+    let Some(status) = wait_headless(&mut child, &launcher.bin, timeout)? else {
+        return Ok(ChallengeResult {
+            buffer_matches: false,
+            keystrokes,
+            elapsed_ms: 0,
+            violation: None,
+            technique_missing: false,
+            keys,
+            shell_commands: Vec::new(),
+            hints_used: 0,
+            outcome: Outcome::TimedOut,
+        });
+    };
     if !status.success() {
-        return Err(io::Error::other(format!(
-            "nvim exited with status: {status}"
-        )));
+        let output = fs::read(&stderr).unwrap_or_default();
+        return Err(launcher
+            .exit_error(status, &String::from_utf8_lossy(&output))
+            .into());
     }
 
-    // Read results
-    let result_content = fs::read_to_string(&files.buffer)?;
-    let (keystrokes, elapsed_secs, keys) = read_results(&files.results);
-    let buffer_matches = normalize(&result_content) == normalize(&challenge.target.content);
+    let content = read_session_file(&buffer)?;
+    let results = read_results(&results_path);
+    let keystrokes = match (&challenge.recorded_macro, &results.macro_keys) {
+        (Some(spec), Some(macro_keys)) => {
+            u32::try_from(spec.keystrokes(macro_keys, &keys)).unwrap_or(u32::MAX)
+        }
+        _ => keystrokes,
+    };
 
+    let cursor_matches = challenge
+        .target()
+        .cursor
+        .is_none_or(|target| results.cursor == Some(target));
+    let buffer_matches = if challenge.is_tutorial() {
+        challenge.tutorial_steps_done(&keys) == challenge.tutorial_steps.len()
+    } else {
+        cursor_matches && normalize(&content) == normalize(&challenge.target().content)
+    };
     Ok(ChallengeResult {
         buffer_matches,
         keystrokes,
-        elapsed_secs,
+        elapsed_ms: 0,
+        violation: challenge.violation_in(&keys),
+        technique_missing: !challenge.technique_used(&keys),
         keys,
+        shell_commands: Vec::new(),
+        hints_used: 0,
+        outcome: if buffer_matches {
+            Outcome::Completed
+        } else {
+            Outcome::SubmittedEarly
+        },
     })
 }
 
+/// Wait for a headless editor to exit, killing it once `timeout` has passed.
+/// `None` when it was killed.
+fn wait_headless(
+    child: &mut Child,
+    bin: &str,
+    timeout: Duration,
+) -> Result<Option<ExitStatus>, NvimError> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait().map_err(|e| NvimError::spawn(bin, e))? {
+            Some(status) => return Ok(Some(status)),
+            None if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+/// The buffer and cursor of a challenge partway through a replay.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Snapshot {
+    pub lines: Vec<String>,
+    /// `[line, column]`, both 1-based.
+    pub cursor: [u32; 2],
+}
+
+/// Replay `steps`, a keystroke or a whole command each, on a challenge in a
+/// headless nvim and snapshot the buffer before the first step and after
+/// every one. Each snapshot feeds its keys from the start like `:normal`, so
+/// a keystroke in the middle of a command, the `d` of `dw`, shows the buffer
+/// as it was. A replay that does not finish within `timeout` is cut short at
+/// the snapshots taken so far.
+pub fn snapshots(
+    launcher: &Launcher,
+    challenge: &Challenge,
+    steps: &[String],
+    timeout: Duration,
+) -> Result<Vec<Snapshot>, Error> {
+    let dir = session_dir("replay")?;
+    let buffer = dir.path().join("buffer");
+    let frames = dir.path().join("frames");
+    let script = dir.path().join("replay.lua");
+    let stderr = dir.path().join("stderr");
+    write_session_file(&buffer, &challenge.start().content)?;
+    write_session_file(&script, &snapshot_lua(challenge, steps, &frames))?;
+    let log = fs::File::create(&stderr).map_err(SessionError::at(&stderr))?;
+
+    let mut child = Command::new(&launcher.bin)
+        .arg("--headless")
+        .arg("-u")
+        .arg("NONE")
+        .arg("-i")
+        .arg("NONE")
+        .arg("--cmd")
+        .arg("set noswapfile noundofile nobackup nowritebackup")
+        .arg("-c")
+        .arg(format!("luafile {}", fnameescape(&script)))
+        .arg("-c")
+        .arg("qall!")
+        .arg(&buffer)
+        .stderr(log)
+        .spawn()
+        .map_err(|e| NvimError::spawn(&launcher.bin, e))?;
+    if let Some(status) = wait_headless(&mut child, &launcher.bin, timeout)?
+        && !status.success()
+    {
+        let output = fs::read(&stderr).unwrap_or_default();
+        return Err(launcher
+            .exit_error(status, &String::from_utf8_lossy(&output))
+            .into());
+    }
+    // Missing when the replay was cut short before the first snapshot
+    let content = fs::read_to_string(&frames).unwrap_or_default();
+    Ok(content
+        .lines()
+        .map_while(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The Lua script of a replay: for none of the steps, then for each longer
+/// run of them, reset the buffer to the start, feed the keys and append a
+/// snapshot to `frames` as a JSON line. The reset is kept out of the undo
+/// history, so an undo in the keys doesn't undo it.
+fn snapshot_lua(challenge: &Challenge, steps: &[String], frames: &Path) -> String {
+    let [line, col] = challenge.start().cursor.unwrap_or([1, 1]);
+    format!(
+        "local buf = 0\n\
+         local keys = {keys}\n\
+         local start = vim.api.nvim_buf_get_lines(0, 0, -1, false)\n\
+         for i = 0, #keys do\n\
+         \x20 local levels = vim.o.undolevels\n\
+         \x20 vim.o.undolevels = -1\n\
+         \x20 vim.api.nvim_buf_set_lines(0, 0, -1, false, start)\n\
+         \x20 vim.o.undolevels = levels\n\
+         \x20 {setup}\n\
+         \x20 vim.api.nvim_win_set_cursor(0, {{ {line}, {col} }})\n\
+         \x20 local typed = table.concat(keys, '', 1, i)\n\
+         \x20 pcall(vim.api.nvim_feedkeys, vim.api.nvim_replace_termcodes(typed, true, true, true), 'ntx', false)\n\
+         \x20 local p = vim.api.nvim_win_get_cursor(0)\n\
+         \x20 local lines = vim.api.nvim_buf_get_lines(0, 0, -1, false)\n\
+         \x20 vim.fn.writefile({{ vim.fn.json_encode({{ lines = lines, cursor = {{ p[1], p[2] + 1 }} }}) }}, '{frames}', 'a')\n\
+         end\n",
+        keys = lua_string_list(steps),
+        setup = setup_lua(&challenge.setup),
+        col = col.saturating_sub(1),
+        frames = escape_for_lua_sq(&frames.display().to_string()),
+    )
+}
+
+/// The `-c` command of a headless run: apply the setup, feed `keys`, then
+/// write the results file.
+fn headless_lua(challenge: &Challenge, keys: &str, keystrokes: u32, results_path: &Path) -> String {
+    let cursor = challenge.start().cursor.map_or_else(String::new, |[l, c]| {
+        format!(
+            "vim.api.nvim_win_set_cursor(0, {{{l}, {}}}); ",
+            c.saturating_sub(1)
+        )
+    });
+
+    // Macro challenges are scored on the recorded macro (not replayed here)
+    let macro_line = challenge
+        .recorded_macro
+        .as_ref()
+        .map_or_else(String::new, |m| {
+            format!(
+                ", 'macro=' .. vim.fn.keytrans(vim.fn.getreg('{}'))",
+                m.register
+            )
+        });
+
+    // do_lt (3rd arg of nvim_replace_termcodes) is true so <lt> becomes a literal '<'.
+    // The final cursor goes to a results file in the runtime's format; write/quit
+    // is a separate -c command to avoid timeouts.
+    format!(
+        "lua local buf = 0; {setup}{cursor}\
+         vim.api.nvim_feedkeys( \
+           vim.api.nvim_replace_termcodes('{keys}<Esc>', true, true, true), 'ntx', false); \
+         local p = vim.api.nvim_win_get_cursor(0); \
+         vim.fn.writefile({{'{keystrokes}', '0', '', 'cursor=' .. p[1] .. ',' .. (p[2] + 1){macro_line}}}, \
+           '{results}')",
+        setup = setup_lua(&challenge.setup),
+        keys = escape_for_lua_sq(keys),
+        results = escape_for_lua_sq(&results_path.display().to_string()),
+    )
+}
+
+/// Escape a path for an Ex command argument such as `:split`, like `fnameescape()`.
+/// Every path put into an Ex command goes through this, and every path put into
+/// a Lua string through [`escape_for_lua_sq`].
+pub fn fnameescape(path: &Path) -> String {
+    fnameescape_for(Platform::CURRENT, &path.display().to_string())
+}
+
+/// [`fnameescape`] for `platform`. On Windows backslashes separate directories,
+/// so they become `/`, which Vim accepts there too.
+pub fn fnameescape_for(platform: Platform, path: &str) -> String {
+    let (path, special) = match platform {
+        Platform::Unix => (path.to_string(), " \t\n*?[{`$\\%#'\"|!<"),
+        Platform::Windows => (path.replace('\\', "/"), " \t\n*?[{`$%#'\"|!<"),
+    };
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 /// Escape a string for use in a Lua single-quoted string literal.
 pub fn escape_for_lua_sq(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -115,40 +723,209 @@ pub fn escape_for_lua_sq(s: &str) -> String {
         .replace('\r', "\\r")
 }
 
+/// Lua statements applying a challenge `[setup]` to the buffer held in the
+/// Lua variable `buf` (0 for the current buffer).
+pub fn setup_lua(setup: &Setup) -> String {
+    let options = setup.options.iter().map(|option| {
+        format!(
+            "vim.api.nvim_buf_call(buf, function() vim.cmd('setlocal {}') end); ",
+            escape_for_lua_sq(option)
+        )
+    });
+    let registers = setup.registers.iter().map(|(name, content)| {
+        format!(
+            "vim.fn.setreg('{}', '{}'); ",
+            escape_for_lua_sq(name),
+            escape_for_lua_sq(content)
+        )
+    });
+    let marks = setup.marks.iter().map(|(name, [line, col])| {
+        format!(
+            "pcall(vim.api.nvim_buf_set_mark, buf, '{}', {line}, {}, {{}}); ",
+            escape_for_lua_sq(name),
+            col.saturating_sub(1)
+        )
+    });
+    let mut lua: String = options.chain(registers).chain(marks).collect();
+    if !setup.quickfix.is_empty() {
+        let items: Vec<String> = setup
+            .quickfix
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{ bufnr = qf_buf, lnum = {}, col = {}, text = '{}' }}",
+                    entry.line,
+                    entry.col,
+                    escape_for_lua_sq(&entry.text)
+                )
+            })
+            .collect();
+        // A new list, so the player's own quickfix lists stay in the history
+        let _ = write!(
+            lua,
+            "do local qf_buf = buf ~= 0 and buf or vim.api.nvim_get_current_buf(); \
+             vim.fn.setqflist({{}}, ' ', {{ title = 'nvimkata', items = {{ {} }} }}) end; ",
+            items.join(", ")
+        );
+    }
+    lua
+}
+
+/// Format strings as a Lua list literal of single-quoted strings.
+fn lua_string_list<S: AsRef<str>>(items: &[S]) -> String {
+    let quoted: Vec<String> = items
+        .iter()
+        .map(|item| format!("'{}'", escape_for_lua_sq(item.as_ref())))
+        .collect();
+    format!("{{ {} }}", quoted.join(", "))
+}
+
+/// Format key sequences as a Lua list of `{ sequence, { key, ... } }` pairs, the
+/// sequence as written and its keys in key notation.
+fn lua_key_sequences(sequences: &[String]) -> String {
+    let pairs: Vec<String> = sequences
+        .iter()
+        .map(|seq| {
+            let keys: Vec<String> = notation::tokenize(seq)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            format!(
+                "{{ '{}', {} }}",
+                escape_for_lua_sq(seq),
+                lua_string_list(&keys)
+            )
+        })
+        .collect();
+    format!("{{ {} }}", pairs.join(", "))
+}
+
+/// Format tutorial steps as a Lua list of `{ instruction, keys }` pairs.
+fn lua_tutorial_steps(steps: &[TutorialStep]) -> String {
+    let pairs: Vec<String> = steps
+        .iter()
+        .map(|step| {
+            format!(
+                "{{ '{}', '{}' }}",
+                escape_for_lua_sq(&step.instruction),
+                escape_for_lua_sq(&step.keys)
+            )
+        })
+        .collect();
+    format!("{{ {} }}", pairs.join(", "))
+}
+
+/// Format `[line, column, length]` spans as a Lua list of triples.
+fn lua_spans(spans: &[[u32; 3]]) -> String {
+    let triples: Vec<String> = spans
+        .iter()
+        .map(|[line, col, len]| format!("{{ {line}, {col}, {len} }}"))
+        .collect();
+    format!("{{ {} }}", triples.join(", "))
+}
+
+/// Format an optional `[line, column]` cursor as a Lua table literal, or `nil`.
+fn lua_cursor(cursor: Option<[u32; 2]>) -> String {
+    cursor.map_or_else(|| "nil".to_string(), |[l, c]| format!("{{ {l}, {c} }}"))
+}
+
 /// Build the full Lua script by prepending variable definitions to the template.
 fn build_lua_script(
     challenge: &Challenge,
     number: usize,
     freestyle: bool,
     files: &SessionFiles,
+    options: &SessionOptions,
+    embedded: bool,
 ) -> String {
     let title = escape_for_lua_sq(&challenge.title);
-    let hint = escape_for_lua_sq(&challenge.hint);
-    let detailed_hint = challenge
-        .detailed_hint
-        .as_deref()
-        .map_or_else(String::new, escape_for_lua_sq);
-    let results_path = files.results.display();
-    let target_path = files.target.display();
-    let start_path = files.start.display();
+    // Escaped, since Windows paths are full of backslashes
+    let results_path = escape_for_lua_sq(&files.results.display().to_string());
+    let target_path = escape_for_lua_sq(&files.target.display().to_string());
+    let start_path = escape_for_lua_sq(&files.start.display().to_string());
 
     let preamble = format!(
         "_VK_NUMBER = {number}\n\
          _VK_TITLE = '{title}'\n\
          _VK_PAR = {par}\n\
-         _VK_HINT = '{hint}'\n\
-         _VK_DETAILED_HINT = '{detailed_hint}'\n\
+         _VK_HINTS = {hints}\n\
+         _VK_HINT_SPANS = {hint_spans}\n\
+         _VK_INTRO = '{intro}'\n\
          _VK_FREESTYLE = {freestyle}\n\
+         _VK_EMBEDDED = {embedded}\n\
+         _VK_BLIND = {blind}\n\
+         _VK_REVEAL_MS = {reveal_ms}\n\
+         _VK_SINGLE_COMMAND = {single_command}\n\
+         _VK_MACRO_REGISTER = '{macro_register}'\n\
+         _VK_MACRO_REPLAYS = {macro_replays}\n\
+         _VK_SHELL_ALLOWED = {shell_allowed}\n\
+         _VK_BRIEFING = {briefing}\n\
+         _VK_HARDCORE = {hardcore}\n\
+         _VK_BANNER_PATH = {banner_path}\n\
+         _VK_PERFECT_MOVES = {perfect_moves}\n\
+         _VK_REFERENCE = {reference}\n\
          _VK_RESULTS_PATH = '{results_path}'\n\
          _VK_TARGET_PATH = '{target_path}'\n\
          _VK_START_PATH = '{start_path}'\n\
+         _VK_START_CURSOR = {start_cursor}\n\
+         _VK_TARGET_CURSOR = {target_cursor}\n\
+         _VK_APPLY_SETUP = function(buf) {setup} end\n\
+         _VK_FORBIDDEN = {forbidden}\n\
+         _VK_ONE_HAND = {one_hand}\n\
+         _VK_TECHNIQUE_NAME = '{technique_name}'\n\
+         _VK_TECHNIQUE_KEYS = {technique_keys}\n\
+         _VK_TUTORIAL_STEPS = {tutorial_steps}\n\
          _VK_THRESHOLD_A = {ta}\n\
          _VK_THRESHOLD_B = {tb}\n\
          _VK_THRESHOLD_C = {tc}\n\
          _VK_THRESHOLD_D = {td}\n\
          _VK_THRESHOLD_E = {te}\n\
          _VK_THRESHOLD_F = {tf}\n",
-        par = challenge.par_keystrokes,
+        par = challenge.par(),
+        hints = lua_string_list(&challenge.hint_tiers()),
+        hint_spans = lua_spans(&challenge.hint_spans()),
+        intro = escape_for_lua_sq(challenge.intro.as_deref().unwrap_or_default()),
+        blind = challenge.blind,
+        reveal_ms = challenge.reveal_secs.map_or(0, |s| u64::from(s) * 1000),
+        single_command = challenge.single_command,
+        macro_register = challenge
+            .recorded_macro
+            .as_ref()
+            .map_or_else(String::new, |m| m.register.to_string()),
+        macro_replays = challenge.recorded_macro.as_ref().map_or(0, |m| m.replays),
+        shell_allowed = options
+            .shell
+            .allowed_programs()
+            .map_or_else(|| "nil".to_string(), lua_string_list),
+        briefing = options.briefing,
+        hardcore = options.hardcore && challenge.has_key_budget(),
+        banner_path = options.banner.as_ref().map_or_else(
+            || "nil".to_string(),
+            |path| format!("'{}'", escape_for_lua_sq(&path.display().to_string()))
+        ),
+        perfect_moves = lua_string_list(challenge.perfect_moves.as_deref().unwrap_or_default()),
+        reference = challenge.reference_keystrokes.unwrap_or(0),
+        start_cursor = lua_cursor(challenge.start().cursor),
+        target_cursor = lua_cursor(challenge.target().cursor),
+        setup = setup_lua(&challenge.setup),
+        forbidden = lua_key_sequences(&challenge.forbidden_keys),
+        one_hand = challenge.one_hand_keys().map_or_else(
+            || "nil".to_string(),
+            |keys| lua_string_list(&keys.iter().map(ToString::to_string).collect::<Vec<_>>())
+        ),
+        technique_name = escape_for_lua_sq(
+            challenge
+                .required_technique
+                .as_ref()
+                .map_or("", |t| t.name.as_str())
+        ),
+        technique_keys = lua_key_sequences(
+            challenge
+                .required_technique
+                .as_ref()
+                .map_or(&[][..], |t| t.keys.as_slice())
+        ),
+        tutorial_steps = lua_tutorial_steps(&challenge.tutorial_steps),
         ta = challenge.threshold(Grade::A),
         tb = challenge.threshold(Grade::B),
         tc = challenge.threshold(Grade::C),
@@ -161,21 +938,74 @@ fn build_lua_script(
     format!("{preamble}\n{template}")
 }
 
-/// Read keystroke count, elapsed seconds, and key log from the results file.
-/// Format: three lines — keystroke count, elapsed seconds, key presses.
-fn read_results(path: &Path) -> (u32, u32, String) {
-    let contents = fs::read_to_string(path).unwrap_or_default();
+/// Values written by the Lua runtime when a session ends.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SessionResults {
+    keystrokes: u32,
+    elapsed_ms: u32,
+    keys: String,
+    /// Final cursor as `[line, column]`, both 1-based.
+    cursor: Option<[u32; 2]>,
+    violation: Option<String>,
+    technique_missing: bool,
+    /// Tutorial steps completed.
+    steps_done: usize,
+    /// The command that solved a single-command challenge.
+    command: Option<String>,
+    /// The recorded macro, once it replayed to the target.
+    macro_keys: Option<String>,
+    shell_commands: Vec<String>,
+    /// Deepest hint tier revealed.
+    hints_used: u32,
+    /// `Aborted` when the file is missing.
+    outcome: Outcome,
+}
+
+/// Read the results file written by the Lua runtime.
+/// Format: three lines — keystroke count, elapsed milliseconds, key presses — followed
+/// by optional `name=value` lines (`cursor=line,col`, `violation=keys`,
+/// `technique=missing`, `steps=n`, `command=cmd`, `macro=keys`, a `shell=cmd` line
+/// per shell command run, `hints=n`, `outcome=completed|submitted`). Results
+/// without an outcome are from a finished session.
+fn read_results(path: &Path) -> SessionResults {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return SessionResults::default();
+    };
     let mut lines = contents.lines();
-    let keystrokes = lines
-        .next()
-        .and_then(|s| s.trim().parse().ok())
-        .unwrap_or(0);
-    let elapsed = lines
-        .next()
-        .and_then(|s| s.trim().parse().ok())
-        .unwrap_or(0);
-    let keys = lines.next().unwrap_or("").to_string();
-    (keystrokes, elapsed, keys)
+    let mut results = SessionResults {
+        outcome: Outcome::SubmittedEarly,
+        keystrokes: lines
+            .next()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0),
+        elapsed_ms: lines
+            .next()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0),
+        keys: lines.next().unwrap_or("").to_string(),
+        ..SessionResults::default()
+    };
+    for line in lines {
+        match line.split_once('=') {
+            Some(("cursor", value)) => {
+                results.cursor = value
+                    .split_once(',')
+                    .and_then(|(l, c)| Some([l.trim().parse().ok()?, c.trim().parse().ok()?]));
+            }
+            Some(("violation", value)) => results.violation = Some(value.to_string()),
+            Some(("technique", "missing")) => results.technique_missing = true,
+            Some(("steps", value)) => results.steps_done = value.trim().parse().unwrap_or(0),
+            Some(("command", value)) => results.command = Some(value.to_string()),
+            Some(("macro", value)) => results.macro_keys = Some(value.to_string()),
+            Some(("shell", value)) => results.shell_commands.push(value.to_string()),
+            Some(("hints", value)) => results.hints_used = value.trim().parse().unwrap_or(0),
+            Some(("outcome", value)) => {
+                results.outcome = Outcome::parse(value.trim()).unwrap_or(results.outcome);
+            }
+            _ => {}
+        }
+    }
+    results
 }
 
 /// Normalize content for comparison: trim trailing whitespace per line,
@@ -193,25 +1023,186 @@ pub fn normalize(s: &str) -> String {
 mod tests {
     use super::*;
 
+    fn results(keystrokes: u32, elapsed_ms: u32, keys: &str) -> SessionResults {
+        SessionResults {
+            keystrokes,
+            elapsed_ms,
+            keys: keys.to_string(),
+            outcome: Outcome::SubmittedEarly,
+            ..SessionResults::default()
+        }
+    }
+
     #[test]
     fn test_read_results_valid() {
         let tmp = std::env::temp_dir().join("rlv_test_results");
         fs::write(&tmp, "42\n15\njf8cw3000").unwrap();
-        assert_eq!(read_results(&tmp), (42, 15, "jf8cw3000".to_string()));
+        assert_eq!(read_results(&tmp), results(42, 15, "jf8cw3000"));
         let _ = fs::remove_file(&tmp);
     }
 
     #[test]
     fn test_read_results_missing_file() {
         let tmp = std::env::temp_dir().join("rlv_nonexistent_results");
-        assert_eq!(read_results(&tmp), (0, 0, String::new()));
+        let parsed = read_results(&tmp);
+        assert_eq!(parsed, SessionResults::default());
+        assert_eq!(parsed.outcome, Outcome::Aborted);
+    }
+
+    #[test]
+    fn test_read_results_outcome() {
+        let tmp = std::env::temp_dir().join("rlv_test_results_outcome");
+        fs::write(&tmp, "7\n3\nxx\noutcome=completed").unwrap();
+        assert_eq!(read_results(&tmp).outcome, Outcome::Completed);
+        fs::write(&tmp, "7\n3\nxx\noutcome=submitted").unwrap();
+        assert_eq!(read_results(&tmp).outcome, Outcome::SubmittedEarly);
+        fs::write(&tmp, "7\n3\nxx\noutcome=over_budget").unwrap();
+        assert_eq!(read_results(&tmp).outcome, Outcome::OverBudget);
+        assert!(Outcome::OverBudget.is_attempt());
+        fs::write(&tmp, "7\n3\nxx\noutcome=gave_up").unwrap();
+        assert_eq!(read_results(&tmp).outcome, Outcome::GaveUp);
+        assert!(Outcome::GaveUp.is_attempt() && !Outcome::GaveUp.can_match());
+        let _ = fs::remove_file(&tmp);
     }
 
     #[test]
     fn test_read_results_partial() {
         let tmp = std::env::temp_dir().join("rlv_test_results_partial");
         fs::write(&tmp, "35\n").unwrap();
-        assert_eq!(read_results(&tmp), (35, 0, String::new()));
+        assert_eq!(read_results(&tmp), results(35, 0, ""));
+        let _ = fs::remove_file(&tmp);
+    }
+
+    #[test]
+    fn test_read_results_flags() {
+        let tmp = std::env::temp_dir().join("rlv_test_results_violation");
+        fs::write(&tmp, "7\n3\nxx\ncursor=1,1\nviolation=<Left>\nhints=2").unwrap();
+        let results = read_results(&tmp);
+        assert_eq!(results.violation.as_deref(), Some("<Left>"));
+        assert_eq!(results.hints_used, 2);
+        assert!(!results.technique_missing);
+        let _ = fs::remove_file(&tmp);
+
+        let tmp = std::env::temp_dir().join("rlv_test_results_technique");
+        fs::write(&tmp, "7\n3\nxx\ncursor=1,1\ntechnique=missing").unwrap();
+        assert!(read_results(&tmp).technique_missing);
         let _ = fs::remove_file(&tmp);
+
+        let tmp = std::env::temp_dir().join("rlv_test_results_steps");
+        fs::write(&tmp, "7\n3\nxx\ncursor=1,1\nsteps=2").unwrap();
+        assert_eq!(read_results(&tmp).steps_done, 2);
+        let _ = fs::remove_file(&tmp);
+    }
+
+    #[test]
+    fn test_single_command_needs_the_command() {
+        let challenge: Challenge = toml::from_str(
+            r#"
+id = "ex_999"
+version = "1.0.0"
+title = "T"
+topic = "ex_commands"
+difficulty = 1
+hint = "h"
+par_keystrokes = 7
+single_command = true
+[start]
+content = "a\nb"
+[target]
+content = "b"
+"#,
+        )
+        .unwrap();
+        let mut results = results(7, 900, "dd");
+        assert!(!evaluate(&challenge, "b", results.clone()).buffer_matches);
+        results.command = Some("g/a/d".to_string());
+        assert!(evaluate(&challenge, "b", results.clone()).buffer_matches);
+        // Giving up fails the run, though the playback leaves the target
+        results.outcome = Outcome::GaveUp;
+        assert!(!evaluate(&challenge, "b", results).buffer_matches);
+    }
+
+    #[test]
+    fn test_lua_string_list() {
+        let items = ["x".to_string(), "it's".to_string()];
+        assert_eq!(lua_string_list(&items), r"{ 'x', 'it\'s' }");
+        assert_eq!(lua_string_list::<String>(&[]), "{  }");
+    }
+
+    #[test]
+    fn test_read_results_cursor() {
+        let tmp = std::env::temp_dir().join("rlv_test_results_cursor");
+        fs::write(&tmp, "7\n3\nwww\ncursor=4,12\n").unwrap();
+        let parsed = read_results(&tmp);
+        assert_eq!(parsed.keys, "www");
+        assert_eq!(parsed.cursor, Some([4, 12]));
+        let _ = fs::remove_file(&tmp);
+    }
+
+    #[test]
+    fn test_fnameescape_pathological_paths() {
+        for (path, escaped) in [
+            ("/tmp/a b/c", r"/tmp/a\ b/c"),
+            ("/tmp/it's \"q\"", r#"/tmp/it\'s\ \"q\""#),
+            ("/tmp/x|y!z", r"/tmp/x\|y\!z"),
+            (r"/tmp/back\slash", r"/tmp/back\\slash"),
+            ("/tmp/$HOME/[ab]*?{c}`", r"/tmp/\$HOME/\[ab]\*\?\{c}\`"),
+            ("/tmp/%#<", r"/tmp/\%\#\<"),
+            ("/tmp/tab\there", "/tmp/tab\\\there"),
+            ("/tmp/ünïcödé", "/tmp/ünïcödé"),
+        ] {
+            assert_eq!(fnameescape_for(Platform::Unix, path), escaped, "{path}");
+        }
+        assert_eq!(
+            fnameescape_for(Platform::Windows, r"C:\Temp dir\50%\x"),
+            r"C:/Temp\ dir/50\%/x"
+        );
+    }
+
+    #[test]
+    fn test_lua_script_escapes_paths() {
+        let challenge: Challenge = toml::from_str(
+            r#"
+id = "x_001"
+version = "1.0.0"
+title = "T"
+topic = "x"
+difficulty = 1
+hint = "h"
+par_keystrokes = 1
+[start]
+content = "a"
+[target]
+content = "b"
+"#,
+        )
+        .unwrap();
+        let files = SessionFiles::in_dir(Path::new(r"/tmp/it's a \ dir"));
+        let script = build_lua_script(
+            &challenge,
+            1,
+            false,
+            &files,
+            &SessionOptions::default(),
+            false,
+        );
+        assert!(script.contains(r"_VK_RESULTS_PATH = '/tmp/it\'s a \\ dir/results'"));
+        assert!(script.contains(r"_VK_TARGET_PATH = '/tmp/it\'s a \\ dir/challenge_target'"));
+    }
+
+    #[test]
+    fn test_snapshot_lua_feeds_growing_runs_of_keys() {
+        let challenge: Challenge = toml::from_str(
+            "id = \"x_001\"\nversion = \"1\"\ntitle = \"T\"\ntopic = \"x\"\n\
+             difficulty = 1\npar_keystrokes = 1\n[start]\ncontent = \"a\"\ncursor = [1, 3]\n\
+             [target]\ncontent = \"b\"\n",
+        )
+        .unwrap();
+        let keys = ["d".to_string(), "w".to_string(), "'".to_string()];
+        let script = snapshot_lua(&challenge, &keys, Path::new("/tmp/it's/frames"));
+        assert!(script.contains("local keys = { 'd', 'w', '\\'' }"));
+        assert!(script.contains("for i = 0, #keys do"));
+        assert!(script.contains("vim.api.nvim_win_set_cursor(0, { 1, 2 })"));
+        assert!(script.contains(r"'/tmp/it\'s/frames', 'a')"));
     }
 }
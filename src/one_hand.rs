@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+
+use crate::heatmap::KeyboardLayout;
+use crate::notation::{self, Key};
+
+/// Keys beside the rows of each hand: Escape and Tab on the left, Enter and
+/// Backspace on the right, and the space bar under either thumb.
+const LEFT_EXTRAS: [&str; 3] = ["<Esc>", "<Tab>", "<Space>"];
+const RIGHT_EXTRAS: [&str; 3] = ["<Enter>", "<BS>", "<Space>"];
+
+/// The keys of a one-hand set from the config: `left` or `right` for the keys
+/// of that hand on `layout`, or else the keys themselves in key notation, like
+/// `qwertasdfg<Esc>`.
+pub fn keys(set: &str, layout: KeyboardLayout) -> Vec<Key> {
+    let (left, extras) = match set {
+        "left" => (true, LEFT_EXTRAS),
+        "right" => (false, RIGHT_EXTRAS),
+        keys => return notation::tokenize(keys),
+    };
+    let mut keys = layout.hand_keys(left);
+    keys.extend(extras.iter().filter_map(|name| Key::parse(name)));
+    keys
+}
+
+static ACTIVE: OnceLock<Option<Vec<Key>>> = OnceLock::new();
+
+/// Play the one-hand variants with `keys` for the rest of the session, or
+/// none of them for `None`. Only the first call has an effect; returns false
+/// when it selected other keys.
+pub fn set(keys: Option<Vec<Key>>) -> bool {
+    match ACTIVE.set(keys) {
+        Ok(()) => true,
+        Err(keys) => ACTIVE.get() == Some(&keys),
+    }
+}
+
+/// The keys of the one-hand variants, `None` unless they are played.
+pub fn active() -> Option<&'static [Key]> {
+    ACTIVE.get().and_then(Option::as_deref)
+}
+
+/// The first key of a key log outside `allowed`, in canonical notation.
+pub fn stray_key(allowed: &[Key], keys: &str) -> Option<String> {
+    notation::tokenize(keys)
+        .into_iter()
+        .find(|key| !allowed.contains(key))
+        .map(|key| key.to_string())
+}
@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use serde::Deserialize;
+
+use crate::challenge::Challenge;
+
+/// A virtual opponent with a fixed score relative to par, such as a rookie at
+/// 2.5x par: a goal between the player's current grade and par.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Opponent {
+    pub name: String,
+    /// Keystrokes in tenths of par, like the grading curve: 25 for 2.5x par.
+    pub par_tenths: u32,
+    /// Category names such as "beginner" the opponent plays, ignoring case.
+    /// All graded categories when empty.
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+impl Opponent {
+    /// Whether the opponent plays `challenge`. Freestyle challenges have no
+    /// par to play against.
+    pub fn plays(&self, challenge: &Challenge) -> bool {
+        !challenge.is_freestyle()
+            && (self.categories.is_empty()
+                || challenge.category.is_some_and(|category| {
+                    self.categories
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(category.name()))
+                }))
+    }
+
+    /// The opponent's score on `challenge`, at least one keystroke.
+    pub fn keystrokes(&self, challenge: &Challenge) -> u32 {
+        (challenge.par() * self.par_tenths / 10).max(1)
+    }
+}
+
+static ACTIVE: OnceLock<Vec<Opponent>> = OnceLock::new();
+
+/// Select the opponents for the rest of the session. Only the first call has an effect.
+pub fn set(opponents: Vec<Opponent>) {
+    let _ = ACTIVE.set(opponents);
+}
+
+/// The opponents playing `challenge` with their scores, highest first.
+pub fn against(challenge: &Challenge) -> Vec<(&'static Opponent, u32)> {
+    let mut scores: Vec<(&Opponent, u32)> = ACTIVE
+        .get()
+        .map_or(&[][..], Vec::as_slice)
+        .iter()
+        .filter(|o| o.plays(challenge))
+        .map(|o| (o, o.keystrokes(challenge)))
+        .collect();
+    scores.sort_by_key(|&(_, keystrokes)| std::cmp::Reverse(keystrokes));
+    scores
+}
+
+/// A row per opponent of `challenge`: its score, and whether `keystrokes`
+/// beat it or how many fewer it takes. Empty without opponents.
+pub fn lines(challenge: &Challenge, keystrokes: Option<u32>) -> Vec<Line<'static>> {
+    let dim = Style::new().fg(Color::Gray);
+    against(challenge)
+        .into_iter()
+        .map(|(opponent, score)| {
+            let mut spans = vec![
+                Span::styled(" vs ", dim),
+                Span::raw(format!("{:<10} {score:>4} keys", opponent.name)),
+            ];
+            match keystrokes {
+                Some(k) if k <= score => {
+                    spans.push(Span::styled("  beaten", Style::new().fg(Color::Green)));
+                }
+                Some(k) => spans.push(Span::styled(
+                    format!("  {} to go", k - score),
+                    Style::new().fg(Color::Yellow),
+                )),
+                None => {}
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
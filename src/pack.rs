@@ -0,0 +1,251 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::challenge::Topic;
+use crate::curriculum;
+use crate::dirs::{self, SessionDir};
+use crate::registry;
+
+/// Manifest file at the root of a pack.
+pub const MANIFEST: &str = "pack.toml";
+
+/// Topic ID of the first installed pack; the others follow in name order.
+/// IDs from 150 to 199 are reserved for packs.
+pub const FIRST_PACK_ID: u8 = 150;
+
+/// Topic ID of the last pack that can be shown.
+pub const LAST_PACK_ID: u8 = 199;
+
+/// `pack.toml`: what a pack is called, who made it and which version it is.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Manifest {
+    /// Name the pack is installed and removed by: letters, digits, `-` and `_`.
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    /// Shown in the hub; "by {author}" when unset.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// An installed pack: a directory of challenge files with a manifest. It is
+/// shown as one extra topic in the hub.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pack {
+    pub manifest: Manifest,
+    pub dir: PathBuf,
+}
+
+impl Pack {
+    /// Topic description in the hub.
+    pub fn description(&self) -> String {
+        self.manifest
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("by {}", self.manifest.author))
+    }
+}
+
+/// Directory packs are installed to, `packs` in the data directory.
+pub fn packs_dir() -> PathBuf {
+    dirs::data_dir().join("packs")
+}
+
+/// Pack names are directory names, so they are kept to letters, digits, `-` and `_`.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Read and check the manifest of the pack in `dir`.
+pub fn read_manifest(dir: &Path) -> io::Result<Manifest> {
+    let path = dir.join(MANIFEST);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to read {}: {e}", path.display())))?;
+    let manifest: Manifest = toml::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("failed to parse {}: {e}", path.display()),
+        )
+    })?;
+    if !is_valid_name(&manifest.name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid pack name '{}' in {} (use letters, digits, '-' and '_')",
+                manifest.name,
+                path.display()
+            ),
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Load the curriculum in `challenges_dir` followed by the installed packs,
+/// through the challenge cache.
+pub fn load_with_packs(challenges_dir: &Path) -> (Vec<Topic>, curriculum::LoadStats) {
+    curriculum::load_curriculum_with(
+        challenges_dir,
+        &installed(&packs_dir()),
+        Some(&curriculum::cache_path()),
+    )
+}
+
+/// The packs installed in `dir`, by name. Directories without a usable
+/// manifest are reported and skipped; a missing directory has no packs.
+pub fn installed(dir: &Path) -> Vec<Pack> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut packs: Vec<Pack> = entries
+        .flatten()
+        .map(|e| e.path())
+        // Hidden directories are installs in progress
+        .filter(|p| p.is_dir() && !is_hidden(p))
+        .filter_map(|dir| match read_manifest(&dir) {
+            Ok(manifest) => Some(Pack { manifest, dir }),
+            Err(e) => {
+                eprintln!("Warning: ignoring pack in {}: {e}", dir.display());
+                None
+            }
+        })
+        .collect();
+    packs.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    packs
+}
+
+/// Install the pack archive at `source`, a file or an `http(s)://` URL, into
+/// `dir`, replacing an installed pack of the same name. An archive is a tar
+/// file, compressed or not, or a zip file, with `pack.toml` and the challenge
+/// files at its root or in a single top-level directory. Unpacking is left to
+/// `tar` and `unzip`.
+pub fn install(source: &str, dir: &Path) -> io::Result<Pack> {
+    install_with(source, dir, |_| Ok(()))
+}
+
+/// [`install`], but only once `check` accepts the archive's manifest; an
+/// installed pack is left in place when it doesn't.
+pub fn install_with(
+    source: &str,
+    dir: &Path,
+    check: impl FnOnce(&Manifest) -> io::Result<()>,
+) -> io::Result<Pack> {
+    fs::create_dir_all(dir)?;
+    // Unpacked next to the installed packs, so the pack is moved in by a rename
+    let work = SessionDir::create_in(dir, ".install")?;
+    let archive = if is_url(source) {
+        let path = work.path().join(registry::archive_name(source));
+        registry::download(source, &path)?;
+        path
+    } else {
+        let path = PathBuf::from(source);
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{source} is not a file"),
+            ));
+        }
+        path
+    };
+
+    let unpacked = work.path().join("unpacked");
+    fs::create_dir(&unpacked)?;
+    if archive
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    {
+        run(Command::new("unzip")
+            .arg("-q")
+            .arg(&archive)
+            .arg("-d")
+            .arg(&unpacked))?;
+    } else {
+        run(Command::new("tar")
+            .arg("-xf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&unpacked))?;
+    }
+
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let root =
+        pack_root(&unpacked).ok_or_else(|| invalid(format!("{source} has no {MANIFEST}")))?;
+    let manifest = read_manifest(&root)?;
+    if curriculum::challenge_files(&root).is_empty() {
+        return Err(invalid(format!("{source} has no challenge files")));
+    }
+    check(&manifest)?;
+    let dest = dir.join(&manifest.name);
+    if dest.exists() {
+        fs::remove_dir_all(&dest)?;
+    }
+    fs::rename(&root, &dest)?;
+    Ok(Pack {
+        manifest,
+        dir: dest,
+    })
+}
+
+/// Remove the installed pack `name` from `dir`. Returns the removed pack.
+pub fn remove(name: &str, dir: &Path) -> io::Result<Pack> {
+    let pack = installed(dir)
+        .into_iter()
+        .find(|p| p.manifest.name == name)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no pack named '{name}' is installed"),
+            )
+        })?;
+    fs::remove_dir_all(&pack.dir)?;
+    Ok(pack)
+}
+
+/// Whether an install source is a URL rather than a file or a pack name.
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Directory of an unpacked archive holding the manifest: the archive root,
+/// or its only directory.
+fn pack_root(unpacked: &Path) -> Option<PathBuf> {
+    if unpacked.join(MANIFEST).is_file() {
+        return Some(unpacked.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = fs::read_dir(unpacked)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    match entries.as_slice() {
+        [only] if only.join(MANIFEST).is_file() => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// Run an unpack tool, failing with its error output.
+fn run(command: &mut Command) -> io::Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to run {program}: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(io::Error::other(format!(
+        "{program} failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
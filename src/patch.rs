@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use crate::generate::{Buffer, Generated};
+use crate::mutate;
+
+/// One hunk of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Path of the file after the change, without the `b/` of git diffs.
+    pub file: String,
+    /// First line of the hunk in the old file, counting from 1.
+    pub line: usize,
+    /// What follows the second `@@` of the header, usually the enclosing
+    /// function.
+    pub section: String,
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+}
+
+/// The hunks of a unified diff, as written by `diff -u` and `git diff`.
+/// Anything outside the hunks, like commit messages, is skipped.
+pub fn parse(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks = Vec::new();
+    let mut old_file = None;
+    let mut new_file = None;
+    let mut lines = patch.lines().enumerate();
+    while let Some((n, line)) = lines.next() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_file = Some(file_name(path, "a/"));
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            new_file = Some(file_name(path, "b/"));
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            let error = |message: &str| format!("line {}: {message}", n + 1);
+            let (line, mut old, mut new, section) =
+                parse_header(header).ok_or_else(|| error("malformed hunk header"))?;
+            // Deleted files are only named by their old path
+            let file = new_file
+                .iter()
+                .chain(&old_file)
+                .find(|f| *f != "/dev/null")
+                .cloned()
+                .ok_or_else(|| error("hunk before a file header"))?;
+            let mut hunk = Hunk {
+                file,
+                line,
+                section,
+                pre: Vec::new(),
+                post: Vec::new(),
+            };
+            while old + new > 0 {
+                let (_, body) = lines
+                    .next()
+                    .ok_or_else(|| error("patch ends in the hunk"))?;
+                // Some tools strip the space of blank context lines
+                let (kind, text) = body.split_at(body.len().min(1));
+                match kind {
+                    " " | "" if old > 0 && new > 0 => {
+                        hunk.pre.push(text.to_string());
+                        hunk.post.push(text.to_string());
+                        old -= 1;
+                        new -= 1;
+                    }
+                    "-" if old > 0 => {
+                        hunk.pre.push(text.to_string());
+                        old -= 1;
+                    }
+                    "+" if new > 0 => {
+                        hunk.post.push(text.to_string());
+                        new -= 1;
+                    }
+                    "\\" => {}
+                    _ => return Err(error("hunk is shorter than its header says")),
+                }
+            }
+            hunks.push(hunk);
+        }
+    }
+    Ok(hunks)
+}
+
+/// The path of a `---` or `+++` line, without `prefix` and the timestamp
+/// `diff -u` adds.
+fn file_name(path: &str, prefix: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// Old start line, old and new line counts and section of a hunk header
+/// after its `@@ `, e.g. `-12,7 +12,8 @@ fn main() {`.
+fn parse_header(header: &str) -> Option<(usize, usize, usize, String)> {
+    let (ranges, section) = header.split_once("@@")?;
+    let mut ranges = ranges.split_whitespace();
+    let (line, old) = parse_range(ranges.next()?.strip_prefix('-')?)?;
+    let (_, new) = parse_range(ranges.next()?.strip_prefix('+')?)?;
+    Some((line, old, new, section.trim().to_string()))
+}
+
+/// Start and count of `12,7`, where a missing count is 1.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Freestyle challenges turning the pre-image of each hunk into its
+/// post-image, or with `whole` one challenge for every hunk of the patch,
+/// separated by blank lines. IDs are made of the file stem of `patch`.
+pub fn challenges(patch: &Path, hunks: &[Hunk], whole: bool) -> Vec<Generated> {
+    let name = patch
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let command = format!("nvimkata import-diff {}", patch.display());
+    if whole {
+        let join = |side: fn(&Hunk) -> &Vec<String>| {
+            hunks
+                .iter()
+                .map(|h| side(h).join("\n"))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+        let files: Vec<&str> = hunks.iter().fold(Vec::new(), |mut files, h| {
+            if !files.contains(&h.file.as_str()) {
+                files.push(&h.file);
+            }
+            files
+        });
+        let filetype = match files.as_slice() {
+            [file] => filetype(file),
+            _ => None,
+        };
+        return vec![challenge(
+            &format!("{command} --whole"),
+            format!("diff_{name}"),
+            format!("Patch {name}"),
+            format!(
+                "Apply the {} hunk(s) of {name} to {}.",
+                hunks.len(),
+                files.join(", ")
+            ),
+            filetype,
+            join(|h| &h.pre),
+            join(|h| &h.post),
+        )];
+    }
+    hunks
+        .iter()
+        .enumerate()
+        .map(|(i, hunk)| {
+            let title = if hunk.section.is_empty() {
+                format!("{} line {}", hunk.file, hunk.line)
+            } else {
+                format!("{}: {}", hunk.file, hunk.section)
+            };
+            challenge(
+                &command,
+                format!("diff_{name}_{}", i + 1),
+                title,
+                format!(
+                    "Apply hunk {} of {name}, at line {} of {}.",
+                    i + 1,
+                    hunk.line,
+                    hunk.file
+                ),
+                filetype(&hunk.file),
+                hunk.pre.join("\n"),
+                hunk.post.join("\n"),
+            )
+        })
+        .collect()
+}
+
+fn filetype(file: &str) -> Option<&'static str> {
+    Path::new(file)
+        .extension()
+        .and_then(|ext| mutate::filetype(&ext.to_string_lossy()))
+}
+
+fn challenge(
+    command: &str,
+    id: String,
+    title: String,
+    description: String,
+    filetype: Option<&str>,
+    start: String,
+    target: String,
+) -> Generated {
+    Generated {
+        id,
+        version: "1.0.0".to_string(),
+        title,
+        topic: "imported".to_string(),
+        difficulty: 2,
+        description,
+        hint: "Work down from the first difference; . and n repeat a change".to_string(),
+        filetype: filetype.map(str::to_string),
+        perfect_moves: Vec::new(),
+        par_keystrokes: None,
+        start: Buffer { content: start },
+        target: Buffer { content: target },
+        command: command.to_string(),
+    }
+}
@@ -0,0 +1,186 @@
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+
+use crate::challenge::{Challenge, Grade, grade_display};
+use crate::game;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::nvim;
+use crate::screen;
+use crate::state::GameState;
+use crate::theme;
+
+/// Number of challenges in a weak-spot drill.
+pub const DRILL_SIZE: usize = 5;
+
+/// Best result of a challenge: the grade, and the keystrokes it took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Score {
+    grade: Option<Grade>,
+    keystrokes: Option<u32>,
+}
+
+impl Score {
+    fn before(challenge: &Challenge, state: &GameState) -> Self {
+        Self {
+            grade: state.best_grade(&challenge.id),
+            keystrokes: state.best_keystrokes(&challenge.id),
+        }
+    }
+
+    fn after(challenge: &Challenge, keystrokes: Option<u32>) -> Self {
+        Self {
+            grade: keystrokes.map(|k| challenge.score(k)),
+            keystrokes,
+        }
+    }
+
+    /// Badge text and style; freestyle challenges show keystrokes instead of a grade.
+    fn badge(self, freestyle: bool) -> (String, Style) {
+        if freestyle {
+            return self.keystrokes.map_or_else(
+                || ("[-]".to_string(), Style::new().fg(Color::Gray)),
+                |k| (format!("[{k}]"), Style::new().fg(Color::Cyan)),
+            );
+        }
+        let (label, style) = grade_display(self.grade);
+        (format!("[{label}]"), style)
+    }
+
+    fn improves_on(self, before: Self, freestyle: bool) -> bool {
+        if freestyle {
+            return self
+                .keystrokes
+                .is_some_and(|k| before.keystrokes.is_none_or(|b| k < b));
+        }
+        self.grade
+            .is_some_and(|g| before.grade.is_none_or(|b| g.rank() < b.rank()))
+    }
+}
+
+/// Play `challenges` one after another. Between challenges a summary lists each
+/// one's best result before the playlist next to the best of this run; it stays
+/// up once the last challenge is done. Stopping early keeps what was played.
+pub fn run_playlist(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    state: &mut GameState,
+    title: &str,
+    challenges: &[&Challenge],
+) -> std::io::Result<()> {
+    if challenges.is_empty() {
+        return Ok(());
+    }
+
+    let before: Vec<Score> = challenges.iter().map(|c| Score::before(c, state)).collect();
+    let mut after: Vec<Option<Score>> = vec![None; challenges.len()];
+    let mut next = 0;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            render_playlist(frame, keymap, title, challenges, &before, &after, next);
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Select) if next < challenges.len() => {
+                    let challenge = challenges[next];
+                    let best = game::play_challenge_loop(
+                        terminal,
+                        launcher,
+                        keymap,
+                        state,
+                        challenge,
+                        next + 1,
+                    )?;
+                    after[next] = Some(Score::after(challenge, best));
+                    next += 1;
+                }
+                Some(Action::Select | Action::Back | Action::Quit) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn render_playlist(
+    frame: &mut ratatui::Frame,
+    keymap: &Keymap,
+    title: &str,
+    challenges: &[&Challenge],
+    before: &[Score],
+    after: &[Option<Score>],
+    next: usize,
+) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+
+    let dim = Style::new().fg(Color::Gray);
+    let mut improved = 0;
+    let items: Vec<ListItem> = challenges
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let freestyle = c.is_freestyle();
+            let (was, was_style) = before[i].badge(freestyle);
+            let mut spans = vec![
+                Span::styled(format!("{:>2}. ", i + 1), dim),
+                Span::styled(was, was_style),
+            ];
+            if let Some(score) = after[i] {
+                let (now, now_style) = score.badge(freestyle);
+                spans.push(Span::styled(" -> ", dim));
+                spans.push(Span::styled(now, now_style));
+                if score.improves_on(before[i], freestyle) {
+                    improved += 1;
+                    spans.push(Span::styled(" +", Style::new().fg(Color::Green)));
+                }
+            }
+            spans.push(Span::raw(format!(" {}", c.title)));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let played = after.iter().flatten().count();
+    let block = Block::bordered()
+        .title(format!(" {title} "))
+        .title_bottom(format!(
+            " {played}/{} played, {improved} improved ",
+            challenges.len()
+        ));
+    let mut list_state = ListState::default();
+    list_state.select((next < challenges.len()).then_some(next));
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(block)
+            .highlight_style(
+                Style::new()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> "),
+        main,
+        &mut list_state,
+    );
+
+    let text = if next < challenges.len() {
+        format!(
+            " {}: play next | {}/{}: stop",
+            keymap.label(Action::Select),
+            keymap.label(Action::Back),
+            keymap.label(Action::Quit),
+        )
+    } else {
+        format!(" All done | {}: close", keymap.label(Action::Quit))
+    };
+    frame.render_widget(
+        Paragraph::new(text).style(Style::new().fg(Color::DarkGray)),
+        footer,
+    );
+}
@@ -0,0 +1,221 @@
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+
+use crate::challenge::Challenge;
+use crate::diff::{self, DiffOp};
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::screen::{self, NarrowLayout};
+use crate::theme;
+
+/// Numbered, dimmed lines of buffer content for previews.
+pub fn numbered_lines(content: &str) -> Vec<Line<'_>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Line::from(vec![
+                Span::styled(format!("{:>3} ", i + 1), Style::new().fg(Color::DarkGray)),
+                Span::styled(line, Style::new().fg(Color::Gray)),
+            ])
+        })
+        .collect()
+}
+
+/// New scroll offset after a scroll action, clamped to `0..=max`.
+pub fn scroll(offset: u16, action: Action, page: u16, max: u16) -> u16 {
+    let half = (page / 2).max(1);
+    let offset = match action {
+        Action::Down | Action::ScrollDown => offset.saturating_add(1),
+        Action::Up | Action::ScrollUp => offset.saturating_sub(1),
+        Action::HalfPageDown => offset.saturating_add(half),
+        Action::HalfPageUp => offset.saturating_sub(half),
+        Action::Top => 0,
+        Action::Bottom => max,
+        _ => offset,
+    };
+    offset.min(max)
+}
+
+/// Show the full target of a challenge. Returns on the preview key, back or quit.
+pub fn show_preview(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &Keymap,
+    challenge: &Challenge,
+) -> std::io::Result<()> {
+    let lines = numbered_lines(&challenge.target().content);
+    let max = u16::try_from(lines.len().saturating_sub(1)).unwrap_or(u16::MAX);
+    let mut offset: u16 = 0;
+    let mut page: u16 = 0;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            page = main.height.saturating_sub(2);
+            let title = Line::from(Span::styled(
+                format!(" Target: {} ({} lines) ", challenge.title, lines.len()),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            frame.render_widget(
+                Paragraph::new(lines.clone())
+                    .block(Block::bordered().title(title))
+                    .scroll((offset, 0)),
+                main,
+            );
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: scroll | {}/{}: half page | {}/{}: close",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::HalfPageDown),
+                    keymap.label(Action::HalfPageUp),
+                    keymap.label(Action::Preview),
+                    keymap.label(Action::Quit),
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Preview | Action::Back | Action::Quit) => return Ok(()),
+                Some(action) => offset = scroll(offset, action, page, max),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Styled line of one side of the comparison. Changed rows are tinted with
+/// `color`; when the other side has a counterpart, only the differing
+/// characters are highlighted.
+fn compare_line<'a>(
+    number: Option<usize>,
+    text: Option<&'a str>,
+    other: Option<&str>,
+    changed: bool,
+    color: Color,
+) -> Line<'a> {
+    let gutter = number.map_or_else(|| "    ".to_string(), |n| format!("{:>3} ", n + 1));
+    let mut spans = vec![Span::styled(gutter, Style::new().fg(Color::DarkGray))];
+    let Some(text) = text else {
+        return Line::from(spans);
+    };
+    if !changed {
+        spans.push(Span::styled(text, Style::new().fg(Color::Gray)));
+        return Line::from(spans);
+    }
+    let Some(other) = other else {
+        spans.push(Span::styled(text, Style::new().fg(color)));
+        return Line::from(spans);
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+    let other_chars: Vec<char> = other.chars().collect();
+    let highlight = Style::new().fg(Color::Black).bg(color);
+    let plain = Style::new().fg(color);
+    let mut run = String::new();
+    let mut run_highlighted = false;
+    let ops = diff::diff(&chars, &other_chars);
+    for op in ops {
+        let (c, highlighted) = match op {
+            DiffOp::Equal(i, _) => (chars[i], false),
+            DiffOp::Delete(i) => (chars[i], true),
+            DiffOp::Insert(_) => continue,
+        };
+        if highlighted != run_highlighted && !run.is_empty() {
+            let style = if run_highlighted { highlight } else { plain };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_highlighted = highlighted;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(
+            run,
+            if run_highlighted { highlight } else { plain },
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Show the start and target content side by side with changes highlighted:
+/// red for what must go, green for what must appear. Returns on the compare
+/// key, back or quit.
+pub fn show_comparison(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &Keymap,
+    challenge: &Challenge,
+) -> std::io::Result<()> {
+    let start: Vec<&str> = challenge.start().content.lines().collect();
+    let target: Vec<&str> = challenge.target().content.lines().collect();
+    let rows = diff::side_by_side(&start, &target);
+    let (left, right): (Vec<Line>, Vec<Line>) = rows
+        .iter()
+        .map(|row| {
+            let a = row.left.map(|i| start[i]);
+            let b = row.right.map(|j| target[j]);
+            (
+                compare_line(row.left, a, b, row.changed, Color::Red),
+                compare_line(row.right, b, a, row.changed, Color::Green),
+            )
+        })
+        .unzip();
+    let changed = rows.iter().filter(|r| r.changed).count();
+
+    let max = u16::try_from(rows.len().saturating_sub(1)).unwrap_or(u16::MAX);
+    let mut offset: u16 = 0;
+    let mut page: u16 = 0;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            let [left_area, right_area] = screen::split_panels(main, NarrowLayout::Stacked);
+            page = left_area.height.saturating_sub(2);
+            frame.render_widget(
+                Paragraph::new(left.clone())
+                    .block(Block::bordered().title(format!(" Start: {} ", challenge.title)))
+                    .scroll((offset, 0)),
+                left_area,
+            );
+            frame.render_widget(
+                Paragraph::new(right.clone())
+                    .block(Block::bordered().title(format!(" Target ({changed} changed lines) ")))
+                    .scroll((offset, 0)),
+                right_area,
+            );
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: scroll | {}/{}: half page | {}/{}: close",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::HalfPageDown),
+                    keymap.label(Action::HalfPageUp),
+                    keymap.label(Action::Compare),
+                    keymap.label(Action::Quit),
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Compare | Action::Back | Action::Quit) => return Ok(()),
+                Some(action) => offset = scroll(offset, action, page, max),
+                None => {}
+            }
+        }
+    }
+}
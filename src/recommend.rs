@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::challenge::{Challenge, Grade};
+use crate::state::{GameState, TagStats};
+
+/// Assumed weakness for tags (or untagged challenges) without any results yet:
+/// the midpoint between Grade A (0) and Grade F (5).
+const UNKNOWN_WEAKNESS: f64 = 2.5;
+
+/// Pick up to `count` challenges worth practicing next.
+///
+/// Challenges are ranked by the worst average grade among their `focused_actions`
+/// tags, boosted when their own score (or their tags) are stale or decayed.
+/// Challenges far above the player's current difficulty level are pushed down,
+/// and challenges already solved with a fresh Grade A are never suggested. Freestyle challenges
+/// are skipped since they carry no grade.
+pub fn recommend<'a>(
+    challenges: &[&'a Challenge],
+    state: &GameState,
+    count: usize,
+) -> Vec<&'a Challenge> {
+    let tags = state.tag_stats(challenges.iter().copied());
+    let level = player_level(challenges, state);
+
+    let mut scored: Vec<(f64, &Challenge)> = challenges
+        .iter()
+        .copied()
+        .filter(|c| !c.is_freestyle())
+        .filter(|c| {
+            state.is_stale(&c.id)
+                || state.is_decayed(&c.id)
+                || state.best_grade(&c.id) != Some(Grade::A)
+        })
+        .map(|c| (priority(c, state, &tags, level), c))
+        .collect();
+
+    scored.sort_by(|(pa, a), (pb, b)| {
+        pb.total_cmp(pa)
+            .then(a.difficulty.cmp(&b.difficulty))
+            .then(a.id.cmp(&b.id))
+    });
+    scored.into_iter().take(count).map(|(_, c)| c).collect()
+}
+
+/// Highest difficulty the player has cleared with Grade C or better (at least 1).
+fn player_level(challenges: &[&Challenge], state: &GameState) -> u8 {
+    challenges
+        .iter()
+        .filter(|c| {
+            state
+                .best_grade(&c.id)
+                .is_some_and(|g| g.rank() <= Grade::C.rank())
+        })
+        .map(|c| c.difficulty)
+        .max()
+        .unwrap_or(1)
+        .max(1)
+}
+
+fn priority(
+    challenge: &Challenge,
+    state: &GameState,
+    tags: &HashMap<String, TagStats>,
+    level: u8,
+) -> f64 {
+    let tag_stats: Vec<&TagStats> = challenge
+        .focused_actions
+        .iter()
+        .flatten()
+        .filter_map(|t| tags.get(t))
+        .collect();
+
+    let weakness = tag_stats
+        .iter()
+        .map(|t| t.average_rank().unwrap_or(UNKNOWN_WEAKNESS))
+        .reduce(f64::max)
+        .unwrap_or(UNKNOWN_WEAKNESS);
+    let tag_staleness = tag_stats
+        .iter()
+        .map(|t| t.stale_ratio())
+        .fold(0.0, f64::max);
+
+    let own = match state.best_grade(&challenge.id) {
+        _ if state.is_stale(&challenge.id) => 3.0,
+        _ if state.is_decayed(&challenge.id) => 2.0,
+        Some(grade) => f64::from(grade.rank()) * 0.5,
+        None => 1.0,
+    };
+
+    let stretch = challenge.difficulty.saturating_sub(level.saturating_add(1));
+
+    weakness + own + tag_staleness * 2.0 - f64::from(stretch) * 2.0
+}
+
+/// Pick up to `count` attempted challenges with the worst results for their
+/// difficulty, for a drill.
+///
+/// Challenges are ranked by their best grade's rank divided by difficulty, so a
+/// poor grade on an easy challenge comes first; ties go to the one furthest over
+/// par. Unattempted, freestyle and Grade A challenges are skipped.
+pub fn weakest<'a>(
+    challenges: &[&'a Challenge],
+    state: &GameState,
+    count: usize,
+) -> Vec<&'a Challenge> {
+    let mut scored: Vec<(f64, i64, &Challenge)> = challenges
+        .iter()
+        .copied()
+        .filter(|c| !c.is_freestyle())
+        .filter_map(|c| {
+            let grade = state.best_grade(&c.id).filter(|&g| g != Grade::A)?;
+            let ratio = f64::from(grade.rank()) / f64::from(c.difficulty.max(1));
+            let over_par =
+                i64::from(state.best_keystrokes(&c.id).unwrap_or(0)) - i64::from(c.par());
+            Some((ratio, over_par, c))
+        })
+        .collect();
+
+    scored.sort_by(|(ra, oa, a), (rb, ob, b)| {
+        rb.total_cmp(ra).then(ob.cmp(oa)).then(a.id.cmp(&b.id))
+    });
+    scored.into_iter().take(count).map(|(_, _, c)| c).collect()
+}
@@ -0,0 +1,357 @@
+use std::fs;
+use std::path::Path;
+
+use ratatui::crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use serde::Deserialize;
+
+use crate::challenge::{Challenge, Topic};
+use crate::collection;
+use crate::commands::{self, Command};
+use crate::game;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::notation;
+use crate::nvim;
+use crate::screen;
+use crate::state::{CommandMastery, GameState};
+use crate::theme;
+
+/// File name of the command reference inside the challenges directory.
+pub const REFERENCE_FILE: &str = "reference.toml";
+
+/// Operators, longest first, that prefix the name of an operator command.
+const OPERATORS: [&str; 13] = [
+    "gu", "gU", "g~", "gq", "gw", "g?", "d", "c", "y", "<", ">", "=", "!",
+];
+
+/// A command in the reference, such as `ciw` under "Text objects".
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReferenceCommand {
+    pub keys: String,
+    pub category: String,
+    pub description: String,
+    /// Command names that count as using it, as in [`Command::name`]: `:s` for
+    /// `:%s/a/b/`, `f` for `fx`. The keys themselves when empty.
+    #[serde(default)]
+    pub uses: Vec<String>,
+}
+
+impl ReferenceCommand {
+    /// Whether `command` uses this one: its name or register does.
+    pub fn used_by(&self, command: &Command) -> bool {
+        self.has_name(&command.name) || command.register().is_some_and(|r| self.has_name(&r))
+    }
+
+    /// Whether a command name or register (`"0`) is this command: it is one of
+    /// its names, or an operator command whose operator or motion is, as `diw`
+    /// is both `d` and `iw`.
+    pub fn has_name(&self, name: &str) -> bool {
+        let names = if self.uses.is_empty() {
+            std::slice::from_ref(&self.keys)
+        } else {
+            &self.uses
+        };
+        let parts = operator_parts(name);
+        names
+            .iter()
+            .any(|n| n == name || parts.is_some_and(|(op, motion)| op == n || motion == n))
+    }
+
+    /// Use of this command in solved runs, over all its names.
+    pub fn mastery(&self, state: &GameState) -> CommandMastery {
+        let mut total = CommandMastery::default();
+        for (_, mastery) in state
+            .command_mastery
+            .iter()
+            .filter(|(name, _)| self.has_name(name))
+        {
+            total.uses += mastery.uses;
+            total.challenges.extend(mastery.challenges.iter().cloned());
+        }
+        total
+    }
+
+    /// Whether the keys, description or category contain `query`, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.keys, &self.description, &self.category]
+            .iter()
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+/// Split the name of an operator command into the operator and its motion or
+/// text object. Special keys such as `<C-a>` are no operator commands.
+fn operator_parts(name: &str) -> Option<(&str, &str)> {
+    if name.len() > 2 && name.starts_with('<') && name.ends_with('>') {
+        return None;
+    }
+    OPERATORS
+        .iter()
+        .find_map(|op| name.strip_prefix(op).map(|motion| (*op, motion)))
+}
+
+/// Commands listed on the reference screen, grouped by category in file order.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Reference {
+    #[serde(default)]
+    pub commands: Vec<ReferenceCommand>,
+}
+
+impl Reference {
+    /// For each command, the challenges whose perfect moves use it, in
+    /// curriculum order.
+    pub fn challenges<'a>(&self, topics: &'a [Topic]) -> Vec<Vec<&'a Challenge>> {
+        let solutions: Vec<(&Challenge, Vec<Command>)> = topics
+            .iter()
+            .flat_map(|t| &t.challenges)
+            .filter_map(|c| {
+                let moves = c.perfect_moves.as_ref()?.concat();
+                Some((c, commands::classify(&notation::tokenize(&moves))))
+            })
+            .collect();
+        self.commands
+            .iter()
+            .map(|entry| {
+                solutions
+                    .iter()
+                    .filter(|(_, commands)| commands.iter().any(|c| entry.used_by(c)))
+                    .map(|(challenge, _)| *challenge)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Indices of the commands never used in a solved run, among those some
+    /// challenge uses: practice suggestions. `challenges` is from
+    /// [`Self::challenges`].
+    pub fn never_used(&self, state: &GameState, challenges: &[Vec<&Challenge>]) -> Vec<usize> {
+        (0..self.commands.len())
+            .filter(|&i| !challenges[i].is_empty() && self.commands[i].mastery(state).uses == 0)
+            .collect()
+    }
+}
+
+/// Load the command reference from `reference.toml` in the challenges
+/// directory. A missing or unreadable reference yields an empty one.
+pub fn load_reference(challenges_dir: &Path) -> Reference {
+    let path = challenges_dir.join(REFERENCE_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Reference::default();
+    };
+    match toml::from_str(&content) {
+        Ok(reference) => reference,
+        Err(e) => {
+            eprintln!("Warning: failed to parse {}: {}", path.display(), e);
+            Reference::default()
+        }
+    }
+}
+
+/// What the reference screen lists.
+#[derive(Default)]
+struct Filter {
+    query: String,
+    /// Typing the query.
+    searching: bool,
+    /// Only the never used commands, as practice suggestions.
+    unused: bool,
+}
+
+impl Filter {
+    fn title(&self) -> String {
+        let query = if self.searching || !self.query.is_empty() {
+            format!(": /{}", self.query)
+        } else {
+            String::new()
+        };
+        if self.unused {
+            format!(" Never used: practice suggestions{query} ")
+        } else {
+            format!(" Command reference{query} ")
+        }
+    }
+
+    fn footer(&self, keymap: &Keymap) -> String {
+        if self.searching {
+            return " type to search | Enter: done | Esc: clear".to_string();
+        }
+        format!(
+            " {}/{}: navigate | {}: challenges using it | {}: search | {}: {} | {}/{}: back",
+            keymap.label(Action::Down),
+            keymap.label(Action::Up),
+            keymap.label(Action::Select),
+            keymap.label(Action::Search),
+            keymap.label(Action::Sort),
+            if self.unused {
+                "all commands"
+            } else {
+                "never used"
+            },
+            keymap.label(Action::Back),
+            keymap.label(Action::Quit),
+        )
+    }
+
+    /// Edit the query while searching.
+    fn type_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Esc => {
+                self.query.clear();
+                self.searching = false;
+            }
+            KeyCode::Enter => self.searching = false,
+            _ => {}
+        }
+    }
+}
+
+/// Browse the command reference, filtered by a search query or to the
+/// commands never used. Selecting a command lists the challenges using it, to
+/// play them from there.
+pub fn run_reference(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    state: &mut GameState,
+    reference: &Reference,
+    topics: &[Topic],
+    unlock_all: bool,
+) -> std::io::Result<()> {
+    let challenges = reference.challenges(topics);
+    let mut filter = Filter::default();
+    let mut list_state = ListState::default();
+    let mut pending: Vec<KeyPress> = Vec::new();
+    let mut list_height: u16 = 0;
+    loop {
+        let candidates = if filter.unused {
+            reference.never_used(state, &challenges)
+        } else {
+            (0..reference.commands.len()).collect()
+        };
+        let shown: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&i| reference.commands[i].matches(&filter.query))
+            .collect();
+        let len = shown.len();
+        list_state.select((len > 0).then(|| list_state.selected().unwrap_or(0).min(len - 1)));
+
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            list_height = main.height.saturating_sub(2);
+            let items: Vec<ListItem> = shown
+                .iter()
+                .map(|&i| {
+                    let command = &reference.commands[i];
+                    ListItem::new(command_line(
+                        command,
+                        challenges[i].len(),
+                        &command.mastery(state),
+                    ))
+                })
+                .collect();
+            let list = List::new(items)
+                .block(Block::bordered().title(filter.title()))
+                .highlight_style(
+                    Style::new()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol(">> ");
+            frame.render_stateful_widget(list, main, &mut list_state);
+            frame.render_widget(
+                Paragraph::new(filter.footer(keymap)).style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        let Some(key) = screen::poll_key(terminal)? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if filter.searching {
+            filter.type_key(key.code);
+            continue;
+        }
+        let selected = list_state.selected().unwrap_or(0);
+        let half = usize::from((list_height / 2).max(1));
+        match keymap.resolve(&mut pending, key.into()) {
+            Some(Action::Back | Action::Quit) => return Ok(()),
+            Some(Action::Search) => filter.searching = true,
+            Some(Action::Sort) => filter.unused = !filter.unused,
+            Some(Action::Down) if len > 0 => list_state.select(Some((selected + 1) % len)),
+            Some(Action::Up) if len > 0 => list_state.select(Some((selected + len - 1) % len)),
+            Some(Action::Top) => list_state.select(Some(0)),
+            Some(Action::Bottom) => list_state.select(Some(len.saturating_sub(1))),
+            Some(Action::HalfPageDown) => {
+                list_state.select(Some((selected + half).min(len.saturating_sub(1))));
+            }
+            Some(Action::HalfPageUp) => list_state.select(Some(selected.saturating_sub(half))),
+            Some(Action::Select) => {
+                if let Some(&i) = shown.get(selected)
+                    && !challenges[i].is_empty()
+                {
+                    let topic = users(&reference.commands[i], &challenges[i]);
+                    game::run_challenge_picker(
+                        terminal, launcher, keymap, state, &topic, 0, unlock_all,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A reference entry as a list row: keys, description, category, the number
+/// of challenges using it and how much it was used.
+fn command_line(
+    command: &ReferenceCommand,
+    challenges: usize,
+    mastery: &CommandMastery,
+) -> Line<'static> {
+    let dim = Style::new().fg(Color::Gray);
+    Line::from(vec![
+        Span::styled(format!("{:<14}", command.category), dim),
+        Span::styled(
+            format!("{:<10}", command.keys),
+            Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(command.description.clone()),
+        Span::styled(
+            match challenges {
+                0 => String::new(),
+                1 => "  (1 challenge)".to_string(),
+                n => format!("  ({n} challenges)"),
+            },
+            dim,
+        ),
+        match (mastery.uses, mastery.challenges.len()) {
+            (0, _) => Span::styled("  never used", Style::new().fg(Color::Yellow)),
+            (uses, 1) => Span::styled(format!("  used {uses}x in 1 challenge"), dim),
+            (uses, n) => Span::styled(format!("  used {uses}x in {n} challenges"), dim),
+        },
+    ])
+}
+
+/// The challenges using a command as a collection to pick from.
+fn users(command: &ReferenceCommand, challenges: &[&Challenge]) -> Topic {
+    Topic {
+        // Listed as a collection, like the favorites
+        id: collection::FAVORITES_ID,
+        name: format!("Using {}", command.keys),
+        description: command.description.clone(),
+        intro: None,
+        challenges: challenges.iter().map(|c| (*c).clone()).collect(),
+    }
+}
@@ -0,0 +1,207 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::dirs::SessionDir;
+use crate::pack::{self, Pack};
+
+/// A pack as listed in a registry index.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Where the pack archive is downloaded from.
+    pub url: String,
+    /// SHA-256 of the archive in hex, checked before it is installed.
+    pub sha256: String,
+}
+
+/// A registry index: a static JSON file of the form `{"packs": [...]}`,
+/// fetched from `registry_url` in the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Index {
+    pub packs: Vec<IndexEntry>,
+}
+
+impl Index {
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Entries whose name, author or description contains `query`, ignoring
+    /// case. An empty query matches every entry.
+    pub fn search(&self, query: &str) -> Vec<&IndexEntry> {
+        let query = query.to_lowercase();
+        self.packs
+            .iter()
+            .filter(|entry| {
+                [
+                    Some(entry.name.as_str()),
+                    Some(entry.author.as_str()),
+                    entry.description.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|field| field.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+
+    /// The entry of pack `name`; the newest version if it is listed more than once.
+    pub fn find(&self, name: &str) -> Option<&IndexEntry> {
+        self.packs
+            .iter()
+            .filter(|entry| entry.name == name)
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+
+    /// Installed packs with a newer version in the index, with the entry of
+    /// that version.
+    pub fn updates<'a>(&'a self, installed: &'a [Pack]) -> Vec<(&'a Pack, &'a IndexEntry)> {
+        installed
+            .iter()
+            .filter_map(|pack| {
+                let entry = self.find(&pack.manifest.name)?;
+                let newer = compare_versions(&entry.version, &pack.manifest.version);
+                (newer == Ordering::Greater).then_some((pack, entry))
+            })
+            .collect()
+    }
+}
+
+/// Order two versions like `1.10.0` and `1.9`: by their dot-separated numbers,
+/// with missing parts counted as 0. Versions that aren't numeric are compared
+/// as text.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let numbers = |v: &str| -> Option<Vec<u64>> { v.split('.').map(|p| p.parse().ok()).collect() };
+    match (numbers(a), numbers(b)) {
+        (Some(mut a), Some(mut b)) => {
+            let len = a.len().max(b.len());
+            a.resize(len, 0);
+            b.resize(len, 0);
+            a.cmp(&b)
+        }
+        _ => a.cmp(b),
+    }
+}
+
+/// Fetch and parse the registry index at `url`.
+pub fn fetch_index(url: &str) -> io::Result<Index> {
+    let work = SessionDir::create("registry")?;
+    let path = work.path().join("index.json");
+    download(url, &path)?;
+    Index::parse(&fs::read_to_string(&path)?).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid registry index at {url}: {e}"),
+        )
+    })
+}
+
+/// Download the archive of `entry`, check it against its checksum and install
+/// it into `dir`. The archive must hold the pack and version the index lists.
+pub fn install(entry: &IndexEntry, dir: &Path) -> io::Result<Pack> {
+    let work = SessionDir::create("download")?;
+    let archive = work.path().join(archive_name(&entry.url));
+    download(&entry.url, &archive)?;
+    verify(&archive, &entry.sha256)?;
+    pack::install_with(&archive.to_string_lossy(), dir, |manifest| {
+        if manifest.name == entry.name && manifest.version == entry.version {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} holds pack {} {}, but the index lists {} {}",
+                entry.url, manifest.name, manifest.version, entry.name, entry.version
+            ),
+        ))
+    })
+}
+
+/// File name of the archive at `url`, for telling tar and zip files apart.
+/// Falls back to `pack` when the URL ends in nothing usable as a file name,
+/// such as `..` or a Windows path.
+pub fn archive_name(url: &str) -> &str {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|url| url.rsplit('/').next())
+        .filter(|name| !matches!(*name, "" | "." | "..") && !name.contains('\\'))
+        .unwrap_or("pack")
+}
+
+/// Check that the file at `path` has the SHA-256 checksum `expected`.
+pub fn verify(path: &Path, expected: &str) -> io::Result<()> {
+    let actual = sha256_file(path)?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "checksum mismatch for {}: expected {expected}, got {actual}",
+            path.display()
+        ),
+    ))
+}
+
+/// SHA-256 of a file in hex, computed by `sha256sum` or, where there is none
+/// like on macOS, `shasum -a 256`.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let output = Command::new("sha256sum")
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .or_else(|_| {
+            Command::new("shasum")
+                .args(["-a", "256"])
+                .arg(path)
+                .stdin(Stdio::null())
+                .output()
+        })
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to run sha256sum: {e}")))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.split_whitespace().next() {
+        Some(hash) if output.status.success() => Ok(hash.to_lowercase()),
+        _ => Err(io::Error::other(format!(
+            "failed to checksum {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Download `url` to `path` with `curl`.
+#[cfg(feature = "registry")]
+pub fn download(url: &str, path: &Path) -> io::Result<()> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(path)
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to run curl: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(io::Error::other(format!(
+        "failed to download {url}: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+/// Builds without the `registry` feature have no network access.
+#[cfg(not(feature = "registry"))]
+pub fn download(url: &str, _path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("cannot download {url}: nvimkata was built without the registry feature"),
+    ))
+}
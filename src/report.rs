@@ -0,0 +1,269 @@
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::challenge::{Challenge, Topic};
+use crate::goals;
+use crate::skills::{SkillProgress, SkillRegistry};
+use crate::state::{AttemptRecord, GameState};
+
+/// Output format of the progress report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(format!(
+                "invalid report format '{s}' (use markdown or html)"
+            )),
+        }
+    }
+}
+
+/// Parse a report period like `7d` or `2w` into days.
+pub fn parse_period(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let (count, unit) = s.split_at(s.len().saturating_sub(1));
+    let days = match (count.parse::<u32>(), unit) {
+        (Ok(n), "d") => n,
+        (Ok(n), "w") => n.saturating_mul(7),
+        _ => {
+            return Err(format!(
+                "invalid period '{s}' (use days like 7d or weeks like 2w)"
+            ));
+        }
+    };
+    if days == 0 {
+        return Err(format!("invalid period '{s}' (must be at least one day)"));
+    }
+    Ok(days)
+}
+
+/// A challenge whose best attempt was set during the report period.
+pub struct PersonalBest<'a> {
+    pub challenge: &'a Challenge,
+    /// Best attempt from before the period, if there was one.
+    pub before: Option<&'a AttemptRecord>,
+    pub after: &'a AttemptRecord,
+}
+
+impl PersonalBest<'_> {
+    /// The best grade went up, or the challenge was solved for the first time.
+    /// Freestyle challenges have no grades.
+    pub fn improves_grade(&self) -> bool {
+        !self.challenge.is_freestyle()
+            && self
+                .before
+                .is_none_or(|b| self.after.grade.rank() < b.grade.rank())
+    }
+}
+
+/// Practice over the last days up to today, from the activity log and the
+/// attempt history.
+pub struct ProgressReport<'a> {
+    /// First and last day of the period, as days since the Unix epoch.
+    pub from: u32,
+    pub to: u32,
+    /// Runs that matched the target.
+    pub completed: u32,
+    pub practice_secs: u32,
+    pub days_practiced: usize,
+    pub personal_bests: Vec<PersonalBest<'a>>,
+    pub skills: Vec<SkillProgress<'a>>,
+}
+
+impl<'a> ProgressReport<'a> {
+    pub fn new(
+        topics: &'a [Topic],
+        registry: &'a SkillRegistry,
+        state: &'a GameState,
+        today: u32,
+        days: u32,
+    ) -> Self {
+        let from = (today + 1).saturating_sub(days);
+        let window: Vec<_> = state.activity.range(from..=today).map(|(_, a)| a).collect();
+        let start = u64::from(from) * 86_400;
+        let personal_bests = topics
+            .iter()
+            .flat_map(|t| t.challenges.iter())
+            .filter_map(|challenge| {
+                let history = state.history.get(&challenge.id)?;
+                let after = history.first().filter(|a| a.timestamp >= Some(start))?;
+                let before = history
+                    .iter()
+                    .find(|a| a.timestamp.is_none_or(|t| t < start));
+                Some(PersonalBest {
+                    challenge,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        Self {
+            from,
+            to: today,
+            completed: window.iter().map(|a| a.challenges).sum(),
+            practice_secs: window.iter().map(|a| a.secs).sum(),
+            days_practiced: window
+                .iter()
+                .filter(|a| a.challenges > 0 || a.secs > 0)
+                .count(),
+            personal_bests,
+            skills: registry.progress(topics, state),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        let title = "nvimkata progress report";
+        let period = format!(
+            "{} to {}",
+            goals::format_day(self.from),
+            goals::format_day(self.to)
+        );
+        let improvements: Vec<&PersonalBest> = self
+            .personal_bests
+            .iter()
+            .filter(|pb| pb.improves_grade())
+            .collect();
+        let summary = vec![
+            format!("Challenges completed: {}", self.completed),
+            format!(
+                "Practice time: {} on {} days",
+                format_duration(u64::from(self.practice_secs)),
+                self.days_practiced
+            ),
+            format!("New personal bests: {}", self.personal_bests.len()),
+            format!("Grade improvements: {}", improvements.len()),
+        ];
+        let sections = [
+            (
+                "Grade improvements",
+                improvements.iter().map(|pb| grade_line(pb)).collect(),
+            ),
+            (
+                "New personal bests",
+                self.personal_bests.iter().map(best_line).collect(),
+            ),
+            (
+                "Skill coverage",
+                self.skills
+                    .iter()
+                    .map(|p| {
+                        format!(
+                            "{}: {}% ({}/{} attempted)",
+                            p.skill.name, p.percent, p.completed, p.tagged
+                        )
+                    })
+                    .collect::<Vec<String>>(),
+            ),
+        ];
+        match format {
+            ReportFormat::Markdown => markdown(title, &period, &summary, &sections),
+            ReportFormat::Html => html(title, &period, &summary, &sections),
+        }
+    }
+}
+
+fn grade_line(pb: &PersonalBest) -> String {
+    let before = pb
+        .before
+        .map_or("-".to_string(), |b| b.grade.display_char().to_string());
+    format!(
+        "{} {}: {before} -> {}",
+        pb.challenge.id,
+        pb.challenge.title,
+        pb.after.grade.display_char()
+    )
+}
+
+fn best_line(pb: &PersonalBest) -> String {
+    let grade = |a: &AttemptRecord| {
+        if pb.challenge.is_freestyle() {
+            String::new()
+        } else {
+            format!(" ({})", a.grade.display_char())
+        }
+    };
+    let result = match pb.before {
+        Some(b) => format!(
+            "{}{} -> {} keys{}",
+            b.keystrokes,
+            grade(b),
+            pb.after.keystrokes,
+            grade(pb.after)
+        ),
+        None => format!(
+            "first solve, {} keys{}",
+            pb.after.keystrokes,
+            grade(pb.after)
+        ),
+    };
+    format!("{} {}: {result}", pb.challenge.id, pb.challenge.title)
+}
+
+/// Seconds as `1h 5m`, `12m` or `40s`.
+/// Seconds as e.g. `45s`, `12m` or `3h 5m`.
+pub fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{secs}s"),
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+fn markdown(
+    title: &str,
+    period: &str,
+    summary: &[String],
+    sections: &[(&str, Vec<String>)],
+) -> String {
+    let mut out = format!("# {title}\n\n{period}\n\n");
+    for line in summary {
+        let _ = writeln!(out, "- {line}");
+    }
+    for (heading, items) in sections.iter().filter(|(_, items)| !items.is_empty()) {
+        let _ = write!(out, "\n## {heading}\n\n");
+        for item in items {
+            let _ = writeln!(out, "- {item}");
+        }
+    }
+    out
+}
+
+fn html(title: &str, period: &str, summary: &[String], sections: &[(&str, Vec<String>)]) -> String {
+    let list = |items: &[String]| {
+        let mut out = String::from("<ul>\n");
+        for item in items {
+            let _ = writeln!(out, "<li>{}</li>", escape(item));
+        }
+        out.push_str("</ul>\n");
+        out
+    };
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<p>{period}</p>\n"
+    );
+    out.push_str(&list(summary));
+    for (heading, items) in sections.iter().filter(|(_, items)| !items.is_empty()) {
+        let _ = writeln!(out, "<h2>{heading}</h2>");
+        out.push_str(&list(items));
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Escape text for HTML.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,222 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::challenge::{Category, Challenge, Topic};
+use crate::editor::SessionOptions;
+use crate::nvim::{ChallengeResult, EmbeddedSession};
+use crate::state::GameState;
+use crate::unlock::{self, UnlockPolicy};
+
+/// A request from the neovim plugin. Each line on stdin is a JSON object with an
+/// `id`, a `method` and its `params`; each reply is a line with the same `id` and
+/// either a `result` or an `error`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    /// IDs and titles of all challenges, for completion.
+    List,
+    /// Prepare a session for a challenge.
+    Open { challenge: String },
+    /// Evaluate and record the open session, given the final buffer lines.
+    Finish { lines: Vec<String> },
+}
+
+/// Serves challenges to the plugin for one neovim instance.
+pub struct Server<'a> {
+    topics: &'a [Topic],
+    state: &'a mut GameState,
+    dir: PathBuf,
+    /// The open challenge with its buffers loaded, and its session.
+    session: Option<(Challenge, EmbeddedSession)>,
+    /// Save the state after each recorded run.
+    save: bool,
+    options: SessionOptions,
+    unlock_all: bool,
+    /// Category unlock rule, `Off` when `unlock_all` is set.
+    unlock_policy: UnlockPolicy,
+}
+
+impl<'a> Server<'a> {
+    /// Session files go to `dir`.
+    pub fn new(topics: &'a [Topic], state: &'a mut GameState, dir: PathBuf, save: bool) -> Self {
+        Self {
+            topics,
+            state,
+            dir,
+            session: None,
+            save,
+            options: SessionOptions::default(),
+            unlock_all: false,
+            unlock_policy: UnlockPolicy::default(),
+        }
+    }
+
+    /// Open locked challenges too with `unlock_all`, and judge categories on
+    /// `policy` otherwise, as the picker does.
+    #[must_use]
+    pub fn with_unlock(mut self, unlock_all: bool, policy: UnlockPolicy) -> Self {
+        self.unlock_all = unlock_all;
+        self.unlock_policy = if unlock_all {
+            UnlockPolicy::Off
+        } else {
+            policy
+        };
+        self
+    }
+
+    /// Apply `options` to the sessions served.
+    #[must_use]
+    pub fn with_session_options(mut self, options: SessionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn handle(&mut self, request: Request) -> Result<Value, String> {
+        match request {
+            Request::List => Ok(self
+                .challenges()
+                .map(|c| json!({ "id": c.id, "title": c.title }))
+                .collect()),
+            Request::Open { challenge: id } => {
+                let (number, (topic, challenge)) = self
+                    .topics
+                    .iter()
+                    .flat_map(|t| t.challenges.iter().map(move |c| (t, c)))
+                    .enumerate()
+                    .find(|(_, (_, c))| c.id == id)
+                    .ok_or_else(|| format!("no challenge with id '{id}'"))?;
+                self.check_unlocked(topic, challenge)?;
+                let challenge = challenge.loaded().map_err(|e| e.to_string())?;
+                let session =
+                    EmbeddedSession::create(&self.dir, &challenge, number + 1, &self.options)
+                        .map_err(|e| format!("failed to write session files: {e}"))?;
+                let reply = json!({
+                    "id": challenge.id,
+                    "title": challenge.title,
+                    "filetype": challenge.filetype,
+                    "start": session.start_path(),
+                    "target": session.target_path(),
+                    "blind": challenge.blind,
+                    "instructions": challenge
+                        .instructions()
+                        .map(|_| session.instructions_path()),
+                    "script": session.script_path(),
+                });
+                self.session = Some((challenge.into_owned(), session));
+                Ok(reply)
+            }
+            Request::Finish { lines } => {
+                let (challenge, session) = self.session.take().ok_or("no challenge is open")?;
+                let result = session.finish(&challenge, &lines);
+                let solved = self.state.record_session(&challenge, &result);
+                if self.save {
+                    self.state
+                        .save()
+                        .map_err(|e| format!("failed to save progress: {e}"))?;
+                }
+                Ok(json!({ "solved": solved, "message": summary(&challenge, &result) }))
+            }
+        }
+    }
+
+    /// The reason `challenge` of `topic` cannot be played yet, if any.
+    fn check_unlocked(&self, topic: &Topic, challenge: &Challenge) -> Result<(), String> {
+        let category = Category::for_topic(topic.id);
+        if !unlock::is_category_unlocked(category, self.topics, self.state, self.unlock_policy) {
+            return Err(format!(
+                "challenge '{}' is locked: the {} category is not unlocked yet",
+                challenge.id,
+                category.name().to_lowercase()
+            ));
+        }
+        let missing = unlock::missing_requirements(challenge, self.state);
+        if !self.unlock_all && !missing.is_empty() {
+            return Err(format!(
+                "challenge '{}' is locked: complete {} first",
+                challenge.id,
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    fn challenges(&self) -> impl Iterator<Item = &'a Challenge> + use<'a> {
+        self.topics.iter().flat_map(|t| t.challenges.iter())
+    }
+}
+
+/// One line describing a finished run, shown by the plugin.
+fn summary(challenge: &Challenge, result: &ChallengeResult) -> String {
+    let title = &challenge.title;
+    if !result.outcome.is_attempt() {
+        return format!("{title}: aborted");
+    }
+    if !result.buffer_matches {
+        return format!(
+            "{title}: target not matched ({} keystrokes)",
+            result.keystrokes
+        );
+    }
+    if let Some(seq) = &result.violation {
+        return format!("{title}: constraint violated (used {seq})");
+    }
+    let mut summary = if challenge.is_freestyle() {
+        let reference = challenge
+            .reference_keystrokes
+            .map_or_else(String::new, |r| format!(" (reference: {r})"));
+        format!(
+            "{title}: completed in {} keystrokes{reference}",
+            result.keystrokes
+        )
+    } else {
+        format!(
+            "{title}: grade {} in {} keystrokes (par: {})",
+            challenge
+                .score_with_hints(result.keystrokes, result.hints_used)
+                .display_char(),
+            result.keystrokes,
+            challenge.par()
+        )
+    };
+    if let Some(technique) = challenge
+        .required_technique
+        .as_ref()
+        .filter(|_| result.technique_missing)
+    {
+        let _ = write!(summary, ", technique not used: {}", technique.name);
+    }
+    summary
+}
+
+/// Answer requests line by line until `input` closes.
+pub fn serve(input: impl BufRead, mut output: impl Write, server: &mut Server) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Value>(&line) {
+            Ok(mut message) => {
+                let id = message
+                    .as_object_mut()
+                    .and_then(|m| m.remove("id"))
+                    .unwrap_or_default();
+                let reply = serde_json::from_value::<Request>(message)
+                    .map_err(|e| format!("invalid request: {e}"))
+                    .and_then(|request| server.handle(request));
+                match reply {
+                    Ok(result) => json!({ "id": id, "result": result }),
+                    Err(error) => json!({ "id": id, "error": error }),
+                }
+            }
+            Err(e) => json!({ "id": null, "error": format!("invalid request: {e}") }),
+        };
+        writeln!(output, "{reply}")?;
+        output.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+
+use crate::challenge::{Challenge, Topic};
+use crate::editor;
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::nvim;
+use crate::screen;
+use crate::theme;
+
+/// Pick a starting buffer for the sandbox and open it in neovim: a blank
+/// buffer or the start content of any challenge. Nothing is graded or saved.
+pub fn run_sandbox(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    topics: &[Topic],
+) -> std::io::Result<()> {
+    // `None` is the blank buffer
+    let mut entries: Vec<Option<&Challenge>> = vec![None];
+    entries.extend(topics.iter().flat_map(|t| t.challenges.iter().map(Some)));
+
+    let dim = Style::new().fg(Color::Gray);
+    let mut number = 0;
+    let mut items = vec![ListItem::new(Line::styled(
+        "Blank buffer",
+        Style::new().fg(Color::Cyan),
+    ))];
+    for topic in topics {
+        for challenge in &topic.challenges {
+            number += 1;
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(format!("#{number:03} "), dim),
+                Span::raw(challenge.title.as_str()),
+                Span::styled(format!(" ({})", topic.name), dim),
+            ])));
+        }
+    }
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+    let mut pending: Vec<KeyPress> = Vec::new();
+    let mut list_height: u16 = 0;
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            list_height = main.height.saturating_sub(2);
+            let list = List::new(items.clone())
+                .block(Block::bordered().title(" Sandbox: choose a start buffer "))
+                .highlight_style(
+                    Style::new()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol(">> ");
+            frame.render_stateful_widget(list, main, &mut list_state);
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: navigate | {}: open in nvim (ungraded) | {}/{}: back",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::Select),
+                    keymap.label(Action::Back),
+                    keymap.label(Action::Quit),
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            let len = entries.len();
+            let selected = list_state.selected().unwrap_or(0);
+            let half = usize::from((list_height / 2).max(1));
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Back | Action::Quit) => return Ok(()),
+                Some(Action::Down) => list_state.select(Some((selected + 1) % len)),
+                Some(Action::Up) => list_state.select(Some((selected + len - 1) % len)),
+                Some(Action::Top) => list_state.select(Some(0)),
+                Some(Action::Bottom) => list_state.select(Some(len - 1)),
+                Some(Action::HalfPageDown) => {
+                    list_state.select(Some((selected + half).min(len - 1)));
+                }
+                Some(Action::HalfPageUp) => {
+                    list_state.select(Some(selected.saturating_sub(half)));
+                }
+                Some(Action::Select) => {
+                    ratatui::restore();
+                    let result = editor::editor(launcher).run_sandbox(entries[selected]);
+                    *terminal = ratatui::init();
+                    if let Err(e) = result {
+                        let hints = editor::launch_hints(launcher, None, &e);
+                        screen::show_error(terminal, "Editor failed", &e.to_string(), &hints)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+-- nvimkata sandbox runtime: keystroke HUD without grading or results
+-- Variables injected by Rust preamble:
+--   _VK_TITLE, _VK_START_PATH,
+--   _VK_START_CURSOR ({ line, col } 1-based, or nil),
+--   _VK_APPLY_SETUP (function(buf) applying registers, options and marks)
+
+-- vim.uv is vim.loop before 0.10
+local uv = vim.uv or vim.loop
+local ks = 0
+local win = vim.api.nvim_get_current_win()
+local buf = vim.api.nvim_get_current_buf()
+local t0 = uv.now()
+local reset_code = vim.api.nvim_replace_termcodes("<F5>", true, false, true)
+local key_log = {}
+
+-- Number of most recent keys shown in the winbar
+local RECENT = 20
+
+local function set_bar()
+  if not vim.api.nvim_win_is_valid(win) then
+    return
+  end
+  local elapsed = math.floor((uv.now() - t0) / 1000)
+  local recent = table.concat(key_log, "", math.max(1, #key_log - RECENT + 1))
+  local bar = string.format(
+    "  SANDBOX - %s | %d keys | %02d:%02d | %s",
+    _VK_TITLE,
+    ks,
+    math.floor(elapsed / 60),
+    elapsed % 60,
+    recent
+  )
+  vim.api.nvim_set_option_value("winbar", bar:gsub("%%", "%%%%"), { win = win })
+end
+
+local function reset()
+  local start_lines = vim.fn.readfile(_VK_START_PATH)
+  vim.api.nvim_buf_set_lines(buf, 0, -1, false, start_lines)
+  _VK_APPLY_SETUP(buf)
+  if _VK_START_CURSOR then
+    pcall(vim.api.nvim_win_set_cursor, win, { _VK_START_CURSOR[1], _VK_START_CURSOR[2] - 1 })
+  end
+  ks = 0
+  key_log = {}
+  t0 = uv.now()
+  set_bar()
+end
+
+reset()
+
+-- F5 restores the start content and clears the counter
+for _, mode in ipairs({ "n", "i", "v" }) do
+  vim.keymap.set(mode, "<F5>", reset, { noremap = true, silent = true })
+end
+
+-- Before 0.10 only the key after mappings is passed
+vim.on_key(function(key, typed)
+  typed = typed or key
+  if not typed or typed == "" or typed == reset_code then
+    return
+  end
+  ks = ks + 1
+  key_log[#key_log + 1] = vim.fn.keytrans(typed)
+  set_bar()
+end)
+
+local t = uv.new_timer()
+t:start(1000, 1000, vim.schedule_wrap(set_bar))
+
+vim.notify("Sandbox: nothing is graded. F5 resets, :w or :q! leaves.")
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+
+use crate::theme;
+
+/// Terminals narrower than this don't split list screens side by side.
+pub const NARROW_WIDTH: u16 = 100;
+
+/// How a list screen arranges its list and detail panel on a narrow terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NarrowLayout {
+    /// The list above the detail panel.
+    #[default]
+    Stacked,
+    /// Only the list.
+    List,
+    /// Only the detail panel.
+    Detail,
+}
+
+impl NarrowLayout {
+    /// The layout the layout key switches to next.
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            Self::Stacked => Self::List,
+            Self::List => Self::Detail,
+            Self::Detail => Self::Stacked,
+        }
+    }
+}
+
+/// Areas of the list and the detail panel in `area`: side by side on wide
+/// terminals, arranged by `narrow` below [`NARROW_WIDTH`]. A panel left out
+/// by a single-pane layout gets an empty area.
+pub fn split_panels(area: Rect, narrow: NarrowLayout) -> [Rect; 2] {
+    if area.width >= NARROW_WIDTH {
+        return Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .areas(area);
+    }
+    match narrow {
+        NarrowLayout::Stacked => {
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area)
+        }
+        NarrowLayout::List => [area, Rect::new(area.x, area.bottom(), area.width, 0)],
+        NarrowLayout::Detail => [Rect::new(area.x, area.y, area.width, 0), area],
+    }
+}
+
+/// Ask `question` in a box in the middle of the screen. Returns true for `y`;
+/// any other key declines.
+pub fn confirm(terminal: &mut ratatui::DefaultTerminal, question: &str) -> std::io::Result<bool> {
+    loop {
+        terminal.draw(|frame| {
+            let width = frame.area().width.min(60);
+            let [area] = Layout::horizontal([Constraint::Length(width)])
+                .flex(Flex::Center)
+                .areas(frame.area());
+            let [area] = Layout::vertical([Constraint::Length(6)])
+                .flex(Flex::Center)
+                .areas(area);
+            let lines = vec![
+                Line::from(question.to_string()),
+                Line::from(""),
+                Line::styled("y: yes | any other key: no", Style::new().fg(Color::Gray)),
+            ];
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: true })
+                    .block(Block::bordered().title(" Confirm ")),
+                area,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+        if let Some(key) = poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(key.code == KeyCode::Char('y'));
+        }
+    }
+}
+
+/// Show `message` in a red box in the middle of the screen, with `hints` on what
+/// to try below it, until any key is pressed.
+pub fn show_error(
+    terminal: &mut ratatui::DefaultTerminal,
+    title: &str,
+    message: &str,
+    hints: &[String],
+) -> std::io::Result<()> {
+    let mut lines: Vec<Line> = message.lines().map(Line::from).collect();
+    if !hints.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Try:", Style::new().fg(Color::Yellow)));
+        lines.extend(hints.iter().map(|hint| Line::from(format!("- {hint}"))));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "any key: continue",
+        Style::new().fg(Color::Gray),
+    ));
+    loop {
+        terminal.draw(|frame| {
+            let width = frame.area().width.min(90);
+            let [area] = Layout::horizontal([Constraint::Length(width)])
+                .flex(Flex::Center)
+                .areas(frame.area());
+            let paragraph = Paragraph::new(lines.clone())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::bordered()
+                        .title(format!(" {title} "))
+                        .border_style(Style::new().fg(Color::Red)),
+                );
+            // Rows the wrapped lines take, with one to spare for each line that
+            // wraps since wrapping breaks at words
+            let inner = usize::from(width.saturating_sub(2)).max(1);
+            let rows: usize = lines
+                .iter()
+                .map(|l| match l.width().div_ceil(inner) {
+                    0 | 1 => 1,
+                    n => n + 1,
+                })
+                .sum();
+            let height = u16::try_from(rows + 2).unwrap_or(u16::MAX);
+            let [area] = Layout::vertical([Constraint::Length(height)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(Clear, area);
+            frame.render_widget(paragraph, area);
+            theme::apply(frame.buffer_mut());
+        })?;
+        if let Some(key) = poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// Wait up to 100 ms for a key event. After a resize the whole terminal is
+/// cleared, so the next frame is drawn from scratch at the new size.
+pub fn poll_key(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<Option<KeyEvent>> {
+    poll_key_within(terminal, Duration::from_millis(100))
+}
+
+/// [`poll_key`] with a custom timeout, for screens that animate.
+pub fn poll_key_within(
+    terminal: &mut ratatui::DefaultTerminal,
+    timeout: Duration,
+) -> std::io::Result<Option<KeyEvent>> {
+    if !event::poll(timeout)? {
+        return Ok(None);
+    }
+    match event::read()? {
+        Event::Key(key) => Ok(Some(key)),
+        Event::Resize(..) => {
+            terminal.autoresize()?;
+            terminal.clear()?;
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,280 @@
+use std::fs;
+use std::path::Path;
+
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use serde::Deserialize;
+
+use crate::challenge::{Challenge, Grade, Topic};
+use crate::goals;
+use crate::heatmap::{self, KeyboardLayout};
+use crate::keymap::{Action, Keymap};
+use crate::screen;
+use crate::state::GameState;
+use crate::theme;
+
+/// File name of the skill registry inside the challenges directory.
+pub const SKILLS_FILE: &str = "skills.toml";
+
+/// Number of most used commands listed on the skills screen.
+const COMMAND_USAGE_SIZE: usize = 20;
+
+/// A named skill grouping one or more `focused_actions` tags.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Skill {
+    pub id: String,
+    pub name: String,
+    pub group: String,
+    /// Tags that belong to this skill verbatim.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Tags starting with any of these prefixes also belong to this skill.
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+}
+
+impl Skill {
+    pub fn matches(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag) || self.prefixes.iter().any(|p| tag.starts_with(p))
+    }
+}
+
+/// Ordered list of skills. A tag resolves to the first skill that matches it.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SkillRegistry {
+    #[serde(default)]
+    pub skills: Vec<Skill>,
+}
+
+/// Mastery of a single skill, computed from best grades of tagged challenges.
+#[derive(Debug, Clone, Copy)]
+pub struct SkillProgress<'a> {
+    pub skill: &'a Skill,
+    /// Challenges tagged with this skill.
+    pub tagged: usize,
+    /// Tagged challenges with a recorded result.
+    pub completed: usize,
+    /// Mastery from 0 to 100.
+    pub percent: u8,
+}
+
+impl SkillRegistry {
+    /// Find the skill a `focused_actions` tag belongs to.
+    pub fn skill_for_tag(&self, tag: &str) -> Option<&Skill> {
+        self.skills.iter().find(|s| s.matches(tag))
+    }
+
+    /// Skills a challenge exercises, in registry order.
+    pub fn skills_for(&self, challenge: &Challenge) -> Vec<&Skill> {
+        self.skills
+            .iter()
+            .filter(|skill| {
+                challenge
+                    .focused_actions
+                    .iter()
+                    .flatten()
+                    .any(|tag| self.skill_for_tag(tag).is_some_and(|s| s.id == skill.id))
+            })
+            .collect()
+    }
+
+    /// Mastery per skill in registry order. Skills without tagged challenges are omitted.
+    /// Each tagged challenge contributes 100% for Grade A down to 0% for Grade F or no result.
+    pub fn progress<'a>(&'a self, topics: &[Topic], state: &GameState) -> Vec<SkillProgress<'a>> {
+        let challenges: Vec<&Challenge> = topics
+            .iter()
+            .flat_map(|t| t.challenges.iter())
+            .filter(|c| !c.is_freestyle())
+            .collect();
+
+        self.skills
+            .iter()
+            .filter_map(|skill| {
+                let tagged: Vec<&Challenge> = challenges
+                    .iter()
+                    .copied()
+                    .filter(|c| self.skills_for(c).iter().any(|s| s.id == skill.id))
+                    .collect();
+                if tagged.is_empty() {
+                    return None;
+                }
+                let completed = tagged
+                    .iter()
+                    .filter(|c| state.best_grade(&c.id).is_some())
+                    .count();
+                let score: usize = tagged
+                    .iter()
+                    .map(|c| state.best_grade(&c.id).map_or(0, grade_score))
+                    .sum();
+                let percent = u8::try_from(score / tagged.len()).unwrap_or(100);
+                Some(SkillProgress {
+                    skill,
+                    tagged: tagged.len(),
+                    completed,
+                    percent,
+                })
+            })
+            .collect()
+    }
+}
+
+fn grade_score(grade: Grade) -> usize {
+    (5 - usize::from(grade.rank())) * 20
+}
+
+/// Load the skill registry from `skills.toml` in the challenges directory.
+/// A missing or unreadable registry yields an empty one.
+pub fn load_skills(challenges_dir: &Path) -> SkillRegistry {
+    let path = challenges_dir.join(SKILLS_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SkillRegistry::default();
+    };
+    match toml::from_str(&content) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("Warning: failed to parse {}: {}", path.display(), e);
+            SkillRegistry::default()
+        }
+    }
+}
+
+/// Show per-skill mastery, grouped by skill group. Returns on h/q/Esc.
+pub fn show_skills(
+    terminal: &mut ratatui::DefaultTerminal,
+    keymap: &Keymap,
+    registry: &SkillRegistry,
+    topics: &[Topic],
+    state: &GameState,
+    layout: KeyboardLayout,
+) -> std::io::Result<()> {
+    let progress = registry.progress(topics, state);
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut group = "";
+    for p in &progress {
+        if p.skill.group != group {
+            group = &p.skill.group;
+            if !items.is_empty() {
+                items.push(ListItem::new(Line::from("")));
+            }
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("── {group} ──"),
+                Style::new().add_modifier(Modifier::BOLD),
+            ))));
+        }
+        items.push(ListItem::new(progress_line(p)));
+    }
+    let usage = state.stats.top_commands(COMMAND_USAGE_SIZE);
+    if let Some(&(_, max)) = usage.first() {
+        items.push(ListItem::new(Line::from("")));
+        items.push(ListItem::new(Line::from(Span::styled(
+            "── Command usage ──",
+            Style::new().add_modifier(Modifier::BOLD),
+        ))));
+        items.extend(
+            usage
+                .iter()
+                .map(|&(name, count)| ListItem::new(usage_line(name, count, max))),
+        );
+    }
+    if !state.activity.is_empty() {
+        items.push(ListItem::new(Line::from("")));
+        items.push(ListItem::new(Line::from(Span::styled(
+            "── Practice calendar ──",
+            Style::new().add_modifier(Modifier::BOLD),
+        ))));
+        items.extend(
+            heatmap::calendar_lines(&state.activity, goals::today())
+                .into_iter()
+                .map(ListItem::new),
+        );
+    }
+    if !state.stats.key_usage.is_empty() {
+        items.push(ListItem::new(Line::from("")));
+        items.push(ListItem::new(Line::from(Span::styled(
+            format!("── Key heatmap ({}) ──", layout.name()),
+            Style::new().add_modifier(Modifier::BOLD),
+        ))));
+        items.extend(
+            heatmap::heatmap_lines(layout, &state.stats.key_usage)
+                .into_iter()
+                .map(ListItem::new),
+        );
+    }
+
+    let mut list_state = ListState::default();
+    let mut pending = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            let [main, footer] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+            let list = List::new(items.clone()).block(Block::bordered().title(" Skills "));
+            frame.render_stateful_widget(list, main, &mut list_state);
+            frame.render_widget(
+                Paragraph::new(format!(
+                    " {}/{}: scroll | {}/{}: back",
+                    keymap.label(Action::Down),
+                    keymap.label(Action::Up),
+                    keymap.label(Action::Back),
+                    keymap.label(Action::Quit)
+                ))
+                .style(Style::new().fg(Color::DarkGray)),
+                footer,
+            );
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Down) => {
+                    let max = items.len().saturating_sub(1);
+                    *list_state.offset_mut() = (list_state.offset() + 1).min(max);
+                }
+                Some(Action::Up) => {
+                    *list_state.offset_mut() = list_state.offset().saturating_sub(1);
+                }
+                Some(Action::Back | Action::Quit) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One command's usage, with a bar relative to the most used command.
+fn usage_line(name: &str, count: u32, max: u32) -> Line<'static> {
+    const WIDTH: u32 = 20;
+    let filled = count * WIDTH / max.max(1);
+    Line::from(vec![
+        Span::raw(format!("  {name:<28} ")),
+        Span::styled("█".repeat(filled as usize), Style::new().fg(Color::Cyan)),
+        Span::styled(
+            "░".repeat((WIDTH - filled) as usize),
+            Style::new().fg(Color::DarkGray),
+        ),
+        Span::raw(format!(" {count:>4}")),
+    ])
+}
+
+fn progress_line(p: &SkillProgress) -> Line<'static> {
+    const WIDTH: usize = 20;
+    let filled = usize::from(p.percent) * WIDTH / 100;
+    let color = match p.percent {
+        80.. => Color::Green,
+        40..80 => Color::Yellow,
+        _ => Color::Red,
+    };
+    Line::from(vec![
+        Span::raw(format!("  {:<28} ", p.skill.name)),
+        Span::styled("█".repeat(filled), Style::new().fg(color)),
+        Span::styled("░".repeat(WIDTH - filled), Style::new().fg(Color::DarkGray)),
+        Span::raw(format!(" {:>3}%", p.percent)),
+        Span::styled(
+            format!("  ({}/{} attempted)", p.completed, p.tagged),
+            Style::new().fg(Color::Gray),
+        ),
+    ])
+}
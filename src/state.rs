@@ -1,15 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 
-use crate::challenge::{Challenge, Grade};
+use crate::challenge::{Category, Challenge, Grade, Topic};
+use crate::commands;
+use crate::dirs;
+use crate::error::StateError;
+use crate::goals::{self, Goal, Streak};
+use crate::notation;
+use crate::nvim::ChallengeResult;
 
 #[derive(Debug)]
 pub struct SaveError {
     pub path: PathBuf,
     pub source: String,
+    /// The file has git merge-conflict markers, see [`conflict_sides`].
+    pub conflict: bool,
 }
 
 impl std::fmt::Display for SaveError {
@@ -25,41 +36,445 @@ impl std::fmt::Display for SaveError {
 
 impl std::error::Error for SaveError {}
 
+/// Saved progress. Every map is ordered, so the same state always saves to
+/// the same JSON and a save kept in git diffs and merges line by line.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GameState {
-    pub challenges: HashMap<String, BestResult>,
+    pub challenges: BTreeMap<String, BestResult>,
     pub stats: Stats,
     #[serde(default)]
-    pub history: HashMap<String, Vec<AttemptRecord>>,
+    pub history: BTreeMap<String, Vec<AttemptRecord>>,
+    /// Practice per day, keyed by days since the Unix epoch.
+    #[serde(default)]
+    pub activity: BTreeMap<u32, DayActivity>,
+    /// Personal notes and ratings per challenge ID.
+    #[serde(default)]
+    pub notes: BTreeMap<String, ChallengeNote>,
+    /// Bookmarked challenge IDs, oldest first.
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Use of each command in solved runs, by
+    /// [`Command::name`](crate::commands::Command::name) and by register (`"a`).
+    #[serde(default)]
+    pub command_mastery: BTreeMap<String, CommandMastery>,
+    /// Outdated best results accepted as historical, by challenge ID: kept for
+    /// reference, but no longer counted as results.
+    #[serde(default)]
+    pub archived: BTreeMap<String, BestResult>,
+    /// Content of the challenge versions that results were achieved on, by
+    /// challenge ID and version, to show what changed once they go stale.
+    #[serde(default)]
+    pub versions: BTreeMap<String, BTreeMap<String, VersionContent>>,
+    /// Distinct solutions per challenge ID, fewest keystrokes first. Unlike the
+    /// history, nothing is trimmed: each approach is kept once.
+    #[serde(default)]
+    pub solutions: BTreeMap<String, Vec<Solution>>,
+    /// Named save profile this state was loaded from, `None` for the default save.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// IDs shared by several loaded challenges. Results are keyed by ID, so
+    /// runs of these are not recorded.
+    #[serde(skip)]
+    pub ambiguous: BTreeSet<String>,
+}
+
+/// What [`GameState::merge`] took from the other save.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Challenges whose best result came from the other save.
+    pub improved: usize,
+    /// Attempts added to the histories.
+    pub attempts: usize,
+}
+
+/// A challenge version as it was when a result was achieved on it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionContent {
+    /// Hashes of the start and target buffers, to tell which of them changed.
+    pub start_hash: String,
+    pub target_hash: String,
+    /// The target buffer, for a diff against a later version.
+    pub target: String,
+}
+
+impl VersionContent {
+    pub fn of(challenge: &Challenge) -> Self {
+        Self {
+            start_hash: content_hash(&challenge.start().content),
+            target_hash: content_hash(&challenge.target().content),
+            target: challenge.target().content.clone(),
+        }
+    }
+}
+
+/// FNV-1a hash of a buffer in hex, stable across builds unlike the hashers of
+/// the standard library.
+pub fn content_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Hash of what a result depends on: the start and target buffers and the
+/// pars, whatever the player's layout. Editing any of them makes results stale
+/// even when the version stays. An unloaded challenge has the hash it was
+/// loaded with.
+pub fn challenge_hash(challenge: &Challenge) -> String {
+    if let Some(source) = &challenge.source {
+        return source.content_hash.clone();
+    }
+    let mut hashed = format!(
+        "{}\0{}\0{}",
+        challenge.start().content,
+        challenge.target().content,
+        challenge.par_keystrokes
+    );
+    for (layout, par) in &challenge.layout_par {
+        let _ = write!(hashed, "\0{}={par}", layout.name());
+    }
+    if let Some(par) = challenge.one_hand_par {
+        let _ = write!(hashed, "\0one_hand={par}");
+    }
+    content_hash(&hashed)
+}
+
+/// A distinct way of solving a challenge: the solved runs with the same keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Solution {
+    /// Key log in notation, as saved by the key log policy.
+    pub keys: String,
+    pub keystrokes: u32,
+    /// Solved runs that used it.
+    pub uses: u32,
+    /// Latest run that used it, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<u64>,
+}
+
+/// How much a command was used in solved runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandMastery {
+    /// Times it was used.
+    pub uses: u32,
+    /// IDs of the challenges it was used in.
+    pub challenges: BTreeSet<String>,
+}
+
+/// Practice logged on a single day, used for goal streaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayActivity {
+    /// Runs that matched the target.
+    pub challenges: u32,
+    /// Seconds spent in challenges.
+    pub secs: u32,
+}
+
+/// A player's own note and difficulty rating for a challenge.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeNote {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// Perceived difficulty from 1 to 5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+}
+
+impl ChallengeNote {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.rating.is_none()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredAttempt")]
 pub struct AttemptRecord {
-    #[serde(alias = "medal")]
     pub grade: Grade,
     pub keystrokes: u32,
-    pub time_secs: u32,
-    #[serde(default)]
+    /// How long the run took, unknown for runs that weren't timed, like the
+    /// headless ones of `author solve --record`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<u32>,
     pub keys: String,
+    /// The target was matched without the challenge's required technique.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub technique_missing: bool,
+    /// When the attempt was recorded, in seconds since the Unix epoch. Unknown
+    /// for attempts from older saves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+impl AttemptRecord {
+    /// True if both records describe the same run, as after copying a save.
+    fn same_run(&self, other: &AttemptRecord) -> bool {
+        (self.timestamp, self.keystrokes, self.time_ms, &self.keys)
+            == (
+                other.timestamp,
+                other.keystrokes,
+                other.time_ms,
+                &other.keys,
+            )
+    }
+}
+
+/// An `AttemptRecord` as saved; older saves have whole seconds in `time_secs`.
+#[derive(Deserialize)]
+struct StoredAttempt {
+    #[serde(alias = "medal")]
+    grade: Grade,
+    keystrokes: u32,
+    #[serde(default)]
+    time_ms: Option<u32>,
+    #[serde(default)]
+    time_secs: Option<u32>,
+    #[serde(default)]
+    keys: String,
+    #[serde(default)]
+    technique_missing: bool,
+    #[serde(default)]
+    timestamp: Option<u64>,
+}
+
+impl From<StoredAttempt> for AttemptRecord {
+    fn from(stored: StoredAttempt) -> Self {
+        Self {
+            grade: stored.grade,
+            keystrokes: stored.keystrokes,
+            time_ms: millis(stored.time_ms, stored.time_secs),
+            keys: stored.keys,
+            technique_missing: stored.technique_missing,
+            timestamp: stored.timestamp,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredBest")]
 pub struct BestResult {
-    #[serde(alias = "medal")]
     pub grade: Grade,
     pub keystrokes: u32,
+    /// Time of the best run, unknown if it wasn't timed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<u32>,
+    pub version: String,
+    pub stale: bool,
+    /// Freestyle only: the reference keystroke count has been matched or beaten.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub beat_reference: bool,
+    /// The best run skipped the challenge's required technique.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub technique_missing: bool,
+    /// Fastest solve, which need not be the run with the fewest keystrokes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_time_ms: Option<u32>,
+    /// Latest recorded attempt of any result, in seconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_attempt: Option<u64>,
+    /// [`challenge_hash`] of the challenge the result was achieved on. Older
+    /// saves get the hash of the challenge as first loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+}
+
+impl BestResult {
+    /// Fastest timed solve in milliseconds; saves from before it was tracked
+    /// fall back to the time of the best run.
+    pub fn best_time(&self) -> Option<u32> {
+        self.best_time_ms.or(self.time_ms)
+    }
+
+    /// Whether the result was last refreshed more than `days` days before
+    /// `now`. Results without a timestamp never decay.
+    pub fn is_decayed(&self, days: u32, now: u64) -> bool {
+        self.last_attempt
+            .is_some_and(|t| now.saturating_sub(t) > u64::from(days) * 86_400)
+    }
+}
+
+/// Replace `ours` with `theirs` if that is the better result, by the same
+/// order as [`GameState::record_attempt`] with a current result beating a
+/// stale one, and combine the times and badges of both. Returns true if
+/// `theirs` won.
+fn merged_best(ours: &mut BestResult, theirs: BestResult) -> bool {
+    let rank = |b: &BestResult| (b.stale, b.technique_missing, b.grade.rank(), b.keystrokes);
+    let best_time = [ours.best_time(), theirs.best_time()]
+        .into_iter()
+        .flatten()
+        .min();
+    let last_attempt = ours.last_attempt.max(theirs.last_attempt);
+    let beat_reference = ours.beat_reference || theirs.beat_reference;
+    let better = rank(&theirs) < rank(ours);
+    if better {
+        *ours = theirs;
+    }
+    ours.best_time_ms = best_time;
+    ours.last_attempt = last_attempt;
+    ours.beat_reference = beat_reference;
+    better
+}
+
+/// Add the solutions of another save to `ours`, combining the uses of the same
+/// keys like [`GameState::merge`] does with counts.
+fn merge_solutions(ours: &mut Vec<Solution>, theirs: Vec<Solution>, shared_past: bool) {
+    for solution in theirs {
+        if let Some(same) = ours.iter_mut().find(|s| s.keys == solution.keys) {
+            same.uses = if shared_past {
+                same.uses.max(solution.uses)
+            } else {
+                same.uses + solution.uses
+            };
+            same.last_used = same.last_used.max(solution.last_used);
+        } else {
+            ours.push(solution);
+        }
+    }
+    ours.sort_by_key(|s| s.keystrokes);
+}
+
+/// A `BestResult` as saved; older saves have whole seconds in `time_secs`.
+#[derive(Deserialize)]
+struct StoredBest {
+    #[serde(alias = "medal")]
+    grade: Grade,
+    keystrokes: u32,
     #[serde(default)]
-    pub time_secs: u32,
+    time_ms: Option<u32>,
     #[serde(default)]
-    pub version: String,
+    time_secs: Option<u32>,
     #[serde(default)]
-    pub stale: bool,
+    version: String,
+    #[serde(default)]
+    stale: bool,
+    #[serde(default)]
+    beat_reference: bool,
+    #[serde(default)]
+    technique_missing: bool,
+    #[serde(default)]
+    best_time_ms: Option<u32>,
+    #[serde(default)]
+    last_attempt: Option<u64>,
+    #[serde(default)]
+    content_hash: Option<String>,
+}
+
+impl From<StoredBest> for BestResult {
+    fn from(stored: StoredBest) -> Self {
+        Self {
+            grade: stored.grade,
+            keystrokes: stored.keystrokes,
+            time_ms: millis(stored.time_ms, stored.time_secs),
+            version: stored.version,
+            stale: stored.stale,
+            beat_reference: stored.beat_reference,
+            technique_missing: stored.technique_missing,
+            best_time_ms: stored.best_time_ms,
+            last_attempt: stored.last_attempt,
+            content_hash: stored.content_hash,
+        }
+    }
+}
+
+/// A saved time in milliseconds, or in whole seconds from before millisecond
+/// precision. Neither is saved for untimed runs.
+fn millis(time_ms: Option<u32>, time_secs: Option<u32>) -> Option<u32> {
+    time_ms.or(time_secs.map(|secs| secs.saturating_mul(1000)))
+}
+
+/// Aggregated results for all challenges sharing a `focused_actions` tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TagStats {
+    /// Number of challenges carrying the tag.
+    pub total: u32,
+    /// Number of those challenges with a recorded result.
+    pub attempted: u32,
+    /// Number of recorded results that are stale.
+    pub stale: u32,
+    /// Sum of `Grade::rank` over attempted challenges.
+    pub rank_sum: u32,
+}
+
+impl TagStats {
+    /// Average grade rank (0 = A, 5 = F) over attempted challenges, if any.
+    pub fn average_rank(&self) -> Option<f64> {
+        (self.attempted > 0).then(|| f64::from(self.rank_sum) / f64::from(self.attempted))
+    }
+
+    /// Fraction of attempted challenges whose result is stale.
+    pub fn stale_ratio(&self) -> f64 {
+        if self.attempted == 0 {
+            0.0
+        } else {
+            f64::from(self.stale) / f64::from(self.attempted)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Stats {
     pub total_keystrokes: u64,
     pub challenges_attempted: u32,
+    /// How often each command was used over all recorded attempts, by
+    /// [`Command::name`](crate::commands::Command::name).
+    #[serde(default)]
+    pub command_usage: BTreeMap<String, u32>,
+    /// How often each key was pressed over all recorded attempts, by key notation.
+    #[serde(default)]
+    pub key_usage: BTreeMap<String, u32>,
+    /// Seconds spent in challenges, finished or not.
+    #[serde(default)]
+    pub practice_secs: u64,
+    /// Graded runs per grade letter.
+    #[serde(default)]
+    pub grade_counts: BTreeMap<String, u32>,
+    /// When every challenge of a category was first seen completed, in
+    /// seconds since the Unix epoch, by [`Category::name`].
+    #[serde(default)]
+    pub category_completed: BTreeMap<String, u64>,
+    /// Most days in a row with at least one run.
+    #[serde(default)]
+    pub longest_streak: u32,
+    /// First and latest day with a run, as days since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_played: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<u32>,
+}
+
+impl Stats {
+    fn record(&mut self, keystrokes: u32, keys: &str) {
+        self.total_keystrokes += u64::from(keystrokes);
+        self.challenges_attempted += 1;
+        let keys = notation::tokenize(keys);
+        for key in &keys {
+            *self.key_usage.entry(key.to_string()).or_default() += 1;
+        }
+        for command in commands::classify(&keys) {
+            if !command.name.is_empty() {
+                *self.command_usage.entry(command.name).or_default() += 1;
+            }
+        }
+    }
+
+    /// The `count` most used commands, most used first.
+    pub fn top_commands(&self, count: usize) -> Vec<(&str, u32)> {
+        let mut usage: Vec<(&str, u32)> = self
+            .command_usage
+            .iter()
+            .map(|(name, n)| (name.as_str(), *n))
+            .collect();
+        usage.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        usage.truncate(count);
+        usage
+    }
+
+    /// Graded runs that got `grade`.
+    pub fn grade_count(&self, grade: Grade) -> u32 {
+        self.grade_counts
+            .get(grade.display_char())
+            .copied()
+            .unwrap_or(0)
+    }
 }
 
 impl GameState {
@@ -68,55 +483,141 @@ impl GameState {
         challenge_id: &str,
         grade: Grade,
         keystrokes: u32,
-        time_secs: u32,
+        time_ms: u32,
         keys: &str,
         version: &str,
     ) {
+        self.record_attempt(
+            challenge_id,
+            AttemptRecord {
+                grade,
+                keystrokes,
+                time_ms: Some(time_ms),
+                keys: keys.to_string(),
+                technique_missing: false,
+                timestamp: None,
+            },
+            version,
+        );
+    }
+
+    /// Record a finished run of `challenge` like the TUI does: practice time
+    /// for any attempt, and solved runs as a graded attempt or freestyle
+    /// result. Returns whether it was solved. Aborted runs are not recorded.
+    pub fn record_session(&mut self, challenge: &Challenge, result: &ChallengeResult) -> bool {
+        if !result.outcome.is_attempt() {
+            return false;
+        }
+        let solved = result.buffer_matches && result.violation.is_none();
+        self.log_activity(goals::today(), solved, result.elapsed_ms / 1000);
+        if solved && challenge.is_freestyle() {
+            self.record_freestyle_result(
+                &challenge.id,
+                result.keystrokes,
+                Some(result.elapsed_ms),
+                &result.keys,
+                &challenge.version,
+                challenge.reference_keystrokes,
+            );
+        } else if solved {
+            self.record_attempt(
+                &challenge.id,
+                AttemptRecord {
+                    grade: challenge.score_with_hints(result.keystrokes, result.hints_used),
+                    keystrokes: result.keystrokes,
+                    time_ms: Some(result.elapsed_ms),
+                    keys: result.keys.clone(),
+                    technique_missing: result.technique_missing,
+                    timestamp: None,
+                },
+                &challenge.version,
+            );
+        }
+        if solved {
+            self.record_content(challenge);
+        }
+        solved
+    }
+
+    /// Record a graded attempt. A run that used the required technique always
+    /// beats one that skipped it, regardless of grade. Attempts without a
+    /// timestamp are stamped with the current time. Stats count every key, but
+    /// the stored key log follows [`set_key_log`]. Returns false without
+    /// recording anything when the ID is ambiguous.
+    pub fn record_attempt(
+        &mut self,
+        challenge_id: &str,
+        mut attempt: AttemptRecord,
+        version: &str,
+    ) -> bool {
+        if self.ambiguous.contains(challenge_id) {
+            return false;
+        }
+        let timestamp = *attempt.timestamp.get_or_insert_with(goals::now);
         let was_stale = self.challenges.get(challenge_id).is_some_and(|b| b.stale);
         let is_improvement = self.challenges.get(challenge_id).is_none_or(|best| {
             best.stale
-                || grade_rank(grade) < grade_rank(best.grade)
-                || (grade == best.grade && keystrokes < best.keystrokes)
+                || (
+                    attempt.technique_missing,
+                    attempt.grade.rank(),
+                    attempt.keystrokes,
+                ) < (best.technique_missing, best.grade.rank(), best.keystrokes)
         });
         if is_improvement {
             self.challenges.insert(
                 challenge_id.to_string(),
                 BestResult {
-                    grade,
-                    keystrokes,
-                    time_secs,
+                    grade: attempt.grade,
+                    keystrokes: attempt.keystrokes,
+                    time_ms: attempt.time_ms,
                     version: version.to_string(),
                     stale: false,
+                    beat_reference: false,
+                    technique_missing: attempt.technique_missing,
+                    best_time_ms: self.best_time(challenge_id),
+                    last_attempt: None,
+                    content_hash: None,
                 },
             );
             if was_stale {
                 self.history.remove(challenge_id);
+                self.solutions.remove(challenge_id);
             }
         }
-        self.stats.total_keystrokes += u64::from(keystrokes);
-        self.stats.challenges_attempted += 1;
+        self.record_run(challenge_id, attempt.time_ms, timestamp);
+        self.stats.record(attempt.keystrokes, &attempt.keys);
+        self.record_mastery(challenge_id, &attempt.keys);
+        *self
+            .stats
+            .grade_counts
+            .entry(attempt.grade.display_char().to_string())
+            .or_default() += 1;
+        attempt.keys = key_log().apply(&attempt.keys);
+        self.record_solution(challenge_id, &attempt.keys, attempt.keystrokes, timestamp);
 
-        // Store in history (keep top 10 by keystrokes)
         let history = self.history.entry(challenge_id.to_string()).or_default();
-        history.push(AttemptRecord {
-            grade,
-            keystrokes,
-            time_secs,
-            keys: keys.to_string(),
-        });
-        history.sort_by_key(|a| a.keystrokes);
-        history.truncate(10);
+        history.push(attempt);
+        trim_history(history);
+        true
     }
 
     /// Record a freestyle result — improves on fewer keystrokes only, no grade comparison.
+    /// Reaching `reference` (the challenge's `reference_keystrokes`) earns a lasting badge.
+    /// Returns false without recording anything when the ID is ambiguous.
     pub fn record_freestyle_result(
         &mut self,
         challenge_id: &str,
         keystrokes: u32,
-        time_secs: u32,
+        time_ms: Option<u32>,
         keys: &str,
         version: &str,
-    ) {
+        reference: Option<u32>,
+    ) -> bool {
+        if self.ambiguous.contains(challenge_id) {
+            return false;
+        }
+        let timestamp = goals::now();
+        let beat_reference = reference.is_some_and(|r| keystrokes <= r);
         let was_stale = self.challenges.get(challenge_id).is_some_and(|b| b.stale);
         let is_improvement = self
             .challenges
@@ -128,41 +629,446 @@ impl GameState {
                 BestResult {
                     grade: Grade::F, // placeholder, never displayed for freestyle
                     keystrokes,
-                    time_secs,
+                    time_ms,
                     version: version.to_string(),
                     stale: false,
+                    beat_reference,
+                    technique_missing: false,
+                    best_time_ms: self.best_time(challenge_id),
+                    last_attempt: None,
+                    content_hash: None,
                 },
             );
             if was_stale {
                 self.history.remove(challenge_id);
+                self.solutions.remove(challenge_id);
             }
+        } else if beat_reference && let Some(best) = self.challenges.get_mut(challenge_id) {
+            best.beat_reference = true;
         }
-        self.stats.total_keystrokes += u64::from(keystrokes);
-        self.stats.challenges_attempted += 1;
+        self.record_run(challenge_id, time_ms, timestamp);
+        self.stats.record(keystrokes, keys);
+        self.record_mastery(challenge_id, keys);
+        let keys = key_log().apply(keys);
+        self.record_solution(challenge_id, &keys, keystrokes, timestamp);
 
-        // Store in history (keep top 10 by keystrokes)
         let history = self.history.entry(challenge_id.to_string()).or_default();
         history.push(AttemptRecord {
             grade: Grade::F,
             keystrokes,
-            time_secs,
-            keys: keys.to_string(),
+            time_ms,
+            keys,
+            technique_missing: false,
+            timestamp: Some(timestamp),
         });
-        history.sort_by_key(|a| a.keystrokes);
-        history.truncate(10);
+        trim_history(history);
+        true
+    }
+
+    /// Update the best time and the latest attempt after recording a run.
+    /// Untimed runs leave the best time alone.
+    fn record_run(&mut self, challenge_id: &str, time_ms: Option<u32>, timestamp: u64) {
+        if let Some(best) = self.challenges.get_mut(challenge_id) {
+            if let Some(time_ms) = time_ms {
+                best.best_time_ms = Some(best.best_time().map_or(time_ms, |t| t.min(time_ms)));
+            }
+            best.last_attempt = Some(best.last_attempt.map_or(timestamp, |t| t.max(timestamp)));
+        }
+    }
+
+    /// Count a solved run towards its solution, a new one unless the same keys
+    /// solved the challenge before. Runs without a key log are not counted.
+    fn record_solution(&mut self, challenge_id: &str, keys: &str, keystrokes: u32, timestamp: u64) {
+        if keys.is_empty() {
+            return;
+        }
+        let keys = notation::format(&notation::tokenize(keys));
+        let solutions = self.solutions.entry(challenge_id.to_string()).or_default();
+        if let Some(solution) = solutions.iter_mut().find(|s| s.keys == keys) {
+            solution.uses += 1;
+            solution.last_used = solution.last_used.max(Some(timestamp));
+        } else {
+            solutions.push(Solution {
+                keys,
+                keystrokes,
+                uses: 1,
+                last_used: Some(timestamp),
+            });
+            solutions.sort_by_key(|s| s.keystrokes);
+        }
+    }
+
+    /// Count the commands and registers of a solved run towards their mastery.
+    fn record_mastery(&mut self, challenge_id: &str, keys: &str) {
+        for command in commands::classify(&notation::tokenize(keys)) {
+            let register = command.register();
+            for name in [Some(command.name), register].into_iter().flatten() {
+                if name.is_empty() {
+                    continue;
+                }
+                let mastery = self.command_mastery.entry(name).or_default();
+                mastery.uses += 1;
+                mastery.challenges.insert(challenge_id.to_string());
+            }
+        }
+    }
+
+    /// Remove a stored attempt by its position in the challenge's history. The
+    /// best result is a separate record and is kept.
+    pub fn delete_attempt(&mut self, challenge_id: &str, index: usize) -> Option<AttemptRecord> {
+        let history = self.history.get_mut(challenge_id)?;
+        if index >= history.len() {
+            return None;
+        }
+        let attempt = history.remove(index);
+        if history.is_empty() {
+            self.history.remove(challenge_id);
+        }
+        Some(attempt)
+    }
+
+    /// Forget the best result, stored attempts and solutions of a challenge, keeping its
+    /// note, rating and bookmark. Returns false if there was nothing to forget.
+    pub fn reset_challenge(&mut self, challenge_id: &str) -> bool {
+        let best = self.challenges.remove(challenge_id).is_some();
+        let history = self.history.remove(challenge_id).is_some();
+        let archived = self.archived.remove(challenge_id).is_some();
+        self.versions.remove(challenge_id);
+        self.solutions.remove(challenge_id);
+        best || history || archived
+    }
+
+    /// [`reset_challenge`](Self::reset_challenge) for each of `challenges`.
+    /// Returns how many had progress.
+    pub fn reset_challenges(&mut self, challenges: &[Challenge]) -> usize {
+        challenges
+            .iter()
+            .filter(|c| self.reset_challenge(&c.id))
+            .count()
+    }
+
+    /// Forget all results, archived ones included, attempts, solutions,
+    /// lifetime stats, command mastery and practice activity.
+    /// Notes, ratings and bookmarks are kept.
+    pub fn reset_all(&mut self) {
+        self.challenges.clear();
+        self.archived.clear();
+        self.versions.clear();
+        self.solutions.clear();
+        self.history.clear();
+        self.stats = Stats::default();
+        self.activity.clear();
+        self.command_mastery.clear();
+    }
+
+    /// Fold another save into this one, as when combining the saves of two
+    /// machines. Each challenge keeps the better best result (a current one
+    /// beats a stale one), the fastest time and the latest attempt; histories
+    /// are joined without duplicates; stats, command and key usage, command
+    /// mastery, solution uses and daily activity are added up, keeping the earliest and latest
+    /// dates; notes, bookmarks, archived results and version contents missing here are copied
+    /// over.
+    pub fn merge(&mut self, other: GameState) -> MergeSummary {
+        self.merge_with(other, false)
+    }
+
+    /// Merge the two sides of a conflicted save like [`Self::merge`]. Both
+    /// sides share their past, so stats and activity take the larger count of
+    /// the two instead of adding up.
+    pub fn merge_sides(&mut self, other: GameState) -> MergeSummary {
+        self.merge_with(other, true)
+    }
+
+    fn merge_with(&mut self, other: GameState, shared_past: bool) -> MergeSummary {
+        fn combine<T: Ord + std::ops::AddAssign>(ours: &mut T, theirs: T, shared_past: bool) {
+            if !shared_past {
+                *ours += theirs;
+            } else if theirs > *ours {
+                *ours = theirs;
+            }
+        }
+        let mut summary = MergeSummary::default();
+        for (id, theirs) in other.challenges {
+            let improved = if let Some(ours) = self.challenges.get_mut(&id) {
+                merged_best(ours, theirs)
+            } else {
+                self.challenges.insert(id, theirs);
+                true
+            };
+            summary.improved += usize::from(improved);
+        }
+        for (id, attempts) in other.history {
+            let history = self.history.entry(id).or_default();
+            for attempt in attempts {
+                if !history.iter().any(|a| a.same_run(&attempt)) {
+                    history.push(attempt);
+                    summary.attempts += 1;
+                }
+            }
+            trim_history(history);
+        }
+        let stats = &mut self.stats;
+        combine(
+            &mut stats.total_keystrokes,
+            other.stats.total_keystrokes,
+            shared_past,
+        );
+        combine(
+            &mut stats.challenges_attempted,
+            other.stats.challenges_attempted,
+            shared_past,
+        );
+        for (name, n) in other.stats.command_usage {
+            combine(stats.command_usage.entry(name).or_default(), n, shared_past);
+        }
+        for (key, n) in other.stats.key_usage {
+            combine(stats.key_usage.entry(key).or_default(), n, shared_past);
+        }
+        for (name, theirs) in other.command_mastery {
+            let ours = self.command_mastery.entry(name).or_default();
+            combine(&mut ours.uses, theirs.uses, shared_past);
+            ours.challenges.extend(theirs.challenges);
+        }
+        combine(
+            &mut stats.practice_secs,
+            other.stats.practice_secs,
+            shared_past,
+        );
+        for (grade, n) in other.stats.grade_counts {
+            combine(stats.grade_counts.entry(grade).or_default(), n, shared_past);
+        }
+        for (category, theirs) in other.stats.category_completed {
+            let ours = stats.category_completed.entry(category).or_insert(theirs);
+            *ours = (*ours).min(theirs);
+        }
+        stats.first_played = stats
+            .first_played
+            .into_iter()
+            .chain(other.stats.first_played)
+            .min();
+        stats.last_played = stats.last_played.max(other.stats.last_played);
+        for (day, theirs) in other.activity {
+            let ours = self.activity.entry(day).or_default();
+            combine(&mut ours.challenges, theirs.challenges, shared_past);
+            combine(&mut ours.secs, theirs.secs, shared_past);
+        }
+        // Runs of days may join up across the two saves
+        self.stats.longest_streak = self
+            .stats
+            .longest_streak
+            .max(other.stats.longest_streak)
+            .max(longest_run(&self.activity));
+        for (id, theirs) in other.notes {
+            let ours = self.notes.entry(id).or_insert_with(|| theirs.clone());
+            if ours.text.is_empty() {
+                ours.text = theirs.text;
+            }
+            ours.rating = ours.rating.or(theirs.rating);
+        }
+        for id in other.favorites {
+            if !self.is_favorite(&id) {
+                self.favorites.push(id);
+            }
+        }
+        for (id, theirs) in other.archived {
+            self.archived.entry(id).or_insert(theirs);
+        }
+        for (id, theirs) in other.solutions {
+            merge_solutions(self.solutions.entry(id).or_default(), theirs, shared_past);
+        }
+        for (id, mut theirs) in other.versions {
+            // Ours win where both kept a version
+            theirs.extend(self.versions.remove(&id).unwrap_or_default());
+            self.versions.insert(id, theirs);
+        }
+        summary
+    }
+
+    /// Stored attempt with the fewest keystrokes.
+    pub fn best_attempt(&self, challenge_id: &str) -> Option<&AttemptRecord> {
+        self.history.get(challenge_id).and_then(|h| h.first())
+    }
+
+    pub fn is_favorite(&self, challenge_id: &str) -> bool {
+        self.favorites.iter().any(|id| id == challenge_id)
+    }
+
+    /// Bookmark a challenge, or remove the bookmark if it is already set.
+    pub fn toggle_favorite(&mut self, challenge_id: &str) {
+        if self.is_favorite(challenge_id) {
+            self.favorites.retain(|id| id != challenge_id);
+        } else {
+            self.favorites.push(challenge_id.to_string());
+        }
+    }
+
+    pub fn note(&self, challenge_id: &str) -> Option<&ChallengeNote> {
+        self.notes.get(challenge_id)
+    }
+
+    /// Replace the note text of a challenge. Surrounding whitespace is trimmed.
+    pub fn set_note_text(&mut self, challenge_id: &str, text: &str) {
+        self.update_note(challenge_id, |note| {
+            note.text = text.trim().to_string();
+        });
+    }
+
+    /// Set the difficulty rating of a challenge, clamped to 1-5. `None` clears it.
+    pub fn set_rating(&mut self, challenge_id: &str, rating: Option<u8>) {
+        self.update_note(challenge_id, |note| {
+            note.rating = rating.map(|r| r.clamp(1, 5));
+        });
+    }
+
+    /// Edit a note in place, dropping it once it has neither text nor rating.
+    fn update_note(&mut self, challenge_id: &str, edit: impl FnOnce(&mut ChallengeNote)) {
+        let note = self.notes.entry(challenge_id.to_string()).or_default();
+        edit(note);
+        if note.is_empty() {
+            self.notes.remove(challenge_id);
+        }
     }
 
-    /// Mark saved results as stale when their version doesn't match the current challenge.
+    /// Log a finished run on `day` (see `goals::today`) for goal tracking and
+    /// the lifetime stats.
+    pub fn log_activity(&mut self, day: u32, completed: bool, secs: u32) {
+        let entry = self.activity.entry(day).or_default();
+        entry.challenges += u32::from(completed);
+        entry.secs += secs;
+        let stats = &mut self.stats;
+        stats.practice_secs += u64::from(secs);
+        // Saves from before these stats still have their activity to go by
+        stats.first_played = stats
+            .first_played
+            .into_iter()
+            .chain(self.activity.keys().next().copied())
+            .min();
+        stats.last_played = stats.last_played.max(Some(day));
+        stats.longest_streak = stats.longest_streak.max(longest_run(&self.activity));
+    }
+
+    /// Stamp each category whose challenges in `topics` are now all completed
+    /// with `timestamp`, unless it already has one. Returns true if any was new.
+    pub fn record_category_completions(&mut self, topics: &[Topic], timestamp: u64) -> bool {
+        let mut changed = false;
+        for category in Category::ALL {
+            let mut challenges = topics
+                .iter()
+                .filter(|t| Category::for_topic(t.id) == category)
+                .flat_map(|t| &t.challenges)
+                .peekable();
+            if challenges.peek().is_none()
+                || self.stats.category_completed.contains_key(category.name())
+                || !challenges.all(|c| self.is_completed(&c.id))
+            {
+                continue;
+            }
+            self.stats
+                .category_completed
+                .insert(category.name().to_string(), timestamp);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Activity logged on `day`, if any.
+    pub fn activity_on(&self, day: u32) -> DayActivity {
+        self.activity.get(&day).copied().unwrap_or_default()
+    }
+
+    /// Streak for a goal as of `today`.
+    pub fn streak(&self, goal: &Goal, today: u32) -> Streak {
+        goal.streak(&self.activity, today)
+    }
+
+    /// Mark saved results as stale when their version doesn't match the current
+    /// challenge, or its [`challenge_hash`] doesn't: an edit without a version
+    /// bump. Results without a hash take the current one. The content of the
+    /// current version is kept for results achieved on it, read from the
+    /// challenge file if it wasn't yet, and contents of versions no result
+    /// refers to anymore are dropped.
     pub fn mark_stale(&mut self, challenges: &[Challenge]) {
         let challenge_map: HashMap<&str, &Challenge> =
             challenges.iter().map(|c| (c.id.as_str(), c)).collect();
         for (id, best) in &mut self.challenges {
-            if let Some(c) = challenge_map.get(id.as_str())
-                && best.version != c.version
-            {
+            let Some(c) = challenge_map.get(id.as_str()) else {
+                continue;
+            };
+            let hash = challenge_hash(c);
+            if best.version != c.version || best.content_hash.as_ref().is_some_and(|h| *h != hash) {
                 best.stale = true;
+            } else if !best.stale {
+                best.content_hash = Some(hash);
+                let versions = self.versions.entry(id.clone()).or_default();
+                if !versions.contains_key(&c.version)
+                    && let Ok(loaded) = c.loaded()
+                {
+                    versions.insert(c.version.clone(), VersionContent::of(&loaded));
+                }
+            }
+        }
+        let (challenges, archived) = (&self.challenges, &self.archived);
+        self.versions.retain(|id, versions| {
+            versions.retain(|version, _| {
+                [challenges.get(id), archived.get(id)]
+                    .into_iter()
+                    .flatten()
+                    .any(|b| &b.version == version)
+            });
+            !versions.is_empty()
+        });
+    }
+
+    /// Remember which content of `challenge` its best result was achieved on,
+    /// after recording a run of it. Results achieved earlier keep theirs.
+    pub fn record_content(&mut self, challenge: &Challenge) {
+        if let Some(best) = self.challenges.get_mut(&challenge.id)
+            && best.content_hash.is_none()
+            && best.version == challenge.version
+        {
+            best.content_hash = Some(challenge_hash(challenge));
+            self.versions
+                .entry(challenge.id.clone())
+                .or_default()
+                .insert(challenge.version.clone(), VersionContent::of(challenge));
+        }
+    }
+
+    /// Content of the version the best result of a challenge was achieved on,
+    /// if it was kept.
+    pub fn best_version_content(&self, challenge_id: &str) -> Option<&VersionContent> {
+        let best = self.challenges.get(challenge_id)?;
+        self.versions.get(challenge_id)?.get(&best.version)
+    }
+
+    /// Accept the stale best results as historical: move them to
+    /// [`Self::archived`], replacing older archived ones, so they stop warning
+    /// about outdated scores. Their challenges count as not completed until
+    /// solved again; attempts are kept. Returns how many were archived.
+    pub fn archive_stale(&mut self) -> usize {
+        let stale: Vec<String> = self
+            .challenges
+            .iter()
+            .filter(|(_, b)| b.stale)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &stale {
+            if let Some(best) = self.challenges.remove(id) {
+                self.archived.insert(id.clone(), best);
             }
         }
+        stale.len()
+    }
+
+    /// Refuse to record results for IDs shared by several challenges in `topics`.
+    pub fn mark_ambiguous(&mut self, topics: &[Topic]) {
+        let mut seen = HashSet::new();
+        self.ambiguous = topics
+            .iter()
+            .flat_map(|t| &t.challenges)
+            .filter(|c| !seen.insert(c.id.as_str()))
+            .map(|c| c.id.clone())
+            .collect();
     }
 
     /// Count challenges with stale scores.
@@ -175,62 +1081,403 @@ impl GameState {
         self.challenges.get(challenge_id).is_some_and(|b| b.stale)
     }
 
+    /// Count challenges whose grades have decayed, see [`Self::is_decayed`].
+    pub fn decayed_count(&self) -> usize {
+        decay_days().map_or(0, |days| {
+            let now = goals::now();
+            self.challenges
+                .values()
+                .filter(|b| b.is_decayed(days, now))
+                .count()
+        })
+    }
+
+    /// Whether the grade of a challenge has decayed: it was not refreshed
+    /// within the configured `decay_days`. Never without that setting.
+    pub fn is_decayed(&self, challenge_id: &str) -> bool {
+        decay_days().is_some_and(|days| {
+            self.challenges
+                .get(challenge_id)
+                .is_some_and(|b| b.is_decayed(days, goals::now()))
+        })
+    }
+
     /// Get the best keystroke count for a challenge, if attempted.
     pub fn best_keystrokes(&self, challenge_id: &str) -> Option<u32> {
         self.challenges.get(challenge_id).map(|r| r.keystrokes)
     }
 
+    /// Latest recorded attempt at any of `challenges`, in seconds since the Unix epoch.
+    pub fn last_practiced(&self, challenges: &[Challenge]) -> Option<u64> {
+        challenges
+            .iter()
+            .filter_map(|c| self.challenges.get(&c.id)?.last_attempt)
+            .max()
+    }
+
+    /// Fastest solve of a challenge in milliseconds. Stale results don't count, as the
+    /// challenge has changed since.
+    pub fn best_time(&self, challenge_id: &str) -> Option<u32> {
+        self.challenges
+            .get(challenge_id)
+            .filter(|b| !b.stale)
+            .and_then(BestResult::best_time)
+    }
+
+    /// Check if the reference keystroke count of a freestyle challenge has been reached.
+    pub fn beat_reference(&self, challenge_id: &str) -> bool {
+        self.challenges
+            .get(challenge_id)
+            .is_some_and(|b| b.beat_reference && !b.stale)
+    }
+
+    /// A challenge counts as completed once it has a result that used the
+    /// required technique (if any).
+    pub fn is_completed(&self, challenge_id: &str) -> bool {
+        self.challenges
+            .get(challenge_id)
+            .is_some_and(|b| !b.technique_missing)
+    }
+
+    /// Check if the best result skipped the challenge's required technique.
+    pub fn technique_missing(&self, challenge_id: &str) -> bool {
+        self.challenges
+            .get(challenge_id)
+            .is_some_and(|b| b.technique_missing)
+    }
+
     pub fn best_grade(&self, challenge_id: &str) -> Option<Grade> {
         self.challenges.get(challenge_id).map(|r| r.grade)
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let path = save_path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+    /// Aggregate best results per `focused_actions` tag. Freestyle challenges are
+    /// skipped since they carry no grade.
+    pub fn tag_stats<'a>(
+        &self,
+        challenges: impl IntoIterator<Item = &'a Challenge>,
+    ) -> HashMap<String, TagStats> {
+        let mut tags: HashMap<String, TagStats> = HashMap::new();
+        for challenge in challenges {
+            if challenge.is_freestyle() {
+                continue;
+            }
+            let best = self.challenges.get(&challenge.id);
+            for tag in challenge.focused_actions.iter().flatten() {
+                let entry = tags.entry(tag.clone()).or_default();
+                entry.total += 1;
+                if let Some(best) = best {
+                    entry.attempted += 1;
+                    entry.rank_sum += u32::from(best.grade.rank());
+                    if best.stale {
+                        entry.stale += 1;
+                    }
+                }
+            }
         }
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(&path, json)?;
-        Ok(())
+        tags
+    }
+
+    pub fn save(&self) -> Result<(), StateError> {
+        let path = save_path(self.profile.as_deref());
+        let write = |path: &Path| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string_pretty(self)?;
+            fs::write(path, json + "\n")
+        };
+        write(&path).map_err(|source| StateError::Write { path, source })
     }
 
     pub fn load() -> Result<Self, SaveError> {
-        let path = save_path();
+        Self::load_profile(None)
+    }
+
+    /// Load a save file from any path, such as one copied from another machine.
+    pub fn load_file(path: &Path) -> Result<Self, SaveError> {
+        let path = path.to_path_buf();
         match fs::read_to_string(&path) {
-            Ok(json) => serde_json::from_str(&json).map_err(|e| SaveError {
+            Ok(json) => Self::parse(&json, path),
+            Err(e) => Err(SaveError {
                 path,
                 source: e.to_string(),
+                conflict: false,
+            }),
+        }
+    }
+
+    fn parse(json: &str, path: PathBuf) -> Result<Self, SaveError> {
+        serde_json::from_str(json).map_err(|e| SaveError {
+            path,
+            source: e.to_string(),
+            conflict: conflict_sides(json).is_some(),
+        })
+    }
+
+    /// Load a save profile like [`Self::load_profile`], merging the two sides
+    /// of a file left with git merge-conflict markers. Returns what the merge
+    /// took over, `None` if the file had no conflicts.
+    pub fn load_resolving_conflicts(
+        profile: Option<&str>,
+    ) -> Result<(Self, Option<MergeSummary>), SaveError> {
+        let path = save_path(profile);
+        let sides = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| conflict_sides(&text));
+        let Some((ours, theirs)) = sides else {
+            return Self::load_profile(profile).map(|state| (state, None));
+        };
+        let mut state = Self::parse(&ours, path.clone())?;
+        let summary = state.merge_sides(Self::parse(&theirs, path)?);
+        state.profile = profile.map(str::to_string);
+        Ok((state, Some(summary)))
+    }
+
+    /// Load a named save profile, or the default save for `None`.
+    pub fn load_profile(profile: Option<&str>) -> Result<Self, SaveError> {
+        let path = save_path(profile);
+        let profile = profile.map(str::to_string);
+        match fs::read_to_string(&path) {
+            Ok(json) => Self::parse(&json, path).map(|state| Self { profile, ..state }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self {
+                profile,
+                ..Self::default()
             }),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
             Err(e) => Err(SaveError {
                 path,
                 source: e.to_string(),
+                conflict: false,
             }),
         }
     }
 }
 
-fn save_path() -> PathBuf {
-    let local = PathBuf::from("save.json");
-    if local.exists() {
-        return local;
-    }
-    let data_dir = if let Ok(dir) = std::env::var("XDG_DATA_HOME") {
-        PathBuf::from(dir)
-    } else {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        PathBuf::from(home).join(".local/share")
-    };
-    data_dir.join("nvimkata/save.json")
+/// Most consecutive days in `activity`.
+fn longest_run(activity: &BTreeMap<u32, DayActivity>) -> u32 {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for &day in activity.keys() {
+        run = if previous.is_some_and(|p: u32| p + 1 == day) {
+            run + 1
+        } else {
+            1
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+    longest
+}
+
+/// Attempts kept per challenge under [`HistoryRetention::Best`].
+const HISTORY_LEN: usize = 10;
+
+/// Which attempts are kept per challenge, from `history` in the config.
+/// Written as `best`, `last N`, `all` or `daily`. The best result of a
+/// challenge is kept apart from its attempts and never dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum HistoryRetention {
+    /// The 10 attempts with the fewest keystrokes.
+    #[default]
+    Best,
+    /// The most recent attempts.
+    Last(usize),
+    /// Every attempt.
+    All,
+    /// The attempt with the fewest keystrokes of each day.
+    Daily,
+}
+
+impl HistoryRetention {
+    /// Drop the attempts this policy doesn't keep and sort the rest by
+    /// keystrokes, best first.
+    pub fn retain(self, history: &mut Vec<AttemptRecord>) {
+        let day = |a: &AttemptRecord| a.timestamp.map(goals::day_of);
+        match self {
+            Self::Best => {
+                history.sort_by_key(|a| a.keystrokes);
+                history.truncate(HISTORY_LEN);
+            }
+            Self::Last(count) => {
+                history.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+                history.truncate(count);
+            }
+            Self::All => {}
+            Self::Daily => {
+                history.sort_by_key(|a| (day(a), a.keystrokes));
+                // Attempts from before timestamps were saved are all kept
+                history.dedup_by(|a, b| a.timestamp.is_some() && day(a) == day(b));
+            }
+        }
+        history.sort_by_key(|a| a.keystrokes);
+    }
+}
+
+impl FromStr for HistoryRetention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        if let Some(count) = lower.strip_prefix("last") {
+            return match count.trim().parse() {
+                Ok(n) if n > 0 => Ok(Self::Last(n)),
+                _ => Err(format!(
+                    "invalid history retention '{s}' (use a count like 'last 50')"
+                )),
+            };
+        }
+        match lower.as_str() {
+            "best" => Ok(Self::Best),
+            "all" => Ok(Self::All),
+            "daily" => Ok(Self::Daily),
+            _ => Err(format!(
+                "invalid history retention '{s}' (use best, all, daily or 'last 50')"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for HistoryRetention {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+static RETENTION: OnceLock<HistoryRetention> = OnceLock::new();
+
+/// Select the history retention for the rest of the session. Only the first
+/// call has an effect; returns false when it selected another retention.
+pub fn set_history_retention(retention: HistoryRetention) -> bool {
+    *RETENTION.get_or_init(|| retention) == retention
+}
+
+fn trim_history(history: &mut Vec<AttemptRecord>) {
+    RETENTION.get().copied().unwrap_or_default().retain(history);
+}
+
+/// Profile names may only use ASCII letters, digits, `-` and `_`.
+pub fn is_valid_profile(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
-fn grade_rank(grade: Grade) -> u8 {
-    match grade {
-        Grade::A => 0,
-        Grade::B => 1,
-        Grade::C => 2,
-        Grade::D => 3,
-        Grade::E => 4,
-        Grade::F => 5,
+/// The two sides of a file with git merge-conflict markers, `None` if it has
+/// none. Lines outside the conflicts go to both sides; the common ancestor of
+/// a diff3-style conflict is dropped.
+pub fn conflict_sides(text: &str) -> Option<(String, String)> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Side {
+        Both,
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut side = Side::Both;
+    let mut conflicted = false;
+    let (mut ours, mut theirs) = (String::new(), String::new());
+    for line in text.split_inclusive('\n') {
+        side = match side {
+            Side::Both if line.starts_with("<<<<<<<") => Side::Ours,
+            Side::Ours if line.starts_with("|||||||") => Side::Base,
+            Side::Ours | Side::Base if line.starts_with("=======") => Side::Theirs,
+            Side::Theirs if line.starts_with(">>>>>>>") => Side::Both,
+            Side::Both => {
+                ours.push_str(line);
+                theirs.push_str(line);
+                continue;
+            }
+            Side::Ours => {
+                ours.push_str(line);
+                continue;
+            }
+            Side::Theirs => {
+                theirs.push_str(line);
+                continue;
+            }
+            Side::Base => continue,
+        };
+        conflicted = true;
+    }
+    conflicted.then_some((ours, theirs))
+}
+
+/// How much of each run's key log is saved, from `key_log` in the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyLog {
+    /// Every key as typed.
+    #[default]
+    Full,
+    /// Text typed in insert mode replaced by placeholders.
+    Scrub,
+    /// No key logs at all; attempts keep their counts and times.
+    Off,
+}
+
+impl KeyLog {
+    /// `keys` as this policy saves them.
+    pub fn apply(self, keys: &str) -> String {
+        match self {
+            Self::Full => keys.to_string(),
+            Self::Scrub => notation::format(&commands::scrub_typed_text(&notation::tokenize(keys))),
+            Self::Off => String::new(),
+        }
+    }
+}
+
+static KEY_LOG: OnceLock<KeyLog> = OnceLock::new();
+
+/// Select the key log policy for the rest of the session. Only the first call
+/// has an effect; returns false when it selected another policy.
+pub fn set_key_log(key_log: KeyLog) -> bool {
+    *KEY_LOG.get_or_init(|| key_log) == key_log
+}
+
+fn key_log() -> KeyLog {
+    KEY_LOG.get().copied().unwrap_or_default()
+}
+
+static DECAY_DAYS: OnceLock<Option<u32>> = OnceLock::new();
+
+/// Show grades not refreshed within `days` days as decayed for the rest of the
+/// session; `None` keeps them all fresh. Only the first call has an effect;
+/// returns false when it selected another decay.
+pub fn set_decay_days(days: Option<u32>) -> bool {
+    *DECAY_DAYS.get_or_init(|| days) == days
+}
+
+fn decay_days() -> Option<u32> {
+    DECAY_DAYS.get().copied().flatten()
+}
+
+static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Keep the save files in `dir` for the rest of the session, e.g. a dotfiles
+/// repository, instead of the data directory; `None` keeps the default. Only
+/// the first call has an effect; returns false when it selected another one.
+pub fn set_dir(dir: Option<PathBuf>) -> bool {
+    match DIR.set(dir) {
+        Ok(()) => true,
+        Err(dir) => DIR.get() == Some(&dir),
+    }
+}
+
+/// Path of the save file of `profile`, or of the default save for `None`.
+pub fn save_path(profile: Option<&str>) -> PathBuf {
+    let local = PathBuf::from("save.json");
+    let dir = match DIR.get().and_then(Option::as_ref) {
+        Some(dir) => dir.clone(),
+        None if profile.is_none() && local.exists() => return local,
+        None => dirs::data_dir(),
+    };
+    match profile {
+        Some(name) => dir.join("profiles").join(format!("{name}.json")),
+        None => dir.join("save.json"),
     }
 }
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use ratatui::Frame;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::cast;
+use crate::challenge::Challenge;
+use crate::commands;
+use crate::diff::{self, DiffOp};
+use crate::editor::{self, EditorKind};
+use crate::keymap::{Action, KeyPress, Keymap};
+use crate::notation;
+use crate::nvim::{self, Snapshot};
+use crate::screen;
+use crate::theme;
+
+/// How long the headless editor gets to replay every step of a key log.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The steps of a key log: its commands in canonical notation.
+pub fn steps(keys: &str) -> Vec<String> {
+    commands::classify(&notation::tokenize(keys))
+        .iter()
+        .map(commands::Command::notation)
+        .collect()
+}
+
+/// Lines still to change before the buffer of `snapshot` is `target`: the
+/// lines to delete plus those to insert. 0 once the buffer matches.
+pub fn lines_to_go(snapshot: &Snapshot, target: &str) -> usize {
+    let buffer: Vec<&str> = snapshot.lines.iter().map(String::as_str).collect();
+    let target: Vec<&str> = target.lines().collect();
+    diff::diff(&buffer, &target)
+        .iter()
+        .filter(|op| !matches!(op, DiffOp::Equal(..)))
+        .count()
+}
+
+/// Step through a key log of `challenge` one command at a time, with the
+/// buffer after each and its diff against the target. The buffers are
+/// snapshots of a headless Neovim replaying the keys on the start buffer,
+/// which needs `nvim` even when playing in Vim.
+pub fn show_stepper(
+    terminal: &mut ratatui::DefaultTerminal,
+    launcher: &nvim::Launcher,
+    keymap: &Keymap,
+    challenge: &Challenge,
+    keys: &str,
+) -> std::io::Result<()> {
+    let challenge = &*challenge.loaded()?;
+    let steps = steps(keys);
+    let headless = match launcher.editor {
+        EditorKind::Neovim => launcher.clone(),
+        EditorKind::Vim => nvim::Launcher::default(),
+    };
+    terminal.draw(|frame| {
+        frame.render_widget(
+            Paragraph::new(format!(" Replaying {} commands...", steps.len()))
+                .style(Style::new().fg(Color::Gray)),
+            frame.area(),
+        );
+        theme::apply(frame.buffer_mut());
+    })?;
+    let snapshots = match nvim::snapshots(&headless, challenge, &steps, TIMEOUT) {
+        Ok(snapshots) if !snapshots.is_empty() => snapshots,
+        Ok(_) => {
+            return screen::show_error(
+                terminal,
+                "Stepping failed",
+                "The replay ended before the first snapshot.",
+                &[],
+            );
+        }
+        Err(e) => {
+            let hints = editor::launch_hints(&headless, Some(&challenge.id), &e);
+            return screen::show_error(terminal, "Stepping failed", &e.to_string(), &hints);
+        }
+    };
+
+    let mut at = 0;
+    let last = snapshots.len() - 1;
+    let mut pending: Vec<KeyPress> = Vec::new();
+    loop {
+        terminal.draw(|frame| {
+            render_stepper(frame, keymap, challenge, &steps, &snapshots, at);
+            theme::apply(frame.buffer_mut());
+        })?;
+
+        if let Some(key) = screen::poll_key(terminal)?
+            && key.kind == KeyEventKind::Press
+        {
+            match keymap.resolve(&mut pending, key.into()) {
+                Some(Action::Back | Action::Quit) => return Ok(()),
+                Some(Action::Down) => at = (at + 1).min(last),
+                Some(Action::Up) => at = at.saturating_sub(1),
+                Some(Action::Top) => at = 0,
+                Some(Action::Bottom) => at = last,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Draw step `at`: the commands with the current one highlighted, the buffer
+/// after it and its diff against the target.
+fn render_stepper(
+    frame: &mut Frame,
+    keymap: &Keymap,
+    challenge: &Challenge,
+    steps: &[String],
+    snapshots: &[Snapshot],
+    at: usize,
+) {
+    let [header, commands_area, panes, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(4),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [buffer_area, diff_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(panes);
+
+    let snapshot = &snapshots[at];
+    let to_go = lines_to_go(snapshot, &challenge.target().content);
+    let mut spans = vec![
+        Span::styled(
+            format!(" {}", cast::caption(at, steps)),
+            Style::new().add_modifier(Modifier::BOLD),
+        ),
+        match to_go {
+            0 => Span::styled("  matches the target", Style::new().fg(Color::Green)),
+            1 => Span::styled("  1 line to go", Style::new().fg(Color::Yellow)),
+            n => Span::styled(format!("  {n} lines to go"), Style::new().fg(Color::Yellow)),
+        },
+    ];
+    if snapshots.len() <= steps.len() {
+        spans.push(Span::styled(
+            format!(
+                "  (timed out after {} of {} commands)",
+                snapshots.len() - 1,
+                steps.len()
+            ),
+            Style::new().fg(Color::Red),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(spans))
+            .block(Block::bordered().title(format!(" Step through: {} ", challenge.title))),
+        header,
+    );
+
+    frame.render_widget(
+        Paragraph::new(command_line(steps, at))
+            .block(Block::bordered().title(" Commands "))
+            .wrap(Wrap { trim: false }),
+        commands_area,
+    );
+    frame.render_widget(
+        Paragraph::new(buffer_lines(snapshot)).block(Block::bordered().title(" Buffer ")),
+        buffer_area,
+    );
+    frame.render_widget(
+        Paragraph::new(diff_lines(snapshot, &challenge.target().content))
+            .block(Block::bordered().title(" Diff to target ")),
+        diff_area,
+    );
+
+    frame.render_widget(
+        Paragraph::new(format!(
+            " {}/{}: next/previous command | {}/{}: first/last | {}/{}: back",
+            keymap.label(Action::Down),
+            keymap.label(Action::Up),
+            keymap.label(Action::Top),
+            keymap.label(Action::Bottom),
+            keymap.label(Action::Back),
+            keymap.label(Action::Quit),
+        ))
+        .style(Style::new().fg(Color::DarkGray)),
+        footer,
+    );
+}
+
+/// The commands of the key log: those done in the default style, the one
+/// just done highlighted and those to come dimmed.
+fn command_line(steps: &[String], at: usize) -> Line<'static> {
+    let spans = (1..).zip(steps).flat_map(|(n, step)| {
+        let style = match n.cmp(&at) {
+            std::cmp::Ordering::Less => Style::new(),
+            std::cmp::Ordering::Equal => Style::new()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            std::cmp::Ordering::Greater => Style::new().fg(Color::DarkGray),
+        };
+        [Span::styled(step.clone(), style), Span::raw(" ")]
+    });
+    Line::from(spans.collect::<Vec<_>>())
+}
+
+/// The buffer of a snapshot with the cursor in reverse video.
+fn buffer_lines(snapshot: &Snapshot) -> Vec<Line<'static>> {
+    let [cursor_line, cursor_col] = snapshot.cursor;
+    (1..)
+        .zip(&snapshot.lines)
+        .map(|(n, line)| {
+            if n != cursor_line {
+                return Line::raw(line.clone());
+            }
+            let (before, at, after) = cast::split_at_cursor(line, cursor_col);
+            Line::from(vec![
+                Span::raw(before.to_string()),
+                Span::styled(
+                    at.unwrap_or(' ').to_string(),
+                    Style::new().add_modifier(Modifier::REVERSED),
+                ),
+                Span::raw(after.to_string()),
+            ])
+        })
+        .collect()
+}
+
+/// The buffer of a snapshot against the target: lines to delete in red, to
+/// insert in green, the rest dimmed.
+fn diff_lines(snapshot: &Snapshot, target: &str) -> Vec<Line<'static>> {
+    let buffer: Vec<&str> = snapshot.lines.iter().map(String::as_str).collect();
+    let target: Vec<&str> = target.lines().collect();
+    diff::diff(&buffer, &target)
+        .into_iter()
+        .map(|op| match op {
+            DiffOp::Equal(i, _) => {
+                Line::styled(format!("  {}", buffer[i]), Style::new().fg(Color::Gray))
+            }
+            DiffOp::Delete(i) => {
+                Line::styled(format!("- {}", buffer[i]), Style::new().fg(Color::Red))
+            }
+            DiffOp::Insert(j) => {
+                Line::styled(format!("+ {}", target[j]), Style::new().fg(Color::Green))
+            }
+        })
+        .collect()
+}
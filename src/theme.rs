@@ -0,0 +1,96 @@
+use std::sync::OnceLock;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Color theme for the TUI. Screens draw with the default palette and the
+/// active theme remaps the finished frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Colors as drawn, tuned for dark terminals.
+    #[default]
+    Default,
+    /// Swap light and dark grays for light terminal backgrounds.
+    Light,
+    /// No colors; bold and other modifiers are kept.
+    Mono,
+    /// A palette told apart with color vision deficiencies, with markers on
+    /// everything that is otherwise distinguished by hue alone.
+    Accessible,
+}
+
+static ACTIVE: OnceLock<Theme> = OnceLock::new();
+
+/// Select the theme for the rest of the session. Only the first call has an effect.
+pub fn set(theme: Theme) {
+    let _ = ACTIVE.set(theme);
+}
+
+pub fn active() -> Theme {
+    ACTIVE.get().copied().unwrap_or_default()
+}
+
+/// True when distinctions drawn in different hues must also differ in shape:
+/// a letter, a symbol or a modifier.
+pub fn markers() -> bool {
+    matches!(active(), Theme::Mono | Theme::Accessible)
+}
+
+/// `style` with `modifier` added when the active theme uses [`markers`].
+pub fn mark(style: Style, modifier: Modifier) -> Style {
+    if markers() {
+        style.add_modifier(modifier)
+    } else {
+        style
+    }
+}
+
+/// The theme to use for `configured`: `Mono` when `--no-color` is given or
+/// `NO_COLOR` is set to a non-empty value (<https://no-color.org>).
+pub fn resolve(configured: Theme, no_color: bool) -> Theme {
+    let env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color || env {
+        Theme::Mono
+    } else {
+        configured
+    }
+}
+
+/// Remap every cell of a drawn frame to the active theme. In `Mono`, cells with
+/// a background (selections, badges) are shown in reverse video instead.
+pub fn apply(buf: &mut Buffer) {
+    let theme = active();
+    if theme == Theme::Default {
+        return;
+    }
+    for cell in &mut buf.content {
+        if theme == Theme::Mono && cell.bg != Color::Reset {
+            cell.modifier |= Modifier::REVERSED;
+        }
+        cell.fg = remap(theme, cell.fg);
+        cell.bg = remap(theme, cell.bg);
+    }
+}
+
+fn remap(theme: Theme, color: Color) -> Color {
+    match (theme, color) {
+        (Theme::Mono, _) => Color::Reset,
+        (Theme::Light, Color::White) => Color::Black,
+        (Theme::Light, Color::Black) => Color::White,
+        (Theme::Light, Color::Gray) => Color::DarkGray,
+        (Theme::Light, Color::DarkGray) => Color::Gray,
+        (Theme::Light, Color::Yellow) => Color::Rgb(160, 120, 0),
+        (Theme::Light, Color::Cyan) => Color::Blue,
+        // Okabe-Ito colors, which stay apart for red-green deficiencies
+        (Theme::Accessible, Color::Red) => Color::Rgb(213, 94, 0),
+        (Theme::Accessible, Color::Green) => Color::Rgb(0, 158, 115),
+        (Theme::Accessible, Color::Yellow) => Color::Rgb(240, 228, 66),
+        (Theme::Accessible, Color::Blue) => Color::Rgb(0, 114, 178),
+        (Theme::Accessible, Color::Cyan) => Color::Rgb(86, 180, 233),
+        (Theme::Accessible, Color::Magenta) => Color::Rgb(204, 121, 167),
+        (Theme::Accessible, Color::Rgb(255, 165, 0)) => Color::Rgb(230, 159, 0),
+        (Theme::Default | Theme::Light | Theme::Accessible, c) => c,
+    }
+}
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::challenge::{Category, Challenge, Grade, Topic};
+use crate::state::GameState;
+
+/// Rule deciding when a category unlocks, based on the previous category.
+/// Written as `all`, a percentage such as `80%`, a minimum grade such as `C`, or `off`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum UnlockPolicy {
+    /// Every challenge completed.
+    #[default]
+    All,
+    /// At least this percentage of challenges completed.
+    Percent(u8),
+    /// Every challenge completed with at least this grade.
+    MinGrade(Grade),
+    /// Categories are never locked.
+    Off,
+}
+
+impl FromStr for UnlockPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let grade = |c: &str| {
+            [Grade::A, Grade::B, Grade::C, Grade::D, Grade::E, Grade::F]
+                .into_iter()
+                .find(|g| g.display_char().eq_ignore_ascii_case(c))
+        };
+        if let Some(pct) = s.strip_suffix('%') {
+            return match pct.trim().parse() {
+                Ok(p @ 0..=100) => Ok(Self::Percent(p)),
+                _ => Err(format!("invalid unlock percentage '{s}' (use 0% to 100%)")),
+            };
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "off" | "none" => Ok(Self::Off),
+            _ => grade(s).map(Self::MinGrade).ok_or_else(|| {
+                format!(
+                    "invalid unlock policy '{s}' \
+                     (use all, off, a percentage like 80% or a grade like C)"
+                )
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for UnlockPolicy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Whether a category is unlocked under `policy`. The tutorial, beginner and
+/// freestyle categories, packs and collections are always open; the others are
+/// judged on the category before them, whose completions honor required
+/// techniques.
+pub fn is_category_unlocked(
+    cat: Category,
+    topics: &[Topic],
+    state: &GameState,
+    policy: UnlockPolicy,
+) -> bool {
+    let prev = match cat {
+        Category::Tutorial
+        | Category::Beginner
+        | Category::Freestyle
+        | Category::Pack
+        | Category::Collection => return true,
+        Category::Intermediate => Category::Beginner,
+        Category::Advanced => Category::Intermediate,
+        Category::Legendary => Category::Advanced,
+    };
+    let mut challenges = topics
+        .iter()
+        .filter(|t| Category::for_topic(t.id) == prev)
+        .flat_map(|t| t.challenges.iter());
+    match policy {
+        UnlockPolicy::Off => true,
+        UnlockPolicy::All => challenges.all(|c| state.is_completed(&c.id)),
+        UnlockPolicy::MinGrade(min) => challenges.all(|c| {
+            state.is_completed(&c.id)
+                && state
+                    .best_grade(&c.id)
+                    .is_some_and(|g| g.rank() <= min.rank())
+        }),
+        UnlockPolicy::Percent(pct) => {
+            let (total, completed) = challenges.fold((0usize, 0usize), |(t, d), c| {
+                (t + 1, d + usize::from(state.is_completed(&c.id)))
+            });
+            completed * 100 >= total * usize::from(pct)
+        }
+    }
+}
+
+/// A challenge is unlocked once every challenge it `requires` has been completed.
+pub fn is_challenge_unlocked(challenge: &Challenge, state: &GameState, unlock_all: bool) -> bool {
+    unlock_all || challenge.requires.iter().all(|id| state.is_completed(id))
+}
+
+/// Required challenge IDs that are not completed yet, in `requires` order.
+pub fn missing_requirements<'a>(challenge: &'a Challenge, state: &GameState) -> Vec<&'a str> {
+    challenge
+        .requires
+        .iter()
+        .map(String::as_str)
+        .filter(|id| !state.is_completed(id))
+        .collect()
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+use crate::game;
+use crate::nvim::{ChallengeResult, Outcome};
+
+/// File name of the versus ledger in the data directory.
+pub const LEDGER_FILE: &str = "versus.json";
+
+/// Path of the versus ledger, kept apart from the save files so matches with
+/// friends leave no trace in anyone's progress.
+pub fn ledger_path() -> PathBuf {
+    dirs::data_dir().join(LEDGER_FILE)
+}
+
+/// One player's run at the challenge of a match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Turn {
+    pub player: String,
+    /// The target was reached without a forbidden key.
+    pub solved: bool,
+    pub keystrokes: u32,
+    pub time_ms: u32,
+}
+
+impl Turn {
+    /// `player`'s turn from the result of the run. Quitting the editor counts
+    /// as not solved.
+    pub fn of(player: &str, result: &ChallengeResult) -> Self {
+        Self {
+            player: player.to_string(),
+            solved: result.outcome != Outcome::Aborted
+                && result.buffer_matches
+                && result.violation.is_none(),
+            keystrokes: result.keystrokes,
+            time_ms: result.elapsed_ms,
+        }
+    }
+}
+
+/// Index of the winning turn: the fewest keystrokes among the solved ones,
+/// then the faster time. `None` for a draw or when nobody solved it.
+pub fn winner(turns: &[Turn]) -> Option<usize> {
+    let key = |t: &Turn| (t.keystrokes, t.time_ms);
+    let best = turns.iter().filter(|t| t.solved).map(key).min()?;
+    let mut leaders = (0..turns.len()).filter(|&i| turns[i].solved && key(&turns[i]) == best);
+    let first = leaders.next()?;
+    leaders.next().is_none().then_some(first)
+}
+
+/// The running score of a group of players.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    /// Matches won per player.
+    #[serde(default)]
+    pub wins: BTreeMap<String, u32>,
+    /// Matches without a single winner.
+    #[serde(default)]
+    pub draws: u32,
+}
+
+impl Score {
+    /// Matches played by the group.
+    pub fn played(&self) -> u32 {
+        self.wins.values().sum::<u32>() + self.draws
+    }
+}
+
+/// Running scores of every group of players that met, by [`pairing`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    #[serde(default)]
+    pub matches: BTreeMap<String, Score>,
+}
+
+/// Ledger key of a group of players, the same whatever order they took
+/// turns in: `ada vs bob`.
+pub fn pairing(players: &[String]) -> String {
+    let mut names: Vec<&str> = players.iter().map(String::as_str).collect();
+    names.sort_unstable();
+    names.join(" vs ")
+}
+
+impl Ledger {
+    /// Load the ledger from `path`. A missing file is an empty ledger.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(io::Error::from),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n")
+    }
+
+    /// Count a match in the score of its players and return that score.
+    pub fn record(&mut self, turns: &[Turn]) -> &Score {
+        let players: Vec<String> = turns.iter().map(|t| t.player.clone()).collect();
+        let score = self.matches.entry(pairing(&players)).or_default();
+        for player in &players {
+            score.wins.entry(player.clone()).or_default();
+        }
+        match winner(turns) {
+            Some(i) => *score.wins.entry(players[i].clone()).or_default() += 1,
+            None => score.draws += 1,
+        }
+        score
+    }
+}
+
+/// The versus summary: each turn's keystrokes and time, the winner of the
+/// match and the running score of the players.
+pub fn summary(turns: &[Turn], score: &Score) -> Vec<String> {
+    let mut lines = results(turns);
+    let wins: Vec<String> = score
+        .wins
+        .iter()
+        .map(|(player, wins)| format!("{player} {wins}"))
+        .collect();
+    lines.push(format!(
+        "Match score after {} played: {}, draws {}",
+        score.played(),
+        wins.join(", "),
+        score.draws
+    ));
+    lines
+}
+
+/// Each turn's keystrokes and time with the winner marked, or a draw.
+pub fn results(turns: &[Turn]) -> Vec<String> {
+    let width = turns.iter().map(|t| t.player.len()).max().unwrap_or(0);
+    let winner = winner(turns);
+    let mut lines: Vec<String> = turns
+        .iter()
+        .enumerate()
+        .map(|(i, turn)| {
+            let result = if turn.solved {
+                format!(
+                    "{:>4} keystrokes  {}",
+                    turn.keystrokes,
+                    game::format_time(turn.time_ms)
+                )
+            } else {
+                "not solved".to_string()
+            };
+            let mark = if winner == Some(i) { "  wins" } else { "" };
+            format!("{:<width$}  {result}{mark}", turn.player)
+        })
+        .collect();
+    if winner.is_none() {
+        lines.push("Draw".to_string());
+    }
+    lines
+}
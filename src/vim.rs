@@ -0,0 +1,622 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::challenge::{Challenge, Grade, Setup};
+use crate::editor::{Editor, LaunchPlan, SessionOptions};
+use crate::error::Error;
+use crate::nvim::{self, ChallengeResult, Launcher, Outcome, fnameescape, normalize};
+
+/// Classic Vim sessions, driven by a vimscript runtime. Vim has no `on_key`, so
+/// keys are logged with `-W` (scriptout) and counted once Vim exits. Hardcore
+/// mode can't stop a session early either; runs over budget fail afterwards.
+pub struct Vim<'a>(pub &'a Launcher);
+
+impl Editor for Vim<'_> {
+    fn run_challenge(
+        &self,
+        challenge: &Challenge,
+        number: usize,
+    ) -> Result<ChallengeResult, Error> {
+        let dir = nvim::session_dir("vim")?;
+        self.plan_challenge(challenge, number, dir.path())
+            .run(self.0)?;
+
+        let content = nvim::read_session_file(&dir.path().join("challenge_buffer"))?;
+        let mut keys = scriptout_keys(&fs::read(dir.path().join("keys")).unwrap_or_default());
+        // The briefing ends on the first Enter
+        if self.0.session.briefing
+            && let Some(pos) = keys.iter().position(|k| k == "<CR>")
+        {
+            keys.drain(..=pos);
+        }
+        let mut results = read_results(&dir.path().join("results"));
+        results.macro_keys = fs::read(dir.path().join("macro"))
+            .ok()
+            .map(|bytes| scriptout_keys(&bytes).concat());
+        let mut result = evaluate(challenge, &content, keys, &results);
+        if self.0.session.hardcore
+            && challenge.has_key_budget()
+            && result.outcome.is_attempt()
+            && result.keystrokes > challenge.threshold(Grade::F)
+        {
+            result.outcome = Outcome::OverBudget;
+            result.buffer_matches = false;
+        }
+        Ok(result)
+    }
+
+    fn plan_challenge(&self, challenge: &Challenge, number: usize, dir: &Path) -> LaunchPlan {
+        let buffer = dir.join("challenge_buffer");
+        let target = dir.join("challenge_target");
+        let results = dir.join("results");
+        let keys = dir.join("keys");
+        let recorded_macro = dir.join("macro");
+        let script = dir.join("runtime.vim");
+
+        let mut files = vec![
+            (buffer.clone(), challenge.start().content.clone()),
+            (target.clone(), challenge.target().content.clone()),
+            (
+                script.clone(),
+                build_vim_script(
+                    challenge,
+                    number,
+                    &self.0.session,
+                    &target,
+                    &results,
+                    &recorded_macro,
+                ),
+            ),
+        ];
+
+        let filetype = challenge
+            .filetype
+            .as_deref()
+            .map_or_else(String::new, |ft| format!(" | setlocal filetype={ft}"));
+
+        // Open the instructions (the description, and the hint in blind mode) and
+        // the target in horizontal splits above the challenge buffer, read-only and
+        // labeled. Blind challenges have no target split.
+        let instructions = match challenge.instructions() {
+            Some(text) => {
+                let path = dir.join("instructions");
+                let split = format!(
+                    "split {} | setlocal readonly nomodifiable noswapfile buftype=nofile wrap | \
+                     let &l:statusline = '  [INSTRUCTIONS]' | resize {} | wincmd j | ",
+                    fnameescape(&path),
+                    text.lines().count() + 1
+                );
+                files.push((path, text));
+                split
+            }
+            None => String::new(),
+        };
+        let split = if challenge.blind {
+            format!("{}{filetype}", instructions.trim_end_matches(" | "))
+        } else {
+            format!(
+                "{instructions}split {} | \
+                 setlocal readonly nomodifiable noswapfile buftype=nofile{filetype} | \
+                 let &l:statusline = '  [TARGET]' | \
+                 diffthis | set diffopt+=context:99999 | setlocal wrap nocursorbind | \
+                 wincmd j{filetype} | diffthis | setlocal wrap nocursorbind",
+                fnameescape(&target)
+            )
+        };
+
+        let mut command = Command::new(&self.0.bin);
+        command
+            .args(&self.0.args)
+            .arg("--cmd")
+            .arg("set noswapfile noundofile nobackup nowritebackup")
+            // Log every typed key
+            .arg("-W")
+            .arg(&keys)
+            .arg("-c")
+            .arg(split)
+            .arg("-c")
+            .arg(format!("source {}", fnameescape(&script)))
+            .arg(&buffer);
+        LaunchPlan {
+            files,
+            command,
+            stderr: dir.join("stderr"),
+        }
+    }
+
+    fn run_sandbox(&self, challenge: Option<&Challenge>) -> Result<(), Error> {
+        let dir = nvim::session_dir("vim_sandbox")?;
+        let buffer = dir.path().join("sandbox_buffer");
+        let script = dir.path().join("sandbox.vim");
+
+        nvim::write_session_file(
+            &buffer,
+            challenge.map_or("", |c| c.start().content.as_str()),
+        )?;
+        let title = challenge.map_or("blank buffer", |c| c.title.as_str());
+        nvim::write_session_file(
+            &script,
+            &format!(
+                "{setup}\
+                 call VkApplySetup()\n\
+                 {cursor}\
+                 set laststatus=2\n\
+                 let &l:statusline = {status}\n\
+                 autocmd BufWritePost <buffer> qall!\n",
+                setup = setup_vim(challenge.map_or(&Setup::default(), |c| &c.setup)),
+                cursor = challenge
+                    .and_then(|c| c.start().cursor)
+                    .map_or_else(String::new, |[l, c]| format!("call cursor({l}, {c})\n")),
+                status = vim_string(&format!(
+                    "  [SANDBOX] {} | :w to leave",
+                    title.replace('%', "%%")
+                )),
+            ),
+        )?;
+
+        let filetype = challenge
+            .and_then(|c| c.filetype.as_deref())
+            .map_or_else(String::new, |ft| format!("setlocal filetype={ft} | "));
+
+        self.0.run_logged(
+            Command::new(&self.0.bin)
+                .args(&self.0.args)
+                .arg("--cmd")
+                .arg("set noswapfile noundofile nobackup nowritebackup")
+                .arg("-c")
+                .arg(format!("{filetype}source {}", fnameescape(&script)))
+                .arg(&buffer),
+            &dir.path().join("stderr"),
+        )
+    }
+}
+
+/// Escape a string as a vimscript double-quoted string literal.
+pub fn vim_string(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{escaped}\"")
+}
+
+/// A `VkApplySetup()` function applying a challenge `[setup]` to the current buffer.
+pub fn setup_vim(setup: &Setup) -> String {
+    let options = setup
+        .options
+        .iter()
+        .map(|option| format!("  execute 'setlocal ' . {}\n", vim_string(option)));
+    let registers = setup.registers.iter().map(|(name, content)| {
+        format!(
+            "  call setreg({}, {})\n",
+            vim_string(name),
+            vim_string(content)
+        )
+    });
+    let marks = setup.marks.iter().map(|(name, [line, col])| {
+        format!(
+            "  silent! call setpos(\"'\" . {}, [0, {line}, {col}, 0])\n",
+            vim_string(name)
+        )
+    });
+    let mut body: String = options.chain(registers).chain(marks).collect();
+    if !setup.quickfix.is_empty() {
+        let items: Vec<String> = setup
+            .quickfix
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{'bufnr': bufnr('%'), 'lnum': {}, 'col': {}, 'text': {}}}",
+                    entry.line,
+                    entry.col,
+                    vim_string(&entry.text)
+                )
+            })
+            .collect();
+        let _ = writeln!(
+            body,
+            "  call setqflist([], ' ', {{'title': 'nvimkata', 'items': [{}]}})",
+            items.join(", ")
+        );
+    }
+    format!("function! VkApplySetup() abort\n{body}endfunction\n")
+}
+
+/// Format strings as a vimscript list literal.
+fn vim_list<S: AsRef<str>>(items: &[S]) -> String {
+    let quoted: Vec<String> = items.iter().map(|item| vim_string(item.as_ref())).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// Format an optional `[line, column]` cursor as a vimscript list, empty for none.
+fn vim_cursor(cursor: Option<[u32; 2]>) -> String {
+    cursor.map_or_else(|| "[]".to_string(), |[l, c]| format!("[{l}, {c}]"))
+}
+
+/// Build the full vimscript runtime by prepending variable definitions to the template.
+fn build_vim_script(
+    challenge: &Challenge,
+    number: usize,
+    options: &SessionOptions,
+    target: &Path,
+    results: &Path,
+    recorded_macro: &Path,
+) -> String {
+    let preamble = format!(
+        "let g:vk_number = {number}\n\
+         let g:vk_title = {title}\n\
+         let g:vk_par = {par}\n\
+         let g:vk_hints = {hints}\n\
+         let g:vk_hint_spans = {hint_spans:?}\n\
+         let g:vk_intro = {intro}\n\
+         let g:vk_freestyle = {freestyle}\n\
+         let g:vk_reference = {reference}\n\
+         let g:vk_tutorial = {tutorial}\n\
+         let g:vk_blind = {blind}\n\
+         let g:vk_reveal_ms = {reveal_ms}\n\
+         let g:vk_single_command = {single_command}\n\
+         let g:vk_macro_register = '{macro_register}'\n\
+         let g:vk_macro_replays = {macro_replays}\n\
+         let g:vk_shell_allowed = {shell_allowed}\n\
+         let g:vk_briefing = {briefing}\n\
+         let g:vk_banner_path = {banner}\n\
+         let g:vk_results_path = {results}\n\
+         let g:vk_target_path = {target}\n\
+         let g:vk_macro_path = {macro_path}\n\
+         let g:vk_start_cursor = {start_cursor}\n\
+         let g:vk_target_cursor = {target_cursor}\n\
+         {setup}",
+        title = vim_string(&challenge.title),
+        par = challenge.par(),
+        hints = vim_list(&challenge.hint_tiers()),
+        hint_spans = challenge.hint_spans(),
+        intro = vim_list(
+            &challenge
+                .intro
+                .as_deref()
+                .map_or_else(Vec::new, |intro| intro.trim_end().lines().collect())
+        ),
+        freestyle = u8::from(challenge.is_freestyle()),
+        reference = challenge.reference_keystrokes.unwrap_or(0),
+        tutorial = u8::from(challenge.is_tutorial()),
+        blind = u8::from(challenge.blind),
+        reveal_ms = challenge.reveal_secs.map_or(0, |s| u64::from(s) * 1000),
+        single_command = u8::from(challenge.single_command),
+        macro_register = challenge
+            .recorded_macro
+            .as_ref()
+            .map_or_else(String::new, |spec| spec.register.to_string()),
+        macro_replays = challenge
+            .recorded_macro
+            .as_ref()
+            .map_or(0, |spec| spec.replays),
+        shell_allowed = options
+            .shell
+            .allowed_programs()
+            .map_or_else(|| "v:null".to_string(), vim_list),
+        briefing = u8::from(options.briefing),
+        banner = vim_string(
+            &options
+                .banner
+                .as_ref()
+                .map_or_else(String::new, |path| path.display().to_string())
+        ),
+        results = vim_string(&results.display().to_string()),
+        target = vim_string(&target.display().to_string()),
+        macro_path = vim_string(&recorded_macro.display().to_string()),
+        start_cursor = vim_cursor(challenge.start().cursor),
+        target_cursor = vim_cursor(challenge.target().cursor),
+        setup = setup_vim(&challenge.setup),
+    );
+    format!("{preamble}\n{}", include_str!("challenge_runtime.vim"))
+}
+
+/// Values written by the vimscript runtime when a session ends.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct VimResults {
+    elapsed_ms: u32,
+    /// Final cursor as `[line, column]`, both 1-based.
+    cursor: Option<[u32; 2]>,
+    /// The command that solved a single-command challenge.
+    command: Option<String>,
+    /// The macro that solved a macro challenge, read from its own file.
+    macro_keys: Option<String>,
+    shell_commands: Vec<String>,
+    /// Deepest hint tier revealed.
+    hints_used: u32,
+    outcome: Outcome,
+}
+
+/// Read the results file written by the vimscript runtime.
+/// Format: elapsed milliseconds, followed by `cursor=line,col`,
+/// `outcome=completed|submitted`, `command=cmd`, `shell=cmd` and `hints=n` lines.
+fn read_results(path: &Path) -> VimResults {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let mut lines = contents.lines();
+    let mut results = VimResults {
+        elapsed_ms: lines
+            .next()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0),
+        ..VimResults::default()
+    };
+    for line in lines {
+        match line.split_once('=') {
+            Some(("cursor", value)) => {
+                results.cursor = value
+                    .split_once(',')
+                    .and_then(|(l, c)| Some([l.trim().parse().ok()?, c.trim().parse().ok()?]));
+            }
+            Some(("outcome", value)) => {
+                results.outcome = Outcome::parse(value.trim()).unwrap_or_default();
+            }
+            Some(("command", value)) => results.command = Some(value.to_string()),
+            Some(("shell", value)) => results.shell_commands.push(value.to_string()),
+            Some(("hints", value)) => results.hints_used = value.trim().parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    results
+}
+
+/// Grade a session from the final buffer `content` and the logged `keys`.
+fn evaluate(
+    challenge: &Challenge,
+    content: &str,
+    mut keys: Vec<String>,
+    results: &VimResults,
+) -> ChallengeResult {
+    // Leave the `:w` that ended the session out of the count
+    if results.outcome == Outcome::SubmittedEarly
+        && let Some(pos) = keys.iter().rposition(|k| k == ":")
+    {
+        keys.truncate(pos);
+    }
+    let keys = keys.concat();
+    // A single command is graded on its length, with its `:` and Enter
+    // A macro is graded on its own keys plus its invocations
+    let keystrokes = match (
+        &results.command,
+        &results.macro_keys,
+        &challenge.recorded_macro,
+    ) {
+        (Some(command), _, _) if challenge.single_command => command.chars().count() + 2,
+        (_, Some(macro_keys), Some(spec)) => spec.keystrokes(macro_keys, &keys),
+        _ => crate::challenge::count_keystrokes(&keys),
+    };
+    let keystrokes = u32::try_from(keystrokes).unwrap_or(u32::MAX);
+
+    let buffer_matches = results.outcome.can_match()
+        && (!challenge.single_command || results.command.is_some())
+        && (challenge.recorded_macro.is_none() || results.macro_keys.is_some())
+        && if challenge.is_tutorial() {
+            challenge.tutorial_steps_done(&keys) == challenge.tutorial_steps.len()
+        } else {
+            challenge
+                .target()
+                .cursor
+                .is_none_or(|target| results.cursor == Some(target))
+                && normalize(content) == normalize(&challenge.target().content)
+        };
+    ChallengeResult {
+        buffer_matches,
+        keystrokes,
+        elapsed_ms: results.elapsed_ms,
+        violation: challenge.violation_in(&keys),
+        technique_missing: !challenge.technique_used(&keys),
+        keys,
+        shell_commands: results.shell_commands.clone(),
+        hints_used: results.hints_used,
+        outcome: results.outcome,
+    }
+}
+
+/// Vim's escape byte for special keys in typed input.
+const K_SPECIAL: u8 = 0x80;
+/// A modifier mask for the next key follows.
+const KS_MODIFIER: u8 = 0xfc;
+/// An editor-internal key follows (mouse, focus, ...).
+const KS_EXTRA: u8 = 0xfd;
+/// An escaped `K_SPECIAL` byte.
+const KS_SPECIAL: u8 = 0xfe;
+/// An escaped NUL byte.
+const KS_ZERO: u8 = 0xff;
+
+/// One item of a decoded scriptout log.
+enum Item {
+    Byte(u8),
+    Key(String),
+    Modifiers(u8),
+}
+
+/// Name of a special key given its termcap code.
+fn termcap_key(code: [u8; 2]) -> Option<&'static str> {
+    Some(match &code {
+        b"ku" => "Up",
+        b"kd" => "Down",
+        b"kl" => "Left",
+        b"kr" => "Right",
+        b"kb" => "BS",
+        b"kD" => "Del",
+        b"kI" => "Insert",
+        b"kh" => "Home",
+        b"@7" => "End",
+        b"kP" => "PageUp",
+        b"kN" => "PageDown",
+        b"k1" => "F1",
+        b"k2" => "F2",
+        b"k3" => "F3",
+        b"k4" => "F4",
+        b"k5" => "F5",
+        b"k6" => "F6",
+        b"k7" => "F7",
+        b"k8" => "F8",
+        b"k9" => "F9",
+        b"k;" => "F10",
+        b"F1" => "F11",
+        b"F2" => "F12",
+        _ => return None,
+    })
+}
+
+/// Name of a control character, as `keytrans()` writes it.
+fn control_key(byte: u8) -> String {
+    match byte {
+        0x09 => "<Tab>".to_string(),
+        0x0a => "<NL>".to_string(),
+        0x0d => "<CR>".to_string(),
+        0x1b => "<Esc>".to_string(),
+        0x7f => "<BS>".to_string(),
+        _ => format!("<C-{}>", char::from(byte + b'@')),
+    }
+}
+
+/// Apply a modifier mask to a key, e.g. `<Left>` with Ctrl to `<C-Left>`.
+fn with_modifiers(key: &str, mask: u8) -> String {
+    let prefix: String = [(0x08, "M-"), (0x04, "C-"), (0x02, "S-")]
+        .iter()
+        .filter(|(bit, _)| mask & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    match key.strip_prefix('<').and_then(|k| k.strip_suffix('>')) {
+        Some(name) if key.len() > 1 => format!("<{prefix}{name}>"),
+        _ => format!("<{prefix}{key}>"),
+    }
+}
+
+/// Split a Vim scriptout log (`-w`/`-W`) into one key per item in `keytrans()`
+/// notation, so it can be counted and searched like neovim's key log. `<F1>`,
+/// which shows the hint, and editor-internal events are left out.
+pub fn scriptout_keys(log: &[u8]) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < log.len() {
+        if log[i] == K_SPECIAL && i + 2 < log.len() {
+            let code = [log[i + 1], log[i + 2]];
+            i += 3;
+            match code[0] {
+                KS_SPECIAL => items.push(Item::Byte(K_SPECIAL)),
+                KS_ZERO => items.push(Item::Key("<Nul>".to_string())),
+                KS_MODIFIER => items.push(Item::Modifiers(code[1])),
+                KS_EXTRA => {}
+                _ => {
+                    if let Some(name) = termcap_key(code) {
+                        items.push(Item::Key(format!("<{name}>")));
+                    }
+                }
+            }
+        } else {
+            items.push(Item::Byte(log[i]));
+            i += 1;
+        }
+    }
+
+    let mut keys = Vec::new();
+    let mut modifiers = 0;
+    let mut bytes = Vec::new();
+    let mut items = items.into_iter().peekable();
+    while let Some(item) = items.next() {
+        let key = match item {
+            Item::Modifiers(mask) => {
+                modifiers = mask;
+                continue;
+            }
+            Item::Key(key) => key,
+            Item::Byte(byte) if byte < 0x20 || byte == 0x7f => control_key(byte),
+            Item::Byte(byte) => {
+                // Collect the rest of a UTF-8 sequence
+                bytes.clear();
+                bytes.push(byte);
+                while let Some(Item::Byte(next)) = items.peek()
+                    && (0x80..0xc0).contains(next)
+                {
+                    bytes.push(*next);
+                    items.next();
+                }
+                match String::from_utf8_lossy(&bytes).as_ref() {
+                    " " => "<Space>".to_string(),
+                    "<" => "<lt>".to_string(),
+                    c => c.to_string(),
+                }
+            }
+        };
+        let key = if modifiers == 0 {
+            key
+        } else {
+            with_modifiers(&key, modifiers)
+        };
+        modifiers = 0;
+        if !matches!(key.as_str(), "<F1>" | "<F3>" | "<F4>") {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(log: &[u8]) -> String {
+        scriptout_keys(log).concat()
+    }
+
+    #[test]
+    fn test_scriptout_plain_and_control() {
+        assert_eq!(keys(b"ciwfoo\x1b"), "ciwfoo<Esc>");
+        assert_eq!(keys(b":w\r"), ":w<CR>");
+        assert_eq!(keys(b"i \x09<\x17"), "i<Space><Tab><lt><C-W>");
+        assert_eq!(scriptout_keys(b"dd\x1b").len(), 3);
+    }
+
+    #[test]
+    fn test_scriptout_special_keys() {
+        assert_eq!(keys(b"\x80kl\x80kr\x80kb"), "<Left><Right><BS>");
+        assert_eq!(keys(b"\x80\xfc\x04\x80kl"), "<C-Left>");
+        assert_eq!(keys(b"\x80\xfc\x08x"), "<M-x>");
+        // F1, F3, F4 and editor-internal events are not keys
+        assert_eq!(keys(b"x\x80k1\x80\xfd\x35y\x80k3\x80k4"), "xy");
+    }
+
+    #[test]
+    fn test_scriptout_utf8() {
+        assert_eq!(
+            scriptout_keys("iä€\x1b".as_bytes()),
+            ["i", "ä", "€", "<Esc>"]
+        );
+        // A 0x80 byte inside a character is escaped
+        assert_eq!(keys(b"\xe2\x82\x80\xfe\x58"), "₀");
+    }
+
+    #[test]
+    fn test_read_results() {
+        let path = std::env::temp_dir().join("rlv_vim_results");
+        fs::write(
+            &path,
+            "1500\ncursor=2,3\noutcome=completed\ncommand=%s/a=b/c/g\nshell=!ls\nhints=1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_results(&path),
+            VimResults {
+                elapsed_ms: 1500,
+                cursor: Some([2, 3]),
+                command: Some("%s/a=b/c/g".to_string()),
+                macro_keys: None,
+                shell_commands: vec!["!ls".to_string()],
+                hints_used: 1,
+                outcome: Outcome::Completed,
+            }
+        );
+        let _ = fs::remove_file(&path);
+        assert_eq!(read_results(&path), VimResults::default());
+    }
+
+    #[test]
+    fn test_vim_string() {
+        assert_eq!(vim_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
@@ -0,0 +1,49 @@
+use nvimkata::animation::{self, Clock, REVEAL_FRAMES};
+use nvimkata::challenge::Grade;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+
+#[test]
+fn test_bar_fills_to_the_total() {
+    assert_eq!(animation::portion(0, 12), 0);
+    assert_eq!(animation::portion(REVEAL_FRAMES / 2, 12), 6);
+    assert_eq!(animation::portion(REVEAL_FRAMES, 12), 12);
+    assert_eq!(animation::portion(REVEAL_FRAMES + 5, 12), 12);
+}
+
+#[test]
+fn test_grade_climbs_from_f() {
+    assert_eq!(animation::climbing_grade(0, Grade::A), Grade::F);
+    assert_eq!(
+        animation::climbing_grade(REVEAL_FRAMES / 2, Grade::B),
+        Grade::D
+    );
+    assert_eq!(animation::climbing_grade(REVEAL_FRAMES, Grade::A), Grade::A);
+    assert_eq!(animation::climbing_grade(0, Grade::F), Grade::F);
+}
+
+#[test]
+fn test_skip_jumps_to_the_end() {
+    let mut clock = Clock::start(REVEAL_FRAMES);
+    assert!(clock.running());
+    clock.skip();
+    assert!(!clock.running());
+    assert_eq!(clock.frame(), REVEAL_FRAMES);
+}
+
+#[test]
+fn test_confetti_after_the_reveal_on_blank_cells() {
+    let area = Rect::new(0, 0, 20, 5);
+    let mut drawn = Buffer::empty(area);
+    drawn.set_string(0, 0, "x".repeat(20), Style::new());
+    let confetti = |frame| {
+        let mut buf = drawn.clone();
+        animation::draw_confetti(&mut buf, area, frame);
+        buf
+    };
+    assert_eq!(confetti(REVEAL_FRAMES - 1), drawn);
+    let buf = confetti(REVEAL_FRAMES);
+    assert!((0..20).all(|x| buf[(x, 0)].symbol() == "x"));
+    assert!(buf.content.iter().any(|c| !matches!(c.symbol(), " " | "x")));
+}
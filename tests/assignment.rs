@@ -0,0 +1,104 @@
+use std::fs;
+
+use nvimkata::assignment::{self, Assignment};
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::goals::parse_day;
+use nvimkata::state::GameState;
+
+fn challenge(id: &str) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"Test {id}\"\ntopic = \"registers\"\n\
+         difficulty = 1\npar_keystrokes = 10\n[start]\ncontent = \"a\"\n\
+         [target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn assignment(min_grade: Option<Grade>) -> Assignment {
+    Assignment {
+        title: "Week 1".to_string(),
+        description: String::new(),
+        due: parse_day("2026-11-01"),
+        min_grade,
+        challenges: vec![challenge("r1"), challenge("r2"), challenge("r3")],
+    }
+}
+
+#[test]
+fn test_load_assignments() {
+    let tmp = std::env::temp_dir().join("rlv_test_assignments");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(&tmp).unwrap();
+    fs::write(
+        tmp.join("week1.toml"),
+        r#"
+title = "Week 1"
+due = "2026-11-01"
+min_grade = "C"
+challenges = ["r2", "r1", "gone"]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp.join("week2.toml"),
+        r#"
+title = "Week 2"
+due = "November"
+challenges = ["r1"]
+"#,
+    )
+    .unwrap();
+
+    let topics = vec![Topic {
+        id: 3,
+        name: "Registers".to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: vec![challenge("r1"), challenge("r2")],
+    }];
+    let assignments = assignment::load_assignments(&tmp, &topics);
+    assert_eq!(assignments.len(), 1);
+    let week1 = &assignments[0];
+    assert_eq!(week1.title, "Week 1");
+    assert_eq!(week1.due, parse_day("2026-11-01"));
+    assert_eq!(week1.min_grade, Some(Grade::C));
+    let ids: Vec<&str> = week1.challenges.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["r2", "r1"]);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_min_grade_decides_progress() {
+    let mut state = GameState::default();
+    state.record_result("r1", Grade::B, 12, 10, "keys", "1.0.0");
+    state.record_result("r2", Grade::D, 25, 10, "keys", "1.0.0");
+
+    let graded = assignment(Some(Grade::C));
+    assert_eq!(graded.done_count(&state), 1);
+    let any = assignment(None);
+    assert_eq!(any.done_count(&state), 2);
+    assert!(!any.is_complete(&state));
+
+    let due = parse_day("2026-11-01").unwrap();
+    assert!(!any.is_overdue(&state, due));
+    assert!(any.is_overdue(&state, due + 1));
+    state.record_result("r3", Grade::F, 40, 10, "keys", "1.0.0");
+    assert!(!any.is_overdue(&state, due + 1));
+}
+
+#[test]
+fn test_report_lists_each_challenge() {
+    let mut state = GameState::default();
+    state.record_result("r1", Grade::B, 12, 10, "keys", "1.0.0");
+    state.record_result("r2", Grade::D, 25, 10, "keys", "1.0.0");
+
+    let today = parse_day("2026-11-03").unwrap();
+    let report = assignment::report(&[assignment(Some(Grade::C))], &state, today);
+    assert!(report.contains("Generated: 2026-11-03"));
+    assert!(report.contains("## Week 1"));
+    assert!(report.contains("Due: 2026-11-01 | Minimum grade: C | Progress: 1/3 (overdue)"));
+    assert!(report.contains("- [x] r1 Test r1: B, 12 keys"));
+    assert!(report.contains("- [ ] r2 Test r2: D, 25 keys"));
+    assert!(report.contains("- [ ] r3 Test r3: not attempted"));
+}
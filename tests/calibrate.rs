@@ -0,0 +1,95 @@
+use nvimkata::calibrate::{self, Verdict};
+use nvimkata::challenge::{Challenge, Grade};
+use nvimkata::state::{AttemptRecord, GameState};
+
+fn challenge(par: u32) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"c\"\nversion = \"1\"\ntitle = \"Change a word\"\ntopic = \"t\"\n\
+         difficulty = 1\npar_keystrokes = {par}\n[start]\ncontent = \"a\"\n\
+         [target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn save(keystrokes: &[u32]) -> GameState {
+    let mut state = GameState::default();
+    state.history.insert(
+        "c".to_string(),
+        keystrokes
+            .iter()
+            .map(|&keystrokes| AttemptRecord {
+                grade: Grade::C,
+                keystrokes,
+                time_ms: Some(1000),
+                keys: String::new(),
+                technique_missing: false,
+                timestamp: None,
+            })
+            .collect(),
+    );
+    state
+}
+
+#[test]
+fn test_attempts_are_pooled_from_every_save() {
+    let saves = [save(&[12, 14]), GameState::default(), save(&[20])];
+    assert_eq!(calibrate::attempts(&challenge(10), &saves), [12, 14, 20]);
+}
+
+#[test]
+fn test_a_median_at_b_fits() {
+    let calibration = calibrate::calibrate(&challenge(10), &[16, 12, 14, 13, 15], 5).unwrap();
+    assert_eq!(calibration.median, 14);
+    assert_eq!(calibration.median_grade, Grade::B);
+    assert_eq!(calibration.verdict, Verdict::Fits);
+    assert_eq!(calibration.par, None);
+}
+
+#[test]
+fn test_a_median_at_e_is_too_strict() {
+    let calibration = calibrate::calibrate(&challenge(10), &[20, 25, 26, 30, 40], 5).unwrap();
+    assert_eq!(calibration.median_grade, Grade::E);
+    assert_eq!(calibration.verdict, Verdict::TooStrict);
+    // The median attempt grades C on the proposed curve
+    assert_eq!(calibration.thresholds, [10, 25, 26, 30, 31]);
+}
+
+#[test]
+fn test_an_attempt_beating_par_is_too_lax() {
+    let calibration = calibrate::calibrate(&challenge(10), &[10, 8, 10, 12, 10], 5).unwrap();
+    assert_eq!(calibration.verdict, Verdict::TooLax);
+    assert_eq!(calibration.par, Some(8));
+    // Relative to the new par, rising by at least a tenth
+    assert_eq!(calibration.thresholds, [10, 13, 14, 15, 16]);
+}
+
+#[test]
+fn test_too_few_attempts_or_no_par_are_skipped() {
+    assert_eq!(calibrate::calibrate(&challenge(10), &[10, 12], 5), None);
+    assert!(calibrate::calibrate(&challenge(10), &[10, 12], 2).is_some());
+    assert_eq!(calibrate::calibrate(&challenge(0), &[10; 5], 5), None);
+}
+
+#[test]
+fn test_report_lists_only_flagged_challenges() {
+    let strict = challenge(10);
+    let fitting = challenge(10);
+    let calibrations = [
+        (
+            &strict,
+            calibrate::calibrate(&strict, &[20, 25, 26, 30, 40], 5).unwrap(),
+        ),
+        (
+            &fitting,
+            calibrate::calibrate(&fitting, &[12, 13, 14, 15, 16], 5).unwrap(),
+        ),
+    ];
+    let report = calibrate::report(&calibrations, 2, 3);
+    assert_eq!(
+        report,
+        "c - Change a word: par 10 looks too strict\n\
+         \x20 5 attempts, best 20 keys, median 26 keys (2.6x par, grade E on [10, 14, 18, 24, 28])\n\
+         \x20 thresholds = [10, 25, 26, 30, 31]\n\
+         1 of 2 challenges flagged from 2 saves; 3 with too few attempts\n"
+    );
+}
@@ -0,0 +1,113 @@
+use nvimkata::cast::{self, Cast};
+use nvimkata::challenge::{Challenge, Grade};
+use nvimkata::nvim::Snapshot;
+use nvimkata::state::AttemptRecord;
+
+fn challenge() -> Challenge {
+    toml::from_str(
+        "id = \"w_001\"\nversion = \"1\"\ntitle = \"Delete a word\"\ntopic = \"t\"\n\
+         difficulty = 1\npar_keystrokes = 2\n[start]\ncontent = \"one two\"\n\
+         [target]\ncontent = \"two\"\n",
+    )
+    .unwrap()
+}
+
+fn snapshot(line: &str, col: u32) -> Snapshot {
+    Snapshot {
+        lines: vec![line.to_string()],
+        cursor: [1, col],
+    }
+}
+
+#[test]
+fn test_replay_renders_a_frame_per_keystroke() {
+    let keys = ["d".to_string(), "w".to_string()];
+    let snapshots = [
+        snapshot("one two", 1),
+        snapshot("one two", 1),
+        snapshot("two", 1),
+    ];
+    let cast = Cast::replay(&challenge(), &keys, &snapshots, 600, "Solved");
+    let times: Vec<u32> = cast.events.iter().map(|(ms, _)| *ms).collect();
+    // A keystroke every 300ms, then the result after a pause
+    assert_eq!(times, [0, 300, 600, 2400]);
+    assert!(
+        cast.events[0]
+            .1
+            .contains("w_001 - Delete a word | 0/2 keys")
+    );
+    assert!(cast.events[0].1.contains("\x1b[7mo\x1b[0mne two\r\n"));
+    assert!(cast.events[2].1.contains("2/2 keys"));
+    assert!(cast.events[2].1.contains("\x1b[7mt\x1b[0mwo\r\n"));
+    assert!(cast.events[2].1.ends_with("\x1b[2mdw\x1b[0m"));
+    assert_eq!(cast.events[3].1, "\r\nSolved\r\n");
+    assert_eq!(cast.height, 5);
+
+    // Past the end of a line, the cursor is a space
+    let cast = Cast::replay(&challenge(), &[], &[snapshot("ab", 3)], 0, "");
+    assert!(cast.events[0].1.contains("ab\x1b[7m \x1b[0m\r\n"));
+}
+
+#[test]
+fn test_asciicast_has_a_header_and_output_events() {
+    let cast = Cast {
+        title: "w_001 - \"quoted\"".to_string(),
+        width: 40,
+        height: 5,
+        events: vec![(0, "a\r\n".to_string()), (1250, "\x1b[7mb".to_string())],
+    };
+    let text = cast.to_asciicast();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines[0],
+        r#"{"version":2,"width":40,"height":5,"title":"w_001 - \"quoted\""}"#
+    );
+    assert_eq!(lines[1], r#"[0.000, "o", "a\r\n"]"#);
+    assert_eq!(lines[2], r#"[1.250, "o", "\u001b[7mb"]"#);
+    let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(header["version"], 2);
+}
+
+#[test]
+fn test_text_and_html_show_a_frame_per_command() {
+    let steps = ["dw".to_string()];
+    let snapshots = [snapshot("one two", 5), snapshot("<two>", 1)];
+    assert_eq!(
+        cast::text(&challenge(), &steps, &snapshots, "Solved"),
+        "w_001 - Delete a word\n\
+         \n-- start --\n> one two\n      ^\n\
+         \n-- 1/1: dw --\n> <two>\n  ^\n\
+         \nSolved\n"
+    );
+
+    let html = cast::html(&challenge(), &steps, &snapshots, "Solved");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(
+        "<figcaption>start</figcaption><pre>one <span class=\"cursor\">t</span>wo\n</pre>"
+    ));
+    assert!(html.contains(
+        "<figcaption>1/1: dw</figcaption><pre><span class=\"cursor\">&lt;</span>two&gt;\n</pre>"
+    ));
+    assert_eq!(html.matches("<figure class=\"frame\">").count(), 2);
+    assert!(html.contains("<p>Solved</p>"));
+}
+
+#[test]
+fn test_result_line() {
+    let attempt = AttemptRecord {
+        grade: Grade::A,
+        keystrokes: 2,
+        time_ms: Some(1500),
+        keys: "dw".to_string(),
+        technique_missing: false,
+        timestamp: None,
+    };
+    assert_eq!(
+        cast::result_line(&attempt, false),
+        "Solved in 2 keystrokes, 00:01.500, grade A"
+    );
+    assert_eq!(
+        cast::result_line(&attempt, true),
+        "Solved in 2 keystrokes, 00:01.500"
+    );
+}
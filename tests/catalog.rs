@@ -0,0 +1,132 @@
+use nvimkata::catalog::{self, ListFormat, ListSort};
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::state::GameState;
+
+fn challenge(id: &str, topic: &str, tags: &[&str]) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"Test {id}\"\ntopic = \"{topic}\"\n\
+         difficulty = 2\npar_keystrokes = 10\nfocused_actions = {tags:?}\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn fixture() -> (Vec<Topic>, GameState) {
+    let topics = vec![
+        Topic {
+            id: 1,
+            name: "Motions".to_string(),
+            description: "f/t".to_string(),
+            intro: None,
+            challenges: vec![
+                challenge("m1", "motions", &["f", ";"]),
+                challenge("m2", "motions", &[]),
+            ],
+        },
+        Topic {
+            id: 2,
+            name: "Empty".to_string(),
+            description: String::new(),
+            intro: None,
+            challenges: Vec::new(),
+        },
+        Topic {
+            id: 100,
+            name: "Refactoring".to_string(),
+            description: String::new(),
+            intro: None,
+            challenges: vec![Challenge {
+                par_keystrokes: 0,
+                ..challenge("f1", "freestyle", &[])
+            }],
+        },
+    ];
+    let mut state = GameState::default();
+    state.record_result("m1", Grade::B, 12, 4_000, "", "1.0.0");
+    state.record_freestyle_result("f1", 40, Some(9_000), "", "1.0.0", None);
+    (topics, state)
+}
+
+#[test]
+fn test_parse_list_format() {
+    assert_eq!("TSV".parse(), Ok(ListFormat::Tsv));
+    assert_eq!("json".parse(), Ok(ListFormat::Json));
+    assert!("csv".parse::<ListFormat>().is_err());
+}
+
+#[test]
+fn test_entries_skip_empty_topics() {
+    let (topics, state) = fixture();
+    let entries = catalog::entries(&topics, &state);
+    assert_eq!(entries.len(), 2);
+    let m1 = &entries[0].challenges[0];
+    assert_eq!(m1.par, Some(10));
+    assert_eq!(m1.grade, Some(Grade::B));
+    assert!(m1.completed);
+    let f1 = &entries[1].challenges[0];
+    assert_eq!(
+        (f1.par, f1.grade, f1.best_keystrokes),
+        (None, None, Some(40))
+    );
+}
+
+#[test]
+fn test_tsv() {
+    let (topics, state) = fixture();
+    assert_eq!(
+        catalog::render(&topics, &state, ListFormat::Tsv).unwrap(),
+        "topic\tid\ttitle\tdifficulty\tpar\ttags\tgrade\tbest\n\
+         Motions\tm1\tTest m1\t2\t10\tf,;\tB\t12\n\
+         Motions\tm2\tTest m2\t2\t10\t\t\t\n\
+         Refactoring\tf1\tTest f1\t2\t\t\t\t40\n"
+    );
+}
+
+#[test]
+fn test_json() {
+    let (topics, state) = fixture();
+    let json: serde_json::Value =
+        serde_json::from_str(&catalog::render(&topics, &state, ListFormat::Json).unwrap()).unwrap();
+    assert_eq!(json[0]["name"], "Motions");
+    assert_eq!(json[0]["challenges"][0]["grade"], "B");
+    assert_eq!(json[0]["challenges"][0]["tags"][1], ";");
+    assert_eq!(json[1]["challenges"][0]["freestyle"], true);
+    assert!(json[1]["challenges"][0]["par"].is_null());
+}
+
+#[test]
+fn test_table() {
+    let (topics, state) = fixture();
+    let table = catalog::render(&topics, &state, ListFormat::Table).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Motions (1/2)");
+    assert_eq!(lines[1], "  id difficulty par grade best title    tags");
+    assert_eq!(lines[2], "  m1 2          10  B     12   Test m1  f,;");
+    assert_eq!(lines[3], "  m2 2          10             Test m2");
+    assert_eq!(lines[4], "Refactoring (1/1)");
+}
+
+#[test]
+fn test_speedrun_ranks_by_best_time() {
+    let (topics, state) = fixture();
+    assert_eq!("Time".parse(), Ok(ListSort::Time));
+    assert!("grade".parse::<ListSort>().is_err());
+    let ids: Vec<&str> = catalog::ranking(&topics, &state)
+        .iter()
+        .map(|entry| entry.id)
+        .collect();
+    assert_eq!(ids, ["m1", "f1", "m2"]);
+    assert_eq!(
+        catalog::speedrun(&topics, &state, ListFormat::Tsv).unwrap(),
+        "topic\tid\ttitle\ttime_ms\tbest\n\
+         Motions\tm1\tTest m1\t4000\t12\n\
+         Refactoring\tf1\tTest f1\t9000\t40\n\
+         Motions\tm2\tTest m2\t\t\n"
+    );
+    let table = catalog::speedrun(&topics, &state, ListFormat::Table).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Fastest solves (2 of 3 timed)");
+    assert_eq!(lines[1], "  time      id title   topic");
+    assert_eq!(lines[2], "  00:04.000 m1 Test m1 Motions");
+    assert_eq!(lines[4], "  -         m2 Test m2 Motions");
+}
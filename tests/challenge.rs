@@ -1,24 +1,17 @@
-use nvimkata::challenge::{BufferContent, Category, Challenge, Grade};
+use nvimkata::challenge::{
+    self, Category, Challenge, Grade, HintRegion, MacroSpec, QuickfixEntry, Setup, Technique,
+    TutorialStep, split_keys,
+};
+use nvimkata::heatmap::KeyboardLayout;
 
 fn sample_challenge() -> Challenge {
-    Challenge {
-        id: "motion_001".to_string(),
-        version: "1.0.0".to_string(),
-        title: "Test Challenge".to_string(),
-        topic: "motions".to_string(),
-        difficulty: 1,
-        hint: "Use f to find".to_string(),
-        detailed_hint: Some("Try 3fw".to_string()),
-        par_keystrokes: 10,
-        perfect_moves: None,
-        focused_actions: None,
-        start: BufferContent {
-            content: "hello world".to_string(),
-        },
-        target: BufferContent {
-            content: "hello rust".to_string(),
-        },
-    }
+    toml::from_str(
+        "id = \"motion_001\"\nversion = \"1.0.0\"\ntitle = \"Test Challenge\"\n\
+         topic = \"motions\"\ndifficulty = 1\nhint = \"Use f to find\"\n\
+         detailed_hint = \"Try 3fw\"\npar_keystrokes = 10\n\
+         [start]\ncontent = \"hello world\"\n[target]\ncontent = \"hello rust\"\n",
+    )
+    .unwrap()
 }
 
 #[test]
@@ -87,6 +80,7 @@ fn test_thresholds() {
 
 #[test]
 fn test_category_for_topic() {
+    assert_eq!(Category::for_topic(0), Category::Tutorial);
     assert_eq!(Category::for_topic(1), Category::Beginner);
     assert_eq!(Category::for_topic(2), Category::Beginner);
     assert_eq!(Category::for_topic(3), Category::Intermediate);
@@ -97,13 +91,15 @@ fn test_category_for_topic() {
     assert_eq!(Category::for_topic(8), Category::Legendary);
     assert_eq!(Category::for_topic(100), Category::Freestyle);
     assert_eq!(Category::for_topic(107), Category::Freestyle);
+    assert_eq!(Category::for_topic(150), Category::Pack);
+    assert_eq!(Category::for_topic(199), Category::Pack);
 }
 
 #[test]
 fn test_category_freestyle() {
     assert_eq!(Category::Freestyle.name(), "FREESTYLE");
     assert_eq!(Category::Freestyle.color(), ratatui::style::Color::Red);
-    assert_eq!(Category::ALL.len(), 5);
+    assert_eq!(Category::ALL.len(), 7);
     assert!(Category::ALL.contains(&Category::Freestyle));
 }
 
@@ -143,5 +139,498 @@ content = "The quick brown cat"
     let challenge: Challenge = toml::from_str(toml_str).unwrap();
     assert_eq!(challenge.id, "motion_001");
     assert_eq!(challenge.par_keystrokes, 8);
-    assert_eq!(challenge.target.content, "The quick brown cat");
+    assert_eq!(challenge.target().content, "The quick brown cat");
+}
+
+#[test]
+fn test_hint_tiers() {
+    let mut c = sample_challenge();
+    assert_eq!(c.hint_tiers(), ["Use f to find", "Try 3fw"]);
+    c.hints = vec![
+        "A motion".to_string(),
+        " ".to_string(),
+        "f and a count".to_string(),
+        "3fw".to_string(),
+    ];
+    assert_eq!(c.hint_tiers(), ["A motion", "f and a count", "3fw"]);
+
+    let toml_str = r#"
+id = "motion_002"
+version = "1.0.0"
+title = "T"
+topic = "motions"
+difficulty = 1
+hints = ["category", "motion family", "exact keys"]
+par_keystrokes = 8
+[start]
+content = "a"
+[target]
+content = "b"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert!(challenge.hint.is_empty());
+    assert_eq!(challenge.hint_tiers().len(), 3);
+}
+
+#[test]
+fn test_hint_region_spans() {
+    let region = |line, col, end_line, end_col| HintRegion {
+        line,
+        col,
+        end_line,
+        end_col,
+    };
+    let content = "hello world\nsecond line\n\nlast";
+    assert_eq!(
+        region(1, Some(7), None, Some(11)).spans(content),
+        [[1, 7, 5]]
+    );
+    // To the end of the line without end_col, whole lines without col
+    assert_eq!(region(1, Some(7), None, None).spans(content), [[1, 7, 5]]);
+    assert_eq!(
+        region(2, None, Some(4), None).spans(content),
+        [[2, 1, 11], [4, 1, 4]]
+    );
+    assert_eq!(
+        region(1, Some(7), Some(2), Some(6)).spans(content),
+        [[1, 7, 5], [2, 1, 6]]
+    );
+    // Past the end of the content
+    assert!(region(9, None, None, None).spans(content).is_empty());
+    assert_eq!(
+        region(4, Some(2), None, Some(99)).spans(content),
+        [[4, 2, 3]]
+    );
+
+    assert!(region(1, Some(7), None, Some(11)).is_valid());
+    assert!(region(1, Some(7), Some(2), Some(1)).is_valid());
+    assert!(!region(0, None, None, None).is_valid());
+    assert!(!region(1, Some(0), None, None).is_valid());
+    assert!(!region(2, None, Some(1), None).is_valid());
+    assert!(!region(1, Some(7), None, Some(3)).is_valid());
+
+    let mut c = sample_challenge();
+    c.hint_region = vec![region(1, Some(7), None, None)];
+    assert_eq!(c.hint_spans(), [[1, 7, 4]]);
+    let parsed: HintRegion = toml::from_str("line = 2\ncol = 5\nend_col = 9").unwrap();
+    assert_eq!(parsed, region(2, Some(5), None, Some(9)));
+}
+
+#[test]
+fn test_score_with_hints() {
+    let c = sample_challenge();
+    // The first tier is free, each deeper one costs a grade
+    assert_eq!(c.score_with_hints(10, 0), Grade::A);
+    assert_eq!(c.score_with_hints(10, 1), Grade::A);
+    assert_eq!(c.score_with_hints(10, 3), Grade::C);
+    assert_eq!(c.score_with_hints(25, 3), Grade::F);
+    assert_eq!(Grade::E.lowered(u32::MAX), Grade::F);
+}
+
+#[test]
+fn test_custom_curve_thresholds() {
+    let mut c = sample_challenge();
+    c.thresholds = Some([10, 12, 15, 20, 25]);
+    // par=10
+    assert_eq!(c.threshold(Grade::B), 12);
+    assert_eq!(c.threshold(Grade::E), 25);
+    assert_eq!(c.threshold(Grade::F), 30); // extends the last step (25 - 20)
+    assert_eq!(c.score(12), Grade::B);
+    assert_eq!(c.score(13), Grade::C);
+    assert_eq!(c.score(26), Grade::F);
+}
+
+#[test]
+fn test_layout_par_replaces_par_on_its_layout() {
+    challenge::set_layout(KeyboardLayout::Dvorak);
+    let mut c: Challenge = toml::from_str(
+        "id = \"c\"\nversion = \"1\"\ntitle = \"t\"\ntopic = \"t\"\ndifficulty = 1\n\
+         par_keystrokes = 10\n[layout_par]\ncolemak = 20\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n",
+    )
+    .unwrap();
+    // Only the player's layout counts
+    assert_eq!(c.par(), 10);
+    c.layout_par.insert(KeyboardLayout::Dvorak, 15);
+    assert_eq!(c.par(), 15);
+    assert_eq!(c.threshold(Grade::B), 21);
+    assert_eq!(c.score(15), Grade::A);
+    assert_eq!(c.score(16), Grade::B);
+}
+
+#[test]
+fn test_generous_curve_widens_grade_a() {
+    let mut c = sample_challenge();
+    c.thresholds = Some([15, 20, 25, 30, 35]);
+    assert_eq!(c.score(15), Grade::A);
+    assert_eq!(c.score(16), Grade::B);
+}
+
+#[test]
+fn test_curve_validation() {
+    use nvimkata::challenge::{DEFAULT_CURVE, is_valid_curve};
+    assert!(is_valid_curve(&DEFAULT_CURVE));
+    assert!(is_valid_curve(&[10, 10, 10, 10, 10]));
+    assert!(!is_valid_curve(&[10, 14, 12, 24, 28]));
+    assert!(!is_valid_curve(&[8, 14, 18, 24, 28]));
+}
+
+#[test]
+fn test_filetype_validation() {
+    use nvimkata::challenge::is_valid_filetype;
+    assert!(is_valid_filetype("python"));
+    assert!(is_valid_filetype("c.doxygen"));
+    assert!(is_valid_filetype("objective-c"));
+    assert!(!is_valid_filetype(""));
+    assert!(!is_valid_filetype("python | !rm"));
+    assert!(!is_valid_filetype("lua\nx"));
+}
+
+#[test]
+fn test_deserialize_cursor_positions() {
+    let toml_str = r#"
+id = "motion_999"
+version = "1.0.0"
+title = "Cursor"
+topic = "motions"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 2
+
+[start]
+content = "one\ntwo"
+cursor = [2, 3]
+
+[target]
+content = "one\ntw"
+cursor = [2, 2]
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(challenge.start().cursor, Some([2, 3]));
+    assert_eq!(challenge.target().cursor, Some([2, 2]));
+
+    let plain = sample_challenge();
+    assert_eq!(plain.start().cursor, None);
+}
+
+#[test]
+fn test_deserialize_setup() {
+    let toml_str = r#"
+id = "reg_999"
+version = "1.0.0"
+title = "Setup"
+topic = "registers"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 3
+
+[setup]
+options = ["shiftwidth=2", "expandtab"]
+
+[setup.registers]
+a = "hello"
+"0" = "line\n"
+
+[setup.marks]
+a = [2, 1]
+
+[[setup.quickfix]]
+line = 1
+col = 3
+text = "unused"
+
+[[setup.quickfix]]
+line = 2
+
+[start]
+content = "x"
+
+[target]
+content = "y"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(challenge.setup.registers["a"], "hello");
+    assert_eq!(challenge.setup.registers["0"], "line\n");
+    assert_eq!(challenge.setup.marks["a"], [2, 1]);
+    assert_eq!(challenge.setup.options, ["shiftwidth=2", "expandtab"]);
+    assert_eq!(
+        challenge.setup.quickfix,
+        [
+            QuickfixEntry {
+                line: 1,
+                col: 3,
+                text: "unused".to_string()
+            },
+            QuickfixEntry {
+                line: 2,
+                col: 1,
+                text: String::new()
+            },
+        ]
+    );
+    assert!(challenge.setup.validate().is_ok());
+    assert!(sample_challenge().setup.is_empty());
+}
+
+#[test]
+fn test_setup_validation_rejects_bad_names() {
+    let mut setup = Setup::default();
+    setup.registers.insert("ab".to_string(), "x".to_string());
+    assert!(setup.validate().is_err());
+
+    let mut setup = Setup::default();
+    setup.marks.insert("1".to_string(), [1, 1]);
+    assert!(setup.validate().is_err());
+
+    let mut setup = Setup::default();
+    setup.options.push("sw=2 | !rm".to_string());
+    assert!(setup.validate().is_err());
+
+    let mut setup = Setup::default();
+    setup.quickfix.push(QuickfixEntry {
+        line: 0,
+        col: 1,
+        text: String::new(),
+    });
+    assert!(setup.validate().is_err());
+}
+
+#[test]
+fn test_deserialize_forbidden_keys() {
+    let toml_str = r#"
+id = "motion_999"
+version = "1.0.0"
+title = "No arrows"
+topic = "motions"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 2
+forbidden_keys = ["x", "<Left>", ":s"]
+
+[start]
+content = "ab"
+
+[target]
+content = "a"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(challenge.forbidden_keys, ["x", "<Left>", ":s"]);
+    assert!(sample_challenge().forbidden_keys.is_empty());
+}
+
+#[test]
+fn test_deserialize_blind() {
+    let toml_str = r#"
+id = "motion_998"
+version = "1.0.0"
+title = "From the description"
+topic = "motions"
+difficulty = 1
+hint = "Delete the b"
+par_keystrokes = 2
+blind = true
+
+[start]
+content = "ab"
+
+[target]
+content = "a"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert!(challenge.blind);
+    assert!(!sample_challenge().blind);
+}
+
+#[test]
+fn test_deserialize_description() {
+    let toml_str = r#"
+id = "f02_999"
+version = "1.0.0"
+title = "Dates"
+topic = "data_wrangling"
+difficulty = 1
+hint = "Try :s with groups"
+description = """
+Normalize these dates to ISO-8601.
+"""
+
+[start]
+content = "01/02/2024"
+
+[target]
+content = "2024-02-01"
+"#;
+    let mut challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(
+        challenge.description.as_deref(),
+        Some("Normalize these dates to ISO-8601.\n")
+    );
+    assert_eq!(
+        challenge.instructions().as_deref(),
+        Some("Normalize these dates to ISO-8601.")
+    );
+    // Blind challenges add the hint in place of the target
+    challenge.blind = true;
+    assert_eq!(
+        challenge.instructions().as_deref(),
+        Some("Normalize these dates to ISO-8601.\n\nTry :s with groups")
+    );
+    assert_eq!(sample_challenge().instructions(), None);
+}
+
+#[test]
+fn test_deserialize_reveal_secs() {
+    let toml_str = r#"
+id = "motion_997"
+version = "1.0.0"
+title = "From memory"
+topic = "motions"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 2
+reveal_secs = 5
+
+[start]
+content = "ab"
+
+[target]
+content = "a"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(challenge.reveal_secs, Some(5));
+    assert_eq!(sample_challenge().reveal_secs, None);
+}
+
+#[test]
+fn test_deserialize_macro() {
+    let toml_str = r#"
+id = "macro_997"
+version = "1.0.0"
+title = "Record once"
+topic = "macros"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 8
+
+[macro]
+replays = 3
+
+[start]
+content = "a\nb\nc"
+
+[target]
+content = "- a\n- b\n- c"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    assert_eq!(
+        challenge.recorded_macro,
+        Some(MacroSpec {
+            register: 'q',
+            replays: 3
+        })
+    );
+    assert_eq!(sample_challenge().recorded_macro, None);
+}
+
+#[test]
+fn test_macro_keystrokes() {
+    let spec = MacroSpec {
+        register: 'a',
+        replays: 3,
+    };
+    assert_eq!(spec.invocations("qaI- <Esc>jq2@a"), 1);
+    assert_eq!(spec.invocations("@a@@@@@b"), 3);
+    // The macro's own keys plus each invocation
+    assert_eq!(spec.keystrokes("I- <Esc>j", "qaI- <Esc>jq@a@@"), 7);
+}
+
+#[test]
+fn test_deserialize_required_technique() {
+    let toml_str = r#"
+id = "macro_999"
+version = "1.0.0"
+title = "Use a macro"
+topic = "macros"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 2
+
+[required_technique]
+name = "a macro"
+keys = ["@"]
+
+[start]
+content = "ab"
+
+[target]
+content = "a"
+"#;
+    let challenge: Challenge = toml::from_str(toml_str).unwrap();
+    let technique = challenge.required_technique.unwrap();
+    assert_eq!(technique.name, "a macro");
+    assert_eq!(technique.keys, ["@"]);
+    assert!(sample_challenge().required_technique.is_none());
+}
+
+#[test]
+fn test_constraint_checks_scan_key_log() {
+    let mut challenge = sample_challenge();
+    assert_eq!(challenge.forbidden_key_in("ciwfoo<Esc>"), None);
+    assert!(challenge.technique_used("x"));
+
+    challenge.forbidden_keys = vec!["x".to_string(), "<Left>".to_string()];
+    challenge.required_technique = Some(Technique {
+        name: "a macro".to_string(),
+        keys: vec!["@".to_string()],
+    });
+    assert_eq!(challenge.forbidden_key_in("jj<Left>i"), Some("<Left>"));
+    assert_eq!(challenge.forbidden_key_in("jjdw"), None);
+
+    // Sequences match whole keys outside of insert-mode text
+    challenge.forbidden_keys = vec!["c".to_string(), "h".to_string(), "<CR>".to_string()];
+    assert_eq!(challenge.forbidden_key_in("A;<Esc>j<Right>"), None);
+    assert_eq!(challenge.forbidden_key_in("Ach<Esc>"), None);
+    assert_eq!(challenge.forbidden_key_in("Ach<Esc>cw"), Some("c"));
+    assert_eq!(challenge.forbidden_key_in("o<Enter>"), Some("<CR>"));
+    assert!(challenge.technique_used("qaA;<Esc>jq2@a"));
+    assert!(!challenge.technique_used("A;<Esc>jA;<Esc>"));
+    // Typed text doesn't use the technique
+    assert!(!challenge.technique_used("A@a<Esc>"));
+    challenge.required_technique = Some(Technique {
+        name: ":g".to_string(),
+        keys: vec![":g".to_string()],
+    });
+    assert!(challenge.technique_used(":g/x/d<CR>"));
+    assert!(!challenge.technique_used("o:g<Esc>"));
+}
+
+#[test]
+fn test_tutorial_steps_complete_in_order() {
+    let mut challenge = sample_challenge();
+    assert!(!challenge.is_tutorial());
+    challenge.tutorial_steps = ["www", "b", "dw"]
+        .iter()
+        .map(|keys| TutorialStep {
+            instruction: String::new(),
+            keys: (*keys).to_string(),
+        })
+        .collect();
+    assert!(challenge.is_tutorial());
+    assert_eq!(challenge.tutorial_steps_done(""), 0);
+    assert_eq!(challenge.tutorial_steps_done("wwwbdw"), 3);
+    assert_eq!(challenge.tutorial_steps_done("jwwwjjbx"), 2);
+    // Keys typed before a step began do not count for it
+    assert_eq!(challenge.tutorial_steps_done("dwbwww"), 1);
+}
+
+#[test]
+fn test_split_keys() {
+    assert_eq!(
+        split_keys("ciwfoo<Esc>"),
+        ["c", "i", "w", "f", "o", "o", "<Esc>"]
+    );
+    assert_eq!(split_keys("<C-r>a<lt>"), ["<C-r>", "a", "<lt>"]);
+    assert_eq!(split_keys("a<b c>"), ["a", "<", "b", " ", "c", ">"]);
+    assert_eq!(split_keys("dw<"), ["d", "w", "<"]);
+    assert!(split_keys("").is_empty());
 }
@@ -0,0 +1,347 @@
+use std::path::PathBuf;
+
+use nvimkata::cast::ReplayExport;
+use nvimkata::catalog::{ListFormat, ListSort};
+use nvimkata::cli::{
+    self, AuthorCommand, CliError, Command, ConfigCommand, DuelCommand, PackCommand, PlayArgs,
+    ResetScope, StateCommand,
+};
+use nvimkata::mutate::Mutation;
+use nvimkata::report::ReportFormat;
+use nvimkata::unlock::UnlockPolicy;
+
+fn parse(args: &[&str]) -> Result<Command, CliError> {
+    let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+    cli::parse(&args)
+}
+
+#[test]
+fn test_bare_options_launch_the_tui() {
+    assert_eq!(parse(&[]), Ok(Command::Play(PlayArgs::default())));
+    let expected = Command::Play(PlayArgs {
+        unlock_all: true,
+        unlock_policy: Some(UnlockPolicy::Percent(80)),
+        profile: Some("work".to_string()),
+        nvim_bin: Some("nvim-nightly".to_string()),
+        no_color: true,
+        verbose: true,
+        hardcore: true,
+        dry_run: Some("motion_001".to_string()),
+    });
+    let flags = [
+        "--unlock-all",
+        "--unlock-policy",
+        "80%",
+        "--profile",
+        "work",
+        "--nvim-bin",
+        "nvim-nightly",
+        "--no-color",
+        "-v",
+        "--hardcore",
+        "--dry-run",
+        "motion_001",
+    ];
+    assert_eq!(parse(&flags), Ok(expected.clone()));
+    assert_eq!(parse(&[&["play"], &flags[..]].concat()), Ok(expected));
+}
+
+#[test]
+fn test_subcommands() {
+    assert_eq!(
+        parse(&["list", "--profile", "work", "--format", "json"]),
+        Ok(Command::List {
+            profile: Some("work".to_string()),
+            format: ListFormat::Json,
+            sort: ListSort::Topic,
+        })
+    );
+    assert_eq!(
+        parse(&["list", "--sort", "time"]),
+        Ok(Command::List {
+            profile: None,
+            format: ListFormat::Table,
+            sort: ListSort::Time,
+        })
+    );
+    assert_eq!(parse(&["stats"]), Ok(Command::Stats { profile: None }));
+    assert_eq!(
+        parse(&["export", "-o", "save.json"]),
+        Ok(Command::Export {
+            profile: None,
+            output: Some(PathBuf::from("save.json")),
+        })
+    );
+    assert_eq!(
+        parse(&[
+            "gen",
+            "--seed",
+            "42",
+            "--template",
+            "delete_word",
+            "-o",
+            "g.toml"
+        ]),
+        Ok(Command::Gen {
+            template: Some("delete_word".to_string()),
+            seed: Some(42),
+            output: Some(PathBuf::from("g.toml")),
+        })
+    );
+    assert_eq!(
+        parse(&["from-file", "src/foo.rs", "--mutate", "rename-var"]),
+        Ok(Command::FromFile {
+            file: PathBuf::from("src/foo.rs"),
+            mutation: Mutation::RenameVar,
+            seed: None,
+            output: None,
+            nvim_bin: None,
+        })
+    );
+    assert_eq!(
+        parse(&["import-diff", "review.diff", "--whole", "-o", "katas"]),
+        Ok(Command::ImportDiff {
+            patch: PathBuf::from("review.diff"),
+            whole: true,
+            dir: Some(PathBuf::from("katas")),
+        })
+    );
+    assert_eq!(
+        parse(&["replay", "t1", "--attempt", "2", "--asciinema", "out.cast"]),
+        Ok(Command::Replay {
+            id: "t1".to_string(),
+            attempt: 2,
+            export: Some(ReplayExport::Asciinema(PathBuf::from("out.cast"))),
+            profile: None,
+            nvim_bin: None,
+        })
+    );
+    assert_eq!(
+        parse(&["versus", "t1", "--players", "ada, bob"]),
+        Ok(Command::Versus {
+            id: "t1".to_string(),
+            players: vec!["ada".to_string(), "bob".to_string()],
+            nvim_bin: None,
+        })
+    );
+    assert_eq!(
+        parse(&[
+            "duel",
+            "join",
+            "example.org",
+            "--room",
+            "lunch",
+            "--player",
+            "ada"
+        ]),
+        Ok(Command::Duel(DuelCommand::Join {
+            relay: "example.org:7373".to_string(),
+            room: "lunch".to_string(),
+            player: "ada".to_string(),
+            challenge: None,
+            nvim_bin: None,
+        }))
+    );
+    assert_eq!(
+        parse(&["duel", "relay", "--listen", "127.0.0.1:9000"]),
+        Ok(Command::Duel(DuelCommand::Relay {
+            listen: "127.0.0.1:9000".to_string()
+        }))
+    );
+    assert_eq!(
+        parse(&["report", "--since", "2w", "--format", "html"]),
+        Ok(Command::Report {
+            profile: None,
+            since: Some(14),
+            format: Some(ReportFormat::Html),
+        })
+    );
+    assert_eq!(
+        parse(&["reset", "--topic", "Motions", "--profile", "work"]),
+        Ok(Command::Reset {
+            profile: Some("work".to_string()),
+            scope: ResetScope::Topic("Motions".to_string()),
+        })
+    );
+    assert_eq!(
+        parse(&["state", "merge", "laptop.json"]),
+        Ok(Command::State(StateCommand::Merge {
+            file: PathBuf::from("laptop.json"),
+            profile: None,
+        }))
+    );
+    assert_eq!(
+        parse(&["pack", "install", "https://example.com/regex.tar.gz"]),
+        Ok(Command::Pack(PackCommand::Install {
+            source: "https://example.com/regex.tar.gz".to_string()
+        }))
+    );
+    assert_eq!(
+        parse(&["pack", "list"]),
+        Ok(Command::Pack(PackCommand::List))
+    );
+    assert_eq!(
+        parse(&["pack", "browse"]),
+        Ok(Command::Pack(PackCommand::Search { query: None }))
+    );
+    assert_eq!(
+        parse(&["pack", "update", "--check"]),
+        Ok(Command::Pack(PackCommand::Update {
+            name: None,
+            check: true
+        }))
+    );
+    assert_eq!(
+        parse(&["pack", "remove", "regex"]),
+        Ok(Command::Pack(PackCommand::Remove {
+            name: "regex".to_string()
+        }))
+    );
+    assert_eq!(
+        parse(&["author", "check", "katas"]),
+        Ok(Command::Author(AuthorCommand::Check {
+            dir: Some(PathBuf::from("katas")),
+            verify: true,
+            nvim_bin: None,
+        }))
+    );
+    assert_eq!(
+        parse(&["author", "check", "--no-verify"]),
+        Ok(Command::Author(AuthorCommand::Check {
+            dir: None,
+            verify: false,
+            nvim_bin: None,
+        }))
+    );
+    assert_eq!(parse(&["config"]), Ok(Command::Config(ConfigCommand::Show)));
+    assert_eq!(
+        parse(&["config", "path"]),
+        Ok(Command::Config(ConfigCommand::Path))
+    );
+}
+
+#[test]
+fn test_parse_author_calibrate() {
+    assert_eq!(
+        parse(&[
+            "author",
+            "calibrate",
+            "--save",
+            "ada.json",
+            "--save",
+            "bob.json"
+        ]),
+        Ok(Command::Author(AuthorCommand::Calibrate {
+            dir: None,
+            saves: vec![PathBuf::from("ada.json"), PathBuf::from("bob.json")],
+            profile: None,
+            min_attempts: 5,
+        }))
+    );
+    assert_eq!(
+        parse(&["author", "calibrate", "katas", "--min-attempts", "20"]),
+        Ok(Command::Author(AuthorCommand::Calibrate {
+            dir: Some(PathBuf::from("katas")),
+            saves: vec![],
+            profile: None,
+            min_attempts: 20,
+        }))
+    );
+    assert!(parse(&["author", "calibrate", "--min-attempts", "0"]).is_err());
+    assert!(
+        parse(&[
+            "author",
+            "calibrate",
+            "--save",
+            "a.json",
+            "--profile",
+            "work"
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn test_solve_is_kept_as_an_alias() {
+    let expected = Ok(Command::Author(AuthorCommand::Solve {
+        id: "mot_001".to_string(),
+        keys: "ciwfoo<Esc>".to_string(),
+        record: true,
+        profile: None,
+        nvim_bin: None,
+    }));
+    let args = ["mot_001", "--keys", "ciwfoo<Esc>", "--record"];
+    assert_eq!(parse(&[&["author", "solve"], &args[..]].concat()), expected);
+    assert_eq!(parse(&[&["solve"], &args[..]].concat()), expected);
+}
+
+#[test]
+fn test_help() {
+    assert_eq!(parse(&["--help"]), Ok(Command::Help(None)));
+    assert_eq!(parse(&["help", "stats"]), Ok(Command::Help(Some("stats"))));
+    assert_eq!(parse(&["report", "-h"]), Ok(Command::Help(Some("report"))));
+    assert_eq!(
+        parse(&["--unlock-all", "-h"]),
+        Ok(Command::Help(Some("play")))
+    );
+    for (name, _) in cli::COMMANDS {
+        assert!(cli::help(Some(name)).starts_with("Usage: nvimkata"));
+    }
+    let overview = cli::help(None);
+    assert!(
+        cli::COMMANDS
+            .iter()
+            .all(|(name, _)| overview.contains(name))
+    );
+}
+
+#[test]
+fn test_usage_errors_point_to_the_command_help() {
+    let err = parse(&["list", "--foo"]).unwrap_err();
+    assert_eq!(err.exit_code(), 2);
+    assert_eq!(
+        err.report(),
+        "error: unknown option: --foo\nrun 'nvimkata help list' for usage."
+    );
+    let err = parse(&["bogus"]).unwrap_err();
+    assert_eq!(
+        err.report(),
+        "error: unknown command: bogus\nrun 'nvimkata help' for usage."
+    );
+    assert!(parse(&["--profile", "../etc"]).is_err());
+    assert!(parse(&["--unlock-policy"]).is_err());
+    assert!(parse(&["--nvim-bin"]).is_err());
+    assert!(parse(&["report", "--since", "0d"]).is_err());
+    assert!(parse(&["solve", "mot_001"]).is_err());
+    assert!(parse(&["author"]).is_err());
+    assert!(parse(&["reset"]).is_err());
+    assert!(parse(&["gen", "--seed", "-1"]).is_err());
+    assert!(parse(&["from-file", "src/foo.rs"]).is_err());
+    assert!(parse(&["from-file", "--mutate", "rename-var"]).is_err());
+    assert!(parse(&["import-diff"]).is_err());
+    assert!(parse(&["versus", "--players", "ada,bob"]).is_err());
+    assert!(parse(&["versus", "t1", "--players", "ada"]).is_err());
+    assert!(parse(&["versus", "t1", "--players", "ada,ada"]).is_err());
+    assert!(parse(&["duel"]).is_err());
+    assert!(parse(&["replay", "t1", "--attempt", "0"]).is_err());
+    assert!(parse(&["replay", "t1", "--html", "a.html", "--text", "a.txt"]).is_err());
+    assert!(parse(&["duel", "join", "example.org", "--room", "lunch"]).is_err());
+    assert!(parse(&["state", "merge"]).is_err());
+    assert!(parse(&["pack", "install"]).is_err());
+    assert!(parse(&["pack", "list", "extra"]).is_err());
+    assert!(parse(&["pack", "search"]).is_err());
+    assert!(parse(&["pack", "install", "regex", "--check"]).is_err());
+    assert_eq!(
+        parse(&["state", "doctor", "--profile", "work"]),
+        Ok(Command::State(StateCommand::Doctor {
+            profile: Some("work".to_string())
+        }))
+    );
+    assert!(parse(&["reset", "--all", "--challenge", "t1"]).is_err());
+    assert!(parse(&["list", "--format", "csv"]).is_err());
+    assert_eq!(CliError::failed("no assignments").exit_code(), 1);
+    assert_eq!(
+        CliError::failed("no assignments").report(),
+        "error: no assignments"
+    );
+}
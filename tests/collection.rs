@@ -0,0 +1,85 @@
+use std::fs;
+
+use nvimkata::challenge::{Category, Challenge, Topic};
+use nvimkata::collection::{self, FAVORITES_ID, FIRST_PLAYLIST_ID};
+use nvimkata::state::GameState;
+
+fn challenge(id: &str) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1\"\ntitle = \"{id}\"\ntopic = \"motions\"\n\
+         difficulty = 1\npar_keystrokes = 5\n[start]\ncontent = \"a\"\n\
+         [target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn topic(id: u8, challenges: &[&str]) -> Topic {
+    Topic {
+        id,
+        name: format!("topic {id}"),
+        description: String::new(),
+        intro: None,
+        challenges: challenges.iter().map(|c| challenge(c)).collect(),
+    }
+}
+
+#[test]
+fn test_favorites_follow_bookmark_order() {
+    let topics = vec![topic(1, &["a", "b"]), topic(2, &["c"])];
+    let mut state = GameState::default();
+    state.toggle_favorite("c");
+    state.toggle_favorite("gone");
+    state.toggle_favorite("a");
+
+    let favorites = collection::favorites(&topics, &state);
+    assert_eq!(favorites.id, FAVORITES_ID);
+    assert_eq!(Category::for_topic(favorites.id), Category::Collection);
+    let ids: Vec<&str> = favorites.challenges.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["c", "a"]);
+}
+
+#[test]
+fn test_load_playlists() {
+    let tmp = std::env::temp_dir().join("rlv_test_playlists");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(&tmp).unwrap();
+    fs::write(
+        tmp.join("a_workshop.toml"),
+        r#"
+name = "Registers + macros"
+description = "Today's workshop"
+challenges = ["c", "missing", "a"]
+"#,
+    )
+    .unwrap();
+    fs::write(tmp.join("b_quick.toml"), r#"challenges = ["b"]"#).unwrap();
+    fs::write(tmp.join("c_broken.toml"), "challenges = 3").unwrap();
+    fs::write(tmp.join("notes.txt"), "not a playlist").unwrap();
+
+    let topics = vec![topic(1, &["a", "b"]), topic(2, &["c"])];
+    let playlists = collection::load_playlists(&tmp, &topics);
+    assert_eq!(playlists.len(), 2);
+
+    assert_eq!(playlists[0].id, FIRST_PLAYLIST_ID);
+    assert_eq!(playlists[0].name, "Registers + macros");
+    assert_eq!(playlists[0].description, "Today's workshop");
+    let ids: Vec<&str> = playlists[0]
+        .challenges
+        .iter()
+        .map(|c| c.id.as_str())
+        .collect();
+    assert_eq!(ids, ["c", "a"]);
+
+    assert_eq!(playlists[1].id, FIRST_PLAYLIST_ID + 1);
+    assert_eq!(playlists[1].name, "b_quick");
+    assert_eq!(Category::for_topic(playlists[1].id), Category::Collection);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_missing_playlist_dir() {
+    let tmp = std::env::temp_dir().join("rlv_test_no_playlists");
+    let _ = fs::remove_dir_all(&tmp);
+    assert!(collection::load_playlists(&tmp, &[]).is_empty());
+}
@@ -0,0 +1,99 @@
+use nvimkata::challenge::Grade;
+use nvimkata::commands::{CommandKind, classify};
+use nvimkata::notation::tokenize;
+use nvimkata::state::{GameState, KeyLog};
+
+fn segments(keys: &str) -> Vec<(String, String, CommandKind)> {
+    classify(&tokenize(keys))
+        .into_iter()
+        .map(|c| (c.notation(), c.name, c.kind))
+        .collect()
+}
+
+#[test]
+fn test_classify_counts_operators_and_insert() {
+    assert_eq!(
+        segments("3fwciwfoo<Esc>d2j\"ayy"),
+        [
+            ("3fw".to_string(), "f".to_string(), CommandKind::Motion),
+            (
+                "ciwfoo<Esc>".to_string(),
+                "ciw".to_string(),
+                CommandKind::Operator
+            ),
+            ("d2j".to_string(), "dj".to_string(), CommandKind::Operator),
+            ("\"ayy".to_string(), "yy".to_string(), CommandKind::Operator),
+        ]
+    );
+}
+
+#[test]
+fn test_classify_command_lines() {
+    assert_eq!(
+        segments(":%s/a/b/g<CR>/foo<CR>n"),
+        [
+            (
+                ":%s/a/b/g<Enter>".to_string(),
+                ":s".to_string(),
+                CommandKind::Ex
+            ),
+            (
+                "/foo<Enter>".to_string(),
+                "/".to_string(),
+                CommandKind::Search
+            ),
+            ("n".to_string(), "n".to_string(), CommandKind::Motion),
+        ]
+    );
+}
+
+#[test]
+fn test_classify_visual_and_macros() {
+    let names: Vec<String> = classify(&tokenize("viwdqaA;<Esc>jq3@a"))
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["v", "iw", "d", "q", "A", "j", "q", "@"]);
+}
+
+#[test]
+fn test_command_usage_is_aggregated() {
+    let mut state = GameState::default();
+    state.record_result("101", Grade::A, 6, 3, "ciwx<Esc>w", "1");
+    state.record_result("102", Grade::B, 4, 3, "2ciwy<Esc>", "1");
+    assert_eq!(state.stats.top_commands(5), [("ciw", 2), ("w", 1)]);
+}
+
+#[test]
+fn test_command_mastery_counts_uses_and_challenges() {
+    let mut state = GameState::default();
+    state.record_result("101", Grade::A, 6, 3, "ciwx<Esc>\"ayy", "1");
+    state.record_result("101", Grade::A, 5, 3, "ciwz<Esc>", "1");
+    state.record_result("102", Grade::B, 4, 3, "2ciwy<Esc>", "1");
+    let ciw = &state.command_mastery["ciw"];
+    assert_eq!(ciw.uses, 3);
+    assert_eq!(ciw.challenges.len(), 2);
+    // Registers count on their own, next to the command
+    assert_eq!(state.command_mastery["\"a"].uses, 1);
+    assert_eq!(state.command_mastery["yy"].uses, 1);
+
+    let mut laptop = GameState::default();
+    laptop.record_result("103", Grade::A, 4, 3, "ciwy<Esc>", "1");
+    state.merge(laptop);
+    assert_eq!(state.command_mastery["ciw"].uses, 4);
+    assert_eq!(state.command_mastery["ciw"].challenges.len(), 3);
+}
+
+#[test]
+fn test_scrubbed_key_logs_keep_keystrokes_but_not_text() {
+    let keys = "ciwhunter2<Esc>o<lt>pw<BS>d<Esc>:w secret<CR>";
+    let scrubbed = KeyLog::Scrub.apply(keys);
+    assert_eq!(
+        scrubbed,
+        "ciw*******<Esc>o***<BS>*<Esc>:w<Space>secret<Enter>"
+    );
+    assert_eq!(tokenize(&scrubbed).len(), tokenize(keys).len());
+    assert_eq!(KeyLog::Scrub.apply("ifoo"), "i***");
+    assert_eq!(KeyLog::Full.apply(keys), keys);
+    assert_eq!(KeyLog::Off.apply(keys), "");
+}
@@ -0,0 +1,43 @@
+use nvimkata::compare::{compare, summary};
+
+/// Segments as (your commands, perfect commands, matches).
+fn lined_up(keys: &str, moves: &[&str]) -> Vec<(String, String, bool)> {
+    let moves: Vec<String> = moves.iter().map(ToString::to_string).collect();
+    compare(keys, &moves)
+        .iter()
+        .map(|s| (summary(&s.mine), summary(&s.perfect), s.matches()))
+        .collect()
+}
+
+#[test]
+fn test_compare_lines_up_commands() {
+    assert_eq!(
+        lined_up("2jdwdwdwdwx", &["2j", "daw"]),
+        [
+            ("2j".to_string(), "2j".to_string(), true),
+            ("4x dw x".to_string(), "daw".to_string(), false),
+        ]
+    );
+    assert_eq!(
+        lined_up("wciwfoo<Esc>", &["w", "ciw", "foo", "<Esc>"]),
+        [
+            ("w".to_string(), "w".to_string(), true),
+            ("ciwfoo<Esc>".to_string(), "ciwfoo<Esc>".to_string(), true),
+        ]
+    );
+}
+
+#[test]
+fn test_compare_missing_and_extra_commands() {
+    assert_eq!(
+        lined_up("jjdd", &["dd"]),
+        [
+            ("2x j".to_string(), "-".to_string(), false),
+            ("dd".to_string(), "dd".to_string(), true),
+        ]
+    );
+    assert_eq!(
+        lined_up("", &["dd"]),
+        [("-".to_string(), "dd".to_string(), false)]
+    );
+}
@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use nvimkata::config::Config;
+use nvimkata::editor::{EditorKind, ShellMode};
+use nvimkata::feedback::FeedbackEvent;
+use nvimkata::goals::GoalKind;
+use nvimkata::heatmap::KeyboardLayout;
+use nvimkata::state::{HistoryRetention, KeyLog, is_valid_profile};
+use nvimkata::theme::Theme;
+use nvimkata::unlock::UnlockPolicy;
+
+#[test]
+fn test_empty_config_uses_defaults() {
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.challenges_dir, None);
+    assert_eq!(config.theme, Theme::Default);
+    assert_eq!(config.keyboard_layout, KeyboardLayout::Qwerty);
+    assert!(!config.unlock_all);
+    assert_eq!(config.unlock_policy, UnlockPolicy::All);
+    assert!(config.goals.is_empty());
+    let launcher = config.launcher();
+    assert_eq!(launcher.bin, "nvim");
+    assert!(launcher.args.is_empty());
+    assert_eq!(launcher.session.shell.mode, ShellMode::Log);
+    assert_eq!(launcher.session.shell.allowed_programs(), None);
+    assert!(!launcher.session.briefing);
+    assert!(!launcher.session.hardcore);
+}
+
+#[test]
+fn test_full_config() {
+    let config: Config = toml::from_str(
+        r#"
+challenges_dir = "/srv/katas"
+theme = "mono"
+keyboard_layout = "colemak"
+unlock_all = true
+unlock_policy = "80%"
+nvim_bin = "/opt/nvim/bin/nvim"
+nvim_args = ["--clean"]
+profile = "work"
+state_dir = "/home/me/dotfiles/kata"
+key_log = "scrub"
+history = "last 50"
+briefing = true
+hardcore = true
+reduce_motion = true
+
+[[goals]]
+kind = "challenges"
+target = 3
+
+[[opponents]]
+name = "Golfer"
+par_tenths = 11
+categories = ["advanced"]
+
+[feedback]
+bell = true
+completed = "paplay done.oga"
+
+[shell]
+mode = "whitelist"
+allowed = ["sort", "column"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.challenges_dir, Some(PathBuf::from("/srv/katas")));
+    assert_eq!(config.theme, Theme::Mono);
+    assert_eq!(config.keyboard_layout, KeyboardLayout::Colemak);
+    assert!(config.unlock_all);
+    assert_eq!(config.unlock_policy, UnlockPolicy::Percent(80));
+    assert_eq!(config.profile.as_deref(), Some("work"));
+    assert_eq!(config.key_log, KeyLog::Scrub);
+    assert_eq!(config.history, HistoryRetention::Last(50));
+    assert_eq!(
+        config.state_dir,
+        Some(PathBuf::from("/home/me/dotfiles/kata"))
+    );
+    assert!(config.session_options().briefing);
+    assert!(config.session_options().hardcore);
+    assert!(config.reduce_motion);
+    assert!(config.feedback.bell);
+    assert_eq!(
+        config.feedback.command(FeedbackEvent::NewBest),
+        Some("paplay done.oga")
+    );
+    assert_eq!(config.feedback.command(FeedbackEvent::Failed), None);
+    assert_eq!(config.goals[0].kind, GoalKind::Challenges);
+    assert_eq!(config.opponents[0].name, "Golfer");
+    assert_eq!(config.opponents[0].par_tenths, 11);
+    assert_eq!(config.opponents[0].categories, ["advanced"]);
+    let launcher = config.launcher();
+    assert_eq!(launcher.bin, "/opt/nvim/bin/nvim");
+    assert_eq!(launcher.args, ["--clean"]);
+    assert_eq!(
+        launcher.session.shell.allowed_programs(),
+        Some(&["sort".to_string(), "column".to_string()][..])
+    );
+}
+
+#[test]
+fn test_shell_discourage() {
+    let config: Config = toml::from_str("[shell]\nmode = \"discourage\"").unwrap();
+    assert_eq!(config.shell.allowed_programs(), Some(&[][..]));
+    assert!(toml::from_str::<Config>("[shell]\nmode = \"sometimes\"").is_err());
+    // Typed commands are all it can refuse, so there is no mode claiming to block
+    assert!(toml::from_str::<Config>("[shell]\nmode = \"block\"").is_err());
+}
+
+#[test]
+fn test_vim_editor() {
+    let config: Config = toml::from_str("editor = \"vim\"").unwrap();
+    let launcher = config.launcher();
+    assert_eq!(launcher.editor, EditorKind::Vim);
+    assert_eq!(launcher.bin, "vim");
+
+    let config: Config = toml::from_str("editor = \"nvim\"").unwrap();
+    assert_eq!(config.launcher().editor, EditorKind::Neovim);
+    assert!(toml::from_str::<Config>("editor = \"emacs\"").is_err());
+}
+
+#[test]
+fn test_profile_names() {
+    assert!(is_valid_profile("work"));
+    assert!(is_valid_profile("alt_2-b"));
+    assert!(!is_valid_profile(""));
+    assert!(!is_valid_profile("../save"));
+    assert!(!is_valid_profile("a b"));
+}
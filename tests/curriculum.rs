@@ -1,8 +1,11 @@
 use std::fs;
 use std::path::PathBuf;
 
-use nvimkata::challenge::count_keystrokes;
-use nvimkata::curriculum::load_curriculum;
+use nvimkata::challenge::{Grade, count_keystrokes};
+use nvimkata::curriculum::{load_curriculum, load_curriculum_with};
+use nvimkata::error::CurriculumError;
+use nvimkata::nvim::Outcome;
+use nvimkata::state::{self, GameState};
 
 #[test]
 fn test_load_curriculum_from_fixture() {
@@ -33,13 +36,15 @@ content = "world"
     .unwrap();
 
     let topics = load_curriculum(&tmp);
-    assert_eq!(topics.len(), 16);
-    assert_eq!(topics[0].name, "Advanced Motions");
-    assert_eq!(topics[0].challenges.len(), 1);
-    assert_eq!(topics[0].challenges[0].id, "motion_001");
+    assert_eq!(topics.len(), 17);
+    assert_eq!(topics[0].name, "Tutorial");
+    assert_eq!(topics[1].name, "Advanced Motions");
+    assert_eq!(topics[1].challenges.len(), 1);
+    assert_eq!(topics[1].challenges[0].id, "motion_001");
 
     // Topics without dirs have empty challenge lists
-    assert!(topics[1].challenges.is_empty());
+    assert!(topics[0].challenges.is_empty());
+    assert!(topics[2].challenges.is_empty());
 
     let _ = fs::remove_dir_all(&tmp);
 }
@@ -53,16 +58,17 @@ fn test_all_challenge_files_parse() {
     for topic in &topics {
         for challenge in &topic.challenges {
             total += 1;
+            let challenge = challenge.loaded().unwrap();
             if challenge.par_keystrokes == 0 && !challenge.is_freestyle() {
                 errors.push(format!("{}: par_keystrokes is 0", challenge.id));
             }
-            if challenge.start.content.is_empty() {
+            if challenge.start().content.is_empty() {
                 errors.push(format!("{}: start content is empty", challenge.id));
             }
-            if challenge.target.content.is_empty() {
+            if challenge.target().content.is_empty() {
                 errors.push(format!("{}: target content is empty", challenge.id));
             }
-            if challenge.start.content == challenge.target.content {
+            if challenge.start().content == challenge.target().content {
                 errors.push(format!(
                     "{}: start and target content are identical",
                     challenge.id
@@ -108,7 +114,8 @@ fn test_par_auto_calculated_from_perfect_moves() {
     let mut errors = Vec::new();
     for topic in &topics {
         for challenge in &topic.challenges {
-            if challenge.is_freestyle() {
+            // Macro challenges are graded on the macro, not on the perfect moves
+            if challenge.is_freestyle() || challenge.recorded_macro.is_some() {
                 continue;
             }
             if let Some(moves) = &challenge.perfect_moves {
@@ -148,100 +155,31 @@ fn test_perfect_moves_produce_target() {
     let mut errors = Vec::new();
     let mut checked = 0;
     let timeout = Duration::from_secs(5);
-
-    let tmp = std::env::temp_dir().join("nvimkata_test");
-    let _ = fs::create_dir_all(&tmp);
+    let launcher = nvimkata::nvim::Launcher::default();
 
     for topic in &topics {
         for challenge in &topic.challenges {
             let Some(moves) = &challenge.perfect_moves else {
                 continue;
             };
+            let challenge = challenge.loaded().unwrap();
 
-            let buffer = tmp.join(format!("test_{}", challenge.id));
-            fs::write(&buffer, &challenge.start.content).unwrap();
-
-            let moves_lua: String = moves
-                .iter()
-                .map(|m| {
-                    let escaped = nvimkata::nvim::escape_for_lua_sq(m);
-                    format!("'{escaped}'")
-                })
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            // Concatenate all moves and feed at once so insert-mode
-            // sequences that span adjacent moves work correctly.
-            // do_lt (3rd arg) is true so <lt> converts to literal '<'.
-            // Write/quit is a separate -c command to avoid timeouts.
-            let lua = format!(
-                "lua local ms = {{{}}}; \
-                 local all = ''; \
-                 for _, m in ipairs(ms) do \
-                   all = all .. vim.api.nvim_replace_termcodes(m, true, true, true) \
-                 end; \
-                 vim.api.nvim_feedkeys( \
-                   all .. vim.api.nvim_replace_termcodes('<Esc>', true, true, true), \
-                   'ntx', false)",
-                moves_lua
-            );
-
-            let result = std::process::Command::new("nvim")
-                .arg("--headless")
-                .arg("-u")
-                .arg("NONE")
-                .arg("-i")
-                .arg("NONE")
-                .arg("--cmd")
-                .arg("set noswapfile noundofile nobackup nowritebackup")
-                .arg("-c")
-                .arg(&lua)
-                .arg("-c")
-                .arg("silent! write | qall!")
-                .arg(&buffer)
-                .spawn()
-                .and_then(|mut child| {
-                    let start = std::time::Instant::now();
-                    loop {
-                        match child.try_wait()? {
-                            Some(status) => return Ok(status),
-                            None if start.elapsed() > timeout => {
-                                let _ = child.kill();
-                                let _ = child.wait();
-                                return Err(std::io::Error::new(
-                                    std::io::ErrorKind::TimedOut,
-                                    "nvim timed out",
-                                ));
-                            }
-                            None => std::thread::sleep(Duration::from_millis(50)),
-                        }
-                    }
-                });
-
-            match result {
-                Ok(status) if status.success() => {
-                    let content = fs::read_to_string(&buffer).unwrap_or_default();
-                    let result_norm = nvimkata::nvim::normalize(&content);
-                    let target_norm = nvimkata::nvim::normalize(&challenge.target.content);
-                    if result_norm != target_norm {
-                        errors.push(format!("{}: buffer does not match target", challenge.id));
-                    }
+            match nvimkata::nvim::run_headless(&launcher, &challenge, moves, timeout) {
+                Ok(result) if result.buffer_matches => {}
+                Ok(result) if result.outcome == Outcome::TimedOut => {
+                    errors.push(format!("{}: timed out", challenge.id));
                 }
-                Ok(status) => {
-                    errors.push(format!("{}: nvim exited with {status}", challenge.id));
+                Ok(_) => {
+                    errors.push(format!("{}: buffer does not match target", challenge.id));
                 }
                 Err(e) => {
                     errors.push(format!("{}: {e}", challenge.id));
                 }
             }
-
-            let _ = fs::remove_file(&buffer);
             checked += 1;
         }
     }
 
-    let _ = fs::remove_dir_all(&tmp);
-
     assert!(checked > 0, "No challenges with perfect_moves found");
     if !errors.is_empty() {
         panic!(
@@ -252,3 +190,363 @@ fn test_perfect_moves_produce_target() {
         );
     }
 }
+
+#[test]
+fn test_topic_curve_applies_to_challenges_without_own_curve() {
+    let tmp = std::env::temp_dir().join("rlv_test_topic_curve");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+
+    let challenge = |id: &str, extra: &str| {
+        format!(
+            r#"
+id = "{id}"
+version = "1.0.0"
+title = "Test"
+topic = "motions"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 10
+{extra}
+
+[start]
+content = "hello"
+
+[target]
+content = "world"
+"#
+        )
+    };
+    fs::write(
+        dir.join("topic.toml"),
+        "thresholds = [12, 16, 20, 26, 30]\n",
+    )
+    .unwrap();
+    fs::write(dir.join("motion_001.toml"), challenge("motion_001", "")).unwrap();
+    fs::write(
+        dir.join("motion_002.toml"),
+        challenge("motion_002", "thresholds = [10, 11, 12, 13, 14]"),
+    )
+    .unwrap();
+
+    let topics = load_curriculum(&tmp);
+    // topic.toml is not parsed as a challenge
+    assert_eq!(topics[1].challenges.len(), 2);
+    assert_eq!(
+        topics[1].challenges[0].thresholds,
+        Some([12, 16, 20, 26, 30])
+    );
+    assert_eq!(
+        topics[1].challenges[1].thresholds,
+        Some([10, 11, 12, 13, 14])
+    );
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_topic_intro_is_shared_with_its_challenges() {
+    let tmp = std::env::temp_dir().join("rlv_test_topic_intro");
+    let _ = fs::remove_dir_all(&tmp);
+    let registers = tmp.join("03_registers");
+    let macros = tmp.join("05_macros");
+    fs::create_dir_all(&registers).unwrap();
+    fs::create_dir_all(&macros).unwrap();
+    let challenge = |id: &str| {
+        format!(
+            "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"Test\"\ntopic = \"t\"\n\
+             difficulty = 1\n[start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+        )
+    };
+    fs::write(registers.join("reg_001.toml"), challenge("reg_001")).unwrap();
+    fs::write(
+        registers.join("intro.md"),
+        "# Registers\n`\"0` holds the last yank\n",
+    )
+    .unwrap();
+    fs::write(macros.join("macro_001.toml"), challenge("macro_001")).unwrap();
+    // A blank intro counts as none
+    fs::write(macros.join("intro.md"), "\n  \n").unwrap();
+
+    let (topics, _) = load_curriculum_with(&tmp, &[], None);
+    let registers = topics.iter().find(|t| t.id == 3).unwrap();
+    let intro = "# Registers\n`\"0` holds the last yank\n";
+    assert_eq!(registers.intro.as_deref(), Some(intro));
+    assert_eq!(registers.challenges[0].intro.as_deref(), Some(intro));
+    let macros = topics.iter().find(|t| t.id == 5).unwrap();
+    assert_eq!(macros.intro, None);
+    assert_eq!(macros.challenges[0].intro, None);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_tutorial_steps_match_perfect_moves() {
+    let challenges_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("challenges");
+    let topics = load_curriculum(&challenges_dir);
+    let tutorial = topics.iter().find(|t| t.id == 0).unwrap();
+    assert!(!tutorial.challenges.is_empty());
+    for challenge in &tutorial.challenges {
+        assert!(
+            challenge.is_tutorial(),
+            "{}: no tutorial steps",
+            challenge.id
+        );
+        let keys = challenge.perfect_moves.as_ref().unwrap().concat();
+        assert_eq!(
+            challenge.tutorial_steps_done(&keys),
+            challenge.tutorial_steps.len(),
+            "{}: perfect moves skip a step",
+            challenge.id
+        );
+    }
+}
+
+#[test]
+fn test_unknown_requirements_are_dropped() {
+    let tmp = std::env::temp_dir().join("rlv_test_requires");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+
+    let challenge = |id: &str, requires: &str| {
+        format!(
+            r#"
+id = "{id}"
+version = "1.0.0"
+title = "Test"
+topic = "motions"
+difficulty = 1
+hint = "hint"
+par_keystrokes = 10
+requires = {requires}
+
+[start]
+content = "hello"
+
+[target]
+content = "world"
+"#
+        )
+    };
+    fs::write(dir.join("motion_001.toml"), challenge("motion_001", "[]")).unwrap();
+    fs::write(
+        dir.join("motion_002.toml"),
+        challenge("motion_002", r#"["motion_001", "motion_002", "nope"]"#),
+    )
+    .unwrap();
+
+    let topics = load_curriculum(&tmp);
+    assert_eq!(topics[1].challenges[1].requires, ["motion_001"]);
+
+    // Of each cycle, the requirement closing it is dropped
+    for (id, requires) in [
+        ("motion_001", r#"["motion_002"]"#),
+        ("motion_002", r#"["motion_001"]"#),
+        ("motion_003", r#"["motion_004"]"#),
+        ("motion_004", r#"["motion_003", "motion_001"]"#),
+    ] {
+        fs::write(dir.join(format!("{id}.toml")), challenge(id, requires)).unwrap();
+    }
+    let (topics, stats) = load_curriculum_with(&tmp, &[], None);
+    let requires: Vec<&[String]> = topics[1]
+        .challenges
+        .iter()
+        .map(|c| c.requires.as_slice())
+        .collect();
+    assert_eq!(
+        requires,
+        [
+            &["motion_002".to_string()][..],
+            &[],
+            &["motion_004".to_string()],
+            &["motion_001".to_string()],
+        ]
+    );
+    let messages: Vec<&str> = stats
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "ignoring cyclic requirement 'motion_001' of motion_002",
+            "ignoring cyclic requirement 'motion_003' of motion_004",
+        ]
+    );
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_cached_curriculum_matches_parsed() {
+    let challenges_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("challenges");
+    let cache = std::env::temp_dir().join("rlv_test_cache_bundled.json");
+    let _ = fs::remove_file(&cache);
+
+    let (parsed, first) = load_curriculum_with(&challenges_dir, &[], Some(&cache));
+    assert_eq!(first.cached, 0);
+    assert!(first.challenges > 100);
+    let (cached, second) = load_curriculum_with(&challenges_dir, &[], Some(&cache));
+    assert_eq!(second.cached, second.files);
+    assert_eq!(second.challenges, first.challenges);
+    assert_eq!(second.lints, first.lints);
+    let json = |topics: &[nvimkata::challenge::Topic]| {
+        let challenges: Vec<_> = topics.iter().flat_map(|t| &t.challenges).collect();
+        serde_json::to_string(&challenges).unwrap()
+    };
+    assert_eq!(json(&cached), json(&parsed));
+
+    let _ = fs::remove_file(&cache);
+}
+
+#[test]
+fn test_cache_reloads_changed_and_drops_deleted_files() {
+    let tmp = std::env::temp_dir().join("rlv_test_cache_changes");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+    let cache = tmp.join("cache.json");
+    let challenge = |title: &str| {
+        format!(
+            "id = \"m1\"\nversion = \"1\"\ntitle = \"{title}\"\ntopic = \"motions\"\n\
+             difficulty = 1\nhint = \"h\"\npar_keystrokes = 3\n\
+             [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+        )
+    };
+    fs::write(dir.join("m1.toml"), challenge("First")).unwrap();
+    fs::write(dir.join("m2.toml"), "not toml").unwrap();
+
+    let (_, stats) = load_curriculum_with(&tmp, &[], Some(&cache));
+    assert_eq!((stats.files, stats.cached, stats.challenges), (2, 0, 1));
+    let broken = fs::canonicalize(dir.join("m2.toml")).unwrap();
+    assert_eq!(stats.diagnostics.len(), 1);
+    assert_eq!(stats.diagnostics[0].path, broken);
+    assert!(stats.diagnostics[0].failed);
+    assert!(
+        stats.diagnostics[0]
+            .message
+            .starts_with("failed to parse: ")
+    );
+    let (_, cached) = load_curriculum_with(&tmp, &[], Some(&cache));
+    assert_eq!(cached.cached, 2);
+    assert_eq!(cached.diagnostics, stats.diagnostics);
+
+    fs::write(dir.join("m1.toml"), challenge("Second title")).unwrap();
+    fs::remove_file(dir.join("m2.toml")).unwrap();
+    let (topics, stats) = load_curriculum_with(&tmp, &[], Some(&cache));
+    assert_eq!((stats.files, stats.cached), (1, 0));
+    assert_eq!(topics[1].challenges[0].title, "Second title");
+    assert!(!fs::read_to_string(&cache).unwrap().contains("m2.toml"));
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_buffers_are_read_when_played() {
+    let tmp = std::env::temp_dir().join("rlv_test_lazy_buffers");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("m1.toml");
+    let challenge = |target: &str| {
+        format!(
+            "id = \"m1\"\nversion = \"1\"\ntitle = \"T\"\ntopic = \"motions\"\n\
+             difficulty = 1\nhint = \"h\"\npar_keystrokes = 3\n\
+             [start]\ncontent = \"a\"\ncursor = [1, 1]\n[target]\ncontent = \"{target}\"\n"
+        )
+    };
+    fs::write(&file, challenge("b")).unwrap();
+    let without_target = challenge("b").replace("[target]\ncontent = \"b\"\n", "");
+    fs::write(dir.join("m2.toml"), without_target.replace("m1", "m2")).unwrap();
+
+    let (topics, stats) = load_curriculum_with(&tmp, &[], None);
+    assert_eq!(topics[1].challenges.len(), 1);
+    assert!(stats.diagnostics[0].failed);
+    assert_eq!(
+        stats.diagnostics[0].message,
+        "missing start or target buffer"
+    );
+    let meta = &topics[1].challenges[0];
+    assert!(meta.start.is_none() && meta.target.is_none());
+    let read = std::panic::catch_unwind(|| meta.target().content.clone());
+    assert!(read.is_err());
+    assert_eq!(
+        meta.source.as_ref().map(|source| source.path.as_path()),
+        Some(file.canonicalize().unwrap().as_path())
+    );
+    let full = meta.loaded().unwrap();
+    assert_eq!(
+        (full.start().content.as_str(), full.start().cursor),
+        ("a", Some([1, 1]))
+    );
+    assert_eq!(full.target().content, "b");
+    assert!(full.loaded().unwrap().source.is_none());
+
+    fs::write(&file, challenge("c")).unwrap();
+    assert_eq!(meta.loaded().unwrap().target().content, "c");
+    fs::write(&file, "id = ").unwrap();
+    assert!(matches!(
+        meta.loaded(),
+        Err(CurriculumError::Parse { path, .. }) if path == meta.source.clone().unwrap().path
+    ));
+    fs::remove_file(&file).unwrap();
+    assert!(matches!(meta.loaded(), Err(CurriculumError::Read { .. })));
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_loaded_challenges_keep_their_content_hash() {
+    let tmp = std::env::temp_dir().join("rlv_test_content_hash");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+    let cache = tmp.join("cache.json");
+    let file = dir.join("m1.toml");
+    let challenge = |target: &str| {
+        format!(
+            "id = \"m1\"\nversion = \"1\"\ntitle = \"T\"\ntopic = \"motions\"\n\
+             difficulty = 1\nhint = \"h\"\npar_keystrokes = 3\n\
+             [start]\ncontent = \"a\"\n[target]\ncontent = \"{target}\"\n"
+        )
+    };
+    fs::write(&file, challenge("b")).unwrap();
+    let load = || {
+        let (mut topics, _) = load_curriculum_with(&tmp, &[], Some(&cache));
+        topics[1].challenges.remove(0)
+    };
+
+    let meta = load();
+    let full = meta.loaded().unwrap().into_owned();
+    assert_eq!(state::challenge_hash(&meta), state::challenge_hash(&full));
+    let mut state = GameState::default();
+    state.record_result("m1", Grade::A, 3, 40, "keys", "1");
+    state.record_content(&full);
+    // Unchanged on the next start, whether parsed or taken from the cache
+    state.mark_stale(std::slice::from_ref(&meta));
+    state.mark_stale(&[load()]);
+    assert!(!state.is_stale("m1"));
+
+    // Older saves without a hash take the hash of the buffers
+    let json = r#"{"challenges":{"m1":{"grade":"A","keystrokes":3,"time_ms":40,"version":"1"}},"stats":{"total_keystrokes":3,"challenges_attempted":1}}"#;
+    let mut old: GameState = serde_json::from_str(json).unwrap();
+    old.mark_stale(std::slice::from_ref(&meta));
+    assert_eq!(
+        old.challenges["m1"].content_hash,
+        Some(state::challenge_hash(&full))
+    );
+    assert_eq!(old.best_version_content("m1").unwrap().target, "b");
+
+    // An edit without a version bump is caught
+    fs::write(&file, challenge("c")).unwrap();
+    state.mark_stale(&[load()]);
+    assert!(state.is_stale("m1"));
+    let kept = state.best_version_content("m1").unwrap();
+    assert_eq!(kept.target, "b");
+    assert_eq!(kept.start_hash, state::content_hash("a"));
+
+    let _ = fs::remove_dir_all(&tmp);
+}
@@ -0,0 +1,37 @@
+use nvimkata::diff::{DiffOp, Row, diff, side_by_side};
+
+#[test]
+fn test_diff_equal_insert_delete() {
+    assert_eq!(
+        diff(&["a", "b"], &["a", "b"]),
+        vec![DiffOp::Equal(0, 0), DiffOp::Equal(1, 1)]
+    );
+    assert_eq!(
+        diff(&["a", "c"], &["a", "b", "c"]),
+        vec![DiffOp::Equal(0, 0), DiffOp::Insert(1), DiffOp::Equal(1, 2)]
+    );
+    assert_eq!(
+        diff(&["a", "b", "c"], &["a", "c"]),
+        vec![DiffOp::Equal(0, 0), DiffOp::Delete(1), DiffOp::Equal(2, 1)]
+    );
+    assert_eq!(diff::<&str>(&[], &["x"]), vec![DiffOp::Insert(0)]);
+}
+
+#[test]
+fn test_side_by_side_pairs_changed_lines() {
+    let rows = side_by_side(&["keep", "old", "gone", "end"], &["keep", "new", "end"]);
+    let row = |left, right, changed| Row {
+        left,
+        right,
+        changed,
+    };
+    assert_eq!(
+        rows,
+        vec![
+            row(Some(0), Some(0), false),
+            row(Some(1), Some(1), true),
+            row(Some(2), None, true),
+            row(Some(3), Some(2), false),
+        ]
+    );
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::PathBuf;
+
+use nvimkata::dirs::{
+    Platform, SessionDir, cache_dir_for, clean_stale_sessions, config_dir_for, data_dir_for,
+};
+use nvimkata::nvim::fnameescape_for;
+
+/// An environment with only the given variables set.
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: Vec<(String, String)> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |name| vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+}
+
+#[test]
+fn test_unix_dirs() {
+    let home = env(&[("HOME", "/home/kata")]);
+    assert_eq!(
+        config_dir_for(Platform::Unix, &home),
+        PathBuf::from("/home/kata/.config/nvimkata")
+    );
+    assert_eq!(
+        data_dir_for(Platform::Unix, &home),
+        PathBuf::from("/home/kata/.local/share/nvimkata")
+    );
+    assert_eq!(
+        cache_dir_for(Platform::Unix, &home),
+        PathBuf::from("/home/kata/.cache/nvimkata")
+    );
+    let xdg = env(&[
+        ("HOME", "/home/kata"),
+        ("XDG_CONFIG_HOME", "/cfg"),
+        ("XDG_DATA_HOME", "/data"),
+        ("XDG_CACHE_HOME", "/cache"),
+    ]);
+    assert_eq!(
+        config_dir_for(Platform::Unix, &xdg),
+        PathBuf::from("/cfg/nvimkata")
+    );
+    assert_eq!(
+        data_dir_for(Platform::Unix, &xdg),
+        PathBuf::from("/data/nvimkata")
+    );
+    assert_eq!(
+        cache_dir_for(Platform::Unix, &xdg),
+        PathBuf::from("/cache/nvimkata")
+    );
+    assert_eq!(
+        config_dir_for(Platform::Unix, env(&[])),
+        PathBuf::from("./.config/nvimkata")
+    );
+}
+
+#[test]
+fn test_windows_dirs() {
+    let appdata = env(&[
+        ("USERPROFILE", r"C:\Users\Kata"),
+        ("APPDATA", r"C:\Users\Kata\AppData\Roaming"),
+        ("LOCALAPPDATA", r"C:\Users\Kata\AppData\Local"),
+        ("XDG_CONFIG_HOME", "/ignored"),
+    ]);
+    assert_eq!(
+        config_dir_for(Platform::Windows, &appdata),
+        PathBuf::from(r"C:\Users\Kata\AppData\Roaming").join("nvimkata")
+    );
+    assert_eq!(
+        data_dir_for(Platform::Windows, &appdata),
+        PathBuf::from(r"C:\Users\Kata\AppData\Local").join("nvimkata")
+    );
+    assert_eq!(
+        cache_dir_for(Platform::Windows, &appdata),
+        PathBuf::from(r"C:\Users\Kata\AppData\Local")
+            .join("nvimkata")
+            .join("cache")
+    );
+    let profile = env(&[("USERPROFILE", r"C:\Users\Kata")]);
+    assert_eq!(
+        data_dir_for(Platform::Windows, &profile),
+        PathBuf::from(r"C:\Users\Kata")
+            .join("AppData")
+            .join("Local")
+            .join("nvimkata")
+    );
+}
+
+#[test]
+fn test_fnameescape() {
+    assert_eq!(
+        fnameescape_for(Platform::Unix, "/tmp/nvimkata/challenge_target"),
+        "/tmp/nvimkata/challenge_target"
+    );
+    assert_eq!(
+        fnameescape_for(Platform::Unix, "/tmp/my dir/a%b#c"),
+        r"/tmp/my\ dir/a\%b\#c"
+    );
+    assert_eq!(
+        fnameescape_for(
+            Platform::Windows,
+            r"C:\Users\John Doe\AppData\Local\Temp\nvimkata"
+        ),
+        r"C:/Users/John\ Doe/AppData/Local/Temp/nvimkata"
+    );
+}
+
+#[test]
+fn test_session_dirs_are_unique_and_removed() {
+    let parent = std::env::temp_dir().join("rlv_sessions_unique");
+    let a = SessionDir::create_in(&parent, "challenge").unwrap();
+    let b = SessionDir::create_in(&parent, "challenge").unwrap();
+    assert_ne!(a.path(), b.path());
+    assert!(a.path().is_dir() && b.path().is_dir());
+    let name = a.path().file_name().unwrap().to_str().unwrap().to_string();
+    assert!(name.starts_with(&format!("challenge-{}-", std::process::id())));
+
+    let path = a.path().to_path_buf();
+    fs::write(path.join("results"), "1").unwrap();
+    drop(a);
+    assert!(!path.exists());
+    assert!(b.path().is_dir());
+    drop(b);
+    let _ = fs::remove_dir_all(&parent);
+}
+
+#[test]
+fn test_clean_stale_sessions() {
+    let parent = std::env::temp_dir().join("rlv_sessions_stale");
+    let _ = fs::remove_dir_all(&parent);
+    let live = SessionDir::create_in(&parent, "vim").unwrap();
+    // No process has this id: pids stay far below it
+    let dead = parent.join("challenge-4000000000-abc");
+    fs::create_dir_all(&dead).unwrap();
+    let unrelated = parent.join("notes");
+    fs::create_dir_all(&unrelated).unwrap();
+
+    if std::path::Path::new("/proc").is_dir() {
+        assert_eq!(clean_stale_sessions(&parent), 1);
+        assert!(!dead.exists());
+    }
+    assert!(live.path().is_dir());
+    assert!(unrelated.is_dir());
+    drop(live);
+    let _ = fs::remove_dir_all(&parent);
+}
@@ -0,0 +1,142 @@
+use nvimkata::duel::{self, Message};
+use nvimkata::versus::Turn;
+
+fn turn(player: &str, solved: bool, keystrokes: u32) -> Turn {
+    Turn {
+        player: player.to_string(),
+        solved,
+        keystrokes,
+        time_ms: 4000,
+    }
+}
+
+#[test]
+fn test_protocol_lines() {
+    let join = Message::Join {
+        room: "lunch".to_string(),
+        player: "bob".to_string(),
+        challenge: None,
+    };
+    assert_eq!(
+        join.to_line().unwrap(),
+        "{\"type\":\"join\",\"room\":\"lunch\",\"player\":\"bob\"}\n"
+    );
+    assert_eq!(Message::from_line(&join.to_line().unwrap()).unwrap(), join);
+    assert_eq!(
+        Message::from_line(r#"{"type":"start","challenge":"t1","opponent":"ada"}"#).unwrap(),
+        Message::Start {
+            challenge: "t1".to_string(),
+            opponent: "ada".to_string(),
+        }
+    );
+    let result = Message::Result {
+        turn: turn("ada", true, 12),
+    };
+    assert_eq!(
+        Message::from_line(&result.to_line().unwrap()).unwrap(),
+        result
+    );
+    assert!(Message::from_line(r#"{"type":"cheat"}"#).is_err());
+
+    assert_eq!(duel::with_port("example.org"), "example.org:7373");
+    assert_eq!(duel::with_port("10.0.0.2:9000"), "10.0.0.2:9000");
+    assert_eq!(
+        duel::banner(&turn("ada", true, 12)),
+        "ada finished in 12 keys"
+    );
+    assert_eq!(
+        duel::banner(&turn("ada", false, 40)),
+        "ada gave up after 40 keys"
+    );
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn test_relay_starts_both_players_and_passes_results_on() {
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || duel::relay(&listener));
+
+    let error = duel::Connection::join(&addr, "lunch", "bob", None).err();
+    assert!(error.unwrap().to_string().contains("nobody is waiting"));
+
+    let host_addr = addr.clone();
+    let host = std::thread::spawn(move || {
+        duel::Connection::join(&host_addr, "lunch", "ada", Some("t1")).unwrap()
+    });
+    // Retry until the host is waiting in the room
+    let (guest, challenge, opponent) = loop {
+        match duel::Connection::join(&addr, "lunch", "bob", None) {
+            Ok(joined) => break joined,
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+        }
+    };
+    assert_eq!((challenge.as_str(), opponent.as_str()), ("t1", "ada"));
+    let (host, challenge, opponent) = host.join().unwrap();
+    assert_eq!((challenge.as_str(), opponent.as_str()), ("t1", "bob"));
+
+    let (banner, banners) = mpsc::channel();
+    let host = host.watch(move |turn| banner.send(duel::banner(turn)).unwrap());
+    let guest = guest.watch(|_| {});
+    guest.send_result(&turn("bob", true, 12)).unwrap();
+    assert_eq!(banners.recv().unwrap(), "bob finished in 12 keys");
+    host.send_result(&turn("ada", true, 9)).unwrap();
+    assert_eq!(guest.opponent(), Some(turn("ada", true, 9)));
+    assert_eq!(host.opponent(), Some(turn("bob", true, 12)));
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn test_relay_drops_hosts_that_left_and_endless_lines() {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || duel::relay(&listener));
+
+    let join = Message::Join {
+        room: "lunch".to_string(),
+        player: "ada".to_string(),
+        challenge: Some("t1".to_string()),
+    };
+    let mut host = TcpStream::connect(&addr).unwrap();
+    host.write_all(join.to_line().unwrap().as_bytes()).unwrap();
+    // Retry until the host is waiting in the room
+    while !duel::Connection::join(&addr, "lunch", "ada", None)
+        .err()
+        .is_some_and(|e| e.to_string().contains("already in the room"))
+    {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let error = duel::Connection::join(&addr, "lunch", "bob", Some("t2")).err();
+    assert!(
+        error
+            .unwrap()
+            .to_string()
+            .contains("room lunch is playing t1")
+    );
+    drop(host);
+    std::thread::sleep(Duration::from_millis(50));
+    let error = duel::Connection::join(&addr, "lunch", "bob", None).err();
+    assert!(error.unwrap().to_string().contains("nobody is waiting"));
+
+    // A line without end is cut off instead of read on forever
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let _ = stream.write_all(&vec![b'x'; 512 * 1024]);
+    let closed = match stream.read(&mut [0; 64]) {
+        Ok(read) => read == 0,
+        Err(e) => !matches!(
+            e.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ),
+    };
+    assert!(closed);
+}
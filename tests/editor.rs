@@ -0,0 +1,211 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use nvimkata::challenge::Challenge;
+use nvimkata::editor::{self, EditorKind, SessionOptions, Version, check_version, shell_quote};
+use nvimkata::error::{Error, NvimError, SessionError};
+use nvimkata::nvim::Launcher;
+
+/// An executable shell script running `body`.
+fn fake_script(name: &str, body: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("rlv_editor");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// An executable script printing `output` for `--version`.
+fn fake_bin(name: &str, output: &str) -> PathBuf {
+    fake_script(name, &format!("printf '{output}'"))
+}
+
+fn launcher(editor: EditorKind, bin: &Path) -> Launcher {
+    Launcher {
+        editor,
+        bin: bin.display().to_string(),
+        args: Vec::new(),
+        version: None,
+        session: SessionOptions::default(),
+    }
+}
+
+#[test]
+fn test_parse_version() {
+    assert_eq!(Version::parse("NVIM v0.10.2"), Some(Version::new(0, 10, 2)));
+    assert_eq!(
+        Version::parse("NVIM v0.11.0-dev-1234+gabcdef"),
+        Some(Version::new(0, 11, 0))
+    );
+    assert_eq!(
+        Version::parse("VIM - Vi IMproved 9.1 (2024 Jan 02, compiled Jan 03 2024)"),
+        Some(Version::new(9, 1, 0))
+    );
+    assert_eq!(Version::parse("NVIM"), None);
+    assert!(Version::new(0, 9, 5) < Version::new(0, 10, 0));
+    assert_eq!(Version::new(0, 10, 2).to_string(), "0.10.2");
+}
+
+// One test, so no other test forks while a script is open for writing (ETXTBSY)
+#[test]
+fn test_check_version() {
+    let nvim = fake_bin("nvim", "NVIM v0.10.2\\nBuild type: Release\\n");
+    assert_eq!(
+        check_version(&launcher(EditorKind::Neovim, &nvim)),
+        Ok(Version::new(0, 10, 2))
+    );
+    let vim = fake_bin("vim", "VIM - Vi IMproved 9.0 (2022 Jun 28)\\n");
+    assert_eq!(
+        check_version(&launcher(EditorKind::Vim, &vim)),
+        Ok(Version::new(9, 0, 0))
+    );
+
+    let old = fake_bin("old_nvim", "NVIM v0.7.2\\n");
+    let err = check_version(&launcher(EditorKind::Neovim, &old)).unwrap_err();
+    assert!(
+        err.contains("0.7.2") && err.contains("needs 0.8.0 or newer"),
+        "{err}"
+    );
+
+    let vim = fake_bin("not_nvim", "VIM - Vi IMproved 9.0\\n");
+    let err = check_version(&launcher(EditorKind::Neovim, &vim)).unwrap_err();
+    assert!(err.contains("does not look like neovim"), "{err}");
+    assert!(err.contains("VIM - Vi IMproved 9.0"), "{err}");
+
+    let missing = PathBuf::from("/nonexistent/rlv_nvim");
+    let err = check_version(&launcher(EditorKind::Neovim, &missing)).unwrap_err();
+    assert!(err.contains("was not found"), "{err}");
+    assert!(err.contains("--nvim-bin"), "{err}");
+
+    // A session that fails before its UI starts reports what it wrote to stderr
+    let broken = fake_script(
+        "broken_nvim",
+        "printf 'E1: first\\n\\nE2: second\\n' >&2\nexit 3",
+    );
+    let broken = launcher(EditorKind::Neovim, &broken);
+    let err = editor::editor(&broken).run_sandbox(None).unwrap_err();
+    assert!(
+        matches!(&err, Error::Nvim(NvimError::Exited { stderr, .. }) if stderr == &["E1: first", "E2: second"]),
+        "{err:?}"
+    );
+    assert_eq!(
+        err.to_string(),
+        "nvim exited with status: exit status: 3\nE1: first\nE2: second"
+    );
+    let hints = editor::launch_hints(&broken, Some("motion_001"), &err);
+    assert_eq!(hints.len(), 3, "{hints:?}");
+    assert!(hints[0].contains("--clean"), "{hints:?}");
+    assert!(hints[1].contains("play --dry-run motion_001"), "{hints:?}");
+}
+
+#[test]
+fn test_launch_hints_for_missing_editor() {
+    let mut missing = launcher(EditorKind::Vim, Path::new("/nonexistent/rlv_vim"));
+    missing.args = vec!["--clean".to_string()];
+    let err = editor::editor(&missing).run_sandbox(None).unwrap_err();
+    assert!(
+        matches!(&err, Error::Nvim(NvimError::NotFound { bin }) if bin == "/nonexistent/rlv_vim"),
+        "{err:?}"
+    );
+    let hints = editor::launch_hints(&missing, None, &err);
+    assert_eq!(hints.len(), 1);
+    assert!(hints[0].contains("vim ('/nonexistent/rlv_vim') was not found"));
+
+    let err = Error::Nvim(NvimError::Spawn {
+        bin: missing.bin.clone(),
+        source: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+    });
+    let hints = editor::launch_hints(&missing, None, &err);
+    assert_eq!(
+        hints,
+        ["'/nonexistent/rlv_vim --version' checks that vim itself starts"]
+    );
+
+    // Nothing to try with the editor when the session files can't be written
+    let err = Error::Session(SessionError {
+        path: PathBuf::from("/nonexistent/session"),
+        source: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+    });
+    assert!(editor::launch_hints(&missing, None, &err).is_empty());
+}
+
+#[test]
+fn test_shell_quote() {
+    assert_eq!(shell_quote("--cmd"), "--cmd");
+    assert_eq!(shell_quote("/tmp/a_b.lua"), "/tmp/a_b.lua");
+    assert_eq!(shell_quote("set nowrap"), "'set nowrap'");
+    assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    assert_eq!(shell_quote(""), "''");
+}
+
+#[test]
+fn test_plan_challenge_writes_nothing() {
+    let challenge: Challenge = toml::from_str(
+        r#"
+id = "motion_001"
+version = "1.0.0"
+title = "Plan"
+topic = "motions"
+difficulty = 1
+description = "Read me"
+hint = "hint"
+par_keystrokes = 3
+
+[start]
+content = "one\ntwo"
+
+[target]
+content = "two"
+"#,
+    )
+    .unwrap();
+    let dir = std::env::temp_dir().join("rlv_editor_plan");
+    let _ = fs::remove_dir_all(&dir);
+    for kind in [EditorKind::Neovim, EditorKind::Vim] {
+        let mut launcher = launcher(kind, Path::new(kind.default_bin()));
+        launcher.args = vec!["--clean".to_string()];
+        let plan = editor::editor(&launcher).plan_challenge(&challenge, 1, &dir);
+        let names: Vec<_> = plan
+            .files
+            .iter()
+            .map(|(path, _)| path.strip_prefix(&dir).unwrap().to_string_lossy())
+            .collect();
+        assert!(names.contains(&"challenge_buffer".into()), "{names:?}");
+        assert!(names.contains(&"instructions".into()), "{names:?}");
+        let line = plan.command_line();
+        assert!(line.starts_with(&format!("{} --clean --cmd ", kind.default_bin())));
+        assert!(line.ends_with(&dir.join("challenge_buffer").display().to_string()));
+    }
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_hardcore_needs_a_key_budget() {
+    let keyed: Challenge = toml::from_str(
+        "id = \"k\"\nversion = \"1.0.0\"\ntitle = \"T\"\ntopic = \"t\"\ndifficulty = 1\n\
+         hint = \"h\"\npar_keystrokes = 4\n[start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n",
+    )
+    .unwrap();
+    let freestyle = Challenge {
+        par_keystrokes: 0,
+        ..keyed.clone()
+    };
+    assert!(keyed.has_key_budget());
+    assert!(!freestyle.has_key_budget());
+
+    let dir = std::env::temp_dir().join("rlv_editor_hardcore");
+    let mut launcher = launcher(EditorKind::Neovim, Path::new("nvim"));
+    launcher.session.hardcore = true;
+    let script = |challenge: &Challenge| {
+        let plan = editor::editor(&launcher).plan_challenge(challenge, 1, &dir);
+        plan.files
+            .into_iter()
+            .find(|(path, _)| path.extension().is_some_and(|ext| ext == "lua"))
+            .unwrap()
+            .1
+    };
+    assert!(script(&keyed).contains("_VK_HARDCORE = true\n"));
+    assert!(script(&freestyle).contains("_VK_HARDCORE = false\n"));
+}
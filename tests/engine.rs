@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::PathBuf;
+
+use nvimkata::challenge::Grade;
+use nvimkata::config::Config;
+use nvimkata::error::{ConfigError, CurriculumError, Error, NvimError, StateError};
+use nvimkata::{LoadOptions, Nvimkata};
+
+/// Options loading the challenges in `dir` only, with the saves in a
+/// directory of their own. Every test uses the same save directory, since
+/// only the first load in a process sets it.
+fn options(dir: PathBuf) -> LoadOptions {
+    LoadOptions {
+        config: Config {
+            state_dir: Some(std::env::temp_dir().join("rlv_engine_state")),
+            nvim_bin: Some("/nonexistent/rlv_nvim".to_string()),
+            ..Config::default()
+        },
+        challenges_dir: Some(dir),
+        profile: Some("engine".to_string()),
+        packs: false,
+        cache: false,
+    }
+}
+
+fn write_challenges(dir: &std::path::Path) {
+    let motions = dir.join("01_motions");
+    fs::create_dir_all(&motions).unwrap();
+    for (id, start, target) in [("m1", "foo bar", "bar"), ("m2", "a", "b")] {
+        fs::write(
+            motions.join(format!("{id}.toml")),
+            format!(
+                "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"T {id}\"\ntopic = \"motions\"\n\
+                 difficulty = 1\nhint = \"h\"\npar_keystrokes = 2\n\
+                 [start]\ncontent = \"{start}\"\n[target]\ncontent = \"{target}\"\n"
+            ),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_load_embed_and_finish() {
+    let tmp = std::env::temp_dir().join("rlv_engine_load");
+    let _ = fs::remove_dir_all(&tmp);
+    write_challenges(&tmp);
+    let save = std::env::temp_dir().join("rlv_engine_state/profiles/engine.json");
+    let _ = fs::remove_file(&save);
+
+    let mut kata = Nvimkata::load(options(tmp.clone())).unwrap();
+    let loaded: usize = kata.topics().iter().map(|t| t.challenges.len()).sum();
+    assert_eq!(loaded, 2);
+    assert!(kata.load_stats().diagnostics.is_empty());
+    let (number, challenge) = kata.challenge("m2").unwrap();
+    assert_eq!((number, challenge.title.as_str()), (2, "T m2"));
+    assert!(kata.challenge("nope").is_none());
+
+    let sessions = tmp.join("sessions");
+    let session = kata.embed("m1", &sessions).unwrap();
+    assert_eq!(fs::read_to_string(session.start_path()).unwrap(), "foo bar");
+    // As written by the runtime when the target is reached
+    let results = session.script_path().parent().unwrap().join("results");
+    fs::write(results, "2\n1500\ndw\n").unwrap();
+    let result = kata.finish("m1", &session, &["bar".to_string()]).unwrap();
+    assert!(result.buffer_matches);
+    assert_eq!(kata.state().best_grade("m1"), Some(Grade::A));
+    assert!(fs::read_to_string(&save).unwrap().contains("\"m1\""));
+
+    assert!(matches!(
+        kata.embed("nope", &sessions),
+        Err(Error::Curriculum(CurriculumError::UnknownChallenge { id })) if id == "nope"
+    ));
+    // The editor is checked before anything is played
+    assert!(matches!(
+        kata.play("m2"),
+        Err(Error::Nvim(NvimError::Unusable { message })) if message.contains("was not found")
+    ));
+
+    drop(session);
+    let _ = fs::remove_dir_all(&tmp);
+    let _ = fs::remove_file(&save);
+}
+
+#[test]
+fn test_load_errors() {
+    let empty = std::env::temp_dir().join("rlv_engine_empty");
+    let _ = fs::remove_dir_all(&empty);
+    fs::create_dir_all(&empty).unwrap();
+    assert!(matches!(
+        Nvimkata::load(options(empty.clone())),
+        Err(Error::Curriculum(CurriculumError::Empty { dir })) if dir == empty
+    ));
+
+    let tmp = std::env::temp_dir().join("rlv_engine_profile");
+    let _ = fs::remove_dir_all(&tmp);
+    write_challenges(&tmp);
+    let invalid = LoadOptions {
+        profile: Some("../work".to_string()),
+        ..options(tmp.clone())
+    };
+    assert!(matches!(
+        Nvimkata::load(invalid),
+        Err(Error::State(StateError::InvalidProfile { name })) if name == "../work"
+    ));
+
+    // The loads above applied the settings of the process
+    let mut elsewhere = options(tmp.clone());
+    elsewhere.config.state_dir = Some(tmp.join("state"));
+    assert!(matches!(
+        Nvimkata::load(elsewhere),
+        Err(Error::Config(ConfigError {
+            setting: "state_dir"
+        }))
+    ));
+
+    let _ = fs::remove_dir_all(&empty);
+    let _ = fs::remove_dir_all(&tmp);
+}
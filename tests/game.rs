@@ -0,0 +1,57 @@
+use std::fs;
+
+use nvimkata::curriculum::load_curriculum_with;
+use nvimkata::game;
+use nvimkata::state::GameState;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+#[test]
+fn test_detail_panel_of_a_deleted_challenge_file() {
+    let tmp = std::env::temp_dir().join("rlv_test_detail_unreadable");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("01_motions");
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("m1.toml");
+    fs::write(
+        &file,
+        "id = \"m1\"\nversion = \"1\"\ntitle = \"T\"\ntopic = \"motions\"\n\
+         difficulty = 1\nhint = \"h\"\npar_keystrokes = 3\n\
+         [start]\ncontent = \"a\"\ncursor = [1, 1]\n[target]\ncontent = \"target line\"\n",
+    )
+    .unwrap();
+    let (topics, _) = load_curriculum_with(&tmp, &[], None);
+    let challenge = &topics[1].challenges[0];
+    fs::remove_file(&file).unwrap();
+
+    let error = game::with_buffers(challenge).unwrap_err();
+    let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    terminal
+        .draw(|frame| {
+            let area = frame.area();
+            game::render_challenge_detail(
+                frame,
+                area,
+                challenge,
+                Some(&error),
+                &GameState::default(),
+                &mut 0,
+                false,
+                false,
+            );
+        })
+        .unwrap();
+    let text: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(ratatui::buffer::Cell::symbol)
+        .collect();
+    assert!(text.contains("File unreadable: "), "{text}");
+    assert!(!text.contains("Cursor:"));
+    assert!(!text.contains("Preview:"));
+    assert!(!text.contains("target line"));
+
+    let _ = fs::remove_dir_all(&tmp);
+}
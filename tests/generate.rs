@@ -0,0 +1,33 @@
+use nvimkata::challenge::count_keystrokes;
+use nvimkata::generate::{self, TEMPLATES};
+use nvimkata::lint;
+
+#[test]
+fn test_same_seed_same_challenge() {
+    let template = generate::template("delete_word").unwrap();
+    let first = generate::generate(template, 42);
+    assert_eq!(first, generate::generate(template, 42));
+    assert_eq!(first.id, "gen_delete_word_42");
+    assert!((0..20).any(|seed| generate::generate(template, seed).start != first.start));
+    assert!(generate::template("nope").is_none());
+}
+
+#[test]
+fn test_generated_challenges_are_playable() {
+    for template in TEMPLATES {
+        for seed in 0..50 {
+            let generated = generate::generate(template, seed);
+            let toml = generated.to_toml();
+            assert!(toml.starts_with(&format!(
+                "# Generated by `nvimkata gen --template {} --seed {seed}`\n",
+                template.name
+            )));
+            let challenge = generated.challenge().unwrap();
+            assert_eq!(challenge.id, generated.id);
+            assert_ne!(challenge.start().content, challenge.target().content);
+            let keys = challenge.perfect_moves.as_ref().unwrap().concat();
+            assert_eq!(challenge.par_keystrokes as usize, count_keystrokes(&keys));
+            assert!(lint::check(&challenge).is_empty(), "{}", challenge.id);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use nvimkata::goals::{Goal, GoalKind, MAX_FREEZES, Streak, format_day, parse_day};
+use nvimkata::state::{DayActivity, GameState};
+
+fn goal(grace_days: u32) -> Goal {
+    Goal {
+        kind: GoalKind::Challenges,
+        target: 2,
+        grace_days,
+    }
+}
+
+/// Activity where each listed day meets a 2-challenge goal.
+fn met_days(days: impl IntoIterator<Item = u32>) -> BTreeMap<u32, DayActivity> {
+    days.into_iter()
+        .map(|d| {
+            (
+                d,
+                DayActivity {
+                    challenges: 2,
+                    secs: 60,
+                },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_streak_counts_consecutive_met_days() {
+    let activity = met_days(100..105);
+    let streak = goal(0).streak(&activity, 104);
+    assert_eq!(streak.current, 5);
+    assert_eq!(streak.best, 5);
+    // Today not being met yet does not break the streak
+    assert_eq!(goal(0).streak(&activity, 105).current, 5);
+}
+
+#[test]
+fn test_grace_days_pause_the_streak() {
+    let mut activity = met_days(100..103);
+    activity.extend(met_days(104..106));
+    assert_eq!(goal(1).streak(&activity, 105).current, 5);
+    assert_eq!(goal(0).streak(&activity, 105).current, 2);
+    assert_eq!(goal(0).streak(&activity, 105).best, 3);
+}
+
+#[test]
+fn test_freezes_are_earned_and_spent() {
+    // 7 met days bank a freeze that covers a two-day gap with one grace day
+    let mut activity = met_days(100..107);
+    assert_eq!(
+        goal(1).streak(&activity, 106),
+        Streak {
+            current: 7,
+            best: 7,
+            freezes: 1
+        }
+    );
+    activity.extend(met_days(109..110));
+    let streak = goal(1).streak(&activity, 109);
+    assert_eq!(streak.current, 8);
+    assert_eq!(streak.freezes, 0);
+
+    // Freezes are capped
+    let long = met_days(0..70);
+    assert_eq!(goal(0).streak(&long, 69).freezes, MAX_FREEZES);
+}
+
+#[test]
+fn test_minutes_goal_and_activity_log() {
+    let mut state = GameState::default();
+    state.log_activity(200, true, 600);
+    state.log_activity(200, false, 700);
+    assert_eq!(
+        state.activity_on(200),
+        DayActivity {
+            challenges: 1,
+            secs: 1300
+        }
+    );
+    let minutes = Goal {
+        kind: GoalKind::Minutes,
+        target: 20,
+        grace_days: 1,
+    };
+    assert_eq!(minutes.progress(&state.activity_on(200)), 21);
+    assert_eq!(state.streak(&minutes, 200).current, 1);
+    assert_eq!(state.streak(&goal(0), 200).current, 0);
+}
+
+#[test]
+fn test_goal_deserializes_with_default_grace() {
+    let goal: Goal = toml::from_str("kind = \"minutes\"\ntarget = 20").unwrap();
+    assert_eq!(goal.kind, GoalKind::Minutes);
+    assert_eq!(goal.grace_days, 1);
+}
+
+#[test]
+fn test_day_dates_round_trip() {
+    assert_eq!(parse_day("1970-01-01"), Some(0));
+    assert_eq!(parse_day("2000-03-01"), Some(11_017));
+    assert_eq!(
+        parse_day("2024-02-29").map(format_day).as_deref(),
+        Some("2024-02-29")
+    );
+    assert_eq!(format_day(20_742), "2026-10-16");
+    assert_eq!(parse_day("2023-02-29"), None);
+    assert_eq!(parse_day("2026-13-01"), None);
+    assert_eq!(parse_day("1969-12-31"), None);
+    assert_eq!(parse_day("next week"), None);
+}
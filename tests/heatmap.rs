@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use nvimkata::heatmap::{KeyboardLayout, calendar_lines, heatmap_lines};
+use nvimkata::state::DayActivity;
+use ratatui::style::Color;
+use ratatui::text::Line;
+
+/// Background of the key cap showing `label` on the drawn keyboard.
+fn key_bg(lines: &[Line], label: &str) -> Option<Color> {
+    lines
+        .iter()
+        .flat_map(|line| &line.spans)
+        .find(|span| span.content.trim() == label)
+        .and_then(|span| span.style.bg)
+}
+
+#[test]
+fn test_heatmap_folds_shift_and_ctrl() {
+    let usage: BTreeMap<String, u32> = [("w", 2), ("W", 1), ("<C-w>", 1), ("j", 1)]
+        .into_iter()
+        .map(|(k, n)| (k.to_string(), n))
+        .collect();
+    let lines = heatmap_lines(KeyboardLayout::Qwerty, &usage);
+    assert_eq!(key_bg(&lines, "w"), Some(Color::Red));
+    assert_eq!(key_bg(&lines, "j"), Some(Color::Cyan));
+    assert_eq!(key_bg(&lines, "x"), None);
+}
+
+#[test]
+fn test_heatmap_layouts_place_keys() {
+    let first_row = |layout| {
+        heatmap_lines(layout, &BTreeMap::new())[1]
+            .spans
+            .iter()
+            .map(|span| span.content.trim().to_string())
+            .collect::<String>()
+    };
+    assert!(first_row(KeyboardLayout::Qwerty).starts_with("qwerty"));
+    assert!(first_row(KeyboardLayout::Colemak).starts_with("qwfpg"));
+    assert!(first_row(KeyboardLayout::Dvorak).starts_with("',.py"));
+}
+
+#[test]
+fn test_calendar_marks_practiced_days() {
+    // 2026-10-16 is a Friday
+    let today = 20742;
+    let activity: BTreeMap<u32, DayActivity> = [
+        (
+            today,
+            DayActivity {
+                challenges: 2,
+                secs: 60,
+            },
+        ),
+        (
+            today - 3,
+            DayActivity {
+                challenges: 1,
+                secs: 30,
+            },
+        ),
+        (
+            today - 400,
+            DayActivity {
+                challenges: 9,
+                secs: 900,
+            },
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let lines = calendar_lines(&activity, today);
+    let text: Vec<String> = lines.iter().map(ToString::to_string).collect();
+    assert_eq!(text.len(), 9);
+    assert!(text[0].contains("Oct"));
+    // Friday row ends with today, Tuesday row with three days ago
+    assert!(text[5].trim_end().ends_with('■'));
+    assert!(text[2].trim_end().ends_with('■'));
+    assert!(text[1].trim_end().ends_with('·'));
+    assert_eq!(text[8].trim(), "3 challenges completed on 2 days");
+}
@@ -0,0 +1,25 @@
+use ratatui::style::Modifier;
+
+use nvimkata::intro;
+
+#[test]
+fn test_render_headings_code_and_fences() {
+    let lines = intro::render("# Registers\n\nUse `\"0p` to paste.\n```\n\"+y  yank\n```\n");
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].to_string(), "Registers");
+    assert!(lines[0].style.add_modifier.contains(Modifier::BOLD));
+    assert_eq!(lines[1].to_string(), "");
+
+    let spans: Vec<String> = lines[2].spans.iter().map(ToString::to_string).collect();
+    assert_eq!(spans, ["Use ", "\"0p", " to paste."]);
+    assert_ne!(lines[2].spans[1].style, lines[2].spans[0].style);
+
+    // Fence markers are dropped and the block is indented as written
+    assert_eq!(lines[3].to_string(), "  \"+y  yank");
+}
+
+#[test]
+fn test_render_unpaired_backtick_is_text() {
+    let lines = intro::render("a ` b");
+    assert_eq!(lines[0].to_string(), "a ` b");
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use nvimkata::keymap::{Action, KeyPress, Keymap, parse_keys};
+use ratatui::crossterm::event::KeyCode;
+
+fn press(c: char) -> KeyPress {
+    KeyPress {
+        code: KeyCode::Char(c),
+        ctrl: false,
+    }
+}
+
+#[test]
+fn test_parse_keys() {
+    assert_eq!(parse_keys("gg").unwrap(), [press('g'), press('g')]);
+    assert_eq!(
+        parse_keys("<C-d>").unwrap(),
+        [KeyPress {
+            code: KeyCode::Char('d'),
+            ctrl: true
+        }]
+    );
+    assert_eq!(parse_keys("<Down>").unwrap()[0].code, KeyCode::Down);
+    assert_eq!(parse_keys("<CR>").unwrap()[0].code, KeyCode::Enter);
+    assert_eq!(parse_keys("<").unwrap(), [press('<')]);
+    assert!(parse_keys("<Nope>").is_err());
+    assert!(parse_keys("").is_err());
+}
+
+#[test]
+fn test_default_sequences() {
+    let keymap = Keymap::default();
+    let mut pending = Vec::new();
+    assert_eq!(keymap.resolve(&mut pending, press('j')), Some(Action::Down));
+    assert_eq!(keymap.resolve(&mut pending, press('g')), None);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(keymap.resolve(&mut pending, press('g')), Some(Action::Top));
+    assert!(pending.is_empty());
+    // An unfinished sequence falls back to the latest key
+    assert_eq!(keymap.resolve(&mut pending, press('g')), None);
+    assert_eq!(keymap.resolve(&mut pending, press('k')), Some(Action::Up));
+    assert!(pending.is_empty());
+    assert_eq!(keymap.resolve(&mut pending, press('z')), None);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn test_overrides_replace_defaults() {
+    let overrides: BTreeMap<Action, Vec<String>> = toml::from_str(
+        r#"
+down = ["n", "<Down>"]
+up = ["e"]
+select = ["<Enter>"]
+"#,
+    )
+    .unwrap();
+    let keymap = Keymap::new(&overrides);
+    let mut pending = Vec::new();
+    assert_eq!(keymap.resolve(&mut pending, press('n')), Some(Action::Down));
+    assert_eq!(keymap.resolve(&mut pending, press('e')), Some(Action::Up));
+    assert_eq!(keymap.resolve(&mut pending, press('j')), None);
+    assert_eq!(keymap.resolve(&mut pending, press('l')), None);
+    assert_eq!(keymap.label(Action::Down), "n");
+    assert_eq!(keymap.label(Action::Select), "<Enter>");
+    assert_eq!(keymap.label(Action::Quit), "q");
+}
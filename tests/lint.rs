@@ -0,0 +1,121 @@
+use nvimkata::challenge::{Challenge, Topic};
+use nvimkata::lint::{self, Lint};
+
+fn challenge(id: &str, hint: &str, moves: &str, start: &str, target: &str) -> Challenge {
+    toml::from_str(&format!(
+        r#"
+id = "{id}"
+version = "1.0.0"
+title = "Lint"
+topic = "ex"
+difficulty = 1
+hint = "{hint}"
+par_keystrokes = 3
+perfect_moves = {moves}
+
+[start]
+content = "{start}"
+
+[target]
+content = "{target}"
+"#
+    ))
+    .unwrap()
+}
+
+fn messages(challenge: &Challenge) -> Vec<String> {
+    lint::check(challenge)
+        .into_iter()
+        .map(|lint| lint.message)
+        .collect()
+}
+
+#[test]
+fn test_clean_challenge_has_no_lints() {
+    let c = challenge(
+        "ex_001",
+        "Use :norm with <C-r>",
+        r#"[":%norm A;<CR>", "i<C-R>a<Esc>"]"#,
+        "a\\n  b",
+        "a;\\n  b;",
+    );
+    assert!(lint::check(&c).is_empty(), "{:?}", lint::check(&c));
+}
+
+#[test]
+fn test_whitespace_and_width() {
+    let long = "x".repeat(lint::MAX_LINE_WIDTH + 5);
+    let c = challenge(
+        "ex_002",
+        "hint",
+        "[]",
+        &format!("\\tone\\n{long}"),
+        "  one \\nb\\t",
+    );
+    assert_eq!(
+        messages(&c),
+        [
+            "trailing whitespace on target line 1, 2, which matching ignores".to_string(),
+            "start indents with tabs but target with spaces".to_string(),
+            format!(
+                "start line 2 is 85 columns wide, more than {}",
+                lint::MAX_LINE_WIDTH
+            ),
+        ]
+    );
+
+    let mixed = challenge("ex_003", "hint", "[]", "\\ta\\n  b", "a");
+    assert_eq!(
+        messages(&mixed),
+        ["start indents with both tabs and spaces"]
+    );
+}
+
+#[test]
+fn test_hint_keys_missing_from_perfect_moves() {
+    let c = challenge(
+        "ex_004",
+        "Try :sort or :'<,'>s, then <C-a> (not <li> or <spaces>). :t copies",
+        r#"[":copy 0<Enter>", "<c-x>"]"#,
+        "a",
+        "a",
+    );
+    assert_eq!(
+        messages(&c),
+        [
+            "hint mentions :sort, which perfect_moves never uses",
+            "hint mentions :s, which perfect_moves never uses",
+            "hint mentions <C-a>, which perfect_moves never uses",
+        ]
+    );
+
+    // Without perfect moves there is nothing to compare the hint with
+    let mut free = c.clone();
+    free.perfect_moves = None;
+    assert!(lint::check(&free).is_empty());
+}
+
+#[test]
+fn test_duplicate_ids_across_topics() {
+    let topic = |id, name: &str, ids: &[&str]| Topic {
+        id,
+        name: name.to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: ids
+            .iter()
+            .map(|id| challenge(id, "hint", "[]", "a", "a"))
+            .collect(),
+    };
+    let topics = [
+        topic(1, "Motions", &["motion_001", "shared"]),
+        topic(2, "Registers", &["reg_001", "shared"]),
+    ];
+    assert_eq!(
+        lint::duplicate_ids(&topics),
+        [Lint {
+            challenge: "shared".to_string(),
+            message: "ID is used by 2 challenges, in Motions, Registers".to_string(),
+        }]
+    );
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use nvimkata::generate::Rng;
+use nvimkata::mutate::{self, Mutation};
+
+const SOURCE: &str = "use std::fs;
+use std::io;
+use std::path::Path;
+
+fn main() {
+    let name = \"world\";
+    let greeting = format!(\"hello {name}\");
+    println!(\"{greeting}\");
+}
+";
+
+#[test]
+fn test_mutations_change_the_source() {
+    for mutation in Mutation::ALL {
+        let (mutated, description) =
+            mutate::mutate(SOURCE, mutation, Some("rust"), &mut Rng::new(1)).unwrap();
+        assert_ne!(mutated, SOURCE.trim_end(), "{mutation}");
+        assert!(!description.is_empty());
+        assert_eq!(mutation.name().parse(), Ok(mutation));
+    }
+    assert!("shuffle".parse::<Mutation>().is_err());
+
+    let (renamed, description) =
+        mutate::mutate(SOURCE, Mutation::RenameVar, None, &mut Rng::new(1)).unwrap();
+    let old = if description.contains("'name'") {
+        "name"
+    } else {
+        "greeting"
+    };
+    assert!(!renamed.contains(&format!("let {old} ")));
+    assert_eq!(renamed.lines().count(), SOURCE.lines().count());
+
+    let (reordered, _) =
+        mutate::mutate(SOURCE, Mutation::ReorderImports, None, &mut Rng::new(1)).unwrap();
+    let mut imports: Vec<&str> = reordered.lines().take(3).collect();
+    imports.sort_unstable();
+    assert_eq!(imports, SOURCE.lines().take(3).collect::<Vec<_>>());
+
+    let (wrapped, _) = mutate::mutate(
+        SOURCE,
+        Mutation::WrapInFunction,
+        Some("python"),
+        &mut Rng::new(1),
+    )
+    .unwrap();
+    assert!(wrapped.contains("def wrapped():"));
+    assert_eq!(wrapped.lines().count(), SOURCE.lines().count() + 1);
+}
+
+#[test]
+fn test_from_file_restores_the_original() {
+    let path = Path::new("src/hello.rs");
+    let generated = mutate::from_file(path, SOURCE, Mutation::WrapInFunction, 7).unwrap();
+    assert_eq!(
+        generated,
+        mutate::from_file(path, SOURCE, Mutation::WrapInFunction, 7).unwrap()
+    );
+    assert_eq!(generated.id, "file_hello_wrap_in_function_7");
+    assert!(generated.to_toml().starts_with(
+        "# Generated by `nvimkata from-file src/hello.rs --mutate wrap-in-function --seed 7`\n"
+    ));
+    let challenge = generated.challenge().unwrap();
+    assert!(challenge.is_freestyle());
+    assert_eq!(challenge.filetype.as_deref(), Some("rust"));
+    assert_eq!(challenge.target().content, SOURCE.trim_end());
+    assert!(challenge.start().content.contains("fn wrapped() {"));
+
+    let err = mutate::from_file(
+        Path::new("notes.txt"),
+        "one line\n",
+        Mutation::ReorderImports,
+        1,
+    )
+    .unwrap_err();
+    assert!(err.starts_with("notes.txt has nothing to reorder"), "{err}");
+}
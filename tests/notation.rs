@@ -0,0 +1,48 @@
+use nvimkata::challenge::count_keystrokes;
+use nvimkata::notation::{Key, format, tokenize};
+
+#[test]
+fn test_tokenize_structured_keys() {
+    assert_eq!(
+        tokenize("ciw<C-R>a<Esc>"),
+        [
+            Key::Char('c'),
+            Key::Char('i'),
+            Key::Char('w'),
+            Key::Ctrl('r'),
+            Key::Char('a'),
+            Key::Special("Esc".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_aliases_are_canonical() {
+    assert_eq!(tokenize("<CR>"), tokenize("<Enter>"));
+    assert_eq!(
+        tokenize("<lt><Space><bar>"),
+        [Key::Char('<'), Key::Char(' '), Key::Char('|')]
+    );
+    assert_eq!(tokenize("<F5>"), [Key::Special("F5".to_string())]);
+    assert_eq!(
+        Key::parse("<C-S-x>"),
+        Some(Key::Special("C-S-x".to_string()))
+    );
+    assert_eq!(Key::parse("ab"), None);
+    assert_eq!(Key::parse("<>"), None);
+}
+
+#[test]
+fn test_format_round_trips() {
+    for notation in [
+        "ciwfoo<Esc>",
+        "qa0<C-a>jq3@a",
+        ":%s/a/b/g<CR>",
+        "i<lt>T> <BS>",
+    ] {
+        let keys = tokenize(notation);
+        assert_eq!(keys.len(), count_keystrokes(notation), "{notation}");
+        assert_eq!(tokenize(&format(&keys)), keys, "{notation}");
+    }
+    assert_eq!(format(&tokenize("<c-r><cr>a b")), "<C-r><Enter>a<Space>b");
+}
@@ -27,3 +27,30 @@ fn test_escape_for_lua_sq() {
     assert_eq!(escape_for_lua_sq("line1\nline2"), "line1\\nline2");
     assert_eq!(escape_for_lua_sq("cr\rhere"), "cr\\rhere");
 }
+
+#[test]
+fn test_setup_lua_escapes_register_content() {
+    use nvimkata::challenge::{QuickfixEntry, Setup};
+    use nvimkata::nvim::setup_lua;
+
+    let mut setup = Setup::default();
+    setup
+        .registers
+        .insert("a".to_string(), "it's\n".to_string());
+    setup.marks.insert("b".to_string(), [3, 4]);
+    setup.options.push("shiftwidth=2".to_string());
+    let lua = setup_lua(&setup);
+    assert!(lua.contains(r"vim.fn.setreg('a', 'it\'s\n')"));
+    assert!(lua.contains("nvim_buf_set_mark, buf, 'b', 3, 3"));
+    assert!(lua.contains("setlocal shiftwidth=2"));
+    assert!(!lua.contains("setqflist"));
+
+    setup.quickfix.push(QuickfixEntry {
+        line: 2,
+        col: 5,
+        text: "it's unused".to_string(),
+    });
+    let lua = setup_lua(&setup);
+    assert!(lua.contains(r"{ bufnr = qf_buf, lnum = 2, col = 5, text = 'it\'s unused' }"));
+    assert!(setup_lua(&Setup::default()).is_empty());
+}
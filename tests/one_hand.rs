@@ -0,0 +1,63 @@
+use nvimkata::challenge::Challenge;
+use nvimkata::heatmap::KeyboardLayout;
+use nvimkata::notation::Key;
+use nvimkata::one_hand;
+
+fn one_hand_challenge() -> Challenge {
+    toml::from_str(
+        "id = \"c\"\nversion = \"1\"\ntitle = \"t\"\ntopic = \"t\"\ndifficulty = 1\n\
+         par_keystrokes = 4\none_hand_par = 8\nforbidden_keys = [\"xx\"]\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_left_hand_keys() {
+    let keys = one_hand::keys("left", KeyboardLayout::Qwerty);
+    for key in ["q", "Q", "g", "%", "<C-a>", "<Esc>", "<Space>"] {
+        assert!(keys.contains(&Key::parse(key).unwrap()), "{key}");
+    }
+    for key in ["j", "h", "y", "<C-k>", "<CR>"] {
+        assert!(!keys.contains(&Key::parse(key).unwrap()), "{key}");
+    }
+}
+
+#[test]
+fn test_right_hand_keys_follow_the_layout() {
+    let keys = one_hand::keys("right", KeyboardLayout::Colemak);
+    assert!(keys.contains(&Key::Char('n')));
+    assert!(keys.contains(&Key::Special("Enter".to_string())));
+    assert!(!keys.contains(&Key::Char('t')));
+}
+
+#[test]
+fn test_custom_keys_and_stray_key() {
+    let keys = one_hand::keys("hjkl<C-d>", KeyboardLayout::Qwerty);
+    assert_eq!(keys.len(), 5);
+    assert_eq!(one_hand::stray_key(&keys, "jj<C-D>k"), None);
+    assert_eq!(
+        one_hand::stray_key(&keys, "jj<C-u>w"),
+        Some("<C-u>".to_string())
+    );
+}
+
+#[test]
+fn test_one_hand_variant_par_and_violation() {
+    let c = one_hand_challenge();
+    assert_eq!(c.par(), 4);
+    assert_eq!(c.violation_in("gg"), None);
+
+    one_hand::set(Some(one_hand::keys("left", KeyboardLayout::Qwerty)));
+    assert_eq!(c.par(), 8);
+    assert_eq!(c.violation_in("dw"), None);
+    assert_eq!(c.violation_in("djx"), Some("j".to_string()));
+    // A forbidden sequence is reported first
+    assert_eq!(c.violation_in("jxx"), Some("xx".to_string()));
+
+    // Challenges without a variant are played with any key
+    let mut plain = one_hand_challenge();
+    plain.one_hand_par = None;
+    assert_eq!(plain.par(), 4);
+    assert_eq!(plain.violation_in("djx"), None);
+}
@@ -0,0 +1,43 @@
+use nvimkata::challenge::{Category, Challenge};
+use nvimkata::opponents::Opponent;
+
+fn challenge(par: u32, category: Category) -> Challenge {
+    let mut challenge: Challenge = toml::from_str(&format!(
+        "id = \"c\"\nversion = \"1\"\ntitle = \"t\"\ntopic = \"t\"\ndifficulty = 1\n\
+         par_keystrokes = {par}\n[start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap();
+    challenge.category = Some(category);
+    challenge
+}
+
+fn opponent(par_tenths: u32, categories: &[&str]) -> Opponent {
+    Opponent {
+        name: "Rookie".to_string(),
+        par_tenths,
+        categories: categories.iter().map(ToString::to_string).collect(),
+    }
+}
+
+#[test]
+fn test_opponent_scores_a_multiple_of_par() {
+    let rookie = opponent(25, &[]);
+    assert_eq!(rookie.keystrokes(&challenge(10, Category::Beginner)), 25);
+    assert_eq!(rookie.keystrokes(&challenge(7, Category::Beginner)), 17);
+    assert_eq!(
+        opponent(1, &[]).keystrokes(&challenge(3, Category::Beginner)),
+        1
+    );
+}
+
+#[test]
+fn test_opponent_plays_its_categories_only() {
+    let rookie = opponent(25, &["beginner", "Intermediate"]);
+    assert!(rookie.plays(&challenge(10, Category::Beginner)));
+    assert!(rookie.plays(&challenge(10, Category::Intermediate)));
+    assert!(!rookie.plays(&challenge(10, Category::Legendary)));
+
+    // Freestyle challenges have no par to play against
+    assert!(opponent(25, &[]).plays(&challenge(10, Category::Legendary)));
+    assert!(!opponent(25, &[]).plays(&challenge(0, Category::Freestyle)));
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use nvimkata::challenge::Category;
+use nvimkata::curriculum::load_curriculum_with;
+use nvimkata::pack::{self, FIRST_PACK_ID};
+
+const MANIFEST: &str = "name = \"regex\"\nauthor = \"Ada\"\nversion = \"1.2.0\"\n";
+
+fn challenge(id: &str) -> String {
+    format!(
+        "id = \"{id}\"\nversion = \"1\"\ntitle = \"{id}\"\ntopic = \"regex\"\n\
+         difficulty = 1\nhint = \"h\"\npar_keystrokes = 3\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    )
+}
+
+/// Tar `dir` into `archive`, with the files under `prefix` inside the archive.
+fn tar(dir: &Path, prefix: &str, archive: &Path) {
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .arg(prefix)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_install_list_and_remove() {
+    let tmp = std::env::temp_dir().join("rlv_test_pack_install");
+    let _ = fs::remove_dir_all(&tmp);
+    let src = tmp.join("src").join("regex-pack");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(src.join(pack::MANIFEST), MANIFEST).unwrap();
+    fs::write(src.join("re_001.toml"), challenge("re_001")).unwrap();
+    fs::write(src.join("re_002.toml"), challenge("re_002")).unwrap();
+    let archive = tmp.join("regex.tar.gz");
+    tar(&tmp.join("src"), "regex-pack", &archive);
+
+    let packs = tmp.join("packs");
+    let installed = pack::install(archive.to_str().unwrap(), &packs).unwrap();
+    assert_eq!(installed.manifest.name, "regex");
+    assert_eq!(installed.dir, packs.join("regex"));
+    assert_eq!(installed.description(), "by Ada");
+    // Only the installed pack is left, not the unpacked archive
+    assert_eq!(fs::read_dir(&packs).unwrap().count(), 1);
+    assert_eq!(pack::installed(&packs), std::slice::from_ref(&installed));
+
+    // Installing again replaces the pack
+    assert!(pack::install(archive.to_str().unwrap(), &packs).is_ok());
+    assert_eq!(pack::installed(&packs).len(), 1);
+
+    assert_eq!(pack::remove("regex", &packs).unwrap(), installed);
+    assert!(pack::installed(&packs).is_empty());
+    assert!(pack::remove("regex", &packs).is_err());
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_install_rejects_archives_without_a_usable_manifest() {
+    let tmp = std::env::temp_dir().join("rlv_test_pack_invalid");
+    let _ = fs::remove_dir_all(&tmp);
+    let src = tmp.join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(src.join("re_001.toml"), challenge("re_001")).unwrap();
+    let archive = tmp.join("bare.tar");
+    tar(&tmp, "src", &archive);
+    let packs = tmp.join("packs");
+    assert!(pack::install(archive.to_str().unwrap(), &packs).is_err());
+
+    fs::write(src.join(pack::MANIFEST), MANIFEST.replace("regex", "../up")).unwrap();
+    tar(&tmp, "src", &archive);
+    assert!(pack::install(archive.to_str().unwrap(), &packs).is_err());
+    assert!(pack::install(tmp.join("missing.tar").to_str().unwrap(), &packs).is_err());
+    assert_eq!(fs::read_dir(&packs).unwrap().count(), 0);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_packs_load_as_extra_topics() {
+    let tmp = std::env::temp_dir().join("rlv_test_pack_topics");
+    let _ = fs::remove_dir_all(&tmp);
+    let dir = tmp.join("packs").join("regex");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join(pack::MANIFEST),
+        format!("{MANIFEST}description = \"Regex katas\"\n"),
+    )
+    .unwrap();
+    fs::write(dir.join("re_001.toml"), challenge("re_001")).unwrap();
+
+    let packs = pack::installed(&tmp.join("packs"));
+    let (topics, stats) = load_curriculum_with(&tmp.join("curriculum"), &packs, None);
+    let topic = topics.last().unwrap();
+    assert_eq!(topic.id, FIRST_PACK_ID);
+    assert_eq!(Category::for_topic(topic.id), Category::Pack);
+    assert_eq!(
+        (topic.name.as_str(), topic.description.as_str()),
+        ("regex", "Regex katas")
+    );
+    assert_eq!(topic.challenges[0].id, "re_001");
+    assert_eq!(stats.files, 1);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
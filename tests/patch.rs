@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use nvimkata::patch::{self, Hunk};
+
+const PATCH: &str = "commit 1234
+Author: Ada <ada@example.com>
+
+    Tidy up
+
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -3,4 +3,4 @@ fn main() {
+     let x = 1;
+-    let y = 2;
++    let y = x + 1;
+
+     println!(\"{y}\");
+@@ -20 +20,2 @@
+-// old
++// new
++-- not a file header
+diff --git a/notes.txt b/notes.txt
+deleted file mode 100644
+--- a/notes.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+\\ No newline at end of file
+";
+
+#[test]
+fn test_parse_hunks() {
+    let hunks = patch::parse(PATCH).unwrap();
+    assert_eq!(hunks.len(), 3);
+    assert_eq!(
+        hunks[0],
+        Hunk {
+            file: "src/main.rs".to_string(),
+            line: 3,
+            section: "fn main() {".to_string(),
+            pre: vec![
+                "    let x = 1;".to_string(),
+                "    let y = 2;".to_string(),
+                String::new(),
+                "    println!(\"{y}\");".to_string(),
+            ],
+            post: vec![
+                "    let x = 1;".to_string(),
+                "    let y = x + 1;".to_string(),
+                String::new(),
+                "    println!(\"{y}\");".to_string(),
+            ],
+        }
+    );
+    assert_eq!(hunks[1].post, ["// new", "-- not a file header"]);
+    assert_eq!(hunks[2].file, "notes.txt");
+    assert_eq!(hunks[2].pre, ["gone"]);
+    assert!(hunks[2].post.is_empty());
+
+    assert_eq!(
+        patch::parse("--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n"),
+        Err("line 3: patch ends in the hunk".to_string())
+    );
+    assert!(patch::parse("@@ -1 +1 @@\n-a\n+b\n").is_err());
+    assert!(patch::parse("--- a/x\n+++ b/x\n@@ bogus @@\n").is_err());
+    assert_eq!(patch::parse("no diff here\n"), Ok(Vec::new()));
+}
+
+#[test]
+fn test_challenges_from_hunks() {
+    let hunks = patch::parse(PATCH).unwrap();
+    let path = Path::new("review.diff");
+    let challenges = patch::challenges(path, &hunks, false);
+    assert_eq!(challenges.len(), 3);
+    assert_eq!(challenges[0].id, "diff_review_1");
+    assert_eq!(challenges[0].title, "src/main.rs: fn main() {");
+    assert_eq!(challenges[1].title, "src/main.rs line 20");
+    assert_eq!(challenges[0].filetype.as_deref(), Some("rust"));
+    let challenge = challenges[0].challenge().unwrap();
+    assert!(challenge.is_freestyle());
+    assert!(challenge.target().content.contains("let y = x + 1;"));
+    assert!(
+        challenges[0]
+            .to_toml()
+            .starts_with("# Generated by `nvimkata import-diff review.diff`\n")
+    );
+
+    let whole = patch::challenges(path, &hunks, true);
+    assert_eq!(whole.len(), 1);
+    assert_eq!(whole[0].id, "diff_review");
+    assert_eq!(whole[0].filetype, None);
+    assert_eq!(
+        whole[0].target.content,
+        "    let x = 1;\n    let y = x + 1;\n\n    println!(\"{y}\");\n\n// new\n-- not a file header\n\n"
+    );
+    assert!(whole[0].challenge().is_ok());
+}
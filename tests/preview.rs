@@ -0,0 +1,21 @@
+use nvimkata::keymap::Action;
+use nvimkata::preview::{numbered_lines, scroll};
+
+#[test]
+fn test_scroll_is_clamped() {
+    assert_eq!(scroll(0, Action::Up, 10, 30), 0);
+    assert_eq!(scroll(0, Action::ScrollDown, 10, 30), 1);
+    assert_eq!(scroll(4, Action::HalfPageDown, 10, 30), 9);
+    assert_eq!(scroll(28, Action::HalfPageDown, 10, 30), 30);
+    assert_eq!(scroll(3, Action::HalfPageUp, 10, 30), 0);
+    assert_eq!(scroll(12, Action::Top, 10, 30), 0);
+    assert_eq!(scroll(12, Action::Bottom, 10, 30), 30);
+    assert_eq!(scroll(12, Action::Select, 10, 30), 12);
+}
+
+#[test]
+fn test_numbered_lines() {
+    let lines = numbered_lines("one\ntwo");
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].to_string(), "  2 two");
+}
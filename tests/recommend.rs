@@ -0,0 +1,92 @@
+use nvimkata::challenge::{Challenge, Grade};
+use nvimkata::recommend::{recommend, weakest};
+use nvimkata::state::GameState;
+
+fn tagged(id: &str, difficulty: u8, tags: &[&str]) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"Test {id}\"\ntopic = \"motions\"\n\
+         difficulty = {difficulty}\npar_keystrokes = 10\nfocused_actions = {tags:?}\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_recommend_prefers_weakest_tag() {
+    let done_f = tagged("f1", 1, &["f"]);
+    let done_q = tagged("q1", 1, &["qa"]);
+    let next_f = tagged("f2", 1, &["f"]);
+    let next_q = tagged("q2", 1, &["qa"]);
+    let mut state = GameState::default();
+    state.record_result("f1", Grade::B, 12, 10, "keys", "1.0.0");
+    state.record_result("q1", Grade::E, 27, 10, "keys", "1.0.0");
+
+    let all = [&done_f, &done_q, &next_f, &next_q];
+    let picks = recommend(&all, &state, 2);
+    let ids: Vec<&str> = picks.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["q1", "q2"]);
+}
+
+#[test]
+fn test_recommend_skips_fresh_grade_a() {
+    let a = tagged("m1", 1, &["w"]);
+    let b = tagged("m2", 1, &["w"]);
+    let mut state = GameState::default();
+    state.record_result("m1", Grade::A, 10, 10, "keys", "1.0.0");
+
+    let picks = recommend(&[&a, &b], &state, 5);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].id, "m2");
+}
+
+#[test]
+fn test_recommend_includes_stale_grade_a() {
+    let a = tagged("m1", 1, &["w"]);
+    let mut state = GameState::default();
+    state.record_result("m1", Grade::A, 10, 10, "keys", "1.0.0");
+    let mut changed = tagged("m1", 1, &["w"]);
+    changed.version = "1.0.1".to_string();
+    state.mark_stale(&[changed]);
+
+    let picks = recommend(&[&a], &state, 5);
+    assert_eq!(picks.len(), 1);
+}
+
+#[test]
+fn test_recommend_defers_hard_challenges() {
+    let easy = tagged("easy", 1, &["w"]);
+    let hard = tagged("hard", 5, &["w"]);
+    let state = GameState::default();
+
+    let picks = recommend(&[&hard, &easy], &state, 1);
+    assert_eq!(picks[0].id, "easy");
+}
+
+#[test]
+fn test_recommend_skips_freestyle() {
+    let mut free = tagged("free", 1, &["w"]);
+    free.par_keystrokes = 0;
+    let state = GameState::default();
+    assert!(recommend(&[&free], &state, 5).is_empty());
+}
+
+#[test]
+fn test_weakest_ranks_grade_by_difficulty() {
+    let easy_d = tagged("easy_d", 1, &["w"]);
+    let hard_e = tagged("hard_e", 4, &["w"]);
+    let easy_b = tagged("easy_b", 1, &["w"]);
+    let easy_b2 = tagged("easy_b2", 1, &["w"]);
+    let perfect = tagged("perfect", 1, &["w"]);
+    let untried = tagged("untried", 1, &["w"]);
+    let mut state = GameState::default();
+    state.record_result("easy_d", Grade::D, 20, 10, "keys", "1.0.0");
+    state.record_result("hard_e", Grade::E, 30, 10, "keys", "1.0.0");
+    state.record_result("easy_b", Grade::B, 12, 10, "keys", "1.0.0");
+    state.record_result("easy_b2", Grade::B, 13, 10, "keys", "1.0.0");
+    state.record_result("perfect", Grade::A, 10, 10, "keys", "1.0.0");
+
+    let all = [&easy_d, &hard_e, &easy_b, &easy_b2, &perfect, &untried];
+    let picks = weakest(&all, &state, 10);
+    let ids: Vec<&str> = picks.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["easy_d", "hard_e", "easy_b2", "easy_b"]);
+}
@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use nvimkata::challenge::{Challenge, Grade};
+use nvimkata::commands::{self, Command};
+use nvimkata::curriculum::load_curriculum_with;
+use nvimkata::notation;
+use nvimkata::reference::{self, Reference, ReferenceCommand};
+use nvimkata::state::GameState;
+
+fn entry(keys: &str, uses: &[&str]) -> ReferenceCommand {
+    ReferenceCommand {
+        keys: keys.to_string(),
+        category: "Text objects".to_string(),
+        description: "A word".to_string(),
+        uses: uses.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn challenge(id: &str) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1\"\ntitle = \"t\"\ntopic = \"t\"\ndifficulty = 1\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn commands(keys: &str) -> Vec<Command> {
+    commands::classify(&notation::tokenize(keys))
+}
+
+fn used_by(entry: &ReferenceCommand, keys: &str) -> bool {
+    commands(keys).iter().any(|c| entry.used_by(c))
+}
+
+#[test]
+fn test_operator_commands_use_their_parts() {
+    let iw = entry("iw aw", &["iw", "aw"]);
+    assert!(used_by(&iw, "ciwfoo<Esc>"));
+    assert!(used_by(&iw, "d2aw"));
+    assert!(!used_by(&iw, "dw"));
+
+    let d = entry("d", &[]);
+    assert!(used_by(&d, "diw"));
+    assert!(used_by(&d, "dd"));
+    assert!(!used_by(&d, "ciw"));
+
+    // gU is not the g of gg
+    let gu = entry("gU", &[]);
+    assert!(used_by(&gu, "gUiw"));
+    assert!(!used_by(&entry("g", &[]), "gUiw"));
+}
+
+#[test]
+fn test_registers_match_commands_using_them() {
+    let zero = entry("\"0", &[]);
+    assert!(used_by(&zero, "\"0p"));
+    assert!(used_by(&zero, "2\"0p"));
+    assert!(!used_by(&zero, "p"));
+    // Typed text is not a register
+    assert!(!used_by(&entry("\"a", &[]), "i\"a<Esc>"));
+}
+
+#[test]
+fn test_special_keys_are_not_operators() {
+    let indent = entry("> <", &[">", "<"]);
+    assert!(indent.has_name("<<"));
+    assert!(indent.has_name(">ip"));
+    assert!(!indent.has_name("<C-a>"));
+    assert!(!indent.has_name("<Esc>"));
+}
+
+#[test]
+fn test_mastery_and_never_used_commands() {
+    let mut state = GameState::default();
+    state.record_result("a", Grade::A, 5, 3, "ciwx<Esc>daw", "1");
+    state.record_result("b", Grade::A, 5, 3, "yiw", "1");
+    let iw = entry("iw aw", &["iw", "aw"]);
+    let mastery = iw.mastery(&state);
+    assert_eq!(mastery.uses, 3);
+    assert_eq!(mastery.challenges.len(), 2);
+
+    let reference = Reference {
+        commands: vec![iw, entry("\"0", &[]), entry("it at", &["it", "at"])],
+    };
+    let (x, y) = (challenge("x"), challenge("y"));
+    // Only commands some challenge uses are suggested
+    let challenges = vec![vec![&x], vec![&y], vec![]];
+    assert_eq!(reference.never_used(&state, &challenges), [1]);
+}
+
+#[test]
+fn test_matches_keys_description_and_category() {
+    let iw = entry("iw aw", &[]);
+    assert!(iw.matches("AW"));
+    assert!(iw.matches("word"));
+    assert!(iw.matches("objects"));
+    assert!(!iw.matches("macro"));
+}
+
+#[test]
+fn test_bundled_reference_links_challenges() {
+    let challenges_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("challenges");
+    let reference = reference::load_reference(&challenges_dir);
+    assert!(!reference.commands.is_empty());
+    for command in &reference.commands {
+        assert!(!command.keys.is_empty() && !command.description.is_empty());
+    }
+
+    let (topics, _) = load_curriculum_with(&challenges_dir, &[], None);
+    let challenges = reference.challenges(&topics);
+    assert_eq!(challenges.len(), reference.commands.len());
+    let quotes = reference
+        .commands
+        .iter()
+        .position(|c| c.keys == "i\" a\"")
+        .unwrap();
+    assert!(challenges[quotes].iter().any(|challenge| {
+        challenge
+            .perfect_moves
+            .as_ref()
+            .is_some_and(|moves| moves.concat().contains("ci\""))
+    }));
+    assert!(
+        !challenges[quotes]
+            .iter()
+            .any(|challenge| challenge.perfect_moves.is_none())
+    );
+}
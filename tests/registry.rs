@@ -0,0 +1,155 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::PathBuf;
+
+use nvimkata::pack::{Manifest, Pack};
+use nvimkata::registry::{self, Index, compare_versions};
+
+const INDEX: &str = r#"{
+  "packs": [
+    {"name": "regex", "author": "Ada", "version": "1.2.0", "description": "Regex katas",
+     "url": "https://example.com/regex-1.2.0.tar.gz", "sha256": "00"},
+    {"name": "regex", "author": "Ada", "version": "1.10.0",
+     "url": "https://example.com/regex-1.10.0.tar.gz", "sha256": "00"},
+    {"name": "lua", "author": "Grace", "version": "0.1",
+     "url": "https://example.com/lua.zip", "sha256": "00"}
+  ]
+}"#;
+
+fn installed(name: &str, version: &str) -> Pack {
+    Pack {
+        manifest: Manifest {
+            name: name.to_string(),
+            author: "Ada".to_string(),
+            version: version.to_string(),
+            description: None,
+        },
+        dir: PathBuf::from(name),
+    }
+}
+
+#[test]
+fn test_search_matches_name_author_and_description() {
+    let index = Index::parse(INDEX).unwrap();
+    let names =
+        |query| -> Vec<String> { index.search(query).iter().map(|e| e.name.clone()).collect() };
+    assert_eq!(names("KATAS"), ["regex"]);
+    assert_eq!(names("grace"), ["lua"]);
+    assert_eq!(names("").len(), 3);
+    assert!(names("python").is_empty());
+}
+
+#[test]
+fn test_versions_compare_by_number() {
+    assert_eq!(compare_versions("1.10.0", "1.9"), Ordering::Greater);
+    assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+    assert_eq!(compare_versions("0.9", "1.0"), Ordering::Less);
+    assert_eq!(compare_versions("beta", "alpha"), Ordering::Greater);
+
+    let index = Index::parse(INDEX).unwrap();
+    assert_eq!(index.find("regex").unwrap().version, "1.10.0");
+    assert!(index.find("python").is_none());
+}
+
+#[test]
+fn test_updates_list_only_newer_versions() {
+    let index = Index::parse(INDEX).unwrap();
+    let packs = [
+        installed("regex", "1.2.0"),
+        installed("lua", "0.1"),
+        installed("local", "1.0"),
+    ];
+    let updates = index.updates(&packs);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0.manifest.name, "regex");
+    assert_eq!(updates[0].1.version, "1.10.0");
+}
+
+#[test]
+fn test_checksum_verification() {
+    let path = std::env::temp_dir().join("rlv_test_registry_checksum");
+    fs::write(&path, "abc").unwrap();
+    let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert_eq!(registry::sha256_file(&path).unwrap(), abc);
+    assert!(registry::verify(&path, &abc.to_uppercase()).is_ok());
+    assert!(registry::verify(&path, "00").is_err());
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_archive_name_from_url() {
+    assert_eq!(
+        registry::archive_name("https://example.com/p/regex.zip?token=1"),
+        "regex.zip"
+    );
+    assert_eq!(registry::archive_name("https://example.com/"), "pack");
+    assert_eq!(registry::archive_name("https://example.com/.."), "pack");
+    assert_eq!(registry::archive_name("https://example.com/.?x=1"), "pack");
+    assert_eq!(
+        registry::archive_name("https://example.com/a\\..\\b.zip"),
+        "pack"
+    );
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_install_from_index_verifies_the_checksum() {
+    use std::process::Command;
+
+    use nvimkata::pack;
+
+    let tmp = std::env::temp_dir().join("rlv_test_registry_install");
+    let _ = fs::remove_dir_all(&tmp);
+    let src = tmp.join("regex");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("pack.toml"),
+        "name = \"regex\"\nauthor = \"Ada\"\nversion = \"1.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("re_001.toml"),
+        "id = \"re_001\"\nversion = \"1\"\ntitle = \"T\"\ntopic = \"regex\"\n\
+         difficulty = 1\nhint = \"h\"\n[start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n",
+    )
+    .unwrap();
+    let archive = tmp.join("regex.tar");
+    let status = Command::new("tar")
+        .arg("-cf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&tmp)
+        .arg("regex")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let sha256 = registry::sha256_file(&archive).unwrap();
+    let index = format!(
+        r#"{{"packs": [{{"name": "regex", "author": "Ada", "version": "1.0",
+            "url": "file://{}", "sha256": "{sha256}"}}]}}"#,
+        archive.display()
+    );
+    let index_path = tmp.join("index.json");
+    fs::write(&index_path, index).unwrap();
+
+    let index = registry::fetch_index(&format!("file://{}", index_path.display())).unwrap();
+    let packs = tmp.join("packs");
+    let mut entry = index.find("regex").unwrap().clone();
+    entry.sha256 = "00".to_string();
+    assert!(registry::install(&entry, &packs).is_err());
+    let pack = registry::install(index.find("regex").unwrap(), &packs).unwrap();
+    assert_eq!(pack.dir, packs.join("regex"));
+
+    // An archive holding another pack or version than listed is refused,
+    // leaving the installed pack alone
+    for (name, version) in [("regex", "2.0"), ("lua", "1.0")] {
+        let mut entry = index.find("regex").unwrap().clone();
+        entry.name = name.to_string();
+        entry.version = version.to_string();
+        let err = registry::install(&entry, &packs).unwrap_err();
+        assert!(err.to_string().contains("holds pack regex 1.0"), "{err}");
+    }
+    assert_eq!(pack::installed(&packs), [pack]);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
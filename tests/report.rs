@@ -0,0 +1,73 @@
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::report::{ProgressReport, ReportFormat, parse_period};
+use nvimkata::skills::SkillRegistry;
+use nvimkata::state::{AttemptRecord, GameState};
+
+fn challenge(id: &str) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"Test {id}\"\ntopic = \"motions\"\n\
+         difficulty = 1\npar_keystrokes = 10\n[start]\ncontent = \"a\"\n\
+         [target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+fn attempt(grade: Grade, keystrokes: u32, day: u32) -> AttemptRecord {
+    AttemptRecord {
+        grade,
+        keystrokes,
+        time_ms: Some(5_000),
+        keys: String::new(),
+        technique_missing: false,
+        timestamp: Some(u64::from(day) * 86_400 + 3_600),
+    }
+}
+
+#[test]
+fn test_parse_period_and_format() {
+    assert_eq!(parse_period("7d"), Ok(7));
+    assert_eq!(parse_period("2w"), Ok(14));
+    assert!(parse_period("0d").is_err());
+    assert!(parse_period("7").is_err());
+    assert_eq!("HTML".parse(), Ok(ReportFormat::Html));
+    assert_eq!("md".parse(), Ok(ReportFormat::Markdown));
+    assert!("pdf".parse::<ReportFormat>().is_err());
+}
+
+#[test]
+fn test_progress_report_covers_the_period() {
+    let today = 20742;
+    let topics = vec![Topic {
+        id: 1,
+        name: "Motions".to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: vec![challenge("m1"), challenge("m2"), challenge("m3")],
+    }];
+    let mut state = GameState::default();
+    // m1 improved from C to A this week, m2 was solved for the first time,
+    // m3 was only practiced long ago
+    state.record_attempt("m1", attempt(Grade::C, 20, today - 30), "1.0.0");
+    state.record_attempt("m1", attempt(Grade::A, 8, today - 2), "1.0.0");
+    state.record_attempt("m2", attempt(Grade::B, 12, today), "1.0.0");
+    state.record_attempt("m3", attempt(Grade::A, 8, today - 30), "1.0.0");
+    state.log_activity(today - 30, true, 600);
+    state.log_activity(today - 2, true, 90);
+    state.log_activity(today, true, 3_000);
+    state.log_activity(today, false, 60);
+
+    let registry = SkillRegistry::default();
+    let report = ProgressReport::new(&topics, &registry, &state, today, 7);
+    assert_eq!(report.completed, 2);
+    assert_eq!(report.days_practiced, 2);
+    let markdown = report.render(ReportFormat::Markdown);
+    assert!(markdown.starts_with("# nvimkata progress report\n\n2026-10-10 to 2026-10-16\n"));
+    assert!(markdown.contains("- Practice time: 52m on 2 days\n"));
+    assert!(markdown.contains("- m1 Test m1: C -> A\n"));
+    assert!(markdown.contains("- m1 Test m1: 20 (C) -> 8 keys (A)\n"));
+    assert!(markdown.contains("- m2 Test m2: first solve, 12 keys (B)\n"));
+    assert!(!markdown.contains("m3"));
+
+    let html = report.render(ReportFormat::Html);
+    assert!(html.contains("<h2>Grade improvements</h2>\n<ul>\n<li>m1 Test m1: C -&gt; A</li>"));
+}
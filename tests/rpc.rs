@@ -0,0 +1,261 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::rpc::{Request, Server, serve};
+use nvimkata::state::GameState;
+use nvimkata::unlock::UnlockPolicy;
+
+fn topics() -> Vec<Topic> {
+    let challenge: Challenge = toml::from_str(
+        "id = \"m1\"\nversion = \"1.0.0\"\ntitle = \"Delete word\"\ntopic = \"motions\"\n\
+         difficulty = 1\npar_keystrokes = 2\n[start]\ncontent = \"foo bar\\n\"\n\
+         [target]\ncontent = \"bar\\n\"\n",
+    )
+    .unwrap();
+
+    vec![Topic {
+        id: 1,
+        name: "Motions".to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: vec![challenge],
+    }]
+}
+
+#[test]
+fn test_open_and_finish_records_the_run() {
+    let dir = std::env::temp_dir().join("rlv_rpc_session");
+    let topics = topics();
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+
+    assert_eq!(
+        server.handle(Request::List),
+        Ok(json!([{ "id": "m1", "title": "Delete word" }]))
+    );
+    assert!(
+        server
+            .handle(Request::Finish { lines: Vec::new() })
+            .is_err()
+    );
+    assert!(
+        server
+            .handle(Request::Open {
+                challenge: "nope".to_string()
+            })
+            .is_err()
+    );
+
+    let reply = server
+        .handle(Request::Open {
+            challenge: "m1".to_string(),
+        })
+        .unwrap();
+    let path = |key: &str| reply[key].as_str().unwrap().to_string();
+    assert_eq!(fs::read_to_string(path("start")).unwrap(), "foo bar\n");
+    assert_eq!(fs::read_to_string(path("target")).unwrap(), "bar\n");
+    assert!(
+        fs::read_to_string(path("script"))
+            .unwrap()
+            .contains("_VK_EMBEDDED = true")
+    );
+    assert!(reply["instructions"].is_null());
+
+    // As written by the runtime when the target is reached, next to the script
+    let session = PathBuf::from(path("script"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    assert!(session.starts_with(&dir));
+    fs::write(session.join("results"), "2\n1500\ndw\ncursor=1,1").unwrap();
+    let reply = server
+        .handle(Request::Finish {
+            lines: vec!["bar".to_string()],
+        })
+        .unwrap();
+    assert_eq!(reply["solved"], true);
+    assert_eq!(
+        reply["message"],
+        "Delete word: grade A in 2 keystrokes (par: 2)"
+    );
+    // The session directory goes with the session
+    assert!(!session.exists());
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(state.best_grade("m1"), Some(Grade::A));
+    assert_eq!(state.best_time("m1"), Some(1500));
+}
+
+#[test]
+fn test_unsolved_run_is_not_recorded() {
+    let dir = std::env::temp_dir().join("rlv_rpc_unsolved");
+    let topics = topics();
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+    let reply = server
+        .handle(Request::Open {
+            challenge: "m1".to_string(),
+        })
+        .unwrap();
+    let script = PathBuf::from(reply["script"].as_str().unwrap());
+    fs::write(script.with_file_name("results"), "1\n900\nx\ncursor=1,1").unwrap();
+    let reply = server
+        .handle(Request::Finish {
+            lines: vec!["oo bar".to_string()],
+        })
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(reply["solved"], false);
+    assert_eq!(
+        reply["message"],
+        "Delete word: target not matched (1 keystrokes)"
+    );
+    assert!(state.best_grade("m1").is_none());
+    assert_eq!(state.stats.challenges_attempted, 0);
+}
+
+#[test]
+fn test_unloaded_challenge_is_read_back() {
+    let dir = std::env::temp_dir().join("rlv_rpc_unloaded");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("m1.toml");
+    fs::write(
+        &file,
+        "[start]\ncontent = \"foo bar\\n\"\n[target]\ncontent = \"bar\\n\"\n",
+    )
+    .unwrap();
+    let mut topics = topics();
+    topics[0].challenges[0].unload(&file, String::new());
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+    let reply = server
+        .handle(Request::Open {
+            challenge: "m1".to_string(),
+        })
+        .unwrap();
+    let script = PathBuf::from(reply["script"].as_str().unwrap());
+    assert_eq!(
+        fs::read_to_string(reply["start"].as_str().unwrap()).unwrap(),
+        "foo bar\n"
+    );
+    fs::write(script.with_file_name("results"), "2\n900\ndw\n").unwrap();
+    let reply = server
+        .handle(Request::Finish {
+            lines: vec!["bar".to_string()],
+        })
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(reply["solved"], true);
+}
+
+#[test]
+fn test_blind_challenge_shows_the_hint() {
+    let dir = std::env::temp_dir().join("rlv_rpc_blind");
+    let mut topics = topics();
+    topics[0].challenges[0].blind = true;
+    topics[0].challenges[0].hint = "Delete the first word".to_string();
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+    let reply = server
+        .handle(Request::Open {
+            challenge: "m1".to_string(),
+        })
+        .unwrap();
+    let instructions = fs::read_to_string(reply["instructions"].as_str().unwrap());
+    let script = fs::read_to_string(reply["script"].as_str().unwrap()).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(instructions.unwrap(), "Delete the first word");
+    assert!(script.contains("_VK_BLIND = true"));
+    assert_eq!(reply["blind"], true);
+}
+
+#[test]
+fn test_description_shows_above_the_target() {
+    let dir = std::env::temp_dir().join("rlv_rpc_description");
+    let mut topics = topics();
+    topics[0].challenges[0].description = Some("Normalize the dates\n".to_string());
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+    let reply = server
+        .handle(Request::Open {
+            challenge: "m1".to_string(),
+        })
+        .unwrap();
+    let instructions = fs::read_to_string(reply["instructions"].as_str().unwrap());
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(instructions.unwrap(), "Normalize the dates");
+    assert_eq!(reply["blind"], false);
+}
+
+#[test]
+fn test_locked_challenge_is_not_opened() {
+    let dir = std::env::temp_dir().join("rlv_rpc_locked");
+    let mut topics = topics();
+    let mut m2 = topics[0].challenges[0].clone();
+    m2.id = "m2".to_string();
+    m2.requires = vec!["m1".to_string()];
+    topics[0].challenges.push(m2);
+    let mut i1 = topics[0].challenges[0].clone();
+    i1.id = "i1".to_string();
+    topics.push(Topic {
+        id: 3,
+        name: "Text objects".to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: vec![i1],
+    });
+    let mut state = GameState::default();
+    let mut server = Server::new(&topics, &mut state, dir.clone(), false);
+    let open = |server: &mut Server, id: &str| {
+        server.handle(Request::Open {
+            challenge: id.to_string(),
+        })
+    };
+    assert_eq!(
+        open(&mut server, "m2"),
+        Err("challenge 'm2' is locked: complete m1 first".to_string())
+    );
+    assert_eq!(
+        open(&mut server, "i1"),
+        Err("challenge 'i1' is locked: the intermediate category is not unlocked yet".to_string())
+    );
+
+    let mut server = server.with_unlock(true, UnlockPolicy::All);
+    assert!(open(&mut server, "m2").is_ok());
+    assert!(open(&mut server, "i1").is_ok());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_serve_answers_each_line() {
+    let topics = topics();
+    let mut state = GameState::default();
+    let dir = std::env::temp_dir().join("rlv_rpc_serve");
+    let mut server = Server::new(&topics, &mut state, dir, false);
+    let input = "{\"id\":1,\"method\":\"list\"}\n\n\
+                 {\"id\":2,\"method\":\"finish\",\"params\":{\"lines\":[]}}\n\
+                 {\"id\":3,\"method\":\"dance\"}\n\
+                 not json\n";
+    let mut output = Vec::new();
+    serve(input.as_bytes(), &mut output, &mut server).unwrap();
+    let replies: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 4);
+    assert_eq!(replies[0]["id"], 1);
+    assert_eq!(replies[0]["result"][0]["id"], "m1");
+    assert_eq!(replies[1]["error"], "no challenge is open");
+    assert_eq!(replies[2]["id"], 3);
+    assert!(
+        replies[2]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid request")
+    );
+    assert!(replies[3]["id"].is_null());
+}
@@ -0,0 +1,46 @@
+use nvimkata::screen::{NARROW_WIDTH, NarrowLayout, split_panels};
+use ratatui::layout::Rect;
+
+#[test]
+fn test_wide_terminals_split_side_by_side() {
+    let area = Rect::new(0, 0, 120, 40);
+    let [list, detail] = split_panels(area, NarrowLayout::List);
+    assert_eq!((list.width, detail.width), (60, 60));
+    assert_eq!((list.height, detail.height), (40, 40));
+}
+
+#[test]
+fn test_narrow_terminals_stack_or_show_one_pane() {
+    let area = Rect::new(0, 0, NARROW_WIDTH - 1, 40);
+    let [list, detail] = split_panels(area, NarrowLayout::Stacked);
+    assert_eq!((list.width, detail.width), (area.width, area.width));
+    assert_eq!((list.height, detail.height), (20, 20));
+    assert_eq!(detail.y, 20);
+
+    let [list, detail] = split_panels(area, NarrowLayout::List);
+    assert_eq!(list, area);
+    assert!(detail.is_empty());
+
+    let [list, detail] = split_panels(area, NarrowLayout::Detail);
+    assert!(list.is_empty());
+    assert_eq!(detail, area);
+}
+
+#[test]
+fn test_layout_key_cycles() {
+    let mut layout = NarrowLayout::default();
+    let seen: Vec<NarrowLayout> = (0..3)
+        .map(|_| {
+            layout = layout.next();
+            layout
+        })
+        .collect();
+    assert_eq!(
+        seen,
+        [
+            NarrowLayout::List,
+            NarrowLayout::Detail,
+            NarrowLayout::Stacked
+        ]
+    );
+}
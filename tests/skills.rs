@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::curriculum::load_curriculum;
+use nvimkata::skills::{SkillRegistry, load_skills};
+use nvimkata::state::GameState;
+
+fn registry() -> SkillRegistry {
+    toml::from_str(
+        r#"
+[[skills]]
+id = "sort"
+name = "Sorting"
+group = "Ex"
+prefixes = [":sort"]
+
+[[skills]]
+id = "substitute"
+name = "Substitute"
+group = "Ex"
+tags = [":'a,'bs"]
+prefixes = [":s", ":%s"]
+"#,
+    )
+    .unwrap()
+}
+
+fn tagged(id: &str, tags: &[&str]) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1.0.0\"\ntitle = \"{id}\"\ntopic = \"ex\"\n\
+         difficulty = 1\npar_keystrokes = 10\nfocused_actions = {tags:?}\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_tag_resolves_to_first_matching_skill() {
+    let reg = registry();
+    assert_eq!(reg.skill_for_tag(":sort u").unwrap().id, "sort");
+    assert_eq!(reg.skill_for_tag(":s///g").unwrap().id, "substitute");
+    assert_eq!(reg.skill_for_tag(":'a,'bs").unwrap().id, "substitute");
+    assert!(reg.skill_for_tag("dd").is_none());
+}
+
+#[test]
+fn test_progress_scores_grades() {
+    let reg = registry();
+    let topic = Topic {
+        id: 6,
+        name: "Ex".to_string(),
+        description: String::new(),
+        intro: None,
+        challenges: vec![tagged("a", &[":sort"]), tagged("b", &[":sort n", ":%s"])],
+    };
+    let mut state = GameState::default();
+    state.record_result("a", Grade::A, 10, 10, "keys", "1.0.0");
+
+    let progress = reg.progress(&[topic], &state);
+    assert_eq!(progress.len(), 2);
+    assert_eq!(progress[0].skill.id, "sort");
+    assert_eq!(progress[0].tagged, 2);
+    assert_eq!(progress[0].completed, 1);
+    assert_eq!(progress[0].percent, 50);
+    assert_eq!(progress[1].percent, 0);
+}
+
+#[test]
+fn test_every_shipped_tag_has_a_skill() {
+    let challenges_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("challenges");
+    let reg = load_skills(&challenges_dir);
+    assert!(!reg.skills.is_empty(), "skills.toml missing or empty");
+    let mut missing = Vec::new();
+    for topic in load_curriculum(&challenges_dir) {
+        for challenge in &topic.challenges {
+            for tag in challenge.focused_actions.iter().flatten() {
+                if reg.skill_for_tag(tag).is_none() {
+                    missing.push(format!("{}: {tag}", challenge.id));
+                }
+            }
+        }
+    }
+    assert!(
+        missing.is_empty(),
+        "Tags without a skill:\n{}",
+        missing.join("\n")
+    );
+}
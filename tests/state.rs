@@ -1,25 +1,13 @@
-use nvimkata::challenge::{BufferContent, Challenge, Grade};
-use nvimkata::state::GameState;
+use nvimkata::challenge::{Challenge, Grade, Topic};
+use nvimkata::state::{self, AttemptRecord, GameState, HistoryRetention};
 
 fn test_challenge(id: &str, version: &str) -> Challenge {
-    Challenge {
-        id: id.to_string(),
-        version: version.to_string(),
-        title: format!("Test {id}"),
-        topic: "motions".to_string(),
-        difficulty: 1,
-        hint: "hint".to_string(),
-        detailed_hint: None,
-        par_keystrokes: 10,
-        perfect_moves: None,
-        focused_actions: None,
-        start: BufferContent {
-            content: "a".to_string(),
-        },
-        target: BufferContent {
-            content: "b".to_string(),
-        },
-    }
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"{version}\"\ntitle = \"Test {id}\"\ntopic = \"motions\"\n\
+         difficulty = 1\nhint = \"hint\"\npar_keystrokes = 10\n[start]\ncontent = \"a\"\n\
+         [target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
 }
 
 #[test]
@@ -92,6 +80,7 @@ fn test_mark_stale_matching_version_not_stale() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_get_then_check)]
 fn test_mark_stale_mismatched_version_marked() {
     let mut state = GameState::default();
     state.record_result("m001", Grade::B, 10, 20, "keys", "1.0.0");
@@ -128,6 +117,30 @@ fn test_mark_stale_unknown_challenge_not_touched() {
     assert_eq!(state.best_grade("deleted"), Some(Grade::C));
 }
 
+#[test]
+fn test_ambiguous_ids_are_not_recorded() {
+    let topic = |id, challenges: &[&str]| Topic {
+        id,
+        name: format!("t{id}"),
+        description: String::new(),
+        intro: None,
+        challenges: challenges
+            .iter()
+            .map(|c| test_challenge(c, "1.0.0"))
+            .collect(),
+    };
+    let mut state = GameState::default();
+    state.mark_ambiguous(&[topic(1, &["m001", "dup"]), topic(150, &["dup"])]);
+    assert_eq!(state.ambiguous.iter().collect::<Vec<_>>(), ["dup"]);
+
+    assert!(!state.record_attempt("dup", attempt(Grade::A, 5, false), "1.0.0"));
+    assert!(!state.record_freestyle_result("dup", 5, Some(10), "keys", "1.0.0", None));
+    assert!(state.record_attempt("m001", attempt(Grade::A, 5, false), "1.0.0"));
+    assert_eq!(state.best_grade("dup"), None);
+    assert!(!state.history.contains_key("dup"));
+    assert_eq!(state.stats.challenges_attempted, 1);
+}
+
 #[test]
 fn test_stale_cleared_on_new_result() {
     let mut state = GameState::default();
@@ -166,6 +179,18 @@ fn test_backward_compat_old_medal_key() {
     assert_eq!(state.history["m001"][0].grade, Grade::B);
 }
 
+#[test]
+fn test_times_in_seconds_load_as_millis() {
+    let json = r#"{"challenges":{"m001":{"grade":"A","keystrokes":10,"time_secs":20}},"stats":{"total_keystrokes":10,"challenges_attempted":1},"history":{"m001":[{"grade":"A","keystrokes":10,"time_secs":20,"keys":"jcw"}]}}"#;
+    let state: GameState = serde_json::from_str(json).unwrap();
+    assert_eq!(state.challenges["m001"].time_ms, Some(20_000));
+    assert_eq!(state.history["m001"][0].time_ms, Some(20_000));
+
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(json.contains(r#""time_ms":20000"#));
+    assert!(!json.contains("time_secs"));
+}
+
 #[test]
 fn test_new_format_serializes_as_grade() {
     let mut state = GameState::default();
@@ -175,3 +200,533 @@ fn test_new_format_serializes_as_grade() {
     assert!(json.contains(r#""grade":"B""#));
     assert!(!json.contains(r#""medal""#));
 }
+
+#[test]
+fn test_tag_stats_aggregates_best_grades() {
+    let mut a = test_challenge("m001", "1.0.0");
+    a.focused_actions = Some(vec!["f".to_string(), "cw".to_string()]);
+    let mut b = test_challenge("m002", "1.0.0");
+    b.focused_actions = Some(vec!["f".to_string()]);
+    let mut state = GameState::default();
+    state.record_result("m001", Grade::A, 10, 20, "keys", "1.0.0");
+    state.record_result("m002", Grade::C, 15, 20, "keys", "1.0.0");
+    state.mark_stale(&[test_challenge("m002", "1.0.1")]);
+
+    let tags = state.tag_stats([&a, &b]);
+    let f = tags["f"];
+    assert_eq!(f.total, 2);
+    assert_eq!(f.attempted, 2);
+    assert_eq!(f.stale, 1);
+    assert_eq!(f.average_rank(), Some(1.0));
+    assert_eq!(tags["cw"].average_rank(), Some(0.0));
+}
+
+#[test]
+fn test_tag_stats_unattempted_has_no_average() {
+    let mut a = test_challenge("m001", "1.0.0");
+    a.focused_actions = Some(vec!["dd".to_string()]);
+    let state = GameState::default();
+    let tags = state.tag_stats([&a]);
+    assert_eq!(tags["dd"].total, 1);
+    assert_eq!(tags["dd"].average_rank(), None);
+    assert!(tags["dd"].stale_ratio().abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_freestyle_reference_badge_is_sticky() {
+    let mut state = GameState::default();
+    state.record_freestyle_result("free_001", 30, Some(60), "keys", "1.0.0", Some(25));
+    assert!(!state.beat_reference("free_001"));
+    state.record_freestyle_result("free_001", 24, Some(50), "keys", "1.0.0", Some(25));
+    assert!(state.beat_reference("free_001"));
+    // A worse run keeps both the best and the badge
+    state.record_freestyle_result("free_001", 40, Some(70), "keys", "1.0.0", Some(25));
+    assert_eq!(state.best_keystrokes("free_001"), Some(24));
+    assert!(state.beat_reference("free_001"));
+}
+
+#[test]
+fn test_freestyle_without_reference_never_earns_badge() {
+    let mut state = GameState::default();
+    state.record_freestyle_result("free_001", 1, Some(5), "x", "1.0.0", None);
+    assert!(!state.beat_reference("free_001"));
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(!json.contains("beat_reference"));
+}
+
+fn attempt(grade: Grade, keystrokes: u32, technique_missing: bool) -> AttemptRecord {
+    AttemptRecord {
+        grade,
+        keystrokes,
+        time_ms: Some(10_000),
+        keys: String::new(),
+        technique_missing,
+        timestamp: None,
+    }
+}
+
+#[test]
+fn test_technique_missing_is_recorded_but_not_completed() {
+    let mut state = GameState::default();
+    state.record_attempt("macro_001", attempt(Grade::A, 5, true), "1.0.0");
+    assert_eq!(state.best_grade("macro_001"), Some(Grade::A));
+    assert!(state.technique_missing("macro_001"));
+    assert!(!state.is_completed("macro_001"));
+
+    // A run with the technique replaces the flagged one even at a worse grade
+    state.record_attempt("macro_001", attempt(Grade::C, 9, false), "1.0.0");
+    assert_eq!(state.best_grade("macro_001"), Some(Grade::C));
+    assert!(state.is_completed("macro_001"));
+
+    // ...and is never replaced by a flagged run again
+    state.record_attempt("macro_001", attempt(Grade::A, 5, true), "1.0.0");
+    assert_eq!(state.best_grade("macro_001"), Some(Grade::C));
+    assert!(state.is_completed("macro_001"));
+}
+
+#[test]
+fn test_delete_attempt_keeps_best_result() {
+    let mut state = GameState::default();
+    state.record_attempt("t1", attempt(Grade::A, 5, false), "1");
+    state.record_attempt("t1", attempt(Grade::B, 9, false), "1");
+
+    assert!(state.delete_attempt("t1", 2).is_none());
+    let removed = state.delete_attempt("t1", 0).unwrap();
+    assert_eq!(removed.keystrokes, 5);
+    assert_eq!(state.history["t1"].len(), 1);
+    assert_eq!(state.best_keystrokes("t1"), Some(5));
+
+    state.delete_attempt("t1", 0).unwrap();
+    assert!(!state.history.contains_key("t1"));
+    assert!(state.delete_attempt("t1", 0).is_none());
+}
+
+#[test]
+fn test_reset_keeps_notes_and_bookmarks() {
+    let mut state = GameState::default();
+    state.record_attempt("t1", attempt(Grade::A, 5, false), "1");
+    state.record_attempt("t2", attempt(Grade::B, 9, false), "1");
+    state.set_note_text("t1", "use ci(");
+    state.toggle_favorite("t1");
+
+    assert!(state.reset_challenge("t1"));
+    assert!(!state.reset_challenge("t1"));
+    assert!(state.best_grade("t1").is_none());
+    assert!(state.best_attempt("t1").is_none());
+    assert!(state.note("t1").is_some());
+    assert!(state.is_favorite("t1"));
+    assert!(state.is_completed("t2"));
+
+    state.log_activity(3, true, 60);
+    state.reset_all();
+    assert!(state.challenges.is_empty() && state.history.is_empty());
+    assert!(state.activity.is_empty());
+    assert_eq!(state.stats.challenges_attempted, 0);
+    assert!(state.note("t1").is_some());
+}
+
+#[test]
+fn test_merge_keeps_the_better_of_both_saves() {
+    let run = |grade, keystrokes, timestamp| AttemptRecord {
+        timestamp: Some(timestamp),
+        ..attempt(grade, keystrokes, false)
+    };
+    let mut desktop = GameState::default();
+    desktop.record_attempt("t1", run(Grade::B, 9, 100), "1");
+    desktop.record_attempt("t2", run(Grade::A, 4, 200), "1");
+    desktop.set_note_text("t1", "use ci(");
+    desktop.toggle_favorite("t1");
+    let mut laptop = desktop.clone();
+    laptop.record_attempt("t1", run(Grade::A, 5, 300), "1");
+    laptop.record_attempt("t3", run(Grade::C, 12, 400), "1");
+    laptop.set_rating("t1", Some(4));
+    laptop.toggle_favorite("t3");
+
+    let summary = desktop.merge(laptop);
+    assert_eq!(summary.improved, 2);
+    assert_eq!(summary.attempts, 2);
+    assert_eq!(desktop.best_keystrokes("t1"), Some(5));
+    assert_eq!(desktop.best_keystrokes("t2"), Some(4));
+    assert!(desktop.is_completed("t3"));
+    // The copied attempts are not counted twice
+    assert_eq!(desktop.history["t1"].len(), 2);
+    assert_eq!(desktop.history["t2"].len(), 1);
+    assert_eq!(desktop.stats.challenges_attempted, 6);
+    let note = desktop.note("t1").unwrap();
+    assert_eq!((note.text.as_str(), note.rating), ("use ci(", Some(4)));
+    assert_eq!(desktop.favorites, vec!["t1", "t3"]);
+}
+
+#[test]
+fn test_merge_prefers_current_results_over_stale() {
+    let mut ours = GameState::default();
+    ours.record_attempt("t1", attempt(Grade::A, 3, false), "1");
+    ours.challenges.get_mut("t1").unwrap().stale = true;
+    let mut theirs = GameState::default();
+    theirs.record_attempt("t1", attempt(Grade::C, 12, false), "2");
+
+    ours.merge(theirs);
+    assert_eq!(ours.best_keystrokes("t1"), Some(12));
+    assert!(!ours.challenges["t1"].stale);
+}
+
+#[test]
+fn test_save_json_is_stable() {
+    let run = AttemptRecord {
+        timestamp: Some(1000),
+        ..attempt(Grade::B, 9, false)
+    };
+    let mut a = GameState::default();
+    let mut b = GameState::default();
+    for id in ["t3", "t1", "t2"] {
+        a.record_attempt(id, run.clone(), "1");
+    }
+    for id in ["t2", "t3", "t1"] {
+        b.record_attempt(id, run.clone(), "1");
+    }
+    let json = serde_json::to_string_pretty(&a).unwrap();
+    assert_eq!(json, serde_json::to_string_pretty(&b).unwrap());
+    assert!(json.find("\"t1\"") < json.find("\"t2\""));
+}
+
+#[test]
+fn test_conflict_sides_split_both_versions() {
+    let text = "{\n  \"a\": 1,\n<<<<<<< HEAD\n  \"b\": 2\n||||||| base\n  \"b\": 0\n\
+                =======\n  \"b\": 3\n>>>>>>> laptop\n}\n";
+    let (ours, theirs) = state::conflict_sides(text).unwrap();
+    assert_eq!(ours, "{\n  \"a\": 1,\n  \"b\": 2\n}\n");
+    assert_eq!(theirs, "{\n  \"a\": 1,\n  \"b\": 3\n}\n");
+    assert!(state::conflict_sides("{\n  \"a\": 1\n}\n").is_none());
+}
+
+#[test]
+fn test_merge_sides_does_not_count_the_shared_past_twice() {
+    let mut ours = GameState::default();
+    ours.record_attempt("t1", attempt(Grade::B, 9, false), "1");
+    let mut theirs = ours.clone();
+    theirs.record_attempt("t1", attempt(Grade::A, 5, false), "1");
+
+    ours.merge_sides(theirs);
+    assert_eq!(ours.best_keystrokes("t1"), Some(5));
+    assert_eq!(ours.stats.challenges_attempted, 2);
+}
+
+fn dated(keystrokes: u32, timestamp: u64) -> AttemptRecord {
+    AttemptRecord {
+        timestamp: Some(timestamp),
+        ..attempt(Grade::B, keystrokes, false)
+    }
+}
+
+#[test]
+fn test_history_retention_policies() {
+    const DAY: u64 = 86_400;
+    let history: Vec<AttemptRecord> = (0..12u32)
+        .map(|i| dated(20 - i, u64::from(i) * DAY / 3))
+        .collect();
+    let kept = |retention: HistoryRetention| {
+        let mut history = history.clone();
+        retention.retain(&mut history);
+        history.iter().map(|a| a.keystrokes).collect::<Vec<_>>()
+    };
+    assert_eq!(kept(HistoryRetention::Best), (9..=18).collect::<Vec<_>>());
+    assert_eq!(kept(HistoryRetention::Last(3)), [9, 10, 11]);
+    assert_eq!(kept(HistoryRetention::All).len(), 12);
+    // Three attempts a day, the last one of each is the best
+    assert_eq!(kept(HistoryRetention::Daily), [9, 12, 15, 18]);
+
+    let mut undated = vec![attempt(Grade::B, 5, false), attempt(Grade::B, 6, false)];
+    HistoryRetention::Daily.retain(&mut undated);
+    assert_eq!(undated.len(), 2);
+}
+
+#[test]
+fn test_history_retention_parses() {
+    assert_eq!("best".parse(), Ok(HistoryRetention::Best));
+    assert_eq!("Last 50".parse(), Ok(HistoryRetention::Last(50)));
+    assert_eq!("all".parse(), Ok(HistoryRetention::All));
+    assert_eq!("daily".parse(), Ok(HistoryRetention::Daily));
+    assert!("last 0".parse::<HistoryRetention>().is_err());
+    assert!("latest".parse::<HistoryRetention>().is_err());
+}
+
+#[test]
+fn test_lifetime_stats() {
+    let mut state = GameState::default();
+    state.record_attempt("t1", attempt(Grade::A, 5, false), "1");
+    state.record_attempt("t2", attempt(Grade::A, 7, false), "1");
+    state.record_attempt("t3", attempt(Grade::C, 12, false), "1");
+    assert_eq!(state.stats.grade_count(Grade::A), 2);
+    assert_eq!(state.stats.grade_count(Grade::C), 1);
+    assert_eq!(state.stats.grade_count(Grade::F), 0);
+
+    for (day, secs) in [(100, 60), (101, 30), (103, 10), (104, 5), (105, 5)] {
+        state.log_activity(day, true, secs);
+    }
+    assert_eq!(state.stats.practice_secs, 110);
+    assert_eq!(state.stats.longest_streak, 3);
+    assert_eq!(state.stats.first_played, Some(100));
+    assert_eq!(state.stats.last_played, Some(105));
+
+    // Saves from before the lifetime stats load with them empty
+    let json = r#"{"challenges":{},"stats":{"total_keystrokes":0,"challenges_attempted":0}}"#;
+    let old: GameState = serde_json::from_str(json).unwrap();
+    assert_eq!(old.stats.practice_secs, 0);
+    assert!(old.stats.first_played.is_none());
+}
+
+#[test]
+fn test_category_completions_are_stamped_once() {
+    let topic = |id, challenges: &[&str]| Topic {
+        id,
+        name: format!("Topic {id}"),
+        description: String::new(),
+        intro: None,
+        challenges: challenges.iter().map(|c| test_challenge(c, "1")).collect(),
+    };
+    let topics = [topic(1, &["b1"]), topic(2, &["b2"]), topic(3, &["i1"])];
+    let mut state = GameState::default();
+    state.record_attempt("b1", attempt(Grade::A, 5, false), "1");
+    assert!(!state.record_category_completions(&topics, 1000));
+
+    state.record_attempt("b2", attempt(Grade::C, 9, false), "1");
+    assert!(state.record_category_completions(&topics, 2000));
+    assert!(!state.record_category_completions(&topics, 3000));
+    assert_eq!(state.stats.category_completed["BEGINNER"], 2000);
+    assert!(!state.stats.category_completed.contains_key("INTERMEDIATE"));
+}
+
+#[test]
+fn test_notes_and_ratings() {
+    let mut state = GameState::default();
+    assert!(state.note("t1").is_none());
+
+    state.set_note_text("t1", "  remember: use gv ");
+    state.set_rating("t1", Some(9));
+    let note = state.note("t1").unwrap();
+    assert_eq!(note.text, "remember: use gv");
+    assert_eq!(note.rating, Some(5));
+
+    let json = serde_json::to_string(&state).unwrap();
+    let loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.note("t1"), state.note("t1"));
+
+    state.set_note_text("t1", "");
+    assert_eq!(state.note("t1").unwrap().text, "");
+    state.set_rating("t1", None);
+    assert!(state.note("t1").is_none());
+}
+
+#[test]
+fn test_toggle_favorite() {
+    let mut state = GameState::default();
+    state.toggle_favorite("t2");
+    state.toggle_favorite("t1");
+    assert!(state.is_favorite("t1"));
+    assert_eq!(state.favorites, vec!["t2", "t1"]);
+
+    state.toggle_favorite("t2");
+    assert!(!state.is_favorite("t2"));
+    assert_eq!(state.favorites, vec!["t1"]);
+}
+
+#[test]
+fn test_best_attempt_has_fewest_keystrokes() {
+    let mut state = GameState::default();
+    assert!(state.best_attempt("t1").is_none());
+    state.record_result("t1", Grade::C, 20, 10, "slow", "1.0.0");
+    state.record_result("t1", Grade::B, 12, 10, "fast", "1.0.0");
+    state.record_result("t1", Grade::D, 25, 10, "slower", "1.0.0");
+    assert_eq!(state.best_attempt("t1").unwrap().keys, "fast");
+}
+
+#[test]
+fn test_best_time_is_tracked_separately() {
+    let mut state = GameState::default();
+    assert_eq!(state.best_time("t1"), None);
+    state.record_result("t1", Grade::B, 12, 30, "first", "1.0.0");
+    state.record_result("t1", Grade::C, 20, 9, "quick", "1.0.0");
+    state.record_result("t1", Grade::A, 8, 40, "best", "1.0.0");
+    assert_eq!(state.best_keystrokes("t1"), Some(8));
+    assert_eq!(state.best_time("t1"), Some(9));
+
+    // A changed challenge starts over
+    state.challenges.get_mut("t1").unwrap().stale = true;
+    assert_eq!(state.best_time("t1"), None);
+    state.record_result("t1", Grade::B, 12, 25, "again", "2.0.0");
+    assert_eq!(state.best_time("t1"), Some(25));
+}
+
+#[test]
+fn test_untimed_runs_leave_the_best_time_alone() {
+    let untimed = |keystrokes| AttemptRecord {
+        time_ms: None,
+        ..attempt(Grade::A, keystrokes, false)
+    };
+    let mut state = GameState::default();
+    state.record_attempt("t1", untimed(8), "1.0.0");
+    assert_eq!(state.best_time("t1"), None);
+    state.record_result("t1", Grade::B, 12, 30, "timed", "1.0.0");
+    state.record_attempt("t1", untimed(6), "1.0.0");
+    assert_eq!(state.best_keystrokes("t1"), Some(6));
+    assert_eq!(state.best_time("t1"), Some(30));
+
+    // Untimed runs are saved without a time
+    let json = serde_json::to_string(&state).unwrap();
+    let loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.challenges["t1"].time_ms, None);
+    assert_eq!(loaded.history["t1"][0].time_ms, None);
+    assert_eq!(loaded.best_time("t1"), Some(30));
+}
+
+#[test]
+fn test_attempts_are_timestamped() {
+    let mut state = GameState::default();
+    let mut stamped = attempt(Grade::B, 12, false);
+    stamped.timestamp = Some(1_000);
+    state.record_attempt("t1", stamped, "1.0.0");
+    state.record_attempt("t1", attempt(Grade::C, 20, false), "1.0.0");
+    let history = &state.history["t1"];
+    assert_eq!(history[0].timestamp, Some(1_000));
+    assert!(history[1].timestamp.is_some_and(|t| t > 1_000));
+    assert_eq!(state.challenges["t1"].last_attempt, history[1].timestamp);
+
+    // Older saves have no timestamps
+    let json = r#"{"grade":"A","keystrokes":10,"time_ms":500,"keys":"jcw"}"#;
+    let old: AttemptRecord = serde_json::from_str(json).unwrap();
+    assert_eq!(old.timestamp, None);
+}
+
+#[test]
+fn test_archive_stale_accepts_outdated_scores() {
+    let mut state = GameState::default();
+    state.record_result("t1", Grade::A, 8, 40, "old", "1.0.0");
+    state.record_result("t2", Grade::B, 12, 30, "current", "1.0.0");
+    state.mark_stale(&[test_challenge("t1", "2.0.0"), test_challenge("t2", "1.0.0")]);
+    assert_eq!(state.stale_count(), 1);
+
+    assert_eq!(state.archive_stale(), 1);
+    assert_eq!(state.stale_count(), 0);
+    assert_eq!(state.best_grade("t1"), None);
+    assert_eq!(state.archived["t1"].grade, Grade::A);
+    assert_eq!(state.best_grade("t2"), Some(Grade::B));
+    assert_eq!(state.history["t1"].len(), 1);
+
+    // Archived results survive a save and go with a reset
+    let json = serde_json::to_string(&state).unwrap();
+    let mut loaded: GameState = serde_json::from_str(&json).unwrap();
+    assert!(loaded.archived.contains_key("t1"));
+    assert!(loaded.reset_challenge("t1"));
+    assert!(loaded.archived.is_empty());
+}
+
+#[test]
+fn test_version_content_is_kept_for_stale_results() {
+    let mut state = GameState::default();
+    state.record_result("t1", Grade::A, 8, 40, "keys", "1.0.0");
+    state.mark_stale(&[test_challenge("t1", "1.0.0")]);
+    assert!(!state.is_stale("t1"));
+
+    let mut changed = test_challenge("t1", "2.0.0");
+    changed.target.as_mut().unwrap().content = "c".to_string();
+    state.mark_stale(&[changed.clone()]);
+    assert!(state.is_stale("t1"));
+    let old = state.best_version_content("t1").unwrap();
+    assert_eq!(old.target, "b");
+    assert_eq!(old.start_hash, state::content_hash("a"));
+    assert_ne!(
+        old.target_hash,
+        state::content_hash(&changed.target().content)
+    );
+
+    // Solving the new version drops the old content once nothing refers to it
+    state.record_result("t1", Grade::B, 12, 30, "keys", "2.0.0");
+    state.mark_stale(&[changed]);
+    assert_eq!(state.best_version_content("t1").unwrap().target, "c");
+    assert_eq!(state.versions["t1"].len(), 1);
+}
+
+#[test]
+fn test_edits_without_a_version_bump_make_results_stale() {
+    let mut state = GameState::default();
+    let challenge = test_challenge("t1", "1.0.0");
+    state.record_result("t1", Grade::A, 8, 40, "keys", "1.0.0");
+    state.record_content(&challenge);
+    assert_eq!(
+        state.challenges["t1"].content_hash,
+        Some(state::challenge_hash(&challenge))
+    );
+
+    let mut edited = challenge.clone();
+    edited.par_keystrokes = 6;
+    state.mark_stale(&[edited.clone()]);
+    assert!(state.is_stale("t1"));
+    assert_eq!(state.best_version_content("t1").unwrap().target, "b");
+
+    // Older saves without a hash take the current one
+    let json = r#"{"challenges":{"t1":{"grade":"A","keystrokes":8,"time_ms":40,"version":"1.0.0"}},"stats":{"total_keystrokes":8,"challenges_attempted":1}}"#;
+    let mut old: GameState = serde_json::from_str(json).unwrap();
+    old.mark_stale(&[edited.clone()]);
+    assert!(!old.is_stale("t1"));
+    assert_eq!(
+        old.challenges["t1"].content_hash,
+        Some(state::challenge_hash(&edited))
+    );
+}
+
+#[test]
+fn test_distinct_solutions_are_kept_with_their_uses() {
+    let mut state = GameState::default();
+    for _ in 0..12 {
+        state.record_result("t1", Grade::B, 12, 30, "jjjjdw", "1.0.0");
+    }
+    state.record_result("t1", Grade::A, 3, 20, "4jdw", "1.0.0");
+    state.record_freestyle_result("f1", 5, Some(20), "", "1.0.0", None);
+
+    let solutions = &state.solutions["t1"];
+    assert_eq!(solutions.len(), 2);
+    assert_eq!((solutions[0].keys.as_str(), solutions[0].uses), ("4jdw", 1));
+    assert_eq!(
+        (solutions[1].keys.as_str(), solutions[1].uses),
+        ("jjjjdw", 12)
+    );
+    // Runs without a key log are no solution
+    assert!(!state.solutions.contains_key("f1"));
+
+    let mut other = GameState::default();
+    other.record_result("t1", Grade::A, 3, 25, "4jdw", "1.0.0");
+    other.record_result("t1", Grade::A, 2, 25, "dj", "1.0.0");
+    state.merge(other);
+    let uses: Vec<(&str, u32)> = state.solutions["t1"]
+        .iter()
+        .map(|s| (s.keys.as_str(), s.uses))
+        .collect();
+    assert_eq!(uses, [("dj", 1), ("4jdw", 2), ("jjjjdw", 12)]);
+
+    assert!(state.reset_challenge("t1"));
+    assert!(!state.solutions.contains_key("t1"));
+}
+
+#[test]
+fn test_grades_decay_until_solved_again() {
+    let day = 86_400;
+    let mut state = GameState::default();
+    let mut stamped = attempt(Grade::A, 8, false);
+    stamped.timestamp = Some(10 * day);
+    state.record_attempt("t1", stamped, "1.0.0");
+    let best = &state.challenges["t1"];
+    assert!(!best.is_decayed(30, 40 * day));
+    assert!(best.is_decayed(30, 41 * day));
+
+    // Solving it again refreshes the grade, even without beating it
+    let mut again = attempt(Grade::C, 20, false);
+    again.timestamp = Some(40 * day);
+    state.record_attempt("t1", again, "1.0.0");
+    let best = &state.challenges["t1"];
+    assert_eq!(best.grade, Grade::A);
+    assert!(!best.is_decayed(30, 41 * day));
+
+    // Saves without a timestamp never decay
+    state.challenges.get_mut("t1").unwrap().last_attempt = None;
+    assert!(!state.challenges["t1"].is_decayed(30, u64::MAX));
+}
@@ -0,0 +1,33 @@
+use nvimkata::nvim::Snapshot;
+use nvimkata::stepper;
+
+fn snapshot(lines: &[&str]) -> Snapshot {
+    Snapshot {
+        lines: lines.iter().map(ToString::to_string).collect(),
+        cursor: [1, 1],
+    }
+}
+
+#[test]
+fn test_steps_are_the_commands_of_the_key_log() {
+    assert_eq!(stepper::steps("dwjciwfoo<Esc>"), ["dw", "j", "ciwfoo<Esc>"]);
+    assert!(stepper::steps("").is_empty());
+}
+
+#[test]
+fn test_lines_to_go_shrink_to_zero_at_the_target() {
+    let target = "one\ntwo\nthree\n";
+    assert_eq!(
+        stepper::lines_to_go(&snapshot(&["one", "2", "3"]), target),
+        4
+    );
+    assert_eq!(
+        stepper::lines_to_go(&snapshot(&["one", "two", "3"]), target),
+        2
+    );
+    assert_eq!(stepper::lines_to_go(&snapshot(&["one", "two"]), target), 1);
+    assert_eq!(
+        stepper::lines_to_go(&snapshot(&["one", "two", "three"]), target),
+        0
+    );
+}
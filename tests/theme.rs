@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use nvimkata::heatmap::{KeyboardLayout, heatmap_lines};
+use nvimkata::theme::{self, Theme};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+
+// The theme is set once per process, so every test here runs with `Accessible`.
+fn accessible() {
+    theme::set(Theme::Accessible);
+    assert_eq!(theme::active(), Theme::Accessible);
+}
+
+#[test]
+fn test_accessible_remaps_red_and_green() {
+    accessible();
+    let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+    buf[(0, 0)].set_fg(Color::Red).set_bg(Color::Green);
+    buf[(1, 0)].set_fg(Color::Gray);
+    theme::apply(&mut buf);
+    assert_eq!(buf[(0, 0)].fg, Color::Rgb(213, 94, 0));
+    assert_eq!(buf[(0, 0)].bg, Color::Rgb(0, 158, 115));
+    assert_eq!(buf[(1, 0)].fg, Color::Gray);
+}
+
+#[test]
+fn test_accessible_adds_markers() {
+    accessible();
+    assert!(theme::markers());
+    let style = theme::mark(Style::new().fg(Color::Red), Modifier::CROSSED_OUT);
+    assert!(style.add_modifier.contains(Modifier::CROSSED_OUT));
+
+    let usage: BTreeMap<String, u32> = [("w", 5), ("j", 1)]
+        .into_iter()
+        .map(|(k, n)| (k.to_string(), n))
+        .collect();
+    let labels: Vec<String> = heatmap_lines(KeyboardLayout::Qwerty, &usage)
+        .iter()
+        .flat_map(|line| &line.spans)
+        .map(|span| span.content.trim().to_string())
+        .collect();
+    assert!(labels.contains(&"w5".to_string()));
+    assert!(labels.contains(&"j1".to_string()));
+    assert!(labels.contains(&"x".to_string()));
+}
+
+#[test]
+fn test_no_color_flag_selects_mono() {
+    accessible();
+    assert_eq!(theme::resolve(Theme::Light, true), Theme::Mono);
+}
@@ -0,0 +1,88 @@
+use nvimkata::challenge::{Category, Challenge, Grade, Topic};
+use nvimkata::state::GameState;
+use nvimkata::unlock::{
+    UnlockPolicy, is_category_unlocked, is_challenge_unlocked, missing_requirements,
+};
+
+fn challenge(id: &str, requires: &[&str]) -> Challenge {
+    toml::from_str(&format!(
+        "id = \"{id}\"\nversion = \"1\"\ntitle = \"{id}\"\ntopic = \"motions\"\n\
+         difficulty = 1\npar_keystrokes = 5\nrequires = {requires:?}\n\
+         [start]\ncontent = \"a\"\n[target]\ncontent = \"b\"\n"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_challenge_without_requirements_is_unlocked() {
+    let state = GameState::default();
+    assert!(is_challenge_unlocked(&challenge("c1", &[]), &state, false));
+}
+
+#[test]
+fn test_requirements_gate_until_completed() {
+    let mut state = GameState::default();
+    let gated = challenge("c3", &["c1", "c2"]);
+    assert!(!is_challenge_unlocked(&gated, &state, false));
+    assert!(is_challenge_unlocked(&gated, &state, true));
+    assert_eq!(missing_requirements(&gated, &state), ["c1", "c2"]);
+
+    state.record_result("c1", Grade::F, 20, 10, "", "1");
+    assert_eq!(missing_requirements(&gated, &state), ["c2"]);
+    state.record_result("c2", Grade::C, 8, 10, "", "1");
+    assert!(is_challenge_unlocked(&gated, &state, false));
+}
+
+#[test]
+fn test_parse_unlock_policy() {
+    assert_eq!("all".parse(), Ok(UnlockPolicy::All));
+    assert_eq!("off".parse(), Ok(UnlockPolicy::Off));
+    assert_eq!("80%".parse(), Ok(UnlockPolicy::Percent(80)));
+    assert_eq!("c".parse(), Ok(UnlockPolicy::MinGrade(Grade::C)));
+    assert!("120%".parse::<UnlockPolicy>().is_err());
+    assert!("G".parse::<UnlockPolicy>().is_err());
+}
+
+fn topic(id: u8, challenges: Vec<Challenge>) -> Topic {
+    Topic {
+        id,
+        name: String::new(),
+        description: String::new(),
+        intro: None,
+        challenges,
+    }
+}
+
+#[test]
+fn test_category_unlock_policies() {
+    let topics = vec![
+        topic(
+            1,
+            (1..=4).map(|i| challenge(&format!("b{i}"), &[])).collect(),
+        ),
+        topic(3, vec![challenge("i1", &[])]),
+    ];
+    let mut state = GameState::default();
+    state.record_result("b1", Grade::A, 5, 10, "", "1");
+    state.record_result("b2", Grade::B, 7, 10, "", "1");
+    state.record_result("b3", Grade::C, 9, 10, "", "1");
+    let unlocked = |state: &GameState, policy| {
+        is_category_unlocked(Category::Intermediate, &topics, state, policy)
+    };
+
+    assert!(is_category_unlocked(
+        Category::Beginner,
+        &topics,
+        &state,
+        UnlockPolicy::All
+    ));
+    assert!(!unlocked(&state, UnlockPolicy::All));
+    assert!(unlocked(&state, UnlockPolicy::Percent(75)));
+    assert!(!unlocked(&state, UnlockPolicy::Percent(80)));
+    assert!(unlocked(&state, UnlockPolicy::Off));
+
+    state.record_result("b4", Grade::D, 11, 10, "", "1");
+    assert!(unlocked(&state, UnlockPolicy::All));
+    assert!(unlocked(&state, UnlockPolicy::MinGrade(Grade::D)));
+    assert!(!unlocked(&state, UnlockPolicy::MinGrade(Grade::C)));
+}
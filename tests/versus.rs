@@ -0,0 +1,60 @@
+use nvimkata::versus::{self, Ledger, Turn};
+
+fn turn(player: &str, solved: bool, keystrokes: u32, time_ms: u32) -> Turn {
+    Turn {
+        player: player.to_string(),
+        solved,
+        keystrokes,
+        time_ms,
+    }
+}
+
+#[test]
+fn test_fewest_keystrokes_then_faster_time_wins() {
+    let turns = [turn("ada", true, 9, 4000), turn("bob", true, 7, 9000)];
+    assert_eq!(versus::winner(&turns), Some(1));
+    let turns = [turn("ada", true, 7, 4000), turn("bob", true, 7, 9000)];
+    assert_eq!(versus::winner(&turns), Some(0));
+    // Unsolved runs never win, however short
+    let turns = [turn("ada", false, 2, 1000), turn("bob", true, 30, 9000)];
+    assert_eq!(versus::winner(&turns), Some(1));
+    let turns = [turn("ada", true, 7, 4000), turn("bob", true, 7, 4000)];
+    assert_eq!(versus::winner(&turns), None);
+    let turns = [turn("ada", false, 7, 4000), turn("bob", false, 7, 4000)];
+    assert_eq!(versus::winner(&turns), None);
+}
+
+#[test]
+fn test_ledger_keeps_a_running_score_per_group() {
+    let tmp = std::env::temp_dir().join("rlv_test_versus_ledger");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let path = tmp.join(versus::LEDGER_FILE);
+    let mut ledger = Ledger::load(&path).unwrap();
+    assert_eq!(ledger, Ledger::default());
+
+    ledger.record(&[turn("bob", true, 7, 4000), turn("ada", true, 9, 4000)]);
+    ledger.record(&[turn("ada", true, 5, 4000), turn("bob", true, 7, 4000)]);
+    ledger.record(&[turn("ada", false, 5, 4000), turn("bob", false, 7, 4000)]);
+    ledger.record(&[turn("ada", true, 5, 4000), turn("cy", true, 7, 4000)]);
+    ledger.save(&path).unwrap();
+
+    let ledger = Ledger::load(&path).unwrap();
+    let score = &ledger.matches["ada vs bob"];
+    assert_eq!(score.wins["ada"], 1);
+    assert_eq!(score.wins["bob"], 1);
+    assert_eq!(score.draws, 1);
+    assert_eq!(score.played(), 3);
+    assert_eq!(ledger.matches["ada vs cy"].wins["cy"], 0);
+
+    let lines = versus::summary(
+        &[turn("ada", true, 5, 4000), turn("bob", false, 7, 4000)],
+        score,
+    );
+    assert!(lines[0].starts_with("ada     5 keystrokes"));
+    assert!(lines[0].ends_with("wins"));
+    assert_eq!(lines[1], "bob  not solved");
+    assert_eq!(
+        lines[2],
+        "Match score after 3 played: ada 1, bob 1, draws 1"
+    );
+}